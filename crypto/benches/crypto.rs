@@ -0,0 +1,180 @@
+//! Benchmarks of the key derivation and encryption paths.
+//!
+//! Run with `cargo bench -p telepass_crypto` on the target machine before changing
+//! [`DEFAULT_KDF_ITERATIONS`] or the default [`Algorithm`]:
+//!
+//! - `derive_key` shows the cost of a single key derivation for the number of iterations;
+//! - `single` shows the cost of [`encrypt()`] and [`decrypt()`] including the key derivation;
+//! - `cipher` shows the cost of the algorithm alone with an already derived key;
+//! - `batch` compares [`encrypt_many()`] and [`decrypt_many()`] with calling [`encrypt()`] and
+//!   [`decrypt()`] for every item, so the key derivation amortization is visible.
+
+#![expect(
+    clippy::unwrap_used,
+    reason = "benchmarks have nothing to measure after a failure"
+)]
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use telepass_crypto::{
+    decrypt, decrypt_many, decrypt_with_key, encrypt, encrypt_many, encrypt_with_key, Algorithm,
+    DerivedKey, EncryptParams, DEFAULT_KDF_ITERATIONS,
+};
+
+/// Master password to encrypt with.
+const PASSWORD: &str = "bench master password";
+
+/// Numbers of key derivation iterations: a tenth of the default, the default and the one
+/// recommended by OWASP for PBKDF2-HMAC-SHA256.
+const KDF_ITERATIONS: [u32; 3] = [10_000, DEFAULT_KDF_ITERATIONS, 600_000];
+
+/// Payload sizes in bytes: a password, a note and an attachment-like blob.
+const PAYLOAD_SIZES: [usize; 3] = [16, 1024, 64 * 1024];
+
+/// Number of items in batches.
+const BATCH_SIZE: usize = 8;
+
+/// Both supported algorithms.
+const ALGORITHMS: [Algorithm; 2] = [Algorithm::Aes256Gcm, Algorithm::XChaCha20Poly1305];
+
+/// Encryption parameters with default number of iterations and `algorithm`.
+fn params(algorithm: Algorithm) -> EncryptParams {
+    EncryptParams {
+        algorithm,
+        ..EncryptParams::default()
+    }
+}
+
+/// Construct text payload of `size` bytes.
+fn payload(size: usize) -> String {
+    "x".repeat(size)
+}
+
+/// Id of a benchmark of `function` with `algorithm` and payload of `size` bytes.
+fn id(function: &str, algorithm: Algorithm, size: usize) -> BenchmarkId {
+    BenchmarkId::new(format!("{function}/{}", algorithm.name()), size)
+}
+
+/// Throughput of processing payload of `size` bytes.
+fn bytes(size: usize) -> Throughput {
+    Throughput::Bytes(u64::try_from(size).unwrap())
+}
+
+/// Benchmark key derivation with different numbers of iterations.
+fn derive_key(c: &mut Criterion) {
+    let mut group = c.benchmark_group("derive_key");
+    group.sample_size(10);
+
+    for kdf_iterations in KDF_ITERATIONS {
+        let derive_params = EncryptParams {
+            kdf_iterations,
+            ..EncryptParams::default()
+        };
+        group.bench_function(BenchmarkId::from_parameter(kdf_iterations), |b| {
+            b.iter(|| DerivedKey::derive(PASSWORD, Some(derive_params)).unwrap());
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmark [`encrypt()`] and [`decrypt()`] including the key derivation.
+fn single(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single");
+    group.sample_size(10);
+
+    for algorithm in ALGORITHMS {
+        for size in PAYLOAD_SIZES {
+            let payload = payload(size);
+            let output = encrypt(&payload, PASSWORD, Some(params(algorithm))).unwrap();
+            group.throughput(bytes(size));
+
+            group.bench_function(id("encrypt", algorithm, size), |b| {
+                b.iter(|| encrypt(&payload, PASSWORD, Some(params(algorithm))).unwrap());
+            });
+            group.bench_function(id("decrypt", algorithm, size), |b| {
+                b.iter_batched(
+                    || output.clone(),
+                    |encrypted| decrypt(encrypted, PASSWORD).unwrap(),
+                    BatchSize::SmallInput,
+                );
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Benchmark [`encrypt_with_key()`] and [`decrypt_with_key()`] without the key derivation.
+fn cipher(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cipher");
+
+    for algorithm in ALGORITHMS {
+        let key = DerivedKey::derive(PASSWORD, Some(params(algorithm))).unwrap();
+        for size in PAYLOAD_SIZES {
+            let payload = payload(size);
+            let output = encrypt_with_key(&key, &payload).unwrap();
+            group.throughput(bytes(size));
+
+            group.bench_function(id("encrypt", algorithm, size), |b| {
+                b.iter(|| encrypt_with_key(&key, &payload).unwrap());
+            });
+            group.bench_function(id("decrypt", algorithm, size), |b| {
+                b.iter_batched(
+                    || output.clone(),
+                    |encrypted| decrypt_with_key(&key, encrypted).unwrap(),
+                    BatchSize::SmallInput,
+                );
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Benchmark batch functions against calling single ones for every item.
+fn batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch");
+    group.sample_size(10);
+
+    for size in PAYLOAD_SIZES {
+        let payloads = vec![payload(size); BATCH_SIZE];
+        let outputs = encrypt_many(payloads.iter().map(String::as_str), PASSWORD, None).unwrap();
+        group.throughput(bytes(size.saturating_mul(BATCH_SIZE)));
+
+        group.bench_function(BenchmarkId::new("encrypt_many", size), |b| {
+            b.iter(|| encrypt_many(payloads.iter().map(String::as_str), PASSWORD, None).unwrap());
+        });
+        group.bench_function(BenchmarkId::new("encrypt_each", size), |b| {
+            b.iter(|| {
+                payloads
+                    .iter()
+                    .map(|item| encrypt(item, PASSWORD, None).unwrap())
+                    .collect::<Vec<_>>()
+            });
+        });
+        group.bench_function(BenchmarkId::new("decrypt_many", size), |b| {
+            b.iter_batched(
+                || outputs.clone(),
+                |encrypted| decrypt_many(encrypted, PASSWORD).unwrap(),
+                BatchSize::SmallInput,
+            );
+        });
+        group.bench_function(BenchmarkId::new("decrypt_each", size), |b| {
+            b.iter_batched(
+                || outputs.clone(),
+                |encrypted| {
+                    encrypted
+                        .into_iter()
+                        .map(|item| decrypt(item, PASSWORD).unwrap())
+                        .collect::<Vec<_>>()
+                },
+                BatchSize::SmallInput,
+            );
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, derive_key, single, cipher, batch);
+criterion_main!(benches);
@@ -0,0 +1,27 @@
+//! Prints the number of key derivation iterations taking the given time on this host.
+//!
+//! Run with `cargo run --release -p telepass_crypto --example calibrate -- <milliseconds>`
+//! on the target machine to tune the encryption cost of the deployment.
+//! See [`calibrate_iterations()`] for the bounds of the result.
+
+use std::{str::FromStr as _, time::Duration};
+
+use telepass_crypto::calibration::calibrate_iterations;
+
+fn main() -> Result<(), String> {
+    let target = std::env::args()
+        .nth(1)
+        .ok_or_else(|| "Expected time budget, usage: calibrate <milliseconds>".to_owned())
+        .and_then(|millis| {
+            u64::from_str(&millis)
+                .map_err(|error| format!("Failed to parse `{millis}` as milliseconds: {error}"))
+        })
+        .map(Duration::from_millis)?;
+
+    let iterations = calibrate_iterations(target);
+    #[expect(clippy::print_stdout, reason = "result is the output of the example")]
+    {
+        println!("{iterations}");
+    }
+    Ok(())
+}
@@ -0,0 +1,222 @@
+//! Module with encryption and decryption of many payloads deriving the key only once.
+
+use aes_gcm::aead::{rand_core::RngCore as _, OsRng};
+use zeroize::Zeroizing;
+
+use crate::{
+    decode_text, derive_key, open_with_key, pad_text, seal_with_key, validate_ciphertext,
+    EncryptParams, EncryptionOutput, KdfSalt, Key, Result, DEFAULT_MAX_CIPHERTEXT_SIZE,
+    LATEST_OUTPUT_VERSION,
+};
+
+/// Encrypt every payload of `items` with password deriving the key only once.
+///
+/// Unlike calling [`encrypt()`](crate::encrypt) for every item, the expensive key derivation
+/// doesn't repeat, so it's suitable for hundreds of records, e.g. for export. All outputs share the
+/// key derivation salt, but every one has its own nonce. Outputs are in the order of `items`.
+///
+/// Uses [`EncryptParams::default()`] if `params` are not provided.
+///
+/// # Errors
+///
+/// See [`encrypt()`](crate::encrypt).
+pub fn encrypt_many<'item>(
+    items: impl IntoIterator<Item = &'item str>,
+    password: &str,
+    params: Option<EncryptParams>,
+) -> Result<Vec<EncryptionOutput>> {
+    let EncryptParams {
+        kdf_iterations,
+        algorithm,
+        pad_to,
+    } = params.unwrap_or_default();
+    let mut kdf_salt = KdfSalt::default();
+    OsRng.fill_bytes(&mut kdf_salt);
+
+    let key = Zeroizing::new(derive_key(
+        password,
+        LATEST_OUTPUT_VERSION,
+        Some(&kdf_salt),
+        kdf_iterations,
+    )?);
+    items
+        .into_iter()
+        .map(|payload| {
+            seal_with_key(
+                &key,
+                &pad_text(payload, pad_to),
+                kdf_salt,
+                kdf_iterations,
+                algorithm,
+                &[],
+                &mut OsRng,
+            )
+        })
+        .collect()
+}
+
+/// Decrypt every output of `outputs` with password, e.g. of [`encrypt_many()`].
+///
+/// The key is derived again only when the version, the key derivation salt or the number of
+/// iterations differs from the previous output, so outputs of one [`encrypt_many()`] call cost a
+/// single key derivation. Payloads are in the order of `outputs`.
+///
+/// # Errors
+///
+/// See [`decrypt()`](crate::decrypt), the first failed output fails the whole batch.
+pub fn decrypt_many(
+    outputs: impl IntoIterator<Item = EncryptionOutput>,
+    password: &str,
+) -> Result<Vec<String>> {
+    let mut derived: Option<(u8, Option<KdfSalt>, u32, Zeroizing<Key>)> = None;
+
+    outputs
+        .into_iter()
+        .map(|output| {
+            validate_ciphertext(&output.encrypted_payload, DEFAULT_MAX_CIPHERTEXT_SIZE)?;
+            let cached = derived
+                .as_ref()
+                .filter(|&&(version, kdf_salt, kdf_iterations, _)| {
+                    version == output.version
+                        && kdf_salt == output.kdf_salt
+                        && kdf_iterations == output.kdf_iterations
+                })
+                .map(|cached| &cached.3);
+            let key = match cached {
+                Some(key) => key,
+                None => {
+                    let key = Zeroizing::new(derive_key(
+                        password,
+                        output.version,
+                        output.kdf_salt.as_ref(),
+                        output.kdf_iterations,
+                    )?);
+                    &derived
+                        .insert((output.version, output.kdf_salt, output.kdf_iterations, key))
+                        .3
+                }
+            };
+
+            let payload = open_with_key(
+                key,
+                output.key_commitment.as_ref(),
+                output.salt,
+                &output.encrypted_payload,
+                &[],
+            )?;
+            decode_text(payload)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::expect_used, reason = "it's ok in tests")]
+
+    use super::*;
+    use crate::{decrypt, encrypt, tests::encrypt_legacy, Algorithm, Error};
+
+    #[test]
+    fn encrypt_many_shares_kdf_salt_but_not_nonces() {
+        let payloads = ["first", "second", "third"];
+        let password = "password";
+
+        let outputs = encrypt_many(payloads, password, None).expect("Failed to encrypt payloads");
+
+        let [first, second, third] =
+            <[EncryptionOutput; 3]>::try_from(outputs.clone()).expect("Wrong number of outputs");
+        assert_eq!(first.kdf_salt, second.kdf_salt);
+        assert_eq!(second.kdf_salt, third.kdf_salt);
+        assert_ne!(first.salt, second.salt);
+        assert_ne!(second.salt, third.salt);
+        for (output, payload) in outputs.iter().zip(payloads) {
+            let decrypted_payload =
+                decrypt(output.clone(), password).expect("Failed to decrypt payload");
+            assert_eq!(decrypted_payload, payload);
+        }
+
+        let decrypted_payloads =
+            decrypt_many(outputs, password).expect("Failed to decrypt payloads");
+        assert_eq!(decrypted_payloads, payloads);
+    }
+
+    #[test]
+    fn decrypt_many_handles_different_kdf_salts() {
+        let password = "password";
+        let outputs = vec![
+            encrypt("first", password, None).expect("Failed to encrypt payload"),
+            encrypt_legacy("second", password),
+            encrypt("third", password, None).expect("Failed to encrypt payload"),
+        ];
+
+        let decrypted_payloads =
+            decrypt_many(outputs, password).expect("Failed to decrypt payloads");
+        assert_eq!(decrypted_payloads, ["first", "second", "third"]);
+    }
+
+    #[test]
+    fn decrypt_many_with_wrong_password_fails() {
+        let outputs =
+            encrypt_many(["first", "second"], "password", None).expect("Failed to encrypt");
+
+        let error = decrypt_many(outputs, "wrong_password").expect_err("Decryption must fail");
+        assert!(matches!(error, Error::WrongPassword));
+    }
+
+    #[test]
+    fn batch_cost_is_dominated_by_ciphers() {
+        /// Number of payloads encrypted in a batch.
+        const BATCH_SIZE: usize = 100;
+        /// Number of payloads encrypted one by one, much less than [`BATCH_SIZE`].
+        const SINGLE_COUNT: usize = 10;
+
+        /// Call `f` measuring how long it takes.
+        fn measure<T>(f: impl FnOnce() -> T) -> (T, std::time::Duration) {
+            let start = std::time::Instant::now();
+            let value = f();
+            (value, start.elapsed())
+        }
+
+        let params = EncryptParams {
+            kdf_iterations: 20_000,
+            algorithm: Algorithm::Aes256Gcm,
+            pad_to: None,
+        };
+        let password = "password";
+        let payloads = vec!["payload"; BATCH_SIZE];
+
+        let (outputs, batch_encryption) = measure(|| {
+            encrypt_many(payloads.iter().copied(), password, Some(params))
+                .expect("Failed to encrypt")
+        });
+        let (single_outputs, single_encryption) = measure(|| {
+            payloads
+                .iter()
+                .take(SINGLE_COUNT)
+                .map(|payload| encrypt(payload, password, Some(params)).expect("Failed to encrypt"))
+                .collect::<Vec<_>>()
+        });
+
+        // Key derivation per item would make the batch 10 times slower than single encryptions
+        assert!(
+            batch_encryption < single_encryption,
+            "{BATCH_SIZE} batch encryptions took {batch_encryption:?}, \
+             {SINGLE_COUNT} single ones took {single_encryption:?}"
+        );
+
+        let ((), batch_decryption) = measure(|| {
+            decrypt_many(outputs, password).expect("Failed to decrypt");
+        });
+        let ((), single_decryption) = measure(|| {
+            for output in single_outputs {
+                decrypt(output, password).expect("Failed to decrypt");
+            }
+        });
+
+        assert!(
+            batch_decryption < single_decryption,
+            "{BATCH_SIZE} batch decryptions took {batch_decryption:?}, \
+             {SINGLE_COUNT} single ones took {single_decryption:?}"
+        );
+    }
+}
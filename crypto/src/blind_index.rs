@@ -0,0 +1,246 @@
+//! Module with [`BlindIndexKey`] implementation.
+
+use aes_gcm::{aes::cipher::Unsigned, KeyInit, KeySizeUser};
+use pbkdf2::{
+    hmac::{digest::OutputSizeUser, Hmac, Mac as _},
+    pbkdf2_hmac_array,
+};
+use sha2::Sha256;
+
+use crate::{
+    keywords, normalize_password, BlindToken, Error, PasswordFingerprint, Result,
+    BLIND_INDEX_VERSION_1, BLIND_INDEX_VERSION_2, BLIND_TOKEN_SIZE, DEFAULT_KDF_ITERATIONS,
+    MAX_INDEXED_KEYWORDS, PASSWORD_FINGERPRINT_SIZE,
+};
+
+/// Key to blind keywords of records encrypted with the same master password.
+///
+/// Blinding is deterministic, so the storage can match tokens without knowing the keywords,
+/// but it also sees which records share a keyword.
+pub struct BlindIndexKey(pbkdf2::hmac::digest::Key<Hmac<Sha256>>);
+
+impl BlindIndexKey {
+    /// Derive key of [`LATEST_BLIND_INDEX_VERSION`](crate::LATEST_BLIND_INDEX_VERSION) from
+    /// `master_password`.
+    ///
+    /// Uses its own salt, so the key is unrelated to the encryption key.
+    /// Always uses [`DEFAULT_KDF_ITERATIONS`], so that tokens of all records stay comparable.
+    #[must_use]
+    pub fn derive(master_password: &str) -> Self {
+        let master_password = normalize_password(master_password);
+        Self::derive_from_bytes(master_password.as_bytes())
+    }
+
+    /// Derive key of blind index `version` from `master_password`.
+    ///
+    /// Needed to search records indexed before
+    /// [`LATEST_BLIND_INDEX_VERSION`](crate::LATEST_BLIND_INDEX_VERSION).
+    ///
+    /// # Errors
+    ///
+    /// [`Error::UnsupportedBlindIndexVersion`] if `version` is newer than
+    /// [`LATEST_BLIND_INDEX_VERSION`](crate::LATEST_BLIND_INDEX_VERSION).
+    pub fn derive_with_version(master_password: &str, version: u8) -> Result<Self> {
+        match version {
+            BLIND_INDEX_VERSION_1 => Ok(Self::derive_from_bytes(master_password.as_bytes())),
+            BLIND_INDEX_VERSION_2 => Ok(Self::derive(master_password)),
+            _ => Err(Error::UnsupportedBlindIndexVersion(version)),
+        }
+    }
+
+    /// Derive key of [`BLIND_INDEX_VERSION_1`] from `master_password` if it differs from the one
+    /// of [`derive()`](Self::derive), which happens only if `master_password` isn't normalized.
+    ///
+    /// Cheaper than [`derive_with_version()`](Self::derive_with_version) when the keys are the
+    /// same, since key derivation is skipped.
+    #[must_use]
+    pub fn derive_legacy(master_password: &str) -> Option<Self> {
+        (*normalize_password(master_password) != master_password)
+            .then(|| Self::derive_from_bytes(master_password.as_bytes()))
+    }
+
+    /// Derive key from `master_password` bytes as they are.
+    fn derive_from_bytes(master_password: &[u8]) -> Self {
+        /// Salt to be used for key derivation
+        const BLIND_INDEX_SALT: &[u8] = b"telepass_blind_index_salt";
+        /// Size of the key in bytes
+        const KEY_SIZE: usize = <<Hmac<Sha256> as KeySizeUser>::KeySize as Unsigned>::USIZE;
+
+        let key = pbkdf2_hmac_array::<Sha256, KEY_SIZE>(
+            master_password,
+            BLIND_INDEX_SALT,
+            DEFAULT_KDF_ITERATIONS,
+        );
+        Self(key.into())
+    }
+
+    /// Construct blind index of `text` with tokens of its first [`MAX_INDEXED_KEYWORDS`]
+    /// [`keywords()`].
+    #[must_use]
+    pub fn index(&self, text: &str) -> Vec<BlindToken> {
+        keywords(text)
+            .iter()
+            .take(MAX_INDEXED_KEYWORDS)
+            .map(|keyword| self.blind(keyword))
+            .collect()
+    }
+
+    /// Construct token to search for `word` in the blind index.
+    ///
+    /// Returns [`None`] if `word` is not exactly one keyword.
+    #[must_use]
+    pub fn token(&self, word: &str) -> Option<BlindToken> {
+        let mut keywords = keywords(word).into_iter();
+        match (keywords.next(), keywords.next()) {
+            (Some(keyword), None) => Some(self.blind(&keyword)),
+            _ => None,
+        }
+    }
+
+    /// Construct fingerprint of `password` to detect its reuse across records.
+    ///
+    /// Never equals a token of any keyword, even if the password is a keyword itself.
+    #[must_use]
+    pub fn password_fingerprint(&self, password: &str) -> PasswordFingerprint {
+        /// Prefix separating passwords from keywords, which never contain `\0`
+        const PASSWORD_PREFIX: &[u8] = b"password\0";
+        /// Health check
+        const _: () = assert!(
+            PASSWORD_FINGERPRINT_SIZE
+                <= <<Sha256 as OutputSizeUser>::OutputSize as Unsigned>::USIZE,
+            "Password fingerprint is longer than SHA 256 output"
+        );
+
+        self.mac([PASSWORD_PREFIX, password.as_bytes()])
+    }
+
+    /// Blind already normalized `keyword` with HMAC truncated to [`BLIND_TOKEN_SIZE`].
+    fn blind(&self, keyword: &str) -> BlindToken {
+        /// Health check
+        const _: () = assert!(
+            BLIND_TOKEN_SIZE <= <<Sha256 as OutputSizeUser>::OutputSize as Unsigned>::USIZE,
+            "Blind token is longer than SHA 256 output"
+        );
+
+        self.mac([keyword.as_bytes()])
+    }
+
+    /// Compute HMAC of concatenated `parts` truncated to `N` bytes.
+    fn mac<const N: usize, const P: usize>(&self, parts: [&[u8]; P]) -> [u8; N] {
+        let mut mac = <Hmac<Sha256> as KeyInit>::new(&self.0);
+        for part in parts {
+            mac.update(part);
+        }
+        let mut output = [0; N];
+        for (output_byte, mac_byte) in output.iter_mut().zip(mac.finalize().into_bytes()) {
+            *output_byte = mac_byte;
+        }
+        output
+    }
+}
+
+impl core::fmt::Debug for BlindIndexKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BlindIndexKey").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::expect_used, reason = "it's ok in tests")]
+
+    use super::*;
+    use crate::LATEST_BLIND_INDEX_VERSION;
+
+    #[test]
+    fn blind_index_token_matches_only_same_keyword_and_password() {
+        let key = BlindIndexKey::derive("password");
+        let index = key.index("Login for the home Router");
+
+        assert_eq!(index.len(), 3);
+        let token = key.token(" router ").expect("Router should be a keyword");
+        assert!(index.contains(&token));
+        assert!(!index.contains(&key.token("routers").expect("Routers should be a keyword")));
+
+        let other_key = BlindIndexKey::derive("password2");
+        assert!(!index.contains(
+            &other_key
+                .token("router")
+                .expect("Router should be a keyword")
+        ));
+    }
+
+    #[test]
+    fn blind_index_token_requires_single_keyword() {
+        let key = BlindIndexKey::derive("password");
+
+        for word in ["", "the", "ab", "home router"] {
+            assert_eq!(key.token(word), None, "{word:?} gave a token");
+        }
+    }
+
+    #[test]
+    fn password_fingerprint_matches_only_same_password_and_master_password() {
+        let key = BlindIndexKey::derive("password");
+        let fingerprint = key.password_fingerprint("hunter2");
+
+        assert_eq!(fingerprint, key.password_fingerprint("hunter2"));
+        assert_ne!(fingerprint, key.password_fingerprint("Hunter2"));
+        assert_ne!(
+            fingerprint,
+            BlindIndexKey::derive("password2").password_fingerprint("hunter2")
+        );
+    }
+
+    #[test]
+    fn legacy_blind_index_key_uses_raw_master_password() {
+        let composed = "caf\u{e9}";
+        let decomposed = "cafe\u{301}";
+        let legacy_key = |master_password| {
+            BlindIndexKey::derive_with_version(master_password, BLIND_INDEX_VERSION_1)
+                .expect("Failed to derive legacy key")
+        };
+
+        assert_ne!(
+            legacy_key(composed).token("router"),
+            legacy_key(decomposed).token("router")
+        );
+        assert_eq!(
+            BlindIndexKey::derive_legacy(decomposed).map(|key| key.token("router")),
+            Some(legacy_key(decomposed).token("router"))
+        );
+        assert!(BlindIndexKey::derive_legacy(composed).is_none());
+        assert_eq!(
+            legacy_key("password").token("router"),
+            BlindIndexKey::derive("password").token("router")
+        );
+        assert!(matches!(
+            BlindIndexKey::derive_with_version("password", LATEST_BLIND_INDEX_VERSION + 1),
+            Err(Error::UnsupportedBlindIndexVersion(3))
+        ));
+    }
+
+    #[test]
+    fn password_fingerprint_differs_from_keyword_token() {
+        let key = BlindIndexKey::derive("password");
+        let token = key.token("router").expect("Router should be a keyword");
+
+        assert_ne!(
+            key.password_fingerprint("router").as_slice(),
+            &token[..PASSWORD_FINGERPRINT_SIZE]
+        );
+    }
+
+    #[test]
+    fn blind_index_is_limited() {
+        let text = (0..MAX_INDEXED_KEYWORDS * 2)
+            .map(|i| format!("word{i}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        assert_eq!(
+            BlindIndexKey::derive("password").index(&text).len(),
+            MAX_INDEXED_KEYWORDS
+        );
+    }
+}
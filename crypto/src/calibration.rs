@@ -0,0 +1,104 @@
+//! Calibration of the key derivation cost to the host.
+//!
+//! A fixed number of iterations is either too slow on weak hosts or too weak on powerful ones,
+//! so [`calibrate_iterations()`] measures the host and picks the number fitting into a time budget.
+
+use std::time::{Duration, Instant};
+
+use super::{derive_key, KdfSalt, KDF_SALT_SIZE, LATEST_OUTPUT_VERSION};
+
+/// Lower clamp of [`calibrate_iterations()`], so that a tiny budget doesn't make brute-forcing
+/// trivial.
+pub const MIN_KDF_ITERATIONS: u32 = 10_000;
+
+/// Upper clamp of [`calibrate_iterations()`], so that a huge budget doesn't make decryption
+/// unbearably slow on other devices.
+pub const MAX_KDF_ITERATIONS: u32 = 10_000_000;
+
+/// Number of iterations to measure the host with.
+const SAMPLE_ITERATIONS: u32 = 10_000;
+
+/// Number of measurements, the fastest one is taken to reduce noise.
+const SAMPLES: usize = 3;
+
+/// Measure the host and get the number of key derivation iterations taking `target` time.
+///
+/// The result is clamped to [`MIN_KDF_ITERATIONS`]..=[`MAX_KDF_ITERATIONS`].
+#[must_use]
+pub fn calibrate_iterations(target: Duration) -> u32 {
+    let salt: KdfSalt = [0; KDF_SALT_SIZE];
+    let sample_elapsed = (0..SAMPLES)
+        .map(|_| {
+            let start = Instant::now();
+            let _key = derive_key(
+                "calibration",
+                LATEST_OUTPUT_VERSION,
+                Some(&salt),
+                SAMPLE_ITERATIONS,
+            );
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default();
+
+    iterations_for(target, SAMPLE_ITERATIONS, sample_elapsed)
+}
+
+/// Extrapolate `sample_iterations` taking `sample_elapsed` time to `target` time.
+///
+/// The result is clamped to [`MIN_KDF_ITERATIONS`]..=[`MAX_KDF_ITERATIONS`].
+fn iterations_for(target: Duration, sample_iterations: u32, sample_elapsed: Duration) -> u32 {
+    let iterations = target
+        .as_nanos()
+        .checked_mul(u128::from(sample_iterations))
+        .and_then(|product| product.checked_div(sample_elapsed.as_nanos()))
+        .map_or(MAX_KDF_ITERATIONS, |iterations| {
+            u32::try_from(iterations).unwrap_or(MAX_KDF_ITERATIONS)
+        });
+
+    iterations.clamp(MIN_KDF_ITERATIONS, MAX_KDF_ITERATIONS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iterations_are_proportional_to_target() {
+        assert_eq!(
+            iterations_for(
+                Duration::from_millis(500),
+                10_000,
+                Duration::from_millis(10)
+            ),
+            500_000
+        );
+        assert_eq!(
+            iterations_for(Duration::from_secs(1), 30_000, Duration::from_millis(100)),
+            300_000
+        );
+    }
+
+    #[test]
+    fn iterations_are_clamped() {
+        assert_eq!(
+            iterations_for(Duration::from_millis(1), 10_000, Duration::from_secs(1)),
+            MIN_KDF_ITERATIONS
+        );
+        assert_eq!(
+            iterations_for(Duration::from_secs(3600), 10_000, Duration::from_millis(1)),
+            MAX_KDF_ITERATIONS
+        );
+        assert_eq!(
+            iterations_for(Duration::from_secs(1), 10_000, Duration::ZERO),
+            MAX_KDF_ITERATIONS
+        );
+    }
+
+    #[test]
+    fn calibrated_iterations_are_within_clamps() {
+        let iterations = calibrate_iterations(Duration::from_millis(10));
+
+        assert!((MIN_KDF_ITERATIONS..=MAX_KDF_ITERATIONS).contains(&iterations));
+    }
+}
@@ -0,0 +1,214 @@
+//! Module with [`DerivedKey`] to encrypt and decrypt many payloads during a session.
+
+use aes_gcm::aead::{rand_core::RngCore as _, OsRng};
+use zeroize::{Zeroize as _, ZeroizeOnDrop};
+
+use crate::{
+    decode_text, derive_key, open_with_key, pad_text, seal_with_key, validate_ciphertext,
+    Algorithm, EncryptParams, EncryptionOutput, Error, KdfSalt, Key, Result,
+    DEFAULT_MAX_CIPHERTEXT_SIZE, LATEST_OUTPUT_VERSION,
+};
+
+/// Key derived from a password once to encrypt and decrypt many payloads during a session.
+///
+/// Created with [`DerivedKey::derive()`] and used with [`encrypt_with_key()`] and
+/// [`decrypt_with_key()`]. Zeroized on drop and intentionally not [`Clone`], so that the key
+/// doesn't spread over memory.
+pub struct DerivedKey {
+    /// Derived key.
+    key: Key,
+    /// Random salt the key was derived with.
+    kdf_salt: KdfSalt,
+    /// Number of key derivation iterations.
+    kdf_iterations: u32,
+    /// Algorithm to encrypt with.
+    algorithm: Algorithm,
+    /// Length to pad text payloads to, see [`EncryptParams::pad_to`].
+    pad_to: Option<usize>,
+}
+
+impl DerivedKey {
+    /// Derive key from `password` with a random salt.
+    ///
+    /// Uses [`EncryptParams::default()`] if `params` are not provided.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::ZeroKdfIterations`] if `params` have zero key derivation iterations.
+    pub fn derive(password: &str, params: Option<EncryptParams>) -> Result<Self> {
+        let EncryptParams {
+            kdf_iterations,
+            algorithm,
+            pad_to,
+        } = params.unwrap_or_default();
+        let mut kdf_salt = KdfSalt::default();
+        OsRng.fill_bytes(&mut kdf_salt);
+
+        Ok(Self {
+            key: derive_key(
+                password,
+                LATEST_OUTPUT_VERSION,
+                Some(&kdf_salt),
+                kdf_iterations,
+            )?,
+            kdf_salt,
+            kdf_iterations,
+            algorithm,
+            pad_to,
+        })
+    }
+
+    /// Check if `output` was encrypted with a key derived with the same parameters.
+    fn matches(&self, output: &EncryptionOutput) -> bool {
+        output.version == LATEST_OUTPUT_VERSION
+            && output.kdf_salt == Some(self.kdf_salt)
+            && output.kdf_iterations == self.kdf_iterations
+    }
+}
+
+impl Drop for DerivedKey {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for DerivedKey {}
+
+impl core::fmt::Debug for DerivedKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DerivedKey")
+            .field("kdf_iterations", &self.kdf_iterations)
+            .field("algorithm", &self.algorithm)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Encrypt payload with already derived `key`.
+///
+/// Unlike [`encrypt()`](crate::encrypt), the key derivation doesn't run, so all outputs share the
+/// key derivation salt of the `key`, but every one has its own nonce. Outputs can be decrypted
+/// with the password as well.
+///
+/// # Errors
+///
+/// Any error from underlying libraries.
+pub fn encrypt_with_key(key: &DerivedKey, payload: &str) -> Result<EncryptionOutput> {
+    let payload = pad_text(payload, key.pad_to);
+    seal_with_key(
+        &key.key,
+        &payload,
+        key.kdf_salt,
+        key.kdf_iterations,
+        key.algorithm,
+        &[],
+        &mut OsRng,
+    )
+}
+
+/// Decrypt `output` of [`encrypt_with_key()`] with the same `key`.
+///
+/// # Errors
+///
+/// - [`Error::KeyMismatch`] if `output` is encrypted with a key derived with other parameters, e.g.
+///   with another [`DerivedKey`] or with [`encrypt()`](crate::encrypt);
+/// - See [`decrypt()`](crate::decrypt) for other errors.
+pub fn decrypt_with_key(key: &DerivedKey, output: EncryptionOutput) -> Result<String> {
+    if !key.matches(&output) {
+        return Err(Error::KeyMismatch);
+    }
+    validate_ciphertext(&output.encrypted_payload, DEFAULT_MAX_CIPHERTEXT_SIZE)?;
+    let EncryptionOutput {
+        encrypted_payload,
+        salt,
+        key_commitment,
+        ..
+    } = output;
+    decode_text(open_with_key(
+        &key.key,
+        key_commitment.as_ref(),
+        salt,
+        &encrypted_payload,
+        &[],
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::expect_used, reason = "it's ok in tests")]
+
+    use super::*;
+    use crate::{decrypt, encrypt};
+
+    #[test]
+    fn derived_key_round_trip_is_compatible_with_password() {
+        let params = EncryptParams {
+            kdf_iterations: 1_000,
+            algorithm: Algorithm::XChaCha20Poly1305,
+            pad_to: None,
+        };
+        let key = DerivedKey::derive("password", Some(params)).expect("Failed to derive key");
+
+        let first = encrypt_with_key(&key, "first").expect("Failed to encrypt payload");
+        let second = encrypt_with_key(&key, "second").expect("Failed to encrypt payload");
+        assert_eq!(first.kdf_salt, second.kdf_salt);
+        assert_ne!(first.salt, second.salt);
+        assert_eq!(first.algorithm(), params.algorithm);
+
+        assert_eq!(
+            decrypt_with_key(&key, first).expect("Failed to decrypt payload"),
+            "first"
+        );
+        assert_eq!(
+            decrypt(second, "password").expect("Failed to decrypt payload"),
+            "second"
+        );
+    }
+
+    #[test]
+    fn keys_derived_with_different_params_are_incompatible() {
+        let params = EncryptParams {
+            kdf_iterations: 1_000,
+            ..EncryptParams::default()
+        };
+        let key = DerivedKey::derive("password", Some(params)).expect("Failed to derive key");
+        let output = encrypt_with_key(&key, "payload").expect("Failed to encrypt payload");
+
+        let other_salt_key =
+            DerivedKey::derive("password", Some(params)).expect("Failed to derive key");
+        let other_iterations_key = DerivedKey::derive(
+            "password",
+            Some(EncryptParams {
+                kdf_iterations: 2_000,
+                ..params
+            }),
+        )
+        .expect("Failed to derive key");
+        for other_key in [&other_salt_key, &other_iterations_key] {
+            let error = decrypt_with_key(other_key, output.clone())
+                .expect_err("Decryption with another key is expected to fail");
+            assert!(matches!(error, Error::KeyMismatch), "{error:?}");
+        }
+
+        let password_output =
+            encrypt("payload", "password", Some(params)).expect("Failed to encrypt payload");
+        let error = decrypt_with_key(&key, password_output)
+            .expect_err("Decryption of output with its own salt is expected to fail");
+        assert!(matches!(error, Error::KeyMismatch), "{error:?}");
+    }
+
+    #[test]
+    fn derived_key_debug_hides_key() {
+        let key = DerivedKey::derive(
+            "password",
+            Some(EncryptParams {
+                kdf_iterations: 1_000,
+                ..EncryptParams::default()
+            }),
+        )
+        .expect("Failed to derive key");
+
+        let debug = format!("{key:?}");
+        assert!(!debug.contains("key:"), "{debug}");
+        assert!(!debug.contains("kdf_salt"), "{debug}");
+    }
+}
@@ -0,0 +1,100 @@
+//! Module with signing of exported records.
+
+use aes_gcm::{aes::cipher::Unsigned, KeyInit, KeySizeUser};
+use pbkdf2::{
+    hmac::{digest::OutputSizeUser, Hmac, Mac as _},
+    pbkdf2_hmac_array,
+};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::{
+    normalize_password, Error, ExportTag, Result, DEFAULT_KDF_ITERATIONS, EXPORT_TAG_SIZE,
+};
+
+/// Sign export `bundle` with a key derived from `master_password`.
+///
+/// Tag is an HMAC-SHA256 of the whole bundle, so that tampering is detected by
+/// [`verify_export()`] before any of the exported outputs is decrypted.
+#[must_use]
+pub fn sign_export(bundle: &[u8], master_password: &str) -> ExportTag {
+    export_mac(bundle, master_password)
+        .finalize()
+        .into_bytes()
+        .into()
+}
+
+/// Verify `tag` of export `bundle` produced by [`sign_export()`].
+///
+/// Comparison takes constant time.
+///
+/// # Errors
+///
+/// [`Error::TamperedExport`] if `bundle` or `tag` is modified or `master_password` is wrong.
+pub fn verify_export(bundle: &[u8], master_password: &str, tag: &ExportTag) -> Result<()> {
+    export_mac(bundle, master_password)
+        .verify_slice(tag)
+        .map_err(|_err| Error::TamperedExport)
+}
+
+/// Construct HMAC of export `bundle` keyed with a key derived from `master_password`.
+///
+/// Uses its own salt, so the key is unrelated to the encryption and blind index keys.
+/// Always uses [`DEFAULT_KDF_ITERATIONS`], so that the tag doesn't depend on the host, and
+/// `master_password` normalized to NFKC, so that it doesn't depend on the keyboard.
+fn export_mac(bundle: &[u8], master_password: &str) -> Hmac<Sha256> {
+    /// Salt to be used for key derivation
+    const EXPORT_SIGNING_SALT: &[u8] = b"telepass_export_signing_salt";
+    /// Size of the key in bytes
+    const KEY_SIZE: usize = <<Hmac<Sha256> as KeySizeUser>::KeySize as Unsigned>::USIZE;
+    /// Health check
+    const _: () = assert!(
+        EXPORT_TAG_SIZE == <<Sha256 as OutputSizeUser>::OutputSize as Unsigned>::USIZE,
+        "Export tag size and SHA 256 output size mismatch"
+    );
+
+    let key = Zeroizing::new(pbkdf2_hmac_array::<Sha256, KEY_SIZE>(
+        normalize_password(master_password).as_bytes(),
+        EXPORT_SIGNING_SALT,
+        DEFAULT_KDF_ITERATIONS,
+    ));
+    <Hmac<Sha256> as KeyInit>::new(&(*key).into()).chain_update(bundle)
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::expect_used, reason = "it's ok in tests")]
+
+    use super::*;
+
+    #[test]
+    fn export_signature_round_trips() {
+        let bundle = br#"[{"payload":"abc"},{"payload":"def"}]"#;
+
+        let tag = sign_export(bundle, "password");
+
+        assert_eq!(tag, sign_export(bundle, "password"));
+        verify_export(bundle, "password", &tag).expect("Failed to verify export");
+    }
+
+    #[test]
+    fn tampered_export_is_detected() {
+        let bundle = br#"[{"payload":"abc"},{"payload":"def"}]"#;
+        let tag = sign_export(bundle, "password");
+
+        let mut tampered_bundle = bundle.to_vec();
+        tampered_bundle.truncate(20);
+        let mut tampered_tag = tag;
+        tampered_tag.reverse();
+        for (checked_bundle, password, checked_tag) in [
+            (tampered_bundle.as_slice(), "password", &tag),
+            (bundle.as_slice(), "password", &tampered_tag),
+            (bundle.as_slice(), "password2", &tag),
+        ] {
+            assert!(matches!(
+                verify_export(checked_bundle, password, checked_tag),
+                Err(Error::TamperedExport)
+            ));
+        }
+    }
+}
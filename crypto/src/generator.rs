@@ -0,0 +1,365 @@
+//! Generation of random passwords.
+//!
+//! Characters and words are chosen with [`OsRng`], so generated passwords are suitable
+//! to be stored as is.
+
+use aes_gcm::aead::{
+    rand_core::{CryptoRng, RngCore},
+    OsRng,
+};
+
+/// ASCII lowercase letters.
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+
+/// ASCII uppercase letters.
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// ASCII digits.
+const DIGITS: &str = "0123456789";
+
+/// ASCII punctuation.
+const SYMBOLS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+/// Characters easily confused with each other in common fonts.
+pub const AMBIGUOUS: &str = "0Oo1lI|`'\"";
+
+/// Words of [`Mode::Pronounceable`] passwords.
+const WORDS: [&str; 64] = [
+    "amber", "anvil", "apple", "basin", "berry", "brave", "bread", "cabin", "candy", "cedar",
+    "chess", "cider", "crane", "crest", "dance", "delta", "dream", "dwarf", "eager", "ember",
+    "fable", "fancy", "fresh", "fudge", "gamma", "ghost", "grape", "haste", "heart", "honey",
+    "jumpy", "karma", "kayak", "lemon", "maple", "march", "merry", "nerve", "ocean", "otter",
+    "paper", "parse", "pearl", "quest", "quick", "raven", "river", "sauce", "shade", "spark",
+    "steam", "tempt", "tiger", "trend", "udder", "umber", "vague", "vapor", "waxen", "whale",
+    "yacht", "yearn", "zebra", "zesty",
+];
+
+/// Rules of a generated password.
+///
+/// Default policy gives 16 random characters of all classes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "every flag is an independent user choice"
+)]
+pub struct PasswordPolicy {
+    /// Minimal number of characters.
+    ///
+    /// It's exact for [`Mode::Random`] if it's not less than the number of enabled classes.
+    pub length: usize,
+    /// Use ASCII lowercase letters.
+    pub lowercase: bool,
+    /// Use ASCII uppercase letters.
+    pub uppercase: bool,
+    /// Use ASCII digits.
+    pub digits: bool,
+    /// Use ASCII punctuation.
+    pub symbols: bool,
+    /// Don't use [`AMBIGUOUS`] characters.
+    pub exclude_ambiguous: bool,
+    /// How characters are chosen.
+    pub mode: Mode,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            length: 16,
+            lowercase: true,
+            uppercase: true,
+            digits: true,
+            symbols: true,
+            exclude_ambiguous: false,
+            mode: Mode::Random,
+        }
+    }
+}
+
+/// How characters of a generated password are chosen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Every character is chosen independently.
+    #[default]
+    Random,
+    /// Password consists of words from a fixed list joined with separators.
+    ///
+    /// Easier to type and remember, but has less entropy per character.
+    /// Letters are always used: lowercase if no letter class is enabled,
+    /// capitalized words if both are.
+    /// Separators are symbols if they are enabled, otherwise digits if they are enabled,
+    /// otherwise words are joined as is. Missing symbol and digit are appended to the end.
+    Pronounceable,
+}
+
+impl PasswordPolicy {
+    /// Get character pools of the enabled classes.
+    ///
+    /// Lowercase letters are used if no class is enabled.
+    fn pools(&self) -> Vec<Vec<char>> {
+        let mut pools = [
+            (self.lowercase, LOWERCASE),
+            (self.uppercase, UPPERCASE),
+            (self.digits, DIGITS),
+            (self.symbols, SYMBOLS),
+        ]
+        .into_iter()
+        .filter(|&(enabled, _chars)| enabled)
+        .map(|(_enabled, chars)| self.allowed_chars(chars))
+        .collect::<Vec<_>>();
+        if pools.is_empty() {
+            pools.push(self.allowed_chars(LOWERCASE));
+        }
+        pools
+    }
+
+    /// Get `chars` allowed by the policy.
+    fn allowed_chars(&self, chars: &str) -> Vec<char> {
+        chars.chars().filter(|&c| self.allows(c)).collect()
+    }
+
+    /// Check if `c` is allowed regarding ambiguity.
+    fn allows(&self, c: char) -> bool {
+        !self.exclude_ambiguous || !AMBIGUOUS.contains(c)
+    }
+}
+
+/// Generate a random password following `policy`.
+///
+/// Characters of every enabled class are guaranteed to be present in the password.
+#[must_use]
+pub fn generate_password(policy: PasswordPolicy) -> String {
+    generate_password_with_rng(policy, &mut OsRng)
+}
+
+/// Same as [`generate_password()`], but takes randomness from `rng` instead of the operating
+/// system.
+///
+/// Allows to produce reproducible passwords with a seeded `rng`, e.g. in tests.
+#[must_use]
+pub fn generate_password_with_rng<R: CryptoRng + RngCore>(
+    policy: PasswordPolicy,
+    rng: &mut R,
+) -> String {
+    match policy.mode {
+        Mode::Random => random(&policy, rng),
+        Mode::Pronounceable => pronounceable(&policy, rng),
+    }
+}
+
+/// Generate [`Mode::Random`] password.
+fn random<R: CryptoRng + RngCore>(policy: &PasswordPolicy, rng: &mut R) -> String {
+    let pools = policy.pools();
+    let all = pools.concat();
+
+    // One character of every class first, so that none is missing
+    let mut chars = pools
+        .iter()
+        .filter_map(|pool| choose(pool, rng))
+        .collect::<Vec<_>>();
+    while chars.len() < policy.length {
+        let Some(c) = choose(&all, rng) else {
+            break;
+        };
+        chars.push(c);
+    }
+    shuffle(&mut chars, rng);
+
+    chars.into_iter().collect()
+}
+
+/// Generate [`Mode::Pronounceable`] password.
+fn pronounceable<R: CryptoRng + RngCore>(policy: &PasswordPolicy, rng: &mut R) -> String {
+    let words = WORDS
+        .iter()
+        .map(|word| match (policy.lowercase, policy.uppercase) {
+            (true, true) => capitalize(word),
+            (false, true) => word.to_uppercase(),
+            (_, false) => (*word).to_owned(),
+        })
+        .filter(|word| word.chars().all(|c| policy.allows(c)))
+        .collect::<Vec<_>>();
+    let separators = if policy.symbols {
+        policy.allowed_chars(SYMBOLS)
+    } else if policy.digits {
+        policy.allowed_chars(DIGITS)
+    } else {
+        Vec::new()
+    };
+
+    let mut password = String::new();
+    loop {
+        let Some(word) = choose(&words, rng) else {
+            break;
+        };
+        password.push_str(&word);
+        if password.chars().count() >= policy.length {
+            break;
+        }
+        if let Some(separator) = choose(&separators, rng) {
+            password.push(separator);
+        }
+    }
+    // Separators may be missing or be of another class
+    for (enabled, chars) in [(policy.symbols, SYMBOLS), (policy.digits, DIGITS)] {
+        if enabled && !password.chars().any(|c| chars.contains(c)) {
+            if let Some(c) = choose(&policy.allowed_chars(chars), rng) {
+                password.push(c);
+            }
+        }
+    }
+
+    password
+}
+
+/// Make the first letter of `word` uppercase.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+/// Choose random item of `items`.
+///
+/// Returns [`None`] if `items` are empty.
+fn choose<T: Clone, R: CryptoRng + RngCore>(items: &[T], rng: &mut R) -> Option<T> {
+    items.get(random_index(items.len(), rng)?).cloned()
+}
+
+/// Shuffle `items` with Fisher-Yates algorithm.
+fn shuffle<T, R: CryptoRng + RngCore>(items: &mut [T], rng: &mut R) {
+    for i in (1..items.len()).rev() {
+        if let Some(j) = random_index(i.saturating_add(1), rng) {
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Get uniformly distributed random index less than `len`.
+///
+/// Returns [`None`] if `len` is zero.
+fn random_index<R: CryptoRng + RngCore>(len: usize, rng: &mut R) -> Option<usize> {
+    let bound = u64::try_from(len).ok().filter(|&bound| bound > 0)?;
+    // Values from the incomplete last range are rejected to avoid modulo bias
+    let zone = u64::MAX.saturating_sub(u64::MAX.checked_rem(bound)?);
+    loop {
+        let value = rng.next_u64();
+        if value < zone {
+            return usize::try_from(value.checked_rem(bound)?).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use rand_chacha::{rand_core::SeedableRng as _, ChaCha20Rng};
+
+    use super::*;
+
+    /// Strategy for arbitrary policies.
+    fn any_policy() -> impl Strategy<Value = PasswordPolicy> {
+        (
+            0_usize..64,
+            any::<[bool; 5]>(),
+            prop_oneof![Just(Mode::Random), Just(Mode::Pronounceable)],
+        )
+            .prop_map(
+                |(length, [lowercase, uppercase, digits, symbols, exclude_ambiguous], mode)| {
+                    PasswordPolicy {
+                        length,
+                        lowercase,
+                        uppercase,
+                        digits,
+                        symbols,
+                        exclude_ambiguous,
+                        mode,
+                    }
+                },
+            )
+    }
+
+    /// Check if `password` contains any character of `chars`.
+    fn contains_any(password: &str, chars: &str) -> bool {
+        password.chars().any(|c| chars.contains(c))
+    }
+
+    proptest! {
+        #[test]
+        fn password_is_long_enough(policy in any_policy()) {
+            let password = generate_password(policy);
+
+            prop_assert!(password.chars().count() >= policy.length.max(1));
+            if policy.mode == Mode::Random {
+                let classes = [policy.lowercase, policy.uppercase, policy.digits, policy.symbols]
+                    .into_iter()
+                    .filter(|&enabled| enabled)
+                    .count();
+                prop_assert_eq!(password.chars().count(), policy.length.max(classes).max(1));
+            }
+        }
+
+        #[test]
+        fn only_enabled_classes_are_used(policy in any_policy()) {
+            let password = generate_password(policy);
+            let no_letters = !policy.lowercase && !policy.uppercase;
+            let no_classes = no_letters && !policy.digits && !policy.symbols;
+            let lowercase_implied = match policy.mode {
+                Mode::Random => no_classes,
+                Mode::Pronounceable => no_letters,
+            };
+
+            prop_assert_eq!(
+                contains_any(&password, LOWERCASE),
+                policy.lowercase || lowercase_implied
+            );
+            prop_assert_eq!(contains_any(&password, UPPERCASE), policy.uppercase);
+            prop_assert_eq!(contains_any(&password, DIGITS), policy.digits);
+            prop_assert_eq!(contains_any(&password, SYMBOLS), policy.symbols);
+        }
+
+        #[test]
+        fn ambiguous_characters_are_excluded(mut policy in any_policy()) {
+            policy.exclude_ambiguous = true;
+
+            let password = generate_password(policy);
+
+            prop_assert!(!contains_any(&password, AMBIGUOUS), "{password}");
+        }
+    }
+
+    #[test]
+    fn seeded_rng_gives_same_password() {
+        let policy = PasswordPolicy::default();
+
+        let first = generate_password_with_rng(policy, &mut ChaCha20Rng::seed_from_u64(42));
+        let second = generate_password_with_rng(policy, &mut ChaCha20Rng::seed_from_u64(42));
+
+        assert_eq!(first, second);
+        assert_ne!(first, generate_password(policy));
+    }
+
+    #[test]
+    fn pronounceable_password_consists_of_words() {
+        let policy = PasswordPolicy {
+            length: 20,
+            symbols: false,
+            mode: Mode::Pronounceable,
+            ..PasswordPolicy::default()
+        };
+
+        let password = generate_password(policy);
+
+        let words = password
+            .split(|c: char| c.is_ascii_digit())
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>();
+        assert!(words.len() >= 2, "{password}");
+        assert!(
+            words
+                .iter()
+                .all(|word| WORDS.iter().any(|known| capitalize(known) == *word)),
+            "{password}"
+        );
+    }
+}
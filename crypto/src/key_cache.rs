@@ -0,0 +1,351 @@
+//! Module with [`KeyCache`] implementation.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+};
+
+use aes_gcm::{
+    aead::{rand_core::RngCore as _, OsRng},
+    KeyInit,
+};
+use pbkdf2::hmac::{Hmac, Mac as _};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::{
+    decode_text, derive_key, open_with_key, pad_text, seal_with_key, validate_ciphertext,
+    EncryptParams, EncryptionOutput, KdfSalt, Key, Result, DEFAULT_MAX_CIPHERTEXT_SIZE, KEY_SIZE,
+    LATEST_OUTPUT_VERSION,
+};
+
+/// Maximum number of keys kept by [`KeyCache`], all of them are evicted when it's exceeded.
+///
+/// Same limit applies to the key derivation salts used for encryption.
+pub const MAX_CACHED_KEYS: usize = 256;
+
+/// Cache of keys derived from passwords, so that the slow key derivation runs only once for
+/// every password and key derivation parameters.
+///
+/// Doesn't keep passwords themselves, keys are looked up by an HMAC of the password keyed with
+/// a random key unique for this cache. Outputs of [`KeyCache::encrypt()`] with the same password
+/// and number of key derivation iterations share the key derivation salt, so that only the first
+/// one derives a key. Keys are zeroized when evicted or dropped.
+/// Should live only in memory and only for a single session.
+pub struct KeyCache {
+    /// Random HMAC key unique for this cache.
+    tag_key: pbkdf2::hmac::digest::Key<Hmac<Sha256>>,
+    /// Cached keys.
+    keys: Mutex<CachedKeys>,
+}
+
+/// HMAC of the password used to look up its keys in [`KeyCache`].
+type PasswordTag = [u8; KEY_SIZE];
+
+/// Parameters the key was derived with.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct KeyId {
+    /// Tag of the password.
+    password_tag: PasswordTag,
+    /// Version of the output defining how the password is treated.
+    version: u8,
+    /// Key derivation salt.
+    kdf_salt: Option<KdfSalt>,
+    /// Number of key derivation iterations.
+    kdf_iterations: u32,
+}
+
+/// Keys of [`KeyCache`].
+#[derive(Default)]
+struct CachedKeys {
+    /// Derived keys.
+    keys: HashMap<KeyId, Zeroizing<Key>>,
+    /// Key derivation salts used by [`KeyCache::encrypt()`] for password tags and numbers of
+    /// key derivation iterations.
+    encryption_kdf_salts: HashMap<(PasswordTag, u32), KdfSalt>,
+}
+
+impl KeyCache {
+    /// Create empty cache.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            tag_key: <Hmac<Sha256> as KeyInit>::generate_key(&mut OsRng),
+            keys: Mutex::default(),
+        }
+    }
+
+    /// Same as [`encrypt()`](crate::encrypt), but reuses the key derived for the previous output
+    /// with the same `password` and number of key derivation iterations.
+    ///
+    /// # Errors
+    ///
+    /// See [`encrypt()`](crate::encrypt).
+    pub fn encrypt(
+        &self,
+        payload: &str,
+        password: &str,
+        params: Option<EncryptParams>,
+    ) -> Result<EncryptionOutput> {
+        self.encrypt_with_aad(payload, password, params, &[])
+    }
+
+    /// Same as [`encrypt_with_aad()`](crate::encrypt_with_aad), but reuses the key derived for the
+    /// previous output with the same `password` and number of key derivation iterations.
+    ///
+    /// # Errors
+    ///
+    /// See [`encrypt()`](crate::encrypt).
+    pub fn encrypt_with_aad(
+        &self,
+        payload: &str,
+        password: &str,
+        params: Option<EncryptParams>,
+        aad: &[u8],
+    ) -> Result<EncryptionOutput> {
+        let EncryptParams {
+            kdf_iterations,
+            algorithm,
+            pad_to,
+        } = params.unwrap_or_default();
+        let password_tag = self.tag(password);
+        let kdf_salt = {
+            let mut cached_keys = self.lock();
+            if cached_keys.encryption_kdf_salts.len() >= MAX_CACHED_KEYS {
+                cached_keys.encryption_kdf_salts.clear();
+            }
+            *cached_keys
+                .encryption_kdf_salts
+                .entry((password_tag, kdf_iterations))
+                .or_insert_with(|| {
+                    let mut kdf_salt = KdfSalt::default();
+                    OsRng.fill_bytes(&mut kdf_salt);
+                    kdf_salt
+                })
+        };
+
+        let key = self.key(
+            password,
+            KeyId {
+                password_tag,
+                version: LATEST_OUTPUT_VERSION,
+                kdf_salt: Some(kdf_salt),
+                kdf_iterations,
+            },
+        )?;
+        seal_with_key(
+            &key,
+            &pad_text(payload, pad_to),
+            kdf_salt,
+            kdf_iterations,
+            algorithm,
+            aad,
+            &mut OsRng,
+        )
+    }
+
+    /// Same as [`decrypt()`](crate::decrypt), but reuses the key derived with the same `password`
+    /// and key derivation parameters.
+    ///
+    /// # Errors
+    ///
+    /// See [`decrypt()`](crate::decrypt).
+    pub fn decrypt(&self, output: EncryptionOutput, password: &str) -> Result<String> {
+        self.decrypt_with_aad(output, password, &[])
+    }
+
+    /// Same as [`decrypt_with_aad()`](crate::decrypt_with_aad), but reuses the key derived with the
+    /// same `password` and key derivation parameters.
+    ///
+    /// # Errors
+    ///
+    /// See [`decrypt_with_aad()`](crate::decrypt_with_aad).
+    pub fn decrypt_with_aad(
+        &self,
+        EncryptionOutput {
+            version,
+            encrypted_payload,
+            salt,
+            kdf_iterations,
+            kdf_salt,
+            key_commitment,
+        }: EncryptionOutput,
+        password: &str,
+        aad: &[u8],
+    ) -> Result<String> {
+        validate_ciphertext(&encrypted_payload, DEFAULT_MAX_CIPHERTEXT_SIZE)?;
+        let key = self.key(
+            password,
+            KeyId {
+                password_tag: self.tag(password),
+                version,
+                kdf_salt,
+                kdf_iterations,
+            },
+        )?;
+        let payload = open_with_key(&key, key_commitment.as_ref(), salt, &encrypted_payload, aad)?;
+        decode_text(payload)
+    }
+
+    /// Get cached key with `id` or derive it from `password`.
+    ///
+    /// Key is derived without holding the lock, so that other threads are not blocked.
+    fn key(&self, password: &str, id: KeyId) -> Result<Zeroizing<Key>> {
+        if let Some(key) = self.lock().keys.get(&id) {
+            return Ok(key.clone());
+        }
+
+        let key = Zeroizing::new(derive_key(
+            password,
+            id.version,
+            id.kdf_salt.as_ref(),
+            id.kdf_iterations,
+        )?);
+        let mut cached_keys = self.lock();
+        if cached_keys.keys.len() >= MAX_CACHED_KEYS {
+            cached_keys.keys.clear();
+        }
+        cached_keys.keys.insert(id, key.clone());
+        drop(cached_keys);
+        Ok(key)
+    }
+
+    /// Construct tag of `password`.
+    fn tag(&self, password: &str) -> PasswordTag {
+        let mut mac = <Hmac<Sha256> as KeyInit>::new(&self.tag_key);
+        mac.update(password.as_bytes());
+        mac.finalize().into_bytes().into()
+    }
+
+    /// Lock cached keys.
+    ///
+    /// Poisoning is ignored, because keys are inserted and removed atomically.
+    fn lock(&self) -> std::sync::MutexGuard<'_, CachedKeys> {
+        self.keys.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Health check
+const _: () = {
+    /// Compiles only if `T` can be shared between threads
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<KeyCache>();
+};
+
+impl Default for KeyCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Debug for KeyCache {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("KeyCache")
+            .field("cached_keys", &self.lock().keys.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::expect_used, reason = "it's ok in tests")]
+
+    use super::*;
+    use crate::{decrypt_with_aad, encrypt, Algorithm};
+
+    /// Cheap parameters for [`KeyCache`] tests.
+    const CHEAP_PARAMS: EncryptParams = EncryptParams {
+        kdf_iterations: 1000,
+        algorithm: Algorithm::XChaCha20Poly1305,
+        pad_to: None,
+    };
+
+    #[test]
+    fn key_cache_reuses_keys() {
+        let cache = KeyCache::new();
+
+        let first = cache
+            .encrypt("first", "password", Some(CHEAP_PARAMS))
+            .expect("Failed to encrypt payload");
+        let second = cache
+            .encrypt("second", "password", Some(CHEAP_PARAMS))
+            .expect("Failed to encrypt payload");
+        assert_eq!(first.kdf_salt, second.kdf_salt);
+        assert_ne!(first.salt, second.salt);
+        assert_eq!(cache.lock().keys.len(), 1);
+
+        let separate =
+            encrypt("separate", "password", Some(CHEAP_PARAMS)).expect("Failed to encrypt payload");
+        for (output, payload) in [(first, "first"), (second, "second"), (separate, "separate")] {
+            assert_eq!(
+                cache
+                    .decrypt(output, "password")
+                    .expect("Failed to decrypt payload"),
+                payload
+            );
+        }
+        assert_eq!(cache.lock().keys.len(), 2);
+    }
+
+    #[test]
+    fn key_cache_outputs_are_compatible() {
+        let cache = KeyCache::new();
+
+        let output = cache
+            .encrypt_with_aad("payload", "password", Some(CHEAP_PARAMS), b"bank.com")
+            .expect("Failed to encrypt payload");
+
+        let decrypted_payload =
+            decrypt_with_aad(output, "password", b"bank.com").expect("Failed to decrypt payload");
+        assert_eq!(decrypted_payload, "payload");
+    }
+
+    #[test]
+    fn key_cache_distinguishes_passwords() {
+        let cache = KeyCache::new();
+        let output = cache
+            .encrypt("payload", "password", Some(CHEAP_PARAMS))
+            .expect("Failed to encrypt payload");
+
+        cache
+            .decrypt(output.clone(), "wrong_password")
+            .expect_err("Decryption with wrong password is expected to fail");
+        cache
+            .decrypt_with_aad(output.clone(), "password", b"bank.com")
+            .expect_err("Decryption with wrong aad is expected to fail");
+        assert_eq!(
+            cache
+                .decrypt(output, "password")
+                .expect("Failed to decrypt payload"),
+            "payload"
+        );
+    }
+
+    #[test]
+    fn key_cache_evicts_keys_when_full() {
+        let cache = KeyCache::new();
+        let params = EncryptParams {
+            kdf_iterations: 1,
+            ..CHEAP_PARAMS
+        };
+
+        for i in 0..=MAX_CACHED_KEYS {
+            cache
+                .encrypt("payload", &i.to_string(), Some(params))
+                .expect("Failed to encrypt payload");
+        }
+
+        assert_eq!(cache.lock().keys.len(), 1);
+        assert_eq!(cache.lock().encryption_kdf_salts.len(), 1);
+    }
+
+    #[test]
+    fn key_cache_debug_hides_secrets() {
+        let cache = KeyCache::new();
+        cache
+            .encrypt("payload", "password", Some(CHEAP_PARAMS))
+            .expect("Failed to encrypt payload");
+
+        assert_eq!(format!("{cache:?}"), "KeyCache { cached_keys: 1, .. }");
+    }
+}
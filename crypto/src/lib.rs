@@ -1,203 +1,2711 @@
 //! Crate for passwords encryption and decryption used in Telepass.
 
-use std::string::FromUtf8Error;
+use std::{
+    collections::BTreeSet,
+    fmt,
+    hash::{DefaultHasher, Hasher as _},
+    str::FromStr,
+    string::FromUtf8Error,
+};
 
 #[cfg(feature = "impls")]
 use aes_gcm::{
-    aead::{Aead, OsRng},
+    aead::{
+        rand_core::{CryptoRng, RngCore},
+        Aead, OsRng, Payload,
+    },
     aes::cipher::Unsigned,
-    AeadCore, Aes256Gcm, Key, KeyInit as _, KeySizeUser, Nonce,
+    AeadCore, Aes256Gcm, KeyInit, KeySizeUser,
 };
+use base64::{engine::general_purpose::URL_SAFE, Engine as _};
+#[cfg(feature = "impls")]
+use chacha20poly1305::XChaCha20Poly1305;
 #[cfg(feature = "impls")]
-use pbkdf2::{hmac::digest::OutputSizeUser, pbkdf2_hmac_array};
+use pbkdf2::{
+    hmac::{digest::OutputSizeUser, Hmac, Mac as _},
+    pbkdf2_hmac_array,
+};
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "impls")]
 use sha2::Sha256;
+use subtle::ConstantTimeEq as _;
+#[cfg(feature = "impls")]
+use unicode_normalization::UnicodeNormalization as _;
+#[cfg(feature = "impls")]
+use zeroize::Zeroizing;
+
+#[cfg(feature = "impls")]
+mod batch;
+#[cfg(feature = "impls")]
+mod blind_index;
+// `Instant` isn't supported in browsers
+#[cfg(all(
+    feature = "impls",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+pub mod calibration;
+#[cfg(feature = "impls")]
+mod derived_key;
+#[cfg(feature = "impls")]
+mod export;
+#[cfg(feature = "impls")]
+pub mod generator;
+#[cfg(feature = "impls")]
+mod key_cache;
+#[cfg(feature = "impls")]
+mod padding;
+#[cfg(feature = "impls")]
+mod password_verifier;
+#[cfg(feature = "sharing")]
+pub mod sharing;
+#[cfg(feature = "impls")]
+pub mod signed_link;
+#[cfg(feature = "impls")]
+mod stream;
+pub mod strength;
+#[cfg(feature = "impls")]
+pub mod totp;
+
+#[cfg(feature = "impls")]
+pub use batch::{decrypt_many, encrypt_many};
+#[cfg(feature = "impls")]
+pub use blind_index::BlindIndexKey;
+#[cfg(feature = "impls")]
+pub use derived_key::{decrypt_with_key, encrypt_with_key, DerivedKey};
+#[cfg(feature = "impls")]
+pub use export::{sign_export, verify_export};
+#[cfg(feature = "impls")]
+pub use key_cache::{KeyCache, MAX_CACHED_KEYS};
+#[cfg(feature = "impls")]
+use padding::{decode_text, pad_text};
+#[cfg(feature = "impls")]
+pub use password_verifier::PasswordVerifier;
+#[cfg(feature = "impls")]
+pub use stream::{decrypt_stream, encrypt_chunks, DecryptedChunks};
 
-/// Size of the salt in bytes.
-pub const SALT_SIZE: usize = 12;
+/// Size of the [`Algorithm::Aes256Gcm`] salt in bytes.
+pub const AES_256_GCM_SALT_SIZE: usize = 12;
+
+/// Size of the [`Algorithm::XChaCha20Poly1305`] salt in bytes.
+pub const XCHACHA20_POLY1305_SALT_SIZE: usize = 24;
 
 /// Health check.
 #[cfg(feature = "impls")]
 const _: () = assert!(
-    <Aes256Gcm as AeadCore>::NonceSize::USIZE == SALT_SIZE,
+    <Aes256Gcm as AeadCore>::NonceSize::USIZE == AES_256_GCM_SALT_SIZE
+        && <XChaCha20Poly1305 as AeadCore>::NonceSize::USIZE == XCHACHA20_POLY1305_SALT_SIZE,
     "Nonce size is not equal to the salt size"
 );
 
-/// Encryption salt.
-pub type Salt = [u8; SALT_SIZE];
+/// Size of the authentication tag appended to the ciphertext by every [`Algorithm`] in bytes.
+///
+/// Any ciphertext is at least this long, even the one of an empty payload.
+pub const AEAD_TAG_SIZE: usize = 16;
 
-/// Output of encryption.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct EncryptionOutput {
-    /// Payload encrypted with a password.
-    pub encrypted_payload: Vec<u8>,
-    /// Salt used for encryption.
-    pub salt: Salt,
+/// Health check.
+#[cfg(feature = "impls")]
+const _: () = assert!(
+    <Aes256Gcm as AeadCore>::TagSize::USIZE == AEAD_TAG_SIZE
+        && <XChaCha20Poly1305 as AeadCore>::TagSize::USIZE == AEAD_TAG_SIZE,
+    "Tag size is not equal to the expected one"
+);
+
+/// Default maximum size of the ciphertext accepted for decryption in bytes.
+///
+/// Well above anything the password storage accepts, it only protects from absurd inputs.
+/// Use [`decrypt_bytes_with_max_size()`] to decrypt with another limit.
+pub const DEFAULT_MAX_CIPHERTEXT_SIZE: usize = 1024 * 1024;
+
+/// Authenticated encryption algorithm.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "&str", try_from = "String")]
+pub enum Algorithm {
+    /// AES-256 in Galois/Counter Mode, fast on hardware with AES instructions.
+    ///
+    /// Payloads encrypted before the algorithm was stored used exactly this one.
+    #[default]
+    Aes256Gcm,
+    /// XChaCha20-Poly1305, fast on hardware without AES instructions.
+    XChaCha20Poly1305,
 }
 
-/// Encryption / decryption error.
-#[derive(Debug, Clone, thiserror::Error)]
-pub enum Error {
-    #[error("Failed to encrypt payload")]
-    Encryption,
-    #[error("Failed to decrypt payload")]
-    Decryption,
-    #[error("Failed to parse decrypted payload as UTF-8")]
-    Utf8(FromUtf8Error),
+impl Algorithm {
+    /// Get stable name of the algorithm.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Aes256Gcm => "aes-256-gcm",
+            Self::XChaCha20Poly1305 => "xchacha20-poly1305",
+        }
+    }
+
+    /// Get size of the salt used by the algorithm in bytes.
+    #[must_use]
+    pub const fn salt_size(self) -> usize {
+        match self {
+            Self::Aes256Gcm => AES_256_GCM_SALT_SIZE,
+            Self::XChaCha20Poly1305 => XCHACHA20_POLY1305_SALT_SIZE,
+        }
+    }
 }
 
-/// Result of encryption / decryption.
-pub type Result<T, E = Error> = core::result::Result<T, E>;
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
 
-/// Encrypt payload with password.
+impl From<Algorithm> for &'static str {
+    fn from(algorithm: Algorithm) -> Self {
+        algorithm.name()
+    }
+}
+
+/// Error indicating that algorithm name is unknown.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Unknown encryption algorithm `{0}`")]
+pub struct UnknownAlgorithmError(String);
+
+impl FromStr for Algorithm {
+    type Err = UnknownAlgorithmError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        [Self::Aes256Gcm, Self::XChaCha20Poly1305]
+            .into_iter()
+            .find(|algorithm| algorithm.name() == name)
+            .ok_or_else(|| UnknownAlgorithmError(name.to_owned()))
+    }
+}
+
+impl TryFrom<String> for Algorithm {
+    type Error = UnknownAlgorithmError;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+/// Encryption salt, which is a nonce of the [`Algorithm`] used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Salt {
+    /// Salt of [`Algorithm::Aes256Gcm`].
+    Aes256Gcm([u8; AES_256_GCM_SALT_SIZE]),
+    /// Salt of [`Algorithm::XChaCha20Poly1305`].
+    XChaCha20Poly1305([u8; XCHACHA20_POLY1305_SALT_SIZE]),
+}
+
+/// Error indicating that salt length doesn't match the algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Salt of {algorithm} must be {} bytes long, got {actual}", algorithm.salt_size())]
+pub struct WrongSaltLengthError {
+    /// Algorithm the salt was meant for.
+    pub algorithm: Algorithm,
+    /// Actual length of the salt.
+    pub actual: usize,
+}
+
+impl Salt {
+    /// Construct salt of `algorithm` from `bytes`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if length of `bytes` is not [`Algorithm::salt_size()`].
+    pub fn from_bytes(algorithm: Algorithm, bytes: &[u8]) -> Result<Self, WrongSaltLengthError> {
+        let error = WrongSaltLengthError {
+            algorithm,
+            actual: bytes.len(),
+        };
+        match algorithm {
+            Algorithm::Aes256Gcm => bytes.try_into().map(Self::Aes256Gcm),
+            Algorithm::XChaCha20Poly1305 => bytes.try_into().map(Self::XChaCha20Poly1305),
+        }
+        .map_err(|_err| error)
+    }
+
+    /// Get algorithm the salt is used by.
+    #[must_use]
+    pub const fn algorithm(&self) -> Algorithm {
+        match *self {
+            Self::Aes256Gcm(_) => Algorithm::Aes256Gcm,
+            Self::XChaCha20Poly1305(_) => Algorithm::XChaCha20Poly1305,
+        }
+    }
+
+    /// Get salt bytes.
+    #[must_use]
+    #[expect(
+        clippy::ref_patterns,
+        reason = "conflicts with `pattern_type_mismatch`"
+    )]
+    pub const fn as_bytes(&self) -> &[u8] {
+        match *self {
+            Self::Aes256Gcm(ref bytes) => bytes,
+            Self::XChaCha20Poly1305(ref bytes) => bytes,
+        }
+    }
+}
+
+/// Size of the key derivation salt in bytes.
+pub const KDF_SALT_SIZE: usize = 16;
+
+/// Random salt of the key derivation, unique for every encrypted payload.
+pub type KdfSalt = [u8; KDF_SALT_SIZE];
+
+/// Size of the key commitment in bytes.
+pub const KEY_COMMITMENT_SIZE: usize = 32;
+
+/// Value committing an encrypted payload to the key it was encrypted with.
 ///
-/// Not a pure feature, because it uses random number generator to generate a salt
+/// Allows to tell a wrong password from corrupted data.
+pub type KeyCommitment = [u8; KEY_COMMITMENT_SIZE];
+
+/// Size of the [`sign_export()`] tag in bytes.
+pub const EXPORT_TAG_SIZE: usize = 32;
+
+/// Tag authenticating an export bundle, see [`sign_export()`].
+pub type ExportTag = [u8; EXPORT_TAG_SIZE];
+
+/// Size of the [`name_lookup_tag()`] in bytes.
+pub const NAME_LOOKUP_TAG_SIZE: usize = 32;
+
+/// Resource name blinded with a key derived from the master password, see [`name_lookup_tag()`].
+pub type NameLookupTag = [u8; NAME_LOOKUP_TAG_SIZE];
+
+/// Size of the blind index token in bytes.
+pub const BLIND_TOKEN_SIZE: usize = 16;
+
+/// Minimal length of a keyword in characters, shorter words are not indexed.
+pub const MIN_KEYWORD_LENGTH: usize = 3;
+
+/// Maximum number of keywords indexed for a single record.
 ///
-/// # Errors
+/// Keeps new records small enough to be sent from the Web App.
+pub const MAX_INDEXED_KEYWORDS: usize = 32;
+
+/// Version of the [`BlindIndexKey`] derivation used before the master password was normalized.
 ///
-/// Any error from underlying libraries.
-#[cfg(feature = "impls")]
-pub fn encrypt(payload: &str, password: &str) -> Result<EncryptionOutput> {
-    let key = derive_key(password);
-    let cipher = Aes256Gcm::new(&key);
-    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+/// Raw master password bytes are used for key derivation.
+pub const BLIND_INDEX_VERSION_1: u8 = 1;
 
-    let encrypted_payload = cipher
-        .encrypt(&nonce, payload.as_bytes())
-        .map_err(|_err| Error::Encryption)?;
+/// Version of the [`BlindIndexKey`] derivation with the master password normalized to NFKC,
+/// so that tokens are the same whichever keyboard it was typed on.
+pub const BLIND_INDEX_VERSION_2: u8 = 2;
 
-    Ok(EncryptionOutput {
-        encrypted_payload,
-        salt: nonce.into(),
-    })
+/// Version of the [`BlindIndexKey`] derivation used by [`BlindIndexKey::derive()`].
+///
+/// Tokens of older versions can still be constructed with [`BlindIndexKey::derive_with_version()`].
+pub const LATEST_BLIND_INDEX_VERSION: u8 = BLIND_INDEX_VERSION_2;
+
+/// Keyword blinded with a key derived from the master password.
+///
+/// Allows to check if a record contains a keyword without revealing the keyword itself.
+pub type BlindToken = [u8; BLIND_TOKEN_SIZE];
+
+/// Size of the password fingerprint in bytes.
+pub const PASSWORD_FINGERPRINT_SIZE: usize = 8;
+
+/// Password blinded with a key derived from the master password.
+///
+/// Allows to find records sharing the same password without revealing the password itself.
+pub type PasswordFingerprint = [u8; PASSWORD_FINGERPRINT_SIZE];
+
+/// Words too common to be useful for search.
+const STOP_WORDS: [&str; 32] = [
+    "about", "all", "also", "and", "any", "are", "been", "but", "can", "for", "from", "had", "has",
+    "have", "her", "his", "into", "its", "not", "our", "she", "than", "that", "the", "them",
+    "then", "there", "they", "this", "was", "were", "with",
+];
+
+/// Split `text` into keywords suitable for the blind index.
+///
+/// Keywords are lowercase alphanumeric words of at least [`MIN_KEYWORD_LENGTH`] characters
+/// except common stop words. Every keyword is returned once in order of the first appearance.
+#[must_use]
+pub fn keywords(text: &str) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_KEYWORD_LENGTH)
+        .map(str::to_lowercase)
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .filter(|word| seen.insert(word.clone()))
+        .collect()
 }
 
-/// Decrypt password with password and salt.
+/// Default number of key derivation iterations.
 ///
-/// # Errors
+/// Payloads encrypted before the number of iterations was stored used exactly this value.
+pub const DEFAULT_KDF_ITERATIONS: u32 = 100_000;
+
+/// Bytes formatted with [`Debug`] as their length and a short fingerprint, e.g. `96 bytes
+/// #1a2b3c4d`.
 ///
-/// Any error from underlying libraries.
-#[cfg(feature = "impls")]
-pub fn decrypt(
-    EncryptionOutput {
-        encrypted_payload,
-        salt,
-    }: EncryptionOutput,
-    password: &str,
-) -> Result<String> {
-    let key = derive_key(password);
-    let cipher = Aes256Gcm::new(&key);
-    let nonce = Nonce::from(salt);
+/// Fingerprint tells apart different values in logs without revealing them.
+/// It's not cryptographic and is stable only within the same build.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct RedactedBytes<'bytes>(pub &'bytes [u8]);
+
+impl fmt::Debug for RedactedBytes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(self.0);
+        let fingerprint = hasher.finish() >> 32_u8;
+        write!(f, "{} bytes #{fingerprint:08x}", self.0.len())
+    }
+}
+
+/// Bytes formatted with [`Display`](fmt::Display) as lowercase hex, e.g. `1a2b3c4d`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HexBytes<'bytes>(pub &'bytes [u8]);
+
+impl fmt::Display for HexBytes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+/// Check if secrets `lhs` and `rhs` are equal in constant time.
+///
+/// Comparison time depends only on the lengths, so it doesn't tell how many leading bytes
+/// match. Secrets of different lengths are never equal.
+#[must_use]
+pub fn constant_time_eq(lhs: &[u8], rhs: &[u8]) -> bool {
+    lhs.ct_eq(rhs).into()
+}
+
+/// Check if secret strings `lhs` and `rhs` are equal in constant time,
+/// see [`constant_time_eq()`].
+#[must_use]
+pub fn constant_time_eq_str(lhs: &str, rhs: &str) -> bool {
+    constant_time_eq(lhs.as_bytes(), rhs.as_bytes())
+}
 
-    let payload = cipher
-        .decrypt(&nonce, encrypted_payload.as_slice())
-        .map_err(|_err| Error::Decryption)?;
+/// Size of the [`fingerprint()`] of an encryption output in bytes.
+pub const OUTPUT_FINGERPRINT_SIZE: usize = 8;
 
-    String::from_utf8(payload).map_err(Error::Utf8)
+/// Short checksum of an [`EncryptionOutput`], see [`fingerprint()`].
+pub type OutputFingerprint = [u8; OUTPUT_FINGERPRINT_SIZE];
+
+/// Version of the [`EncryptionOutput`] format produced before the version was stored.
+///
+/// Raw password bytes are used for key derivation.
+pub const OUTPUT_VERSION_1: u8 = 1;
+
+/// Version of the [`EncryptionOutput`] format with the password normalized to NFKC before key
+/// derivation, so that it's the same whichever keyboard it was typed on.
+pub const OUTPUT_VERSION_2: u8 = 2;
+
+/// Version of the [`EncryptionOutput`] format produced by encryption.
+///
+/// Outputs of older versions can be upgraded with [`migrate()`].
+pub const LATEST_OUTPUT_VERSION: u8 = OUTPUT_VERSION_2;
+
+/// Output of encryption.
+///
+/// Serialized with the algorithm name next to the salt bytes.
+/// [`Debug`] output doesn't contain any bytes, see [`RedactedBytes`].
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "RawEncryptionOutput", try_from = "RawEncryptionOutput")]
+pub struct EncryptionOutput {
+    /// Version of the format, defines how the payload is decrypted.
+    ///
+    /// Outputs without a stored version are of [`OUTPUT_VERSION_1`].
+    pub version: u8,
+    /// Payload encrypted with a password.
+    pub encrypted_payload: Vec<u8>,
+    /// Salt used for encryption, also defines the algorithm.
+    pub salt: Salt,
+    /// Number of key derivation iterations used for encryption.
+    pub kdf_iterations: u32,
+    /// Salt used for key derivation.
+    ///
+    /// [`None`] for payloads encrypted before it was random, they use a legacy constant salt.
+    pub kdf_salt: Option<KdfSalt>,
+    /// Commitment to the key used for encryption.
+    ///
+    /// [`None`] for payloads encrypted before it was stored, a wrong password can't be told
+    /// from corrupted data for them.
+    pub key_commitment: Option<KeyCommitment>,
+}
+
+impl fmt::Debug for EncryptionOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptionOutput")
+            .field("version", &self.version)
+            .field("encrypted_payload", &RedactedBytes(&self.encrypted_payload))
+            .field("salt", &format_args!("<redacted>"))
+            .field("algorithm", &self.algorithm())
+            .field("kdf_iterations", &self.kdf_iterations)
+            .field(
+                "kdf_salt",
+                &self
+                    .kdf_salt
+                    .as_ref()
+                    .map(|_kdf_salt| format_args!("<redacted>")),
+            )
+            .field(
+                "key_commitment",
+                &self
+                    .key_commitment
+                    .as_ref()
+                    .map(|_key_commitment| format_args!("<redacted>")),
+            )
+            .finish()
+    }
+}
+
+impl EncryptionOutput {
+    /// Get algorithm used for encryption.
+    #[must_use]
+    pub const fn algorithm(&self) -> Algorithm {
+        self.salt.algorithm()
+    }
+
+    /// Encode output to pass it in url query parameters.
+    #[must_use]
+    pub fn to_url_query(&self) -> UrlQuery {
+        let algorithm = self.algorithm();
+        UrlQuery {
+            payload: URL_SAFE.encode(&self.encrypted_payload),
+            salt: URL_SAFE.encode(self.salt.as_bytes()),
+            kdf_iterations: Some(self.kdf_iterations),
+            kdf_salt: self.kdf_salt.map(|kdf_salt| URL_SAFE.encode(kdf_salt)),
+            algorithm: (algorithm != Algorithm::default()).then(|| algorithm.name().to_owned()),
+            key_commitment: self
+                .key_commitment
+                .map(|key_commitment| URL_SAFE.encode(key_commitment)),
+            version: (self.version != OUTPUT_VERSION_1).then_some(self.version),
+        }
+    }
+
+    /// Decode output from url `query` parameters.
+    ///
+    /// Zero or missing `kdf_iterations` mean [`DEFAULT_KDF_ITERATIONS`], empty or missing
+    /// `kdf_salt` means the legacy constant salt, empty or missing `algorithm` means the
+    /// default one, empty or missing `key_commitment` means there is none and zero or missing
+    /// `version` means [`OUTPUT_VERSION_1`], as in links built before these parameters were added.
+    ///
+    /// # Errors
+    ///
+    /// Fails if parameters are not valid base64, algorithm is unknown or salts or key commitment
+    /// are of wrong length.
+    pub fn from_url_query(query: &UrlQuery) -> Result<Self, UrlQueryError> {
+        let algorithm = query
+            .algorithm
+            .as_deref()
+            .filter(|name| !name.is_empty())
+            .map(str::parse)
+            .transpose()?
+            .unwrap_or_default();
+        let salt = Salt::from_bytes(algorithm, &URL_SAFE.decode(&query.salt)?)?;
+        let kdf_salt = query
+            .kdf_salt
+            .as_deref()
+            .filter(|encoded| !encoded.is_empty())
+            .map(|encoded| {
+                URL_SAFE
+                    .decode(encoded)?
+                    .try_into()
+                    .map_err(|bytes: Vec<u8>| UrlQueryError::WrongKdfSaltLength(bytes.len()))
+            })
+            .transpose()?;
+        let key_commitment = query
+            .key_commitment
+            .as_deref()
+            .filter(|encoded| !encoded.is_empty())
+            .map(|encoded| {
+                URL_SAFE
+                    .decode(encoded)?
+                    .try_into()
+                    .map_err(|bytes: Vec<u8>| UrlQueryError::WrongKeyCommitmentLength(bytes.len()))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            version: query
+                .version
+                .filter(|version| *version != 0)
+                .unwrap_or(OUTPUT_VERSION_1),
+            encrypted_payload: URL_SAFE.decode(&query.payload)?,
+            salt,
+            kdf_iterations: query
+                .kdf_iterations
+                .filter(|iterations| *iterations != 0)
+                .unwrap_or(DEFAULT_KDF_ITERATIONS),
+            kdf_salt,
+            key_commitment,
+        })
+    }
 }
 
-/// Construct encryption key from string password.
+/// Compute fingerprint of `output` to detect identical outputs and verify them by eye.
+///
+/// SHA-256 over the payload length, the encrypted payload, the salt and the version, truncated
+/// to [`OUTPUT_FINGERPRINT_SIZE`]. Depends only on these values, so it's stable across
+/// serialization formats. Render it with [`HexBytes`].
+///
+/// The fingerprint is not secret: it's computed from the ciphertext only and tells nothing
+/// about the password or the plaintext. It's not a MAC either, anyone can forge it.
 #[cfg(feature = "impls")]
-fn derive_key(password: &str) -> Key<Aes256Gcm> {
-    /// Salt to be used for key derivation
-    const KEY_DERIVATION_SALT: &[u8] = b"telepass_key_derivation_salt";
-    /// Number of hashing rounds
-    const KEY_DERIVATION_ITERATION_ROUNDS: u32 = 100_000;
-    /// Size of the key in bytes
-    const KEY_SIZE: usize = <<Aes256Gcm as KeySizeUser>::KeySize as Unsigned>::USIZE;
+#[must_use]
+#[expect(clippy::big_endian_bytes, reason = "length prefix is big-endian")]
+pub fn fingerprint(output: &EncryptionOutput) -> OutputFingerprint {
+    use sha2::Digest as _;
 
     /// Health check
     const _: () = assert!(
-        KEY_SIZE == <<Sha256 as OutputSizeUser>::OutputSize as Unsigned>::USIZE,
-        "AES 256 GCM and SHA 256 key size mismatch"
+        OUTPUT_FINGERPRINT_SIZE <= <<Sha256 as OutputSizeUser>::OutputSize as Unsigned>::USIZE,
+        "Output fingerprint is longer than SHA 256 output"
     );
 
-    let key = pbkdf2_hmac_array::<sha2::Sha256, KEY_SIZE>(
-        password.as_bytes(),
-        KEY_DERIVATION_SALT,
-        KEY_DERIVATION_ITERATION_ROUNDS,
-    );
-    key.into()
+    // Length prefix makes the boundary between the payload and salts of different sizes unambiguous
+    let payload_length = u64::try_from(output.encrypted_payload.len()).unwrap_or(u64::MAX);
+    let digest = Sha256::new()
+        .chain_update(payload_length.to_be_bytes())
+        .chain_update(&output.encrypted_payload)
+        .chain_update(output.salt.as_bytes())
+        .chain_update([output.version])
+        .finalize();
+
+    let mut fingerprint = [0; OUTPUT_FINGERPRINT_SIZE];
+    for (fingerprint_byte, digest_byte) in fingerprint.iter_mut().zip(digest) {
+        *fingerprint_byte = digest_byte;
+    }
+    fingerprint
 }
 
-#[cfg(test)]
-#[cfg(feature = "impls")]
-mod tests {
-    #![expect(clippy::expect_used, reason = "it's ok in tests")]
+/// [`EncryptionOutput`] encoded to be passed in url query parameters.
+///
+/// Bytes are encoded with URL-safe base64.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UrlQuery {
+    /// Encrypted payload.
+    pub payload: String,
+    /// Salt used for encryption.
+    pub salt: String,
+    /// Number of key derivation iterations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kdf_iterations: Option<u32>,
+    /// Salt used for key derivation, omitted for the legacy constant one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kdf_salt: Option<String>,
+    /// Name of the encryption algorithm, omitted for the default one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<String>,
+    /// Commitment to the encryption key, omitted if there is none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_commitment: Option<String>,
+    /// Version of the output, omitted for [`OUTPUT_VERSION_1`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u8>,
+}
 
-    use super::*;
+impl UrlQuery {
+    /// Get present parameters as name-value pairs.
+    #[must_use]
+    pub fn pairs(&self) -> Vec<(&'static str, String)> {
+        [
+            ("payload", Some(self.payload.clone())),
+            ("salt", Some(self.salt.clone())),
+            (
+                "kdf_iterations",
+                self.kdf_iterations.map(|iterations| iterations.to_string()),
+            ),
+            ("kdf_salt", self.kdf_salt.clone()),
+            ("algorithm", self.algorithm.clone()),
+            ("key_commitment", self.key_commitment.clone()),
+            ("version", self.version.map(|version| version.to_string())),
+        ]
+        .into_iter()
+        .filter_map(|(name, param)| param.map(|value| (name, value)))
+        .collect()
+    }
+}
 
-    #[test]
-    fn encrypt_and_decrypt_work() {
-        let payload = "payload";
-        let password = "password";
+/// Error decoding [`EncryptionOutput`] from [`UrlQuery`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum UrlQueryError {
+    #[error("Failed to decode base64 parameter")]
+    Base64(#[from] base64::DecodeError),
+    #[error(transparent)]
+    UnknownAlgorithm(#[from] UnknownAlgorithmError),
+    #[error(transparent)]
+    WrongSaltLength(#[from] WrongSaltLengthError),
+    #[error("Key derivation salt must be {KDF_SALT_SIZE} bytes long, got {0}")]
+    WrongKdfSaltLength(usize),
+    #[error("Key commitment must be {KEY_COMMITMENT_SIZE} bytes long, got {0}")]
+    WrongKeyCommitmentLength(usize),
+}
 
-        let output = encrypt(payload, password).expect("Failed to encrypt payload");
-        let decrypted_payload = decrypt(output, password).expect("Failed to decrypt payload");
+/// Serialized form of [`EncryptionOutput`].
+///
+/// Fields missing in outputs of older versions fall back to the values used back then.
+#[derive(Serialize, Deserialize)]
+struct RawEncryptionOutput {
+    /// Version of the format.
+    #[serde(default = "default_version")]
+    version: u8,
+    /// Payload encrypted with a password.
+    #[serde(with = "bytes")]
+    encrypted_payload: Vec<u8>,
+    /// Algorithm used for encryption.
+    #[serde(default)]
+    algorithm: Algorithm,
+    /// Salt used for encryption.
+    #[serde(with = "bytes")]
+    salt: Vec<u8>,
+    /// Number of key derivation iterations used for encryption.
+    #[serde(default = "default_kdf_iterations")]
+    kdf_iterations: u32,
+    /// Salt used for key derivation.
+    #[serde(default, with = "optional_array")]
+    kdf_salt: Option<KdfSalt>,
+    /// Commitment to the key used for encryption.
+    #[serde(default, with = "optional_array")]
+    key_commitment: Option<KeyCommitment>,
+}
 
-        assert_eq!(payload, decrypted_payload);
+impl From<EncryptionOutput> for RawEncryptionOutput {
+    fn from(output: EncryptionOutput) -> Self {
+        Self {
+            version: output.version,
+            encrypted_payload: output.encrypted_payload,
+            algorithm: output.salt.algorithm(),
+            salt: output.salt.as_bytes().to_vec(),
+            kdf_iterations: output.kdf_iterations,
+            kdf_salt: output.kdf_salt,
+            key_commitment: output.key_commitment,
+        }
     }
+}
 
-    #[test]
-    fn encrypt_same_payload_with_different_passwords_gives_different_results() {
-        let payload = "payload";
+impl TryFrom<RawEncryptionOutput> for EncryptionOutput {
+    type Error = WrongSaltLengthError;
 
-        let first_output =
-            encrypt(payload, "password1").expect("Failed to encrypt payload first time");
-        let second_output =
-            encrypt(payload, "password2").expect("Failed to encrypt payload second time");
+    fn try_from(raw: RawEncryptionOutput) -> Result<Self, Self::Error> {
+        Ok(Self {
+            version: raw.version,
+            encrypted_payload: raw.encrypted_payload,
+            salt: Salt::from_bytes(raw.algorithm, &raw.salt)?,
+            kdf_iterations: raw.kdf_iterations,
+            kdf_salt: raw.kdf_salt,
+            key_commitment: raw.key_commitment,
+        })
+    }
+}
 
-        assert_ne!(
-            first_output.encrypted_payload,
-            second_output.encrypted_payload
-        );
-        assert_ne!(first_output.salt, second_output.salt);
+/// Serialization of byte fields of [`RawEncryptionOutput`].
+///
+/// Human-readable formats get URL-safe base64 without padding, because JSON arrays of numbers
+/// quickly exceed the size limit of data sent by the Web App. Binary formats keep raw bytes.
+mod bytes {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use serde::{de, Deserializer, Serializer};
+
+    /// Serialize `bytes` as base64 string or as raw bytes depending on the format.
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&URL_SAFE_NO_PAD.encode(bytes))
+        } else {
+            serializer.serialize_bytes(bytes)
+        }
     }
 
-    #[test]
-    fn encrypt_same_payload_twice_gives_different_results() {
-        let payload = "payload";
-        let password = "password";
+    /// Deserialize bytes serialized with [`serialize()`].
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(BytesVisitor)
+        } else {
+            deserializer.deserialize_byte_buf(BytesVisitor)
+        }
+    }
 
-        let first_output =
-            encrypt(payload, password).expect("Failed to encrypt payload first time");
-        let second_output =
-            encrypt(payload, password).expect("Failed to encrypt payload second time");
+    /// Visitor accepting base64 strings, raw bytes and arrays of numbers.
+    struct BytesVisitor;
 
-        assert_ne!(
-            first_output.encrypted_payload,
-            second_output.encrypted_payload
-        );
-        assert_ne!(first_output.salt, second_output.salt);
+    impl<'de> de::Visitor<'de> for BytesVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            formatter.write_str("base64 string or bytes")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            URL_SAFE_NO_PAD.decode(v).map_err(E::custom)
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            Ok(v.to_vec())
+        }
+
+        fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+            Ok(v)
+        }
+
+        // TODO: Remove after the next release, outputs serialized to JSON before contain arrays.
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+            while let Some(byte) = seq.next_element()? {
+                bytes.push(byte);
+            }
+            Ok(bytes)
+        }
     }
+}
 
-    #[test]
-    fn decrypt_with_wrong_password_fails() {
-        let payload = "payload";
+/// Serialization of optional fixed-size byte fields of [`RawEncryptionOutput`].
+///
+/// Human-readable formats get base64 like [`bytes`], but arrays of numbers serialized before
+/// are still accepted. Binary formats keep arrays, so that stored outputs stay readable.
+mod optional_array {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
-        let output = encrypt(payload, "password1").expect("Failed to encrypt payload");
-        decrypt(output, "password2").expect_err("Decryption is expected to fail");
+    /// Serialize `array` as optional base64 string or as optional array depending on the format.
+    pub fn serialize<S: Serializer, const N: usize>(
+        array: &Option<[u8; N]>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        [u8; N]: Serialize,
+    {
+        if serializer.is_human_readable() {
+            array
+                .map(|bytes| URL_SAFE_NO_PAD.encode(bytes))
+                .serialize(serializer)
+        } else {
+            array.serialize(serializer)
+        }
     }
 
-    #[test]
-    #[expect(clippy::indexing_slicing, reason = "it's ok in tests")]
-    fn decrypt_with_wrong_payload_fails() {
-        let payload = "payload";
-        let password = "password";
+    /// Deserialize array serialized with [`serialize()`].
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<Option<[u8; N]>, D::Error>
+    where
+        [u8; N]: Deserialize<'de>,
+    {
+        if !deserializer.is_human_readable() {
+            return Option::<[u8; N]>::deserialize(deserializer);
+        }
 
-        let mut output = encrypt(payload, password).expect("Failed to encrypt payload");
-        output.encrypted_payload[0] = !output.encrypted_payload[0];
-        decrypt(output, password).expect_err("Decryption is expected to fail");
+        Option::<Bytes>::deserialize(deserializer)?
+            .map(|Bytes(bytes)| {
+                let len = bytes.len();
+                <[u8; N]>::try_from(bytes)
+                    .map_err(|_bytes| de::Error::invalid_length(len, &"array of the fixed size"))
+            })
+            .transpose()
     }
 
-    #[test]
-    fn decrypt_with_wrong_salt_fails() {
-        let payload = "payload";
-        let password = "password";
+    /// Bytes deserialized with [`super::bytes`].
+    struct Bytes(Vec<u8>);
 
-        let mut output = encrypt(payload, password).expect("Failed to encrypt payload");
-        output.salt[0] = !output.salt[0];
-        decrypt(output, password).expect_err("Decryption is expected to fail");
+    impl<'de> Deserialize<'de> for Bytes {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            super::bytes::deserialize(deserializer).map(Self)
+        }
+    }
+}
+
+/// Get [`OUTPUT_VERSION_1`] for `serde`, outputs without a version are of it.
+const fn default_version() -> u8 {
+    OUTPUT_VERSION_1
+}
+
+/// Get [`DEFAULT_KDF_ITERATIONS`] for `serde`.
+const fn default_kdf_iterations() -> u32 {
+    DEFAULT_KDF_ITERATIONS
+}
+
+/// Parameters of encryption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncryptParams {
+    /// Number of key derivation iterations.
+    ///
+    /// More iterations make brute-forcing the password slower, but encryption and decryption
+    /// become slower as well.
+    pub kdf_iterations: u32,
+    /// Encryption algorithm.
+    pub algorithm: Algorithm,
+    /// Pad text payloads to a multiple of this number of bytes, so that the output length
+    /// doesn't reveal the exact text length, e.g. of a password.
+    ///
+    /// [`None`] disables padding. Padding is stripped by [`decrypt()`] transparently.
+    /// Arbitrary bytes of [`encrypt_bytes()`] are never padded, because [`decrypt_bytes()`]
+    /// can't tell padding from data.
+    pub pad_to: Option<usize>,
+}
+
+impl Default for EncryptParams {
+    fn default() -> Self {
+        Self {
+            kdf_iterations: DEFAULT_KDF_ITERATIONS,
+            algorithm: Algorithm::default(),
+            pad_to: None,
+        }
+    }
+}
+
+/// Encryption / decryption error.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Error {
+    #[error("Failed to encrypt payload")]
+    Encryption,
+    #[error("Failed to decrypt payload, either password is wrong or data is corrupted")]
+    Decryption,
+    #[error("Wrong password")]
+    WrongPassword,
+    #[error("Encrypted data is corrupted")]
+    CorruptedData,
+    #[error("Failed to parse decrypted payload as UTF-8")]
+    Utf8(FromUtf8Error),
+    #[error("Number of key derivation iterations must be positive")]
+    ZeroKdfIterations,
+    #[error("Encryption output version {0} is not supported, latest supported is {LATEST_OUTPUT_VERSION}")]
+    UnsupportedVersion(u8),
+    #[error("Blind index version {0} is not supported, latest supported is {LATEST_BLIND_INDEX_VERSION}")]
+    UnsupportedBlindIndexVersion(u8),
+    #[error("Export bundle is tampered with or password is wrong")]
+    TamperedExport,
+    #[error("Key is derived with other parameters than the encrypted payload")]
+    KeyMismatch,
+    #[error("Ciphertext is malformed: {reason}")]
+    MalformedCiphertext { reason: MalformedCiphertextReason },
+}
+
+/// Reason of [`Error::MalformedCiphertext`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum MalformedCiphertextReason {
+    #[error("it's empty")]
+    Empty,
+    #[error("its {0} bytes are shorter than the authentication tag of {AEAD_TAG_SIZE} bytes")]
+    ShorterThanTag(usize),
+    #[error("its {size} bytes exceed the maximum of {max_size} bytes")]
+    TooLarge { size: usize, max_size: usize },
+}
+
+/// Check that `encrypted_payload` can be a ciphertext of at most `max_size` bytes.
+///
+/// Done before decryption, so that malformed ciphertexts are reported as such rather than
+/// as a wrong password. Salt length needs no check, it's enforced by [`Salt`] itself.
+///
+/// # Errors
+///
+/// Fails with [`Error::MalformedCiphertext`] if `encrypted_payload` is empty, shorter than
+/// [`AEAD_TAG_SIZE`] or larger than `max_size`.
+pub const fn validate_ciphertext(encrypted_payload: &[u8], max_size: usize) -> Result<()> {
+    let size = encrypted_payload.len();
+    let reason = if size == 0 {
+        MalformedCiphertextReason::Empty
+    } else if size < AEAD_TAG_SIZE {
+        MalformedCiphertextReason::ShorterThanTag(size)
+    } else if size > max_size {
+        MalformedCiphertextReason::TooLarge { size, max_size }
+    } else {
+        return Ok(());
+    };
+    Err(Error::MalformedCiphertext { reason })
+}
+
+/// Result of encryption / decryption.
+pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+/// Encrypt payload with password.
+///
+/// Uses [`EncryptParams::default()`] if `params` are not provided.
+/// Same as [`encrypt_with_aad()`] with empty associated data.
+///
+/// Not a pure feature, because it uses random number generator to generate salts
+///
+/// # Errors
+///
+/// - [`Error::ZeroKdfIterations`] if `params` have zero key derivation iterations;
+/// - Any error from underlying libraries.
+#[cfg(feature = "impls")]
+pub fn encrypt(
+    payload: &str,
+    password: &str,
+    params: Option<EncryptParams>,
+) -> Result<EncryptionOutput> {
+    encrypt_with_aad(payload, password, params, &[])
+}
+
+/// Same as [`encrypt()`], but takes salts from `rng` instead of the operating system.
+///
+/// Allows to produce reproducible outputs with a seeded `rng`, e.g. in tests.
+///
+/// # Errors
+///
+/// See [`encrypt()`].
+#[cfg(feature = "impls")]
+pub fn encrypt_with_rng<R: CryptoRng + RngCore>(
+    payload: &str,
+    password: &str,
+    params: Option<EncryptParams>,
+    rng: &mut R,
+) -> Result<EncryptionOutput> {
+    let payload = pad_text(
+        payload,
+        params.and_then(|encrypt_params| encrypt_params.pad_to),
+    );
+    encrypt_bytes_with_rng(&payload, password, params, &[], rng)
+}
+
+/// Encrypt payload with password binding it to `aad`.
+///
+/// Associated data is authenticated, but neither encrypted nor stored in the output,
+/// so the same `aad` must be passed to [`decrypt_with_aad()`].
+///
+/// # Errors
+///
+/// See [`encrypt()`].
+#[cfg(feature = "impls")]
+pub fn encrypt_with_aad(
+    payload: &str,
+    password: &str,
+    params: Option<EncryptParams>,
+    aad: &[u8],
+) -> Result<EncryptionOutput> {
+    let payload = pad_text(
+        payload,
+        params.and_then(|encrypt_params| encrypt_params.pad_to),
+    );
+    encrypt_bytes(&payload, password, params, aad)
+}
+
+/// Same as [`encrypt_with_aad()`], but for arbitrary bytes like file contents.
+///
+/// Decrypt the output with [`decrypt_bytes()`].
+///
+/// # Errors
+///
+/// See [`encrypt()`].
+#[cfg(feature = "impls")]
+pub fn encrypt_bytes(
+    payload: &[u8],
+    password: &str,
+    params: Option<EncryptParams>,
+    aad: &[u8],
+) -> Result<EncryptionOutput> {
+    encrypt_bytes_with_rng(payload, password, params, aad, &mut OsRng)
+}
+
+/// [`encrypt_bytes()`] implementation taking salts from `rng`.
+#[cfg(feature = "impls")]
+fn encrypt_bytes_with_rng<R: CryptoRng + RngCore>(
+    payload: &[u8],
+    password: &str,
+    params: Option<EncryptParams>,
+    aad: &[u8],
+    rng: &mut R,
+) -> Result<EncryptionOutput> {
+    let EncryptParams {
+        kdf_iterations,
+        algorithm,
+        ..
+    } = params.unwrap_or_default();
+    let mut kdf_salt = KdfSalt::default();
+    rng.fill_bytes(&mut kdf_salt);
+
+    let key = Zeroizing::new(derive_key(
+        password,
+        LATEST_OUTPUT_VERSION,
+        Some(&kdf_salt),
+        kdf_iterations,
+    )?);
+    seal_with_key(&key, payload, kdf_salt, kdf_iterations, algorithm, aad, rng)
+}
+
+/// Encrypt `payload` bound to `aad` with `key` derived with `kdf_salt` and `kdf_iterations`
+/// taking nonce from `rng`.
+#[cfg(feature = "impls")]
+fn seal_with_key<R: CryptoRng + RngCore>(
+    key: &Key,
+    payload: &[u8],
+    kdf_salt: KdfSalt,
+    kdf_iterations: u32,
+    algorithm: Algorithm,
+    aad: &[u8],
+    rng: &mut R,
+) -> Result<EncryptionOutput> {
+    let (encrypted_payload, salt) = match algorithm {
+        Algorithm::Aes256Gcm => {
+            let (encrypted_payload, nonce) = seal::<Aes256Gcm, _>(key, payload, aad, rng)?;
+            (encrypted_payload, Salt::Aes256Gcm(nonce.into()))
+        }
+        Algorithm::XChaCha20Poly1305 => {
+            let (encrypted_payload, nonce) = seal::<XChaCha20Poly1305, _>(key, payload, aad, rng)?;
+            (encrypted_payload, Salt::XChaCha20Poly1305(nonce.into()))
+        }
+    };
+
+    Ok(EncryptionOutput {
+        version: LATEST_OUTPUT_VERSION,
+        encrypted_payload,
+        salt,
+        kdf_iterations,
+        kdf_salt: Some(kdf_salt),
+        key_commitment: Some(commit_to_key(key)?),
+    })
+}
+
+/// Decrypt password with password, salts and number of key derivation iterations.
+///
+/// Same as [`decrypt_with_aad()`] with empty associated data.
+///
+/// # Errors
+///
+/// - [`Error::UnsupportedVersion`] if the output is of a newer version than
+///   [`LATEST_OUTPUT_VERSION`];
+/// - [`Error::ZeroKdfIterations`] if `kdf_iterations` is zero;
+/// - [`Error::MalformedCiphertext`] if `encrypted_payload` is empty, shorter than [`AEAD_TAG_SIZE`]
+///   or larger than [`DEFAULT_MAX_CIPHERTEXT_SIZE`];
+/// - [`Error::WrongPassword`] if `password` doesn't match the key commitment;
+/// - [`Error::CorruptedData`] if the output with a key commitment fails to decrypt;
+/// - [`Error::Decryption`] if the output without a key commitment fails to decrypt;
+/// - Any error from underlying libraries.
+#[cfg(feature = "impls")]
+pub fn decrypt(output: EncryptionOutput, password: &str) -> Result<String> {
+    decrypt_with_aad(output, password, &[])
+}
+
+/// Decrypt password with password, salts, number of key derivation iterations and `aad`
+/// the payload was bound to.
+///
+/// # Errors
+///
+/// - [`Error::CorruptedData`] or [`Error::Decryption`] if `aad` differs from the one used for
+///   encryption;
+/// - See [`decrypt()`] for other errors.
+#[cfg(feature = "impls")]
+pub fn decrypt_with_aad(output: EncryptionOutput, password: &str, aad: &[u8]) -> Result<String> {
+    decode_text(decrypt_bytes(output, password, aad)?)
+}
+
+/// Same as [`encrypt()`], but runs on the blocking thread pool of `tokio`.
+///
+/// Key derivation is pure CPU work taking a noticeable time, so calling [`encrypt()`] inline
+/// stalls the async executor.
+///
+/// # Errors
+///
+/// See [`encrypt()`].
+///
+/// # Panics
+///
+/// Resumes the panic of [`encrypt()`] if any.
+#[cfg(feature = "tokio")]
+pub async fn encrypt_async(
+    payload: &str,
+    password: &str,
+    params: Option<EncryptParams>,
+) -> Result<EncryptionOutput> {
+    let payload = Zeroizing::new(payload.to_owned());
+    let password = Zeroizing::new(password.to_owned());
+    run_blocking(move || encrypt(&payload, &password, params)).await
+}
+
+/// Same as [`decrypt()`], but runs on the blocking thread pool of `tokio`.
+///
+/// See [`encrypt_async()`] for the rationale.
+///
+/// # Errors
+///
+/// See [`decrypt()`].
+///
+/// # Panics
+///
+/// Resumes the panic of [`decrypt()`] if any.
+#[cfg(feature = "tokio")]
+pub async fn decrypt_async(output: EncryptionOutput, password: &str) -> Result<String> {
+    let password = Zeroizing::new(password.to_owned());
+    run_blocking(move || decrypt(output, &password)).await
+}
+
+/// Run `f` with [`tokio::task::spawn_blocking()`] resuming its panic if any.
+#[cfg(feature = "tokio")]
+async fn run_blocking<T, F>(f: F) -> T
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(value) => value,
+        Err(error) => std::panic::resume_unwind(error.into_panic()),
+    }
+}
+
+/// Same as [`decrypt_with_aad()`], but returns raw bytes, e.g. of [`encrypt_bytes()`] output.
+///
+/// # Errors
+///
+/// See [`decrypt_with_aad()`], except that there are no UTF-8 errors.
+#[cfg(feature = "impls")]
+pub fn decrypt_bytes(output: EncryptionOutput, password: &str, aad: &[u8]) -> Result<Vec<u8>> {
+    decrypt_bytes_with_max_size(output, password, aad, DEFAULT_MAX_CIPHERTEXT_SIZE)
+}
+
+/// Same as [`decrypt_bytes()`], but accepts ciphertexts of up to `max_size` bytes instead of
+/// [`DEFAULT_MAX_CIPHERTEXT_SIZE`].
+///
+/// # Errors
+///
+/// See [`decrypt_bytes()`].
+#[cfg(feature = "impls")]
+pub fn decrypt_bytes_with_max_size(
+    EncryptionOutput {
+        version,
+        encrypted_payload,
+        salt,
+        kdf_iterations,
+        kdf_salt,
+        key_commitment,
+    }: EncryptionOutput,
+    password: &str,
+    aad: &[u8],
+    max_size: usize,
+) -> Result<Vec<u8>> {
+    validate_ciphertext(&encrypted_payload, max_size)?;
+    let key = Zeroizing::new(derive_key(
+        password,
+        version,
+        kdf_salt.as_ref(),
+        kdf_iterations,
+    )?);
+    open_with_key(&key, key_commitment.as_ref(), salt, &encrypted_payload, aad)
+}
+
+/// Decrypt `encrypted_payload` bound to `aad` with `key` checked against `key_commitment`
+/// and `salt`.
+#[cfg(feature = "impls")]
+fn open_with_key(
+    key: &Key,
+    key_commitment: Option<&KeyCommitment>,
+    salt: Salt,
+    encrypted_payload: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>> {
+    let failure = verify_key(key, key_commitment)?;
+    match salt {
+        Salt::Aes256Gcm(nonce) => open::<Aes256Gcm>(key, &nonce.into(), encrypted_payload, aad),
+        Salt::XChaCha20Poly1305(nonce) => {
+            open::<XChaCha20Poly1305>(key, &nonce.into(), encrypted_payload, aad)
+        }
+    }
+    .map_err(|_err| failure)
+}
+
+/// Label of the key commitment, so that it differs from any other MAC made with the key.
+#[cfg(feature = "impls")]
+const KEY_COMMITMENT_LABEL: &[u8] = b"telepass_key_commitment";
+
+/// Commit to `key`.
+///
+/// Commitment is an HMAC of a constant label keyed with `key`, so it reveals nothing about the
+/// key, and checking a password against it still requires the full key derivation.
+#[cfg(feature = "impls")]
+fn commit_to_key(key: &Key) -> Result<KeyCommitment> {
+    Ok(<Hmac<Sha256> as KeyInit>::new_from_slice(key)
+        .map_err(|_err| Error::Encryption)?
+        .chain_update(KEY_COMMITMENT_LABEL)
+        .finalize()
+        .into_bytes()
+        .into())
+}
+
+/// Check `key` against `key_commitment`.
+///
+/// Returns the error to report if decryption with the checked key fails:
+/// [`Error::CorruptedData`] if there is a commitment, [`Error::Decryption`] otherwise.
+///
+/// # Errors
+///
+/// [`Error::WrongPassword`] if `key` doesn't match `key_commitment`.
+#[cfg(feature = "impls")]
+fn verify_key(key: &Key, key_commitment: Option<&KeyCommitment>) -> Result<Error> {
+    let Some(key_commitment) = key_commitment else {
+        return Ok(Error::Decryption);
+    };
+    <Hmac<Sha256> as KeyInit>::new_from_slice(key)
+        .map_err(|_err| Error::Decryption)?
+        .chain_update(KEY_COMMITMENT_LABEL)
+        .verify_slice(key_commitment)
+        .map_err(|_err| Error::WrongPassword)?;
+    Ok(Error::CorruptedData)
+}
+
+/// Re-encrypt payload encrypted with `old_password` with `new_password`.
+///
+/// Same as [`reencrypt_with_aad()`] with empty associated data.
+///
+/// # Errors
+///
+/// See [`reencrypt_with_aad()`].
+#[cfg(feature = "impls")]
+pub fn reencrypt(
+    output: EncryptionOutput,
+    old_password: &str,
+    new_password: &str,
+) -> Result<EncryptionOutput> {
+    reencrypt_with_aad(output, old_password, new_password, &[])
+}
+
+/// Re-encrypt payload bound to `aad` with `new_password` without exposing it to the caller.
+///
+/// New output is of [`LATEST_OUTPUT_VERSION`] and uses the same algorithm and number of key
+/// derivation iterations, but fresh salts, so legacy outputs get a random key derivation salt
+/// as well.
+///
+/// # Errors
+///
+/// - See [`decrypt_with_aad()`] for errors with wrong `old_password` or `aad`;
+/// - See [`encrypt()`] for other errors.
+#[cfg(feature = "impls")]
+pub fn reencrypt_with_aad(
+    output: EncryptionOutput,
+    old_password: &str,
+    new_password: &str,
+    aad: &[u8],
+) -> Result<EncryptionOutput> {
+    // Padding of text payloads is kept as is
+    let params = EncryptParams {
+        kdf_iterations: output.kdf_iterations,
+        algorithm: output.algorithm(),
+        pad_to: None,
+    };
+    let payload = Zeroizing::new(decrypt_bytes(output, old_password, aad)?);
+
+    encrypt_bytes(&payload, new_password, Some(params), aad)
+}
+
+/// Upgrade output encrypted with `password` to [`LATEST_OUTPUT_VERSION`].
+///
+/// Same as [`migrate_with_aad()`] with empty associated data.
+///
+/// # Errors
+///
+/// See [`migrate_with_aad()`].
+#[cfg(feature = "impls")]
+pub fn migrate(output: EncryptionOutput, password: &str) -> Result<EncryptionOutput> {
+    migrate_with_aad(output, password, &[])
+}
+
+/// Upgrade output bound to `aad` to [`LATEST_OUTPUT_VERSION`] re-encrypting it with the same
+/// `password`.
+///
+/// Algorithm and number of key derivation iterations are kept, salts are fresh,
+/// so legacy outputs get a random key derivation salt as well.
+///
+/// # Errors
+///
+/// - See [`decrypt_with_aad()`] for errors with wrong `password` or `aad`;
+/// - See [`encrypt()`] for other errors.
+#[cfg(feature = "impls")]
+pub fn migrate_with_aad(
+    output: EncryptionOutput,
+    password: &str,
+    aad: &[u8],
+) -> Result<EncryptionOutput> {
+    reencrypt_with_aad(output, password, password, aad)
+}
+
+/// Record with both resource name and payload encrypted, see [`encrypt_record()`].
+///
+/// Payload is bound to the name, so that it fails to decrypt with a name of another record.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EncryptedRecord {
+    /// Encrypted resource name.
+    pub name: EncryptionOutput,
+    /// Encrypted payload bound to the resource name.
+    pub payload: EncryptionOutput,
+}
+
+/// Encrypt resource `name` and `payload` with password deriving the key only once.
+///
+/// Both outputs share the key derivation salt, but have their own nonces.
+/// Use [`name_lookup_tag()`] to find the record by its name.
+///
+/// Uses [`EncryptParams::default()`] if `params` are not provided.
+///
+/// # Errors
+///
+/// See [`encrypt()`].
+#[cfg(feature = "impls")]
+pub fn encrypt_record(
+    name: &str,
+    payload: &str,
+    password: &str,
+    params: Option<EncryptParams>,
+) -> Result<EncryptedRecord> {
+    let EncryptParams {
+        kdf_iterations,
+        algorithm,
+        pad_to,
+    } = params.unwrap_or_default();
+    let mut kdf_salt = KdfSalt::default();
+    OsRng.fill_bytes(&mut kdf_salt);
+
+    let key = Zeroizing::new(derive_key(
+        password,
+        LATEST_OUTPUT_VERSION,
+        Some(&kdf_salt),
+        kdf_iterations,
+    )?);
+    let encrypt_part = |part: &str, aad: &[u8]| {
+        seal_with_key(
+            &key,
+            &pad_text(part, pad_to),
+            kdf_salt,
+            kdf_iterations,
+            algorithm,
+            aad,
+            &mut OsRng,
+        )
+    };
+    Ok(EncryptedRecord {
+        name: encrypt_part(name, &[])?,
+        payload: encrypt_part(payload, name.as_bytes())?,
+    })
+}
+
+/// Decrypt resource name and payload of `record` produced by [`encrypt_record()`].
+///
+/// The key is derived only once if both outputs share key derivation parameters.
+/// Returns the name and the payload.
+///
+/// # Errors
+///
+/// See [`decrypt()`], payload also fails to decrypt if it's swapped with the one of
+/// another record.
+#[cfg(feature = "impls")]
+pub fn decrypt_record(record: EncryptedRecord, password: &str) -> Result<(String, String)> {
+    let EncryptedRecord { name, payload } = record;
+    validate_ciphertext(&name.encrypted_payload, DEFAULT_MAX_CIPHERTEXT_SIZE)?;
+    validate_ciphertext(&payload.encrypted_payload, DEFAULT_MAX_CIPHERTEXT_SIZE)?;
+
+    let name_key = Zeroizing::new(derive_key(
+        password,
+        name.version,
+        name.kdf_salt.as_ref(),
+        name.kdf_iterations,
+    )?);
+    let other_payload_key = if payload.version == name.version
+        && payload.kdf_salt == name.kdf_salt
+        && payload.kdf_iterations == name.kdf_iterations
+    {
+        None
+    } else {
+        Some(Zeroizing::new(derive_key(
+            password,
+            payload.version,
+            payload.kdf_salt.as_ref(),
+            payload.kdf_iterations,
+        )?))
+    };
+    let payload_key = other_payload_key.as_ref().unwrap_or(&name_key);
+
+    let name = decode_text(open_with_key(
+        &name_key,
+        name.key_commitment.as_ref(),
+        name.salt,
+        &name.encrypted_payload,
+        &[],
+    )?)?;
+    let payload = decode_text(open_with_key(
+        payload_key,
+        payload.key_commitment.as_ref(),
+        payload.salt,
+        &payload.encrypted_payload,
+        name.as_bytes(),
+    )?)?;
+    Ok((name, payload))
+}
+
+/// Construct tag of resource `name` to look up records of [`encrypt_record()`] by exact name.
+///
+/// Tag is an HMAC-SHA256 keyed with a key derived from `master_password` with its own salt,
+/// so the storage can compare tags without learning names. It's deterministic, so the storage
+/// still sees if two records have the same name.
+///
+/// Always uses [`DEFAULT_KDF_ITERATIONS`] and `master_password` normalized to NFKC, so that tags
+/// of all records stay comparable.
+#[cfg(feature = "impls")]
+#[must_use]
+pub fn name_lookup_tag(name: &str, master_password: &str) -> NameLookupTag {
+    /// Salt to be used for key derivation
+    const NAME_LOOKUP_SALT: &[u8] = b"telepass_name_lookup_salt";
+    /// Size of the key in bytes
+    const KEY_SIZE: usize = <<Hmac<Sha256> as KeySizeUser>::KeySize as Unsigned>::USIZE;
+    /// Health check
+    const _: () = assert!(
+        NAME_LOOKUP_TAG_SIZE == <<Sha256 as OutputSizeUser>::OutputSize as Unsigned>::USIZE,
+        "Name lookup tag size and SHA 256 output size mismatch"
+    );
+
+    let key = Zeroizing::new(pbkdf2_hmac_array::<Sha256, KEY_SIZE>(
+        normalize_password(master_password).as_bytes(),
+        NAME_LOOKUP_SALT,
+        DEFAULT_KDF_ITERATIONS,
+    ));
+    <Hmac<Sha256> as KeyInit>::new(&(*key).into())
+        .chain_update(name.as_bytes())
+        .finalize()
+        .into_bytes()
+        .into()
+}
+
+/// Encrypt `payload` bound to `aad` with `key` using cipher `C` and a nonce from `rng`.
+///
+/// Returns encrypted payload and the nonce.
+#[cfg(feature = "impls")]
+fn seal<C: Aead + KeyInit, R: CryptoRng + RngCore>(
+    key: &Key,
+    payload: &[u8],
+    aad: &[u8],
+    rng: &mut R,
+) -> Result<(Vec<u8>, aes_gcm::aead::Nonce<C>)> {
+    let cipher = C::new_from_slice(key).map_err(|_err| Error::Encryption)?;
+    let nonce = C::generate_nonce(rng);
+
+    let encrypted_payload = cipher
+        .encrypt(&nonce, Payload { msg: payload, aad })
+        .map_err(|_err| Error::Encryption)?;
+    Ok((encrypted_payload, nonce))
+}
+
+/// Decrypt `encrypted_payload` bound to `aad` with `key` and `nonce` using cipher `C`.
+#[cfg(feature = "impls")]
+fn open<C: Aead + KeyInit>(
+    key: &Key,
+    nonce: &aes_gcm::aead::Nonce<C>,
+    encrypted_payload: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>> {
+    let cipher = C::new_from_slice(key).map_err(|_err| Error::Decryption)?;
+
+    cipher
+        .decrypt(
+            nonce,
+            Payload {
+                msg: encrypted_payload,
+                aad,
+            },
+        )
+        .map_err(|_err| Error::Decryption)
+}
+
+/// Recommended size of chunks for [`encrypt_chunks()`] in bytes.
+pub const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Size of the encryption key in bytes.
+#[cfg(feature = "impls")]
+const KEY_SIZE: usize = <<Aes256Gcm as KeySizeUser>::KeySize as Unsigned>::USIZE;
+
+/// Encryption key for any [`Algorithm`].
+#[cfg(feature = "impls")]
+type Key = [u8; KEY_SIZE];
+
+/// Construct encryption key from string password with `salt` and `iterations` hashing rounds.
+///
+/// Legacy constant salt is used if `salt` is [`None`]. Password is normalized to NFKC for
+/// outputs of [`OUTPUT_VERSION_2`], so that its composed and decomposed forms give the same key.
+///
+/// # Errors
+///
+/// - [`Error::UnsupportedVersion`] if `version` is newer than [`LATEST_OUTPUT_VERSION`];
+/// - [`Error::ZeroKdfIterations`] if `iterations` is zero.
+#[cfg(feature = "impls")]
+fn derive_key(password: &str, version: u8, salt: Option<&KdfSalt>, iterations: u32) -> Result<Key> {
+    /// Salt used for key derivation before it was random
+    const LEGACY_KEY_DERIVATION_SALT: &[u8] = b"telepass_key_derivation_salt";
+
+    /// Health check
+    const _: () = assert!(
+        KEY_SIZE == <<Sha256 as OutputSizeUser>::OutputSize as Unsigned>::USIZE
+            && KEY_SIZE == <<XChaCha20Poly1305 as KeySizeUser>::KeySize as Unsigned>::USIZE,
+        "Cipher and SHA 256 key size mismatch"
+    );
+
+    let password = match version {
+        OUTPUT_VERSION_1 => Zeroizing::new(password.to_owned()),
+        OUTPUT_VERSION_2 => normalize_password(password),
+        _ => return Err(Error::UnsupportedVersion(version)),
+    };
+    if iterations == 0 {
+        return Err(Error::ZeroKdfIterations);
+    }
+
+    Ok(pbkdf2_hmac_array::<sha2::Sha256, KEY_SIZE>(
+        password.as_bytes(),
+        salt.map_or(LEGACY_KEY_DERIVATION_SALT, KdfSalt::as_slice),
+        iterations,
+    ))
+}
+
+/// Normalize `password` to NFKC, so that its composed and decomposed forms give the same key.
+#[cfg(feature = "impls")]
+fn normalize_password(password: &str) -> Zeroizing<String> {
+    Zeroizing::new(password.nfkc().collect())
+}
+
+/// Helpers to produce reproducible encryption outputs in tests.
+#[cfg(all(feature = "impls", any(test, feature = "test-utils")))]
+pub mod test_utils {
+    use rand_chacha::rand_core::SeedableRng as _;
+
+    /// Cryptographically secure random number generator returned by [`seeded_rng()`].
+    pub type SeededRng = rand_chacha::ChaCha20Rng;
+
+    /// Construct random number generator producing the same numbers for the same `seed`.
+    ///
+    /// Pass it to [`encrypt_with_rng()`](super::encrypt_with_rng) to get the same output for
+    /// the same payload and password.
+    #[must_use]
+    pub fn seeded_rng(seed: u64) -> SeededRng {
+        SeededRng::seed_from_u64(seed)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "impls")]
+mod tests {
+    #![expect(clippy::expect_used, reason = "it's ok in tests")]
+
+    use super::*;
+
+    /// Encrypt `payload` the way it was done before the key derivation salt became random.
+    pub fn encrypt_legacy(payload: &str, password: &str) -> EncryptionOutput {
+        let key = derive_key(password, OUTPUT_VERSION_1, None, DEFAULT_KDF_ITERATIONS)
+            .expect("Failed to derive key");
+        let (encrypted_payload, nonce) =
+            seal::<Aes256Gcm, _>(&key, payload.as_bytes(), &[], &mut OsRng)
+                .expect("Failed to encrypt payload");
+
+        EncryptionOutput {
+            version: OUTPUT_VERSION_1,
+            encrypted_payload,
+            salt: Salt::Aes256Gcm(nonce.into()),
+            kdf_iterations: DEFAULT_KDF_ITERATIONS,
+            kdf_salt: None,
+            key_commitment: None,
+        }
+    }
+
+    #[test]
+    fn encrypt_with_seeded_rng_is_reproducible() {
+        let params = EncryptParams {
+            kdf_iterations: 1000,
+            algorithm: Algorithm::XChaCha20Poly1305,
+            pad_to: None,
+        };
+        let encrypt_seeded = |seed| {
+            encrypt_with_rng(
+                "payload",
+                "password",
+                Some(params),
+                &mut test_utils::seeded_rng(seed),
+            )
+            .expect("Failed to encrypt payload")
+        };
+
+        let output = encrypt_seeded(42);
+        assert_eq!(output, encrypt_seeded(42));
+        assert_ne!(output, encrypt_seeded(43));
+        assert_eq!(
+            decrypt(output, "password").expect("Failed to decrypt payload"),
+            "payload"
+        );
+    }
+
+    #[test]
+    fn encrypt_and_decrypt_work() {
+        let payload = "payload";
+        let password = "password";
+
+        let output = encrypt(payload, password, None).expect("Failed to encrypt payload");
+        let decrypted_payload = decrypt(output, password).expect("Failed to decrypt payload");
+
+        assert_eq!(payload, decrypted_payload);
+    }
+
+    #[test]
+    fn encrypt_and_decrypt_bytes_work() {
+        // Not a valid UTF-8, like most of files
+        let payload = [0xFF, 0x00, 0xFE, 0x80];
+        let password = "password";
+
+        let output = encrypt_bytes(&payload, password, None, b"file.bin")
+            .expect("Failed to encrypt payload");
+        let decrypted_payload =
+            decrypt_bytes(output.clone(), password, b"file.bin").expect("Failed to decrypt");
+
+        assert_eq!(decrypted_payload, payload);
+        assert!(matches!(
+            decrypt_with_aad(output, password, b"file.bin"),
+            Err(Error::Utf8(_))
+        ));
+    }
+
+    #[test]
+    fn encrypt_same_payload_with_different_passwords_gives_different_results() {
+        let payload = "payload";
+
+        let first_output =
+            encrypt(payload, "password1", None).expect("Failed to encrypt payload first time");
+        let second_output =
+            encrypt(payload, "password2", None).expect("Failed to encrypt payload second time");
+
+        assert_ne!(
+            first_output.encrypted_payload,
+            second_output.encrypted_payload
+        );
+        assert_ne!(first_output.salt, second_output.salt);
+    }
+
+    #[test]
+    fn encrypt_same_payload_twice_gives_different_results() {
+        let payload = "payload";
+        let password = "password";
+
+        let first_output =
+            encrypt(payload, password, None).expect("Failed to encrypt payload first time");
+        let second_output =
+            encrypt(payload, password, None).expect("Failed to encrypt payload second time");
+
+        assert_ne!(
+            first_output.encrypted_payload,
+            second_output.encrypted_payload
+        );
+        assert_ne!(first_output.salt, second_output.salt);
+    }
+
+    #[test]
+    fn decrypt_with_wrong_password_fails() {
+        let payload = "payload";
+
+        let output = encrypt(payload, "password1", None).expect("Failed to encrypt payload");
+        decrypt(output, "password2").expect_err("Decryption is expected to fail");
+    }
+
+    #[test]
+    #[expect(clippy::indexing_slicing, reason = "it's ok in tests")]
+    fn decrypt_with_wrong_payload_fails() {
+        let payload = "payload";
+        let password = "password";
+
+        let mut output = encrypt(payload, password, None).expect("Failed to encrypt payload");
+        output.encrypted_payload[0] = !output.encrypted_payload[0];
+        decrypt(output, password).expect_err("Decryption is expected to fail");
+    }
+
+    #[test]
+    fn decrypt_rejects_malformed_ciphertexts() {
+        let password = "password";
+        let output = encrypt("payload", password, None).expect("Failed to encrypt payload");
+        let with_payload = |encrypted_payload: Vec<u8>| EncryptionOutput {
+            encrypted_payload,
+            ..output.clone()
+        };
+        let reason = |result: Result<String>| match result {
+            Err(Error::MalformedCiphertext { reason }) => Some(reason),
+            _ => None,
+        };
+
+        assert_eq!(
+            reason(decrypt(with_payload(Vec::new()), password)),
+            Some(MalformedCiphertextReason::Empty)
+        );
+        assert_eq!(
+            reason(decrypt(with_payload(vec![0; AEAD_TAG_SIZE - 1]), password)),
+            Some(MalformedCiphertextReason::ShorterThanTag(AEAD_TAG_SIZE - 1))
+        );
+        assert_eq!(
+            reason(decrypt(
+                with_payload(vec![0; DEFAULT_MAX_CIPHERTEXT_SIZE + 1]),
+                password
+            )),
+            Some(MalformedCiphertextReason::TooLarge {
+                size: DEFAULT_MAX_CIPHERTEXT_SIZE + 1,
+                max_size: DEFAULT_MAX_CIPHERTEXT_SIZE,
+            })
+        );
+
+        // Tag-sized ciphertext is well-formed, but doesn't authenticate
+        assert!(matches!(
+            decrypt(with_payload(vec![0; AEAD_TAG_SIZE]), password),
+            Err(Error::CorruptedData)
+        ));
+    }
+
+    #[test]
+    fn decrypt_bytes_with_max_size_applies_the_limit() {
+        let password = "password";
+        let payload = [0xFF; 64];
+        let output =
+            encrypt_bytes(&payload, password, None, &[]).expect("Failed to encrypt payload");
+        let size = output.encrypted_payload.len();
+
+        assert_eq!(
+            decrypt_bytes_with_max_size(output.clone(), password, &[], size)
+                .expect("Failed to decrypt payload"),
+            payload
+        );
+        assert!(matches!(
+            decrypt_bytes_with_max_size(output, password, &[], size - 1),
+            Err(Error::MalformedCiphertext {
+                reason: MalformedCiphertextReason::TooLarge { .. }
+            })
+        ));
+        assert!(matches!(
+            validate_ciphertext(&[], usize::MAX),
+            Err(Error::MalformedCiphertext {
+                reason: MalformedCiphertextReason::Empty
+            })
+        ));
+    }
+
+    #[test]
+    fn encrypt_with_custom_kdf_iterations_stores_them() {
+        let payload = "payload";
+        let password = "password";
+        let params = EncryptParams {
+            kdf_iterations: 1_000,
+            ..EncryptParams::default()
+        };
+
+        let output = encrypt(payload, password, Some(params)).expect("Failed to encrypt payload");
+        assert_eq!(output.kdf_iterations, 1_000);
+
+        let mut output_with_default_iterations = output.clone();
+        output_with_default_iterations.kdf_iterations = DEFAULT_KDF_ITERATIONS;
+        decrypt(output_with_default_iterations, password)
+            .expect_err("Decryption with other iterations is expected to fail");
+
+        let decrypted_payload = decrypt(output, password).expect("Failed to decrypt payload");
+        assert_eq!(payload, decrypted_payload);
+    }
+
+    #[test]
+    fn legacy_output_decrypts_with_default_algorithm_and_kdf_params() {
+        let payload = "payload";
+        let password = "password";
+        let output = encrypt_legacy(payload, password);
+        let legacy_output = serde_json::json!({
+            "encrypted_payload": output.encrypted_payload,
+            "salt": output.salt.as_bytes(),
+        });
+
+        let deserialized_output: EncryptionOutput =
+            serde_json::from_value(legacy_output).expect("Failed to deserialize legacy output");
+        assert_eq!(deserialized_output.version, OUTPUT_VERSION_1);
+        assert_eq!(deserialized_output.algorithm(), Algorithm::Aes256Gcm);
+        assert_eq!(deserialized_output.kdf_iterations, DEFAULT_KDF_ITERATIONS);
+        assert_eq!(deserialized_output.kdf_salt, None);
+
+        let decrypted_payload =
+            decrypt(deserialized_output, password).expect("Failed to decrypt payload");
+        assert_eq!(payload, decrypted_payload);
+    }
+
+    #[test]
+    fn payload_swapped_to_another_resource_fails_to_decrypt() {
+        let password = "password";
+
+        for algorithm in [Algorithm::Aes256Gcm, Algorithm::XChaCha20Poly1305] {
+            let params = EncryptParams {
+                algorithm,
+                ..EncryptParams::default()
+            };
+            let output = encrypt_with_aad("secret", password, Some(params), b"github.com")
+                .expect("Failed to encrypt payload");
+
+            let swapped_error = decrypt_with_aad(output.clone(), password, b"bank.com")
+                .expect_err("Decryption with another resource name is expected to fail");
+            assert!(matches!(swapped_error, Error::CorruptedData));
+            let unbound_error = decrypt(output.clone(), password)
+                .expect_err("Decryption without associated data is expected to fail");
+            assert!(matches!(unbound_error, Error::CorruptedData));
+
+            let decrypted_payload = decrypt_with_aad(output, password, b"github.com")
+                .expect("Failed to decrypt payload");
+            assert_eq!(decrypted_payload, "secret");
+        }
+    }
+
+    #[test]
+    fn payload_without_aad_fails_to_decrypt_with_aad() {
+        let password = "password";
+        let output = encrypt("secret", password, None).expect("Failed to encrypt payload");
+
+        let error = decrypt_with_aad(output.clone(), password, b"github.com")
+            .expect_err("Decryption with unexpected associated data is expected to fail");
+        assert!(matches!(error, Error::CorruptedData));
+        assert_eq!(
+            decrypt_with_aad(output, password, &[]).expect("Failed to decrypt payload"),
+            "secret"
+        );
+    }
+
+    #[test]
+    fn encrypt_same_payload_twice_uses_different_kdf_salts() {
+        let first_output =
+            encrypt("payload", "password", None).expect("Failed to encrypt payload first time");
+        let second_output =
+            encrypt("payload", "password", None).expect("Failed to encrypt payload second time");
+
+        assert!(first_output.kdf_salt.is_some() && second_output.kdf_salt.is_some());
+        assert_ne!(first_output.kdf_salt, second_output.kdf_salt);
+    }
+
+    #[test]
+    fn decrypt_with_wrong_kdf_salt_fails() {
+        let password = "password";
+        let output = encrypt("payload", password, None).expect("Failed to encrypt payload");
+
+        let mut output_with_legacy_salt = output.clone();
+        output_with_legacy_salt.kdf_salt = None;
+        decrypt(output_with_legacy_salt, password)
+            .expect_err("Decryption with legacy salt is expected to fail");
+
+        let mut output_with_other_salt = output;
+        output_with_other_salt.kdf_salt = Some([0; KDF_SALT_SIZE]);
+        decrypt(output_with_other_salt, password)
+            .expect_err("Decryption with other salt is expected to fail");
+    }
+
+    #[test]
+    fn encrypt_and_decrypt_with_xchacha20_poly1305_work() {
+        let payload = "payload";
+        let password = "password";
+        let params = EncryptParams {
+            algorithm: Algorithm::XChaCha20Poly1305,
+            ..EncryptParams::default()
+        };
+
+        let output = encrypt(payload, password, Some(params)).expect("Failed to encrypt payload");
+        assert_eq!(output.algorithm(), Algorithm::XChaCha20Poly1305);
+        assert_eq!(output.salt.as_bytes().len(), XCHACHA20_POLY1305_SALT_SIZE);
+
+        decrypt(output.clone(), "wrong password")
+            .expect_err("Decryption with wrong password is expected to fail");
+        let decrypted_payload = decrypt(output, password).expect("Failed to decrypt payload");
+        assert_eq!(payload, decrypted_payload);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn async_and_sync_outputs_are_interchangeable() {
+        let params = EncryptParams {
+            kdf_iterations: 1000,
+            ..EncryptParams::default()
+        };
+
+        let async_output = encrypt_async("payload", "password", Some(params))
+            .await
+            .expect("Failed to encrypt payload asynchronously");
+        let sync_decrypted = decrypt(async_output, "password").expect("Failed to decrypt payload");
+        assert_eq!(sync_decrypted, "payload");
+
+        let sync_output =
+            encrypt("payload", "password", Some(params)).expect("Failed to encrypt payload");
+        let async_decrypted = decrypt_async(sync_output, "password")
+            .await
+            .expect("Failed to decrypt payload asynchronously");
+        assert_eq!(async_decrypted, "payload");
+    }
+
+    #[test]
+    fn encrypted_record_round_trips() {
+        let params = EncryptParams {
+            kdf_iterations: 1000,
+            ..EncryptParams::default()
+        };
+
+        let record = encrypt_record("test.resource.com", "payload", "password", Some(params))
+            .expect("Failed to encrypt record");
+        assert_eq!(record.name.kdf_salt, record.payload.kdf_salt);
+
+        let (name, payload) = decrypt_record(record, "password").expect("Failed to decrypt record");
+        assert_eq!(name, "test.resource.com");
+        assert_eq!(payload, "payload");
+    }
+
+    #[test]
+    fn encrypted_record_with_swapped_payload_fails_to_decrypt() {
+        let params = EncryptParams {
+            kdf_iterations: 1000,
+            ..EncryptParams::default()
+        };
+
+        let first = encrypt_record("first.com", "first payload", "password", Some(params))
+            .expect("Failed to encrypt first record");
+        let second = encrypt_record("second.com", "second payload", "password", Some(params))
+            .expect("Failed to encrypt second record");
+        let swapped = EncryptedRecord {
+            name: first.name,
+            payload: second.payload,
+        };
+
+        decrypt_record(swapped, "password")
+            .expect_err("Decryption of swapped payload is expected to fail");
+    }
+
+    #[test]
+    fn name_lookup_tag_is_deterministic_and_keyed() {
+        let tag = name_lookup_tag("test.resource.com", "password");
+
+        assert_eq!(tag, name_lookup_tag("test.resource.com", "password"));
+        assert_ne!(tag, name_lookup_tag("other.resource.com", "password"));
+        assert_ne!(tag, name_lookup_tag("test.resource.com", "other password"));
+    }
+
+    #[test]
+    fn output_serialization_preserves_algorithm() {
+        for algorithm in [Algorithm::Aes256Gcm, Algorithm::XChaCha20Poly1305] {
+            let params = EncryptParams {
+                algorithm,
+                ..EncryptParams::default()
+            };
+            let output =
+                encrypt("payload", "password", Some(params)).expect("Failed to encrypt payload");
+
+            let serialized = serde_json::to_value(&output).expect("Failed to serialize output");
+            assert_eq!(
+                serialized.get("algorithm"),
+                Some(&serde_json::Value::from(algorithm.name()))
+            );
+
+            let deserialized: EncryptionOutput =
+                serde_json::from_value(serialized).expect("Failed to deserialize output");
+            assert_eq!(deserialized, output);
+        }
+    }
+
+    #[test]
+    fn output_bytes_are_base64_in_json() {
+        let output = EncryptionOutput {
+            version: OUTPUT_VERSION_1,
+            encrypted_payload: b"payload".to_vec(),
+            salt: Salt::Aes256Gcm([1; AES_256_GCM_SALT_SIZE]),
+            kdf_iterations: DEFAULT_KDF_ITERATIONS,
+            kdf_salt: None,
+            key_commitment: None,
+        };
+
+        let serialized = serde_json::to_value(&output).expect("Failed to serialize output");
+        assert_eq!(
+            serialized.get("encrypted_payload"),
+            Some(&serde_json::Value::from("cGF5bG9hZA"))
+        );
+        assert_eq!(
+            serialized.get("salt"),
+            Some(&serde_json::Value::from("AQEBAQEBAQEBAQEB"))
+        );
+
+        let deserialized: EncryptionOutput =
+            serde_json::from_value(serialized).expect("Failed to deserialize output");
+        assert_eq!(deserialized, output);
+    }
+
+    #[test]
+    fn output_kdf_salt_is_base64_in_json() {
+        let output = EncryptionOutput {
+            version: OUTPUT_VERSION_2,
+            encrypted_payload: b"payload".to_vec(),
+            salt: Salt::Aes256Gcm([1; AES_256_GCM_SALT_SIZE]),
+            kdf_iterations: DEFAULT_KDF_ITERATIONS,
+            kdf_salt: Some([2; KDF_SALT_SIZE]),
+            key_commitment: None,
+        };
+
+        let serialized = serde_json::to_value(&output).expect("Failed to serialize output");
+        assert_eq!(
+            serialized.get("kdf_salt"),
+            Some(&serde_json::Value::from("AgICAgICAgICAgICAgICAg"))
+        );
+        let deserialized: EncryptionOutput =
+            serde_json::from_value(serialized).expect("Failed to deserialize output");
+        assert_eq!(deserialized, output);
+
+        // Outputs serialized before contain arrays
+        let legacy_output = serde_json::json!({
+            "version": OUTPUT_VERSION_2,
+            "encrypted_payload": "cGF5bG9hZA",
+            "salt": "AQEBAQEBAQEBAQEB",
+            "kdf_salt": ([2_u8; KDF_SALT_SIZE]),
+        });
+        let deserialized_legacy: EncryptionOutput =
+            serde_json::from_value(legacy_output).expect("Failed to deserialize legacy output");
+        assert_eq!(deserialized_legacy, output);
+
+        let wrong_size = serde_json::json!({
+            "encrypted_payload": "cGF5bG9hZA",
+            "salt": "AQEBAQEBAQEBAQEB",
+            "kdf_salt": "AgIC",
+        });
+        serde_json::from_value::<EncryptionOutput>(wrong_size)
+            .expect_err("Deserialization is expected to fail");
+    }
+
+    #[test]
+    fn output_key_commitment_is_base64_in_json() {
+        let output = EncryptionOutput {
+            version: OUTPUT_VERSION_2,
+            encrypted_payload: b"payload".to_vec(),
+            salt: Salt::Aes256Gcm([1; AES_256_GCM_SALT_SIZE]),
+            kdf_iterations: DEFAULT_KDF_ITERATIONS,
+            kdf_salt: None,
+            key_commitment: Some([3; KEY_COMMITMENT_SIZE]),
+        };
+
+        let serialized = serde_json::to_value(&output).expect("Failed to serialize output");
+        assert_eq!(
+            serialized.get("key_commitment"),
+            Some(&serde_json::Value::from(
+                "AwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwM"
+            ))
+        );
+        let deserialized: EncryptionOutput =
+            serde_json::from_value(serialized).expect("Failed to deserialize output");
+        assert_eq!(deserialized, output);
+
+        // Outputs serialized before contain arrays
+        let legacy_output = serde_json::json!({
+            "version": OUTPUT_VERSION_2,
+            "encrypted_payload": "cGF5bG9hZA",
+            "salt": "AQEBAQEBAQEBAQEB",
+            "key_commitment": ([3_u8; KEY_COMMITMENT_SIZE]),
+        });
+        let deserialized_legacy: EncryptionOutput =
+            serde_json::from_value(legacy_output).expect("Failed to deserialize legacy output");
+        assert_eq!(deserialized_legacy, output);
+    }
+
+    #[test]
+    fn output_bytes_are_raw_in_binary_formats() {
+        use serde_test::{assert_tokens, Configure as _, Token};
+
+        let output = EncryptionOutput {
+            version: OUTPUT_VERSION_1,
+            encrypted_payload: b"payload".to_vec(),
+            salt: Salt::Aes256Gcm([1; AES_256_GCM_SALT_SIZE]),
+            kdf_iterations: DEFAULT_KDF_ITERATIONS,
+            kdf_salt: None,
+            key_commitment: None,
+        };
+
+        assert_tokens(
+            &output.compact(),
+            &[
+                Token::Struct {
+                    name: "RawEncryptionOutput",
+                    len: 7,
+                },
+                Token::Str("version"),
+                Token::U8(OUTPUT_VERSION_1),
+                Token::Str("encrypted_payload"),
+                Token::Bytes(b"payload"),
+                Token::Str("algorithm"),
+                Token::Str("aes-256-gcm"),
+                Token::Str("salt"),
+                Token::Bytes(&[1; AES_256_GCM_SALT_SIZE]),
+                Token::Str("kdf_iterations"),
+                Token::U32(DEFAULT_KDF_ITERATIONS),
+                Token::Str("kdf_salt"),
+                Token::None,
+                Token::Str("key_commitment"),
+                Token::None,
+                Token::StructEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn output_with_invalid_base64_is_rejected() {
+        let output = serde_json::json!({
+            "encrypted_payload": "not base64!",
+            "salt": "AQEBAQEBAQEBAQEB",
+        });
+
+        serde_json::from_value::<EncryptionOutput>(output)
+            .expect_err("Deserialization is expected to fail");
+    }
+
+    #[test]
+    fn url_query_round_trip() {
+        for (version, salt, kdf_salt, key_commitment) in [
+            (
+                OUTPUT_VERSION_1,
+                Salt::Aes256Gcm([1; AES_256_GCM_SALT_SIZE]),
+                None,
+                None,
+            ),
+            (
+                OUTPUT_VERSION_2,
+                Salt::XChaCha20Poly1305([2; XCHACHA20_POLY1305_SALT_SIZE]),
+                Some([3; KDF_SALT_SIZE]),
+                Some([4; KEY_COMMITMENT_SIZE]),
+            ),
+        ] {
+            let output = EncryptionOutput {
+                version,
+                encrypted_payload: b"payload".to_vec(),
+                salt,
+                kdf_iterations: 1_000,
+                kdf_salt,
+                key_commitment,
+            };
+
+            let query = output.to_url_query();
+            assert_eq!(
+                EncryptionOutput::from_url_query(&query).expect("Failed to decode url query"),
+                output
+            );
+        }
+    }
+
+    #[test]
+    fn url_query_omits_default_algorithm_and_legacy_kdf_salt() {
+        let output = EncryptionOutput {
+            version: OUTPUT_VERSION_1,
+            encrypted_payload: b"payload".to_vec(),
+            salt: Salt::Aes256Gcm([1; AES_256_GCM_SALT_SIZE]),
+            kdf_iterations: DEFAULT_KDF_ITERATIONS,
+            kdf_salt: None,
+            key_commitment: None,
+        };
+
+        assert_eq!(
+            output.to_url_query().pairs(),
+            [
+                ("payload", "cGF5bG9hZA==".to_owned()),
+                ("salt", "AQEBAQEBAQEBAQEB".to_owned()),
+                ("kdf_iterations", DEFAULT_KDF_ITERATIONS.to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn legacy_url_query_decodes_with_default_algorithm_and_kdf_params() {
+        let query = UrlQuery {
+            payload: "cGF5bG9hZA==".to_owned(),
+            salt: "AQEBAQEBAQEBAQEB".to_owned(),
+            kdf_iterations: Some(0),
+            kdf_salt: Some(String::new()),
+            algorithm: Some(String::new()),
+            key_commitment: None,
+            version: Some(0),
+        };
+
+        let output = EncryptionOutput::from_url_query(&query).expect("Failed to decode url query");
+        assert_eq!(output.algorithm(), Algorithm::Aes256Gcm);
+        assert_eq!(output.kdf_iterations, DEFAULT_KDF_ITERATIONS);
+        assert_eq!(output.kdf_salt, None);
+        assert_eq!(output.version, OUTPUT_VERSION_1);
+    }
+
+    #[test]
+    fn invalid_url_query_is_rejected() {
+        let valid = EncryptionOutput {
+            version: OUTPUT_VERSION_1,
+            encrypted_payload: b"payload".to_vec(),
+            salt: Salt::Aes256Gcm([1; AES_256_GCM_SALT_SIZE]),
+            kdf_iterations: DEFAULT_KDF_ITERATIONS,
+            kdf_salt: Some([3; KDF_SALT_SIZE]),
+            key_commitment: None,
+        }
+        .to_url_query();
+
+        let not_base64 = UrlQuery {
+            payload: "not base64!".to_owned(),
+            ..valid.clone()
+        };
+        assert!(matches!(
+            EncryptionOutput::from_url_query(&not_base64),
+            Err(UrlQueryError::Base64(_))
+        ));
+
+        let unknown_algorithm = UrlQuery {
+            algorithm: Some("rot13".to_owned()),
+            ..valid.clone()
+        };
+        assert!(matches!(
+            EncryptionOutput::from_url_query(&unknown_algorithm),
+            Err(UrlQueryError::UnknownAlgorithm(_))
+        ));
+
+        let salt_of_another_algorithm = UrlQuery {
+            algorithm: Some(Algorithm::XChaCha20Poly1305.name().to_owned()),
+            ..valid.clone()
+        };
+        assert!(matches!(
+            EncryptionOutput::from_url_query(&salt_of_another_algorithm),
+            Err(UrlQueryError::WrongSaltLength(_))
+        ));
+
+        let short_kdf_salt = UrlQuery {
+            kdf_salt: Some("AQEB".to_owned()),
+            ..valid.clone()
+        };
+        assert_eq!(
+            EncryptionOutput::from_url_query(&short_kdf_salt),
+            Err(UrlQueryError::WrongKdfSaltLength(3))
+        );
+
+        let short_key_commitment = UrlQuery {
+            key_commitment: Some("AQEB".to_owned()),
+            ..valid
+        };
+        assert_eq!(
+            EncryptionOutput::from_url_query(&short_key_commitment),
+            Err(UrlQueryError::WrongKeyCommitmentLength(3))
+        );
+    }
+
+    #[test]
+    fn output_with_salt_of_another_algorithm_is_rejected() {
+        let output = serde_json::json!({
+            "encrypted_payload": b"payload",
+            "algorithm": "xchacha20-poly1305",
+            "salt": vec![0_u8; AES_256_GCM_SALT_SIZE],
+        });
+
+        serde_json::from_value::<EncryptionOutput>(output)
+            .expect_err("Deserialization is expected to fail");
+    }
+
+    #[test]
+    fn algorithm_names_round_trip() {
+        for algorithm in [Algorithm::Aes256Gcm, Algorithm::XChaCha20Poly1305] {
+            assert_eq!(algorithm.name().parse(), Ok(algorithm));
+        }
+        assert_eq!(
+            "rot13".parse::<Algorithm>(),
+            Err(UnknownAlgorithmError("rot13".to_owned()))
+        );
+    }
+
+    #[test]
+    fn zero_kdf_iterations_are_rejected() {
+        let params = EncryptParams {
+            kdf_iterations: 0,
+            ..EncryptParams::default()
+        };
+        let error = encrypt("payload", "password", Some(params))
+            .expect_err("Encryption is expected to fail");
+        assert!(matches!(error, Error::ZeroKdfIterations));
+    }
+
+    #[test]
+    fn encryption_output_debug_hides_bytes() {
+        let output = EncryptionOutput {
+            version: OUTPUT_VERSION_1,
+            encrypted_payload: vec![0xAB; 96],
+            salt: Salt::Aes256Gcm([0xCD; AES_256_GCM_SALT_SIZE]),
+            kdf_iterations: DEFAULT_KDF_ITERATIONS,
+            kdf_salt: Some([0xEF; KDF_SALT_SIZE]),
+            key_commitment: None,
+        };
+
+        let debug = format!("{output:?}");
+
+        for byte in [
+            "171", "205", "239", "0xab", "0xcd", "0xef", "ab, ab", "[171",
+        ] {
+            assert!(!debug.contains(byte), "`{debug}` contains `{byte}`");
+        }
+        assert!(
+            debug.starts_with("EncryptionOutput { version: 1, encrypted_payload: 96 bytes #"),
+            "{debug}"
+        );
+        assert!(
+            debug.ends_with(
+                "salt: <redacted>, algorithm: Aes256Gcm, kdf_iterations: 100000, \
+                 kdf_salt: Some(<redacted>), key_commitment: None }"
+            ),
+            "{debug}"
+        );
+        assert_eq!(
+            format!("{:?}", RedactedBytes(&output.encrypted_payload)),
+            format!("{:?}", RedactedBytes(&[0xAB; 96]))
+        );
+        assert_ne!(
+            format!("{:?}", RedactedBytes(&output.encrypted_payload)),
+            format!("{:?}", RedactedBytes(&[0xAB; 95]))
+        );
+    }
+
+    #[test]
+    fn keywords_are_normalized_and_deduplicated() {
+        assert_eq!(
+            keywords("Router: admin@192.168.0.1, the ROUTER is in the hall; id 42"),
+            ["router", "admin", "192", "168", "hall"]
+        );
+    }
+
+    #[test]
+    fn keywords_keep_non_ascii_words() {
+        assert_eq!(
+            keywords("\u{420}\u{43e}\u{443}\u{442}\u{435}\u{440} wifi"),
+            ["\u{440}\u{43e}\u{443}\u{442}\u{435}\u{440}", "wifi"]
+        );
+    }
+
+    #[test]
+    fn master_password_is_normalized_for_derived_keys() {
+        let composed = "caf\u{e9}";
+        let decomposed = "cafe\u{301}";
+
+        assert_eq!(
+            BlindIndexKey::derive(composed).token("router"),
+            BlindIndexKey::derive(decomposed).token("router")
+        );
+        assert_eq!(
+            name_lookup_tag("test.resource.com", composed),
+            name_lookup_tag("test.resource.com", decomposed)
+        );
+        let bundle = b"bundle";
+        verify_export(bundle, decomposed, &sign_export(bundle, composed))
+            .expect("Failed to verify export signed with composed password");
+    }
+
+    #[test]
+    fn hex_bytes_are_displayed_lowercase() {
+        assert_eq!(HexBytes(&[0x1a, 0x2B, 0x00, 0xff]).to_string(), "1a2b00ff");
+        assert_eq!(HexBytes(&[]).to_string(), "");
+    }
+
+    #[test]
+    fn constant_time_eq_compares_contents() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(constant_time_eq(b"", b""));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(constant_time_eq_str("\u{1f511} token", "\u{1f511} token"));
+        assert!(!constant_time_eq_str("token", "t0ken"));
+    }
+
+    #[test]
+    fn constant_time_eq_rejects_different_lengths() {
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"secret", b"secre"));
+        assert!(!constant_time_eq(b"secret", b""));
+        assert!(!constant_time_eq_str("token", "token "));
+    }
+
+    #[test]
+    fn fingerprint_is_stable() {
+        let output = EncryptionOutput {
+            version: OUTPUT_VERSION_1,
+            encrypted_payload: b"payload".to_vec(),
+            salt: Salt::Aes256Gcm([1; AES_256_GCM_SALT_SIZE]),
+            kdf_iterations: DEFAULT_KDF_ITERATIONS,
+            kdf_salt: Some([2; KDF_SALT_SIZE]),
+            key_commitment: None,
+        };
+
+        assert_eq!(
+            HexBytes(&fingerprint(&output)).to_string(),
+            "d0e29216c6fb6e9d"
+        );
+    }
+
+    #[test]
+    fn fingerprint_survives_serialization() {
+        let output = encrypt(
+            "payload",
+            "password",
+            Some(EncryptParams {
+                kdf_iterations: 1000,
+                algorithm: Algorithm::Aes256Gcm,
+                pad_to: None,
+            }),
+        )
+        .expect("Failed to encrypt payload");
+        let expected = fingerprint(&output);
+
+        let json = serde_json::to_string(&output).expect("Failed to serialize output");
+        let from_json: EncryptionOutput =
+            serde_json::from_str(&json).expect("Failed to deserialize output");
+        assert_eq!(fingerprint(&from_json), expected);
+
+        let from_url_query = EncryptionOutput::from_url_query(&output.to_url_query())
+            .expect("Failed to decode url query");
+        assert_eq!(fingerprint(&from_url_query), expected);
+    }
+
+    #[test]
+    fn fingerprint_depends_on_payload_salt_and_version_only() {
+        let output = encrypt(
+            "payload",
+            "password",
+            Some(EncryptParams {
+                kdf_iterations: 1000,
+                algorithm: Algorithm::Aes256Gcm,
+                pad_to: None,
+            }),
+        )
+        .expect("Failed to encrypt payload");
+        let expected = fingerprint(&output);
+
+        let mut other_kdf_params = output.clone();
+        other_kdf_params.kdf_iterations = other_kdf_params.kdf_iterations.saturating_add(1);
+        other_kdf_params.kdf_salt = None;
+        other_kdf_params.key_commitment = None;
+        assert_eq!(fingerprint(&other_kdf_params), expected);
+
+        let mut other_payload = output.clone();
+        other_payload.encrypted_payload.push(0);
+        assert_ne!(fingerprint(&other_payload), expected);
+
+        let mut other_salt = output.clone();
+        other_salt.salt = Salt::XChaCha20Poly1305([0; XCHACHA20_POLY1305_SALT_SIZE]);
+        assert_ne!(fingerprint(&other_salt), expected);
+
+        let mut other_version = output;
+        other_version.version = other_version.version.wrapping_add(1);
+        assert_ne!(fingerprint(&other_version), expected);
+    }
+
+    #[test]
+    fn decrypt_with_wrong_salt_fails() {
+        let payload = "payload";
+        let password = "password";
+
+        let mut output = encrypt(payload, password, None).expect("Failed to encrypt payload");
+        output.salt = Salt::Aes256Gcm([0; AES_256_GCM_SALT_SIZE]);
+        decrypt(output, password).expect_err("Decryption is expected to fail");
+    }
+
+    #[test]
+    fn reencrypt_changes_password() {
+        let payload = "payload";
+
+        let output = encrypt(payload, "old_password", None).expect("Failed to encrypt payload");
+        let reencrypted_output = reencrypt(output.clone(), "old_password", "new_password")
+            .expect("Failed to re-encrypt payload");
+
+        assert_ne!(reencrypted_output.salt, output.salt);
+        assert_ne!(reencrypted_output.kdf_salt, output.kdf_salt);
+        assert_ne!(
+            reencrypted_output.encrypted_payload,
+            output.encrypted_payload
+        );
+        decrypt(reencrypted_output.clone(), "old_password")
+            .expect_err("Decryption with old password is expected to fail");
+        let decrypted_payload =
+            decrypt(reencrypted_output, "new_password").expect("Failed to decrypt payload");
+        assert_eq!(decrypted_payload, payload);
+    }
+
+    #[test]
+    fn reencrypt_with_wrong_old_password_fails() {
+        let output = encrypt("payload", "old_password", None).expect("Failed to encrypt payload");
+
+        let error = reencrypt(output, "wrong_password", "new_password")
+            .expect_err("Re-encryption is expected to fail");
+        assert!(matches!(error, Error::WrongPassword));
+    }
+
+    #[test]
+    fn wrong_password_is_told_from_corrupted_data() {
+        let password = "password";
+
+        for algorithm in [Algorithm::Aes256Gcm, Algorithm::XChaCha20Poly1305] {
+            let params = EncryptParams {
+                algorithm,
+                ..EncryptParams::default()
+            };
+            let output =
+                encrypt("secret", password, Some(params)).expect("Failed to encrypt payload");
+            assert!(output.key_commitment.is_some());
+
+            let wrong_password_error = decrypt(output.clone(), "wrong_password")
+                .expect_err("Decryption with wrong password is expected to fail");
+            assert!(matches!(wrong_password_error, Error::WrongPassword));
+
+            let mut corrupted_output = output;
+            let byte = corrupted_output
+                .encrypted_payload
+                .first_mut()
+                .expect("Encrypted payload is empty");
+            *byte ^= 1;
+            let corrupted_error = decrypt(corrupted_output.clone(), password)
+                .expect_err("Decryption of corrupted data is expected to fail");
+            assert!(matches!(corrupted_error, Error::CorruptedData));
+            let both_error = decrypt(corrupted_output, "wrong_password")
+                .expect_err("Decryption of corrupted data is expected to fail");
+            assert!(matches!(both_error, Error::WrongPassword));
+        }
+    }
+
+    #[test]
+    fn legacy_output_failures_are_not_told_apart() {
+        let output = encrypt_legacy("secret", "password");
+
+        let wrong_password_error = decrypt(output.clone(), "wrong_password")
+            .expect_err("Decryption with wrong password is expected to fail");
+        assert!(matches!(wrong_password_error, Error::Decryption));
+
+        let mut corrupted_output = output;
+        let byte = corrupted_output
+            .encrypted_payload
+            .first_mut()
+            .expect("Encrypted payload is empty");
+        *byte ^= 1;
+        let corrupted_error = decrypt(corrupted_output, "password")
+            .expect_err("Decryption of corrupted data is expected to fail");
+        assert!(matches!(corrupted_error, Error::Decryption));
+    }
+
+    #[test]
+    fn reencrypt_keeps_algorithm_and_kdf_iterations() {
+        let params = EncryptParams {
+            kdf_iterations: 1_000,
+            algorithm: Algorithm::XChaCha20Poly1305,
+            pad_to: None,
+        };
+        let output =
+            encrypt("payload", "old_password", Some(params)).expect("Failed to encrypt payload");
+
+        let reencrypted_output = reencrypt(output, "old_password", "new_password")
+            .expect("Failed to re-encrypt payload");
+
+        assert_eq!(reencrypted_output.algorithm(), params.algorithm);
+        assert_eq!(reencrypted_output.kdf_iterations, params.kdf_iterations);
+    }
+
+    #[test]
+    fn reencrypt_legacy_output_gets_random_kdf_salt() {
+        let output = encrypt_legacy("payload", "old_password");
+
+        let reencrypted_output = reencrypt(output, "old_password", "new_password")
+            .expect("Failed to re-encrypt payload");
+
+        assert!(reencrypted_output.kdf_salt.is_some());
+    }
+
+    #[test]
+    fn future_output_version_is_not_supported() {
+        let password = "password";
+        let mut output = encrypt("payload", password, None).expect("Failed to encrypt payload");
+        output.version = LATEST_OUTPUT_VERSION.saturating_add(1);
+
+        let errors = [
+            decrypt(output.clone(), password).err(),
+            KeyCache::new().decrypt(output.clone(), password).err(),
+            decrypt_stream(&output, password).err(),
+            migrate(output, password).err(),
+        ];
+
+        for error in errors {
+            assert!(
+                matches!(error, Some(Error::UnsupportedVersion(3))),
+                "{error:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn future_output_version_survives_serialization() {
+        let output = serde_json::json!({
+            "version": 3_u8,
+            "encrypted_payload": "cGF5bG9hZA",
+            "salt": "AQEBAQEBAQEBAQEB",
+        });
+
+        let deserialized_output: EncryptionOutput =
+            serde_json::from_value(output).expect("Failed to deserialize output");
+        assert_eq!(deserialized_output.version, 3);
+        let serialized_output =
+            serde_json::to_string(&deserialized_output).expect("Failed to serialize output");
+        assert!(
+            serialized_output.contains(r#""version":3"#),
+            "{serialized_output}"
+        );
+    }
+
+    #[test]
+    fn composed_and_decomposed_passwords_decrypt_new_output() {
+        let composed = "caf\u{e9}";
+        let decomposed = "cafe\u{301}";
+        assert_ne!(composed, decomposed);
+
+        for (encrypt_password, decrypt_password) in [(composed, decomposed), (decomposed, composed)]
+        {
+            let output =
+                encrypt("payload", encrypt_password, None).expect("Failed to encrypt payload");
+            assert_eq!(output.version, OUTPUT_VERSION_2);
+
+            let decrypted_payload =
+                decrypt(output, decrypt_password).expect("Failed to decrypt payload");
+            assert_eq!(decrypted_payload, "payload");
+        }
+    }
+
+    #[test]
+    fn legacy_output_uses_raw_password_bytes() {
+        let output = encrypt_legacy("payload", "caf\u{e9}");
+
+        decrypt(output.clone(), "cafe\u{301}")
+            .expect_err("Decryption with decomposed password is expected to fail");
+        let decrypted_payload = decrypt(output, "caf\u{e9}").expect("Failed to decrypt payload");
+        assert_eq!(decrypted_payload, "payload");
+    }
+
+    #[test]
+    fn migrate_upgrades_legacy_output_to_latest_version() {
+        let payload = "payload";
+        let password = "password";
+        let output = encrypt_legacy(payload, password);
+
+        let migrated_output = migrate(output, password).expect("Failed to migrate output");
+
+        assert_eq!(migrated_output.version, LATEST_OUTPUT_VERSION);
+        assert!(migrated_output.kdf_salt.is_some());
+        let decrypted_payload = decrypt(migrated_output, password).expect("Failed to decrypt");
+        assert_eq!(decrypted_payload, payload);
+    }
+
+    #[test]
+    fn migrate_with_wrong_password_fails() {
+        let output = encrypt_legacy("payload", "password");
+
+        let error = migrate(output, "wrong_password").expect_err("Migration is expected to fail");
+        assert!(matches!(error, Error::Decryption));
+    }
+
+    #[test]
+    fn migrate_with_aad_keeps_payload_bound() {
+        let payload = "payload";
+        let password = "password";
+        let output = encrypt_with_aad(payload, password, None, b"resource")
+            .expect("Failed to encrypt payload");
+
+        let migrated_output =
+            migrate_with_aad(output, password, b"resource").expect("Failed to migrate output");
+
+        decrypt(migrated_output.clone(), password)
+            .expect_err("Decryption without aad is expected to fail");
+        let decrypted_payload = decrypt_with_aad(migrated_output, password, b"resource")
+            .expect("Failed to decrypt payload");
+        assert_eq!(decrypted_payload, payload);
+    }
+
+    #[test]
+    fn reencrypt_with_aad_keeps_payload_bound() {
+        let output = encrypt_with_aad("payload", "old_password", None, b"bank.com")
+            .expect("Failed to encrypt payload");
+
+        reencrypt(output.clone(), "old_password", "new_password")
+            .expect_err("Re-encryption without aad is expected to fail");
+        let reencrypted_output =
+            reencrypt_with_aad(output, "old_password", "new_password", b"bank.com")
+                .expect("Failed to re-encrypt payload");
+
+        decrypt(reencrypted_output.clone(), "new_password")
+            .expect_err("Decryption without aad is expected to fail");
+        let decrypted_payload = decrypt_with_aad(reencrypted_output, "new_password", b"bank.com")
+            .expect("Failed to decrypt payload");
+        assert_eq!(decrypted_payload, "payload");
     }
 }
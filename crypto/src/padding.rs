@@ -0,0 +1,113 @@
+//! Module with padding of text payloads, see
+//! [`EncryptParams::pad_to`](crate::EncryptParams::pad_to).
+
+use zeroize::Zeroizing;
+
+use crate::{Error, Result};
+
+/// First byte of padded text payloads, see [`EncryptParams::pad_to`].
+///
+/// Never occurs in UTF-8, so unpadded text payloads never start with it.
+const PADDED_TEXT_MARKER: u8 = 0xff;
+
+/// Byte separating padded text from zeros of the padding, as in ISO/IEC 7816-4.
+const PADDING_DELIMITER: u8 = 0x80;
+
+/// Pad text `payload` to a multiple of `pad_to` bytes.
+///
+/// Padded payload is [`PADDED_TEXT_MARKER`], the text, [`PADDING_DELIMITER`] and zeros.
+/// Returns the text as is if `pad_to` is [`None`].
+pub fn pad_text(payload: &str, pad_to: Option<usize>) -> Zeroizing<Vec<u8>> {
+    let Some(pad_to) = pad_to else {
+        return Zeroizing::new(payload.as_bytes().to_vec());
+    };
+
+    let pad_to = pad_to.max(1);
+    let padded_len = payload
+        .len()
+        .saturating_add(2)
+        .div_ceil(pad_to)
+        .saturating_mul(pad_to);
+    let mut padded = Zeroizing::new(Vec::with_capacity(padded_len));
+    padded.push(PADDED_TEXT_MARKER);
+    padded.extend_from_slice(payload.as_bytes());
+    padded.push(PADDING_DELIMITER);
+    padded.resize(padded_len, 0);
+    padded
+}
+
+/// Parse decrypted `payload` as text stripping padding of [`pad_text()`] if any.
+///
+/// # Errors
+///
+/// - [`Error::CorruptedData`] if padding is malformed;
+/// - [`Error::Utf8`] if the text is not a valid UTF-8.
+pub fn decode_text(mut payload: Vec<u8>) -> Result<String> {
+    if payload.first() == Some(&PADDED_TEXT_MARKER) {
+        let text_end = payload
+            .iter()
+            .rposition(|&byte| byte != 0)
+            .filter(|&delimiter| payload.get(delimiter) == Some(&PADDING_DELIMITER))
+            .ok_or(Error::CorruptedData)?;
+        payload.truncate(text_end);
+        payload.remove(0);
+    }
+    String::from_utf8(payload).map_err(Error::Utf8)
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::expect_used, reason = "it's ok in tests")]
+
+    use super::*;
+    use crate::{decrypt, encrypt, encrypt_bytes, EncryptParams};
+
+    #[test]
+    fn padded_payloads_of_different_lengths_have_same_length() {
+        let params = EncryptParams {
+            pad_to: Some(64),
+            ..EncryptParams::default()
+        };
+
+        let short =
+            encrypt("a", "password", Some(params)).expect("Failed to encrypt short payload");
+        let long = encrypt("a-much-longer-password", "password", Some(params))
+            .expect("Failed to encrypt long payload");
+        assert_eq!(short.encrypted_payload.len(), long.encrypted_payload.len());
+    }
+
+    #[test]
+    fn encrypt_and_decrypt_round_trip_with_and_without_padding() {
+        let padded = EncryptParams {
+            pad_to: Some(64),
+            ..EncryptParams::default()
+        };
+
+        for params in [None, Some(padded)] {
+            for payload in [
+                "",
+                "a",
+                "a-much-longer-password",
+                &"x".repeat(62),
+                &"y".repeat(64),
+            ] {
+                let output =
+                    encrypt(payload, "password", params).expect("Failed to encrypt payload");
+                let decrypted_payload =
+                    decrypt(output, "password").expect("Failed to decrypt payload");
+                assert_eq!(payload, decrypted_payload);
+            }
+        }
+    }
+
+    #[test]
+    fn padding_without_delimiter_is_rejected() {
+        let output = encrypt_bytes(&[PADDED_TEXT_MARKER, b'a', 0, 0], "password", None, &[])
+            .expect("Failed to encrypt payload");
+
+        assert!(matches!(
+            decrypt(output, "password"),
+            Err(Error::CorruptedData)
+        ));
+    }
+}
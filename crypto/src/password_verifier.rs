@@ -0,0 +1,86 @@
+//! Module with [`PasswordVerifier`] implementation.
+
+use aes_gcm::{aead::OsRng, KeyInit};
+use pbkdf2::hmac::{Hmac, Mac as _};
+use sha2::Sha256;
+
+/// Cheap verifier of a password which was already checked with the full key derivation.
+///
+/// Allows to detect repeated attempts with the same password without running the slow key
+/// derivation again. Keeps only an HMAC of the password keyed with a random key, which is
+/// compared in constant time. Should live only in memory and only for a single session.
+pub struct PasswordVerifier {
+    /// Random HMAC key unique for this verifier.
+    key: pbkdf2::hmac::digest::Key<Hmac<Sha256>>,
+    /// HMAC of the password.
+    tag: Vec<u8>,
+}
+
+impl PasswordVerifier {
+    /// Create verifier of `password`.
+    #[must_use]
+    pub fn new(password: &str) -> Self {
+        let key = <Hmac<Sha256> as KeyInit>::generate_key(&mut OsRng);
+        let tag = Self::mac(&key, password).finalize().into_bytes().to_vec();
+        Self { key, tag }
+    }
+
+    /// Check if `candidate` is the same password this verifier was created with.
+    #[must_use]
+    pub fn matches(&self, candidate: &str) -> bool {
+        Self::mac(&self.key, candidate)
+            .verify_slice(&self.tag)
+            .is_ok()
+    }
+
+    /// Construct HMAC of `password` with `key`.
+    fn mac(key: &pbkdf2::hmac::digest::Key<Hmac<Sha256>>, password: &str) -> Hmac<Sha256> {
+        let mut mac = <Hmac<Sha256> as KeyInit>::new(key);
+        mac.update(password.as_bytes());
+        mac
+    }
+}
+
+impl core::fmt::Debug for PasswordVerifier {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PasswordVerifier").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn password_verifier_matches_same_password() {
+        for password in ["password", "", "\u{43f}\u{430}\u{440}\u{43e}\u{43b}\u{44c}"] {
+            assert!(PasswordVerifier::new(password).matches(password));
+        }
+    }
+
+    #[test]
+    fn password_verifier_does_not_match_other_passwords() {
+        let verifier = PasswordVerifier::new("password");
+
+        for candidate in ["Password", "password ", "passwor", "", "password\0"] {
+            assert!(!verifier.matches(candidate), "{candidate:?} matched");
+        }
+    }
+
+    #[test]
+    fn password_verifiers_of_same_password_differ() {
+        let first = PasswordVerifier::new("password");
+        let second = PasswordVerifier::new("password");
+
+        assert_ne!(first.tag, second.tag);
+        assert!(first.matches("password") && second.matches("password"));
+    }
+
+    #[test]
+    fn password_verifier_debug_hides_secrets() {
+        assert_eq!(
+            format!("{:?}", PasswordVerifier::new("password")),
+            "PasswordVerifier { .. }"
+        );
+    }
+}
@@ -0,0 +1,413 @@
+//! Splitting of the master password into shares with Shamir's secret sharing.
+//!
+//! Any `threshold` of the shares recover the password, while fewer tell nothing about it.
+//! Every byte of the password is the constant term of its own random polynomial over GF(2⁸)
+//! of degree `threshold - 1`, and a share holds values of all the polynomials at its index.
+
+use std::{fmt, string::FromUtf8Error};
+
+use aes_gcm::aead::{
+    rand_core::{CryptoRng, RngCore},
+    OsRng,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use zeroize::Zeroizing;
+
+use super::RedactedBytes;
+
+/// Size of the [`Share`] checksum in bytes.
+pub const CHECKSUM_SIZE: usize = 4;
+
+/// Checksum of a [`Share`], detects corrupted or mistyped shares.
+pub type Checksum = [u8; CHECKSUM_SIZE];
+
+/// One share of a split password, see [`split()`].
+///
+/// [`Debug`] output doesn't contain share bytes, see [`RedactedBytes`].
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Share {
+    /// Index of the share, starts from 1.
+    index: u8,
+    /// Number of shares required to recover the password.
+    threshold: u8,
+    /// Values of the password polynomials at `index`.
+    #[serde(with = "super::bytes")]
+    data: Vec<u8>,
+    /// Checksum of all the other fields.
+    checksum: Checksum,
+}
+
+impl Share {
+    /// Construct share computing its checksum.
+    fn new(index: u8, threshold: u8, data: Vec<u8>) -> Self {
+        let checksum = checksum(index, threshold, &data);
+        Self {
+            index,
+            threshold,
+            data,
+            checksum,
+        }
+    }
+
+    /// Index of the share, starts from 1.
+    #[must_use]
+    pub const fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Number of shares required to recover the password.
+    #[must_use]
+    pub const fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Checksum of the share.
+    #[must_use]
+    pub const fn checksum(&self) -> Checksum {
+        self.checksum
+    }
+
+    /// Check if the share is not corrupted.
+    fn is_intact(&self) -> bool {
+        self.index != 0 && self.checksum == checksum(self.index, self.threshold, &self.data)
+    }
+}
+
+impl fmt::Debug for Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Share")
+            .field("index", &self.index)
+            .field("threshold", &self.threshold)
+            .field("data", &RedactedBytes(&self.data))
+            .field("checksum", &self.checksum)
+            .finish()
+    }
+}
+
+/// Error of password splitting and recovery.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    #[error(
+        "Threshold must be positive and not greater than the number of shares, got {threshold} of {shares}"
+    )]
+    InvalidThreshold { threshold: u8, shares: u8 },
+    #[error("At least {threshold} shares are required to recover the password, got {provided}")]
+    NotEnoughShares { threshold: u8, provided: usize },
+    #[error("Share {0} is corrupted")]
+    CorruptedShare(u8),
+    #[error("Share {0} is provided more than once")]
+    DuplicateShare(u8),
+    #[error("Shares are from different splits")]
+    MismatchedShares,
+    #[error("Failed to parse recovered password as UTF-8")]
+    Utf8(FromUtf8Error),
+}
+
+/// Result of password splitting and recovery.
+pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+/// Split `password` into `shares` shares, any `threshold` of which recover it.
+///
+/// Not a pure function, because it uses random number generator to generate polynomials.
+///
+/// # Errors
+///
+/// [`Error::InvalidThreshold`] if `threshold` is zero or greater than `shares`.
+pub fn split(password: &str, threshold: u8, shares: u8) -> Result<Vec<Share>> {
+    split_with_rng(password, threshold, shares, &mut OsRng)
+}
+
+/// Same as [`split()`], but takes randomness from `rng` instead of the operating system.
+///
+/// # Errors
+///
+/// See [`split()`].
+pub fn split_with_rng<R: CryptoRng + RngCore>(
+    password: &str,
+    threshold: u8,
+    shares: u8,
+    rng: &mut R,
+) -> Result<Vec<Share>> {
+    if threshold == 0 || threshold > shares {
+        return Err(Error::InvalidThreshold { threshold, shares });
+    }
+
+    let mut data = (1..=shares)
+        .map(|_index| Vec::with_capacity(password.len()))
+        .collect::<Vec<_>>();
+    // Constant term is the password byte, so one less random coefficient is needed
+    let mut coefficients = Zeroizing::new(vec![0; usize::from(threshold.saturating_sub(1))]);
+    for byte in password.bytes() {
+        rng.fill_bytes(&mut coefficients);
+        for (index, share_data) in (1..=shares).zip(&mut data) {
+            // Horner's method, highest degree coefficient first
+            let value = coefficients
+                .iter()
+                .rev()
+                .copied()
+                .chain(std::iter::once(byte))
+                .fold(0, |value, coefficient| gf_mul(value, index) ^ coefficient);
+            share_data.push(value);
+        }
+    }
+
+    Ok((1..=shares)
+        .zip(data)
+        .map(|(index, share_data)| Share::new(index, threshold, share_data))
+        .collect())
+}
+
+/// Recover password from `shares` produced by [`split()`].
+///
+/// Only the first [`Share::threshold()`] shares are used, the rest are only checked to be intact.
+///
+/// # Errors
+///
+/// - [`Error::CorruptedShare`] if checksum of any share doesn't match;
+/// - [`Error::MismatchedShares`] if shares have different thresholds or lengths;
+/// - [`Error::DuplicateShare`] if the same share index is provided twice;
+/// - [`Error::NotEnoughShares`] if there are less shares than the threshold;
+/// - [`Error::Utf8`] if recovered password is not a valid UTF-8, e.g. if shares of different
+///   passwords with the same threshold and length are mixed.
+pub fn recover(shares: &[Share]) -> Result<String> {
+    let Some(first) = shares.first() else {
+        return Err(Error::NotEnoughShares {
+            threshold: 1,
+            provided: 0,
+        });
+    };
+
+    let mut indices = std::collections::BTreeSet::new();
+    for share in shares {
+        if !share.is_intact() {
+            return Err(Error::CorruptedShare(share.index));
+        }
+        if share.threshold != first.threshold || share.data.len() != first.data.len() {
+            return Err(Error::MismatchedShares);
+        }
+        if !indices.insert(share.index) {
+            return Err(Error::DuplicateShare(share.index));
+        }
+    }
+
+    let used_shares = shares
+        .get(..usize::from(first.threshold))
+        .ok_or(Error::NotEnoughShares {
+            threshold: first.threshold,
+            provided: shares.len(),
+        })?;
+
+    // Lagrange basis polynomials at zero, subtraction in GF(2⁸) is XOR
+    let basis = used_shares
+        .iter()
+        .map(|share| {
+            used_shares
+                .iter()
+                .filter(|other| other.index != share.index)
+                .fold(1, |product, other| {
+                    gf_mul(
+                        product,
+                        gf_mul(other.index, gf_inverse(other.index ^ share.index)),
+                    )
+                })
+        })
+        .collect::<Vec<_>>();
+
+    let password = (0..first.data.len())
+        .map(|position| {
+            used_shares
+                .iter()
+                .zip(&basis)
+                .fold(0, |byte, (share, basis_value)| {
+                    let value = share.data.get(position).copied().unwrap_or_default();
+                    byte ^ gf_mul(value, *basis_value)
+                })
+        })
+        .collect();
+
+    String::from_utf8(password).map_err(Error::Utf8)
+}
+
+/// Compute checksum of share fields.
+fn checksum(index: u8, threshold: u8, data: &[u8]) -> Checksum {
+    let digest = Sha256::new()
+        .chain_update([index, threshold])
+        .chain_update(data)
+        .finalize();
+
+    let mut checksum = [0; CHECKSUM_SIZE];
+    for (checksum_byte, digest_byte) in checksum.iter_mut().zip(digest) {
+        *checksum_byte = digest_byte;
+    }
+    checksum
+}
+
+/// Multiply in GF(2⁸) with the AES reduction polynomial.
+///
+/// Has no data-dependent branches, so that timing doesn't leak password bytes.
+fn gf_mul(mut lhs: u8, mut rhs: u8) -> u8 {
+    let mut product = 0;
+    for _bit in 0..8_u8 {
+        product ^= lhs & (rhs & 1).wrapping_neg();
+        let carry = (lhs >> 7_u8).wrapping_neg();
+        lhs = (lhs << 1_u8) ^ (0x1b & carry);
+        rhs >>= 1_u8;
+    }
+    product
+}
+
+/// Multiplicative inverse in GF(2⁸), which is `value²⁵⁴`.
+///
+/// Zero has no inverse and is mapped to itself.
+fn gf_inverse(value: u8) -> u8 {
+    let mut square = value;
+    let mut inverse = 1;
+    // 254 = 2 + 4 + ... + 128
+    for _bit in 1..8_u8 {
+        square = gf_mul(square, square);
+        inverse = gf_mul(inverse, square);
+    }
+    inverse
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(
+        clippy::expect_used,
+        clippy::indexing_slicing,
+        reason = "it's ok in tests"
+    )]
+
+    use rand_chacha::{rand_core::SeedableRng as _, ChaCha20Rng};
+
+    use super::*;
+
+    const PASSWORD: &str = "correct horse battery staple \u{1f40e}";
+
+    fn sample_shares(threshold: u8, shares: u8) -> Vec<Share> {
+        split_with_rng(
+            PASSWORD,
+            threshold,
+            shares,
+            &mut ChaCha20Rng::seed_from_u64(42),
+        )
+        .expect("valid threshold")
+    }
+
+    #[test]
+    fn gf_inverse_is_inverse() {
+        for value in 1..=u8::MAX {
+            assert_eq!(gf_mul(value, gf_inverse(value)), 1, "value {value}");
+        }
+    }
+
+    #[test]
+    fn any_threshold_shares_recover_password() {
+        let shares = sample_shares(2, 3);
+        assert_eq!(shares.len(), 3);
+
+        for (first, second) in [(0, 1), (0, 2), (1, 2), (2, 0)] {
+            let pair = [shares[first].clone(), shares[second].clone()];
+            assert_eq!(recover(&pair).expect("recoverable"), PASSWORD);
+        }
+        assert_eq!(recover(&shares).expect("recoverable"), PASSWORD);
+    }
+
+    #[test]
+    fn split_uses_operating_system_randomness() {
+        let shares = split(PASSWORD, 3, 5).expect("valid threshold");
+
+        assert_ne!(shares, split(PASSWORD, 3, 5).expect("valid threshold"));
+        assert_eq!(recover(&shares[2..]).expect("recoverable"), PASSWORD);
+    }
+
+    #[test]
+    fn single_share_tells_nothing_about_password() {
+        let shares = sample_shares(2, 3);
+
+        for share in &shares {
+            assert_ne!(share.data, PASSWORD.as_bytes());
+        }
+        assert!(!format!("{shares:?}").contains(&format!("{:?}", shares[0].data)));
+    }
+
+    #[test]
+    fn too_few_shares_fail() {
+        let shares = sample_shares(3, 5);
+
+        assert_eq!(
+            recover(&shares[..2]),
+            Err(Error::NotEnoughShares {
+                threshold: 3,
+                provided: 2
+            })
+        );
+        assert_eq!(
+            recover(&[]),
+            Err(Error::NotEnoughShares {
+                threshold: 1,
+                provided: 0
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_threshold_fails() {
+        for (threshold, shares) in [(0, 3), (4, 3), (0, 0)] {
+            assert_eq!(
+                split(PASSWORD, threshold, shares),
+                Err(Error::InvalidThreshold { threshold, shares })
+            );
+        }
+    }
+
+    #[test]
+    fn threshold_of_one_gives_password_copies() {
+        let shares = sample_shares(1, 2);
+
+        assert_eq!(recover(&shares[1..]).expect("recoverable"), PASSWORD);
+    }
+
+    #[test]
+    fn corrupted_share_fails() {
+        let mut shares = sample_shares(2, 3);
+        shares[1].data[0] ^= 1;
+
+        assert_eq!(recover(&shares), Err(Error::CorruptedShare(2)));
+    }
+
+    #[test]
+    fn duplicate_share_fails() {
+        let shares = sample_shares(2, 3);
+
+        assert_eq!(
+            recover(&[shares[0].clone(), shares[0].clone()]),
+            Err(Error::DuplicateShare(1))
+        );
+    }
+
+    #[test]
+    fn shares_of_different_splits_fail() {
+        let shares = sample_shares(2, 3);
+        let other_shares = split("other", 3, 3).expect("valid threshold");
+
+        assert_eq!(
+            recover(&[shares[0].clone(), other_shares[1].clone()]),
+            Err(Error::MismatchedShares)
+        );
+    }
+
+    #[test]
+    fn share_round_trips_through_json() {
+        let shares = sample_shares(2, 3);
+
+        let json = serde_json::to_string(&shares).expect("serializable");
+        let deserialized: Vec<Share> = serde_json::from_str(&json).expect("deserializable");
+
+        assert_eq!(deserialized, shares);
+        assert_eq!(deserialized[0].index(), 1);
+        assert_eq!(deserialized[0].threshold(), 2);
+        assert_eq!(recover(&deserialized[1..]).expect("recoverable"), PASSWORD);
+    }
+}
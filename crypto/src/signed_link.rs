@@ -0,0 +1,295 @@
+//! Signing of temporary links to records.
+//!
+//! A link is signed with an HMAC-SHA256 of all its query parameters including the
+//! [`EXPIRATION_PARAM`], so that none of them can be changed and the link can't be used after
+//! it expires.
+//!
+//! # Trust model
+//!
+//! HMAC is symmetric, so the key verifying links is the key signing them. It's embedded into the
+//! Web App, which is served publicly, so anyone inspecting the Web App can forge a link.
+//! Signatures only stop casual tampering with links and extension of their expiration time.
+//! They don't protect the record any further: its confidentiality relies on the encryption with
+//! the master password only, so a leaked link is as sensitive as the encrypted record itself.
+
+use base64::{engine::general_purpose::URL_SAFE, Engine as _};
+use pbkdf2::hmac::{digest::KeyInit, Hmac, Mac};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+/// Name of the query parameter with the Unix timestamp the link expires at.
+pub const EXPIRATION_PARAM: &str = "exp";
+
+/// Name of the query parameter with the signature of the link.
+pub const SIGNATURE_PARAM: &str = "sig";
+
+/// Error of link signing or verification.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    #[error("Link key is empty")]
+    EmptyKey,
+    #[error("Failed to decode link key: {0}")]
+    KeyDecoding(#[from] base64::DecodeError),
+    #[error("Link is not signed")]
+    MissingSignature,
+    #[error("Link has no expiration time")]
+    MissingExpiration,
+    #[error("Link expiration time is malformed")]
+    MalformedExpiration,
+    #[error("Link signature doesn't match")]
+    InvalidSignature,
+    #[error("Link has expired at {0}")]
+    Expired(u64),
+}
+
+/// Result of link signing or verification.
+pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+/// Key to sign and verify links with.
+#[derive(Clone)]
+pub struct LinkKey(Hmac<Sha256>);
+
+impl LinkKey {
+    /// Construct new [`LinkKey`] from raw `bytes`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `bytes` are empty.
+    pub fn new(bytes: &[u8]) -> Result<Self> {
+        if bytes.is_empty() {
+            return Err(Error::EmptyKey);
+        }
+        <Hmac<Sha256> as KeyInit>::new_from_slice(bytes)
+            .map(Self)
+            .map_err(|_err| Error::EmptyKey)
+    }
+
+    /// Decode [`LinkKey`] from URL-safe base64 `encoded` one.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `encoded` is not a valid base64 or it's empty.
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        Self::new(&Zeroizing::new(URL_SAFE.decode(encoded.trim())?))
+    }
+
+    /// Get parameters to append to the link with `params` to make it valid until `expires_at`.
+    ///
+    /// `expires_at` is a Unix timestamp in seconds.
+    #[must_use]
+    pub fn sign<'param>(
+        &self,
+        params: impl IntoIterator<Item = (&'param str, &'param str)>,
+        expires_at: u64,
+    ) -> [(&'static str, String); 2] {
+        let expiration = expires_at.to_string();
+        let mut signed: Vec<(&str, &str)> = params.into_iter().collect();
+        signed.push((EXPIRATION_PARAM, &expiration));
+        let signature = self.mac(signed).finalize().into_bytes();
+
+        [
+            (EXPIRATION_PARAM, expiration),
+            (SIGNATURE_PARAM, URL_SAFE.encode(signature)),
+        ]
+    }
+
+    /// Verify that the link with `params` is signed with this key and is not expired at `now`.
+    ///
+    /// `params` should contain all query parameters of the link, including [`EXPIRATION_PARAM`]
+    /// and [`SIGNATURE_PARAM`]. `now` is a Unix timestamp in seconds.
+    ///
+    /// # Errors
+    ///
+    /// Fails if:
+    /// - Signature or expiration time is missing;
+    /// - Signature doesn't match the parameters, which means the link is tampered with;
+    /// - Link has expired.
+    pub fn verify<'param>(
+        &self,
+        params: impl IntoIterator<Item = (&'param str, &'param str)>,
+        now: u64,
+    ) -> Result<()> {
+        let (signatures, signed): (Vec<_>, Vec<_>) = params
+            .into_iter()
+            .partition(|&(name, _value)| name == SIGNATURE_PARAM);
+        let &[(_, signature)] = signatures.as_slice() else {
+            return Err(Error::MissingSignature);
+        };
+        let signature = URL_SAFE
+            .decode(signature)
+            .map_err(|_err| Error::InvalidSignature)?;
+
+        let mut expirations = signed
+            .iter()
+            .filter(|&&(name, _value)| name == EXPIRATION_PARAM)
+            .map(|&(_name, value)| value);
+        let (Some(expiration), None) = (expirations.next(), expirations.next()) else {
+            return Err(Error::MissingExpiration);
+        };
+
+        self.mac(signed.clone())
+            .verify_slice(&signature)
+            .map_err(|_err| Error::InvalidSignature)?;
+
+        let expires_at = expiration
+            .parse()
+            .map_err(|_err| Error::MalformedExpiration)?;
+        if now >= expires_at {
+            return Err(Error::Expired(expires_at));
+        }
+        Ok(())
+    }
+
+    /// Get HMAC updated with canonical representation of `params`.
+    ///
+    /// Parameters are sorted, so that their order in the link doesn't matter, and length-prefixed,
+    /// so that different parameters can't produce the same representation.
+    fn mac(&self, mut params: Vec<(&str, &str)>) -> Hmac<Sha256> {
+        params.sort_unstable();
+
+        params
+            .into_iter()
+            .fold(self.0.clone(), |mac, (name, value)| {
+                mac.chain_update(format!("{}:{name}{}:{value}", name.len(), value.len()))
+            })
+    }
+}
+
+impl std::fmt::Debug for LinkKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("LinkKey(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    const EXPIRES_AT: u64 = NOW + 15 * 60;
+
+    const PARAMS: [(&str, &str); 2] = [("payload", "cGF5bG9hZA=="), ("salt", "AQEBAQEBAQEBAQEB")];
+
+    fn key() -> LinkKey {
+        LinkKey::new(b"link key").unwrap()
+    }
+
+    /// Get `params` with expiration time and signature of `PARAMS` signed by `key`.
+    fn link<'param>(
+        key: &LinkKey,
+        params: impl IntoIterator<Item = (&'param str, &'param str)>,
+    ) -> Vec<(String, String)> {
+        params
+            .into_iter()
+            .map(|(name, value)| (name.to_owned(), value.to_owned()))
+            .chain(
+                key.sign(PARAMS, EXPIRES_AT)
+                    .map(|(name, value)| (name.to_owned(), value)),
+            )
+            .collect()
+    }
+
+    fn verify(key: &LinkKey, params: &[(String, String)], now: u64) -> Result<()> {
+        key.verify(
+            params
+                .iter()
+                .map(|param| (param.0.as_str(), param.1.as_str())),
+            now,
+        )
+    }
+
+    #[test]
+    fn signed_link_is_valid_in_any_order_until_expiration() {
+        let key = key();
+        let mut params = link(&key, PARAMS);
+
+        verify(&key, &params, NOW).unwrap();
+        params.reverse();
+        verify(&key, &params, EXPIRES_AT.checked_sub(1).unwrap()).unwrap();
+
+        assert_eq!(
+            verify(&key, &params, EXPIRES_AT),
+            Err(Error::Expired(EXPIRES_AT))
+        );
+    }
+
+    #[test]
+    fn tampered_link_is_rejected() {
+        let key = key();
+
+        let replaced = link(&key, [PARAMS[0], ("salt", "AgICAgICAgICAgIC")]);
+        assert_eq!(verify(&key, &replaced, NOW), Err(Error::InvalidSignature));
+
+        let added = link(
+            &key,
+            PARAMS
+                .into_iter()
+                .chain([("bound_to_resource_name", "true")]),
+        );
+        assert_eq!(verify(&key, &added, NOW), Err(Error::InvalidSignature));
+
+        // Parameter boundaries are part of the signature
+        let shifted = link(&key, [("payloadc", "GF5bG9hZA=="), PARAMS[1]]);
+        assert_eq!(verify(&key, &shifted, NOW), Err(Error::InvalidSignature));
+
+        let other_key = LinkKey::new(b"other key").unwrap();
+        assert_eq!(
+            verify(&other_key, &link(&key, PARAMS), NOW),
+            Err(Error::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn extended_link_is_rejected() {
+        let key = key();
+        let extended: Vec<_> = link(&key, PARAMS)
+            .into_iter()
+            .map(|(name, value)| {
+                if name == EXPIRATION_PARAM {
+                    (name, NOW.saturating_add(24 * 60 * 60).to_string())
+                } else {
+                    (name, value)
+                }
+            })
+            .collect();
+
+        assert_eq!(verify(&key, &extended, NOW), Err(Error::InvalidSignature));
+    }
+
+    #[test]
+    fn unsigned_link_is_rejected() {
+        let key = key();
+        let params = link(&key, PARAMS);
+        let without = |param: &str| -> Vec<_> {
+            params
+                .iter()
+                .filter(|link_param| link_param.0 != param)
+                .cloned()
+                .collect()
+        };
+
+        assert_eq!(
+            verify(&key, &without(SIGNATURE_PARAM), NOW),
+            Err(Error::MissingSignature)
+        );
+        assert_eq!(
+            verify(&key, &without(EXPIRATION_PARAM), NOW),
+            Err(Error::MissingExpiration)
+        );
+    }
+
+    #[test]
+    fn key_is_decoded_from_base64() {
+        let key = LinkKey::from_base64(" bGluayBrZXk= ").unwrap();
+        verify(&key, &link(&self::key(), PARAMS), NOW).unwrap();
+
+        assert!(matches!(
+            LinkKey::from_base64("not base64!"),
+            Err(Error::KeyDecoding(_))
+        ));
+        assert_eq!(LinkKey::from_base64("").unwrap_err(), Error::EmptyKey);
+    }
+}
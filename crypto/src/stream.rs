@@ -0,0 +1,408 @@
+//! Module with encryption of payloads split into chunks and their streaming decryption.
+
+use aes_gcm::{
+    aead::{
+        generic_array::GenericArray,
+        rand_core::RngCore as _,
+        stream::{DecryptorBE32, EncryptorBE32},
+        OsRng,
+    },
+    AeadCore as _, Aes256Gcm,
+};
+use chacha20poly1305::XChaCha20Poly1305;
+use zeroize::Zeroizing;
+
+use crate::{
+    commit_to_key, derive_key, verify_key, Algorithm, EncryptParams, EncryptionOutput, Error,
+    KdfSalt, Key, Result, Salt, LATEST_OUTPUT_VERSION,
+};
+
+/// Number of salt bytes at the end not used as a nonce prefix by [`encrypt_chunks()`].
+///
+/// Every chunk nonce consists of the prefix, 32-bit big-endian chunk counter and
+/// a flag of the last chunk.
+const STREAM_NONCE_OVERHEAD: usize = 5;
+
+/// Size of the big-endian length of every frame of [`encrypt_chunks()`] output.
+const FRAME_LENGTH_SIZE: usize = 4;
+
+/// Encrypt payload split into `chunks` with password without joining them.
+///
+/// Every chunk is encrypted with its own nonce derived from the salt and the chunk number,
+/// and the last one is marked as such, so reordered, dropped or truncated chunks fail to
+/// decrypt. Encrypted payload of the output is a sequence of frames, each one is a chunk
+/// prefixed with its length, which can be decrypted only with [`decrypt_stream()`].
+///
+/// Uses [`EncryptParams::default()`] if `params` are not provided. See
+/// [`STREAM_CHUNK_SIZE`](crate::STREAM_CHUNK_SIZE) for the recommended chunk size.
+///
+/// # Errors
+///
+/// - [`Error::ZeroKdfIterations`] if `params` have zero key derivation iterations;
+/// - [`Error::Encryption`] if any chunk is longer than 4 GiB;
+/// - Any error from underlying libraries.
+pub fn encrypt_chunks<I>(
+    chunks: I,
+    password: &str,
+    params: Option<EncryptParams>,
+) -> Result<EncryptionOutput>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let EncryptParams {
+        kdf_iterations,
+        algorithm,
+        ..
+    } = params.unwrap_or_default();
+    let mut kdf_salt = KdfSalt::default();
+    OsRng.fill_bytes(&mut kdf_salt);
+
+    let key = Zeroizing::new(derive_key(
+        password,
+        LATEST_OUTPUT_VERSION,
+        Some(&kdf_salt),
+        kdf_iterations,
+    )?);
+    let salt = match algorithm {
+        Algorithm::Aes256Gcm => Salt::Aes256Gcm(Aes256Gcm::generate_nonce(&mut OsRng).into()),
+        Algorithm::XChaCha20Poly1305 => {
+            Salt::XChaCha20Poly1305(XChaCha20Poly1305::generate_nonce(&mut OsRng).into())
+        }
+    };
+    let mut encryptor = StreamEncryptor::new(&key, &salt)?;
+
+    let mut encrypted_payload = Vec::new();
+    let mut chunks = chunks.into_iter().peekable();
+    loop {
+        let chunk = chunks.next();
+        let chunk = chunk.as_ref().map_or(&[][..], AsRef::as_ref);
+        if chunks.peek().is_none() {
+            push_frame(&mut encrypted_payload, &encryptor.encrypt_last(chunk)?)?;
+            break;
+        }
+        push_frame(&mut encrypted_payload, &encryptor.encrypt_next(chunk)?)?;
+    }
+
+    Ok(EncryptionOutput {
+        version: LATEST_OUTPUT_VERSION,
+        encrypted_payload,
+        salt,
+        kdf_iterations,
+        kdf_salt: Some(kdf_salt),
+        key_commitment: Some(commit_to_key(&key)?),
+    })
+}
+
+/// Decrypt output of [`encrypt_chunks()`] with password chunk by chunk.
+///
+/// Key is derived eagerly, while chunks are decrypted only when the returned iterator is
+/// advanced. Iterator yields [`Error::CorruptedData`] ([`Error::Decryption`] if the output has
+/// no key commitment) and stops if any chunk is tampered with, or the payload is truncated or
+/// extended.
+///
+/// # Errors
+///
+/// - [`Error::UnsupportedVersion`] if the output is of a newer version than
+///   [`LATEST_OUTPUT_VERSION`];
+/// - [`Error::ZeroKdfIterations`] if `kdf_iterations` is zero;
+/// - [`Error::WrongPassword`] if `password` doesn't match the key commitment;
+/// - Any error from underlying libraries.
+pub fn decrypt_stream<'output>(
+    output: &'output EncryptionOutput,
+    password: &str,
+) -> Result<DecryptedChunks<'output>> {
+    let key = Zeroizing::new(derive_key(
+        password,
+        output.version,
+        output.kdf_salt.as_ref(),
+        output.kdf_iterations,
+    )?);
+    let failure = verify_key(&key, output.key_commitment.as_ref())?;
+
+    Ok(DecryptedChunks {
+        decryptor: Some(StreamDecryptor::new(&key, &output.salt)?),
+        frames: &output.encrypted_payload,
+        failure,
+    })
+}
+
+/// Iterator over decrypted chunks of the payload returned by [`decrypt_stream()`].
+pub struct DecryptedChunks<'output> {
+    /// Decryptor of the next chunk, [`None`] after the last chunk or an error.
+    decryptor: Option<StreamDecryptor>,
+    /// Frames which are not decrypted yet.
+    frames: &'output [u8],
+    /// Error to yield if a chunk fails to decrypt, see [`verify_key()`].
+    failure: Error,
+}
+
+impl<'output> DecryptedChunks<'output> {
+    /// Cut the next frame from the rest.
+    ///
+    /// Returns [`None`] if frames are malformed.
+    #[expect(clippy::big_endian_bytes, reason = "frame length is big-endian")]
+    fn next_frame(&mut self) -> Option<&'output [u8]> {
+        let (length, rest) = self.frames.split_first_chunk::<FRAME_LENGTH_SIZE>()?;
+        let length = usize::try_from(u32::from_be_bytes(*length)).ok()?;
+        let (frame, rest) = rest.split_at_checked(length)?;
+        self.frames = rest;
+        Some(frame)
+    }
+}
+
+impl Iterator for DecryptedChunks<'_> {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut decryptor = self.decryptor.take()?;
+        let Some(frame) = self.next_frame() else {
+            return Some(Err(self.failure.clone()));
+        };
+
+        if self.frames.is_empty() {
+            return Some(
+                decryptor
+                    .decrypt_last(frame)
+                    .map_err(|_err| self.failure.clone()),
+            );
+        }
+        let chunk = decryptor
+            .decrypt_next(frame)
+            .map_err(|_err| self.failure.clone());
+        if chunk.is_ok() {
+            self.decryptor = Some(decryptor);
+        }
+        Some(chunk)
+    }
+}
+
+impl core::fmt::Debug for DecryptedChunks<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DecryptedChunks")
+            .field("remaining_bytes", &self.frames.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Append `frame` prefixed with its length to `frames`.
+#[expect(clippy::big_endian_bytes, reason = "frame length is big-endian")]
+fn push_frame(frames: &mut Vec<u8>, frame: &[u8]) -> Result<()> {
+    let length = u32::try_from(frame.len()).map_err(|_err| Error::Encryption)?;
+    frames.extend_from_slice(&length.to_be_bytes());
+    frames.extend_from_slice(frame);
+    Ok(())
+}
+
+/// Construct nonce prefix of a stream from `salt`.
+fn stream_nonce<N: aes_gcm::aead::generic_array::ArrayLength<u8>>(
+    salt: &Salt,
+) -> Option<GenericArray<u8, N>> {
+    let bytes = salt.as_bytes();
+    let prefix_size = bytes.len().checked_sub(STREAM_NONCE_OVERHEAD)?;
+    GenericArray::from_exact_iter(bytes.iter().copied().take(prefix_size))
+}
+
+/// STREAM encryptor of any [`Algorithm`].
+enum StreamEncryptor {
+    /// Encryptor of [`Algorithm::Aes256Gcm`].
+    Aes256Gcm(EncryptorBE32<Aes256Gcm>),
+    /// Encryptor of [`Algorithm::XChaCha20Poly1305`].
+    XChaCha20Poly1305(EncryptorBE32<XChaCha20Poly1305>),
+}
+
+impl StreamEncryptor {
+    /// Construct encryptor with `key` and nonce prefix taken from `salt`.
+    fn new(key: &Key, salt: &Salt) -> Result<Self> {
+        let error = || Error::Encryption;
+        Ok(match *salt {
+            Salt::Aes256Gcm(_) => Self::Aes256Gcm(EncryptorBE32::new(
+                key.into(),
+                &stream_nonce(salt).ok_or_else(error)?,
+            )),
+            Salt::XChaCha20Poly1305(_) => Self::XChaCha20Poly1305(EncryptorBE32::new(
+                key.into(),
+                &stream_nonce(salt).ok_or_else(error)?,
+            )),
+        })
+    }
+
+    /// Encrypt a chunk which is not the last one.
+    fn encrypt_next(&mut self, chunk: &[u8]) -> Result<Vec<u8>> {
+        match *self {
+            Self::Aes256Gcm(ref mut encryptor) => encryptor.encrypt_next(chunk),
+            Self::XChaCha20Poly1305(ref mut encryptor) => encryptor.encrypt_next(chunk),
+        }
+        .map_err(|_err| Error::Encryption)
+    }
+
+    /// Encrypt the last chunk.
+    fn encrypt_last(self, chunk: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::Aes256Gcm(encryptor) => encryptor.encrypt_last(chunk),
+            Self::XChaCha20Poly1305(encryptor) => encryptor.encrypt_last(chunk),
+        }
+        .map_err(|_err| Error::Encryption)
+    }
+}
+
+/// STREAM decryptor of any [`Algorithm`].
+enum StreamDecryptor {
+    /// Decryptor of [`Algorithm::Aes256Gcm`].
+    Aes256Gcm(DecryptorBE32<Aes256Gcm>),
+    /// Decryptor of [`Algorithm::XChaCha20Poly1305`].
+    XChaCha20Poly1305(DecryptorBE32<XChaCha20Poly1305>),
+}
+
+impl StreamDecryptor {
+    /// Construct decryptor with `key` and nonce prefix taken from `salt`.
+    fn new(key: &Key, salt: &Salt) -> Result<Self> {
+        let error = || Error::Decryption;
+        Ok(match *salt {
+            Salt::Aes256Gcm(_) => Self::Aes256Gcm(DecryptorBE32::new(
+                key.into(),
+                &stream_nonce(salt).ok_or_else(error)?,
+            )),
+            Salt::XChaCha20Poly1305(_) => Self::XChaCha20Poly1305(DecryptorBE32::new(
+                key.into(),
+                &stream_nonce(salt).ok_or_else(error)?,
+            )),
+        })
+    }
+
+    /// Decrypt a chunk which is not the last one.
+    fn decrypt_next(&mut self, frame: &[u8]) -> Result<Vec<u8>> {
+        match *self {
+            Self::Aes256Gcm(ref mut decryptor) => decryptor.decrypt_next(frame),
+            Self::XChaCha20Poly1305(ref mut decryptor) => decryptor.decrypt_next(frame),
+        }
+        .map_err(|_err| Error::Decryption)
+    }
+
+    /// Decrypt the last chunk.
+    fn decrypt_last(self, frame: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::Aes256Gcm(decryptor) => decryptor.decrypt_last(frame),
+            Self::XChaCha20Poly1305(decryptor) => decryptor.decrypt_last(frame),
+        }
+        .map_err(|_err| Error::Decryption)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::expect_used, reason = "it's ok in tests")]
+
+    use super::*;
+
+    /// Size of a frame of [`STREAM_CHUNKS`] in encrypted payload.
+    const STREAM_FRAME_SIZE: usize = 4 + 4 + 16;
+
+    /// Chunks of equal size to tamper with their frames.
+    const STREAM_CHUNKS: [&[u8]; 3] = [b"aaaa", b"bbbb", b"cccc"];
+
+    /// Decrypt all chunks of the stream and join them.
+    fn decrypt_joined(output: &EncryptionOutput, password: &str) -> Result<Vec<u8>> {
+        decrypt_stream(output, password)?
+            .collect::<Result<Vec<_>>>()
+            .map(|chunks| chunks.concat())
+    }
+
+    #[test]
+    fn encrypt_chunks_and_decrypt_stream_work() {
+        let password = "password";
+
+        for algorithm in [Algorithm::Aes256Gcm, Algorithm::XChaCha20Poly1305] {
+            let params = EncryptParams {
+                algorithm,
+                ..EncryptParams::default()
+            };
+            let output = encrypt_chunks(STREAM_CHUNKS, password, Some(params))
+                .expect("Failed to encrypt chunks");
+            assert_eq!(output.algorithm(), algorithm);
+
+            let chunks = decrypt_stream(&output, password)
+                .expect("Failed to derive key")
+                .collect::<Result<Vec<_>>>()
+                .expect("Failed to decrypt stream");
+            assert_eq!(chunks, STREAM_CHUNKS);
+        }
+    }
+
+    #[test]
+    fn encrypt_no_chunks_gives_single_empty_chunk() {
+        let password = "password";
+
+        let output = encrypt_chunks(core::iter::empty::<&[u8]>(), password, None)
+            .expect("Failed to encrypt");
+        let chunks = decrypt_stream(&output, password)
+            .expect("Failed to derive key")
+            .collect::<Result<Vec<_>>>()
+            .expect("Failed to decrypt stream");
+
+        assert_eq!(chunks, [Vec::<u8>::new()]);
+    }
+
+    #[test]
+    fn decrypt_stream_with_wrong_password_fails() {
+        let output = encrypt_chunks(STREAM_CHUNKS, "password", None).expect("Failed to encrypt");
+
+        decrypt_joined(&output, "wrong_password").expect_err("Decryption is expected to fail");
+    }
+
+    #[test]
+    fn truncated_stream_fails_to_decrypt() {
+        let password = "password";
+        let mut output = encrypt_chunks(STREAM_CHUNKS, password, None).expect("Failed to encrypt");
+
+        output.encrypted_payload.truncate(2 * STREAM_FRAME_SIZE);
+        decrypt_joined(&output, password).expect_err("Decryption is expected to fail");
+
+        output.encrypted_payload.truncate(STREAM_FRAME_SIZE - 1);
+        decrypt_joined(&output, password).expect_err("Decryption is expected to fail");
+    }
+
+    #[test]
+    fn reordered_stream_fails_to_decrypt() {
+        let password = "password";
+        let mut output = encrypt_chunks(STREAM_CHUNKS, password, None).expect("Failed to encrypt");
+
+        output
+            .encrypted_payload
+            .get_mut(..2 * STREAM_FRAME_SIZE)
+            .expect("Payload is shorter than two frames")
+            .rotate_left(STREAM_FRAME_SIZE);
+
+        decrypt_joined(&output, password).expect_err("Decryption is expected to fail");
+    }
+
+    #[test]
+    fn extended_stream_fails_to_decrypt() {
+        let password = "password";
+        let mut output = encrypt_chunks(STREAM_CHUNKS, password, None).expect("Failed to encrypt");
+
+        let first_frame = output
+            .encrypted_payload
+            .get(..STREAM_FRAME_SIZE)
+            .expect("Payload is shorter than a frame")
+            .to_vec();
+        output.encrypted_payload.extend(first_frame);
+
+        decrypt_joined(&output, password).expect_err("Decryption is expected to fail");
+    }
+
+    #[test]
+    fn decrypted_chunks_stop_after_error() {
+        let password = "password";
+        let mut output = encrypt_chunks(STREAM_CHUNKS, password, None).expect("Failed to encrypt");
+        output.encrypted_payload.truncate(2 * STREAM_FRAME_SIZE);
+
+        let mut chunks = decrypt_stream(&output, password).expect("Failed to derive key");
+        assert_eq!(
+            chunks.next().map(Result::ok),
+            Some(Some(STREAM_CHUNKS[0].to_vec()))
+        );
+        assert!(matches!(chunks.next(), Some(Err(Error::CorruptedData))));
+        assert!(chunks.next().is_none());
+    }
+}
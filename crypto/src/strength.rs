@@ -0,0 +1,291 @@
+//! Estimation of password strength.
+//!
+//! Doesn't need the `impls` feature, so that the Web App can show it while the user types.
+
+/// Maximal [`Strength::score`].
+pub const MAX_SCORE: u8 = 4;
+
+/// Passwords shorter than this number of characters get [`Warning::TooShort`].
+pub const MIN_LENGTH: usize = 8;
+
+/// Minimal length of a run of the same character or of a sequence to get a warning.
+const MIN_PATTERN_LENGTH: usize = 3;
+
+/// Lowercase passwords guessed first regardless of their entropy.
+const COMMON_PASSWORDS: [&str; 32] = [
+    "000000",
+    "111111",
+    "123123",
+    "123456",
+    "1234567",
+    "12345678",
+    "123456789",
+    "1234567890",
+    "123qwe",
+    "1q2w3e4r",
+    "555555",
+    "654321",
+    "7777777",
+    "888888",
+    "abc123",
+    "admin",
+    "dragon",
+    "football",
+    "iloveyou",
+    "letmein",
+    "master",
+    "monkey",
+    "password",
+    "password1",
+    "princess",
+    "qwerty",
+    "qwerty123",
+    "qwertyuiop",
+    "shadow",
+    "sunshine",
+    "welcome",
+    "zaq12wsx",
+];
+
+/// Entropy thresholds in bits to get the score equal to the index plus one.
+const SCORE_THRESHOLDS: [f64; 4] = [28.0, 36.0, 60.0, 80.0];
+
+/// Estimated strength of a password.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Strength {
+    /// Score from `0` (guessed instantly) to [`MAX_SCORE`] (infeasible to guess).
+    pub score: u8,
+    /// Estimated entropy of the password in bits.
+    pub entropy_bits: f64,
+    /// Found problems, empty if there are none.
+    pub warnings: Vec<Warning>,
+}
+
+/// Problem making a password easier to guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Warning {
+    /// Password is shorter than [`MIN_LENGTH`].
+    TooShort,
+    /// Password consists of characters of a single class, e.g. only digits.
+    SingleCharacterClass,
+    /// Password contains the same character repeated in a row, e.g. `aaa`.
+    RepeatedCharacters,
+    /// Password contains consecutive characters, e.g. `abc` or `321`.
+    Sequence,
+    /// Password is one of the most common ones.
+    Common,
+}
+
+/// Class of characters an attacker would try as a whole.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum CharacterClass {
+    /// ASCII lowercase letters.
+    Lowercase,
+    /// ASCII uppercase letters.
+    Uppercase,
+    /// ASCII digits.
+    Digit,
+    /// ASCII punctuation and space.
+    Symbol,
+    /// Any other character.
+    Other,
+}
+
+impl CharacterClass {
+    /// Get class of `c`.
+    const fn of(c: char) -> Self {
+        match c {
+            'a'..='z' => Self::Lowercase,
+            'A'..='Z' => Self::Uppercase,
+            '0'..='9' => Self::Digit,
+            ' '..='~' => Self::Symbol,
+            _ => Self::Other,
+        }
+    }
+
+    /// Get number of characters in the class.
+    const fn size(self) -> u32 {
+        match self {
+            Self::Lowercase | Self::Uppercase => 26,
+            Self::Digit => 10,
+            Self::Symbol => 33,
+            // Rough number of letters of a non-Latin alphabet in both cases
+            Self::Other => 100,
+        }
+    }
+}
+
+/// Estimate strength of `password`.
+///
+/// Entropy is computed as if every character was chosen at random from all classes present
+/// in the password, except characters continuing a run or a sequence, which add a single bit.
+/// Common passwords get the entropy of choosing one of them.
+#[must_use]
+pub fn estimate(password: &str) -> Strength {
+    let chars = password.chars().collect::<Vec<_>>();
+    let mut warnings = Vec::new();
+
+    if chars.len() < MIN_LENGTH {
+        warnings.push(Warning::TooShort);
+    }
+
+    let mut classes = chars
+        .iter()
+        .map(|&c| CharacterClass::of(c))
+        .collect::<Vec<_>>();
+    classes.sort_unstable();
+    classes.dedup();
+    if classes.len() == 1 {
+        warnings.push(Warning::SingleCharacterClass);
+    }
+
+    let patterns = find_patterns(&chars);
+    if patterns.longest_run >= MIN_PATTERN_LENGTH {
+        warnings.push(Warning::RepeatedCharacters);
+    }
+    if patterns.longest_sequence >= MIN_PATTERN_LENGTH {
+        warnings.push(Warning::Sequence);
+    }
+
+    let entropy_bits = if COMMON_PASSWORDS.contains(&password.to_lowercase().as_str()) {
+        warnings.push(Warning::Common);
+        f64::from(count(COMMON_PASSWORDS.len())).log2()
+    } else {
+        let pool_size = classes.iter().map(|class| class.size()).sum::<u32>();
+        let predictable = patterns.predictable_chars;
+        let random = chars.len().saturating_sub(predictable);
+        f64::from(pool_size.max(1))
+            .log2()
+            .mul_add(f64::from(count(random)), f64::from(count(predictable)))
+    };
+
+    let mut score = SCORE_THRESHOLDS
+        .iter()
+        .zip(1..)
+        .take_while(|&(&threshold, _score)| entropy_bits >= threshold)
+        .last()
+        .map_or(0, |(_threshold, score)| score);
+    if warnings.contains(&Warning::TooShort) || warnings.contains(&Warning::Common) {
+        score = score.min(1);
+    }
+
+    Strength {
+        score,
+        entropy_bits,
+        warnings,
+    }
+}
+
+/// Patterns found in a password.
+#[derive(Debug, Default)]
+struct Patterns {
+    /// Length of the longest run of the same character.
+    longest_run: usize,
+    /// Length of the longest sequence of consecutive characters.
+    longest_sequence: usize,
+    /// Number of characters continuing a run or a sequence.
+    predictable_chars: usize,
+}
+
+/// Find runs and sequences in `chars`.
+fn find_patterns(chars: &[char]) -> Patterns {
+    let mut patterns = Patterns::default();
+    let mut run = 1_usize;
+    let mut sequence = 1_usize;
+    let mut previous_step = None;
+
+    for pair in chars.windows(2) {
+        let &[previous, current] = pair else {
+            unreachable!("windows of size 2 always have 2 elements");
+        };
+        let step = i64::from(u32::from(current)).saturating_sub(i64::from(u32::from(previous)));
+
+        run = if step == 0 { run.saturating_add(1) } else { 1 };
+        sequence = match (step, previous_step) {
+            (-1 | 1, Some(previous_step)) if previous_step == step => sequence.saturating_add(1),
+            (-1 | 1, _) => 2,
+            _ => 1,
+        };
+        previous_step = Some(step);
+
+        if run > 1 || sequence > 2 {
+            patterns.predictable_chars = patterns.predictable_chars.saturating_add(1);
+        }
+        patterns.longest_run = patterns.longest_run.max(run);
+        patterns.longest_sequence = patterns.longest_sequence.max(sequence);
+    }
+
+    patterns
+}
+
+/// Convert `n` to a number suitable for floating point arithmetic.
+fn count(n: usize) -> u32 {
+    u32::try_from(n).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_password_is_weakest() {
+        let strength = estimate("");
+
+        assert_eq!(strength.score, 0);
+        assert!(strength.entropy_bits.abs() < f64::EPSILON);
+        assert_eq!(strength.warnings, [Warning::TooShort]);
+    }
+
+    #[test]
+    fn common_password_is_weak_regardless_of_case() {
+        let strength = estimate("PassWord1");
+
+        assert_eq!(strength.score, 0);
+        assert!(strength.warnings.contains(&Warning::Common));
+    }
+
+    #[test]
+    fn short_password_is_weak() {
+        let strength = estimate("x7#Kq");
+
+        assert_eq!(strength.score, 1);
+        assert_eq!(strength.warnings, [Warning::TooShort]);
+    }
+
+    #[test]
+    fn runs_and_sequences_are_predictable() {
+        let run = estimate("aaaaaaaaaaaa");
+        assert_eq!(
+            run.warnings,
+            [Warning::SingleCharacterClass, Warning::RepeatedCharacters]
+        );
+        assert_eq!(run.score, 0);
+
+        let sequence = estimate("Zyxwvu9876!");
+        assert_eq!(sequence.warnings, [Warning::Sequence]);
+        assert!(sequence.score < estimate("Zqxbvk9381!").score);
+    }
+
+    #[test]
+    fn more_classes_give_more_entropy() {
+        let digits = estimate("52917403865129");
+        let mixed = estimate("5a9K7#0z8e5R2q");
+
+        assert_eq!(digits.warnings, [Warning::SingleCharacterClass]);
+        assert!(mixed.warnings.is_empty());
+        assert!(digits.entropy_bits < mixed.entropy_bits);
+        assert_eq!(mixed.score, MAX_SCORE);
+    }
+
+    #[test]
+    fn non_ascii_characters_are_counted() {
+        // "пароль-очень-длинный-2024"
+        let strength = estimate(
+            "\u{43f}\u{430}\u{440}\u{43e}\u{43b}\u{44c}-\u{43e}\u{447}\u{435}\u{43d}\u{44c}-\
+             \u{434}\u{43b}\u{438}\u{43d}\u{43d}\u{44b}\u{439}-2024",
+        );
+
+        assert!(strength.warnings.is_empty(), "{:?}", strength.warnings);
+        assert_eq!(strength.score, MAX_SCORE);
+    }
+}
@@ -0,0 +1,350 @@
+//! Generation of time-based one-time passwords as defined by RFC 6238.
+//!
+//! A code is an HMAC of the number of [`Params::step_seconds`] intervals passed since the Unix
+//! epoch, keyed with the secret shared with the site and truncated to [`Params::digits`] decimal
+//! digits. Secrets are accepted in base32, the way sites show them next to the QR code.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pbkdf2::hmac::{digest::KeyInit, Hmac, Mac};
+use sha1::Sha1;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+/// Alphabet of the base32 encoding from RFC 4648.
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Hash function of the HMAC.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    /// SHA-1, used by most sites.
+    #[default]
+    Sha1,
+    /// SHA-256.
+    Sha256,
+}
+
+/// Number of digits in a code.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Digits {
+    /// 6 digits, used by most sites.
+    #[default]
+    Six,
+    /// 8 digits.
+    Eight,
+}
+
+impl Digits {
+    /// Get number of digits.
+    #[must_use]
+    pub const fn count(self) -> usize {
+        match self {
+            Self::Six => 6,
+            Self::Eight => 8,
+        }
+    }
+
+    /// Get modulus truncating a number to the number of digits.
+    const fn modulus(self) -> u32 {
+        match self {
+            Self::Six => 1_000_000,
+            Self::Eight => 100_000_000,
+        }
+    }
+}
+
+/// Parameters of code generation.
+///
+/// Default parameters are the ones assumed by authenticator apps when a site doesn't specify
+/// them: SHA-1, 6 digits and a 30 seconds step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Params {
+    /// Hash function of the HMAC.
+    pub algorithm: HashAlgorithm,
+    /// Number of digits in a code.
+    pub digits: Digits,
+    /// Number of seconds each code is valid for.
+    pub step_seconds: u64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            algorithm: HashAlgorithm::default(),
+            digits: Digits::default(),
+            step_seconds: 30,
+        }
+    }
+}
+
+/// Error of code generation.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    #[error("TOTP secret is empty")]
+    EmptySecret,
+    #[error("TOTP secret is not valid base32, invalid character at position {position}")]
+    InvalidSecret { position: usize },
+    #[error("TOTP step must be positive")]
+    ZeroStep,
+    #[error("Time is before the Unix epoch")]
+    TimeBeforeEpoch,
+    #[error("Failed to compute HMAC of the counter")]
+    Hmac,
+}
+
+/// Result of code generation.
+pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+impl Params {
+    /// Generate code of `secret_base32` valid at `time`.
+    ///
+    /// `secret_base32` is case-insensitive and may contain whitespaces and padding.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::EmptySecret`] if `secret_base32` has no characters except whitespaces and
+    ///   padding;
+    /// - [`Error::InvalidSecret`] if `secret_base32` is not valid base32;
+    /// - [`Error::ZeroStep`] if [`step_seconds`](Self::step_seconds) is zero;
+    /// - [`Error::TimeBeforeEpoch`] if `time` is before the Unix epoch.
+    #[expect(clippy::big_endian_bytes, reason = "counter is big-endian")]
+    pub fn generate(&self, secret_base32: &str, time: SystemTime) -> Result<String> {
+        let secret = decode_base32(secret_base32)?;
+        let counter = self.counter(time)?.to_be_bytes();
+        let hash = match self.algorithm {
+            HashAlgorithm::Sha1 => hmac::<Hmac<Sha1>>(&secret, &counter),
+            HashAlgorithm::Sha256 => hmac::<Hmac<Sha256>>(&secret, &counter),
+        }?;
+
+        let code = truncate(&hash)
+            .checked_rem(self.digits.modulus())
+            .unwrap_or_default();
+        Ok(format!("{code:0width$}", width = self.digits.count()))
+    }
+
+    /// Get number of seconds the code valid at `time` remains valid for.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::ZeroStep`] if [`step_seconds`](Self::step_seconds) is zero;
+    /// - [`Error::TimeBeforeEpoch`] if `time` is before the Unix epoch.
+    pub fn remaining_seconds(&self, time: SystemTime) -> Result<u64> {
+        let elapsed = seconds_since_epoch(time)?
+            .checked_rem(self.step_seconds)
+            .ok_or(Error::ZeroStep)?;
+        Ok(self.step_seconds.saturating_sub(elapsed))
+    }
+
+    /// Get number of steps passed since the Unix epoch till `time`.
+    fn counter(&self, time: SystemTime) -> Result<u64> {
+        seconds_since_epoch(time)?
+            .checked_div(self.step_seconds)
+            .ok_or(Error::ZeroStep)
+    }
+}
+
+/// Generate code of `secret_base32` valid at `time` with default [`Params`].
+///
+/// # Errors
+///
+/// See [`Params::generate()`].
+pub fn generate(secret_base32: &str, time: SystemTime) -> Result<String> {
+    Params::default().generate(secret_base32, time)
+}
+
+/// Get number of seconds the code valid at `time` remains valid for with default [`Params`].
+///
+/// # Errors
+///
+/// See [`Params::remaining_seconds()`].
+pub fn remaining_seconds(time: SystemTime) -> Result<u64> {
+    Params::default().remaining_seconds(time)
+}
+
+/// Get number of whole seconds passed since the Unix epoch till `time`.
+fn seconds_since_epoch(time: SystemTime) -> Result<u64> {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .map_err(|_err| Error::TimeBeforeEpoch)
+}
+
+/// Decode `encoded` base32 ignoring case, whitespaces and padding.
+///
+/// Trailing bits not forming a whole byte are dropped.
+#[expect(clippy::big_endian_bytes, reason = "the lowest byte is taken")]
+fn decode_base32(encoded: &str) -> Result<Zeroizing<Vec<u8>>> {
+    let mut decoded = Zeroizing::new(Vec::new());
+    let mut buffer = 0_u32;
+    let mut buffered_bits = 0_u32;
+
+    for (position, c) in encoded.trim_end_matches(['=', ' ']).chars().enumerate() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let value = u8::try_from(c.to_ascii_uppercase())
+            .ok()
+            .and_then(|byte| BASE32_ALPHABET.iter().position(|&symbol| symbol == byte))
+            .and_then(|index| u32::try_from(index).ok())
+            .ok_or(Error::InvalidSecret { position })?;
+
+        buffer = (buffer << 5_u32) | value;
+        buffered_bits = buffered_bits.saturating_add(5);
+        if buffered_bits >= 8 {
+            buffered_bits = buffered_bits.saturating_sub(8);
+            let [.., byte] = (buffer >> buffered_bits).to_be_bytes();
+            decoded.push(byte);
+            buffer &= (1_u32 << buffered_bits).saturating_sub(1);
+        }
+    }
+
+    if decoded.is_empty() {
+        return Err(Error::EmptySecret);
+    }
+    Ok(decoded)
+}
+
+/// Compute HMAC `M` of `message` keyed with `key`.
+fn hmac<M: Mac + KeyInit>(key: &[u8], message: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    Ok(Zeroizing::new(
+        <M as KeyInit>::new_from_slice(key)
+            .map_err(|_err| Error::Hmac)?
+            .chain_update(message)
+            .finalize()
+            .into_bytes()
+            .to_vec(),
+    ))
+}
+
+/// Truncate `hash` to a 31-bit number as defined by RFC 4226.
+#[expect(clippy::big_endian_bytes, reason = "truncated bytes are big-endian")]
+fn truncate(hash: &[u8]) -> u32 {
+    let offset = hash.last().map_or(0, |last| usize::from(last & 0x0f));
+    let mut bytes = [0; 4];
+    for (byte, hash_byte) in bytes.iter_mut().zip(hash.iter().skip(offset)) {
+        *byte = *hash_byte;
+    }
+    u32::from_be_bytes(bytes) & 0x7fff_ffff
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use std::time::Duration;
+
+    use super::*;
+
+    /// ASCII `12345678901234567890`, the SHA-1 secret of RFC 6238 test vectors.
+    const SHA1_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    /// ASCII `12345678901234567890123456789012`, the SHA-256 secret of RFC 6238 test vectors.
+    const SHA256_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZA====";
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH
+            .checked_add(Duration::from_secs(seconds))
+            .unwrap()
+    }
+
+    const fn params(algorithm: HashAlgorithm) -> Params {
+        Params {
+            algorithm,
+            digits: Digits::Eight,
+            step_seconds: 30,
+        }
+    }
+
+    #[test]
+    fn rfc_6238_test_vectors() {
+        for (seconds, sha1_code, sha256_code) in [
+            (59, "94287082", "46119246"),
+            (1_111_111_109, "07081804", "68084774"),
+            (1_111_111_111, "14050471", "67062674"),
+            (1_234_567_890, "89005924", "91819424"),
+            (2_000_000_000, "69279037", "90698825"),
+            (20_000_000_000, "65353130", "77737706"),
+        ] {
+            assert_eq!(
+                params(HashAlgorithm::Sha1)
+                    .generate(SHA1_SECRET, at(seconds))
+                    .unwrap(),
+                sha1_code,
+                "SHA-1 at {seconds}"
+            );
+            assert_eq!(
+                params(HashAlgorithm::Sha256)
+                    .generate(SHA256_SECRET, at(seconds))
+                    .unwrap(),
+                sha256_code,
+                "SHA-256 at {seconds}"
+            );
+        }
+    }
+
+    #[test]
+    fn default_params_give_last_six_digits() {
+        assert_eq!(generate(SHA1_SECRET, at(59)).unwrap(), "287082");
+        assert_eq!(generate(SHA1_SECRET, at(1_111_111_109)).unwrap(), "081804");
+    }
+
+    #[test]
+    fn code_changes_with_step() {
+        let code = generate(SHA1_SECRET, at(60)).unwrap();
+
+        assert_eq!(generate(SHA1_SECRET, at(89)).unwrap(), code);
+        assert_ne!(generate(SHA1_SECRET, at(90)).unwrap(), code);
+
+        let minute = Params {
+            step_seconds: 60,
+            ..Params::default()
+        };
+        // Both counters are 2
+        assert_eq!(minute.generate(SHA1_SECRET, at(179)).unwrap(), code);
+    }
+
+    #[test]
+    fn secret_ignores_case_spaces_and_padding() {
+        assert_eq!(
+            generate("gezd gnbv gy3t qojq gezd gnbv gy3t qojq", at(59)).unwrap(),
+            generate(SHA1_SECRET, at(59)).unwrap()
+        );
+        assert_eq!(
+            generate("MZXW6===", at(59)).unwrap(),
+            generate("mzxw6", at(59)).unwrap()
+        );
+    }
+
+    #[test]
+    fn remaining_seconds_count_till_next_step() {
+        assert_eq!(remaining_seconds(at(0)).unwrap(), 30);
+        assert_eq!(remaining_seconds(at(59)).unwrap(), 1);
+        assert_eq!(remaining_seconds(at(61)).unwrap(), 29);
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        assert_eq!(generate("", at(59)), Err(Error::EmptySecret));
+        assert_eq!(generate(" == ", at(59)), Err(Error::EmptySecret));
+        assert_eq!(
+            generate("GEZD1NBV", at(59)),
+            Err(Error::InvalidSecret { position: 4 })
+        );
+        assert_eq!(
+            generate(
+                SHA1_SECRET,
+                UNIX_EPOCH.checked_sub(Duration::from_secs(1)).unwrap()
+            ),
+            Err(Error::TimeBeforeEpoch)
+        );
+
+        let zero_step = Params {
+            step_seconds: 0,
+            ..Params::default()
+        };
+        assert_eq!(
+            zero_step.generate(SHA1_SECRET, at(59)),
+            Err(Error::ZeroStep)
+        );
+        assert_eq!(zero_step.remaining_seconds(at(59)), Err(Error::ZeroStep));
+    }
+}
@@ -0,0 +1,95 @@
+//! Encryption round-trips on `wasm32-unknown-unknown`, where random numbers come from JS.
+//!
+//! Run with `cargo test -p telepass_crypto --target wasm32-unknown-unknown`,
+//! which needs `wasm-bindgen-test-runner` from `wasm-bindgen-cli` of the same version as
+//! `wasm-bindgen` and `node`.
+
+#![cfg(all(target_arch = "wasm32", target_os = "unknown", feature = "impls"))]
+#![expect(
+    clippy::missing_assert_message,
+    clippy::unwrap_used,
+    reason = "`wasm_bindgen_test` functions are not recognized as tests"
+)]
+
+use telepass_crypto::{
+    decrypt, decrypt_stream, decrypt_with_aad, encrypt, encrypt_chunks, encrypt_with_aad,
+    Algorithm, EncryptParams, KeyCache,
+};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+/// Cheap parameters, so that tests don't spend time on key derivation.
+const fn params(algorithm: Algorithm) -> EncryptParams {
+    EncryptParams {
+        kdf_iterations: 1000,
+        algorithm,
+    }
+}
+
+#[wasm_bindgen_test]
+fn encrypt_and_decrypt_work() {
+    for algorithm in [Algorithm::Aes256Gcm, Algorithm::XChaCha20Poly1305] {
+        let output = encrypt("payload", "password", Some(params(algorithm))).unwrap();
+        assert_eq!(output.algorithm(), algorithm);
+
+        assert_eq!(decrypt(output, "password").unwrap(), "payload");
+    }
+}
+
+#[wasm_bindgen_test]
+fn salts_are_random() {
+    let first = encrypt("payload", "password", Some(params(Algorithm::default()))).unwrap();
+    let second = encrypt("payload", "password", Some(params(Algorithm::default()))).unwrap();
+
+    assert_ne!(first.salt, second.salt);
+    assert_ne!(first.kdf_salt, second.kdf_salt);
+    assert_ne!(first.encrypted_payload, second.encrypted_payload);
+}
+
+#[wasm_bindgen_test]
+fn wrong_password_or_aad_fails() {
+    let output = encrypt_with_aad(
+        "payload",
+        "password",
+        Some(params(Algorithm::default())),
+        b"bank.com",
+    )
+    .unwrap();
+
+    decrypt_with_aad(output.clone(), "wrong_password", b"bank.com").unwrap_err();
+    decrypt_with_aad(output.clone(), "password", b"mail.com").unwrap_err();
+    assert_eq!(
+        decrypt_with_aad(output, "password", b"bank.com").unwrap(),
+        "payload"
+    );
+}
+
+#[wasm_bindgen_test]
+fn chunks_round_trip() {
+    let output = encrypt_chunks(
+        [b"aaaa".as_slice(), b"bbbb", b"cccc"],
+        "password",
+        Some(params(Algorithm::XChaCha20Poly1305)),
+    )
+    .unwrap();
+
+    let chunks = decrypt_stream(&output, "password")
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(chunks.concat(), b"aaaabbbbcccc");
+}
+
+#[wasm_bindgen_test]
+fn key_cache_round_trip() {
+    let cache = KeyCache::new();
+
+    let output = cache
+        .encrypt("payload", "password", Some(params(Algorithm::default())))
+        .unwrap();
+
+    assert_eq!(
+        cache.decrypt(output.clone(), "password").unwrap(),
+        "payload"
+    );
+    assert_eq!(decrypt(output, "password").unwrap(), "payload");
+}
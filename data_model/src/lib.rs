@@ -1,12 +1,1865 @@
 //! Crate with Telepass common data structures which are transferred between services.
 
-use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    convert::Infallible,
+    fmt::{self, Display},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Deserializer, Serialize};
 pub use telepass_crypto as crypto;
 
+/// Maximum length of the resource name in characters, limited by the password storage.
+pub const MAX_RESOURCE_NAME_LENGTH: usize = 255;
+
+/// Maximum size of the attachment content in bytes, limited by the password storage.
+pub const MAX_ATTACHMENT_SIZE: u64 = 64 * 1024;
+
+/// Maximum length of the attachment filename in characters, limited by the password storage.
+pub const MAX_ATTACHMENT_FILENAME_LENGTH: usize = 255;
+
+/// Maximum length of the record tag in characters.
+pub const MAX_TAG_LENGTH: usize = 32;
+
+/// Validated name of the resource.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ResourceName(String);
+
+impl ResourceName {
+    /// Get name as a string slice.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for ResourceName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<ResourceName> for String {
+    fn from(name: ResourceName) -> Self {
+        name.0
+    }
+}
+
+impl TryFrom<String> for ResourceName {
+    type Error = ResourceNameError;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        if name.trim().is_empty() {
+            return Err(ResourceNameError::Empty);
+        }
+        if name.contains('\0') {
+            return Err(ResourceNameError::ContainsNul);
+        }
+        // Line breaks and other control characters break rendering of names in the bot
+        if name.chars().any(char::is_control) {
+            return Err(ResourceNameError::ContainsControl);
+        }
+        if name.chars().count() > MAX_RESOURCE_NAME_LENGTH {
+            return Err(ResourceNameError::TooLong);
+        }
+        Ok(Self(name))
+    }
+}
+
+impl TryFrom<&str> for ResourceName {
+    type Error = ResourceNameError;
+
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        name.to_owned().try_into()
+    }
+}
+
+/// Reason why the resource name is invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum ResourceNameError {
+    #[error("resource name cannot be empty")]
+    Empty,
+    #[error("resource name cannot contain NUL character")]
+    ContainsNul,
+    #[error("resource name cannot contain line breaks or other control characters")]
+    ContainsControl,
+    #[error("resource name cannot be longer than {MAX_RESOURCE_NAME_LENGTH} characters")]
+    TooLong,
+}
+
+/// Allows to pass already validated [`ResourceName`] to [`NewRecordBuilder::resource_name()`].
+impl From<Infallible> for ResourceNameError {
+    fn from(never: Infallible) -> Self {
+        match never {}
+    }
+}
+
+/// Validated tag to group records by, e.g. `work` or `finance`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Tag(String);
+
+impl Tag {
+    /// Get tag as a string slice.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<Tag> for String {
+    fn from(tag: Tag) -> Self {
+        tag.0
+    }
+}
+
+impl TryFrom<String> for Tag {
+    type Error = TagError;
+
+    fn try_from(tag: String) -> Result<Self, Self::Error> {
+        if tag.is_empty() {
+            return Err(TagError::Empty);
+        }
+        if tag.chars().any(char::is_whitespace) {
+            return Err(TagError::ContainsWhitespace);
+        }
+        if tag.chars().any(char::is_uppercase) {
+            return Err(TagError::NotLowercase);
+        }
+        if tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(TagError::TooLong);
+        }
+        Ok(Self(tag))
+    }
+}
+
+impl TryFrom<&str> for Tag {
+    type Error = TagError;
+
+    fn try_from(tag: &str) -> Result<Self, Self::Error> {
+        tag.to_owned().try_into()
+    }
+}
+
+/// Reason why the tag is invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum TagError {
+    #[error("tag cannot be empty")]
+    Empty,
+    #[error("tag cannot contain whitespace")]
+    ContainsWhitespace,
+    #[error("tag must be lowercase")]
+    NotLowercase,
+    #[error("tag cannot be longer than {MAX_TAG_LENGTH} characters")]
+    TooLong,
+}
+
+/// Check that `tags` has no duplicates.
+fn has_unique_tags(tags: &[Tag]) -> bool {
+    let mut seen = HashSet::with_capacity(tags.len());
+    tags.iter().all(|tag| seen.insert(tag))
+}
+
+/// Encrypted file to attach to a new record.
+///
+/// Can be constructed only with [`NewAttachment::new()`] or deserialized, so it's always valid.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct NewAttachment {
+    /// Name of the file, unique within the record.
+    filename: String,
+    /// Encrypted file content.
+    encryption_output: crypto::EncryptionOutput,
+    /// Size of the file content before encryption in bytes.
+    size: u64,
+}
+
+impl NewAttachment {
+    /// Construct new attachment of the `size` bytes long file encrypted into `encryption_output`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the filename is invalid, the file is larger than [`MAX_ATTACHMENT_SIZE`]
+    /// or the encrypted content is empty.
+    pub fn new(
+        filename: impl Into<String>,
+        encryption_output: crypto::EncryptionOutput,
+        size: u64,
+    ) -> Result<Self, AttachmentError> {
+        let filename = filename.into();
+        if filename.trim().is_empty() {
+            return Err(AttachmentError::EmptyFilename);
+        }
+        if filename.contains('\0') {
+            return Err(AttachmentError::FilenameContainsNul);
+        }
+        if filename.chars().count() > MAX_ATTACHMENT_FILENAME_LENGTH {
+            return Err(AttachmentError::FilenameTooLong);
+        }
+        if size > MAX_ATTACHMENT_SIZE {
+            return Err(AttachmentError::TooLarge);
+        }
+        if encryption_output.encrypted_payload.is_empty() {
+            return Err(AttachmentError::EmptyEncryptedContent);
+        }
+
+        Ok(Self {
+            filename,
+            encryption_output,
+            size,
+        })
+    }
+
+    /// Get name of the file.
+    #[must_use]
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Get encrypted file content.
+    #[must_use]
+    pub const fn encryption_output(&self) -> &crypto::EncryptionOutput {
+        &self.encryption_output
+    }
+
+    /// Get size of the file content before encryption in bytes.
+    #[must_use]
+    pub const fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// Reason why the attachment is invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum AttachmentError {
+    #[error("attachment filename cannot be empty")]
+    EmptyFilename,
+    #[error("attachment filename cannot contain NUL character")]
+    FilenameContainsNul,
+    #[error(
+        "attachment filename cannot be longer than {MAX_ATTACHMENT_FILENAME_LENGTH} characters"
+    )]
+    FilenameTooLong,
+    #[error("attachment cannot be larger than {MAX_ATTACHMENT_SIZE} bytes")]
+    TooLarge,
+    #[error("encrypted attachment content is empty")]
+    EmptyEncryptedContent,
+}
+
 /// Data to store a new record.
+///
+/// Can be constructed only with [`NewRecord::new()`], [`NewRecord::builder()`] or from
+/// [`RawNewRecord`], which is also done on deserialization, so it's always valid.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "RawNewRecord")]
 pub struct NewRecord {
     /// Name of the resource.
-    pub resource_name: String,
+    resource_name: ResourceName,
+    /// Encrypted record data.
+    encryption_output: crypto::EncryptionOutput,
+    /// Blinded keywords of the record content to search for, can be empty.
+    blind_index: Vec<crypto::BlindToken>,
+    /// Version of the key derivation used to blind the keywords and the password.
+    blind_index_version: u8,
+    /// Whether the encrypted data is bound to the resource name as associated data.
+    bound_to_resource_name: bool,
+    /// Blinded password of the record to detect its reuse across records.
+    password_fingerprint: Option<crypto::PasswordFingerprint>,
+    /// Files attached to the record, can be empty.
+    attachments: Vec<NewAttachment>,
+    /// Tags to group the record by, can be empty.
+    tags: Vec<Tag>,
+}
+
+impl NewRecord {
+    /// Construct a new record of `resource_name` encrypted into `encryption_output`.
+    ///
+    /// Use [`NewRecord::builder()`] to set the optional parts.
+    ///
+    /// # Errors
+    ///
+    /// Fails with all found problems if the resource name or the encryption output is invalid.
+    pub fn new<N>(
+        resource_name: N,
+        encryption_output: crypto::EncryptionOutput,
+    ) -> Result<Self, BuildError>
+    where
+        N: TryInto<ResourceName>,
+        ResourceNameError: From<N::Error>,
+    {
+        Self::builder()
+            .resource_name(resource_name)
+            .encryption_output(encryption_output)
+            .build()
+    }
+
+    /// Start building a new record.
+    #[must_use]
+    pub fn builder() -> NewRecordBuilder {
+        NewRecordBuilder::default()
+    }
+
+    /// Get name of the resource.
+    #[must_use]
+    pub const fn resource_name(&self) -> &ResourceName {
+        &self.resource_name
+    }
+
+    /// Get encrypted record data.
+    #[must_use]
+    pub const fn encryption_output(&self) -> &crypto::EncryptionOutput {
+        &self.encryption_output
+    }
+
+    /// Get blinded keywords of the record content.
+    #[must_use]
+    pub fn blind_index(&self) -> &[crypto::BlindToken] {
+        &self.blind_index
+    }
+
+    /// Get version of the key derivation used to blind the keywords and the password,
+    /// see [`crypto::LATEST_BLIND_INDEX_VERSION`].
+    #[must_use]
+    pub const fn blind_index_version(&self) -> u8 {
+        self.blind_index_version
+    }
+
+    /// Check if the encrypted data is bound to the resource name as associated data.
+    ///
+    /// Such data can be decrypted only if the same resource name is passed as associated data.
+    #[must_use]
+    pub const fn is_bound_to_resource_name(&self) -> bool {
+        self.bound_to_resource_name
+    }
+
+    /// Get blinded password of the record, if it was provided.
+    #[must_use]
+    pub const fn password_fingerprint(&self) -> Option<crypto::PasswordFingerprint> {
+        self.password_fingerprint
+    }
+
+    /// Get files attached to the record.
+    #[must_use]
+    pub fn attachments(&self) -> &[NewAttachment] {
+        &self.attachments
+    }
+
+    /// Get tags of the record.
+    #[must_use]
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    /// Split record into resource name, encrypted record data and blind index.
+    #[must_use]
+    pub fn into_parts(
+        self,
+    ) -> (
+        ResourceName,
+        crypto::EncryptionOutput,
+        Vec<crypto::BlindToken>,
+    ) {
+        (self.resource_name, self.encryption_output, self.blind_index)
+    }
+}
+
+/// Unvalidated [`NewRecord`] as it's received from the Web App.
+///
+/// Converting it into [`NewRecord`] reports all problems at once as [`BuildError`], while
+/// deserializing [`NewRecord`] directly only tells that the data is invalid.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RawNewRecord {
+    /// Name of the resource.
+    resource_name: String,
+    /// Encrypted record data.
+    encryption_output: crypto::EncryptionOutput,
+    /// Blinded keywords of the record content to search for, can be missing.
+    #[serde(default)]
+    blind_index: Vec<crypto::BlindToken>,
+    /// Version of the key derivation used to blind the keywords and the password.
+    ///
+    /// Missing in records sent by older Web App versions, which used
+    /// [`crypto::BLIND_INDEX_VERSION_1`].
+    #[serde(default = "legacy_blind_index_version")]
+    blind_index_version: u8,
+    /// Whether the encrypted data is bound to the resource name as associated data.
+    ///
+    /// Missing in records sent by older Web App versions, which didn't bind it.
+    #[serde(default)]
+    bound_to_resource_name: bool,
+    /// Blinded password of the record to detect its reuse across records.
+    ///
+    /// Missing in records sent by older Web App versions, which didn't compute it.
+    #[serde(default)]
+    password_fingerprint: Option<crypto::PasswordFingerprint>,
+    /// Files attached to the record.
+    ///
+    /// Missing in records sent by older Web App versions, which didn't support them.
+    #[serde(default)]
+    attachments: Vec<NewAttachment>,
+    /// Tags to group the record by.
+    ///
+    /// Missing in records sent by older Web App versions, which didn't support them.
+    #[serde(default)]
+    tags: Vec<Tag>,
+}
+
+impl TryFrom<RawNewRecord> for NewRecord {
+    type Error = BuildError;
+
+    fn try_from(raw: RawNewRecord) -> Result<Self, Self::Error> {
+        let mut builder = Self::builder()
+            .resource_name(raw.resource_name)
+            .encryption_output(raw.encryption_output)
+            .blind_index(raw.blind_index)
+            .blind_index_version(raw.blind_index_version)
+            .bound_to_resource_name(raw.bound_to_resource_name)
+            .attachments(raw.attachments)
+            .tags(raw.tags);
+        if let Some(password_fingerprint) = raw.password_fingerprint {
+            builder = builder.password_fingerprint(password_fingerprint);
+        }
+        builder.build()
+    }
+}
+
+/// Get [`crypto::BLIND_INDEX_VERSION_1`] for `serde`, records without a blind index version
+/// are of it.
+const fn legacy_blind_index_version() -> u8 {
+    crypto::BLIND_INDEX_VERSION_1
+}
+
+/// Builder of [`NewRecord`] validating all its parts.
+#[derive(Debug, Clone, Default)]
+pub struct NewRecordBuilder {
+    /// Name of the resource or the reason why it's invalid.
+    resource_name: Option<Result<ResourceName, ResourceNameError>>,
+    /// Encrypted record data.
+    encryption_output: Option<crypto::EncryptionOutput>,
+    /// Blinded keywords of the record content.
+    blind_index: Vec<crypto::BlindToken>,
+    /// Version of the key derivation used to blind the keywords and the password.
+    blind_index_version: Option<u8>,
+    /// Whether the encrypted data is bound to the resource name.
+    bound_to_resource_name: bool,
+    /// Blinded password of the record.
+    password_fingerprint: Option<crypto::PasswordFingerprint>,
+    /// Files attached to the record.
+    attachments: Vec<NewAttachment>,
+    /// Tags of the record.
+    tags: Vec<Tag>,
+}
+
+impl NewRecordBuilder {
+    /// Set name of the resource.
+    ///
+    /// Validation errors are reported by [`NewRecordBuilder::build()`].
+    #[must_use]
+    pub fn resource_name<N>(mut self, resource_name: N) -> Self
+    where
+        N: TryInto<ResourceName>,
+        ResourceNameError: From<N::Error>,
+    {
+        self.resource_name = Some(resource_name.try_into().map_err(Into::into));
+        self
+    }
+
+    /// Set encrypted record data.
+    #[must_use]
+    pub fn encryption_output(mut self, encryption_output: crypto::EncryptionOutput) -> Self {
+        self.encryption_output = Some(encryption_output);
+        self
+    }
+
+    /// Set blinded keywords of the record content, empty by default.
+    #[must_use]
+    pub fn blind_index(mut self, blind_index: Vec<crypto::BlindToken>) -> Self {
+        self.blind_index = blind_index;
+        self
+    }
+
+    /// Set version of the key derivation used to blind the keywords and the password,
+    /// [`crypto::LATEST_BLIND_INDEX_VERSION`] by default.
+    #[must_use]
+    pub const fn blind_index_version(mut self, version: u8) -> Self {
+        self.blind_index_version = Some(version);
+        self
+    }
+
+    /// Set whether the encrypted data is bound to the resource name as associated data,
+    /// `false` by default.
+    #[must_use]
+    pub const fn bound_to_resource_name(mut self, bound: bool) -> Self {
+        self.bound_to_resource_name = bound;
+        self
+    }
+
+    /// Set blinded password of the record, none by default.
+    #[must_use]
+    pub const fn password_fingerprint(
+        mut self,
+        password_fingerprint: crypto::PasswordFingerprint,
+    ) -> Self {
+        self.password_fingerprint = Some(password_fingerprint);
+        self
+    }
+
+    /// Set files attached to the record, empty by default.
+    #[must_use]
+    pub fn attachments(mut self, attachments: Vec<NewAttachment>) -> Self {
+        self.attachments = attachments;
+        self
+    }
+
+    /// Set tags of the record, empty by default.
+    #[must_use]
+    pub fn tags(mut self, tags: Vec<Tag>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Build validated [`NewRecord`].
+    ///
+    /// # Errors
+    ///
+    /// Fails with all found problems if any of the parts is missing or invalid.
+    pub fn build(self) -> Result<NewRecord, BuildError> {
+        let mut problems = Vec::new();
+
+        let resource_name = match self.resource_name {
+            Some(Ok(resource_name)) => Some(resource_name),
+            Some(Err(error)) => {
+                problems.push(BuildProblem::InvalidResourceName(error));
+                None
+            }
+            None => {
+                problems.push(BuildProblem::MissingResourceName);
+                None
+            }
+        };
+
+        let encryption_output = match self.encryption_output {
+            Some(encryption_output) if encryption_output.encrypted_payload.is_empty() => {
+                problems.push(BuildProblem::EmptyEncryptedPayload);
+                None
+            }
+            Some(encryption_output) => Some(encryption_output),
+            None => {
+                problems.push(BuildProblem::MissingEncryptionOutput);
+                None
+            }
+        };
+
+        if self.blind_index.len() > crypto::MAX_INDEXED_KEYWORDS {
+            problems.push(BuildProblem::BlindIndexTooLarge);
+        }
+        let blind_index_version = self
+            .blind_index_version
+            .unwrap_or(crypto::LATEST_BLIND_INDEX_VERSION);
+        if !(crypto::BLIND_INDEX_VERSION_1..=crypto::LATEST_BLIND_INDEX_VERSION)
+            .contains(&blind_index_version)
+        {
+            problems.push(BuildProblem::UnsupportedBlindIndexVersion(
+                blind_index_version,
+            ));
+        }
+
+        let mut filenames = HashSet::new();
+        if !self
+            .attachments
+            .iter()
+            .all(|attachment| filenames.insert(attachment.filename()))
+        {
+            problems.push(BuildProblem::DuplicateAttachmentFilename);
+        }
+
+        if !has_unique_tags(&self.tags) {
+            problems.push(BuildProblem::DuplicateTag);
+        }
+
+        match (resource_name, encryption_output) {
+            (Some(resource_name), Some(encryption_output)) if problems.is_empty() => {
+                Ok(NewRecord {
+                    resource_name,
+                    encryption_output,
+                    blind_index: self.blind_index,
+                    blind_index_version,
+                    bound_to_resource_name: self.bound_to_resource_name,
+                    password_fingerprint: self.password_fingerprint,
+                    attachments: self.attachments,
+                    tags: self.tags,
+                })
+            }
+            _ => Err(BuildError { problems }),
+        }
+    }
+}
+
+/// Error of [`NewRecordBuilder::build()`] with all found problems.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub struct BuildError {
+    /// Found problems, never empty.
+    problems: Vec<BuildProblem>,
+}
+
+impl BuildError {
+    /// Get all found problems.
+    #[must_use]
+    pub fn problems(&self) -> &[BuildProblem] {
+        &self.problems
+    }
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid new record: ")?;
+        for (i, problem) in self.problems.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{problem}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Single problem found by [`NewRecordBuilder::build()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum BuildProblem {
+    #[error("resource name is missing")]
+    MissingResourceName,
+    #[error("{0}")]
+    InvalidResourceName(ResourceNameError),
+    #[error("encryption output is missing")]
+    MissingEncryptionOutput,
+    #[error("encrypted payload is empty")]
+    EmptyEncryptedPayload,
+    #[error("blind index has more than {} tokens", crypto::MAX_INDEXED_KEYWORDS)]
+    BlindIndexTooLarge,
+    #[error(
+        "blind index version {0} is not supported, latest supported is {}",
+        crypto::LATEST_BLIND_INDEX_VERSION
+    )]
+    UnsupportedBlindIndexVersion(u8),
+    #[error("several attachments have the same filename")]
+    DuplicateAttachmentFilename,
+    #[error("several tags are the same")]
+    DuplicateTag,
+}
+
+/// Change of an existing record.
+///
+/// Can be constructed only with [`RecordUpdate::new()`] or deserialized, so it always
+/// changes something.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "RawRecordUpdate")]
+pub struct RecordUpdate {
+    /// Current name of the resource.
+    resource_name: ResourceName,
+    /// New name of the resource, if it's renamed.
+    new_resource_name: Option<ResourceName>,
+    /// New encrypted record data, if it's changed.
+    encryption_output: Option<crypto::EncryptionOutput>,
+    /// New tags of the record replacing the old ones, if they are changed.
+    tags: Option<Vec<Tag>>,
+}
+
+impl RecordUpdate {
+    /// Construct update of the record of `resource_name`.
+    ///
+    /// `new_resource_name` equal to `resource_name` is not a rename and is dropped.
+    /// Empty `tags` remove all tags of the record.
+    ///
+    /// # Errors
+    ///
+    /// Fails if nothing is changed, the new encrypted payload is empty or tags are duplicated.
+    pub fn new(
+        resource_name: ResourceName,
+        new_resource_name: Option<ResourceName>,
+        encryption_output: Option<crypto::EncryptionOutput>,
+        tags: Option<Vec<Tag>>,
+    ) -> Result<Self, RecordUpdateError> {
+        let new_resource_name = new_resource_name.filter(|new_name| *new_name != resource_name);
+        if new_resource_name.is_none() && encryption_output.is_none() && tags.is_none() {
+            return Err(RecordUpdateError::NothingToUpdate);
+        }
+        if encryption_output
+            .as_ref()
+            .is_some_and(|output| output.encrypted_payload.is_empty())
+        {
+            return Err(RecordUpdateError::EmptyEncryptedPayload);
+        }
+        if tags
+            .as_deref()
+            .is_some_and(|new_tags| !has_unique_tags(new_tags))
+        {
+            return Err(RecordUpdateError::DuplicateTag);
+        }
+
+        Ok(Self {
+            resource_name,
+            new_resource_name,
+            encryption_output,
+            tags,
+        })
+    }
+
+    /// Get current name of the resource.
+    #[must_use]
+    pub const fn resource_name(&self) -> &ResourceName {
+        &self.resource_name
+    }
+
+    /// Get new name of the resource, if it's renamed.
+    #[must_use]
+    pub const fn new_resource_name(&self) -> Option<&ResourceName> {
+        self.new_resource_name.as_ref()
+    }
+
+    /// Get new encrypted record data, if it's changed.
+    #[must_use]
+    pub const fn encryption_output(&self) -> Option<&crypto::EncryptionOutput> {
+        self.encryption_output.as_ref()
+    }
+
+    /// Get new tags of the record, if they are changed.
+    #[must_use]
+    pub fn tags(&self) -> Option<&[Tag]> {
+        self.tags.as_deref()
+    }
+}
+
+/// Unvalidated [`RecordUpdate`] as it's deserialized.
+#[derive(Deserialize)]
+struct RawRecordUpdate {
+    /// Current name of the resource.
+    resource_name: ResourceName,
+    /// New name of the resource.
+    #[serde(default)]
+    new_resource_name: Option<ResourceName>,
+    /// New encrypted record data.
+    #[serde(default)]
+    encryption_output: Option<crypto::EncryptionOutput>,
+    /// New tags of the record.
+    #[serde(default)]
+    tags: Option<Vec<Tag>>,
+}
+
+impl TryFrom<RawRecordUpdate> for RecordUpdate {
+    type Error = RecordUpdateError;
+
+    fn try_from(raw: RawRecordUpdate) -> Result<Self, Self::Error> {
+        Self::new(
+            raw.resource_name,
+            raw.new_resource_name,
+            raw.encryption_output,
+            raw.tags,
+        )
+    }
+}
+
+/// Reason why the [`RecordUpdate`] is invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum RecordUpdateError {
+    #[error("record update changes nothing")]
+    NothingToUpdate,
+    #[error("encrypted payload is empty")]
+    EmptyEncryptedPayload,
+    #[error("several tags are the same")]
+    DuplicateTag,
+}
+
+/// Request to search records by a keyword of their content without revealing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BlindSearch {
+    /// Keyword blinded with the key of [`crypto::LATEST_BLIND_INDEX_VERSION`].
+    pub token: crypto::BlindToken,
+    /// Keyword blinded with the key of [`crypto::BLIND_INDEX_VERSION_1`] to find records indexed
+    /// before, if it differs from `token`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub legacy_token: Option<crypto::BlindToken>,
+}
+
+/// Data sent by the Web App to the bot on behalf of a Telegram user.
+///
+/// Serialized as `data` fields with an additional `user_id` field.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WebAppMessage<T> {
+    /// Id of the Telegram user who opened the Web App.
+    ///
+    /// Reported by the Telegram client without verification, so it only allows to notice
+    /// Web App buttons opened by someone else.
+    pub user_id: u64,
+    /// Sent data.
+    #[serde(flatten)]
+    pub data: T,
+}
+
+/// Plaintext of a record, which is encrypted into its payload.
+///
+/// It's the only format of payloads, so that all clients agree on it.
+/// Payloads of older clients with `resource_name` and `comments` fields are read as well.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordPayload {
+    /// Login, [`None`] if it's not set.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_non_empty",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub login: Option<String>,
+    /// Password, may be empty.
+    #[serde(default)]
+    pub password: String,
+    /// Any additional notes, [`None`] if there are none.
+    #[serde(
+        default,
+        alias = "comments",
+        deserialize_with = "deserialize_non_empty",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub notes: Option<String>,
+    /// Extra named fields, e.g. a PIN or security questions.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
+}
+
+impl RecordPayload {
+    /// Serialize payload to JSON to be encrypted.
+    ///
+    /// # Panics
+    ///
+    /// Never, cause payload consists of strings only.
+    #[must_use]
+    #[expect(
+        clippy::expect_used,
+        reason = "serialization of strings and string maps can't fail"
+    )]
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(self).expect("`RecordPayload` should always be serializable")
+    }
+
+    /// Deserialize payload from decrypted JSON.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `json` is not a valid payload.
+    pub fn from_json_str(json: &str) -> Result<Self, PayloadError> {
+        serde_json::from_str(json).map_err(PayloadError)
+    }
+}
+
+/// Deserialize optional string treating empty one as [`None`].
+fn deserialize_non_empty<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Option::<String>::deserialize(deserializer).map(|value| value.filter(|text| !text.is_empty()))
+}
+
+/// Error of [`RecordPayload::from_json_str()`].
+#[derive(Debug, thiserror::Error)]
+#[error("invalid record payload: {0}")]
+pub struct PayloadError(#[source] serde_json::Error);
+
+/// Version of the [`VaultExport`] format produced by this crate.
+pub const VAULT_EXPORT_VERSION: u16 = 1;
+
+/// Point in time as seconds since the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Timestamp(u64);
+
+impl Timestamp {
+    /// Construct timestamp `seconds` after the Unix epoch.
+    #[must_use]
+    pub const fn from_unix_seconds(seconds: u64) -> Self {
+        Self(seconds)
+    }
+
+    /// Get number of seconds since the Unix epoch.
+    #[must_use]
+    pub const fn unix_seconds(self) -> u64 {
+        self.0
+    }
+}
+
+/// Times before the Unix epoch are clamped to it.
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        Self(
+            time.duration_since(UNIX_EPOCH)
+                .map_or(0, |since_epoch| since_epoch.as_secs()),
+        )
+    }
+}
+
+/// Backup of all records of a vault, which can be restored on another deployment.
+///
+/// Serialized to JSON or, with the `postcard` feature, to a compact binary format.
+/// Decoding validates the export, see [`VaultExport::validate()`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultExport {
+    /// Version of the format, see [`VAULT_EXPORT_VERSION`].
+    pub version: u16,
+    /// Time the vault was exported at.
+    pub exported_at: Timestamp,
+    /// Exported records.
+    pub records: Vec<ExportedRecord>,
+}
+
+/// Record of a [`VaultExport`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ExportedRecord {
+    /// Name of the resource.
+    pub resource_name: ResourceName,
+    /// Encrypted record data.
     pub encryption_output: crypto::EncryptionOutput,
+    /// Times the record was created and last changed at.
+    ///
+    /// Missing in exports of records stored before it was tracked.
+    #[serde(default)]
+    pub metadata: Option<RecordMetadata>,
+}
+
+impl VaultExport {
+    /// Construct export of `records` of the latest [`VAULT_EXPORT_VERSION`].
+    #[must_use]
+    pub const fn new(exported_at: Timestamp, records: Vec<ExportedRecord>) -> Self {
+        Self {
+            version: VAULT_EXPORT_VERSION,
+            exported_at,
+            records,
+        }
+    }
+
+    /// Check that the export can be restored.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the version is not supported or several records have the same resource name.
+    pub fn validate(&self) -> Result<(), VaultExportError> {
+        if !(1..=VAULT_EXPORT_VERSION).contains(&self.version) {
+            return Err(VaultExportError::UnsupportedVersion(self.version));
+        }
+
+        let mut names = HashSet::with_capacity(self.records.len());
+        if let Some(duplicate) = self
+            .records
+            .iter()
+            .find(|record| !names.insert(&record.resource_name))
+        {
+            return Err(VaultExportError::DuplicateResourceName(
+                duplicate.resource_name.clone(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Serialize export to JSON.
+    ///
+    /// # Panics
+    ///
+    /// Never, cause all parts of the export are serializable.
+    #[must_use]
+    #[expect(
+        clippy::expect_used,
+        reason = "serialization of strings, numbers and bytes can't fail"
+    )]
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(self).expect("`VaultExport` should always be serializable")
+    }
+
+    /// Deserialize and validate export from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `json` is not an export or the export is invalid.
+    pub fn from_json_str(json: &str) -> Result<Self, VaultExportError> {
+        let export: Self = serde_json::from_str(json).map_err(VaultExportError::Json)?;
+        export.validate()?;
+        Ok(export)
+    }
+
+    /// Serialize export to the compact binary format.
+    ///
+    /// # Panics
+    ///
+    /// Never, cause all parts of the export are serializable.
+    #[cfg(feature = "postcard")]
+    #[must_use]
+    #[expect(
+        clippy::expect_used,
+        reason = "serialization of strings, numbers and bytes can't fail"
+    )]
+    pub fn to_postcard(&self) -> Vec<u8> {
+        postcard::to_stdvec(self).expect("`VaultExport` should always be serializable")
+    }
+
+    /// Deserialize and validate export from the compact binary format.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `bytes` are not an export or the export is invalid.
+    #[cfg(feature = "postcard")]
+    pub fn from_postcard(bytes: &[u8]) -> Result<Self, VaultExportError> {
+        let export: Self = postcard::from_bytes(bytes).map_err(VaultExportError::Postcard)?;
+        export.validate()?;
+        Ok(export)
+    }
+}
+
+/// Times a record was created and last changed at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RecordMetadata {
+    /// Time the record was created at.
+    pub created_at: Timestamp,
+    /// Time the record was last changed at.
+    pub updated_at: Timestamp,
+}
+
+#[cfg(feature = "prost")]
+impl RecordMetadata {
+    /// Construct metadata from protobuf timestamps sent by the password storage.
+    ///
+    /// # Errors
+    ///
+    /// Fails if any of the timestamps is out of the supported range.
+    pub fn from_proto(
+        created_at: prost_types::Timestamp,
+        updated_at: prost_types::Timestamp,
+    ) -> Result<Self, TimestampOutOfRange> {
+        Ok(Self {
+            created_at: from_proto_timestamp(created_at)?,
+            updated_at: from_proto_timestamp(updated_at)?,
+        })
+    }
+
+    /// Convert metadata into protobuf timestamps of creation and last change.
+    #[must_use]
+    pub fn to_proto(self) -> (prost_types::Timestamp, prost_types::Timestamp) {
+        (
+            to_proto_timestamp(self.created_at),
+            to_proto_timestamp(self.updated_at),
+        )
+    }
+}
+
+/// Convert protobuf `timestamp` into [`Timestamp`], dropping fractions of a second.
+///
+/// # Errors
+///
+/// Fails if `timestamp` is before the Unix epoch.
+#[cfg(feature = "prost")]
+pub fn from_proto_timestamp(
+    mut timestamp: prost_types::Timestamp,
+) -> Result<Timestamp, TimestampOutOfRange> {
+    timestamp.normalize();
+    u64::try_from(timestamp.seconds)
+        .map(Timestamp::from_unix_seconds)
+        .map_err(|_err| TimestampOutOfRange(timestamp))
+}
+
+/// Convert `timestamp` into protobuf timestamp.
+///
+/// Timestamps beyond the range of protobuf are clamped to its end.
+#[cfg(feature = "prost")]
+#[must_use]
+pub fn to_proto_timestamp(timestamp: Timestamp) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: i64::try_from(timestamp.unix_seconds()).unwrap_or(i64::MAX),
+        nanos: 0,
+    }
+}
+
+/// Error of [`from_proto_timestamp()`].
+#[cfg(feature = "prost")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("timestamp {0} is out of the supported range")]
+pub struct TimestampOutOfRange(pub prost_types::Timestamp);
+
+/// Reason why the [`VaultExport`] can't be restored.
+#[derive(Debug, thiserror::Error)]
+pub enum VaultExportError {
+    #[error("unsupported vault export version {0}, expected at most {VAULT_EXPORT_VERSION}")]
+    UnsupportedVersion(u16),
+    #[error("resource name \"{0}\" is exported more than once")]
+    DuplicateResourceName(ResourceName),
+    #[error("invalid vault export JSON: {0}")]
+    Json(#[source] serde_json::Error),
+    #[cfg(feature = "postcard")]
+    #[error("invalid binary vault export: {0}")]
+    Postcard(#[source] postcard::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use super::*;
+
+    fn encryption_output() -> crypto::EncryptionOutput {
+        crypto::EncryptionOutput {
+            version: crypto::OUTPUT_VERSION_1,
+            encrypted_payload: b"SomeSecret".to_vec(),
+            salt: crypto::Salt::Aes256Gcm([1; crypto::AES_256_GCM_SALT_SIZE]),
+            kdf_iterations: crypto::DEFAULT_KDF_ITERATIONS,
+            kdf_salt: None,
+            key_commitment: None,
+        }
+    }
+
+    #[test]
+    fn build_with_all_parts_succeeds() {
+        let record = NewRecord::builder()
+            .resource_name("test.resource.com")
+            .encryption_output(encryption_output())
+            .build()
+            .unwrap();
+
+        assert_eq!(record.resource_name().as_str(), "test.resource.com");
+        assert_eq!(record.encryption_output(), &encryption_output());
+    }
+
+    #[test]
+    fn build_with_validated_name_succeeds() {
+        let resource_name = ResourceName::try_from("test.resource.com").unwrap();
+
+        let record = NewRecord::builder()
+            .resource_name(resource_name.clone())
+            .encryption_output(encryption_output())
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            record.into_parts(),
+            (resource_name, encryption_output(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn build_with_blind_index_succeeds() {
+        let blind_index = vec![[1; crypto::BLIND_TOKEN_SIZE]; crypto::MAX_INDEXED_KEYWORDS];
+
+        let record = NewRecord::builder()
+            .resource_name("test.resource.com")
+            .encryption_output(encryption_output())
+            .blind_index(blind_index.clone())
+            .build()
+            .unwrap();
+
+        assert_eq!(record.blind_index(), blind_index);
+        assert_eq!(
+            record.blind_index_version(),
+            crypto::LATEST_BLIND_INDEX_VERSION
+        );
+    }
+
+    #[test]
+    fn blind_index_version_is_checked_and_legacy_by_default_on_deserialization() {
+        let builder = NewRecord::builder()
+            .resource_name("test.resource.com")
+            .encryption_output(encryption_output());
+
+        let unsupported = crypto::LATEST_BLIND_INDEX_VERSION + 1;
+        assert_eq!(
+            builder
+                .clone()
+                .blind_index_version(unsupported)
+                .build()
+                .unwrap_err()
+                .problems(),
+            [BuildProblem::UnsupportedBlindIndexVersion(unsupported)]
+        );
+
+        let mut legacy = serde_json::to_value(builder.build().unwrap()).unwrap();
+        legacy
+            .as_object_mut()
+            .unwrap()
+            .remove("blind_index_version");
+        let deserialized: NewRecord = serde_json::from_value(legacy).unwrap();
+        assert_eq!(
+            deserialized.blind_index_version(),
+            crypto::BLIND_INDEX_VERSION_1
+        );
+    }
+
+    #[test]
+    fn binding_to_resource_name_is_kept_and_defaults_to_false() {
+        let builder = NewRecord::builder()
+            .resource_name("test.resource.com")
+            .encryption_output(encryption_output());
+
+        let unbound = builder.clone().build().unwrap();
+        assert!(!unbound.is_bound_to_resource_name());
+
+        let bound = builder.bound_to_resource_name(true).build().unwrap();
+        assert!(bound.is_bound_to_resource_name());
+
+        let mut legacy = serde_json::to_value(&bound).unwrap();
+        legacy
+            .as_object_mut()
+            .unwrap()
+            .remove("bound_to_resource_name");
+        let deserialized: NewRecord = serde_json::from_value(legacy).unwrap();
+        assert!(!deserialized.is_bound_to_resource_name());
+    }
+
+    #[test]
+    fn password_fingerprint_is_kept_and_optional() {
+        let builder = NewRecord::builder()
+            .resource_name("test.resource.com")
+            .encryption_output(encryption_output());
+
+        let without = builder.clone().build().unwrap();
+        assert_eq!(without.password_fingerprint(), None);
+
+        let fingerprint = [7; crypto::PASSWORD_FINGERPRINT_SIZE];
+        let with = builder.password_fingerprint(fingerprint).build().unwrap();
+        assert_eq!(with.password_fingerprint(), Some(fingerprint));
+
+        let mut legacy = serde_json::to_value(&with).unwrap();
+        legacy
+            .as_object_mut()
+            .unwrap()
+            .remove("password_fingerprint");
+        let deserialized: NewRecord = serde_json::from_value(legacy).unwrap();
+        assert_eq!(deserialized.password_fingerprint(), None);
+    }
+
+    #[test]
+    fn build_with_too_large_blind_index_fails() {
+        let error = NewRecord::builder()
+            .resource_name("test.resource.com")
+            .encryption_output(encryption_output())
+            .blind_index(vec![
+                [1; crypto::BLIND_TOKEN_SIZE];
+                crypto::MAX_INDEXED_KEYWORDS + 1
+            ])
+            .build()
+            .unwrap_err();
+
+        assert_eq!(error.problems(), [BuildProblem::BlindIndexTooLarge]);
+    }
+
+    #[test]
+    fn build_with_longest_name_succeeds() {
+        let resource_name = "a".repeat(MAX_RESOURCE_NAME_LENGTH);
+
+        let record = NewRecord::builder()
+            .resource_name(resource_name.clone())
+            .encryption_output(encryption_output())
+            .build()
+            .unwrap();
+
+        assert_eq!(record.resource_name().as_str(), resource_name);
+    }
+
+    #[test]
+    fn build_without_parts_reports_all_of_them() {
+        let error = NewRecord::builder().build().unwrap_err();
+
+        assert_eq!(
+            error.problems(),
+            [
+                BuildProblem::MissingResourceName,
+                BuildProblem::MissingEncryptionOutput
+            ]
+        );
+    }
+
+    #[test]
+    fn build_with_invalid_parts_reports_all_of_them() {
+        let error = NewRecord::builder()
+            .resource_name("")
+            .encryption_output(crypto::EncryptionOutput {
+                version: crypto::OUTPUT_VERSION_1,
+                encrypted_payload: Vec::new(),
+                salt: crypto::Salt::Aes256Gcm([1; crypto::AES_256_GCM_SALT_SIZE]),
+                kdf_iterations: crypto::DEFAULT_KDF_ITERATIONS,
+                kdf_salt: None,
+                key_commitment: None,
+            })
+            .build()
+            .unwrap_err();
+
+        assert_eq!(
+            error.problems(),
+            [
+                BuildProblem::InvalidResourceName(ResourceNameError::Empty),
+                BuildProblem::EmptyEncryptedPayload
+            ]
+        );
+        assert_eq!(
+            error.to_string(),
+            "Invalid new record: resource name cannot be empty, encrypted payload is empty"
+        );
+    }
+
+    #[test]
+    fn build_with_invalid_resource_name_fails() {
+        let cases = [
+            (String::new(), ResourceNameError::Empty),
+            (" \t\n".to_owned(), ResourceNameError::Empty),
+            ("test\0resource".to_owned(), ResourceNameError::ContainsNul),
+            (
+                "test\nresource".to_owned(),
+                ResourceNameError::ContainsControl,
+            ),
+            (
+                "test\u{1b}[31mresource".to_owned(),
+                ResourceNameError::ContainsControl,
+            ),
+            (
+                "a".repeat(MAX_RESOURCE_NAME_LENGTH + 1),
+                ResourceNameError::TooLong,
+            ),
+        ];
+
+        for (resource_name, expected_error) in cases {
+            let error = NewRecord::builder()
+                .resource_name(resource_name)
+                .encryption_output(encryption_output())
+                .build()
+                .unwrap_err();
+
+            assert_eq!(
+                error.problems(),
+                [BuildProblem::InvalidResourceName(expected_error)]
+            );
+        }
+    }
+
+    #[test]
+    fn build_with_missing_encryption_output_fails() {
+        let error = NewRecord::builder()
+            .resource_name("test.resource.com")
+            .build()
+            .unwrap_err();
+
+        assert_eq!(error.problems(), [BuildProblem::MissingEncryptionOutput]);
+    }
+
+    #[test]
+    fn deserialize_validates_resource_name() {
+        let record = NewRecord::builder()
+            .resource_name("test.resource.com")
+            .encryption_output(encryption_output())
+            .build()
+            .unwrap();
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(serde_json::from_str::<NewRecord>(&json).unwrap(), record);
+
+        let invalid_json = json.replace("test.resource.com", "");
+        let _error = serde_json::from_str::<NewRecord>(&invalid_json).unwrap_err();
+    }
+
+    #[test]
+    fn new_validates_resource_name() {
+        let record = NewRecord::new("test.resource.com", encryption_output()).unwrap();
+        assert_eq!(
+            record,
+            NewRecord::builder()
+                .resource_name("test.resource.com")
+                .encryption_output(encryption_output())
+                .build()
+                .unwrap()
+        );
+
+        let error = NewRecord::new("test\nresource", encryption_output()).unwrap_err();
+        assert_eq!(
+            error.problems(),
+            [BuildProblem::InvalidResourceName(
+                ResourceNameError::ContainsControl
+            )]
+        );
+    }
+
+    #[test]
+    fn raw_record_conversion_reports_all_problems() {
+        let json = serde_json::json!({
+            "resource_name": "test\nresource",
+            "encryption_output": crypto::EncryptionOutput {
+                encrypted_payload: Vec::new(),
+                ..encryption_output()
+            },
+        });
+
+        let raw = serde_json::from_value::<RawNewRecord>(json.clone()).unwrap();
+        let error = NewRecord::try_from(raw).unwrap_err();
+        assert_eq!(
+            error.problems(),
+            [
+                BuildProblem::InvalidResourceName(ResourceNameError::ContainsControl),
+                BuildProblem::EmptyEncryptedPayload,
+            ]
+        );
+
+        let _error = serde_json::from_value::<NewRecord>(json).unwrap_err();
+    }
+
+    #[test]
+    fn deserialize_without_blind_index_gives_empty_one() {
+        let json = serde_json::json!({
+            "resource_name": "test.resource.com",
+            "encryption_output": encryption_output(),
+        });
+
+        let record = serde_json::from_value::<NewRecord>(json).unwrap();
+        assert!(record.blind_index().is_empty());
+        assert!(record.attachments().is_empty());
+        assert!(record.tags().is_empty());
+    }
+
+    #[test]
+    fn tags_survive_serde_round_trip() {
+        let record = NewRecord::builder()
+            .resource_name("test.resource.com")
+            .encryption_output(encryption_output())
+            .tags(vec![
+                Tag::try_from("work").unwrap(),
+                Tag::try_from("finance").unwrap(),
+            ])
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(
+            json.get("tags").unwrap(),
+            &serde_json::json!(["work", "finance"])
+        );
+        assert_eq!(serde_json::from_value::<NewRecord>(json).unwrap(), record);
+    }
+
+    #[test]
+    fn tag_is_validated() {
+        assert_eq!(Tag::try_from("personal").unwrap().as_str(), "personal");
+        assert_eq!(
+            Tag::try_from("a".repeat(MAX_TAG_LENGTH))
+                .unwrap()
+                .as_str()
+                .len(),
+            MAX_TAG_LENGTH
+        );
+
+        for (tag, expected) in [
+            ("", TagError::Empty),
+            ("two words", TagError::ContainsWhitespace),
+            ("tab\there", TagError::ContainsWhitespace),
+            ("Work", TagError::NotLowercase),
+            ("\u{414}\u{41e}\u{41c}", TagError::NotLowercase),
+        ] {
+            assert_eq!(Tag::try_from(tag).unwrap_err(), expected, "{tag:?}");
+        }
+        assert_eq!(
+            Tag::try_from("a".repeat(MAX_TAG_LENGTH + 1)).unwrap_err(),
+            TagError::TooLong
+        );
+    }
+
+    #[test]
+    fn deserialize_validates_tags() {
+        let json = serde_json::json!({
+            "resource_name": "test.resource.com",
+            "encryption_output": encryption_output(),
+            "tags": ["Work"],
+        });
+
+        let _error = serde_json::from_value::<NewRecord>(json).unwrap_err();
+    }
+
+    #[test]
+    fn new_attachment_validates_filename_and_size() {
+        let attachment =
+            NewAttachment::new("recovery.pdf", encryption_output(), MAX_ATTACHMENT_SIZE).unwrap();
+        assert_eq!(attachment.filename(), "recovery.pdf");
+        assert_eq!(attachment.size(), MAX_ATTACHMENT_SIZE);
+
+        for (filename, size, error) in [
+            (" ".to_owned(), 1, AttachmentError::EmptyFilename),
+            ("a\0b".to_owned(), 1, AttachmentError::FilenameContainsNul),
+            (
+                "a".repeat(MAX_ATTACHMENT_FILENAME_LENGTH.saturating_add(1)),
+                1,
+                AttachmentError::FilenameTooLong,
+            ),
+            (
+                "recovery.pdf".to_owned(),
+                MAX_ATTACHMENT_SIZE.saturating_add(1),
+                AttachmentError::TooLarge,
+            ),
+        ] {
+            assert_eq!(
+                NewAttachment::new(filename, encryption_output(), size),
+                Err(error)
+            );
+        }
+
+        let empty = crypto::EncryptionOutput {
+            encrypted_payload: Vec::new(),
+            ..encryption_output()
+        };
+        assert_eq!(
+            NewAttachment::new("recovery.pdf", empty, 0),
+            Err(AttachmentError::EmptyEncryptedContent)
+        );
+    }
+
+    #[test]
+    fn build_with_duplicate_attachment_filenames_fails() {
+        let attachment = NewAttachment::new("recovery.pdf", encryption_output(), 10).unwrap();
+        let builder = NewRecord::builder()
+            .resource_name("test.resource.com")
+            .encryption_output(encryption_output());
+
+        let record = builder
+            .clone()
+            .attachments(vec![attachment.clone()])
+            .build()
+            .unwrap();
+        assert_eq!(record.attachments(), [attachment.clone()]);
+
+        let error = builder
+            .attachments(vec![attachment.clone(), attachment])
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            error.problems(),
+            [BuildProblem::DuplicateAttachmentFilename]
+        );
+    }
+
+    #[test]
+    fn build_with_duplicate_tags_fails() {
+        let tag = Tag::try_from("work").unwrap();
+
+        let error = NewRecord::builder()
+            .resource_name("test.resource.com")
+            .encryption_output(encryption_output())
+            .tags(vec![tag.clone(), tag])
+            .build()
+            .unwrap_err();
+
+        assert_eq!(error.problems(), [BuildProblem::DuplicateTag]);
+    }
+
+    #[test]
+    fn record_update_requires_a_change() {
+        let name = || ResourceName::try_from("test.resource.com").unwrap();
+
+        assert_eq!(
+            RecordUpdate::new(name(), None, None, None).unwrap_err(),
+            RecordUpdateError::NothingToUpdate
+        );
+        assert_eq!(
+            RecordUpdate::new(name(), Some(name()), None, None).unwrap_err(),
+            RecordUpdateError::NothingToUpdate
+        );
+        assert_eq!(
+            RecordUpdate::new(
+                name(),
+                None,
+                Some(crypto::EncryptionOutput {
+                    encrypted_payload: Vec::new(),
+                    ..encryption_output()
+                }),
+                None,
+            )
+            .unwrap_err(),
+            RecordUpdateError::EmptyEncryptedPayload
+        );
+
+        let tag = || Tag::try_from("work").unwrap();
+        assert_eq!(
+            RecordUpdate::new(name(), None, None, Some(vec![tag(), tag()])).unwrap_err(),
+            RecordUpdateError::DuplicateTag
+        );
+
+        let update =
+            RecordUpdate::new(name(), Some(name()), Some(encryption_output()), None).unwrap();
+        assert_eq!(update.new_resource_name(), None);
+        assert_eq!(update.encryption_output(), Some(&encryption_output()));
+        assert_eq!(update.tags(), None);
+
+        let untag = RecordUpdate::new(name(), None, None, Some(Vec::new())).unwrap();
+        assert_eq!(untag.tags(), Some([].as_slice()));
+    }
+
+    #[test]
+    fn record_update_round_trip() {
+        let update = RecordUpdate::new(
+            ResourceName::try_from("test.resource.com").unwrap(),
+            Some(ResourceName::try_from("new.resource.com").unwrap()),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let json = serde_json::to_value(&update).unwrap();
+        assert_eq!(
+            serde_json::from_value::<RecordUpdate>(json).unwrap(),
+            update
+        );
+
+        let rename_only = serde_json::json!({
+            "resource_name": "test.resource.com",
+            "new_resource_name": "new.resource.com",
+        });
+        assert_eq!(
+            serde_json::from_value::<RecordUpdate>(rename_only).unwrap(),
+            update
+        );
+    }
+
+    #[test]
+    fn deserialize_validates_record_update() {
+        for json in [
+            serde_json::json!({"resource_name": "test.resource.com"}),
+            serde_json::json!({
+                "resource_name": "test.resource.com",
+                "new_resource_name": "test.resource.com",
+            }),
+            serde_json::json!({"resource_name": "", "new_resource_name": "new.resource.com"}),
+        ] {
+            let _error = serde_json::from_value::<RecordUpdate>(json).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn web_app_message_extends_data_with_user_id() {
+        let message = WebAppMessage {
+            user_id: 42,
+            data: BlindSearch {
+                token: [3; crypto::BLIND_TOKEN_SIZE],
+                legacy_token: None,
+            },
+        };
+
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "user_id": message.user_id,
+                "token": message.data.token,
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<WebAppMessage<BlindSearch>>(json).unwrap(),
+            message
+        );
+    }
+
+    #[test]
+    fn record_payload_round_trip() {
+        let payload = RecordPayload {
+            login: Some("user".to_owned()),
+            password: "secret".to_owned(),
+            notes: Some("recovery codes in the safe".to_owned()),
+            extra: BTreeMap::from([("PIN".to_owned(), "1234".to_owned())]),
+        };
+
+        let json = payload.to_json_string();
+        assert_eq!(RecordPayload::from_json_str(&json).unwrap(), payload);
+    }
+
+    #[test]
+    fn record_payload_skips_missing_fields() {
+        let payload = RecordPayload {
+            password: "secret".to_owned(),
+            ..RecordPayload::default()
+        };
+
+        assert_eq!(payload.to_json_string(), r#"{"password":"secret"}"#);
+    }
+
+    #[test]
+    fn legacy_record_payload_is_read() {
+        let json = serde_json::json!({
+            "resource_name": "test.resource.com",
+            "login": "",
+            "password": "secret",
+            "comments": "old notes",
+        });
+
+        assert_eq!(
+            RecordPayload::from_json_str(&json.to_string()).unwrap(),
+            RecordPayload {
+                login: None,
+                password: "secret".to_owned(),
+                notes: Some("old notes".to_owned()),
+                extra: BTreeMap::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn malformed_record_payload_is_rejected() {
+        let _error = RecordPayload::from_json_str(r#"{"password": 42}"#).unwrap_err();
+    }
+
+    const fn record_metadata() -> RecordMetadata {
+        RecordMetadata {
+            created_at: Timestamp::from_unix_seconds(1_600_000_000),
+            updated_at: Timestamp::from_unix_seconds(1_700_000_000),
+        }
+    }
+
+    #[test]
+    fn record_metadata_is_unix_seconds_in_json() {
+        let json = serde_json::to_value(record_metadata()).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "created_at": 1_600_000_000_u64,
+                "updated_at": 1_700_000_000_u64,
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<RecordMetadata>(json).unwrap(),
+            record_metadata()
+        );
+    }
+
+    #[test]
+    fn exported_record_without_metadata_is_read() {
+        let json = serde_json::json!({
+            "resource_name": "first.com",
+            "encryption_output": encryption_output(),
+        });
+
+        let record = serde_json::from_value::<ExportedRecord>(json).unwrap();
+        assert_eq!(record.metadata, None);
+    }
+
+    #[cfg(feature = "prost")]
+    #[test]
+    fn record_metadata_proto_round_trip() {
+        let metadata = record_metadata();
+
+        let (created_at, mut updated_at) = metadata.to_proto();
+        assert_eq!(updated_at.seconds, 1_700_000_000);
+        assert_eq!(updated_at.nanos, 0_i32);
+
+        // Fractions of a second are dropped
+        updated_at.nanos = 123_456_789_i32;
+        assert_eq!(
+            RecordMetadata::from_proto(created_at, updated_at).unwrap(),
+            metadata
+        );
+    }
+
+    #[cfg(feature = "prost")]
+    #[test]
+    fn proto_timestamp_before_epoch_is_rejected() {
+        let timestamp = prost_types::Timestamp {
+            seconds: -1,
+            nanos: 0,
+        };
+
+        assert_eq!(
+            from_proto_timestamp(timestamp).unwrap_err(),
+            TimestampOutOfRange(timestamp)
+        );
+    }
+
+    fn vault_export() -> VaultExport {
+        VaultExport::new(
+            Timestamp::from_unix_seconds(1_700_000_000),
+            vec![
+                ExportedRecord {
+                    resource_name: ResourceName::try_from("first.com").unwrap(),
+                    encryption_output: encryption_output(),
+                    metadata: Some(record_metadata()),
+                },
+                ExportedRecord {
+                    resource_name: ResourceName::try_from("second.com").unwrap(),
+                    encryption_output: encryption_output(),
+                    metadata: None,
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn vault_export_json_round_trip() {
+        let export = vault_export();
+
+        let restored = VaultExport::from_json_str(&export.to_json_string()).unwrap();
+
+        assert_eq!(restored, export);
+        assert_eq!(restored.version, VAULT_EXPORT_VERSION);
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn vault_export_postcard_round_trip() {
+        let export = vault_export();
+
+        let bytes = export.to_postcard();
+
+        assert!(bytes.len() < export.to_json_string().len());
+        assert_eq!(VaultExport::from_postcard(&bytes).unwrap(), export);
+    }
+
+    #[test]
+    fn vault_export_with_unsupported_version_is_rejected() {
+        for version in [0, VAULT_EXPORT_VERSION + 1] {
+            let mut export = vault_export();
+            export.version = version;
+
+            assert!(matches!(
+                export.validate(),
+                Err(VaultExportError::UnsupportedVersion(unsupported)) if unsupported == version
+            ));
+            assert!(matches!(
+                VaultExport::from_json_str(&export.to_json_string()),
+                Err(VaultExportError::UnsupportedVersion(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn vault_export_with_duplicate_names_is_rejected() {
+        let mut export = vault_export();
+        let mut duplicate = export.records.first().unwrap().clone();
+        duplicate.encryption_output.encrypted_payload = b"OtherSecret".to_vec();
+        export.records.push(duplicate);
+
+        assert!(matches!(
+            export.validate(),
+            Err(VaultExportError::DuplicateResourceName(name)) if name.as_str() == "first.com"
+        ));
+    }
+
+    #[test]
+    fn malformed_vault_export_is_rejected() {
+        assert!(matches!(
+            VaultExport::from_json_str(r#"{"version": 1, "records": []}"#),
+            Err(VaultExportError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn timestamp_before_epoch_is_clamped() {
+        let before_epoch = UNIX_EPOCH
+            .checked_sub(std::time::Duration::from_secs(1))
+            .unwrap();
+        let after_epoch = UNIX_EPOCH
+            .checked_add(std::time::Duration::from_secs(42))
+            .unwrap();
+
+        assert_eq!(Timestamp::from(before_epoch).unix_seconds(), 0);
+        assert_eq!(
+            Timestamp::from(after_epoch),
+            Timestamp::from_unix_seconds(42)
+        );
+    }
 }
@@ -0,0 +1,107 @@
+//! Throughput benchmarks of [`PasswordStorage`] service methods.
+//!
+//! Benchmarks need a Postgres database specified by `TEST_DATABASE_URL` environment variable,
+//! the same one as tests, and are skipped if it's not set. Every benchmark group works in its own
+//! schema, see [`TestSchema`].
+//!
+//! Under `cargo test` every benchmark is run only once to check that it works.
+
+#![expect(clippy::unwrap_used, reason = "it's ok in benchmarks")]
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use telepass_password_storage::{
+    grpc::{self, password_storage_server::PasswordStorage as _},
+    service::{
+        test_db::{seeded_resource_name, TestSchema},
+        PasswordStorage,
+    },
+};
+use tokio::runtime::Runtime;
+use tonic::Request;
+
+/// Number of records in the table for read benchmarks.
+const SEEDED_RECORDS: u32 = 1000;
+/// Index of the seeded record to get, in the middle of the table.
+const GOTTEN_RECORD_INDEX: u32 = 500;
+
+/// Create runtime to run service methods in.
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+}
+
+/// Construct a request to add record for `resource_name`.
+fn add_request(resource_name: String) -> grpc::AddRequest {
+    grpc::AddRequest {
+        resource: Some(grpc::Resource {
+            name: resource_name,
+        }),
+        encrypted_payload: b"encrypted payload".to_vec(),
+        salt: vec![1; 12],
+        ..grpc::AddRequest::default()
+    }
+}
+
+/// Get seeded record with [`GOTTEN_RECORD_INDEX`].
+async fn get(service: &PasswordStorage, bypass_cache: bool) -> grpc::Record {
+    service
+        .get(Request::new(grpc::GetRequest {
+            name: seeded_resource_name(GOTTEN_RECORD_INDEX),
+            bypass_cache,
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+}
+
+/// Benchmark adding new records.
+fn add(c: &mut Criterion) {
+    let Some(schema) = TestSchema::create("bench_add") else {
+        return;
+    };
+    let rt = runtime();
+    let service = rt.block_on(schema.fresh_service(0));
+    let mut resource_names = (0..).map(seeded_resource_name);
+
+    c.bench_function("add", |b| {
+        b.iter(|| {
+            let request = add_request(resource_names.next().unwrap());
+            rt.block_on(service.add(Request::new(request))).unwrap()
+        });
+    });
+}
+
+/// Benchmark reading from the table with [`SEEDED_RECORDS`] records, all of which are cached.
+fn read(c: &mut Criterion) {
+    let Some(schema) = TestSchema::create("bench_read") else {
+        return;
+    };
+    schema.seed(SEEDED_RECORDS);
+    let rt = runtime();
+    let service = rt.block_on(schema.service(SEEDED_RECORDS));
+
+    c.bench_function("get cached", |b| {
+        b.iter(|| rt.block_on(get(&service, false)));
+    });
+    c.bench_function("get uncached", |b| {
+        b.iter(|| rt.block_on(get(&service, true)));
+    });
+    c.bench_function("list", |b| {
+        b.iter(|| {
+            rt.block_on(service.list(Request::new(grpc::Empty {})))
+                .unwrap()
+        });
+    });
+    c.bench_function("search", |b| {
+        b.iter(|| {
+            let request = grpc::Resource {
+                name: "resource-5".to_owned(),
+            };
+            rt.block_on(service.search(Request::new(request))).unwrap()
+        });
+    });
+}
+
+criterion_group!(benches, add, read);
+criterion_main!(benches);
@@ -0,0 +1 @@
+DROP INDEX passwords_lower_resource_name_idx
@@ -0,0 +1,3 @@
+-- Resource names are unique regardless of their case.
+-- Fails if there are already resources differing only in case, they should be renamed manually.
+CREATE UNIQUE INDEX passwords_lower_resource_name_idx ON passwords (lower(resource_name));
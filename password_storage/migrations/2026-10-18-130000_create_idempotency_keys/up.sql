@@ -0,0 +1,9 @@
+-- Keys of successfully processed `Add` requests to make their retries safe.
+-- Expired keys are removed by the service.
+CREATE TABLE idempotency_keys (
+  key VARCHAR(64) PRIMARY KEY,
+  resource_name VARCHAR(255) NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idempotency_keys_created_at_idx ON idempotency_keys (created_at);
@@ -0,0 +1 @@
+DROP TABLE blind_index
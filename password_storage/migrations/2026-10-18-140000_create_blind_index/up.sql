@@ -0,0 +1,12 @@
+-- Keywords of records content blinded by the client to search inside encrypted payloads.
+-- Tokens follow their record when it's renamed.
+CREATE TABLE blind_index (
+  resource_name VARCHAR(255) NOT NULL
+    REFERENCES passwords (resource_name) ON DELETE CASCADE ON UPDATE CASCADE,
+  token BYTEA NOT NULL,
+  -- Version of the key derivation the client used to blind the keyword.
+  version INTEGER NOT NULL DEFAULT 0 CHECK (version >= 0),
+  PRIMARY KEY (resource_name, token)
+);
+
+CREATE INDEX blind_index_token_idx ON blind_index (token);
@@ -0,0 +1 @@
+ALTER TABLE passwords DROP COLUMN kdf_iterations
\ No newline at end of file
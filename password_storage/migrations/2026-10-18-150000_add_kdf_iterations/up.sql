@@ -0,0 +1,3 @@
+-- Number of key derivation iterations used to encrypt the payload.
+-- `NULL` for records encrypted before it was stored, clients use their default for them.
+ALTER TABLE passwords ADD COLUMN kdf_iterations INTEGER CHECK (kdf_iterations > 0);
@@ -0,0 +1 @@
+ALTER TABLE passwords DROP COLUMN kdf_salt
\ No newline at end of file
@@ -0,0 +1,3 @@
+-- Random salt of the key derivation used to encrypt the payload.
+-- `NULL` for records encrypted before it was stored, clients use their legacy constant salt for them.
+ALTER TABLE passwords ADD COLUMN kdf_salt BYTEA;
@@ -0,0 +1 @@
+ALTER TABLE passwords DROP COLUMN algorithm
\ No newline at end of file
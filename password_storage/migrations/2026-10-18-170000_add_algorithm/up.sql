@@ -0,0 +1,3 @@
+-- Encryption algorithm of the payload as defined by `Algorithm` enum in the protobuf schema.
+-- Records encrypted before it was stored use the default one.
+ALTER TABLE passwords ADD COLUMN algorithm INTEGER NOT NULL DEFAULT 0;
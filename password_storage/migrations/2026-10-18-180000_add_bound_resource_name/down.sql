@@ -0,0 +1 @@
+ALTER TABLE passwords DROP COLUMN bound_to_resource_name
\ No newline at end of file
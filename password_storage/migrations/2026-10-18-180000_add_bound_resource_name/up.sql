@@ -0,0 +1,5 @@
+-- Whether the payload is bound to `resource_name` as associated data of the encryption.
+-- The bound name itself isn't stored, cause a copy of the payload could be moved to another record
+-- together with it. Payloads bound to another name were copied by duplicating or renaming and can't be
+-- decrypted with their current name anymore, which is exactly what the binding is for.
+ALTER TABLE passwords ADD COLUMN bound_to_resource_name BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,11 @@
+-- Passwords of records blinded by the client to detect their reuse across records.
+-- Fingerprints follow their record when it's renamed.
+CREATE TABLE password_fingerprints (
+  resource_name VARCHAR(255) PRIMARY KEY
+    REFERENCES passwords (resource_name) ON DELETE CASCADE ON UPDATE CASCADE,
+  fingerprint BYTEA NOT NULL,
+  -- Version of the key derivation the client used to blind the password.
+  version INTEGER NOT NULL DEFAULT 0 CHECK (version >= 0)
+);
+
+CREATE INDEX password_fingerprints_fingerprint_idx ON password_fingerprints (fingerprint);
@@ -0,0 +1,2 @@
+ALTER TABLE passwords DROP COLUMN revision;
+DROP SEQUENCE passwords_revision_seq;
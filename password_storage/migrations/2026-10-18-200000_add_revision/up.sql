@@ -0,0 +1,5 @@
+-- Version of the record to detect concurrent mutations, changes on every mutation.
+-- Taken from a sequence, so a record added again under the same name never gets
+-- a revision of the deleted one.
+CREATE SEQUENCE passwords_revision_seq;
+ALTER TABLE passwords ADD COLUMN revision BIGINT NOT NULL DEFAULT nextval('passwords_revision_seq');
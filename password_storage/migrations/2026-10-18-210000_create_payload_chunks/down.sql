@@ -0,0 +1,3 @@
+DROP TABLE payload_chunks;
+ALTER TABLE passwords DROP COLUMN payload_checksum;
+ALTER TABLE passwords DROP COLUMN chunk_count;
@@ -0,0 +1,12 @@
+-- Payloads too large for a single message are stored in chunks instead of `encrypted_payload`.
+ALTER TABLE passwords ADD COLUMN chunk_count INT NOT NULL DEFAULT 0 CHECK (chunk_count >= 0);
+ALTER TABLE passwords ADD COLUMN payload_checksum BYTEA;
+
+-- Parts of chunked payloads in order of `chunk_index`, following their record when it's renamed.
+CREATE TABLE payload_chunks (
+  resource_name VARCHAR(255) NOT NULL
+    REFERENCES passwords (resource_name) ON DELETE CASCADE ON UPDATE CASCADE,
+  chunk_index INT NOT NULL CHECK (chunk_index >= 0),
+  data BYTEA NOT NULL,
+  PRIMARY KEY (resource_name, chunk_index)
+);
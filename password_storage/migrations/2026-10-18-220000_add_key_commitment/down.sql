@@ -0,0 +1 @@
+ALTER TABLE passwords DROP COLUMN key_commitment
\ No newline at end of file
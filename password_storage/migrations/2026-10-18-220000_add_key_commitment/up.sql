@@ -0,0 +1,3 @@
+-- Commitment to the key used to encrypt the payload.
+-- `NULL` for records encrypted before it was stored, clients can't tell a wrong password from corrupted data for them.
+ALTER TABLE passwords ADD COLUMN key_commitment BYTEA;
@@ -0,0 +1,14 @@
+-- Small encrypted files attached to records.
+-- Attachments are authenticated with the resource name, so they block renaming of their record.
+CREATE TABLE attachments (
+  resource_name VARCHAR(255) NOT NULL REFERENCES passwords (resource_name) ON DELETE CASCADE,
+  filename VARCHAR(255) NOT NULL CHECK (filename <> ''),
+  encrypted_data BYTEA NOT NULL,
+  salt BYTEA NOT NULL,
+  kdf_iterations INT NOT NULL CHECK (kdf_iterations > 0),
+  kdf_salt BYTEA NOT NULL,
+  algorithm INT NOT NULL,
+  key_commitment BYTEA,
+  size BIGINT NOT NULL CHECK (size >= 0),
+  PRIMARY KEY (resource_name, filename)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE attachments DROP COLUMN output_version;
+ALTER TABLE passwords DROP COLUMN output_version;
\ No newline at end of file
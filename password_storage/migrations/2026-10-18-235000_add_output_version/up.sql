@@ -0,0 +1,4 @@
+-- Version of the encryption output format defining how clients derive the key from the password.
+-- Zero for records and attachments encrypted before it was stored, clients use the first version for them.
+ALTER TABLE passwords ADD COLUMN output_version INTEGER NOT NULL DEFAULT 0 CHECK (output_version >= 0);
+ALTER TABLE attachments ADD COLUMN output_version INTEGER NOT NULL DEFAULT 0 CHECK (output_version >= 0);
@@ -11,6 +11,9 @@
     clippy::as_conversions,
     clippy::derive_partial_eq_without_eq,
     clippy::allow_attributes,
+    clippy::doc_markdown,
+    clippy::must_use_candidate,
+    clippy::pattern_type_mismatch,
     reason = "generated code"
 )]
 #![allow(
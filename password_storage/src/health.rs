@@ -0,0 +1,160 @@
+//! Module with `gRPC` health reporting of the [`PasswordStorage`] service.
+//!
+//! Status follows the database availability, so that subscribers of the health `Watch` stream
+//! are notified as soon as the database goes down or comes back.
+
+use std::{sync::Arc, time::Duration};
+
+use tonic::server::NamedService;
+use tonic_health::{server::HealthReporter, ServingStatus};
+use tracing::info;
+
+use crate::{grpc::password_storage_server::PasswordStorageServer, service::PasswordStorage};
+
+/// Default interval between database probes of [`watch_database()`].
+pub const DATABASE_PROBE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Name of the [`PasswordStorage`] service in health checks.
+pub const SERVICE_NAME: &str = <PasswordStorageServer<PasswordStorage> as NamedService>::NAME;
+
+/// Source of the database availability.
+pub trait DatabaseProbe: Send + Sync + 'static {
+    /// Check if the database is available.
+    ///
+    /// Allowed to block the current thread.
+    fn is_database_available(&self) -> bool;
+}
+
+impl DatabaseProbe for PasswordStorage {
+    fn is_database_available(&self) -> bool {
+        self.probe_database()
+    }
+}
+
+/// Probe the database with `probe` every `interval` reporting [`SERVICE_NAME`] and the whole
+/// server as serving only while the database is available.
+///
+/// Status is reported on the first probe and then only when it changes.
+pub async fn watch_database<P: DatabaseProbe>(
+    probe: Arc<P>,
+    mut reporter: HealthReporter,
+    interval: Duration,
+) -> ! {
+    let mut interval = tokio::time::interval(interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    let mut reported_status = None;
+    loop {
+        interval.tick().await;
+
+        let blocking_probe = Arc::clone(&probe);
+        let status =
+            match tokio::task::spawn_blocking(move || blocking_probe.is_database_available()).await
+            {
+                Ok(true) => ServingStatus::Serving,
+                Ok(false) => ServingStatus::NotServing,
+                Err(error) => std::panic::resume_unwind(error.into_panic()),
+            };
+        if reported_status == Some(status) {
+            continue;
+        }
+
+        info!(?status, "Reporting health status");
+        // Empty name stands for the whole server
+        for service_name in ["", SERVICE_NAME] {
+            reporter.set_service_status(service_name, status).await;
+        }
+        reported_status = Some(status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use tokio_stream::StreamExt as _;
+    use tonic::{
+        transport::{server::TcpIncoming, Channel, Server},
+        Streaming,
+    };
+    use tonic_health::pb::{
+        health_check_response::ServingStatus as ResponseStatus, health_client::HealthClient,
+        HealthCheckRequest, HealthCheckResponse,
+    };
+
+    use super::*;
+
+    /// Maximum time to wait for a status change.
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// [`DatabaseProbe`] with availability controlled by the test.
+    struct FakeProbe(AtomicBool);
+
+    impl DatabaseProbe for FakeProbe {
+        fn is_database_available(&self) -> bool {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    /// Start health service watching `probe` in background and connect to it.
+    async fn start_health_service(probe: Arc<FakeProbe>) -> HealthClient<Channel> {
+        let (reporter, health_service) = tonic_health::server::health_reporter();
+        tokio::spawn(watch_database(probe, reporter, Duration::from_millis(10)));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(health_service)
+                .serve_with_incoming(incoming),
+        );
+
+        let channel = Channel::from_shared(url).unwrap().connect().await.unwrap();
+        HealthClient::new(channel)
+    }
+
+    /// Construct health request of the [`PasswordStorage`] service.
+    fn request() -> HealthCheckRequest {
+        HealthCheckRequest {
+            service: SERVICE_NAME.to_owned(),
+        }
+    }
+
+    /// Wait for the next status of the `statuses` stream.
+    async fn next_status(statuses: &mut Streaming<HealthCheckResponse>) -> ResponseStatus {
+        tokio::time::timeout(TIMEOUT, statuses.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn watch_emits_change_when_database_status_flips() {
+        let probe = Arc::new(FakeProbe(AtomicBool::new(true)));
+        let mut client = start_health_service(Arc::clone(&probe)).await;
+
+        // Service is registered by the first probe
+        tokio::time::timeout(TIMEOUT, async {
+            while client.check(request()).await.is_err() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let mut statuses = client.watch(request()).await.unwrap().into_inner();
+        drop(client);
+        assert_eq!(next_status(&mut statuses).await, ResponseStatus::Serving);
+
+        probe.0.store(false, Ordering::SeqCst);
+        assert_eq!(next_status(&mut statuses).await, ResponseStatus::NotServing);
+
+        probe.0.store(true, Ordering::SeqCst);
+        assert_eq!(next_status(&mut statuses).await, ResponseStatus::Serving);
+    }
+}
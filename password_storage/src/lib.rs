@@ -1,8 +1,11 @@
 //! Telepass Password Storage Service library to store and retrieve passwords.
 
 pub mod grpc;
+pub mod health;
 pub mod models;
 /// Module with database schema generated by `diesel`
 #[expect(clippy::single_char_lifetime_names, reason = "generated code")]
 pub mod schema;
 pub mod service;
+#[cfg(feature = "tls")]
+pub mod tls;
@@ -2,6 +2,8 @@
 
 #![cfg(feature = "executable")]
 
+use std::sync::Arc;
+
 use color_eyre::{
     eyre::{eyre, WrapErr as _},
     Result,
@@ -11,11 +13,12 @@ use dotenvy::dotenv;
 use telepass_password_storage::grpc;
 use telepass_password_storage::{
     grpc::password_storage_server::PasswordStorageServer,
+    health,
     service::{self},
 };
 use tonic::transport::Server;
 #[cfg(feature = "tls")]
-use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+use tonic::transport::ServerTlsConfig;
 #[cfg(feature = "reflection")]
 use tonic_reflection::server::{ServerReflection, ServerReflectionServer};
 use tracing::{info, Level};
@@ -34,16 +37,25 @@ async fn main() -> Result<()> {
 
     let database_url = read_env_var("DATABASE_URL")?;
     let cache_size = read_cache_size_env_var()?;
-    let password_storage =
-        PasswordStorageServer::new(service::PasswordStorage::new(&database_url, cache_size)?);
+    let password_storage = Arc::new(
+        service::PasswordStorage::connect(service::Config {
+            database_url,
+            cache_size,
+        })
+        .await
+        .wrap_err("Failed to start password storage service")?,
+    );
 
     #[expect(unused_mut, reason = "used in conditional compilation")]
     let mut server = Server::builder();
 
-    let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
-    health_reporter
-        .set_serving::<PasswordStorageServer<service::PasswordStorage>>()
-        .await;
+    let (health_reporter, health_service) = tonic_health::server::health_reporter();
+    tokio::spawn(health::watch_database(
+        Arc::clone(&password_storage),
+        health_reporter,
+        health::DATABASE_PROBE_INTERVAL,
+    ));
+    let password_storage = PasswordStorageServer::from_arc(password_storage);
 
     #[cfg(feature = "tls")]
     let mut server = {
@@ -135,16 +147,16 @@ fn prepare_tls_config() -> Result<ServerTlsConfig> {
     })?;
     let key = std::fs::read_to_string(&server_key_path)
         .wrap_err_with(|| format!("Failed to read server key at path: {server_key_path}",))?;
-    let server_identity = Identity::from_pem(cert, key);
 
     let client_ca_cert = std::fs::read_to_string(&client_ca_cert_path).wrap_err_with(|| {
         format!("Failed to read client certificate at path: {client_ca_cert_path}",)
     })?;
-    let client_ca_cert = Certificate::from_pem(client_ca_cert);
 
-    Ok(ServerTlsConfig::new()
-        .identity(server_identity)
-        .client_ca_root(client_ca_cert))
+    Ok(telepass_password_storage::tls::server_config(
+        cert,
+        key,
+        client_ca_cert,
+    ))
 }
 
 /// Read cache size from environment variable or use default value.
@@ -3,7 +3,9 @@
 use diesel::prelude::*;
 use thiserror::Error;
 
-use crate::schema::passwords;
+use crate::schema::{
+    attachments, blind_index, idempotency_keys, password_fingerprints, passwords, payload_chunks,
+};
 
 /// `passwords` database record.
 #[derive(Debug, Clone, PartialEq, Eq, Queryable, Insertable)]
@@ -15,21 +17,173 @@ pub struct Record {
     pub encrypted_payload: Vec<u8>,
     /// Salt applied to the payload.
     pub salt: Vec<u8>,
+    /// Number of key derivation iterations used to encrypt the payload.
+    ///
+    /// [`None`] if client relies on its default.
+    pub kdf_iterations: Option<i32>,
+    /// Salt of the key derivation used to encrypt the payload.
+    ///
+    /// [`None`] if client relies on its legacy constant salt.
+    pub kdf_salt: Option<Vec<u8>>,
+    /// Encryption algorithm as defined by [`crate::grpc::Algorithm`].
+    ///
+    /// Not validated, so algorithms unknown to the storage can be stored as well.
+    pub algorithm: i32,
+    /// Version of the record, changes on every mutation.
+    ///
+    /// Assigned by the database, so it's zero until the record is inserted.
+    #[diesel(skip_insertion)]
+    pub revision: i64,
+    /// Number of [`PayloadChunk`]s the payload is stored in.
+    ///
+    /// Zero if the payload is stored in [`encrypted_payload`](Self::encrypted_payload).
+    pub chunk_count: i32,
+    /// SHA-256 of the whole payload stored in chunks.
+    ///
+    /// [`None`] if the payload is not chunked.
+    pub payload_checksum: Option<Vec<u8>>,
+    /// Commitment to the key used to encrypt the payload.
+    ///
+    /// [`None`] if client didn't store it.
+    pub key_commitment: Option<Vec<u8>>,
+    /// Version of the encryption output format.
+    ///
+    /// Zero if client didn't store it.
+    pub output_version: i32,
+    /// Whether the payload is bound to [`resource_name`](Self::resource_name) as associated data
+    /// of the encryption.
+    pub bound_to_resource_name: bool,
 }
 
-/// Error indicating that `resource` field is missing
+/// `idempotency_keys` database record.
+///
+/// Creation time is set by the database.
+#[derive(Debug, Clone, PartialEq, Eq, Queryable, Insertable)]
+#[diesel(table_name = idempotency_keys)]
+pub struct IdempotencyKey {
+    /// Key provided by the client.
+    pub key: String,
+    /// Name of the resource added by the request with this key.
+    pub resource_name: String,
+}
+
+/// `blind_index` database record.
+#[derive(Debug, Clone, PartialEq, Eq, Queryable, Insertable)]
+#[diesel(table_name = blind_index)]
+pub struct BlindIndexEntry {
+    /// Name of the resource containing the keyword.
+    pub resource_name: String,
+    /// Keyword blinded by the client.
+    pub token: Vec<u8>,
+    /// Version of the key derivation the client used to blind the keyword.
+    pub version: i32,
+}
+
+/// `password_fingerprints` database record.
+#[derive(Debug, Clone, PartialEq, Eq, Queryable, Insertable)]
+#[diesel(table_name = password_fingerprints)]
+pub struct PasswordFingerprint {
+    /// Name of the resource with the password.
+    pub resource_name: String,
+    /// Password blinded by the client.
+    pub fingerprint: Vec<u8>,
+    /// Version of the key derivation the client used to blind the password.
+    pub version: i32,
+}
+
+/// `payload_chunks` database record.
+#[derive(Debug, Clone, PartialEq, Eq, Queryable, Insertable)]
+#[diesel(table_name = payload_chunks)]
+pub struct PayloadChunk {
+    /// Name of the resource with the payload.
+    pub resource_name: String,
+    /// Position of the chunk in the payload starting from zero.
+    pub chunk_index: i32,
+    /// Part of the payload.
+    pub data: Vec<u8>,
+}
+
+/// `attachments` database record.
+#[derive(Debug, Clone, PartialEq, Eq, Queryable, Insertable)]
+#[diesel(table_name = attachments)]
+pub struct Attachment {
+    /// Name of the resource the file is attached to.
+    pub resource_name: String,
+    /// Name of the file, unique within the resource.
+    pub filename: String,
+    /// File content encrypted with master password.
+    pub encrypted_data: Vec<u8>,
+    /// Salt applied to the content.
+    pub salt: Vec<u8>,
+    /// Number of key derivation iterations used to encrypt the content.
+    pub kdf_iterations: i32,
+    /// Salt of the key derivation used to encrypt the content.
+    pub kdf_salt: Vec<u8>,
+    /// Encryption algorithm as defined by [`crate::grpc::Algorithm`].
+    pub algorithm: i32,
+    /// Commitment to the key used to encrypt the content.
+    ///
+    /// [`None`] if client didn't store it.
+    pub key_commitment: Option<Vec<u8>>,
+    /// Size of the file before encryption in bytes.
+    pub size: i64,
+    /// Version of the encryption output format.
+    ///
+    /// Zero if client didn't store it.
+    pub output_version: i32,
+}
+
+/// Latest row of `__diesel_schema_migrations` table maintained by `diesel` migrations.
+#[derive(Debug, Clone, PartialEq, Eq, QueryableByName)]
+pub struct SchemaVersion {
+    /// Version of the migration, [`None`] if no migrations were applied.
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub version: Option<String>,
+}
+
+/// Error indicating that `gRPC` record can't be stored.
 #[derive(Debug, Copy, Clone, Error)]
-#[error("`resource` is missing")]
-pub struct ResourceIsMissingError;
+pub enum InvalidRecordError {
+    /// `resource` field is missing.
+    #[error("`resource` is missing")]
+    ResourceIsMissing,
+    /// `kdf_iterations` field doesn't fit into the database.
+    #[error("`kdf_iterations` is too large: {0}")]
+    KdfIterationsTooLarge(u32),
+    /// `output_version` field doesn't fit into the database.
+    #[error("`output_version` is too large: {0}")]
+    OutputVersionTooLarge(u32),
+}
 
 impl TryFrom<crate::grpc::Record> for Record {
-    type Error = ResourceIsMissingError;
+    type Error = InvalidRecordError;
 
     fn try_from(value: crate::grpc::Record) -> Result<Self, Self::Error> {
+        let kdf_iterations = match value.kdf_iterations {
+            0 => None,
+            kdf_iterations => Some(
+                i32::try_from(kdf_iterations)
+                    .map_err(|_err| InvalidRecordError::KdfIterationsTooLarge(kdf_iterations))?,
+            ),
+        };
+
         Ok(Self {
-            resource_name: value.resource.ok_or(ResourceIsMissingError)?.name,
+            resource_name: value
+                .resource
+                .ok_or(InvalidRecordError::ResourceIsMissing)?
+                .name,
             encrypted_payload: value.encrypted_payload,
             salt: value.salt,
+            kdf_iterations,
+            kdf_salt: (!value.kdf_salt.is_empty()).then_some(value.kdf_salt),
+            algorithm: value.algorithm,
+            revision: 0,
+            chunk_count: 0,
+            payload_checksum: None,
+            key_commitment: (!value.key_commitment.is_empty()).then_some(value.key_commitment),
+            output_version: i32::try_from(value.output_version)
+                .map_err(|_err| InvalidRecordError::OutputVersionTooLarge(value.output_version))?,
+            bound_to_resource_name: value.bound_to_resource_name,
         })
     }
 }
@@ -42,6 +196,105 @@ impl From<Record> for crate::grpc::Record {
             }),
             encrypted_payload: value.encrypted_payload,
             salt: value.salt,
+            // Database allows only positive values, so conversion never fails
+            kdf_iterations: value
+                .kdf_iterations
+                .and_then(|kdf_iterations| u32::try_from(kdf_iterations).ok())
+                .unwrap_or_default(),
+            kdf_salt: value.kdf_salt.unwrap_or_default(),
+            algorithm: value.algorithm,
+            // Revisions come from a sequence starting at one, so conversion never fails
+            revision: u64::try_from(value.revision).unwrap_or_default(),
+            // Database allows only non-negative values, so conversion never fails
+            chunk_count: u32::try_from(value.chunk_count).unwrap_or_default(),
+            key_commitment: value.key_commitment.unwrap_or_default(),
+            // Database allows only non-negative values, so conversion never fails
+            output_version: u32::try_from(value.output_version).unwrap_or_default(),
+            bound_to_resource_name: value.bound_to_resource_name,
+        }
+    }
+}
+
+/// Error indicating that `gRPC` attachment can't be stored.
+#[derive(Debug, Copy, Clone, Error)]
+pub enum InvalidAttachmentError {
+    /// `resource` field is missing.
+    #[error("`resource` is missing")]
+    ResourceIsMissing,
+    /// `attachment` field is missing.
+    #[error("`attachment` is missing")]
+    AttachmentIsMissing,
+    /// `kdf_iterations` field is zero.
+    #[error("`kdf_iterations` must be positive")]
+    ZeroKdfIterations,
+    /// `kdf_iterations` field doesn't fit into the database.
+    #[error("`kdf_iterations` is too large: {0}")]
+    KdfIterationsTooLarge(u32),
+    /// `kdf_salt` field is empty.
+    #[error("`kdf_salt` is missing")]
+    KdfSaltIsMissing,
+    /// `size` field doesn't fit into the database.
+    #[error("`size` is too large: {0}")]
+    SizeTooLarge(u64),
+    /// `output_version` field doesn't fit into the database.
+    #[error("`output_version` is too large: {0}")]
+    OutputVersionTooLarge(u32),
+}
+
+impl TryFrom<crate::grpc::AddAttachmentRequest> for Attachment {
+    type Error = InvalidAttachmentError;
+
+    fn try_from(value: crate::grpc::AddAttachmentRequest) -> Result<Self, Self::Error> {
+        let resource_name = value
+            .resource
+            .ok_or(InvalidAttachmentError::ResourceIsMissing)?
+            .name;
+        let attachment = value
+            .attachment
+            .ok_or(InvalidAttachmentError::AttachmentIsMissing)?;
+        let kdf_iterations = match attachment.kdf_iterations {
+            0 => return Err(InvalidAttachmentError::ZeroKdfIterations),
+            kdf_iterations => i32::try_from(kdf_iterations)
+                .map_err(|_err| InvalidAttachmentError::KdfIterationsTooLarge(kdf_iterations))?,
+        };
+        if attachment.kdf_salt.is_empty() {
+            return Err(InvalidAttachmentError::KdfSaltIsMissing);
+        }
+
+        Ok(Self {
+            resource_name,
+            filename: attachment.filename,
+            encrypted_data: attachment.encrypted_data,
+            salt: attachment.salt,
+            kdf_iterations,
+            kdf_salt: attachment.kdf_salt,
+            algorithm: attachment.algorithm,
+            key_commitment: (!attachment.key_commitment.is_empty())
+                .then_some(attachment.key_commitment),
+            size: i64::try_from(attachment.size)
+                .map_err(|_err| InvalidAttachmentError::SizeTooLarge(attachment.size))?,
+            output_version: i32::try_from(attachment.output_version).map_err(|_err| {
+                InvalidAttachmentError::OutputVersionTooLarge(attachment.output_version)
+            })?,
+        })
+    }
+}
+
+impl From<Attachment> for crate::grpc::Attachment {
+    fn from(value: Attachment) -> Self {
+        Self {
+            filename: value.filename,
+            encrypted_data: value.encrypted_data,
+            salt: value.salt,
+            // Database allows only positive values, so conversion never fails
+            kdf_iterations: u32::try_from(value.kdf_iterations).unwrap_or_default(),
+            kdf_salt: value.kdf_salt,
+            algorithm: value.algorithm,
+            key_commitment: value.key_commitment.unwrap_or_default(),
+            // Database allows only non-negative values, so conversion never fails
+            size: u64::try_from(value.size).unwrap_or_default(),
+            // Database allows only non-negative values, so conversion never fails
+            output_version: u32::try_from(value.output_version).unwrap_or_default(),
         }
     }
 }
@@ -1,9 +1,86 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    attachments (resource_name, filename) {
+        #[max_length = 255]
+        resource_name -> Varchar,
+        #[max_length = 255]
+        filename -> Varchar,
+        encrypted_data -> Bytea,
+        salt -> Bytea,
+        kdf_iterations -> Int4,
+        kdf_salt -> Bytea,
+        algorithm -> Int4,
+        key_commitment -> Nullable<Bytea>,
+        size -> Int8,
+        output_version -> Int4,
+    }
+}
+
+diesel::table! {
+    blind_index (resource_name, token) {
+        #[max_length = 255]
+        resource_name -> Varchar,
+        token -> Bytea,
+        version -> Int4,
+    }
+}
+
+diesel::table! {
+    idempotency_keys (key) {
+        #[max_length = 64]
+        key -> Varchar,
+        #[max_length = 255]
+        resource_name -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    password_fingerprints (resource_name) {
+        #[max_length = 255]
+        resource_name -> Varchar,
+        fingerprint -> Bytea,
+        version -> Int4,
+    }
+}
+
+diesel::table! {
+    payload_chunks (resource_name, chunk_index) {
+        #[max_length = 255]
+        resource_name -> Varchar,
+        chunk_index -> Int4,
+        data -> Bytea,
+    }
+}
+
 diesel::table! {
     passwords (resource_name) {
         resource_name -> Varchar,
         encrypted_payload -> Bytea,
         salt -> Bytea,
+        kdf_iterations -> Nullable<Int4>,
+        kdf_salt -> Nullable<Bytea>,
+        algorithm -> Int4,
+        revision -> Int8,
+        chunk_count -> Int4,
+        payload_checksum -> Nullable<Bytea>,
+        key_commitment -> Nullable<Bytea>,
+        output_version -> Int4,
+        bound_to_resource_name -> Bool,
     }
 }
+
+diesel::joinable!(attachments -> passwords (resource_name));
+diesel::joinable!(blind_index -> passwords (resource_name));
+diesel::joinable!(password_fingerprints -> passwords (resource_name));
+diesel::joinable!(payload_chunks -> passwords (resource_name));
+
+diesel::allow_tables_to_appear_in_same_query!(
+    attachments,
+    blind_index,
+    idempotency_keys,
+    password_fingerprints,
+    passwords,
+    payload_chunks,
+);
@@ -1,19 +1,33 @@
 //! Module with [`PasswordStorage Service`](PasswordStorage) implementation.
 
-use std::ops::DerefMut;
+use std::{collections::BTreeSet, fmt, ops::DerefMut, pin::Pin, time::Instant};
 
 use diesel::{
+    dsl::{now, IntervalDsl as _},
     prelude::*,
     r2d2::{ConnectionManager, Pool},
     PgConnection,
 };
+use sha2::{Digest as _, Sha256};
+use telepass_crypto::{BLIND_TOKEN_SIZE, PASSWORD_FINGERPRINT_SIZE};
 use thiserror::Error;
+use tokio_stream::Stream;
 use tonic::{Code, Request, Response, Status};
 use tracing::{info, instrument};
 
-use crate::{grpc, models, schema::passwords};
+use crate::{
+    grpc, models,
+    schema::{
+        attachments, blind_index, idempotency_keys, password_fingerprints, passwords,
+        payload_chunks,
+    },
+};
 
 mod cache;
+#[cfg(test)]
+mod proptests;
+#[cfg(any(test, feature = "test-db"))]
+pub mod test_db;
 
 /// Result type for [`PasswordStorage`] service.
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -39,15 +53,87 @@ pub enum Error {
 
     /// Invalid record.
     #[error("Invalid record: {0}")]
-    InvalidRecord(#[from] models::ResourceIsMissingError),
+    InvalidRecord(#[from] models::InvalidRecordError),
+
+    /// Invalid attachment.
+    #[error("Invalid attachment: {0}")]
+    InvalidAttachment(#[from] models::InvalidAttachmentError),
+
+    /// Attachment filename can't be stored in the database.
+    #[error("Invalid attachment filename: {0}")]
+    InvalidAttachmentFilename(&'static str),
+
+    /// Attachment content is larger than allowed.
+    #[error("Attachment is too large: {0} bytes")]
+    AttachmentTooLarge(u64),
+
+    /// Resource name can't be stored in the database.
+    #[error("Invalid resource name: {0}")]
+    InvalidResourceName(&'static str),
+
+    /// Idempotency key can't be used for the request.
+    #[error("Invalid idempotency key: {0}")]
+    InvalidIdempotencyKey(&'static str),
+
+    /// Blind index tokens are malformed.
+    #[error("Invalid blind index: {0}")]
+    InvalidBlindIndex(&'static str),
+
+    /// Password fingerprint is malformed.
+    #[error("Invalid password fingerprint: wrong size")]
+    InvalidPasswordFingerprint,
+
+    /// Salt can't be used by the encryption algorithm of the payload.
+    #[error("Invalid salt: expected {expected} bytes, got {actual}")]
+    InvalidSalt {
+        /// Salt size of the algorithm.
+        expected: usize,
+        /// Size of the passed salt.
+        actual: usize,
+    },
+
+    /// Encrypted payload is too short to contain the authentication tag.
+    #[error(
+        "Encrypted payload is truncated: {0} bytes, at least {} expected",
+        telepass_crypto::AEAD_TAG_SIZE
+    )]
+    TruncatedPayload(usize),
+
+    /// Chunks of the record are malformed or don't match the checksum.
+    #[error("Invalid chunks: {0}")]
+    InvalidChunks(&'static str),
+
+    /// Stored chunks of the payload don't match the stored checksum.
+    #[error("Payload of resource `{0}` is corrupted")]
+    CorruptedPayload(String),
+
+    /// Streamed request can't be received.
+    #[error("Failed to receive request: {0}")]
+    Stream(Status),
 
     /// Record already exists.
     #[error("Password for resource `{0}` already exists")]
     AlreadyExists(String),
 
+    /// Record has another revision than the expected one.
+    #[error("Password for resource `{0}` was changed concurrently")]
+    RevisionMismatch(String),
+
     /// Resource not found.
     #[error("Resource `{0}` not found")]
     NotFound(String),
+
+    /// Record can't be renamed without breaking authentication of its ciphertexts.
+    #[error("Resource `{0}` can't be renamed: {1}")]
+    NotRenamable(String, &'static str),
+
+    /// Attachment already exists.
+    #[error("Attachment `{0}` already exists")]
+    AttachmentAlreadyExists(String),
+
+    /// Attachment not found.
+    #[error("Attachment `{0}` not found")]
+    AttachmentNotFound(String),
 }
 
 /// Helper error type to wrap foreign errors with context.
@@ -96,10 +182,412 @@ impl From<Error> for Status {
             Error::FailedToCreateConnectionPool(_)
             | Error::FailedToGetConnectionFromThePool(_)
             | Error::Database(_) => Self::internal("Internal error, please try again later"),
-            Error::InvalidRecord(_) => Self::invalid_argument(error.to_string()),
-            Error::AlreadyExists(_) => Self::already_exists(error.to_string()),
-            Error::NotFound(_) => Self::not_found(error.to_string()),
+            Error::InvalidRecord(_)
+            | Error::InvalidAttachment(_)
+            | Error::InvalidAttachmentFilename(_)
+            | Error::AttachmentTooLarge(_)
+            | Error::InvalidResourceName(_)
+            | Error::InvalidIdempotencyKey(_)
+            | Error::InvalidBlindIndex(_)
+            | Error::InvalidPasswordFingerprint
+            | Error::InvalidSalt { .. }
+            | Error::TruncatedPayload(_)
+            | Error::InvalidChunks(_) => Self::invalid_argument(error.to_string()),
+            Error::AlreadyExists(_) | Error::AttachmentAlreadyExists(_) => {
+                Self::already_exists(error.to_string())
+            }
+            Error::RevisionMismatch(_) => Self::aborted(error.to_string()),
+            Error::NotRenamable(..) => Self::failed_precondition(error.to_string()),
+            Error::NotFound(_) | Error::AttachmentNotFound(_) => Self::not_found(error.to_string()),
+            Error::CorruptedPayload(_) => Self::data_loss(error.to_string()),
+            Error::Stream(status) => status,
+        }
+    }
+}
+
+/// Maximum time to wait for a database connection in
+/// [`probe_database()`](PasswordStorage::probe_database).
+pub const DATABASE_PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Maximum supported [`Config::cache_size`].
+///
+/// Records cache is allocated at startup, so bigger sizes can exhaust memory right away.
+pub const MAX_CACHE_SIZE: u32 = 1 << 20;
+
+/// Configuration of [`PasswordStorage`] service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// `PostgreSQL` database url.
+    pub database_url: String,
+    /// Number of cached records, zero disables records caching.
+    pub cache_size: u32,
+}
+
+/// Error starting [`PasswordStorage`] service, tells which startup phase failed.
+#[derive(Debug, Error)]
+pub enum StartupError {
+    /// Database url is not a `PostgreSQL` url.
+    #[error("Database url must start with `postgres://` or `postgresql://`")]
+    BadUrl,
+
+    /// Database can't be connected to.
+    #[error("Database is unreachable: {source}")]
+    Unreachable {
+        /// Connection error.
+        source: diesel::ConnectionError,
+    },
+
+    /// Records can't be loaded, most likely because migrations were not applied.
+    #[error("Failed to load records, check that database migrations were run: {source}")]
+    NotMigrated {
+        /// Database error.
+        source: diesel::result::Error,
+    },
+
+    /// Cache configuration is invalid.
+    #[error("Cache size {0} exceeds the maximum of {MAX_CACHE_SIZE}")]
+    InvalidCacheConfig(u32),
+}
+
+/// Custom SQL functions.
+mod sql {
+    #![expect(
+        clippy::field_scoped_visibility_modifiers,
+        clippy::redundant_pub_crate,
+        reason = "generated by `diesel`"
+    )]
+
+    diesel::define_sql_function! {
+        /// SQL `lower()` function.
+        fn lower(x: diesel::sql_types::Text) -> diesel::sql_types::Text;
+    }
+}
+
+/// Character used to escape special symbols in `LIKE` patterns.
+const LIKE_ESCAPE_CHARACTER: char = '\\';
+
+/// Escape `LIKE` special symbols (`%` and `_`) in `query` so that they are matched literally.
+fn escape_like_pattern(query: &str) -> String {
+    let mut escaped = String::with_capacity(query.len());
+    for c in query.chars() {
+        if matches!(c, '%' | '_' | LIKE_ESCAPE_CHARACTER) {
+            escaped.push(LIKE_ESCAPE_CHARACTER);
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Maximum length of the resource name in characters, limited by the `passwords` table.
+const MAX_RESOURCE_NAME_LENGTH: usize = 255;
+
+/// Check that `resource_name` can be stored in the database.
+///
+/// Rejecting such names early prevents them from turning into internal database errors.
+fn validate_resource_name(resource_name: &str) -> Result<()> {
+    if resource_name.contains('\0') {
+        return Err(Error::InvalidResourceName("contains NUL character"));
+    }
+    if resource_name.chars().count() > MAX_RESOURCE_NAME_LENGTH {
+        return Err(Error::InvalidResourceName("too long"));
+    }
+    Ok(())
+}
+
+/// Maximum length of the attachment filename in characters, limited by the `attachments` table.
+const MAX_ATTACHMENT_FILENAME_LENGTH: usize = 255;
+
+/// Maximum size of the attached file before encryption in bytes.
+const MAX_ATTACHMENT_SIZE: u64 = 64 * 1024;
+
+/// Maximum size of the encrypted attachment content in bytes.
+///
+/// Encryption adds an authentication tag to the file.
+const MAX_ENCRYPTED_ATTACHMENT_SIZE: usize = 64 * 1024 + telepass_crypto::AEAD_TAG_SIZE;
+
+/// Check that `attachment` can be stored in the database and fits the size limits.
+fn validate_attachment(attachment: &models::Attachment) -> Result<()> {
+    if attachment.filename.is_empty() {
+        return Err(Error::InvalidAttachmentFilename("empty"));
+    }
+    if attachment.filename.contains('\0') {
+        return Err(Error::InvalidAttachmentFilename("contains NUL character"));
+    }
+    if attachment.filename.chars().count() > MAX_ATTACHMENT_FILENAME_LENGTH {
+        return Err(Error::InvalidAttachmentFilename("too long"));
+    }
+    let size = u64::try_from(attachment.size).unwrap_or(u64::MAX);
+    if size > MAX_ATTACHMENT_SIZE || attachment.encrypted_data.len() > MAX_ENCRYPTED_ATTACHMENT_SIZE
+    {
+        return Err(Error::AttachmentTooLarge(size));
+    }
+    validate_ciphertext(
+        attachment.algorithm,
+        &attachment.salt,
+        attachment.encrypted_data.len(),
+    )
+}
+
+/// Check that `salt` and encrypted payload of `payload_size` bytes could be produced
+/// by `algorithm`.
+///
+/// Salt size is checked only for algorithms known to the storage, others are stored as is.
+fn validate_ciphertext(algorithm: i32, salt: &[u8], payload_size: usize) -> Result<()> {
+    let salt_size = match grpc::Algorithm::try_from(algorithm) {
+        Ok(grpc::Algorithm::Aes256Gcm) => Some(telepass_crypto::Algorithm::Aes256Gcm.salt_size()),
+        Ok(grpc::Algorithm::Xchacha20Poly1305) => {
+            Some(telepass_crypto::Algorithm::XChaCha20Poly1305.salt_size())
+        }
+        Err(_unknown) => None,
+    };
+    if let Some(expected) = salt_size {
+        if salt.len() != expected {
+            return Err(Error::InvalidSalt {
+                expected,
+                actual: salt.len(),
+            });
+        }
+    }
+    if payload_size < telepass_crypto::AEAD_TAG_SIZE {
+        return Err(Error::TruncatedPayload(payload_size));
+    }
+    Ok(())
+}
+
+/// Maximum length of the idempotency key in characters, limited by the `idempotency_keys` table.
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 64;
+
+/// Time in hours during which repeated `add` requests with the same idempotency key return
+/// the outcome of the first one.
+const IDEMPOTENCY_KEY_TTL_HOURS: i32 = 24;
+
+/// Split `request` into the record to add, idempotency key, blind index, password
+/// fingerprint and version of the key derivation used to blind them.
+fn split_add_request(
+    request: grpc::AddRequest,
+) -> (grpc::Record, String, Vec<Vec<u8>>, Vec<u8>, u32) {
+    let grpc::AddRequest {
+        resource,
+        encrypted_payload,
+        salt,
+        idempotency_key,
+        blind_index,
+        kdf_iterations,
+        kdf_salt,
+        algorithm,
+        password_fingerprint,
+        key_commitment,
+        output_version,
+        bound_to_resource_name,
+        blind_index_version,
+    } = request;
+    let record = grpc::Record {
+        resource,
+        encrypted_payload,
+        salt,
+        kdf_iterations,
+        kdf_salt,
+        algorithm,
+        revision: 0,
+        chunk_count: 0,
+        key_commitment,
+        output_version,
+        bound_to_resource_name,
+    };
+    (
+        record,
+        idempotency_key,
+        blind_index,
+        password_fingerprint,
+        blind_index_version,
+    )
+}
+
+/// Check that `idempotency_key` can be stored in the database.
+fn validate_idempotency_key(idempotency_key: &str) -> Result<()> {
+    if idempotency_key.contains('\0') {
+        return Err(Error::InvalidIdempotencyKey("contains NUL character"));
+    }
+    if idempotency_key.chars().count() > MAX_IDEMPOTENCY_KEY_LENGTH {
+        return Err(Error::InvalidIdempotencyKey("too long"));
+    }
+    Ok(())
+}
+
+/// Maximum number of blind index tokens per request.
+const MAX_BLIND_TOKENS: usize = 32;
+
+/// Check that blind index `tokens` are well-formed and deduplicate them.
+fn validate_blind_tokens(tokens: Vec<Vec<u8>>) -> Result<BTreeSet<Vec<u8>>> {
+    if tokens.len() > MAX_BLIND_TOKENS {
+        return Err(Error::InvalidBlindIndex("too many tokens"));
+    }
+    if tokens.iter().any(|token| token.len() != BLIND_TOKEN_SIZE) {
+        return Err(Error::InvalidBlindIndex("wrong token size"));
+    }
+    Ok(tokens.into_iter().collect())
+}
+
+/// Check that `password_fingerprint` is well-formed.
+///
+/// Returns [`None`] if it's empty, meaning the client didn't compute it.
+fn validate_password_fingerprint(password_fingerprint: Vec<u8>) -> Result<Option<Vec<u8>>> {
+    match password_fingerprint.len() {
+        0 => Ok(None),
+        PASSWORD_FINGERPRINT_SIZE => Ok(Some(password_fingerprint)),
+        _ => Err(Error::InvalidPasswordFingerprint),
+    }
+}
+
+/// Maximum size of the payload part in a single [`grpc::RecordChunk`] in bytes.
+const MAX_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Maximum number of chunks per record.
+const MAX_CHUNKS: usize = 1024;
+
+/// Maximum size of the payload added in chunks in bytes.
+const MAX_CHUNKED_PAYLOAD_SIZE: usize = 4 * 1024 * 1024;
+
+/// Size of the payload checksum in bytes, matches SHA-256 output.
+const PAYLOAD_CHECKSUM_SIZE: usize = 32;
+
+/// Compute checksum of the payload consisting of `chunks`.
+fn payload_checksum(chunks: &[Vec<u8>]) -> Vec<u8> {
+    chunks
+        .iter()
+        .fold(Sha256::new(), sha2::Digest::chain_update)
+        .finalize()
+        .to_vec()
+}
+
+/// Payload split into chunks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PayloadChunks {
+    /// Parts of the payload in order.
+    chunks: Vec<Vec<u8>>,
+    /// Checksum of the whole payload, see [`payload_checksum()`].
+    checksum: Vec<u8>,
+}
+
+impl PayloadChunks {
+    /// Get total size of the payload in bytes.
+    fn size(&self) -> usize {
+        self.chunks.iter().map(Vec::len).sum()
+    }
+
+    /// Convert chunks into rows of `record`, setting its chunk count and checksum.
+    fn into_rows(self, record: &mut models::Record) -> Result<Vec<models::PayloadChunk>> {
+        record.chunk_count = i32::try_from(self.chunks.len())
+            .map_err(|_err| Error::InvalidChunks("too many chunks"))?;
+        record.payload_checksum = Some(self.checksum);
+        Ok(self
+            .chunks
+            .into_iter()
+            .zip(0_i32..)
+            .map(|(data, chunk_index)| models::PayloadChunk {
+                resource_name: record.resource_name.clone(),
+                chunk_index,
+                data,
+            })
+            .collect())
+    }
+}
+
+/// Record being received in chunks by `add_chunked` request.
+#[derive(Debug, Default)]
+struct ChunkedRecord {
+    /// Request from the first chunk.
+    request: Option<grpc::AddRequest>,
+    /// Expected checksum from the first chunk.
+    checksum: Vec<u8>,
+    /// Payload parts received so far.
+    chunks: Vec<Vec<u8>>,
+    /// Total size of [`chunks`](Self::chunks) in bytes.
+    size: usize,
+}
+
+impl ChunkedRecord {
+    /// Append the next `chunk` checking that it's well-formed.
+    fn push(&mut self, chunk: grpc::RecordChunk) -> Result<()> {
+        let grpc::RecordChunk {
+            add_request,
+            record,
+            checksum,
+            data,
+        } = chunk;
+        if record.is_some() {
+            return Err(Error::InvalidChunks("`record` is not expected"));
+        }
+
+        match (self.request.is_some(), add_request) {
+            (false, Some(add_request)) => {
+                if !add_request.encrypted_payload.is_empty() {
+                    return Err(Error::InvalidChunks("payload must be passed in `data`"));
+                }
+                if checksum.len() != PAYLOAD_CHECKSUM_SIZE {
+                    return Err(Error::InvalidChunks("wrong checksum size"));
+                }
+                self.request = Some(add_request);
+                self.checksum = checksum;
+            }
+            (false, None) => {
+                return Err(Error::InvalidChunks(
+                    "first chunk must contain `add_request`",
+                ))
+            }
+            (true, Some(_)) => {
+                return Err(Error::InvalidChunks(
+                    "only the first chunk must contain `add_request`",
+                ))
+            }
+            (true, None) if !checksum.is_empty() => {
+                return Err(Error::InvalidChunks(
+                    "only the first chunk must contain `checksum`",
+                ))
+            }
+            (true, None) => {}
+        }
+
+        if data.is_empty() || data.len() > MAX_CHUNK_SIZE {
+            return Err(Error::InvalidChunks("wrong chunk size"));
+        }
+        if self.chunks.len() >= MAX_CHUNKS {
+            return Err(Error::InvalidChunks("too many chunks"));
+        }
+        self.size = self.size.saturating_add(data.len());
+        if self.size > MAX_CHUNKED_PAYLOAD_SIZE {
+            return Err(Error::InvalidChunks("payload is too large"));
+        }
+        self.chunks.push(data);
+        Ok(())
+    }
+
+    /// Finish receiving checking that the payload matches the checksum.
+    fn finish(self) -> Result<(grpc::AddRequest, PayloadChunks)> {
+        let request = self.request.ok_or(Error::InvalidChunks("no chunks"))?;
+        if payload_checksum(&self.chunks) != self.checksum {
+            return Err(Error::InvalidChunks("checksum mismatch"));
         }
+        Ok((
+            request,
+            PayloadChunks {
+                chunks: self.chunks,
+                checksum: self.checksum,
+            },
+        ))
+    }
+}
+
+/// Chunks of a record to be streamed.
+///
+/// Logged by number only to keep large payloads out of logs.
+struct RecordChunks(Vec<grpc::RecordChunk>);
+
+impl fmt::Debug for RecordChunks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordChunks")
+            .field("count", &self.0.len())
+            .finish()
     }
 }
 
@@ -112,27 +600,80 @@ pub struct PasswordStorage {
     pool: Pool<ConnectionManager<PgConnection>>,
     /// Cache for common requests.
     cache: cache::Cache,
+    /// Moment the service was started.
+    started_at: Instant,
 }
 
 impl PasswordStorage {
-    /// Create new instance of [`PasswordStorage`] service.
+    /// Connect to the database and load the cache.
+    ///
+    /// Blocking database work is done on a dedicated thread to not to block the async runtime.
     ///
     /// # Errors
     ///
-    /// Fails if failed to create database connection pool.
-    pub fn new(database_url: &str, cache_size: u32) -> Result<Self> {
-        info!("Creating database connection pool...");
-        let manager = ConnectionManager::<PgConnection>::new(database_url);
-        let pool = Pool::builder().build(manager)?;
-
-        let cached_records = passwords::table
-            .limit(cache_size.into())
-            .load::<models::Record>(&mut pool.get()?)
-            .map_err(Error::Database)?;
+    /// See [`StartupError`].
+    pub async fn connect(config: Config) -> Result<Self, StartupError> {
+        /// Schemes of `PostgreSQL` urls
+        const SCHEMES: [&str; 2] = ["postgres://", "postgresql://"];
+
+        let Config {
+            database_url,
+            cache_size,
+        } = config;
+        if cache_size > MAX_CACHE_SIZE {
+            return Err(StartupError::InvalidCacheConfig(cache_size));
+        }
+        if !SCHEMES
+            .iter()
+            .any(|scheme| database_url.starts_with(scheme))
+        {
+            return Err(StartupError::BadUrl);
+        }
+
+        tokio::task::spawn_blocking(move || Self::connect_blocking(&database_url, cache_size))
+            .await
+            .unwrap_or_else(|error| std::panic::resume_unwind(error.into_panic()))
+    }
 
-        let cache = cache::Cache::load(cache_size, cached_records);
+    /// Blocking part of [`connect()`](Self::connect).
+    fn connect_blocking(database_url: &str, cache_size: u32) -> Result<Self, StartupError> {
+        info!("Connecting to the database...");
+        let connecting_start = Instant::now();
+        let mut connection = PgConnection::establish(database_url)
+            .map_err(|source| StartupError::Unreachable { source })?;
+        info!(elapsed = ?connecting_start.elapsed(), "Connected to the database");
 
-        Ok(Self { pool, cache })
+        info!("Loading records...");
+        let loading_start = Instant::now();
+        let resources = passwords::table
+            .select(passwords::resource_name)
+            .load::<String>(&mut connection)
+            .map_err(|source| StartupError::NotMigrated { source })?;
+        let cached_records = if cache_size == 0 {
+            info!("Records cache is disabled");
+            Vec::new()
+        } else {
+            passwords::table
+                .limit(cache_size.into())
+                .load::<models::Record>(&mut connection)
+                .map_err(|source| StartupError::NotMigrated { source })?
+        };
+        info!(
+            elapsed = ?loading_start.elapsed(),
+            resources = resources.len(),
+            cached_records = cached_records.len(),
+            "Loaded records"
+        );
+
+        let cache = cache::Cache::load(cache_size, resources, cached_records);
+        // Database is known to be reachable, so the pool can fill itself in background
+        let pool = Pool::builder().build_unchecked(ConnectionManager::new(database_url));
+
+        Ok(Self {
+            pool,
+            cache,
+            started_at: Instant::now(),
+        })
     }
 
     /// Get database connection from the pool.
@@ -140,6 +681,333 @@ impl PasswordStorage {
         self.pool.get().map_err(Into::into)
     }
 
+    /// Check if the database responds to a trivial query within [`DATABASE_PROBE_TIMEOUT`].
+    ///
+    /// Blocks the current thread.
+    pub fn probe_database(&self) -> bool {
+        self.pool
+            .get_timeout(DATABASE_PROBE_TIMEOUT)
+            .map_err(Error::from)
+            .and_then(|mut connection| {
+                diesel::sql_query("SELECT 1")
+                    .execute(&mut *connection)
+                    .map_err(Error::Database)
+            })
+            .inspect_err(|error| tracing::warn!(%error, "Database is unavailable"))
+            .is_ok()
+    }
+
+    /// Get version of the latest applied database migration.
+    ///
+    /// Empty if no migrations were applied.
+    fn schema_version(connection: &mut PgConnection) -> Result<String> {
+        diesel::sql_query("SELECT MAX(version) AS version FROM __diesel_schema_migrations")
+            .get_result::<models::SchemaVersion>(connection)
+            .map(|schema_version| schema_version.version.unwrap_or_default())
+            .map_err(Error::Database)
+    }
+
+    /// Remove expired idempotency keys and check if `idempotency_key` was already used.
+    ///
+    /// Returns `true` if a request with the same key has already added the same resource.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the key was used to add another resource.
+    fn is_replayed(
+        connection: &mut PgConnection,
+        idempotency_key: &models::IdempotencyKey,
+    ) -> Result<bool> {
+        #[expect(clippy::arithmetic_side_effects, reason = "SQL expression")]
+        let expiration_time = now - IDEMPOTENCY_KEY_TTL_HOURS.hours();
+        diesel::delete(
+            idempotency_keys::table.filter(idempotency_keys::created_at.lt(expiration_time)),
+        )
+        .execute(connection)
+        .map_err(Error::Database)?;
+
+        let stored_resource_name = idempotency_keys::table
+            .find(&idempotency_key.key)
+            .select(idempotency_keys::resource_name)
+            .first::<String>(connection)
+            .optional()
+            .map_err(Error::Database)?;
+
+        match stored_resource_name {
+            None => Ok(false),
+            Some(resource_name) if resource_name == idempotency_key.resource_name => Ok(true),
+            Some(_) => Err(Error::InvalidIdempotencyKey(
+                "already used for another resource",
+            )),
+        }
+    }
+
+    /// Respond to a replayed `add` request as the original one was responded.
+    fn replayed(
+        connection: &mut PgConnection,
+        password_fingerprint: Option<&models::PasswordFingerprint>,
+    ) -> Result<Response<grpc::AddResponse>> {
+        Ok(Response::new(grpc::AddResponse {
+            reused_by: Self::find_reused_by(connection, password_fingerprint)?,
+        }))
+    }
+
+    /// Find name of the stored resource matching `resource_name` regardless of case.
+    fn find_existing_resource(
+        connection: &mut PgConnection,
+        resource_name: &str,
+    ) -> Result<Option<String>> {
+        passwords::table
+            .filter(sql::lower(passwords::resource_name).eq(sql::lower(resource_name)))
+            .select(passwords::resource_name)
+            .first::<String>(connection)
+            .optional()
+            .map_err(Error::Database)
+    }
+
+    /// Check if violation of a unique index on adding `resource_name` was caused by a concurrent
+    /// request with the same `idempotency_key` having already added the record.
+    ///
+    /// Retry can pass [`is_replayed()`](Self::is_replayed) while the original request is still
+    /// running and then hit the unique index once it's committed.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::AlreadyExists`] with the name of the existing record if it's not a
+    /// replay, see [`is_replayed()`](Self::is_replayed) for other errors.
+    fn check_concurrent_replay(
+        connection: &mut PgConnection,
+        idempotency_key: Option<&models::IdempotencyKey>,
+        resource_name: &str,
+    ) -> Result<()> {
+        if let Some(idempotency_key) = idempotency_key {
+            if Self::is_replayed(connection, idempotency_key)? {
+                return Ok(());
+            }
+        }
+
+        let existing_resource_name = Self::find_existing_resource(connection, resource_name)?
+            .unwrap_or_else(|| resource_name.to_owned());
+        Err(Error::AlreadyExists(existing_resource_name))
+    }
+
+    /// Find resources other than the owner of `password_fingerprint` with the same fingerprint.
+    ///
+    /// Nothing is reused if there is no `password_fingerprint`.
+    fn find_reused_by(
+        connection: &mut PgConnection,
+        password_fingerprint: Option<&models::PasswordFingerprint>,
+    ) -> Result<Vec<String>> {
+        let Some(password_fingerprint) = password_fingerprint else {
+            return Ok(Vec::new());
+        };
+
+        password_fingerprints::table
+            .filter(password_fingerprints::fingerprint.eq(&password_fingerprint.fingerprint))
+            .filter(password_fingerprints::resource_name.ne(&password_fingerprint.resource_name))
+            .select(password_fingerprints::resource_name)
+            .order(password_fingerprints::resource_name)
+            .load::<String>(connection)
+            .map_err(Error::Database)
+    }
+
+    /// Add record with `request`, storing its payload in `chunks` if they are passed.
+    fn add_record(
+        &self,
+        request: grpc::AddRequest,
+        chunks: Option<PayloadChunks>,
+    ) -> Result<Response<grpc::AddResponse>> {
+        let (record, idempotency_key, blind_index, password_fingerprint, blind_index_version) =
+            split_add_request(request);
+        let mut record = models::Record::try_from(record)?;
+        validate_resource_name(&record.resource_name)?;
+        validate_ciphertext(
+            record.algorithm,
+            &record.salt,
+            chunks
+                .as_ref()
+                .map_or(record.encrypted_payload.len(), PayloadChunks::size),
+        )?;
+        validate_idempotency_key(&idempotency_key)?;
+        let blind_index_version = i32::try_from(blind_index_version)
+            .map_err(|_err| Error::InvalidBlindIndex("version is too large"))?;
+        let blind_index = validate_blind_tokens(blind_index)?
+            .into_iter()
+            .map(|token| models::BlindIndexEntry {
+                resource_name: record.resource_name.clone(),
+                token,
+                version: blind_index_version,
+            })
+            .collect::<Vec<_>>();
+        let password_fingerprint =
+            validate_password_fingerprint(password_fingerprint)?.map(|fingerprint| {
+                models::PasswordFingerprint {
+                    resource_name: record.resource_name.clone(),
+                    fingerprint,
+                    version: blind_index_version,
+                }
+            });
+        let payload_chunks = chunks
+            .map(|payload| payload.into_rows(&mut record))
+            .transpose()?
+            .unwrap_or_default();
+
+        let mut connection = self.connection()?;
+
+        let idempotency_key = (!idempotency_key.is_empty()).then(|| models::IdempotencyKey {
+            key: idempotency_key,
+            resource_name: record.resource_name.clone(),
+        });
+        if let Some(idempotency_key) = idempotency_key.as_ref() {
+            if Self::is_replayed(&mut connection, idempotency_key)? {
+                info!("Replayed request, record is already added");
+                return Self::replayed(&mut connection, password_fingerprint.as_ref());
+            }
+        }
+
+        // Fast path, the unique index is the authority for records added by other instances
+        if let Some(existing_resource_name) = self.cache.find_resource(&record.resource_name) {
+            return Err(Error::AlreadyExists(existing_resource_name));
+        }
+
+        let inserted = connection.transaction(|transaction| {
+            let revision = diesel::insert_into(passwords::table)
+                .values(&record)
+                .returning(passwords::revision)
+                .get_result::<i64>(transaction)?;
+            if !payload_chunks.is_empty() {
+                diesel::insert_into(payload_chunks::table)
+                    .values(&payload_chunks)
+                    .execute(transaction)?;
+            }
+            if !blind_index.is_empty() {
+                diesel::insert_into(blind_index::table)
+                    .values(&blind_index)
+                    .execute(transaction)?;
+            }
+            if let Some(password_fingerprint) = password_fingerprint.as_ref() {
+                diesel::insert_into(password_fingerprints::table)
+                    .values(password_fingerprint)
+                    .execute(transaction)?;
+            }
+            if let Some(idempotency_key) = idempotency_key.as_ref() {
+                diesel::insert_into(idempotency_keys::table)
+                    .values(idempotency_key)
+                    .execute(transaction)?;
+            }
+            diesel::result::QueryResult::Ok(revision)
+        });
+        if matches!(
+            inserted,
+            Err(diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::UniqueViolation,
+                _
+            ))
+        ) {
+            Self::check_concurrent_replay(
+                &mut connection,
+                idempotency_key.as_ref(),
+                &record.resource_name,
+            )?;
+            info!("Concurrently replayed request, record is already added");
+            return Self::replayed(&mut connection, password_fingerprint.as_ref());
+        }
+        record.revision = inserted.map_err(|err| err.with_context(record.resource_name.clone()))?;
+        self.cache.add(record);
+
+        let reused_by = Self::find_reused_by(&mut connection, password_fingerprint.as_ref())?;
+        if !reused_by.is_empty() {
+            info!(reused_by = reused_by.len(), "Password is reused");
+        }
+        Ok(Response::new(grpc::AddResponse { reused_by }))
+    }
+
+    /// Find record of `resource_name`, case-insensitively if there is no exact match.
+    ///
+    /// Cache is neither used nor populated if `bypass_cache` is set.
+    fn find_record(&self, resource_name: &str, bypass_cache: bool) -> Result<models::Record> {
+        let fetch = || -> Result<models::Record> {
+            let mut connection = self.connection()?;
+
+            let exact_match = passwords::table
+                .filter(passwords::resource_name.eq(resource_name))
+                .first::<models::Record>(&mut *connection)
+                .optional()
+                .map_err(|err| err.with_context(resource_name.to_owned()))?;
+            if let Some(record) = exact_match {
+                return Ok(record);
+            }
+
+            passwords::table
+                .filter(sql::lower(passwords::resource_name).eq(sql::lower(resource_name)))
+                .first::<models::Record>(&mut *connection)
+                .map_err(|err| err.with_context(resource_name.to_owned()))
+                .map_err(Into::into)
+        };
+
+        if bypass_cache {
+            info!("Bypassing cache");
+            fetch()
+        } else {
+            self.cache.get_or_try_insert_with(resource_name, fetch)
+        }
+    }
+
+    /// Split payload of `record` into chunks to be streamed.
+    ///
+    /// Payloads stored in chunks are loaded from the database and checked against the stored
+    /// checksum, others are split into chunks of [`MAX_CHUNK_SIZE`].
+    fn load_chunks(&self, mut record: models::Record) -> Result<RecordChunks> {
+        let (chunks, checksum) = if record.chunk_count == 0_i32 {
+            let payload = std::mem::take(&mut record.encrypted_payload);
+            let chunks = if payload.is_empty() {
+                vec![payload]
+            } else {
+                payload.chunks(MAX_CHUNK_SIZE).map(<[u8]>::to_vec).collect()
+            };
+            let checksum = payload_checksum(&chunks);
+            (chunks, checksum)
+        } else {
+            let chunks = payload_chunks::table
+                .filter(payload_chunks::resource_name.eq(&record.resource_name))
+                .order(payload_chunks::chunk_index)
+                .select(payload_chunks::data)
+                .load::<Vec<u8>>(&mut *self.connection()?)
+                .map_err(Error::Database)?;
+            let checksum = record.payload_checksum.take().unwrap_or_default();
+            if i32::try_from(chunks.len()).ok() != Some(record.chunk_count)
+                || payload_checksum(&chunks) != checksum
+            {
+                return Err(Error::CorruptedPayload(record.resource_name));
+            }
+            (chunks, checksum)
+        };
+
+        let mut chunks = chunks
+            .into_iter()
+            .map(|data| grpc::RecordChunk {
+                data,
+                ..grpc::RecordChunk::default()
+            })
+            .collect::<Vec<_>>();
+        if let Some(first) = chunks.first_mut() {
+            first.record = Some(grpc::Record::from(record));
+            first.checksum = checksum;
+        }
+        Ok(RecordChunks(chunks))
+    }
+
+    /// Receive all chunks of `add_chunked` request from `stream`.
+    async fn receive_chunks(
+        mut stream: tonic::Streaming<grpc::RecordChunk>,
+    ) -> Result<ChunkedRecord> {
+        let mut record = ChunkedRecord::default();
+        while let Some(chunk) = stream.message().await.map_err(Error::Stream)? {
+            record.push(chunk)?;
+        }
+        Ok(record)
+    }
+
     /// Call `f`, log the result and unpack [`Status`] if [`Err`].
     fn log_and_transform<T: std::fmt::Debug>(f: impl FnOnce() -> Result<T>) -> Result<T, Status> {
         match f() {
@@ -167,41 +1035,53 @@ impl PasswordStorage {
 
 #[tonic::async_trait]
 impl grpc::password_storage_server::PasswordStorage for PasswordStorage {
+    type GetChunkedStream =
+        Pin<Box<dyn Stream<Item = Result<grpc::RecordChunk, Status>> + Send + 'static>>;
+
     #[instrument(skip(self))]
     async fn add(
         &self,
-        request: Request<grpc::Record>,
-    ) -> Result<Response<grpc::Response>, Status> {
-        Self::log_and_transform(|| {
-            let raw_record = request.into_inner();
-            let record = models::Record::try_from(raw_record)?;
-
-            diesel::insert_into(passwords::table)
-                .values(&record)
-                .execute(&mut *self.connection()?)
-                .map_err(|err| err.with_context(record.resource_name.clone()))?;
-            self.cache.add(record);
-
-            Ok(Response::new(grpc::Response {}))
-        })
+        request: Request<grpc::AddRequest>,
+    ) -> Result<Response<grpc::AddResponse>, Status> {
+        Self::log_and_transform(|| self.add_record(request.into_inner(), None))
     }
 
     #[instrument(skip(self))]
     #[expect(clippy::panic, reason = "should never happen")]
     async fn delete(
         &self,
-        request: Request<grpc::Resource>,
+        request: Request<grpc::DeleteRequest>,
     ) -> Result<Response<grpc::Response>, Status> {
         Self::log_and_transform(|| {
-            let resource_name = request.into_inner().name;
+            let grpc::DeleteRequest {
+                name: resource_name,
+                expected_revision,
+            } = request.into_inner();
+            validate_resource_name(&resource_name)?;
 
-            let affected_rows = diesel::delete(
-                passwords::table.filter(passwords::resource_name.eq(&resource_name)),
-            )
-            .execute(&mut *self.connection()?)
+            let mut connection = self.connection()?;
+            let record = passwords::table.filter(passwords::resource_name.eq(&resource_name));
+            let affected_rows = match expected_revision {
+                0 => diesel::delete(record).execute(&mut *connection),
+                expected_revision => {
+                    // Revision which doesn't fit into the database can't match
+                    let expected_revision = i64::try_from(expected_revision).unwrap_or(-1);
+                    diesel::delete(record.filter(passwords::revision.eq(expected_revision)))
+                        .execute(&mut *connection)
+                }
+            }
             .map_err(|err| err.with_context(resource_name.clone()))?;
 
             match affected_rows {
+                0 if expected_revision != 0
+                    && diesel::select(diesel::dsl::exists(record))
+                        .get_result::<bool>(&mut *connection)
+                        .map_err(Error::Database)? =>
+                {
+                    // Cached record may have been changed by another instance
+                    self.cache.invalidate(&resource_name);
+                    Err(Error::RevisionMismatch(resource_name))
+                }
                 0 => Err(Error::NotFound(resource_name)),
                 1 => {
                     self.cache.invalidate(&resource_name);
@@ -212,23 +1092,81 @@ impl grpc::password_storage_server::PasswordStorage for PasswordStorage {
         })
     }
 
+    #[instrument(skip(self))]
+    async fn rename(
+        &self,
+        request: Request<grpc::RenameRequest>,
+    ) -> Result<Response<grpc::Response>, Status> {
+        Self::log_and_transform(|| {
+            let grpc::RenameRequest {
+                name: resource_name,
+                new_name,
+                expected_revision,
+            } = request.into_inner();
+            validate_resource_name(&resource_name)?;
+            validate_resource_name(&new_name)?;
+
+            // Revision which doesn't fit into the database can't match
+            let revision = i64::try_from(expected_revision).unwrap_or(-1);
+            let record = passwords::table.filter(passwords::resource_name.eq(&resource_name));
+            let renamable = record
+                .filter(
+                    passwords::revision
+                        .eq(revision)
+                        .or((expected_revision == 0).into_sql::<diesel::sql_types::Bool>()),
+                )
+                .filter(passwords::bound_to_resource_name.eq(false))
+                .filter(diesel::dsl::not(diesel::dsl::exists(
+                    attachments::table.filter(attachments::resource_name.eq(&resource_name)),
+                )));
+
+            let mut connection = self.connection()?;
+            // Blind index, password fingerprint and payload chunks follow the record
+            let renamed = diesel::update(renamable)
+                .set((
+                    passwords::resource_name.eq(&new_name),
+                    passwords::revision.eq(diesel::dsl::sql::<diesel::sql_types::BigInt>(
+                        "nextval('passwords_revision_seq')",
+                    )),
+                ))
+                .get_result::<models::Record>(&mut *connection)
+                .optional()
+                .map_err(|err| err.with_context(new_name.clone()))?;
+
+            // Cached record may have been changed by another instance
+            self.cache.invalidate(&resource_name);
+            let Some(renamed) = renamed else {
+                let current = record
+                    .first::<models::Record>(&mut *connection)
+                    .map_err(|err| err.with_context(resource_name.clone()))?;
+                return Err(if expected_revision != 0 && current.revision != revision {
+                    Error::RevisionMismatch(resource_name)
+                } else if current.bound_to_resource_name {
+                    Error::NotRenamable(resource_name, "payload is bound to the name")
+                } else {
+                    Error::NotRenamable(resource_name, "attachments are bound to the name")
+                });
+            };
+
+            self.cache.add(renamed);
+            Ok(Response::new(grpc::Response {}))
+        })
+    }
+
     #[instrument(skip(self))]
     async fn get(
         &self,
-        request: Request<grpc::Resource>,
+        request: Request<grpc::GetRequest>,
     ) -> Result<Response<grpc::Record>, Status> {
         Self::log_and_transform(|| {
-            let resource_name = request.into_inner().name;
+            let grpc::GetRequest {
+                name: resource_name,
+                bypass_cache,
+            } = request.into_inner();
+            validate_resource_name(&resource_name)?;
 
-            self.cache
-                .get_or_try_insert_with(&resource_name, || {
-                    passwords::table
-                        .filter(passwords::resource_name.eq(&resource_name))
-                        .first::<models::Record>(&mut *self.connection()?)
-                        .map_err(|err| err.with_context(resource_name.clone()))
-                        .map_err(Into::into)
-                })
-                .map(|record| Response::new(grpc::Record::from(record)))
+            let record = self.find_record(&resource_name, bypass_cache)?;
+            Ok(Response::new(grpc::Record::from(record)))
         })
     }
 
@@ -256,9 +1194,16 @@ impl grpc::password_storage_server::PasswordStorage for PasswordStorage {
     ) -> Result<Response<grpc::ListOfResources>, Status> {
         Self::log_and_transform(|| {
             let resource_name = request.into_inner().name;
+            validate_resource_name(&resource_name)?;
+
+            let pattern = format!("%{}%", escape_like_pattern(&resource_name));
 
             let found_resource_names = passwords::table
-                .filter(passwords::resource_name.ilike(format!("%{resource_name}%")))
+                .filter(
+                    passwords::resource_name
+                        .ilike(pattern)
+                        .escape(LIKE_ESCAPE_CHARACTER),
+                )
                 .select(passwords::resource_name)
                 .load::<String>(&mut *self.connection()?)
                 .map_err(|err| err.with_context(resource_name))?;
@@ -271,4 +1216,1710 @@ impl grpc::password_storage_server::PasswordStorage for PasswordStorage {
             }))
         })
     }
+
+    #[instrument(skip(self))]
+    async fn search_blind(
+        &self,
+        request: Request<grpc::BlindTokens>,
+    ) -> Result<Response<grpc::ListOfResources>, Status> {
+        Self::log_and_transform(|| {
+            let tokens = validate_blind_tokens(request.into_inner().tokens)?;
+            if tokens.is_empty() {
+                return Err(Error::InvalidBlindIndex("no tokens to search for"));
+            }
+            let tokens_count = i64::try_from(tokens.len())
+                .map_err(|_err| Error::InvalidBlindIndex("too many tokens"))?;
+
+            let found_resource_names = blind_index::table
+                .filter(blind_index::token.eq_any(tokens))
+                .group_by(blind_index::resource_name)
+                .having(diesel::dsl::count(blind_index::token).eq(tokens_count))
+                .select(blind_index::resource_name)
+                .order(blind_index::resource_name)
+                .load::<String>(&mut *self.connection()?)
+                .map_err(Error::Database)?;
+
+            Ok(Response::new(grpc::ListOfResources {
+                resources: found_resource_names
+                    .into_iter()
+                    .map(|resource| grpc::Resource { name: resource })
+                    .collect(),
+            }))
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn add_chunked(
+        &self,
+        request: Request<tonic::Streaming<grpc::RecordChunk>>,
+    ) -> Result<Response<grpc::AddResponse>, Status> {
+        let record = Self::receive_chunks(request.into_inner()).await;
+
+        Self::log_and_transform(|| {
+            let (add_request, chunks) = record?.finish()?;
+            self.add_record(add_request, Some(chunks))
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn get_chunked(
+        &self,
+        request: Request<grpc::GetRequest>,
+    ) -> Result<Response<Self::GetChunkedStream>, Status> {
+        let RecordChunks(chunks) = Self::log_and_transform(|| {
+            let grpc::GetRequest {
+                name: resource_name,
+                bypass_cache,
+            } = request.into_inner();
+            validate_resource_name(&resource_name)?;
+
+            let record = self.find_record(&resource_name, bypass_cache)?;
+            self.load_chunks(record)
+        })?;
+
+        Ok(Response::new(Box::pin(tokio_stream::iter(
+            chunks.into_iter().map(Ok),
+        ))))
+    }
+
+    #[instrument(skip(self))]
+    #[expect(
+        clippy::wildcard_enum_match_arm,
+        reason = "only unique violation is special"
+    )]
+    async fn add_attachment(
+        &self,
+        request: Request<grpc::AddAttachmentRequest>,
+    ) -> Result<Response<grpc::Response>, Status> {
+        Self::log_and_transform(|| {
+            let attachment = models::Attachment::try_from(request.into_inner())?;
+            validate_resource_name(&attachment.resource_name)?;
+            validate_attachment(&attachment)?;
+
+            let mut connection = self.connection()?;
+            let record =
+                passwords::table.filter(passwords::resource_name.eq(&attachment.resource_name));
+            if !diesel::select(diesel::dsl::exists(record))
+                .get_result::<bool>(&mut *connection)
+                .map_err(Error::Database)?
+            {
+                return Err(Error::NotFound(attachment.resource_name));
+            }
+
+            diesel::insert_into(attachments::table)
+                .values(&attachment)
+                .execute(&mut *connection)
+                .map_err(|err| match err {
+                    diesel::result::Error::DatabaseError(
+                        diesel::result::DatabaseErrorKind::UniqueViolation,
+                        _,
+                    ) => Error::AttachmentAlreadyExists(attachment.filename.clone()),
+                    // Record may have been deleted after the check above
+                    diesel::result::Error::DatabaseError(
+                        diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+                        _,
+                    ) => Error::NotFound(attachment.resource_name.clone()),
+                    err => Error::Database(err),
+                })?;
+
+            Ok(Response::new(grpc::Response {}))
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn get_attachment(
+        &self,
+        request: Request<grpc::GetAttachmentRequest>,
+    ) -> Result<Response<grpc::Attachment>, Status> {
+        Self::log_and_transform(|| {
+            let grpc::GetAttachmentRequest { resource, filename } = request.into_inner();
+            let resource_name = resource
+                .ok_or(models::InvalidAttachmentError::ResourceIsMissing)?
+                .name;
+            validate_resource_name(&resource_name)?;
+
+            let attachment = attachments::table
+                .find((&resource_name, &filename))
+                .first::<models::Attachment>(&mut *self.connection()?)
+                .optional()
+                .map_err(Error::Database)?
+                .ok_or(Error::AttachmentNotFound(filename))?;
+
+            Ok(Response::new(grpc::Attachment::from(attachment)))
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn list_attachments(
+        &self,
+        request: Request<grpc::Resource>,
+    ) -> Result<Response<grpc::ListOfAttachments>, Status> {
+        Self::log_and_transform(|| {
+            let resource_name = request.into_inner().name;
+            validate_resource_name(&resource_name)?;
+
+            let mut connection = self.connection()?;
+            let found_attachments = attachments::table
+                .filter(attachments::resource_name.eq(&resource_name))
+                .select((attachments::filename, attachments::size))
+                .order(attachments::filename)
+                .load::<(String, i64)>(&mut *connection)
+                .map_err(Error::Database)?;
+
+            if found_attachments.is_empty()
+                && !diesel::select(diesel::dsl::exists(
+                    passwords::table.filter(passwords::resource_name.eq(&resource_name)),
+                ))
+                .get_result::<bool>(&mut *connection)
+                .map_err(Error::Database)?
+            {
+                return Err(Error::NotFound(resource_name));
+            }
+
+            Ok(Response::new(grpc::ListOfAttachments {
+                attachments: found_attachments
+                    .into_iter()
+                    .map(|(filename, size)| grpc::AttachmentInfo {
+                        filename,
+                        // Size is checked to be non-negative by the database
+                        size: u64::try_from(size).unwrap_or_default(),
+                    })
+                    .collect(),
+            }))
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn service_status(
+        &self,
+        _request: Request<grpc::Empty>,
+    ) -> Result<Response<grpc::ServiceStatus>, Status> {
+        Self::log_and_transform(|| {
+            let schema_version = Self::schema_version(&mut *self.connection()?)?;
+            let cache::Stats {
+                resources,
+                records,
+                capacity,
+                hits,
+                misses,
+            } = self.cache.stats();
+
+            Ok(Response::new(grpc::ServiceStatus {
+                uptime_seconds: self.started_at.elapsed().as_secs(),
+                record_count: u64::try_from(resources).unwrap_or(u64::MAX),
+                cached_records: u64::try_from(records).unwrap_or(u64::MAX),
+                cache_capacity: u64::try_from(capacity).unwrap_or(u64::MAX),
+                cache_hits: hits,
+                cache_misses: misses,
+                schema_version,
+            }))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use diesel::connection::SimpleConnection as _;
+    use grpc::password_storage_server::PasswordStorage as _;
+
+    use super::{test_db::TestSchema, *};
+
+    #[test]
+    fn escape_like_pattern_should_keep_regular_symbols() {
+        assert_eq!(
+            escape_like_pattern("test.resource.com"),
+            "test.resource.com"
+        );
+    }
+
+    #[test]
+    fn escape_like_pattern_should_escape_wildcards() {
+        assert_eq!(escape_like_pattern("100%_sure"), "100\\%\\_sure");
+    }
+
+    #[test]
+    fn escape_like_pattern_should_escape_escape_character() {
+        assert_eq!(escape_like_pattern("back\\slash"), "back\\\\slash");
+    }
+
+    #[test]
+    fn validate_resource_name_should_accept_max_length() {
+        // Length is counted in characters, not bytes
+        validate_resource_name(&"\u{44f}".repeat(MAX_RESOURCE_NAME_LENGTH)).unwrap();
+    }
+
+    #[test]
+    fn validate_resource_name_should_reject_too_long_name() {
+        validate_resource_name(&"n".repeat(MAX_RESOURCE_NAME_LENGTH + 1)).unwrap_err();
+    }
+
+    #[test]
+    fn validate_resource_name_should_reject_nul() {
+        validate_resource_name("nul\0name").unwrap_err();
+    }
+
+    #[test]
+    fn validate_idempotency_key_should_reject_too_long_key() {
+        validate_idempotency_key(&"k".repeat(MAX_IDEMPOTENCY_KEY_LENGTH)).unwrap();
+        validate_idempotency_key(&"k".repeat(MAX_IDEMPOTENCY_KEY_LENGTH + 1)).unwrap_err();
+    }
+
+    #[test]
+    fn replayed_add_should_return_original_outcome() {
+        let Some(schema) = TestSchema::create("replayed_add") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+
+        runtime().block_on(async {
+            for _ in 0..2_u8 {
+                service
+                    .add(Request::new(sample_record_with_key(
+                        b"encrypted payload",
+                        "key",
+                    )))
+                    .await
+                    .unwrap();
+            }
+            assert_eq!(list_resources(&service).await.len(), 1);
+
+            // Requests without a key are not deduplicated
+            let status = service
+                .add(Request::new(sample_record(b"encrypted payload")))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::AlreadyExists);
+        });
+
+        // Keys survive restart
+        let restarted_service = runtime().block_on(schema.service(4));
+        runtime().block_on(async {
+            restarted_service
+                .add(Request::new(sample_record_with_key(
+                    b"encrypted payload",
+                    "key",
+                )))
+                .await
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn add_should_report_existing_name_added_by_another_instance() {
+        let Some(schema) = TestSchema::create("add_existing_from_another_instance") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+        let another_service = runtime().block_on(schema.service(4));
+
+        runtime().block_on(async {
+            service
+                .add(Request::new(sample_record(b"encrypted payload")))
+                .await
+                .unwrap();
+
+            let mut record = sample_record(b"encrypted payload");
+            record.resource = Some(grpc::Resource {
+                name: "TEST.resource.com".to_owned(),
+            });
+            let status = another_service.add(Request::new(record)).await.unwrap_err();
+            assert_eq!(status.code(), Code::AlreadyExists);
+            assert_eq!(
+                status.message(),
+                Error::AlreadyExists("test.resource.com".to_owned()).to_string()
+            );
+        });
+    }
+
+    #[test]
+    fn concurrent_replay_should_not_fail() {
+        let Some(schema) = TestSchema::create("concurrent_replay") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+
+        // Original request is still running, so its record and key are not committed yet
+        let mut original = schema.connection();
+        original
+            .batch_execute(
+                "BEGIN; \
+                 INSERT INTO passwords (resource_name, encrypted_payload, salt) \
+                 VALUES ('test.resource.com', 'payload', 'salt'); \
+                 INSERT INTO idempotency_keys (key, resource_name) \
+                 VALUES ('key', 'test.resource.com');",
+            )
+            .unwrap();
+
+        let retry = std::thread::spawn(move || {
+            runtime().block_on(service.add(Request::new(sample_record_with_key(
+                b"encrypted payload",
+                "key",
+            ))))
+        });
+        // Wait until the retry is blocked on the unique index by the original transaction
+        while !diesel::select(diesel::dsl::sql::<diesel::sql_types::Bool>(
+            "EXISTS (SELECT 1 FROM pg_locks \
+             WHERE NOT granted AND pg_backend_pid() = ANY(pg_blocking_pids(pid)))",
+        ))
+        .get_result::<bool>(&mut original)
+        .unwrap()
+        {
+            assert!(!retry.is_finished(), "retry finished without waiting");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        original.batch_execute("COMMIT;").unwrap();
+
+        retry.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn idempotency_key_should_not_be_reused_for_another_resource() {
+        let Some(schema) = TestSchema::create("reused_idempotency_key") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+
+        runtime().block_on(async {
+            service
+                .add(Request::new(sample_record_with_key(
+                    b"encrypted payload",
+                    "key",
+                )))
+                .await
+                .unwrap();
+
+            let mut another_record = sample_record_with_key(b"encrypted payload", "key");
+            another_record.resource = Some(grpc::Resource {
+                name: "another.resource.com".to_owned(),
+            });
+            let status = service.add(Request::new(another_record)).await.unwrap_err();
+            assert_eq!(status.code(), Code::InvalidArgument);
+            assert_eq!(list_resources(&service).await.len(), 1);
+        });
+    }
+
+    #[test]
+    fn expired_idempotency_key_should_be_forgotten() {
+        let Some(schema) = TestSchema::create("expired_idempotency_key") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+
+        runtime().block_on(async {
+            service
+                .add(Request::new(sample_record_with_key(
+                    b"encrypted payload",
+                    "key",
+                )))
+                .await
+                .unwrap();
+
+            schema.execute(&format!(
+                "UPDATE idempotency_keys SET created_at = now() - interval '{} hours';",
+                IDEMPOTENCY_KEY_TTL_HOURS + 1_i32
+            ));
+
+            let status = service
+                .add(Request::new(sample_record_with_key(
+                    b"encrypted payload",
+                    "key",
+                )))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::AlreadyExists);
+        });
+    }
+
+    #[test]
+    fn validate_blind_tokens_should_reject_malformed_tokens() {
+        let token = vec![1; BLIND_TOKEN_SIZE];
+
+        assert_eq!(
+            validate_blind_tokens(vec![token.clone(), token.clone()])
+                .unwrap()
+                .len(),
+            1
+        );
+        validate_blind_tokens(vec![token.clone(); MAX_BLIND_TOKENS + 1]).unwrap_err();
+        validate_blind_tokens(vec![token, vec![1; BLIND_TOKEN_SIZE - 1]]).unwrap_err();
+    }
+
+    #[test]
+    fn search_blind_should_find_resources_with_all_tokens() {
+        let Some(schema) = TestSchema::create("search_blind") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+        let token = |byte: u8| vec![byte; BLIND_TOKEN_SIZE];
+
+        runtime().block_on(async {
+            for (name, blind_index) in [
+                ("router", vec![token(1), token(2)]),
+                ("switch", vec![token(2), token(3)]),
+                ("printer", Vec::new()),
+            ] {
+                let mut record = sample_record(b"encrypted payload");
+                record.resource = Some(grpc::Resource {
+                    name: name.to_owned(),
+                });
+                record.blind_index = blind_index;
+                service.add(Request::new(record)).await.unwrap();
+            }
+
+            assert_eq!(
+                search_blind(&service, vec![token(2)]).await,
+                ["router", "switch"]
+            );
+            assert_eq!(
+                search_blind(&service, vec![token(1), token(2)]).await,
+                ["router"]
+            );
+            assert!(search_blind(&service, vec![token(1), token(3)])
+                .await
+                .is_empty());
+
+            let status = service
+                .search_blind(Request::new(grpc::BlindTokens { tokens: Vec::new() }))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::InvalidArgument);
+
+            // Index is removed together with the record
+            service
+                .delete(Request::new(grpc::DeleteRequest {
+                    name: "router".to_owned(),
+                    expected_revision: 0,
+                }))
+                .await
+                .unwrap();
+            assert_eq!(search_blind(&service, vec![token(2)]).await, ["switch"]);
+        });
+    }
+
+    #[test]
+    fn blind_index_version_should_be_stored_with_tokens_and_fingerprint() {
+        let Some(schema) = TestSchema::create("blind_index_version") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(0));
+
+        runtime().block_on(async {
+            let status = service
+                .add(Request::new(grpc::AddRequest {
+                    blind_index_version: u32::MAX,
+                    ..sample_record(b"encrypted payload")
+                }))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::InvalidArgument);
+
+            service
+                .add(Request::new(grpc::AddRequest {
+                    blind_index: vec![vec![1; BLIND_TOKEN_SIZE]],
+                    password_fingerprint: vec![2; PASSWORD_FINGERPRINT_SIZE],
+                    blind_index_version: 2,
+                    ..sample_record(b"encrypted payload")
+                }))
+                .await
+                .unwrap();
+        });
+
+        let mut connection = service.connection().unwrap();
+        assert_eq!(
+            blind_index::table
+                .select(blind_index::version)
+                .load::<i32>(&mut *connection)
+                .unwrap(),
+            [2_i32]
+        );
+        assert_eq!(
+            password_fingerprints::table
+                .select(password_fingerprints::version)
+                .load::<i32>(&mut *connection)
+                .unwrap(),
+            [2_i32]
+        );
+    }
+
+    #[test]
+    fn kdf_iterations_should_be_stored_with_record() {
+        let Some(schema) = TestSchema::create("kdf_iterations") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(0));
+
+        runtime().block_on(async {
+            let status = service
+                .add(Request::new(grpc::AddRequest {
+                    kdf_iterations: u32::MAX,
+                    ..sample_record(b"encrypted payload")
+                }))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::InvalidArgument);
+
+            service
+                .add(Request::new(grpc::AddRequest {
+                    kdf_iterations: 200_000,
+                    ..sample_record(b"encrypted payload")
+                }))
+                .await
+                .unwrap();
+            assert_eq!(get_record(&service, true).await.kdf_iterations, 200_000);
+
+            // Records added before the number of iterations was stored
+            schema.execute("UPDATE passwords SET kdf_iterations = NULL;");
+            assert_eq!(get_record(&service, true).await.kdf_iterations, 0);
+        });
+    }
+
+    #[test]
+    fn kdf_salt_should_be_stored_with_record() {
+        let Some(schema) = TestSchema::create("kdf_salt") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(0));
+
+        runtime().block_on(async {
+            service
+                .add(Request::new(grpc::AddRequest {
+                    kdf_salt: b"kdf_salt".to_vec(),
+                    ..sample_record(b"encrypted payload")
+                }))
+                .await
+                .unwrap();
+            assert_eq!(get_record(&service, true).await.kdf_salt, b"kdf_salt");
+
+            // Records added before the key derivation salt was stored
+            schema.execute("UPDATE passwords SET kdf_salt = NULL;");
+            assert!(get_record(&service, true).await.kdf_salt.is_empty());
+        });
+    }
+
+    #[test]
+    fn algorithm_should_be_stored_with_record() {
+        let Some(schema) = TestSchema::create("algorithm") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(0));
+
+        runtime().block_on(async {
+            service
+                .add(Request::new(grpc::AddRequest {
+                    algorithm: grpc::Algorithm::Xchacha20Poly1305.into(),
+                    salt: vec![1; telepass_crypto::XCHACHA20_POLY1305_SALT_SIZE],
+                    ..sample_record(b"encrypted payload")
+                }))
+                .await
+                .unwrap();
+            assert_eq!(
+                get_record(&service, true).await.algorithm(),
+                grpc::Algorithm::Xchacha20Poly1305
+            );
+        });
+    }
+
+    #[test]
+    fn output_version_should_be_stored_with_record() {
+        let Some(schema) = TestSchema::create("output_version") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(0));
+
+        runtime().block_on(async {
+            let status = service
+                .add(Request::new(grpc::AddRequest {
+                    output_version: u32::MAX,
+                    ..sample_record(b"encrypted payload")
+                }))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::InvalidArgument);
+
+            service
+                .add(Request::new(grpc::AddRequest {
+                    output_version: 2,
+                    ..sample_record(b"encrypted payload")
+                }))
+                .await
+                .unwrap();
+            assert_eq!(get_record(&service, true).await.output_version, 2);
+        });
+    }
+
+    #[test]
+    fn bound_to_resource_name_should_be_stored_with_record() {
+        let Some(schema) = TestSchema::create("bound_to_resource_name") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(0));
+
+        runtime().block_on(async {
+            service
+                .add(Request::new(grpc::AddRequest {
+                    bound_to_resource_name: true,
+                    ..sample_record(b"encrypted payload")
+                }))
+                .await
+                .unwrap();
+            assert!(get_record(&service, true).await.bound_to_resource_name);
+
+            // Records encrypted without associated data
+            schema.execute("UPDATE passwords SET bound_to_resource_name = FALSE;");
+            assert!(!get_record(&service, true).await.bound_to_resource_name);
+        });
+    }
+
+    #[test]
+    fn validate_password_fingerprint_should_reject_wrong_size() {
+        assert_eq!(validate_password_fingerprint(Vec::new()).unwrap(), None);
+        assert_eq!(
+            validate_password_fingerprint(vec![1; PASSWORD_FINGERPRINT_SIZE]).unwrap(),
+            Some(vec![1; PASSWORD_FINGERPRINT_SIZE])
+        );
+        validate_password_fingerprint(vec![1; PASSWORD_FINGERPRINT_SIZE + 1]).unwrap_err();
+    }
+
+    #[test]
+    fn add_should_report_resources_with_same_password_fingerprint() {
+        let Some(schema) = TestSchema::create("password_fingerprint") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(0));
+
+        runtime().block_on(async {
+            let add = |name: &str, password_fingerprint: Vec<u8>| {
+                service.add(Request::new(grpc::AddRequest {
+                    resource: Some(grpc::Resource {
+                        name: name.to_owned(),
+                    }),
+                    idempotency_key: format!("key-{name}"),
+                    password_fingerprint,
+                    ..sample_record(b"encrypted payload")
+                }))
+            };
+            let reused_by = |response: Response<grpc::AddResponse>| response.into_inner().reused_by;
+            let fingerprint = vec![1; PASSWORD_FINGERPRINT_SIZE];
+
+            assert!(reused_by(add("b.com", fingerprint.clone()).await.unwrap()).is_empty());
+            assert!(reused_by(add("c.com", Vec::new()).await.unwrap()).is_empty());
+            assert!(reused_by(
+                add("d.com", vec![2; PASSWORD_FINGERPRINT_SIZE])
+                    .await
+                    .unwrap()
+            )
+            .is_empty());
+            assert_eq!(
+                reused_by(add("a.com", fingerprint.clone()).await.unwrap()),
+                ["b.com"]
+            );
+            assert_eq!(
+                reused_by(add("e.com", fingerprint.clone()).await.unwrap()),
+                ["a.com", "b.com"]
+            );
+            // Replayed request reports the same
+            assert_eq!(
+                reused_by(add("e.com", fingerprint.clone()).await.unwrap()),
+                ["a.com", "b.com"]
+            );
+
+            let status = add("f.com", vec![1; PASSWORD_FINGERPRINT_SIZE - 1])
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::InvalidArgument);
+
+            service
+                .delete(Request::new(grpc::DeleteRequest {
+                    name: "a.com".to_owned(),
+                    expected_revision: 0,
+                }))
+                .await
+                .unwrap();
+            assert_eq!(
+                reused_by(add("f.com", fingerprint).await.unwrap()),
+                ["b.com", "e.com"]
+            );
+        });
+    }
+
+    #[test]
+    fn delete_should_check_expected_revision() {
+        let Some(schema) = TestSchema::create("revision") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(1));
+
+        runtime().block_on(async {
+            let delete = |expected_revision| {
+                service.delete(Request::new(grpc::DeleteRequest {
+                    name: "test.resource.com".to_owned(),
+                    expected_revision,
+                }))
+            };
+
+            service
+                .add(Request::new(sample_record(b"encrypted payload")))
+                .await
+                .unwrap();
+            let revision = get_record(&service, false).await.revision;
+            assert_ne!(revision, 0);
+
+            // Record is deleted and added again by someone else
+            delete(0).await.unwrap();
+            service
+                .add(Request::new(sample_record(b"encrypted payload")))
+                .await
+                .unwrap();
+            let new_revision = get_record(&service, false).await.revision;
+            assert_ne!(new_revision, revision);
+
+            for stale_revision in [revision, u64::MAX] {
+                let status = delete(stale_revision).await.unwrap_err();
+                assert_eq!(status.code(), Code::Aborted);
+            }
+
+            delete(new_revision).await.unwrap();
+            assert_eq!(
+                delete(new_revision).await.unwrap_err().code(),
+                Code::NotFound
+            );
+        });
+    }
+
+    #[test]
+    fn rename_should_keep_blind_index_and_password_fingerprint() {
+        let Some(schema) = TestSchema::create("rename") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+        let token = vec![1; BLIND_TOKEN_SIZE];
+        let fingerprint = vec![1; PASSWORD_FINGERPRINT_SIZE];
+
+        runtime().block_on(async {
+            service
+                .add(Request::new(grpc::AddRequest {
+                    blind_index: vec![token.clone()],
+                    password_fingerprint: fingerprint.clone(),
+                    ..sample_record(b"encrypted payload")
+                }))
+                .await
+                .unwrap();
+            let original = get_record(&service, false).await;
+
+            service
+                .rename(Request::new(grpc::RenameRequest {
+                    name: "test.resource.com".to_owned(),
+                    new_name: "renamed.com".to_owned(),
+                    expected_revision: original.revision,
+                }))
+                .await
+                .unwrap();
+
+            let renamed = service
+                .get(Request::new(grpc::GetRequest {
+                    name: "renamed.com".to_owned(),
+                    bypass_cache: false,
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(renamed.encrypted_payload, original.encrypted_payload);
+            assert_ne!(renamed.revision, original.revision);
+            let old_name_status = service
+                .get(Request::new(grpc::GetRequest {
+                    name: "test.resource.com".to_owned(),
+                    bypass_cache: false,
+                }))
+                .await
+                .unwrap_err();
+            assert_eq!(old_name_status.code(), Code::NotFound);
+            let listed = service
+                .list(Request::new(grpc::Empty {}))
+                .await
+                .unwrap()
+                .into_inner()
+                .resources;
+            assert_eq!(
+                listed,
+                [grpc::Resource {
+                    name: "renamed.com".to_owned()
+                }]
+            );
+
+            assert_eq!(search_blind(&service, vec![token]).await, ["renamed.com"]);
+            let reused_by = service
+                .add(Request::new(grpc::AddRequest {
+                    password_fingerprint: fingerprint,
+                    ..sample_record(b"encrypted payload")
+                }))
+                .await
+                .unwrap()
+                .into_inner()
+                .reused_by;
+            assert_eq!(reused_by, ["renamed.com"]);
+
+            let status = service
+                .rename(Request::new(grpc::RenameRequest {
+                    name: "test.resource.com".to_owned(),
+                    new_name: "Renamed.com".to_owned(),
+                    expected_revision: 0,
+                }))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::AlreadyExists);
+        });
+    }
+
+    #[test]
+    fn rename_should_check_revision_and_bindings() {
+        let Some(schema) = TestSchema::create("rename_checks") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+
+        runtime().block_on(async {
+            let rename = |name: &str, expected_revision| {
+                service.rename(Request::new(grpc::RenameRequest {
+                    name: name.to_owned(),
+                    new_name: "renamed.com".to_owned(),
+                    expected_revision,
+                }))
+            };
+
+            assert_eq!(
+                rename("test.resource.com", 0).await.unwrap_err().code(),
+                Code::NotFound
+            );
+
+            service
+                .add(Request::new(sample_record(b"encrypted payload")))
+                .await
+                .unwrap();
+            let revision = get_record(&service, false).await.revision;
+            for stale_revision in [revision.wrapping_add(1), u64::MAX] {
+                let status = rename("test.resource.com", stale_revision)
+                    .await
+                    .unwrap_err();
+                assert_eq!(status.code(), Code::Aborted);
+            }
+
+            // Attachments are authenticated with the name
+            service
+                .add_attachment(Request::new(sample_attachment("a.txt", b"content")))
+                .await
+                .unwrap();
+            assert_eq!(
+                rename("test.resource.com", revision)
+                    .await
+                    .unwrap_err()
+                    .code(),
+                Code::FailedPrecondition
+            );
+
+            service
+                .add(Request::new(grpc::AddRequest {
+                    resource: Some(grpc::Resource {
+                        name: "bound.com".to_owned(),
+                    }),
+                    bound_to_resource_name: true,
+                    ..sample_record(b"encrypted payload")
+                }))
+                .await
+                .unwrap();
+            assert_eq!(
+                rename("bound.com", 0).await.unwrap_err().code(),
+                Code::FailedPrecondition
+            );
+        });
+    }
+
+    /// Split `payload` into chunks of `add_chunked` request adding sample record.
+    fn sample_chunks(payload: &[u8], chunk_size: usize) -> Vec<grpc::RecordChunk> {
+        let parts = payload
+            .chunks(chunk_size)
+            .map(<[u8]>::to_vec)
+            .collect::<Vec<_>>();
+        let checksum = payload_checksum(&parts);
+        let mut chunks = parts
+            .into_iter()
+            .map(|data| grpc::RecordChunk {
+                data,
+                ..grpc::RecordChunk::default()
+            })
+            .collect::<Vec<_>>();
+        let first = chunks.first_mut().unwrap();
+        first.add_request = Some(sample_record(b""));
+        first.checksum = checksum;
+        chunks
+    }
+
+    /// Receive `chunks` as `add_chunked` does.
+    fn receive(chunks: Vec<grpc::RecordChunk>) -> Result<(grpc::AddRequest, PayloadChunks)> {
+        let mut record = ChunkedRecord::default();
+        for chunk in chunks {
+            record.push(chunk)?;
+        }
+        record.finish()
+    }
+
+    #[test]
+    fn chunked_record_should_be_reassembled_in_order() {
+        let payload = (0..=u8::MAX).collect::<Vec<_>>();
+
+        let (request, chunks) = receive(sample_chunks(&payload, 100)).unwrap();
+
+        assert_eq!(request, sample_record(b""));
+        assert_eq!(chunks.chunks.len(), 3);
+        assert_eq!(chunks.chunks.concat(), payload);
+        assert_eq!(chunks.checksum, Sha256::digest(&payload).to_vec());
+    }
+
+    #[test]
+    fn chunked_record_should_reject_checksum_mismatch() {
+        let mut chunks = sample_chunks(b"payload split into chunks", 8);
+        chunks.swap(1, 2);
+
+        let error = receive(chunks).unwrap_err();
+        assert!(
+            matches!(error, Error::InvalidChunks("checksum mismatch")),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn chunked_record_should_reject_malformed_chunks() {
+        let chunks = || sample_chunks(b"payload split into chunks", 8);
+
+        receive(Vec::new()).unwrap_err();
+        receive(chunks().split_off(1)).unwrap_err();
+
+        let mut repeated_header = chunks();
+        repeated_header.last_mut().unwrap().add_request = Some(sample_record(b""));
+        receive(repeated_header).unwrap_err();
+
+        let mut inline_payload = chunks();
+        inline_payload.first_mut().unwrap().add_request = Some(sample_record(b"encrypted payload"));
+        receive(inline_payload).unwrap_err();
+
+        let mut short_checksum = chunks();
+        short_checksum.first_mut().unwrap().checksum.pop();
+        receive(short_checksum).unwrap_err();
+
+        let mut empty_chunk = chunks();
+        empty_chunk.last_mut().unwrap().data.clear();
+        receive(empty_chunk).unwrap_err();
+
+        receive(sample_chunks(
+            &vec![1; MAX_CHUNK_SIZE + 1],
+            MAX_CHUNK_SIZE + 1,
+        ))
+        .unwrap_err();
+    }
+
+    #[test]
+    fn chunked_record_should_be_got_in_chunks() {
+        let Some(schema) = TestSchema::create("chunked_record") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+        // One full chunk and one half-filled
+        let payload = (0_u8..=u8::MAX)
+            .cycle()
+            .take(MAX_CHUNK_SIZE + MAX_CHUNK_SIZE.div_ceil(2))
+            .collect::<Vec<_>>();
+
+        runtime().block_on(async {
+            let (request, received) = receive(sample_chunks(&payload, MAX_CHUNK_SIZE)).unwrap();
+            service.add_record(request, Some(received)).unwrap();
+
+            // Payload is not passed in a single message
+            let record = get_record(&service, false).await;
+            assert_eq!(record.chunk_count, 2);
+            assert!(record.encrypted_payload.is_empty());
+
+            let chunks = get_chunks(&service).await.unwrap();
+            assert_eq!(chunks.len(), 2);
+            let header = chunks.first().unwrap();
+            assert_eq!(header.record.as_ref(), Some(&record));
+            assert_eq!(header.checksum, Sha256::digest(&payload).to_vec());
+            assert_eq!(
+                chunks
+                    .into_iter()
+                    .flat_map(|chunk| chunk.data)
+                    .collect::<Vec<_>>(),
+                payload
+            );
+
+            schema.execute("UPDATE payload_chunks SET data = 'corrupted' WHERE chunk_index = 1;");
+            assert_eq!(
+                get_chunks(&service).await.unwrap_err().code(),
+                Code::DataLoss
+            );
+
+            // Chunks are removed together with the record
+            service
+                .delete(Request::new(grpc::DeleteRequest {
+                    name: "test.resource.com".to_owned(),
+                    expected_revision: 0,
+                }))
+                .await
+                .unwrap();
+            schema.execute(
+                "DO $$ BEGIN \
+                 IF EXISTS (SELECT FROM payload_chunks) THEN RAISE 'chunks left'; END IF; \
+                 END $$;",
+            );
+        });
+    }
+
+    #[test]
+    fn inline_record_should_be_got_in_chunks() {
+        let Some(schema) = TestSchema::create("inline_record_chunks") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(0));
+
+        runtime().block_on(async {
+            service
+                .add(Request::new(sample_record(b"encrypted payload")))
+                .await
+                .unwrap();
+
+            let chunks = get_chunks(&service).await.unwrap();
+            assert_eq!(chunks.len(), 1);
+            let chunk = chunks.first().unwrap();
+            assert_eq!(chunk.data, b"encrypted payload");
+            assert_eq!(
+                chunk.checksum,
+                Sha256::digest(b"encrypted payload").to_vec()
+            );
+            let record = chunk.record.as_ref().unwrap();
+            assert_eq!(record.chunk_count, 0);
+            assert!(record.encrypted_payload.is_empty());
+        });
+    }
+
+    #[test]
+    fn search_should_find_seeded_resources_by_substring() {
+        let Some(schema) = TestSchema::create("search") else {
+            return;
+        };
+        schema.seed(20);
+        let service = runtime().block_on(schema.service(0));
+
+        runtime().block_on(async {
+            assert_eq!(list_resources(&service).await.len(), 20);
+
+            let found = service
+                .search(Request::new(grpc::Resource {
+                    name: "SOURCE-1".to_owned(),
+                }))
+                .await
+                .unwrap()
+                .into_inner()
+                .resources
+                .into_iter()
+                .map(|resource| resource.name)
+                .collect::<BTreeSet<_>>();
+            let expected = std::iter::once(1)
+                .chain(10..20)
+                .map(test_db::seeded_resource_name)
+                .collect::<BTreeSet<_>>();
+            assert_eq!(found, expected);
+        });
+    }
+
+    #[test]
+    fn get_bypassing_cache_should_return_fresh_record() {
+        let Some(schema) = TestSchema::create("bypass_cache") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+
+        runtime().block_on(async {
+            service
+                .add(Request::new(sample_record(b"stale encrypted payload")))
+                .await
+                .unwrap();
+            // Populate cache
+            get_payload(&service, false).await;
+
+            schema.execute("UPDATE passwords SET encrypted_payload = 'fresh';");
+
+            assert_eq!(get_payload(&service, true).await, b"fresh");
+            // Cache is neither used nor populated by bypassing request
+            assert_eq!(
+                get_payload(&service, false).await,
+                b"stale encrypted payload"
+            );
+        });
+    }
+
+    #[test]
+    fn zero_cache_size_should_disable_cache() {
+        let Some(schema) = TestSchema::create("zero_cache_size") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(0));
+
+        runtime().block_on(async {
+            service
+                .add(Request::new(sample_record(b"stale encrypted payload")))
+                .await
+                .unwrap();
+            get_payload(&service, false).await;
+
+            schema.execute("UPDATE passwords SET encrypted_payload = 'fresh';");
+
+            assert_eq!(get_payload(&service, false).await, b"fresh");
+        });
+
+        // Resources are still listed after restart
+        let restarted_service = runtime().block_on(schema.service(0));
+        runtime().block_on(async {
+            let resources = restarted_service
+                .list(Request::new(grpc::Empty {}))
+                .await
+                .unwrap()
+                .into_inner()
+                .resources;
+            assert_eq!(
+                resources,
+                [grpc::Resource {
+                    name: "test.resource.com".to_owned()
+                }]
+            );
+        });
+    }
+
+    #[test]
+    fn connect_should_reject_non_postgres_url() {
+        let config = Config {
+            database_url: "mysql://localhost/telepass".to_owned(),
+            cache_size: 0,
+        };
+
+        let error = runtime()
+            .block_on(PasswordStorage::connect(config))
+            .unwrap_err();
+        assert!(matches!(error, StartupError::BadUrl), "{error:?}");
+    }
+
+    #[test]
+    fn connect_should_report_unreachable_database() {
+        let closed_port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let config = Config {
+            database_url: format!("postgres://postgres@127.0.0.1:{closed_port}/telepass"),
+            cache_size: 0,
+        };
+
+        let error = runtime()
+            .block_on(PasswordStorage::connect(config))
+            .unwrap_err();
+        assert!(
+            matches!(error, StartupError::Unreachable { .. }),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn connect_should_report_not_migrated_database() {
+        let Some(schema) = TestSchema::create("not_migrated") else {
+            return;
+        };
+        schema.execute("DROP TABLE passwords CASCADE;");
+
+        let error = runtime()
+            .block_on(PasswordStorage::connect(schema.config(0)))
+            .unwrap_err();
+        assert!(
+            matches!(error, StartupError::NotMigrated { .. }),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn connect_should_reject_too_big_cache() {
+        let config = Config {
+            database_url: "postgres://localhost/telepass".to_owned(),
+            cache_size: MAX_CACHE_SIZE + 1,
+        };
+
+        let error = runtime()
+            .block_on(PasswordStorage::connect(config))
+            .unwrap_err();
+        assert!(
+            matches!(error, StartupError::InvalidCacheConfig(size) if size == MAX_CACHE_SIZE + 1),
+            "{error:?}"
+        );
+    }
+
+    fn sample_record(encrypted_payload: &[u8]) -> grpc::AddRequest {
+        sample_record_with_key(encrypted_payload, "")
+    }
+
+    fn sample_record_with_key(encrypted_payload: &[u8], idempotency_key: &str) -> grpc::AddRequest {
+        grpc::AddRequest {
+            resource: Some(grpc::Resource {
+                name: "test.resource.com".to_owned(),
+            }),
+            encrypted_payload: encrypted_payload.to_vec(),
+            salt: vec![1; telepass_crypto::AES_256_GCM_SALT_SIZE],
+            idempotency_key: idempotency_key.to_owned(),
+            blind_index: Vec::new(),
+            kdf_iterations: 0,
+            kdf_salt: Vec::new(),
+            algorithm: 0,
+            bound_to_resource_name: false,
+            password_fingerprint: Vec::new(),
+            key_commitment: Vec::new(),
+            output_version: 0,
+            blind_index_version: 0,
+        }
+    }
+
+    #[test]
+    fn validate_attachment_should_reject_bad_filenames_and_sizes() {
+        let attachment = |filename: &str, size: i64| models::Attachment {
+            resource_name: "test.resource.com".to_owned(),
+            filename: filename.to_owned(),
+            encrypted_data: vec![1; 19],
+            salt: vec![4; 12],
+            kdf_iterations: 1,
+            kdf_salt: vec![5; 16],
+            algorithm: 0,
+            key_commitment: None,
+            size,
+            output_version: 0,
+        };
+        let max_size = i64::try_from(MAX_ATTACHMENT_SIZE).unwrap();
+
+        validate_attachment(&attachment("notes.txt", max_size)).unwrap();
+        validate_attachment(&attachment(&"f".repeat(MAX_ATTACHMENT_FILENAME_LENGTH), 0)).unwrap();
+        validate_attachment(&attachment("", 0)).unwrap_err();
+        validate_attachment(&attachment("nul\0name", 0)).unwrap_err();
+        validate_attachment(&attachment(
+            &"f".repeat(MAX_ATTACHMENT_FILENAME_LENGTH + 1),
+            0,
+        ))
+        .unwrap_err();
+        validate_attachment(&attachment("notes.txt", max_size + 1)).unwrap_err();
+    }
+
+    #[test]
+    fn validate_ciphertext_should_use_sizes_of_crypto_crate() {
+        let aes = i32::from(grpc::Algorithm::Aes256Gcm);
+        let xchacha = i32::from(grpc::Algorithm::Xchacha20Poly1305);
+        let tag_size = telepass_crypto::AEAD_TAG_SIZE;
+
+        validate_ciphertext(aes, &[0; telepass_crypto::AES_256_GCM_SALT_SIZE], tag_size).unwrap();
+        validate_ciphertext(
+            xchacha,
+            &[0; telepass_crypto::XCHACHA20_POLY1305_SALT_SIZE],
+            tag_size,
+        )
+        .unwrap();
+        // Algorithms unknown to the storage are stored as is
+        validate_ciphertext(i32::MAX, b"salt", tag_size).unwrap();
+        assert_eq!(
+            MAX_ENCRYPTED_ATTACHMENT_SIZE,
+            usize::try_from(MAX_ATTACHMENT_SIZE).unwrap() + tag_size
+        );
+    }
+
+    #[test]
+    fn validate_ciphertext_should_reject_wrong_salts_and_truncated_payloads() {
+        let aes = i32::from(grpc::Algorithm::Aes256Gcm);
+        let xchacha = i32::from(grpc::Algorithm::Xchacha20Poly1305);
+        let tag_size = telepass_crypto::AEAD_TAG_SIZE;
+
+        for (algorithm, salt_size, expected) in [
+            (aes, 0, telepass_crypto::AES_256_GCM_SALT_SIZE),
+            (
+                aes,
+                telepass_crypto::XCHACHA20_POLY1305_SALT_SIZE,
+                telepass_crypto::AES_256_GCM_SALT_SIZE,
+            ),
+            (
+                xchacha,
+                telepass_crypto::AES_256_GCM_SALT_SIZE,
+                telepass_crypto::XCHACHA20_POLY1305_SALT_SIZE,
+            ),
+        ] {
+            let error = validate_ciphertext(algorithm, &vec![0; salt_size], tag_size).unwrap_err();
+            assert!(
+                matches!(
+                    error,
+                    Error::InvalidSalt { expected: e, actual } if e == expected && actual == salt_size
+                ),
+                "{error:?}"
+            );
+        }
+
+        let error = validate_ciphertext(aes, &[0; 12], tag_size - 1).unwrap_err();
+        assert!(
+            matches!(error, Error::TruncatedPayload(size) if size == tag_size - 1),
+            "{error:?}"
+        );
+        assert_eq!(
+            error.to_string(),
+            "Encrypted payload is truncated: 15 bytes, at least 16 expected"
+        );
+    }
+
+    #[test]
+    fn malformed_ciphertexts_should_be_rejected_with_invalid_argument() {
+        let Some(schema) = TestSchema::create("malformed_ciphertexts") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+
+        runtime().block_on(async {
+            let wrong_salt = grpc::AddRequest {
+                salt: b"salt".to_vec(),
+                ..sample_record(b"encrypted payload")
+            };
+            let salt_status = service.add(Request::new(wrong_salt)).await.unwrap_err();
+            assert_eq!(salt_status.code(), Code::InvalidArgument);
+            assert_eq!(
+                salt_status.message(),
+                "Invalid salt: expected 12 bytes, got 4"
+            );
+
+            let payload_status = service
+                .add(Request::new(sample_record(b"short")))
+                .await
+                .unwrap_err();
+            assert_eq!(payload_status.code(), Code::InvalidArgument);
+            assert_eq!(
+                payload_status.message(),
+                "Encrypted payload is truncated: 5 bytes, at least 16 expected"
+            );
+
+            let (request, received) = receive(sample_chunks(b"short", 2)).unwrap();
+            let error = service.add_record(request, Some(received)).unwrap_err();
+            assert!(matches!(error, Error::TruncatedPayload(5)), "{error:?}");
+            assert!(list_resources(&service).await.is_empty());
+
+            service
+                .add(Request::new(sample_record(b"encrypted payload")))
+                .await
+                .unwrap();
+            let mut truncated = sample_attachment("a.txt", b"first");
+            truncated.attachment.as_mut().unwrap().encrypted_data = b"first".to_vec();
+            let mut foreign_salt = sample_attachment("a.txt", b"first");
+            foreign_salt.attachment.as_mut().unwrap().salt = vec![1; 24];
+            for attachment in [truncated, foreign_salt] {
+                let status = service
+                    .add_attachment(Request::new(attachment))
+                    .await
+                    .unwrap_err();
+                assert_eq!(status.code(), Code::InvalidArgument);
+            }
+            assert!(list_attachments(&service).await.unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn attachment_should_round_trip() {
+        let Some(schema) = TestSchema::create("attachment_round_trip") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+
+        runtime().block_on(async {
+            service
+                .add(Request::new(sample_record(b"encrypted payload")))
+                .await
+                .unwrap();
+            service
+                .add_attachment(Request::new(sample_attachment("b.txt", b"second")))
+                .await
+                .unwrap();
+            service
+                .add_attachment(Request::new(sample_attachment("a.txt", b"first")))
+                .await
+                .unwrap();
+
+            let attachment = service
+                .get_attachment(Request::new(grpc::GetAttachmentRequest {
+                    resource: Some(grpc::Resource {
+                        name: "test.resource.com".to_owned(),
+                    }),
+                    filename: "a.txt".to_owned(),
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(
+                Some(attachment),
+                sample_attachment("a.txt", b"first").attachment
+            );
+
+            assert_eq!(
+                list_attachments(&service).await.unwrap(),
+                vec![
+                    grpc::AttachmentInfo {
+                        filename: "a.txt".to_owned(),
+                        size: 5,
+                    },
+                    grpc::AttachmentInfo {
+                        filename: "b.txt".to_owned(),
+                        size: 6,
+                    },
+                ]
+            );
+
+            let status = service
+                .get_attachment(Request::new(grpc::GetAttachmentRequest {
+                    resource: Some(grpc::Resource {
+                        name: "test.resource.com".to_owned(),
+                    }),
+                    filename: "c.txt".to_owned(),
+                }))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::NotFound);
+        });
+    }
+
+    #[test]
+    fn duplicate_attachment_should_be_rejected() {
+        let Some(schema) = TestSchema::create("duplicate_attachment") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+
+        runtime().block_on(async {
+            service
+                .add(Request::new(sample_record(b"encrypted payload")))
+                .await
+                .unwrap();
+            service
+                .add_attachment(Request::new(sample_attachment("a.txt", b"first")))
+                .await
+                .unwrap();
+
+            let status = service
+                .add_attachment(Request::new(sample_attachment("a.txt", b"again")))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::AlreadyExists);
+        });
+    }
+
+    #[test]
+    fn attachment_of_missing_record_should_not_be_found() {
+        let Some(schema) = TestSchema::create("attachment_of_missing_record") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+
+        runtime().block_on(async {
+            let status = service
+                .add_attachment(Request::new(sample_attachment("a.txt", b"first")))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::NotFound);
+
+            assert_eq!(
+                list_attachments(&service).await.unwrap_err().code(),
+                Code::NotFound
+            );
+        });
+    }
+
+    #[test]
+    fn too_large_attachment_should_be_rejected() {
+        let Some(schema) = TestSchema::create("too_large_attachment") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+
+        runtime().block_on(async {
+            service
+                .add(Request::new(sample_record(b"encrypted payload")))
+                .await
+                .unwrap();
+
+            let content = vec![0; MAX_ENCRYPTED_ATTACHMENT_SIZE + 1];
+            let status = service
+                .add_attachment(Request::new(sample_attachment("big.bin", &content)))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::InvalidArgument);
+            assert!(list_attachments(&service).await.unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn attachments_should_be_deleted_with_record() {
+        let Some(schema) = TestSchema::create("attachments_deleted_with_record") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+
+        runtime().block_on(async {
+            service
+                .add(Request::new(sample_record(b"encrypted payload")))
+                .await
+                .unwrap();
+            service
+                .add_attachment(Request::new(sample_attachment("a.txt", b"first")))
+                .await
+                .unwrap();
+
+            service
+                .delete(Request::new(grpc::DeleteRequest {
+                    name: "test.resource.com".to_owned(),
+                    expected_revision: 0,
+                }))
+                .await
+                .unwrap();
+            service
+                .add(Request::new(sample_record(b"encrypted payload")))
+                .await
+                .unwrap();
+
+            assert!(list_attachments(&service).await.unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn service_status_should_report_statistics() {
+        let Some(schema) = TestSchema::create("service_status") else {
+            return;
+        };
+        let service = runtime().block_on(schema.fresh_service(4));
+
+        runtime().block_on(async {
+            service
+                .add(Request::new(sample_record(b"encrypted payload")))
+                .await
+                .unwrap();
+            // Added record is cached right away
+            get_payload(&service, false).await;
+            get_payload(&service, false).await;
+
+            let status = service
+                .service_status(Request::new(grpc::Empty {}))
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(
+                status,
+                grpc::ServiceStatus {
+                    uptime_seconds: status.uptime_seconds,
+                    record_count: 1,
+                    cached_records: 1,
+                    cache_capacity: 4,
+                    cache_hits: 2,
+                    cache_misses: 0,
+                    schema_version: test_db::latest_schema_version().to_owned(),
+                }
+            );
+        });
+    }
+
+    /// Attachment of `test.resource.com` with `content` pretending to be encrypted,
+    /// followed by a zeroed authentication tag.
+    fn sample_attachment(filename: &str, content: &[u8]) -> grpc::AddAttachmentRequest {
+        let mut encrypted_data = content.to_vec();
+        encrypted_data.resize(
+            content.len().saturating_add(telepass_crypto::AEAD_TAG_SIZE),
+            0,
+        );
+        grpc::AddAttachmentRequest {
+            resource: Some(grpc::Resource {
+                name: "test.resource.com".to_owned(),
+            }),
+            attachment: Some(grpc::Attachment {
+                filename: filename.to_owned(),
+                encrypted_data,
+                salt: vec![1; 12],
+                kdf_iterations: 10,
+                kdf_salt: vec![2; 16],
+                algorithm: 0,
+                key_commitment: Vec::new(),
+                size: u64::try_from(content.len()).unwrap(),
+                output_version: 0,
+            }),
+        }
+    }
+
+    async fn list_attachments(
+        service: &PasswordStorage,
+    ) -> Result<Vec<grpc::AttachmentInfo>, Status> {
+        Ok(service
+            .list_attachments(Request::new(grpc::Resource {
+                name: "test.resource.com".to_owned(),
+            }))
+            .await?
+            .into_inner()
+            .attachments)
+    }
+
+    async fn search_blind(service: &PasswordStorage, tokens: Vec<Vec<u8>>) -> Vec<String> {
+        service
+            .search_blind(Request::new(grpc::BlindTokens { tokens }))
+            .await
+            .unwrap()
+            .into_inner()
+            .resources
+            .into_iter()
+            .map(|resource| resource.name)
+            .collect()
+    }
+
+    async fn get_chunks(service: &PasswordStorage) -> Result<Vec<grpc::RecordChunk>, Status> {
+        let mut stream = service
+            .get_chunked(Request::new(grpc::GetRequest {
+                name: "test.resource.com".to_owned(),
+                bypass_cache: false,
+            }))
+            .await?
+            .into_inner();
+
+        let mut chunks = Vec::new();
+        while let Some(chunk) = tokio_stream::StreamExt::next(&mut stream).await {
+            chunks.push(chunk?);
+        }
+        Ok(chunks)
+    }
+
+    async fn list_resources(service: &PasswordStorage) -> Vec<grpc::Resource> {
+        service
+            .list(Request::new(grpc::Empty {}))
+            .await
+            .unwrap()
+            .into_inner()
+            .resources
+    }
+
+    async fn get_payload(service: &PasswordStorage, bypass_cache: bool) -> Vec<u8> {
+        get_record(service, bypass_cache).await.encrypted_payload
+    }
+
+    async fn get_record(service: &PasswordStorage, bypass_cache: bool) -> grpc::Record {
+        service
+            .get(Request::new(grpc::GetRequest {
+                name: "test.resource.com".to_owned(),
+                bypass_cache,
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+    }
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+    }
 }
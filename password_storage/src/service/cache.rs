@@ -5,7 +5,10 @@ use std::{
     borrow::Borrow,
     collections::BTreeSet,
     hash::{Hash, Hasher},
-    sync::RwLock,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        RwLock,
+    },
 };
 
 use tracing::info;
@@ -22,23 +25,60 @@ mod rated;
 pub struct Cache {
     /// Records cache for [`get`](crate::grpc::password_storage_server::PasswordStorage::get)
     /// request.
-    records: RwLock<rated::Set<ResourceOrientedRecord, String>>,
+    ///
+    /// [`None`] if records caching is disabled.
+    records: Option<RwLock<rated::Set<ResourceOrientedRecord, String>>>,
     /// Cache of sorted resources for
     /// [`list`](crate::grpc::password_storage_server::PasswordStorage::list) request.
     /// Always in actual state.
     resources: RwLock<BTreeSet<String>>,
+    /// Number of records found in the cache.
+    hits: AtomicU64,
+    /// Number of records not found in the cache.
+    misses: AtomicU64,
+}
+
+/// Statistics of the [`Cache`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of cached resources, which is the number of stored records.
+    pub resources: usize,
+    /// Number of cached records.
+    pub records: usize,
+    /// Maximum number of cached records, zero if records caching is disabled.
+    pub capacity: usize,
+    /// Number of records found in the cache.
+    pub hits: u64,
+    /// Number of records not found in the cache, including all requests if records caching is
+    /// disabled.
+    pub misses: u64,
 }
 
-/// Helper struct that implements `Borrow<&str>`.
+/// Helper struct that implements `Borrow<String>`.
 ///
-/// Behaves like [`Record::resource`] is the only field in the struct, which is useful
-/// for [`rated::Set`].
+/// Behaves like lowercase [`Record::resource_name`] is the only field in the struct, which is
+/// useful for [`rated::Set`] with case-insensitive lookups.
 #[derive(Debug)]
-struct ResourceOrientedRecord(Record);
+struct ResourceOrientedRecord {
+    /// Lowercase resource name.
+    key: String,
+    /// Cached record.
+    record: Record,
+}
+
+impl ResourceOrientedRecord {
+    /// Create new [`ResourceOrientedRecord`] keyed by lowercase resource name.
+    fn new(record: Record) -> Self {
+        Self {
+            key: record.resource_name.to_lowercase(),
+            record,
+        }
+    }
+}
 
 impl PartialEq for ResourceOrientedRecord {
     fn eq(&self, other: &Self) -> bool {
-        self.0.resource_name == other.0.resource_name
+        self.key == other.key
     }
 }
 
@@ -46,13 +86,13 @@ impl Eq for ResourceOrientedRecord {}
 
 impl Hash for ResourceOrientedRecord {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.0.resource_name.hash(state);
+        self.key.hash(state);
     }
 }
 
 impl Borrow<String> for ResourceOrientedRecord {
     fn borrow(&self) -> &String {
-        &self.0.resource_name
+        &self.key
     }
 }
 
@@ -85,28 +125,34 @@ macro_rules! write_or_panic {
 }
 
 impl Cache {
-    /// Creates new [`Cache`] instance with max size `size` and pre-loaded `records`.
+    /// Creates new [`Cache`] instance with max size `size`, all existing `resources` and
+    /// pre-loaded `records`.
     ///
     /// All records after `size - 1` index will be ignored.
+    /// Zero `size` disables records caching, but `resources` are still cached.
     #[expect(clippy::expect_used, reason = "u32 to usize conversion is safe")]
-    pub fn load(size: u32, records: impl IntoIterator<Item = Record>) -> Self {
+    pub fn load(
+        size: u32,
+        resources: impl IntoIterator<Item = String>,
+        records: impl IntoIterator<Item = Record>,
+    ) -> Self {
         let size = size
             .try_into()
             .expect("`u32` should always fit into `usize`");
 
-        let mut resources = BTreeSet::new();
-        let mut records_set = rated::Set::new(size);
-        for (n, record) in records.into_iter().enumerate() {
-            resources.insert(record.resource_name.clone());
-
-            if n < size {
-                records_set.insert(ResourceOrientedRecord(record));
+        let records = (size > 0).then(|| {
+            let mut records_set = rated::Set::new(size);
+            for record in records.into_iter().take(size) {
+                records_set.insert(ResourceOrientedRecord::new(record));
             }
-        }
+            RwLock::new(records_set)
+        });
 
         Self {
-            records: RwLock::new(records_set),
-            resources: RwLock::new(resources),
+            records,
+            resources: RwLock::new(resources.into_iter().collect()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
         }
     }
 
@@ -116,17 +162,17 @@ impl Cache {
             let mut resources_write = write_or_panic!(self.resources);
             resources_write.insert(record.resource_name.clone());
         }
-        {
-            let mut records_write = write_or_panic!(self.records);
-            records_write.insert(ResourceOrientedRecord(record));
+        if let Some(records) = self.records.as_ref() {
+            let mut records_write = write_or_panic!(records);
+            records_write.insert(ResourceOrientedRecord::new(record));
         }
     }
 
     /// Invalidate record by resource name.
-    pub fn invalidate(&self, resource_name: &String) {
-        {
-            let mut records_write = write_or_panic!(self.records);
-            records_write.remove(resource_name);
+    pub fn invalidate(&self, resource_name: &str) {
+        if let Some(records) = self.records.as_ref() {
+            let mut records_write = write_or_panic!(records);
+            records_write.remove(&resource_name.to_lowercase());
         }
         {
             let mut resources_write = write_or_panic!(self.resources);
@@ -135,20 +181,28 @@ impl Cache {
     }
 
     /// Get record by resource name or insert it using `f`, if not presented.
-    pub fn get_or_try_insert_with<F, E>(&self, resource_name: &String, f: F) -> Result<Record, E>
+    ///
+    /// Resource name is compared ignoring case.
+    /// Always calls `f` if records caching is disabled.
+    pub fn get_or_try_insert_with<F, E>(&self, resource_name: &str, f: F) -> Result<Record, E>
     where
         F: FnOnce() -> Result<Record, E>,
     {
-        let new_record = {
-            let mut records_write = write_or_panic!(self.records);
-            if let Some(record) = records_write.get(resource_name) {
+        let new_record = if let Some(records) = self.records.as_ref() {
+            let mut records_write = write_or_panic!(records);
+            if let Some(cached) = records_write.get(&resource_name.to_lowercase()) {
                 info!("Using cache");
-                return Ok(record.0.clone());
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(cached.record.clone());
             }
 
+            self.misses.fetch_add(1, Ordering::Relaxed);
             let new_record = f()?;
-            records_write.insert(ResourceOrientedRecord(new_record.clone()));
+            records_write.insert(ResourceOrientedRecord::new(new_record.clone()));
             new_record
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            f()?
         };
 
         write_or_panic!(self.resources).insert(new_record.resource_name.clone());
@@ -156,11 +210,36 @@ impl Cache {
         Ok(new_record)
     }
 
+    /// Find existing resource name equal to `resource_name` ignoring case.
+    pub fn find_resource(&self, resource_name: &str) -> Option<String> {
+        let resource_name = resource_name.to_lowercase();
+        read_or_panic!(self.resources)
+            .iter()
+            .find(|resource| resource.to_lowercase() == resource_name)
+            .cloned()
+    }
+
     /// Get all resources
     pub fn get_all_resources(&self) -> BTreeSet<String> {
         info!("Using cache");
         read_or_panic!(self.resources).clone()
     }
+
+    /// Get statistics of the cache.
+    pub fn stats(&self) -> Stats {
+        let (records, capacity) = self.records.as_ref().map_or((0, 0), |records| {
+            let records_read = read_or_panic!(records);
+            (records_read.len(), records_read.capacity())
+        });
+
+        Stats {
+            resources: read_or_panic!(self.resources).len(),
+            records,
+            capacity,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -172,7 +251,7 @@ mod tests {
 
     #[test]
     fn load_should_take_exact_size_records() {
-        let cache = Cache::load(3, create_records(5));
+        let cache = Cache::load(3, create_resources(5), create_records(5));
 
         let presented_record = cache
             .get_or_try_insert_with(
@@ -186,6 +265,15 @@ mod tests {
                 resource_name: String::from("Sample resource #2"),
                 encrypted_payload: b"some_secret_payload_2".to_vec(),
                 salt: b"some_salt_2".to_vec(),
+                kdf_iterations: None,
+                kdf_salt: None,
+                algorithm: 0,
+                bound_to_resource_name: false,
+                revision: 0,
+                chunk_count: 0,
+                payload_checksum: None,
+                key_commitment: None,
+                output_version: 0,
             }
         );
 
@@ -193,6 +281,15 @@ mod tests {
             resource_name: String::from("Sample sample"),
             encrypted_payload: b"sample".to_vec(),
             salt: b"sample".to_vec(),
+            kdf_iterations: None,
+            kdf_salt: None,
+            algorithm: 0,
+            bound_to_resource_name: false,
+            revision: 0,
+            chunk_count: 0,
+            payload_checksum: None,
+            key_commitment: None,
+            output_version: 0,
         };
         let not_presented_record = cache
             .get_or_try_insert_with(
@@ -205,27 +302,75 @@ mod tests {
 
     #[test]
     fn load_should_take_all_resource_names() {
-        let cache = Cache::load(3, create_records(10));
+        let cache = Cache::load(3, create_resources(10), create_records(3));
 
         let resources = cache.get_all_resources();
+        assert_eq!(resources, create_resources(10).into_iter().collect());
+    }
+
+    #[test]
+    fn zero_size_should_disable_records_caching() {
+        let cache = Cache::load(0, create_resources(3), create_records(3));
+
+        let sample_record = Record {
+            resource_name: String::from("Sample sample"),
+            encrypted_payload: b"sample".to_vec(),
+            salt: b"sample".to_vec(),
+            kdf_iterations: None,
+            kdf_salt: None,
+            algorithm: 0,
+            bound_to_resource_name: false,
+            revision: 0,
+            chunk_count: 0,
+            payload_checksum: None,
+            key_commitment: None,
+            output_version: 0,
+        };
+        cache.add(sample_record.clone());
+
+        for _ in 0..2_u8 {
+            let mut called = false;
+            let record = cache
+                .get_or_try_insert_with(
+                    &String::from("Sample sample"),
+                    || -> Result<_, Infallible> {
+                        called = true;
+                        Ok(sample_record.clone())
+                    },
+                )
+                .unwrap();
+            assert!(called);
+            assert_eq!(record, sample_record);
+        }
+
+        cache.invalidate("Sample resource #1");
         assert_eq!(
-            resources,
-            create_records(10)
+            cache.get_all_resources(),
+            ["Sample resource #0", "Sample resource #2", "Sample sample"]
                 .into_iter()
-                .map(|record| record.resource_name)
+                .map(String::from)
                 .collect()
         );
     }
 
     #[test]
     fn add_should_work() {
-        let cache = Cache::load(3, create_records(2));
+        let cache = Cache::load(3, create_resources(2), create_records(2));
 
         let resource_name = String::from("Sample sample");
         let sample_record = Record {
             resource_name: resource_name.clone(),
             encrypted_payload: b"sample".to_vec(),
             salt: b"sample".to_vec(),
+            kdf_iterations: None,
+            kdf_salt: None,
+            algorithm: 0,
+            bound_to_resource_name: false,
+            revision: 0,
+            chunk_count: 0,
+            payload_checksum: None,
+            key_commitment: None,
+            output_version: 0,
         };
         cache.add(sample_record.clone());
 
@@ -249,7 +394,7 @@ mod tests {
 
     #[test]
     fn add_should_replace_the_least_usable() {
-        let cache = Cache::load(3, create_records(3));
+        let cache = Cache::load(3, create_resources(3), create_records(3));
 
         // Increasing usage rate
         for i in (0..3_u32).chain(1..3) {
@@ -265,6 +410,15 @@ mod tests {
             resource_name: String::from("Sample sample"),
             encrypted_payload: b"sample".to_vec(),
             salt: b"sample".to_vec(),
+            kdf_iterations: None,
+            kdf_salt: None,
+            algorithm: 0,
+            bound_to_resource_name: false,
+            revision: 0,
+            chunk_count: 0,
+            payload_checksum: None,
+            key_commitment: None,
+            output_version: 0,
         };
         cache.add(sample_record);
 
@@ -283,7 +437,7 @@ mod tests {
 
     #[test]
     fn invalidate_should_work() {
-        let cache = Cache::load(3, create_records(3));
+        let cache = Cache::load(3, create_resources(3), create_records(3));
 
         let resource = String::from("Sample resource #1");
         cache.invalidate(&resource);
@@ -292,6 +446,15 @@ mod tests {
             resource_name: resource.clone(),
             encrypted_payload: b"new sample".to_vec(),
             salt: b"new sample".to_vec(),
+            kdf_iterations: None,
+            kdf_salt: None,
+            algorithm: 0,
+            bound_to_resource_name: false,
+            revision: 0,
+            chunk_count: 0,
+            payload_checksum: None,
+            key_commitment: None,
+            output_version: 0,
         };
         let new_record = cache
             .get_or_try_insert_with(&resource, || -> Result<_, Infallible> {
@@ -301,11 +464,118 @@ mod tests {
         assert_eq!(new_record, new_sample_record);
     }
 
+    #[test]
+    fn get_should_ignore_case() {
+        let cache = Cache::load(3, create_resources(3), create_records(3));
+
+        let record = cache
+            .get_or_try_insert_with(
+                &String::from("SAMPLE resource #1"),
+                || -> Result<_, Infallible> { panic!("Shouldn't be called") },
+            )
+            .unwrap();
+        assert_eq!(record.resource_name, "Sample resource #1");
+    }
+
+    #[test]
+    fn find_resource_should_return_canonical_name() {
+        let cache = Cache::load(3, create_resources(3), create_records(3));
+
+        assert_eq!(
+            cache.find_resource("sample RESOURCE #2"),
+            Some(String::from("Sample resource #2"))
+        );
+        assert_eq!(cache.find_resource("Sample resource #3"), None);
+    }
+
+    #[test]
+    fn invalidate_should_ignore_case_of_records() {
+        let cache = Cache::load(3, create_resources(3), create_records(3));
+
+        cache.invalidate(&String::from("sample resource #1"));
+
+        let mut called = false;
+        cache
+            .get_or_try_insert_with(
+                &String::from("Sample resource #1"),
+                || -> Result<_, Infallible> {
+                    called = true;
+                    Ok(create_records(2).into_iter().nth(1).unwrap())
+                },
+            )
+            .unwrap();
+        assert!(called);
+    }
+
+    #[test]
+    fn stats_should_count_hits_and_misses() {
+        let cache = Cache::load(3, create_resources(5), create_records(2));
+        let get = |resource_name: &str| {
+            cache
+                .get_or_try_insert_with(resource_name, || -> Result<_, Infallible> {
+                    Ok(create_records(5).into_iter().nth(4).unwrap())
+                })
+                .unwrap()
+        };
+
+        get("Sample resource #0");
+        get("Sample resource #1");
+        get("Sample resource #4");
+
+        assert_eq!(
+            cache.stats(),
+            Stats {
+                resources: 5,
+                records: 3,
+                capacity: 3,
+                hits: 2,
+                misses: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn stats_should_count_misses_with_disabled_records_caching() {
+        let cache = Cache::load(0, create_resources(2), create_records(2));
+
+        cache
+            .get_or_try_insert_with("Sample resource #0", || -> Result<_, Infallible> {
+                Ok(create_records(1).into_iter().next().unwrap())
+            })
+            .unwrap();
+
+        assert_eq!(
+            cache.stats(),
+            Stats {
+                resources: 2,
+                records: 0,
+                capacity: 0,
+                hits: 0,
+                misses: 1,
+            }
+        );
+    }
+
+    fn create_resources(n: usize) -> impl IntoIterator<Item = String> {
+        create_records(n)
+            .into_iter()
+            .map(|record| record.resource_name)
+    }
+
     fn create_records(n: usize) -> impl IntoIterator<Item = Record> {
         (0..n).map(|i| Record {
             resource_name: format!("Sample resource #{i}"),
             encrypted_payload: format!("some_secret_payload_{i}").into_bytes(),
             salt: format!("some_salt_{i}").into_bytes(),
+            kdf_iterations: None,
+            kdf_salt: None,
+            algorithm: 0,
+            bound_to_resource_name: false,
+            revision: 0,
+            chunk_count: 0,
+            payload_checksum: None,
+            key_commitment: None,
+            output_version: 0,
         })
     }
 }
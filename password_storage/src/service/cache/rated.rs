@@ -88,6 +88,16 @@ impl<V: Borrow<Q> + Eq + Hash, Q: Clone + Eq + Hash> Set<V, Q> {
         }
     }
 
+    /// Get number of values in the set.
+    pub fn len(&self) -> usize {
+        self.internal.len()
+    }
+
+    /// Get maximum number of values to store.
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
     /// Insert `value` into the set. Returns previous value if it was present.
     ///
     /// # Complexity
@@ -0,0 +1,559 @@
+//! Property-based tests driving [`PasswordStorage`] with arbitrary requests.
+//!
+//! Tests need a test database and are skipped if it's not specified, see [`TestSchema`].
+
+#![expect(clippy::unwrap_used, clippy::panic, reason = "it's ok in tests")]
+
+use std::collections::HashMap;
+
+use proptest::{
+    collection::vec,
+    prelude::*,
+    test_runner::{Config, TestRunner},
+};
+use prost::Message as _;
+use tonic::Request;
+
+use super::{test_db::TestSchema, *};
+use crate::grpc::password_storage_server::PasswordStorage as _;
+
+/// Number of cases for each property, kept small to bound CI time.
+const CASES: u32 = 32;
+
+/// Cache size small enough to trigger evictions.
+const CACHE_SIZE: u32 = 4;
+
+/// Status codes which are expected to be returned for malformed requests.
+const EXPECTED_CODES: [Code; 3] = [Code::InvalidArgument, Code::AlreadyExists, Code::NotFound];
+
+/// Request to the service.
+#[derive(Debug, Clone)]
+enum Op {
+    /// `add` request, `resource` is missing if `name` is [`None`].
+    Add {
+        name: Option<String>,
+        encrypted_payload: Vec<u8>,
+        salt: Vec<u8>,
+    },
+    /// `get` request.
+    Get(String),
+    /// `delete` request.
+    Delete(String),
+    /// `search` request.
+    Search(String),
+    /// `list` request.
+    List,
+    /// `add` request decoded from raw bytes.
+    RawAdd(Vec<u8>),
+    /// `get` request decoded from raw bytes.
+    RawGet(Vec<u8>),
+}
+
+/// Strategy for arbitrary resource names.
+fn any_name() -> impl Strategy<Value = String> + Clone {
+    prop_oneof![
+        // Small pool of names to get collisions
+        prop::sample::select(vec!["a", "A", "b.com", "B.COM", "%", "_", "\\", ""])
+            .prop_map(ToOwned::to_owned),
+        "[a-zA-Z0-9._%\\\\-]{0,12}",
+        any::<String>(),
+        // Names around the column length limit
+        (250_usize..260).prop_map(|len| "n".repeat(len)),
+    ]
+}
+
+/// Strategy for ASCII resource names which are likely to collide.
+fn ascii_name() -> impl Strategy<Value = String> + Clone {
+    prop_oneof![
+        prop::sample::select(vec!["a", "A", "b.com", "B.COM", "b", "%", "_"])
+            .prop_map(ToOwned::to_owned),
+        "[a-cA-C._%]{0,4}",
+    ]
+}
+
+/// Strategy for requests with names produced by `name`.
+fn op(name: impl Strategy<Value = String> + Clone + 'static) -> impl Strategy<Value = Op> {
+    prop_oneof![
+        4 => (
+            prop::option::weighted(0.95, name.clone()),
+            vec(any::<u8>(), 0..4096),
+            prop_oneof![
+                3 => vec(any::<u8>(), telepass_crypto::AES_256_GCM_SALT_SIZE),
+                1 => vec(any::<u8>(), 0..64),
+            ],
+        )
+            .prop_map(|(maybe_name, encrypted_payload, salt)| Op::Add {
+                name: maybe_name,
+                encrypted_payload,
+                salt,
+            }),
+        2 => name.clone().prop_map(Op::Get),
+        2 => name.clone().prop_map(Op::Delete),
+        1 => name.prop_map(Op::Search),
+        1 => Just(Op::List),
+        1 => vec(any::<u8>(), 0..128).prop_map(Op::RawAdd),
+        1 => vec(any::<u8>(), 0..128).prop_map(Op::RawGet),
+    ]
+}
+
+/// Check if `op` adds a record which couldn't be produced by the default algorithm.
+#[expect(
+    clippy::ref_patterns,
+    reason = "conflicts with `pattern_type_mismatch`"
+)]
+fn has_malformed_ciphertext(op: &Op) -> bool {
+    matches!(*op, Op::Add { ref encrypted_payload, ref salt, .. }
+        if encrypted_payload.len() < telepass_crypto::AEAD_TAG_SIZE
+            || salt.len() != telepass_crypto::AES_256_GCM_SALT_SIZE)
+}
+
+/// Outcome of a request to the service.
+type Outcome = Result<OpResponse, Status>;
+
+/// Successful response to a request.
+#[derive(Debug, PartialEq)]
+enum OpResponse {
+    /// Empty response.
+    Empty,
+    /// Single record.
+    Record(grpc::Record),
+    /// List of resource names.
+    Resources(Vec<String>),
+}
+
+impl OpResponse {
+    /// Construct [`OpResponse::Record`] without revision, which is assigned by the database
+    /// and can't be predicted by the model.
+    fn record(record: grpc::Record) -> Self {
+        Self::Record(grpc::Record {
+            revision: 0,
+            ..record
+        })
+    }
+}
+
+/// Perform `op` on `service`.
+///
+/// Returns [`None`] if raw request can't be decoded, like `tonic` would reject it.
+async fn perform(service: &PasswordStorage, op: Op) -> Option<Outcome> {
+    let resource_names = |resources: Vec<grpc::Resource>| {
+        OpResponse::Resources(
+            resources
+                .into_iter()
+                .map(|resource| resource.name)
+                .collect(),
+        )
+    };
+
+    Some(match op {
+        Op::Add {
+            name,
+            encrypted_payload,
+            salt,
+        } => service
+            .add(Request::new(grpc::AddRequest {
+                resource: name.map(|resource_name| grpc::Resource {
+                    name: resource_name,
+                }),
+                encrypted_payload,
+                salt,
+                idempotency_key: String::new(),
+                blind_index: Vec::new(),
+                kdf_iterations: 0,
+                kdf_salt: Vec::new(),
+                algorithm: 0,
+                bound_to_resource_name: false,
+                password_fingerprint: Vec::new(),
+                key_commitment: Vec::new(),
+                output_version: 0,
+                blind_index_version: 0,
+            }))
+            .await
+            .map(|_response| OpResponse::Empty),
+        Op::Get(name) => service
+            .get(Request::new(grpc::GetRequest {
+                name,
+                bypass_cache: false,
+            }))
+            .await
+            .map(|response| OpResponse::record(response.into_inner())),
+        Op::Delete(name) => service
+            .delete(Request::new(grpc::DeleteRequest {
+                name,
+                expected_revision: 0,
+            }))
+            .await
+            .map(|_response| OpResponse::Empty),
+        Op::Search(name) => service
+            .search(Request::new(grpc::Resource { name }))
+            .await
+            .map(|response| resource_names(response.into_inner().resources)),
+        Op::List => service
+            .list(Request::new(grpc::Empty {}))
+            .await
+            .map(|response| resource_names(response.into_inner().resources)),
+        Op::RawAdd(bytes) => service
+            .add(Request::new(
+                grpc::AddRequest::decode(bytes.as_slice()).ok()?,
+            ))
+            .await
+            .map(|_response| OpResponse::Empty),
+        Op::RawGet(bytes) => service
+            .get(Request::new(
+                grpc::GetRequest::decode(bytes.as_slice()).ok()?,
+            ))
+            .await
+            .map(|response| OpResponse::record(response.into_inner())),
+    })
+}
+
+/// Check that a valid record can still be added, retrieved and deleted.
+async fn assert_service_is_operational(service: &PasswordStorage) {
+    let record = grpc::Record {
+        resource: Some(grpc::Resource {
+            name: "valid.resource.after.fuzzing".to_owned(),
+        }),
+        encrypted_payload: b"encrypted payload".to_vec(),
+        salt: vec![1; telepass_crypto::AES_256_GCM_SALT_SIZE],
+        kdf_iterations: 0,
+        kdf_salt: Vec::new(),
+        algorithm: 0,
+        bound_to_resource_name: false,
+        revision: 0,
+        chunk_count: 0,
+        key_commitment: Vec::new(),
+        output_version: 0,
+    };
+    let resource = record.resource.clone().unwrap();
+
+    service
+        .add(Request::new(grpc::AddRequest {
+            resource: record.resource.clone(),
+            encrypted_payload: record.encrypted_payload.clone(),
+            salt: record.salt.clone(),
+            idempotency_key: String::new(),
+            blind_index: Vec::new(),
+            kdf_iterations: 0,
+            kdf_salt: Vec::new(),
+            algorithm: 0,
+            bound_to_resource_name: false,
+            password_fingerprint: Vec::new(),
+            key_commitment: Vec::new(),
+            output_version: 0,
+            blind_index_version: 0,
+        }))
+        .await
+        .unwrap();
+    let got = service
+        .get(Request::new(grpc::GetRequest {
+            name: resource.name.clone(),
+            bypass_cache: false,
+        }))
+        .await
+        .unwrap();
+    assert_eq!(
+        OpResponse::record(got.into_inner()),
+        OpResponse::Record(record)
+    );
+    service
+        .delete(Request::new(grpc::DeleteRequest {
+            name: resource.name,
+            expected_revision: 0,
+        }))
+        .await
+        .unwrap();
+}
+
+/// Assert that `outcome` is either success or an expected error.
+fn assert_proper_outcome(op: &Op, outcome: &Outcome) {
+    if let Err(status) = outcome.as_ref() {
+        assert!(
+            EXPECTED_CODES.contains(&status.code()),
+            "Unexpected status for {op:?}: {status:?}"
+        );
+    }
+}
+
+/// Run `property` over `CASES` sequences of requests produced by `op_strategy`.
+fn run_property<S, F>(test_name: &str, op_strategy: S, property: F)
+where
+    S: Strategy<Value = Op>,
+    F: Fn(&PasswordStorage, Vec<Op>),
+{
+    let Some(schema) = TestSchema::create(test_name) else {
+        return;
+    };
+
+    let mut runner = TestRunner::new(Config {
+        cases: CASES,
+        failure_persistence: None,
+        ..Config::default()
+    });
+    runner
+        .run(&vec(op_strategy, 1..24), |ops| {
+            property(&runtime().block_on(schema.fresh_service(CACHE_SIZE)), ops);
+            Ok(())
+        })
+        .unwrap();
+}
+
+/// Build a runtime to call async service methods.
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn arbitrary_requests_return_proper_statuses() {
+    let runtime = runtime();
+
+    run_property("statuses", op(any_name()), |service, ops| {
+        runtime.block_on(async {
+            for op in ops {
+                if let Some(outcome) = perform(service, op.clone()).await {
+                    assert_proper_outcome(&op, &outcome);
+                }
+            }
+            assert_service_is_operational(service).await;
+        });
+    });
+}
+
+#[test]
+fn service_matches_model() {
+    let runtime = runtime();
+
+    run_property("model", op(ascii_name()), |service, ops| {
+        runtime.block_on(async {
+            // Records by lowercase resource names
+            let mut model: HashMap<String, grpc::Record> = HashMap::new();
+
+            for op in ops {
+                let Some(outcome) = perform(service, op.clone()).await else {
+                    continue;
+                };
+                assert_proper_outcome(&op, &outcome);
+                if has_malformed_ciphertext(&op) {
+                    assert_eq!(outcome.unwrap_err().code(), Code::InvalidArgument);
+                    continue;
+                }
+
+                match (op.clone(), outcome) {
+                    (
+                        Op::Add {
+                            name: Some(name),
+                            encrypted_payload,
+                            salt,
+                        },
+                        Ok(_),
+                    ) => {
+                        let previous = model.insert(
+                            name.to_lowercase(),
+                            grpc::Record {
+                                resource: Some(grpc::Resource { name }),
+                                encrypted_payload,
+                                salt,
+                                kdf_iterations: 0,
+                                kdf_salt: Vec::new(),
+                                algorithm: 0,
+                                bound_to_resource_name: false,
+                                revision: 0,
+                                chunk_count: 0,
+                                key_commitment: Vec::new(),
+                                output_version: 0,
+                            },
+                        );
+                        assert!(previous.is_none(), "{op:?} added resource twice");
+                    }
+                    (
+                        Op::Add {
+                            name: Some(name), ..
+                        },
+                        Err(status),
+                    ) => {
+                        assert_eq!(status.code(), Code::AlreadyExists, "{op:?}");
+                        assert!(model.contains_key(&name.to_lowercase()), "{op:?}");
+                    }
+                    (Op::Add { name: None, .. }, outcome) => {
+                        assert_eq!(outcome.unwrap_err().code(), Code::InvalidArgument);
+                    }
+                    (Op::Get(name), outcome) => {
+                        assert_eq!(
+                            outcome.ok(),
+                            model
+                                .get(&name.to_lowercase())
+                                .cloned()
+                                .map(OpResponse::Record),
+                            "{op:?}"
+                        );
+                    }
+                    (Op::Delete(name), outcome) => {
+                        let key = name.to_lowercase();
+                        let exact_match = model
+                            .get(&key)
+                            .is_some_and(|record| record.resource.as_ref().unwrap().name == name);
+                        assert_eq!(outcome.is_ok(), exact_match, "{op:?}");
+                        if exact_match {
+                            model.remove(&key);
+                        }
+                    }
+                    (Op::Search(query), outcome) => {
+                        let OpResponse::Resources(mut found) = outcome.unwrap() else {
+                            panic!("Expected resources for {op:?}");
+                        };
+                        found.sort();
+                        let mut expected: Vec<_> = model
+                            .values()
+                            .map(|record| record.resource.clone().unwrap().name)
+                            .filter(|name| name.to_lowercase().contains(&query.to_lowercase()))
+                            .collect();
+                        expected.sort();
+                        assert_eq!(found, expected, "{op:?}");
+                    }
+                    (Op::List, outcome) => {
+                        let OpResponse::Resources(listed) = outcome.unwrap() else {
+                            panic!("Expected resources for {op:?}");
+                        };
+                        for name in listed {
+                            assert!(model.contains_key(&name.to_lowercase()), "{name} is listed");
+                        }
+                    }
+                    (Op::RawAdd(_) | Op::RawGet(_), _outcome) => {}
+                }
+            }
+
+            assert_service_is_operational(service).await;
+        });
+    });
+}
+
+/// Fixed corpus of inputs which once were or still are interesting.
+fn regression_corpus() -> Vec<(Op, Option<Code>)> {
+    let add = |name: &str, encrypted_payload: Vec<u8>, salt: Vec<u8>| Op::Add {
+        name: Some(name.to_owned()),
+        encrypted_payload,
+        salt,
+    };
+
+    let payload = || b"encrypted payload".to_vec();
+    let salt = || vec![1; telepass_crypto::AES_256_GCM_SALT_SIZE];
+
+    vec![
+        // Empty name is a valid name
+        (add("", payload(), salt()), None),
+        (Op::Get(String::new()), None),
+        (Op::Delete(String::new()), None),
+        // Missing resource
+        (
+            Op::Add {
+                name: None,
+                encrypted_payload: Vec::new(),
+                salt: Vec::new(),
+            },
+            Some(Code::InvalidArgument),
+        ),
+        // Zero-length salt and payload
+        (
+            add("empty.salt", Vec::new(), Vec::new()),
+            Some(Code::InvalidArgument),
+        ),
+        // Salt of another algorithm
+        (
+            add(
+                "wrong.salt",
+                payload(),
+                vec![1; telepass_crypto::XCHACHA20_POLY1305_SALT_SIZE],
+            ),
+            Some(Code::InvalidArgument),
+        ),
+        // Payload shorter than the authentication tag
+        (
+            add(
+                "truncated.payload",
+                vec![0xAB; telepass_crypto::AEAD_TAG_SIZE - 1],
+                salt(),
+            ),
+            Some(Code::InvalidArgument),
+        ),
+        (
+            add(
+                "empty.plaintext",
+                vec![0xAB; telepass_crypto::AEAD_TAG_SIZE],
+                salt(),
+            ),
+            None,
+        ),
+        // Giant payload
+        (add("giant.payload", vec![0xAB; 1024 * 1024], salt()), None),
+        // NUL character is not accepted by Postgres
+        (
+            add("nul\0name", payload(), salt()),
+            Some(Code::InvalidArgument),
+        ),
+        (Op::Get("nul\0name".to_owned()), Some(Code::InvalidArgument)),
+        (
+            Op::Delete("nul\0name".to_owned()),
+            Some(Code::InvalidArgument),
+        ),
+        (Op::Search("\0".to_owned()), Some(Code::InvalidArgument)),
+        // Name longer than the column
+        (
+            add(&"n".repeat(256), payload(), salt()),
+            Some(Code::InvalidArgument),
+        ),
+        (Op::Get("n".repeat(256)), Some(Code::InvalidArgument)),
+        (add(&"n".repeat(255), payload(), salt()), None),
+        // `LIKE` special characters
+        (Op::Search("%".to_owned()), None),
+        (Op::Search("_".to_owned()), None),
+        (Op::Search("\\".to_owned()), None),
+        // Case-insensitive collision
+        (add("Case.Resource", payload(), salt()), None),
+        (
+            add("case.resource", payload(), salt()),
+            Some(Code::AlreadyExists),
+        ),
+        (Op::Delete("CASE.RESOURCE".to_owned()), Some(Code::NotFound)),
+        (Op::Delete("Case.Resource".to_owned()), None),
+        // Non-ASCII names
+        (add("\u{43a}\u{43b}\u{44e}\u{447}", payload(), salt()), None),
+        (Op::Get("\u{43a}\u{43b}\u{44e}\u{447}".to_owned()), None),
+        // Invalid UTF-8 in the resource name is rejected while decoding:
+        // field 1 (resource), length 3, field 1 (name), length 1, invalid byte
+        (Op::RawAdd(vec![0x0A, 0x03, 0x0A, 0x01, 0xFF]), None),
+        (Op::RawGet(vec![0x0A, 0x01, 0xFF]), None),
+    ]
+}
+
+#[test]
+fn regression_corpus_returns_expected_statuses() {
+    let Some(schema) = TestSchema::create("corpus") else {
+        return;
+    };
+    let service = runtime().block_on(schema.fresh_service(CACHE_SIZE));
+
+    runtime().block_on(async {
+        for (op, expected_code) in regression_corpus() {
+            let Some(outcome) = perform(&service, op.clone()).await else {
+                assert!(
+                    matches!(op, Op::RawAdd(_) | Op::RawGet(_)),
+                    "Only raw requests can be rejected while decoding"
+                );
+                continue;
+            };
+            assert_eq!(
+                outcome.as_ref().err().map(Status::code),
+                expected_code,
+                "Unexpected outcome for {op:?}: {outcome:?}"
+            );
+        }
+        assert_service_is_operational(&service).await;
+    });
+}
+
+#[test]
+fn invalid_utf8_names_are_not_decoded() {
+    grpc::GetRequest::decode([0x0A, 0x01, 0xFF].as_slice()).unwrap_err();
+    grpc::Record::decode([0x0A, 0x03, 0x0A, 0x01, 0xFF].as_slice()).unwrap_err();
+}
@@ -0,0 +1,211 @@
+//! Module with [`TestSchema`] to run [`PasswordStorage`] tests against a real database.
+//!
+//! Tests need a Postgres database specified by `TEST_DATABASE_URL` environment variable and are
+//! skipped if it's not set. Every test works in its own schema which is dropped afterwards.
+//!
+//! Public with `test-db` feature to be used by benchmarks and integration tests.
+//! All functions panic on database errors.
+
+#![expect(
+    clippy::unwrap_used,
+    clippy::unwrap_in_result,
+    clippy::missing_panics_doc,
+    reason = "it's ok in tests"
+)]
+
+use std::future::Future;
+
+use diesel::{connection::SimpleConnection as _, Connection as _, PgConnection};
+
+use super::{Config, PasswordStorage};
+
+/// Environment variable with url of the database to run tests in.
+const TEST_DATABASE_URL_ENV_VAR: &str = "TEST_DATABASE_URL";
+
+/// Schema migrations with their `diesel` versions, applied in order.
+const MIGRATIONS: [(&str, &str); 15] = [
+    (
+        "00000000000000",
+        include_str!("../../migrations/00000000000000_diesel_initial_setup/up.sql"),
+    ),
+    (
+        "20230223185718",
+        include_str!("../../migrations/2023-02-23-185718_create_passwords/up.sql"),
+    ),
+    (
+        "20261018120000",
+        include_str!("../../migrations/2026-10-18-120000_unique_lower_resource_name/up.sql"),
+    ),
+    (
+        "20261018130000",
+        include_str!("../../migrations/2026-10-18-130000_create_idempotency_keys/up.sql"),
+    ),
+    (
+        "20261018140000",
+        include_str!("../../migrations/2026-10-18-140000_create_blind_index/up.sql"),
+    ),
+    (
+        "20261018150000",
+        include_str!("../../migrations/2026-10-18-150000_add_kdf_iterations/up.sql"),
+    ),
+    (
+        "20261018160000",
+        include_str!("../../migrations/2026-10-18-160000_add_kdf_salt/up.sql"),
+    ),
+    (
+        "20261018170000",
+        include_str!("../../migrations/2026-10-18-170000_add_algorithm/up.sql"),
+    ),
+    (
+        "20261018180000",
+        include_str!("../../migrations/2026-10-18-180000_add_bound_resource_name/up.sql"),
+    ),
+    (
+        "20261018190000",
+        include_str!("../../migrations/2026-10-18-190000_create_password_fingerprints/up.sql"),
+    ),
+    (
+        "20261018200000",
+        include_str!("../../migrations/2026-10-18-200000_add_revision/up.sql"),
+    ),
+    (
+        "20261018210000",
+        include_str!("../../migrations/2026-10-18-210000_create_payload_chunks/up.sql"),
+    ),
+    (
+        "20261018220000",
+        include_str!("../../migrations/2026-10-18-220000_add_key_commitment/up.sql"),
+    ),
+    (
+        "20261018230000",
+        include_str!("../../migrations/2026-10-18-230000_create_attachments/up.sql"),
+    ),
+    (
+        "20261018235000",
+        include_str!("../../migrations/2026-10-18-235000_add_output_version/up.sql"),
+    ),
+];
+
+/// Database schema existing during the test.
+pub struct TestSchema {
+    /// Connection used to create and drop the schema.
+    connection: PgConnection,
+    /// Schema name.
+    name: String,
+    /// Database url with the schema set as the search path.
+    url: String,
+}
+
+impl TestSchema {
+    /// Create a new schema with applied migrations.
+    ///
+    /// Returns [`None`] if test database is not specified.
+    #[expect(clippy::print_stderr, reason = "to notify about skipped test")]
+    #[must_use]
+    pub fn create(test_name: &str) -> Option<Self> {
+        let Ok(database_url) = std::env::var(TEST_DATABASE_URL_ENV_VAR) else {
+            eprintln!("`{TEST_DATABASE_URL_ENV_VAR}` is not set, skipping `{test_name}`");
+            return None;
+        };
+
+        let name = format!("test_{test_name}_{}", std::process::id());
+        let separator = if database_url.contains('?') { '&' } else { '?' };
+        let url = format!("{database_url}{separator}options=-csearch_path%3D{name}");
+
+        let mut connection = PgConnection::establish(&database_url).unwrap();
+        connection
+            .batch_execute(&format!(
+                "DROP SCHEMA IF EXISTS {name} CASCADE; CREATE SCHEMA {name};"
+            ))
+            .unwrap();
+
+        let mut schema_connection = PgConnection::establish(&url).unwrap();
+        schema_connection
+            .batch_execute(
+                "CREATE TABLE __diesel_schema_migrations (\
+                     version VARCHAR(50) PRIMARY KEY NOT NULL, \
+                     run_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP\
+                 );",
+            )
+            .unwrap();
+        for (version, migration) in MIGRATIONS {
+            schema_connection.batch_execute(migration).unwrap();
+            schema_connection
+                .batch_execute(&format!(
+                    "INSERT INTO __diesel_schema_migrations (version) VALUES ('{version}');"
+                ))
+                .unwrap();
+        }
+
+        Some(Self {
+            connection,
+            name,
+            url,
+        })
+    }
+
+    /// Open a new connection to the schema bypassing the service.
+    #[must_use]
+    pub fn connection(&self) -> PgConnection {
+        PgConnection::establish(&self.url).unwrap()
+    }
+
+    /// Execute `sql` in the schema bypassing the service.
+    pub fn execute(&self, sql: &str) {
+        self.connection().batch_execute(sql).unwrap();
+    }
+
+    /// Insert `count` records named by [`seeded_resource_name()`] bypassing the service.
+    ///
+    /// Services created before seeding don't see the records in their cache.
+    pub fn seed(&self, count: u32) {
+        self.execute(&format!(
+            "INSERT INTO passwords (resource_name, encrypted_payload, salt) \
+             SELECT 'resource-' || i || '.com', 'payload', 'salt' \
+             FROM generate_series(0, {count} - 1) AS i;"
+        ));
+    }
+
+    /// Construct service configuration with `cache_size` to use the schema.
+    #[must_use]
+    pub fn config(&self, cache_size: u32) -> Config {
+        Config {
+            database_url: self.url.clone(),
+            cache_size,
+        }
+    }
+
+    /// Create a new service with `cache_size` on top of the current `passwords` table.
+    pub fn service(&self, cache_size: u32) -> impl Future<Output = PasswordStorage> + Send {
+        let config = self.config(cache_size);
+        async move { PasswordStorage::connect(config).await.unwrap() }
+    }
+
+    /// Create a new service with `cache_size` on top of empty tables.
+    pub fn fresh_service(&self, cache_size: u32) -> impl Future<Output = PasswordStorage> + Send {
+        self.execute("TRUNCATE passwords, idempotency_keys, blind_index, password_fingerprints, payload_chunks, attachments;");
+        self.service(cache_size)
+    }
+}
+
+/// Version of the latest migration applied by [`TestSchema::create()`].
+#[must_use]
+pub fn latest_schema_version() -> &'static str {
+    MIGRATIONS
+        .last()
+        .map_or("", |&(version, _migration)| version)
+}
+
+/// Name of the `index`-th record inserted by [`TestSchema::seed()`].
+#[must_use]
+pub fn seeded_resource_name(index: u32) -> String {
+    format!("resource-{index}.com")
+}
+
+impl Drop for TestSchema {
+    fn drop(&mut self) {
+        let _ignored = self
+            .connection
+            .batch_execute(&format!("DROP SCHEMA IF EXISTS {} CASCADE;", self.name));
+    }
+}
@@ -0,0 +1,17 @@
+//! Module with TLS configuration of the password storage server.
+
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+
+/// Prepare server TLS configuration requiring clients to authenticate.
+///
+/// Accepts PEM-encoded server certificate, server private key and certificate of the CA
+/// issuing client certificates.
+pub fn server_config(
+    cert: impl AsRef<[u8]>,
+    key: impl AsRef<[u8]>,
+    client_ca_cert: impl AsRef<[u8]>,
+) -> ServerTlsConfig {
+    ServerTlsConfig::new()
+        .identity(Identity::from_pem(cert, key))
+        .client_ca_root(Certificate::from_pem(client_ca_cert))
+}
@@ -0,0 +1,167 @@
+//! Mutual TLS tests between `password_storage` server and `telegram_gate` client.
+//!
+//! Certificates are generated at test time, so only a database specified by
+//! `TEST_DATABASE_URL` environment variable is required.
+
+#![expect(
+    clippy::tests_outside_test_module,
+    clippy::unwrap_used,
+    reason = "integration tests"
+)]
+
+use rcgen::{
+    BasicConstraints, Certificate, CertificateParams, DnType, ExtendedKeyUsagePurpose, IsCa,
+    KeyPair,
+};
+use telepass_password_storage::{
+    grpc::password_storage_server::PasswordStorageServer,
+    service::{test_db::TestSchema, PasswordStorage},
+};
+use telepass_telegram_gate::grpc::{
+    password_storage_client::PasswordStorageClient, AddRequest, GetRequest, Record, Resource,
+};
+use tonic::transport::{server::TcpIncoming, Channel, ClientTlsConfig, Server};
+
+/// Certificate authority issuing test certificates.
+struct Ca {
+    /// Self-signed CA certificate.
+    cert: Certificate,
+    /// CA private key.
+    key: KeyPair,
+}
+
+impl Ca {
+    /// Generate a new self-signed CA.
+    fn new(name: &str) -> Self {
+        let mut params = CertificateParams::default();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params.distinguished_name.push(DnType::CommonName, name);
+
+        let key = KeyPair::generate().unwrap();
+        let cert = params.self_signed(&key).unwrap();
+        Self { cert, key }
+    }
+
+    /// Issue PEM-encoded certificate and private key for `domain_name` with `usage`.
+    fn issue(&self, domain_name: &str, usage: ExtendedKeyUsagePurpose) -> (String, String) {
+        let mut params = CertificateParams::new([domain_name.to_owned()]).unwrap();
+        params.extended_key_usages = vec![usage];
+
+        let key = KeyPair::generate().unwrap();
+        let cert = params.signed_by(&key, &self.cert, &self.key).unwrap();
+        (cert.pem(), key.serialize_pem())
+    }
+
+    /// Issue client configuration to connect to the server signed by this CA.
+    fn client_config(&self, server_ca: &Self) -> ClientTlsConfig {
+        let (cert, key) = self.issue("telegram_gate", ExtendedKeyUsagePurpose::ClientAuth);
+        telepass_telegram_gate::tls::client_config(cert, key, server_ca.cert.pem())
+    }
+}
+
+/// Start `service` with certificate issued by `ca` and accepting clients of `ca` in background.
+///
+/// Returns server url.
+async fn start_server(service: PasswordStorage, ca: &Ca) -> String {
+    let (cert, key) = ca.issue(
+        telepass_telegram_gate::tls::PASSWORD_STORAGE_DOMAIN_NAME,
+        ExtendedKeyUsagePurpose::ServerAuth,
+    );
+    let tls_config = telepass_password_storage::tls::server_config(cert, key, ca.cert.pem());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("https://{}", listener.local_addr().unwrap());
+    let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+
+    let server = Server::builder()
+        .tls_config(tls_config)
+        .unwrap()
+        .add_service(PasswordStorageServer::new(service))
+        .serve_with_incoming(incoming);
+    tokio::spawn(server);
+
+    url
+}
+
+/// Connect to the server at `url` with `tls_config`.
+async fn connect(
+    url: String,
+    tls_config: ClientTlsConfig,
+) -> Result<PasswordStorageClient<Channel>, tonic::transport::Error> {
+    let channel = Channel::from_shared(url)
+        .unwrap()
+        .tls_config(tls_config)?
+        .connect()
+        .await?;
+    Ok(PasswordStorageClient::new(channel))
+}
+
+#[tokio::test]
+async fn client_with_certificate_of_the_same_ca_round_trips_record() {
+    let Some(schema) = TestSchema::create("tls_round_trip") else {
+        return;
+    };
+    let ca = Ca::new("Telepass Test CA");
+    let url = start_server(schema.fresh_service(0).await, &ca).await;
+
+    let mut client = connect(url, ca.client_config(&ca)).await.unwrap();
+    let record = Record {
+        resource: Some(Resource {
+            name: "example.com".to_owned(),
+        }),
+        encrypted_payload: b"encrypted payload".to_vec(),
+        salt: vec![1; 12],
+        kdf_iterations: 200_000,
+        kdf_salt: Vec::new(),
+        algorithm: 0,
+        bound_to_resource_name: false,
+        revision: 0,
+        chunk_count: 0,
+        key_commitment: Vec::new(),
+        output_version: 0,
+    };
+    client
+        .add(AddRequest::new(record.clone(), "key".to_owned()))
+        .await
+        .unwrap();
+
+    let received = client
+        .get(GetRequest {
+            name: "example.com".to_owned(),
+            bypass_cache: false,
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    drop(client);
+    assert_ne!(received.revision, 0);
+    assert_eq!(
+        Record {
+            revision: 0,
+            ..received
+        },
+        record
+    );
+}
+
+#[tokio::test]
+async fn client_with_certificate_of_another_ca_is_rejected() {
+    let Some(schema) = TestSchema::create("tls_another_ca") else {
+        return;
+    };
+    let ca = Ca::new("Telepass Test CA");
+    let url = start_server(schema.fresh_service(0).await, &ca).await;
+
+    let another_ca = Ca::new("Another Test CA");
+    // With TLS 1.3 client certificate is verified after the handshake,
+    // so connection may succeed and the first request fails instead
+    let response = async {
+        let mut client = connect(url, another_ca.client_config(&ca)).await.ok()?;
+        client
+            .list(telepass_telegram_gate::grpc::Empty {})
+            .await
+            .ok()
+    }
+    .await;
+    assert!(response.is_none());
+}
@@ -1,15 +1,151 @@
 //! Build script to build the `gRPC` service.
 
+use std::fmt::Write as _;
+
 use color_eyre::Result;
+use prost_build::{Service, ServiceGenerator};
+
+/// Name of the generated trait with all client methods of the service.
+const STORAGE_API_TRAIT: &str = "StorageApi";
+/// Name of the client wrapper implementing [`STORAGE_API_TRAIT`] by delegating to the generated
+/// client. Defined manually in `src/grpc.rs`.
+const STORAGE_CLIENT: &str = "StorageClient";
+/// Name of the boxed stream returned by server streaming methods of [`STORAGE_API_TRAIT`].
+/// Defined manually in `src/grpc.rs`.
+const RESPONSE_STREAM: &str = "ResponseStream";
+
+/// Service generator which additionally emits [`STORAGE_API_TRAIT`] trait.
+///
+/// Both the real client and the mock implement the trait, so adding an RPC to the proto without
+/// mocking it fails to compile right in the `mock!` block.
+struct StorageApiGenerator {
+    /// Generator of the `tonic` client.
+    tonic: Box<dyn ServiceGenerator>,
+}
+
+impl StorageApiGenerator {
+    /// Write [`STORAGE_API_TRAIT`] trait declaration and its implementation for
+    /// [`STORAGE_CLIENT`] to `buf`.
+    fn generate_storage_api(service: &Service, buf: &mut String) -> std::fmt::Result {
+        let mut declarations = String::new();
+        let mut implementations = String::new();
+        for method in &service.methods {
+            let input = &method.input_type;
+            let output = &method.output_type;
+            let (request_bound, response, delegation) = match (
+                method.client_streaming,
+                method.server_streaming,
+            ) {
+                (false, false) => (
+                    format!("tonic::IntoRequest<{input}>"),
+                    output.clone(),
+                    format!("self.0.{}(request).await", method.name),
+                ),
+                (true, false) => (
+                    format!("tonic::IntoStreamingRequest<Message = {input}>"),
+                    output.clone(),
+                    format!("self.0.{}(request).await", method.name),
+                ),
+                (false, true) => (
+                    format!("tonic::IntoRequest<{input}>"),
+                    format!("{RESPONSE_STREAM}<{output}>"),
+                    format!(
+                        "self.0.{}(request).await.map(|response| \
+                                response.map(|stream| -> {RESPONSE_STREAM}<_> {{ Box::pin(stream) }})\
+                            )",
+                        method.name
+                    ),
+                ),
+                (true, true) => {
+                    writeln!(
+                            buf,
+                            "compile_error!(\"bidirectional streaming method `{}` is not supported by `{STORAGE_API_TRAIT}`\");",
+                            method.proto_name
+                        )?;
+                    continue;
+                }
+            };
+
+            let signature = format!(
+                "async fn {name}<R: {request_bound} + Send + 'static>(\
+                    &mut self, request: R\
+                ) -> Result<tonic::Response<{response}>, tonic::Status>",
+                name = method.name,
+            );
+            writeln!(declarations, "    /// Call `{}` method.", method.proto_name)?;
+            writeln!(declarations, "    {signature};")?;
+            writeln!(implementations, "    {signature} {{ {delegation} }}")?;
+        }
+
+        writeln!(
+            buf,
+            "/// All client methods of the `{}` service.",
+            service.proto_name
+        )?;
+        writeln!(buf, "///")?;
+        writeln!(
+            buf,
+            "/// Generated from the proto-file, so that the real client and the mock can't diverge."
+        )?;
+        writeln!(
+            buf,
+            "#[expect(async_fn_in_trait, reason = \"only used with concrete types\")]"
+        )?;
+        writeln!(buf, "pub trait {STORAGE_API_TRAIT} {{\n{declarations}}}")?;
+        writeln!(
+            buf,
+            "impl {STORAGE_API_TRAIT} for {STORAGE_CLIENT} {{\n{implementations}}}"
+        )
+    }
+}
+
+impl ServiceGenerator for StorageApiGenerator {
+    #[expect(clippy::expect_used, reason = "writing to `String` never fails")]
+    fn generate(&mut self, service: Service, buf: &mut String) {
+        Self::generate_storage_api(&service, buf).expect("Writing to `String` should never fail");
+        self.tonic.generate(service, buf);
+    }
+
+    fn finalize(&mut self, buf: &mut String) {
+        self.tonic.finalize(buf);
+    }
+
+    fn finalize_package(&mut self, package: &str, buf: &mut String) {
+        self.tonic.finalize_package(package, buf);
+    }
+}
 
 fn main() -> Result<()> {
-    tonic_build::configure()
-        .build_server(false)
+    // Set by `tonic_build::compile_protos()`, but not by `prost_build`.
+    println!("cargo:rerun-if-changed=../proto");
+
+    // Server is only needed to serve the storage stub of the simulation.
+    let tonic = tonic_build::configure()
+        .build_server(true)
         .build_client(true)
         .client_mod_attribute(
             "password_storage",
             "#[expect(clippy::missing_docs_in_private_items)]",
         )
+        .server_mod_attribute("password_storage", "#[cfg(feature = \"test-doubles\")]")
+        .server_mod_attribute(
+            "password_storage",
+            "#[expect(\
+                clippy::similar_names,\
+                clippy::default_trait_access,\
+                clippy::too_many_lines,\
+                clippy::clone_on_ref_ptr,\
+                clippy::as_conversions,\
+                clippy::allow_attributes,\
+                reason = \"generated code\"\
+            )]",
+        )
+        .service_generator();
+
+    prost_build::Config::new()
+        .service_generator(Box::new(StorageApiGenerator { tonic }))
+        // Implemented manually in `src/grpc.rs` to keep encrypted bytes out of logs.
+        .skip_debug([".password_storage.Record", ".password_storage.Attachment"])
         .compile_protos(&["../proto/password_storage.proto"], &["../proto"])
         .map_err(Into::into)
 }
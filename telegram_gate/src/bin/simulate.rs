@@ -0,0 +1,27 @@
+//! Replays a script of updates through the bot states with mocked Telegram and password storage.
+//!
+//! Usage: `cargo run --bin simulate --features test-doubles -- <script.yaml>`.
+//! See [`simulation`] for the script format and `simulations` directory for examples.
+
+use color_eyre::{
+    eyre::{eyre, WrapErr as _},
+    Result,
+};
+use telepass_telegram_gate::test_utils::simulation::{self, Script};
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .ok_or_else(|| eyre!("Expected path to the script, usage: simulate <script.yaml>"))?;
+    let script = std::fs::read_to_string(&path)
+        .wrap_err_with(|| format!("Failed to read script at path: {path}"))?;
+
+    let report = simulation::run(Script::from_yaml(&script)?).await?;
+
+    #[expect(clippy::print_stdout, reason = "report is the output of the binary")]
+    {
+        print!("{report}");
+    }
+    Ok(())
+}
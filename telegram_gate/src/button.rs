@@ -14,6 +14,7 @@ pub enum ButtonBox {
     Yes(Button<kind::Yes>),
     No(Button<kind::No>),
     Show(Button<kind::Show>),
+    Duplicate(Button<kind::Duplicate>),
 }
 
 impl ButtonBox {
@@ -32,6 +33,7 @@ impl ButtonBox {
             .or_else(|(_, msg)| Button::<kind::Yes>::new(msg, data).map(Into::into))
             .or_else(|(_, msg)| Button::<kind::No>::new(msg, data).map(Into::into))
             .or_else(|(_, msg)| Button::<kind::Show>::new(msg, data).map(Into::into))
+            .or_else(|(_, msg)| Button::<kind::Duplicate>::new(msg, data).map(Into::into))
             .map_err(|_| parse_display::ParseError::with_message("Unexpected button data"))
     }
 }
@@ -77,6 +79,14 @@ impl ButtonBox {
             kind: kind::Show,
         })
     }
+
+    #[must_use]
+    pub fn duplicate() -> Self {
+        Self::Duplicate(Button {
+            message: TelegramMessage::default(),
+            kind: kind::Duplicate,
+        })
+    }
 }
 
 /// Button type generic over button kind
@@ -126,6 +136,18 @@ pub mod kind {
     #[derive(Debug, Display, Clone, FromStr)]
     #[display("👀 Show")]
     pub struct Show;
+
+    /// "Duplicate" button kind.
+    #[derive(Debug, Display, Clone, FromStr)]
+    #[display("📄 Duplicate")]
+    pub struct Duplicate;
+
+    /// "Temp link" button kind.
+    ///
+    /// It's a url button, so it's never sent back as callback data.
+    #[derive(Debug, Display, Clone, FromStr)]
+    #[display("🔗 Temp link")]
+    pub struct TempLink;
 }
 
 #[cfg(test)]
@@ -155,6 +177,7 @@ mod tests {
             ButtonBox::Yes(_) => parse_yes(),
             ButtonBox::No(_) => parse_no(),
             ButtonBox::Show(_) => parse_show(),
+            ButtonBox::Duplicate(_) => parse_duplicate(),
         }
 
         unreachable!()
@@ -195,4 +218,13 @@ mod tests {
         let button = ButtonBox::new(message, data).unwrap();
         assert!(matches!(button, ButtonBox::Show(_)));
     }
+
+    #[test]
+    fn parse_duplicate() {
+        let message = TelegramMessage::default();
+        let data = "📄 Duplicate";
+
+        let button = ButtonBox::new(message, data).unwrap();
+        assert!(matches!(button, ButtonBox::Duplicate(_)));
+    }
 }
@@ -17,6 +17,16 @@ pub enum Command {
     Start(Start),
     #[command(description = "cancel current operation")]
     Cancel(Cancel),
+    #[command(description = "add a new password")]
+    Add(Add),
+    #[command(description = "search for a word inside records content")]
+    DeepFind(DeepFind),
+    #[command(description = "show what's new since your last visit")]
+    WhatsNew(WhatsNew),
+    #[command(description = "rename all records starting with one prefix to another one")]
+    RenamePrefix(RenamePrefix),
+    #[command(description = "show or set your time zone, e.g. /timezone Europe/Berlin")]
+    TimeZone(TimeZone),
 }
 
 #[cfg(test)]
@@ -28,13 +38,46 @@ impl Command {
 
     #[must_use]
     pub const fn start() -> Self {
-        Self::Start(Start)
+        Self::Start(Start(None))
+    }
+
+    #[must_use]
+    pub const fn start_with(action: StartAction) -> Self {
+        Self::Start(Start(Some(action)))
     }
 
     #[must_use]
     pub const fn cancel() -> Self {
         Self::Cancel(Cancel)
     }
+
+    #[must_use]
+    pub const fn add() -> Self {
+        Self::Add(Add)
+    }
+
+    #[must_use]
+    pub fn deep_find(word: &str) -> Self {
+        Self::DeepFind(DeepFind(word.to_owned()))
+    }
+
+    #[must_use]
+    pub const fn whats_new() -> Self {
+        Self::WhatsNew(WhatsNew)
+    }
+
+    #[must_use]
+    pub fn rename_prefix(old: &str, new: &str) -> Self {
+        Self::RenamePrefix(RenamePrefix {
+            old: old.to_owned(),
+            new: new.to_owned(),
+        })
+    }
+
+    #[must_use]
+    pub fn time_zone(name: &str) -> Self {
+        Self::TimeZone(TimeZone(name.to_owned()))
+    }
 }
 
 /// Macro to create blank [`FromStr`] implementation for commands.
@@ -59,14 +102,100 @@ macro_rules! blank_from_str {
 pub struct Help;
 
 /// Start bot command.
+///
+/// Deep links like `t.me/<bot>?start=list` pass a [`StartAction`] to perform right away.
+/// Unknown actions are ignored, so that old links still start the bot.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub struct Start;
+pub struct Start(pub Option<StartAction>);
+
+impl FromStr for Start {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.trim().parse().ok()))
+    }
+}
+
+/// Quick action passed in the payload of the [`Start`] command.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, parse_display::Display, parse_display::FromStr)]
+#[display(style = "lowercase")]
+pub enum StartAction {
+    /// Show the list of stored resources.
+    List,
+    /// Generate a random password.
+    Generate,
+}
 
 /// Cancel current operation command.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Cancel;
 
-blank_from_str!(Help, Start, Cancel);
+/// Add a new password command.
+///
+/// Alternative to the Web App keyboard button for clients hiding reply keyboards.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Add;
+
+/// Show release notes the user hasn't seen yet command.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WhatsNew;
+
+blank_from_str!(Help, Cancel, Add, WhatsNew);
+
+/// Search for a word inside records content command.
+///
+/// Content is encrypted, so the search token is computed by the Web App.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeepFind(pub String);
+
+impl FromStr for DeepFind {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.trim().to_owned()))
+    }
+}
+
+/// Rename all records starting with the `old` prefix to start with the `new` one command.
+///
+/// Both prefixes are empty if not exactly two words are passed, so that the usage is explained.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenamePrefix {
+    /// Prefix to replace.
+    pub old: String,
+    /// Prefix to replace the `old` one with.
+    pub new: String,
+}
+
+impl FromStr for RenamePrefix {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut prefixes = s.split_whitespace();
+        let (Some(old), Some(new), None) = (prefixes.next(), prefixes.next(), prefixes.next())
+        else {
+            return Ok(Self::default());
+        };
+        Ok(Self {
+            old: old.to_owned(),
+            new: new.to_owned(),
+        })
+    }
+}
+
+/// Show or set time zone of the chat command.
+///
+/// Time zone is shown if the name is empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeZone(pub String);
+
+impl FromStr for TimeZone {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.trim().to_owned()))
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -93,6 +222,11 @@ mod tests {
             Command::Help(_) => parse_help(),
             Command::Start(_) => parse_start(),
             Command::Cancel(_) => parse_cancel(),
+            Command::Add(_) => parse_add(),
+            Command::DeepFind(_) => parse_deep_find(),
+            Command::WhatsNew(_) => parse_whats_new(),
+            Command::RenamePrefix(_) => parse_rename_prefix(),
+            Command::TimeZone(_) => parse_time_zone(),
         }
 
         unreachable!()
@@ -107,7 +241,19 @@ mod tests {
     #[test]
     fn parse_start() {
         let command = Command::parse("/start", "test_bot_name").unwrap();
-        assert!(matches!(command, Command::Start(_)));
+        assert_eq!(command, Command::start());
+    }
+
+    #[test]
+    fn parse_start_with_action() {
+        let list = Command::parse("/start list", "test_bot_name").unwrap();
+        assert_eq!(list, Command::start_with(StartAction::List));
+
+        let generate = Command::parse("/start generate", "test_bot_name").unwrap();
+        assert_eq!(generate, Command::start_with(StartAction::Generate));
+
+        let unknown = Command::parse("/start unknown", "test_bot_name").unwrap();
+        assert_eq!(unknown, Command::start());
     }
 
     #[test]
@@ -115,4 +261,46 @@ mod tests {
         let command = Command::parse("/cancel", "test_bot_name").unwrap();
         assert!(matches!(command, Command::Cancel(_)));
     }
+
+    #[test]
+    fn parse_add() {
+        let command = Command::parse("/add", "test_bot_name").unwrap();
+        assert!(matches!(command, Command::Add(_)));
+    }
+
+    #[test]
+    fn parse_deep_find() {
+        let command = Command::parse("/deepfind  router ", "test_bot_name").unwrap();
+        assert_eq!(command, Command::deep_find("router"));
+
+        let empty_command = Command::parse("/deepfind", "test_bot_name").unwrap();
+        assert_eq!(empty_command, Command::deep_find(""));
+    }
+
+    #[test]
+    fn parse_whats_new() {
+        let command = Command::parse("/whatsnew", "test_bot_name").unwrap();
+        assert_eq!(command, Command::whats_new());
+    }
+
+    #[test]
+    fn parse_rename_prefix() {
+        let command = Command::parse("/renameprefix  work/ team/ ", "test_bot_name").unwrap();
+        assert_eq!(command, Command::rename_prefix("work/", "team/"));
+
+        for args in ["", " work/", " work/ team/ extra"] {
+            let without_prefixes =
+                Command::parse(&format!("/renameprefix{args}"), "test_bot_name").unwrap();
+            assert_eq!(without_prefixes, Command::rename_prefix("", ""));
+        }
+    }
+
+    #[test]
+    fn parse_time_zone() {
+        let command = Command::parse("/timezone  Europe/Berlin ", "test_bot_name").unwrap();
+        assert_eq!(command, Command::time_zone("Europe/Berlin"));
+
+        let empty_command = Command::parse("/timezone", "test_bot_name").unwrap();
+        assert_eq!(empty_command, Command::time_zone(""));
+    }
 }
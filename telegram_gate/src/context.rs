@@ -5,11 +5,101 @@
     reason = "mockall is really bad at placing expects in the right place"
 )]
 
+use std::time::Instant;
+
 #[cfg(test)]
 use mockall::automock;
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+use telepass_crypto::signed_link::LinkKey;
 use url::Url;
 
-use super::{Arc, Bot, ChatId, PasswordStorageClient};
+use super::{
+    final_message::RetryQueue, footer::MessageFooter, keyboard::ResourcePrefix,
+    release_notes::SeenVersions, replay_cache::ReplayCache, role::Role,
+    sensitive_message::DeletionQueue, storage_health::StorageAvailability, time_zone::TimeZones,
+    unlock_token::UnlockTokenStore, Arc, Bot, ChatId, PasswordStorageClient,
+};
+
+/// Source of the current time. Mocked in tests to control timeouts.
+#[cfg_attr(test, automock)]
+pub trait Clock: Send + Sync {
+    /// Get the current moment.
+    fn now(&self) -> Instant;
+}
+
+/// [`Clock`] reading the system monotonic clock.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Source of random bytes. Mocked in tests to get reproducible results.
+///
+/// Implementations used outside of tests must be cryptographically secure,
+/// because random bytes are used to generate passwords.
+#[cfg_attr(test, automock)]
+pub trait Rng: Send + Sync {
+    /// Fill `dest` with random bytes.
+    fn fill_bytes(&self, dest: &mut [u8]);
+}
+
+/// Cryptographically secure [`Rng`] provided by the operating system.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OsRandom;
+
+impl Rng for OsRandom {
+    #[inline]
+    fn fill_bytes(&self, dest: &mut [u8]) {
+        OsRng.fill_bytes(dest);
+    }
+}
+
+/// Adapter of [`Rng`] to [`RngCore`], so that it can be passed to generic random consumers.
+///
+/// Implements [`CryptoRng`], relying on the [`Rng`] requirements.
+pub struct RngAdapter<'rng>(pub &'rng dyn Rng);
+
+impl RngCore for RngAdapter<'_> {
+    #[inline]
+    #[expect(
+        clippy::big_endian_bytes,
+        reason = "any byte order is fine for random bytes"
+    )]
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.0.fill_bytes(&mut bytes);
+        u32::from_be_bytes(bytes)
+    }
+
+    #[inline]
+    #[expect(
+        clippy::big_endian_bytes,
+        reason = "any byte order is fine for random bytes"
+    )]
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.0.fill_bytes(&mut bytes);
+        u64::from_be_bytes(bytes)
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest);
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.0.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for RngAdapter<'_> {}
 
 /// Context to pass values and dependencies between different states.
 pub struct Context {
@@ -17,28 +107,171 @@ pub struct Context {
     bot: Bot,
     /// Chat identifier.
     chat_id: ChatId,
+    /// Role of the user in the chat.
+    role: Role,
     /// URL ot the web app frontend.
     web_app_url: Arc<Url>,
+    /// Prefix of resource names on keyboard buttons.
+    resource_prefix: Arc<ResourcePrefix>,
+    /// Footer appended to messages.
+    message_footer: Arc<MessageFooter>,
     /// Client to interact with password storage service.
     storage_client: Arc<tokio::sync::Mutex<PasswordStorageClient>>,
+    /// Store of one-time unlock tokens. [`None`] if unlock links are disabled.
+    unlock_token_store: Option<Arc<UnlockTokenStore>>,
+    /// Availability of the password storage.
+    storage_availability: Arc<StorageAvailability>,
+    /// Source of the current time.
+    clock: Arc<dyn Clock>,
+    /// Source of random bytes.
+    rng: Arc<dyn Rng>,
+    /// Username of the bot. [`None`] if unknown.
+    bot_username: Option<Arc<str>>,
+    /// Queue of final messages to retry. [`None`] if retries are disabled.
+    final_message_retries: Option<RetryQueue>,
+    /// Versions of the release notes seen by chats. [`None`] if they are not tracked.
+    seen_versions: Option<Arc<SeenVersions>>,
+    /// Key to sign temporary links to records with. [`None`] if temporary links are disabled.
+    temp_link_key: Option<Arc<LinkKey>>,
+    /// Outcomes of handled Web App messages. [`None`] if redeliveries are not recognized.
+    web_app_replays: Option<Arc<ReplayCache>>,
+    /// Time zones set by chats. [`None`] if they can't be set.
+    time_zones: Option<Arc<TimeZones>>,
+    /// Queue of sensitive messages to delete. [`None`] if they are kept in the chat.
+    sensitive_deletions: Option<DeletionQueue>,
 }
 
 #[cfg_attr(test, automock)]
 impl Context {
     /// Construct new [`Context`].
+    ///
+    /// Uses [`SystemClock`] and [`OsRandom`], see [`with_clock()`](Self::with_clock) and
+    /// [`with_rng()`](Self::with_rng) to replace them.
     #[allow(clippy::missing_const_for_fn, reason = "not supported by mockall")]
+    #[allow(
+        clippy::too_many_arguments,
+        reason = "all dependencies are passed from the handler"
+    )]
     #[cfg_attr(not(test), inline)]
     pub fn new(
         bot: Bot,
         chat_id: ChatId,
+        role: Role,
         web_app_url: Arc<Url>,
+        resource_prefix: Arc<ResourcePrefix>,
+        message_footer: Arc<MessageFooter>,
         storage_client: Arc<tokio::sync::Mutex<PasswordStorageClient>>,
+        unlock_token_store: Option<Arc<UnlockTokenStore>>,
+        storage_availability: Arc<StorageAvailability>,
     ) -> Self {
         Self {
             bot,
             chat_id,
+            role,
             web_app_url,
+            resource_prefix,
+            message_footer,
             storage_client,
+            unlock_token_store,
+            storage_availability,
+            clock: Arc::new(SystemClock),
+            rng: Arc::new(OsRandom),
+            bot_username: None,
+            final_message_retries: None,
+            seen_versions: None,
+            temp_link_key: None,
+            web_app_replays: None,
+            time_zones: None,
+            sensitive_deletions: None,
+        }
+    }
+
+    /// Replace source of the current time.
+    #[must_use]
+    #[cfg_attr(not(test), inline)]
+    pub fn with_clock(self, clock: Arc<dyn Clock>) -> Self {
+        Self { clock, ..self }
+    }
+
+    /// Replace source of random bytes.
+    #[must_use]
+    #[cfg_attr(not(test), inline)]
+    pub fn with_rng(self, rng: Arc<dyn Rng>) -> Self {
+        Self { rng, ..self }
+    }
+
+    /// Set username of the bot.
+    ///
+    /// Username is fetched once on startup, so that deep links can be built without extra
+    /// requests to Telegram.
+    #[must_use]
+    #[cfg_attr(not(test), inline)]
+    pub fn with_bot_username(self, bot_username: Arc<str>) -> Self {
+        Self {
+            bot_username: Some(bot_username),
+            ..self
+        }
+    }
+
+    /// Set queue of final messages to retry if they fail to be sent.
+    #[must_use]
+    #[cfg_attr(not(test), inline)]
+    pub fn with_final_message_retries(self, final_message_retries: RetryQueue) -> Self {
+        Self {
+            final_message_retries: Some(final_message_retries),
+            ..self
+        }
+    }
+
+    /// Set versions of the release notes seen by chats.
+    #[must_use]
+    #[cfg_attr(not(test), inline)]
+    pub fn with_seen_versions(self, seen_versions: Arc<SeenVersions>) -> Self {
+        Self {
+            seen_versions: Some(seen_versions),
+            ..self
+        }
+    }
+
+    /// Set key to sign temporary links to records with.
+    ///
+    /// [`None`] disables temporary links.
+    #[must_use]
+    #[cfg_attr(not(test), inline)]
+    pub fn with_temp_link_key(self, temp_link_key: Option<Arc<LinkKey>>) -> Self {
+        Self {
+            temp_link_key,
+            ..self
+        }
+    }
+
+    /// Set cache of outcomes of handled Web App messages to recognize their redeliveries.
+    #[must_use]
+    #[cfg_attr(not(test), inline)]
+    pub fn with_web_app_replays(self, web_app_replays: Arc<ReplayCache>) -> Self {
+        Self {
+            web_app_replays: Some(web_app_replays),
+            ..self
+        }
+    }
+
+    /// Set time zones set by chats.
+    #[must_use]
+    #[cfg_attr(not(test), inline)]
+    pub fn with_time_zones(self, time_zones: Arc<TimeZones>) -> Self {
+        Self {
+            time_zones: Some(time_zones),
+            ..self
+        }
+    }
+
+    /// Set queue of sensitive messages to delete after a while.
+    #[must_use]
+    #[cfg_attr(not(test), inline)]
+    pub fn with_sensitive_deletions(self, sensitive_deletions: DeletionQueue) -> Self {
+        Self {
+            sensitive_deletions: Some(sensitive_deletions),
+            ..self
         }
     }
 
@@ -64,6 +297,17 @@ impl Context {
         self.chat_id
     }
 
+    /// Get role of the user in the chat.
+    #[allow(
+        clippy::must_use_candidate,
+        clippy::missing_const_for_fn,
+        reason = "not supported by mockall"
+    )]
+    #[cfg_attr(not(test), inline)]
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
     /// Get web-app url.
     #[allow(
         clippy::must_use_candidate,
@@ -75,10 +319,117 @@ impl Context {
         &self.web_app_url
     }
 
+    /// Get prefix of resource names on keyboard buttons.
+    #[allow(clippy::must_use_candidate, reason = "not supported by mockall")]
+    #[cfg_attr(not(test), inline)]
+    pub fn resource_prefix(&self) -> &ResourcePrefix {
+        &self.resource_prefix
+    }
+
+    /// Get footer appended to messages.
+    #[allow(clippy::must_use_candidate, reason = "not supported by mockall")]
+    #[cfg_attr(not(test), inline)]
+    pub fn message_footer(&self) -> &MessageFooter {
+        &self.message_footer
+    }
+
     /// Get password storage client.
     #[allow(clippy::must_use_candidate, reason = "not supported by mockall")]
     #[cfg_attr(not(test), inline)]
     pub fn storage_client(&self) -> &tokio::sync::Mutex<PasswordStorageClient> {
         &self.storage_client
     }
+
+    /// Get store of one-time unlock tokens.
+    ///
+    /// Returns [`None`] if unlock links are disabled.
+    #[allow(clippy::must_use_candidate, reason = "not supported by mockall")]
+    #[cfg_attr(not(test), inline)]
+    pub fn unlock_token_store(&self) -> Option<Arc<UnlockTokenStore>> {
+        self.unlock_token_store.clone()
+    }
+
+    /// Get availability of the password storage.
+    #[allow(clippy::must_use_candidate, reason = "not supported by mockall")]
+    #[cfg_attr(not(test), inline)]
+    pub fn storage_availability(&self) -> &StorageAvailability {
+        &self.storage_availability
+    }
+
+    /// Get source of the current time.
+    #[allow(clippy::must_use_candidate, reason = "not supported by mockall")]
+    #[cfg_attr(not(test), inline)]
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        Arc::clone(&self.clock)
+    }
+
+    /// Get source of random bytes.
+    #[allow(clippy::must_use_candidate, reason = "not supported by mockall")]
+    #[cfg_attr(not(test), inline)]
+    pub fn rng(&self) -> Arc<dyn Rng> {
+        Arc::clone(&self.rng)
+    }
+
+    /// Get username of the bot.
+    ///
+    /// Returns [`None`] if it's unknown.
+    #[allow(clippy::must_use_candidate, reason = "not supported by mockall")]
+    #[cfg_attr(not(test), inline)]
+    pub fn bot_username(&self) -> Option<Arc<str>> {
+        self.bot_username.clone()
+    }
+
+    /// Get queue of final messages to retry.
+    ///
+    /// Returns [`None`] if retries are disabled.
+    #[allow(clippy::must_use_candidate, reason = "not supported by mockall")]
+    #[cfg_attr(not(test), inline)]
+    pub fn final_message_retries(&self) -> Option<RetryQueue> {
+        self.final_message_retries.clone()
+    }
+
+    /// Get versions of the release notes seen by chats.
+    ///
+    /// Returns [`None`] if they are not tracked.
+    #[allow(clippy::must_use_candidate, reason = "not supported by mockall")]
+    #[cfg_attr(not(test), inline)]
+    pub fn seen_versions(&self) -> Option<Arc<SeenVersions>> {
+        self.seen_versions.clone()
+    }
+
+    /// Get key to sign temporary links to records with.
+    ///
+    /// Returns [`None`] if temporary links are disabled.
+    #[allow(clippy::must_use_candidate, reason = "not supported by mockall")]
+    #[cfg_attr(not(test), inline)]
+    pub fn temp_link_key(&self) -> Option<Arc<LinkKey>> {
+        self.temp_link_key.clone()
+    }
+
+    /// Get cache of outcomes of handled Web App messages.
+    ///
+    /// Returns [`None`] if redeliveries are not recognized.
+    #[allow(clippy::must_use_candidate, reason = "not supported by mockall")]
+    #[cfg_attr(not(test), inline)]
+    pub fn web_app_replays(&self) -> Option<Arc<ReplayCache>> {
+        self.web_app_replays.clone()
+    }
+
+    /// Get time zones set by chats.
+    ///
+    /// Returns [`None`] if they can't be set.
+    #[allow(clippy::must_use_candidate, reason = "not supported by mockall")]
+    #[cfg_attr(not(test), inline)]
+    pub fn time_zones(&self) -> Option<Arc<TimeZones>> {
+        self.time_zones.clone()
+    }
+
+    /// Get queue of sensitive messages to delete.
+    ///
+    /// Returns [`None`] if they are kept in the chat.
+    #[allow(clippy::must_use_candidate, reason = "not supported by mockall")]
+    #[cfg_attr(not(test), inline)]
+    pub fn sensitive_deletions(&self) -> Option<DeletionQueue> {
+        self.sensitive_deletions.clone()
+    }
 }
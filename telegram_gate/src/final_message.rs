@@ -0,0 +1,240 @@
+//! Module with delivery of the final user-visible message of a transition.
+//!
+//! When the last message of a transition fails to be sent, the action is already completed,
+//! but the user sees nothing, e.g. a deleted record without a confirmation. Instead of failing
+//! the transition, a plain-text summary of the action is re-sent once by [`RetryWorker`].
+
+use std::time::Duration;
+
+#[cfg(not(test))]
+use teloxide::requests::Requester as _;
+use teloxide::types::ChatId;
+use tokio::{sync::mpsc, time::Instant};
+use tracing::{error, info, warn};
+
+#[mockall_double::double]
+use crate::context::Context;
+use crate::{footer::MessageClass, Bot, SendMessage};
+
+/// Default delay before re-sending the summary of a completed action.
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Summary of a completed action waiting to be re-sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Retry {
+    /// Chat to send the summary to.
+    chat_id: ChatId,
+    /// Text of the message with footer already applied.
+    text: String,
+    /// Moment the summary was enqueued at.
+    enqueued_at: Instant,
+}
+
+/// Queue of summaries to be re-sent by [`RetryWorker`].
+#[derive(Debug, Clone)]
+pub struct RetryQueue {
+    /// Sender of the retries to the worker.
+    sender: mpsc::UnboundedSender<Retry>,
+}
+
+/// Worker re-sending summaries enqueued to [`RetryQueue`].
+#[derive(Debug)]
+pub struct RetryWorker {
+    /// Receiver of the retries.
+    receiver: mpsc::UnboundedReceiver<Retry>,
+    /// Delay before re-sending a summary.
+    delay: Duration,
+}
+
+/// Construct connected [`RetryQueue`] and [`RetryWorker`] re-sending summaries after `delay`.
+#[must_use]
+pub fn retry_queue(delay: Duration) -> (RetryQueue, RetryWorker) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    (RetryQueue { sender }, RetryWorker { receiver, delay })
+}
+
+impl RetryQueue {
+    /// Enqueue `summary` of the action completed in the chat of the `context`.
+    ///
+    /// Summary is sent as a plain-text message with the footer of the `context`.
+    pub fn enqueue(&self, context: &Context, summary: &str) {
+        let text = context.message_footer().apply(
+            format!("\u{2705} Your last action completed: {summary}"),
+            MessageClass::Plain,
+        );
+        let retry = Retry {
+            chat_id: context.chat_id(),
+            text,
+            enqueued_at: Instant::now(),
+        };
+        if self.sender.send(retry).is_err() {
+            warn!("Final message retry worker is stopped, summary is dropped");
+        }
+    }
+}
+
+impl RetryWorker {
+    /// Re-send enqueued summaries with `bot` each once its delay passes.
+    ///
+    /// Finishes when all [`RetryQueue`]s are dropped.
+    pub async fn run(mut self, bot: Bot) {
+        // All retries have the same delay, so they are due in the order they were enqueued
+        while let Some(retry) = self.receiver.recv().await {
+            tokio::time::sleep(self.delay.saturating_sub(retry.enqueued_at.elapsed())).await;
+
+            match bot.send_message(retry.chat_id, retry.text).await {
+                Ok(_message) => info!(chat_id = %retry.chat_id, "Summary re-sent"),
+                Err(error) => warn!(%error, chat_id = %retry.chat_id, "Failed to re-send summary"),
+            }
+        }
+    }
+}
+
+/// Send `message` being the final user-visible message of a transition.
+///
+/// The action of the transition is already completed, so failure to send doesn't fail the
+/// transition. Instead `summary` of the action is enqueued to the [`RetryQueue`] of the
+/// `context`.
+///
+/// Returns `true` if `message` was delivered.
+pub async fn deliver(context: &Context, message: SendMessage, summary: &str) -> bool {
+    let error = match message.await {
+        Ok(_message) => return true,
+        Err(error) => error,
+    };
+
+    if let Some(retry_queue) = context.final_message_retries() {
+        warn!(%error, "Failed to send final message, enqueueing retry");
+        retry_queue.enqueue(context, summary);
+    } else {
+        error!(%error, "Failed to send final message and retries are disabled");
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use teloxide::{types::Seconds, RequestError};
+
+    use super::*;
+    use crate::{
+        footer::MessageFooter,
+        test_utils::mock_bot::{MockBotBuilder, CHAT_ID},
+    };
+
+    const DELAY: Duration = Duration::from_secs(5);
+
+    /// Construct mock context of the test chat with `footer` and `retry_queue`.
+    fn mock_context(footer: &str, retry_queue: Option<RetryQueue>) -> Context {
+        let mut mock_context = Context::default();
+        mock_context.expect_chat_id().return_const(CHAT_ID);
+        mock_context
+            .expect_message_footer()
+            .return_const(MessageFooter::new(footer));
+        mock_context
+            .expect_final_message_retries()
+            .return_const(retry_queue);
+        mock_context
+    }
+
+    /// Construct failed request to send a message.
+    fn failed_send_message() -> SendMessage {
+        MockBotBuilder::new()
+            .expect_send_message("\u{2705} test.resource.com deleted.")
+            .expect_into_future_with_error(RequestError::RetryAfter(Seconds::from_seconds(1)))
+            .build()
+            .send_message(CHAT_ID, "\u{2705} test.resource.com deleted.")
+    }
+
+    #[tokio::test]
+    async fn delivered_message_enqueues_nothing() {
+        let (retry_queue, mut retry_worker) = retry_queue(DELAY);
+        let mock_context = mock_context("", Some(retry_queue));
+        let message = MockBotBuilder::new()
+            .expect_send_message("\u{2705} test.resource.com deleted.")
+            .expect_into_future()
+            .build()
+            .send_message(CHAT_ID, "\u{2705} test.resource.com deleted.");
+
+        assert!(deliver(&mock_context, message, "test.resource.com deleted").await);
+        drop(mock_context);
+        assert!(retry_worker.receiver.recv().await.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failed_message_enqueues_summary_with_footer() {
+        let (retry_queue, mut retry_worker) = retry_queue(DELAY);
+        let mock_context = mock_context("-- staging", Some(retry_queue));
+
+        assert!(
+            !deliver(
+                &mock_context,
+                failed_send_message(),
+                "test.resource.com deleted"
+            )
+            .await
+        );
+
+        assert_eq!(
+            retry_worker.receiver.try_recv().unwrap(),
+            Retry {
+                chat_id: CHAT_ID,
+                text:
+                    "\u{2705} Your last action completed: test.resource.com deleted\n\n-- staging"
+                        .to_owned(),
+                enqueued_at: Instant::now(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_message_without_retries_is_only_logged() {
+        let mock_context = mock_context("", None);
+
+        assert!(
+            !deliver(
+                &mock_context,
+                failed_send_message(),
+                "test.resource.com deleted"
+            )
+            .await
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn worker_resends_summary_after_delay() {
+        let (retry_queue, retry_worker) = retry_queue(DELAY);
+        let bot = MockBotBuilder::new()
+            .expect_send_message(
+                "\u{2705} Your last action completed: test.resource.com deleted".to_owned(),
+            )
+            .expect_into_future()
+            .build();
+        let mock_context = mock_context("", Some(retry_queue.clone()));
+
+        let start = Instant::now();
+        retry_queue.enqueue(&mock_context, "test.resource.com deleted");
+        drop((retry_queue, mock_context));
+        retry_worker.run(bot).await;
+
+        assert_eq!(start.elapsed(), DELAY);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn worker_gives_up_after_one_retry() {
+        let (retry_queue, retry_worker) = retry_queue(DELAY);
+        let bot = MockBotBuilder::new()
+            .expect_send_message(
+                "\u{2705} Your last action completed: test.resource.com deleted".to_owned(),
+            )
+            .expect_into_future_with_error(RequestError::RetryAfter(Seconds::from_seconds(1)))
+            .build();
+        let mock_context = mock_context("", Some(retry_queue.clone()));
+
+        retry_queue.enqueue(&mock_context, "test.resource.com deleted");
+        drop((retry_queue, mock_context));
+        retry_worker.run(bot).await;
+    }
+}
@@ -0,0 +1,149 @@
+//! Module with [`MessageFooter`] appended to messages sent by the bot.
+
+#[cfg(not(test))]
+use teloxide::requests::Requester as _;
+
+#[mockall_double::double]
+use crate::context::Context;
+use crate::{markdown, SendMessage};
+
+/// Placeholder in the footer replaced with the bot version.
+pub const VERSION_PLACEHOLDER: &str = "{version}";
+
+/// Class of the message defining whether footer can be appended to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageClass {
+    /// Plain-text message.
+    Plain,
+    /// Message in `MarkdownV2` format, footer is escaped.
+    MarkdownV2,
+    /// Message showing user data, footer is never appended.
+    Sensitive,
+}
+
+/// Footer appended to messages sent by the bot, e.g. to tell staging and production bots apart.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageFooter {
+    /// Footer text. [`None`] if disabled.
+    text: Option<String>,
+}
+
+impl MessageFooter {
+    /// Construct new [`MessageFooter`].
+    ///
+    /// [`VERSION_PLACEHOLDER`] in `text` is replaced with the bot version.
+    /// Surrounding whitespaces are trimmed. Empty `text` disables the footer.
+    #[must_use]
+    pub fn new(text: &str) -> Self {
+        let text = text.trim();
+        Self {
+            text: (!text.is_empty())
+                .then(|| text.replace(VERSION_PLACEHOLDER, env!("CARGO_PKG_VERSION"))),
+        }
+    }
+
+    /// Append footer to `text` of the message of `class`.
+    #[must_use]
+    pub fn apply(&self, text: String, class: MessageClass) -> String {
+        match (self.text.as_deref(), class) {
+            (None, _) | (Some(_), MessageClass::Sensitive) => text,
+            (Some(footer), MessageClass::Plain) => format!("{text}\n\n{footer}"),
+            (Some(footer), MessageClass::MarkdownV2) => {
+                format!("{text}\n\n{}", markdown::escape(footer))
+            }
+        }
+    }
+}
+
+/// Send `text` of `class` to the chat, appending [`MessageFooter`] from the `context` if allowed.
+///
+/// Parse mode is not set, so `MarkdownV2` messages still need it.
+pub fn send_text(context: &Context, text: impl Into<String>, class: MessageClass) -> SendMessage {
+    let text = context.message_footer().apply(text.into(), class);
+    context.bot().send_message(context.chat_id(), text)
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(
+        clippy::non_ascii_literal,
+        reason = "footers usually start with a dash"
+    )]
+
+    use super::*;
+    use crate::test_utils::mock_bot::{MockBotBuilder, CHAT_ID};
+
+    #[test]
+    fn plain_gets_footer() {
+        let footer = MessageFooter::new("— telepass (staging)");
+
+        assert_eq!(
+            footer.apply("Hello".to_owned(), MessageClass::Plain),
+            "Hello\n\n— telepass (staging)"
+        );
+    }
+
+    #[test]
+    fn markdown_gets_escaped_footer() {
+        let footer = MessageFooter::new("— telepass (staging)");
+
+        assert_eq!(
+            footer.apply("*Hello*".to_owned(), MessageClass::MarkdownV2),
+            "*Hello*\n\n— telepass \\(staging\\)"
+        );
+    }
+
+    #[test]
+    fn sensitive_never_gets_footer() {
+        let footer = MessageFooter::new("— telepass (staging)");
+
+        assert_eq!(
+            footer.apply("Secret".to_owned(), MessageClass::Sensitive),
+            "Secret"
+        );
+    }
+
+    #[test]
+    fn empty_footer_is_disabled() {
+        for footer in [MessageFooter::new(""), MessageFooter::new("  ")] {
+            assert_eq!(footer, MessageFooter::default());
+            for class in [
+                MessageClass::Plain,
+                MessageClass::MarkdownV2,
+                MessageClass::Sensitive,
+            ] {
+                assert_eq!(footer.apply("Hello".to_owned(), class), "Hello");
+            }
+        }
+    }
+
+    #[test]
+    fn version_placeholder_is_replaced() {
+        let footer = MessageFooter::new("— telepass v{version}");
+
+        assert_eq!(
+            footer.apply("Hello".to_owned(), MessageClass::Plain),
+            format!("Hello\n\n— telepass v{}", env!("CARGO_PKG_VERSION"))
+        );
+    }
+
+    #[tokio::test]
+    #[expect(clippy::unwrap_used, reason = "it's ok in tests")]
+    async fn send_text_applies_footer_from_context() {
+        let mut mock_context = Context::default();
+        mock_context.expect_chat_id().return_const(CHAT_ID);
+        mock_context
+            .expect_message_footer()
+            .return_const(MessageFooter::new("— staging"));
+        mock_context.expect_bot().return_const(
+            MockBotBuilder::new()
+                .expect_send_message("Hello\n\n— staging".to_owned())
+                .expect_into_future()
+                .build(),
+        );
+
+        send_text(&mock_context, "Hello", MessageClass::Plain)
+            .await
+            .unwrap();
+    }
+}
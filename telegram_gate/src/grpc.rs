@@ -6,6 +6,10 @@
     clippy::future_not_send,
     clippy::allow_attributes_without_reason,
     clippy::derive_partial_eq_without_eq,
+    clippy::doc_markdown,
+    clippy::missing_const_for_fn,
+    clippy::must_use_candidate,
+    clippy::pattern_type_mismatch,
     reason = "generated code"
 )]
 #![allow(
@@ -16,47 +20,818 @@
 
 tonic::include_proto!("password_storage");
 
-#[cfg(test)]
+use sha2::{Digest as _, Sha256};
+use tokio_stream::StreamExt as _;
+
+/// Stream of messages returned by server streaming methods of [`StorageApi`].
+pub type ResponseStream<T> =
+    std::pin::Pin<Box<dyn tokio_stream::Stream<Item = Result<T, tonic::Status>> + Send + 'static>>;
+
+/// Payloads larger than this number of bytes are transferred in chunks of this size.
+///
+/// Such payloads don't fit into the Web App urls either.
+pub const MAX_INLINE_PAYLOAD_SIZE: usize = 16 * 1024;
+
+/// Client of the password storage service implementing [`StorageApi`].
+#[derive(Debug, Clone)]
+pub struct StorageClient(password_storage_client::PasswordStorageClient<tonic::transport::Channel>);
+
+impl StorageClient {
+    /// Construct client working over `channel`.
+    #[must_use]
+    pub fn new(channel: tonic::transport::Channel) -> Self {
+        Self(password_storage_client::PasswordStorageClient::new(channel))
+    }
+}
+
+#[cfg(any(test, feature = "test-doubles"))]
 mockall::mock! {
-    pub PasswordStorageClient {
-        // Copy-paste from `include_proto!` macro expansion.
-        // Unfortunately there is no better way to mock this.
+    pub PasswordStorageClient {}
 
-        pub async fn add<R: tonic::IntoRequest<Record> + 'static>(
+    // Signatures are copied from the generated `StorageApi` trait.
+    // If this fails to compile after changing the proto-file, add the new methods here.
+    impl StorageApi for PasswordStorageClient {
+        async fn add<R: tonic::IntoRequest<AddRequest> + Send + 'static>(
+            &mut self,
+            request: R
+        ) -> Result<tonic::Response<AddResponse>, tonic::Status>;
+
+        async fn delete<R: tonic::IntoRequest<DeleteRequest> + Send + 'static>(
             &mut self,
             request: R
         ) -> Result<tonic::Response<Response>, tonic::Status>;
 
-        pub async fn delete<R: tonic::IntoRequest<Resource> + 'static>(
+        async fn rename<R: tonic::IntoRequest<RenameRequest> + Send + 'static>(
             &mut self,
             request: R
         ) -> Result<tonic::Response<Response>, tonic::Status>;
 
-        pub async fn get<R: tonic::IntoRequest<Resource> + 'static>(
+        async fn get<R: tonic::IntoRequest<GetRequest> + Send + 'static>(
             &mut self,
             request: R
         ) -> Result<tonic::Response<Record>, tonic::Status>;
 
-        pub async fn list<R: tonic::IntoRequest<Empty> + 'static>(
+        async fn list<R: tonic::IntoRequest<Empty> + Send + 'static>(
             &mut self,
             request: R
         ) -> Result<tonic::Response<ListOfResources>, tonic::Status>;
 
-        pub async fn search<R: tonic::IntoRequest<Resource> + 'static>(
+        async fn search<R: tonic::IntoRequest<Resource> + Send + 'static>(
+            &mut self,
+            request: R,
+        ) -> Result<tonic::Response<ListOfResources>, tonic::Status>;
+
+        async fn search_blind<R: tonic::IntoRequest<BlindTokens> + Send + 'static>(
             &mut self,
             request: R,
         ) -> Result<tonic::Response<ListOfResources>, tonic::Status>;
+
+        async fn add_chunked<
+            R: tonic::IntoStreamingRequest<Message = RecordChunk> + Send + 'static
+        >(
+            &mut self,
+            request: R,
+        ) -> Result<tonic::Response<AddResponse>, tonic::Status>;
+
+        async fn get_chunked<R: tonic::IntoRequest<GetRequest> + Send + 'static>(
+            &mut self,
+            request: R,
+        ) -> Result<tonic::Response<ResponseStream<RecordChunk>>, tonic::Status>;
+
+        async fn add_attachment<R: tonic::IntoRequest<AddAttachmentRequest> + Send + 'static>(
+            &mut self,
+            request: R,
+        ) -> Result<tonic::Response<Response>, tonic::Status>;
+
+        async fn get_attachment<R: tonic::IntoRequest<GetAttachmentRequest> + Send + 'static>(
+            &mut self,
+            request: R,
+        ) -> Result<tonic::Response<Attachment>, tonic::Status>;
+
+        async fn list_attachments<R: tonic::IntoRequest<Resource> + Send + 'static>(
+            &mut self,
+            request: R,
+        ) -> Result<tonic::Response<ListOfAttachments>, tonic::Status>;
+
+        async fn service_status<R: tonic::IntoRequest<Empty> + Send + 'static>(
+            &mut self,
+            request: R,
+        ) -> Result<tonic::Response<ServiceStatus>, tonic::Status>;
     }
 }
 
+/// Blind index is not a part of the stored record, pass it with [`AddRequest::with_blind_index()`].
 impl From<telepass_data_model::NewRecord> for Record {
     fn from(record: telepass_data_model::NewRecord) -> Self {
+        let bound_to_resource_name = record.is_bound_to_resource_name();
+        let (resource_name, encryption_output, _blind_index) = record.into_parts();
+        let algorithm = Algorithm::from(encryption_output.algorithm());
         Self {
             resource: Some(Resource {
-                name: record.resource_name,
+                name: resource_name.into(),
             }),
-            encrypted_payload: record.encryption_output.encrypted_payload,
-            salt: record.encryption_output.salt.to_vec(),
+            encrypted_payload: encryption_output.encrypted_payload,
+            salt: encryption_output.salt.as_bytes().to_vec(),
+            kdf_iterations: encryption_output.kdf_iterations,
+            kdf_salt: encryption_output
+                .kdf_salt
+                .map(Vec::from)
+                .unwrap_or_default(),
+            algorithm: algorithm.into(),
+            // Assigned by the storage
+            revision: 0,
+            chunk_count: 0,
+            key_commitment: encryption_output
+                .key_commitment
+                .map(Vec::from)
+                .unwrap_or_default(),
+            output_version: encryption_output.version.into(),
+            bound_to_resource_name,
+        }
+    }
+}
+
+impl From<&telepass_data_model::NewAttachment> for Attachment {
+    fn from(attachment: &telepass_data_model::NewAttachment) -> Self {
+        let encryption_output = attachment.encryption_output();
+        Self {
+            filename: attachment.filename().to_owned(),
+            encrypted_data: encryption_output.encrypted_payload.clone(),
+            salt: encryption_output.salt.as_bytes().to_vec(),
+            kdf_iterations: encryption_output.kdf_iterations,
+            kdf_salt: encryption_output
+                .kdf_salt
+                .map(Vec::from)
+                .unwrap_or_default(),
+            algorithm: Algorithm::from(encryption_output.algorithm()).into(),
+            key_commitment: encryption_output
+                .key_commitment
+                .map(Vec::from)
+                .unwrap_or_default(),
+            size: attachment.size(),
+            output_version: encryption_output.version.into(),
+        }
+    }
+}
+
+/// Error indicating that a stored record can't be decrypted.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidRecordError {
+    #[error("Unknown encryption algorithm `{0}`")]
+    UnknownAlgorithm(i32),
+    #[error(transparent)]
+    WrongSaltLength(#[from] telepass_data_model::crypto::WrongSaltLengthError),
+    #[error(
+        "Key derivation salt must be {} bytes long, got {0}",
+        telepass_data_model::crypto::KDF_SALT_SIZE
+    )]
+    WrongKdfSaltLength(usize),
+    #[error(
+        "Key commitment must be {} bytes long, got {0}",
+        telepass_data_model::crypto::KEY_COMMITMENT_SIZE
+    )]
+    WrongKeyCommitmentLength(usize),
+    #[error("Unsupported encryption output version `{0}`")]
+    UnsupportedOutputVersion(u32),
+}
+
+impl Record {
+    /// Check if the payload is too large to be passed to the Web App in a url.
+    #[must_use]
+    pub fn is_large(&self) -> bool {
+        self.encrypted_payload.len() > MAX_INLINE_PAYLOAD_SIZE
+    }
+
+    /// Get encryption output of the record.
+    ///
+    /// Zero `kdf_iterations`, empty `kdf_salt` and zero `output_version` mean the values used
+    /// before they were stored, empty `key_commitment` means there is none.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the algorithm is unknown, the output version doesn't fit into the format or
+    /// salts or key commitment are of wrong length.
+    pub fn encryption_output(
+        &self,
+    ) -> Result<telepass_data_model::crypto::EncryptionOutput, InvalidRecordError> {
+        let algorithm = Algorithm::try_from(self.algorithm)
+            .map_err(|_unknown| InvalidRecordError::UnknownAlgorithm(self.algorithm))?;
+        let salt = telepass_data_model::crypto::Salt::from_bytes(algorithm.into(), &self.salt)?;
+        let kdf_salt = if self.kdf_salt.is_empty() {
+            None
+        } else {
+            Some(
+                self.kdf_salt
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_err| InvalidRecordError::WrongKdfSaltLength(self.kdf_salt.len()))?,
+            )
+        };
+        let key_commitment = if self.key_commitment.is_empty() {
+            None
+        } else {
+            Some(self.key_commitment.as_slice().try_into().map_err(|_err| {
+                InvalidRecordError::WrongKeyCommitmentLength(self.key_commitment.len())
+            })?)
+        };
+
+        let version = match self.output_version {
+            0 => telepass_data_model::crypto::OUTPUT_VERSION_1,
+            version => u8::try_from(version)
+                .map_err(|_err| InvalidRecordError::UnsupportedOutputVersion(version))?,
+        };
+
+        Ok(telepass_data_model::crypto::EncryptionOutput {
+            version,
+            encrypted_payload: self.encrypted_payload.clone(),
+            salt,
+            kdf_iterations: match self.kdf_iterations {
+                0 => telepass_data_model::crypto::DEFAULT_KDF_ITERATIONS,
+                kdf_iterations => kdf_iterations,
+            },
+            kdf_salt,
+            key_commitment,
+        })
+    }
+}
+
+impl core::fmt::Debug for Record {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use telepass_data_model::crypto::RedactedBytes;
+
+        f.debug_struct("Record")
+            .field("resource", &self.resource)
+            .field("encrypted_payload", &RedactedBytes(&self.encrypted_payload))
+            .field("salt", &RedactedBytes(&self.salt))
+            .field("kdf_iterations", &self.kdf_iterations)
+            .field("kdf_salt", &RedactedBytes(&self.kdf_salt))
+            .field("algorithm", &Algorithm::try_from(self.algorithm))
+            .field("revision", &self.revision)
+            .field("chunk_count", &self.chunk_count)
+            .field("key_commitment", &RedactedBytes(&self.key_commitment))
+            .field("output_version", &self.output_version)
+            .field("bound_to_resource_name", &self.bound_to_resource_name)
+            .finish()
+    }
+}
+
+impl core::fmt::Debug for Attachment {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use telepass_data_model::crypto::RedactedBytes;
+
+        f.debug_struct("Attachment")
+            .field("filename", &self.filename)
+            .field("encrypted_data", &RedactedBytes(&self.encrypted_data))
+            .field("salt", &RedactedBytes(&self.salt))
+            .field("kdf_iterations", &self.kdf_iterations)
+            .field("kdf_salt", &RedactedBytes(&self.kdf_salt))
+            .field("algorithm", &Algorithm::try_from(self.algorithm))
+            .field("key_commitment", &RedactedBytes(&self.key_commitment))
+            .field("size", &self.size)
+            .field("output_version", &self.output_version)
+            .finish()
+    }
+}
+
+impl From<telepass_data_model::crypto::Algorithm> for Algorithm {
+    fn from(algorithm: telepass_data_model::crypto::Algorithm) -> Self {
+        match algorithm {
+            telepass_data_model::crypto::Algorithm::Aes256Gcm => Self::Aes256Gcm,
+            telepass_data_model::crypto::Algorithm::XChaCha20Poly1305 => Self::Xchacha20Poly1305,
+        }
+    }
+}
+
+impl From<Algorithm> for telepass_data_model::crypto::Algorithm {
+    fn from(algorithm: Algorithm) -> Self {
+        match algorithm {
+            Algorithm::Aes256Gcm => Self::Aes256Gcm,
+            Algorithm::Xchacha20Poly1305 => Self::XChaCha20Poly1305,
+        }
+    }
+}
+
+/// Get name of the encryption `algorithm` to pass to the Web App.
+///
+/// Returns [`None`] for the default algorithm, which the Web App assumes if the name is omitted.
+/// Algorithms unknown to the bot are passed by number, so the Web App reports a broken link
+/// instead of failing to decrypt.
+#[must_use]
+pub fn web_app_algorithm_name(algorithm: i32) -> Option<String> {
+    match Algorithm::try_from(algorithm) {
+        Ok(Algorithm::Aes256Gcm) => None,
+        Ok(known) => Some(telepass_data_model::crypto::Algorithm::from(known).to_string()),
+        Err(_unknown) => Some(algorithm.to_string()),
+    }
+}
+
+impl AddRequest {
+    /// Construct request to add `record` which can be safely retried with the same
+    /// `idempotency_key`.
+    ///
+    /// Blind index and password fingerprint are not a part of `record`, attach them with
+    /// [`with_blind_index()`](Self::with_blind_index) and
+    /// [`with_password_fingerprint()`](Self::with_password_fingerprint) along with
+    /// [`with_blind_index_version()`](Self::with_blind_index_version).
+    #[must_use]
+    pub fn new(record: Record, idempotency_key: String) -> Self {
+        Self {
+            resource: record.resource,
+            encrypted_payload: record.encrypted_payload,
+            salt: record.salt,
+            idempotency_key,
+            blind_index: Vec::new(),
+            kdf_iterations: record.kdf_iterations,
+            kdf_salt: record.kdf_salt,
+            algorithm: record.algorithm,
+            password_fingerprint: Vec::new(),
+            key_commitment: record.key_commitment,
+            output_version: record.output_version,
+            bound_to_resource_name: record.bound_to_resource_name,
+            blind_index_version: 0,
+        }
+    }
+
+    /// Set version of the key derivation used to blind the keywords and the password.
+    #[must_use]
+    pub fn with_blind_index_version(mut self, version: u8) -> Self {
+        self.blind_index_version = version.into();
+        self
+    }
+
+    /// Attach blinded keywords of the record content to search the record by.
+    #[must_use]
+    pub fn with_blind_index(
+        mut self,
+        blind_index: &[telepass_data_model::crypto::BlindToken],
+    ) -> Self {
+        self.blind_index = blind_index.iter().map(|token| token.to_vec()).collect();
+        self
+    }
+
+    /// Attach blinded password of the record to detect its reuse across records.
+    #[must_use]
+    pub fn with_password_fingerprint(
+        mut self,
+        password_fingerprint: &telepass_data_model::crypto::PasswordFingerprint,
+    ) -> Self {
+        self.password_fingerprint = password_fingerprint.to_vec();
+        self
+    }
+
+    /// Split request into chunks of `AddChunked` method.
+    #[must_use]
+    pub fn into_chunks(mut self) -> Vec<RecordChunk> {
+        let payload = std::mem::take(&mut self.encrypted_payload);
+        let checksum = Sha256::digest(&payload).to_vec();
+        let mut parts = payload.chunks(MAX_INLINE_PAYLOAD_SIZE).map(<[u8]>::to_vec);
+
+        let first = RecordChunk {
+            add_request: Some(self),
+            record: None,
+            checksum,
+            data: parts.next().unwrap_or_default(),
+        };
+        std::iter::once(first)
+            .chain(parts.map(|data| RecordChunk {
+                data,
+                ..RecordChunk::default()
+            }))
+            .collect()
+    }
+}
+
+/// Add record with `request` to the storage with `client`.
+///
+/// Payloads larger than [`MAX_INLINE_PAYLOAD_SIZE`] are sent in chunks with `AddChunked`.
+pub async fn add_record(
+    client: &mut impl StorageApi,
+    request: AddRequest,
+) -> Result<tonic::Response<AddResponse>, tonic::Status> {
+    if request.encrypted_payload.len() <= MAX_INLINE_PAYLOAD_SIZE {
+        return client.add(request).await;
+    }
+    client
+        .add_chunked(tokio_stream::iter(request.into_chunks()))
+        .await
+}
+
+/// Get record with `request` from the storage with `client`.
+///
+/// Payload of records stored in chunks is got with `GetChunked` and verified with the checksum.
+pub async fn get_record(
+    client: &mut impl StorageApi,
+    request: GetRequest,
+) -> Result<tonic::Response<Record>, tonic::Status> {
+    let response = client.get(request.clone()).await?;
+    if response.get_ref().chunk_count == 0 {
+        return Ok(response);
+    }
+
+    let mut chunks = client.get_chunked(request).await?.into_inner();
+    let mut record = None;
+    let mut checksum = Vec::new();
+    let mut payload = Vec::new();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk?;
+        if chunk.record.is_some() {
+            record = chunk.record;
+            checksum = chunk.checksum;
+        }
+        payload.extend(chunk.data);
+    }
+
+    let mut record =
+        record.ok_or_else(|| tonic::Status::data_loss("Chunked record without the header"))?;
+    if Sha256::digest(&payload).as_slice() != checksum.as_slice() {
+        return Err(tonic::Status::data_loss(
+            "Checksum of the chunked record doesn't match",
+        ));
+    }
+    record.encrypted_payload = payload;
+    Ok(tonic::Response::new(record))
+}
+
+/// Derive idempotency key for the `add` request caused by the message with `message_id`.
+///
+/// Message ids are unique within a chat, so processing the same message again produces the same
+/// key and doesn't add the record twice.
+#[must_use]
+pub fn idempotency_key(
+    chat_id: teloxide::types::ChatId,
+    message_id: teloxide::types::MessageId,
+) -> String {
+    let name = format!("tg://{chat_id}/{}", message_id.0);
+    uuid::Uuid::new_v5(&uuid::Uuid::NAMESPACE_URL, name.as_bytes()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use teloxide::types::{ChatId, MessageId};
+
+    use super::*;
+
+    #[test]
+    fn idempotency_key_is_stable_per_message() {
+        let key = idempotency_key(ChatId(1), MessageId(2));
+
+        assert_eq!(key, idempotency_key(ChatId(1), MessageId(2)));
+        assert_eq!(key.len(), 36);
+        assert_ne!(key, idempotency_key(ChatId(1), MessageId(3)));
+        assert_ne!(key, idempotency_key(ChatId(2), MessageId(2)));
+    }
+
+    #[tokio::test]
+    async fn mock_implements_every_storage_api_method() {
+        fn resource() -> Resource {
+            Resource {
+                name: "test.resource.com".to_owned(),
+            }
+        }
+        fn list() -> ListOfResources {
+            ListOfResources {
+                resources: vec![resource()],
+            }
+        }
+
+        let mut client = MockPasswordStorageClient::default();
+        client
+            .expect_add::<AddRequest>()
+            .return_once(|_request| Ok(tonic::Response::new(AddResponse::default())));
+        client
+            .expect_delete::<DeleteRequest>()
+            .return_once(|_request| Ok(tonic::Response::new(Response {})));
+        client
+            .expect_get::<GetRequest>()
+            .return_once(|_request| Ok(tonic::Response::new(Record::default())));
+        client
+            .expect_list::<Empty>()
+            .return_once(|_request| Ok(tonic::Response::new(list())));
+        client
+            .expect_search::<Resource>()
+            .return_once(|_request| Ok(tonic::Response::new(list())));
+        client
+            .expect_search_blind::<BlindTokens>()
+            .return_once(|_request| Ok(tonic::Response::new(list())));
+        client
+            .expect_add_chunked::<tokio_stream::Iter<std::vec::IntoIter<RecordChunk>>>()
+            .return_once(|_request| Ok(tonic::Response::new(AddResponse::default())));
+        client
+            .expect_get_chunked::<GetRequest>()
+            .return_once(|_request| Ok(tonic::Response::new(Box::pin(tokio_stream::empty()))));
+        client
+            .expect_add_attachment::<AddAttachmentRequest>()
+            .return_once(|_request| Ok(tonic::Response::new(Response {})));
+        client
+            .expect_get_attachment::<GetAttachmentRequest>()
+            .return_once(|_request| Ok(tonic::Response::new(Attachment::default())));
+        client
+            .expect_list_attachments::<Resource>()
+            .return_once(|_request| Ok(tonic::Response::new(ListOfAttachments::default())));
+        client
+            .expect_service_status::<Empty>()
+            .return_once(|_request| Ok(tonic::Response::new(ServiceStatus::default())));
+
+        client.add(AddRequest::default()).await.unwrap();
+        client.delete(DeleteRequest::default()).await.unwrap();
+        client.get(GetRequest::default()).await.unwrap();
+        assert_eq!(client.list(Empty {}).await.unwrap().into_inner(), list());
+        assert_eq!(
+            client.search(resource()).await.unwrap().into_inner(),
+            list()
+        );
+        assert_eq!(
+            client
+                .search_blind(BlindTokens::default())
+                .await
+                .unwrap()
+                .into_inner(),
+            list()
+        );
+        client
+            .add_chunked(tokio_stream::iter(Vec::new()))
+            .await
+            .unwrap();
+        assert!(client
+            .get_chunked(GetRequest::default())
+            .await
+            .unwrap()
+            .into_inner()
+            .next()
+            .await
+            .is_none());
+        client
+            .add_attachment(AddAttachmentRequest::default())
+            .await
+            .unwrap();
+        client
+            .get_attachment(GetAttachmentRequest::default())
+            .await
+            .unwrap();
+        assert!(client
+            .list_attachments(resource())
+            .await
+            .unwrap()
+            .into_inner()
+            .attachments
+            .is_empty());
+        client.service_status(Empty {}).await.unwrap();
+    }
+
+    /// Construct request to add record with `payload_size` bytes of payload.
+    fn large_request(payload_size: usize) -> AddRequest {
+        AddRequest {
+            resource: Some(Resource {
+                name: "large.resource.com".to_owned(),
+            }),
+            encrypted_payload: (0_u8..=u8::MAX).cycle().take(payload_size).collect(),
+            ..AddRequest::default()
+        }
+    }
+
+    #[test]
+    fn add_request_is_split_into_chunks() {
+        let request = large_request(MAX_INLINE_PAYLOAD_SIZE * 2 + 1);
+        let payload = request.encrypted_payload.clone();
+
+        let chunks = request.into_chunks();
+
+        assert_eq!(chunks.len(), 3);
+        let (first, rest) = chunks.split_first().unwrap();
+        assert!(first
+            .add_request
+            .as_ref()
+            .unwrap()
+            .encrypted_payload
+            .is_empty());
+        assert_eq!(first.checksum, Sha256::digest(&payload).to_vec());
+        assert!(rest
+            .iter()
+            .all(|chunk| chunk.add_request.is_none() && chunk.checksum.is_empty()));
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.data.len())
+                .collect::<Vec<_>>(),
+            [MAX_INLINE_PAYLOAD_SIZE, MAX_INLINE_PAYLOAD_SIZE, 1]
+        );
+        assert_eq!(
+            chunks
+                .into_iter()
+                .flat_map(|chunk| chunk.data)
+                .collect::<Vec<_>>(),
+            payload
+        );
+    }
+
+    #[tokio::test]
+    async fn add_record_sends_large_payload_in_chunks() {
+        let mut client = MockPasswordStorageClient::default();
+        client
+            .expect_add::<AddRequest>()
+            .withf(|request| request.encrypted_payload.len() == MAX_INLINE_PAYLOAD_SIZE)
+            .return_once(|_request| Ok(tonic::Response::new(AddResponse::default())));
+        client
+            .expect_add_chunked::<tokio_stream::Iter<std::vec::IntoIter<RecordChunk>>>()
+            .return_once(|_request| Ok(tonic::Response::new(AddResponse::default())));
+
+        add_record(&mut client, large_request(MAX_INLINE_PAYLOAD_SIZE))
+            .await
+            .unwrap();
+        add_record(&mut client, large_request(MAX_INLINE_PAYLOAD_SIZE + 1))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn get_record_reassembles_chunked_record() {
+        fn header() -> Record {
+            Record {
+                resource: Some(Resource {
+                    name: "large.resource.com".to_owned(),
+                }),
+                chunk_count: 2,
+                ..Record::default()
+            }
+        }
+        fn chunks(checksum: Vec<u8>) -> Vec<Result<RecordChunk, tonic::Status>> {
+            vec![
+                Ok(RecordChunk {
+                    record: Some(header()),
+                    checksum,
+                    data: b"first ".to_vec(),
+                    ..RecordChunk::default()
+                }),
+                Ok(RecordChunk {
+                    data: b"second".to_vec(),
+                    ..RecordChunk::default()
+                }),
+            ]
+        }
+
+        let mut client = MockPasswordStorageClient::default();
+        client
+            .expect_get::<GetRequest>()
+            .times(2)
+            .returning(|_request| Ok(tonic::Response::new(header())));
+        let mut checksums = vec![b"wrong".to_vec(), Sha256::digest(b"first second").to_vec()];
+        client
+            .expect_get_chunked::<GetRequest>()
+            .times(2)
+            .returning(move |_request| {
+                let checksum = checksums.pop().unwrap();
+                Ok(tonic::Response::new(Box::pin(tokio_stream::iter(chunks(
+                    checksum,
+                )))))
+            });
+
+        let request = GetRequest {
+            name: "large.resource.com".to_owned(),
+            ..GetRequest::default()
+        };
+        let record = get_record(&mut client, request.clone())
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(record.encrypted_payload, b"first second");
+        assert_eq!(record.resource, header().resource);
+
+        let status = get_record(&mut client, request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::DataLoss);
+    }
+
+    #[test]
+    fn record_debug_hides_bytes() {
+        let record = Record {
+            resource: Some(Resource {
+                name: "bank.com".to_owned(),
+            }),
+            encrypted_payload: vec![0xAB; 96],
+            salt: vec![0xCD; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+            kdf_iterations: 0,
+            kdf_salt: Vec::new(),
+            algorithm: 0,
+            revision: 0,
+            chunk_count: 0,
+            key_commitment: Vec::new(),
+            output_version: 0,
+            bound_to_resource_name: false,
+        };
+
+        let debug = format!("{record:?}");
+
+        assert!(!debug.contains("171") && !debug.contains("205"), "{debug}");
+        assert!(debug.contains("bank.com"), "{debug}");
+        assert!(debug.contains("encrypted_payload: 96 bytes #"), "{debug}");
+        assert!(debug.contains("salt: 12 bytes #"), "{debug}");
+    }
+
+    #[test]
+    fn legacy_record_encryption_output_uses_defaults() {
+        let record = Record {
+            resource: None,
+            encrypted_payload: b"payload".to_vec(),
+            salt: vec![1; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+            kdf_iterations: 0,
+            kdf_salt: Vec::new(),
+            algorithm: 0,
+            revision: 0,
+            chunk_count: 0,
+            key_commitment: Vec::new(),
+            output_version: 0,
+            bound_to_resource_name: false,
+        };
+
+        let output = record.encryption_output().unwrap();
+        assert_eq!(
+            output.algorithm(),
+            telepass_data_model::crypto::Algorithm::Aes256Gcm
+        );
+        assert_eq!(
+            output.kdf_iterations,
+            telepass_data_model::crypto::DEFAULT_KDF_ITERATIONS
+        );
+        assert_eq!(output.kdf_salt, None);
+        assert_eq!(
+            output.version,
+            telepass_data_model::crypto::OUTPUT_VERSION_1
+        );
+
+        let unknown_algorithm = Record {
+            algorithm: 42,
+            ..record.clone()
+        };
+        assert_eq!(
+            unknown_algorithm.encryption_output(),
+            Err(InvalidRecordError::UnknownAlgorithm(42))
+        );
+
+        let short_kdf_salt = Record {
+            kdf_salt: b"kdf_salt".to_vec(),
+            ..record
+        };
+        assert_eq!(
+            short_kdf_salt.encryption_output(),
+            Err(InvalidRecordError::WrongKdfSaltLength(8))
+        );
+    }
+
+    #[test]
+    fn output_version_round_trips_through_record() {
+        let record = Record::from(
+            telepass_data_model::NewRecord::builder()
+                .resource_name("test.resource.com")
+                .encryption_output(telepass_data_model::crypto::EncryptionOutput {
+                    version: telepass_data_model::crypto::OUTPUT_VERSION_2,
+                    encrypted_payload: b"SomeSecret".to_vec(),
+                    salt: telepass_data_model::crypto::Salt::Aes256Gcm(
+                        [1; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+                    ),
+                    kdf_iterations: telepass_data_model::crypto::DEFAULT_KDF_ITERATIONS,
+                    kdf_salt: None,
+                    key_commitment: None,
+                })
+                .build()
+                .unwrap(),
+        );
+        assert_eq!(record.output_version, 2);
+        assert_eq!(
+            record.encryption_output().unwrap().version,
+            telepass_data_model::crypto::OUTPUT_VERSION_2
+        );
+
+        let too_large_version = Record {
+            output_version: 256,
+            ..record
+        };
+        assert_eq!(
+            too_large_version.encryption_output(),
+            Err(InvalidRecordError::UnsupportedOutputVersion(256))
+        );
+    }
+
+    #[test]
+    fn new_record_keeps_binding_to_resource_name() {
+        for bound in [false, true] {
+            let record = telepass_data_model::NewRecord::builder()
+                .resource_name("test.resource.com")
+                .encryption_output(telepass_data_model::crypto::EncryptionOutput {
+                    version: telepass_data_model::crypto::OUTPUT_VERSION_1,
+                    encrypted_payload: b"SomeSecret".to_vec(),
+                    salt: telepass_data_model::crypto::Salt::Aes256Gcm(
+                        [1; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+                    ),
+                    kdf_iterations: telepass_data_model::crypto::DEFAULT_KDF_ITERATIONS,
+                    kdf_salt: None,
+                    key_commitment: None,
+                })
+                .bound_to_resource_name(bound)
+                .build()
+                .unwrap();
+
+            assert_eq!(Record::from(record).bound_to_resource_name, bound);
         }
     }
 }
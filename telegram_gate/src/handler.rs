@@ -0,0 +1,100 @@
+//! Module with handling of parsed updates independent of the dispatcher.
+//!
+//! Used by the executable and by the simulation available with `test-doubles` feature.
+
+use tracing::{error, info};
+
+#[mockall_double::double]
+use crate::context::Context;
+use crate::{
+    button::ButtonBox,
+    command,
+    footer::{self, MessageClass},
+    message,
+    state::State,
+    transition::{FailedTransition, TransitionFailureReason, TryFromTransition as _},
+};
+
+/// Enum with either [`command::Command`] or [`message::MessageBox`] parsed from a message.
+#[derive(Debug)]
+pub enum CommandOrMessage {
+    /// Command variant.
+    Command(command::Command),
+    /// Message variant.
+    Message(message::MessageBox),
+}
+
+impl CommandOrMessage {
+    /// Check if it can be handled while password storage is unavailable.
+    ///
+    /// Only leads to the degraded main menu and retrying from there are allowed.
+    #[must_use]
+    pub const fn is_allowed_while_unavailable(&self) -> bool {
+        matches!(
+            *self,
+            Self::Command(command::Command::Start(_))
+                | Self::Message(message::MessageBox::RetryStorage(_))
+        )
+    }
+}
+
+/// Perform transition from `state` by `command_or_message`.
+///
+/// Returns the old state if transition failed, reporting the failure to the user.
+pub async fn handle_command_or_message(
+    state: State,
+    command_or_message: CommandOrMessage,
+    context: &Context,
+) -> State {
+    let res = match command_or_message {
+        CommandOrMessage::Command(command) => {
+            State::try_from_transition(state, command, context).await
+        }
+        CommandOrMessage::Message(message) => {
+            State::try_from_transition(state, message, context).await
+        }
+    };
+
+    // See: https://rust-lang.github.io/rust-clippy/master/index.html#/large_futures
+    Box::pin(unwrap_state(res, context)).await
+}
+
+/// Perform transition from `state` by pressed `button`.
+///
+/// Returns the old state if transition failed, reporting the failure to the user.
+pub async fn handle_button(state: State, button: ButtonBox, context: &Context) -> State {
+    // See: https://rust-lang.github.io/rust-clippy/master/index.html#/large_futures
+    let res = Box::pin(State::try_from_transition(state, button, context)).await;
+    unwrap_state(res, context).await
+}
+
+/// Unpack [`State`] from [`Result`] sending message to the user.
+async fn unwrap_state(res: Result<State, FailedTransition<State>>, context: &Context) -> State {
+    match res {
+        Ok(new_state) => {
+            info!(?new_state, "Transition succeed");
+            new_state
+        }
+        Err(failed_transition) => {
+            let failure_reason = failed_transition.reason;
+            match failure_reason {
+                TransitionFailureReason::User(reason) => {
+                    let _ignored = footer::send_text(context, reason, MessageClass::Plain).await;
+                }
+                TransitionFailureReason::Internal(reason) => {
+                    let _ignored = footer::send_text(
+                        context,
+                        "Internal error occurred, check the server logs.",
+                        MessageClass::Plain,
+                    )
+                    .await;
+                    error!(?reason, "Internal error occurred");
+                }
+            }
+
+            let old_state = failed_transition.target;
+            info!(?old_state, "Transition failed");
+            old_state
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Module to passively prove that the bot is alive by periodically editing a pinned message.
+
+use std::time::{Duration, SystemTime};
+
+#[cfg(not(test))]
+use teloxide::requests::Requester as _;
+use teloxide::{
+    types::{ChatId, MessageId},
+    ApiError, RequestError,
+};
+use tracing::{debug, info, warn};
+
+use crate::{
+    time_zone::{self, Tz},
+    Bot, TelegramMessageGettersExt as _,
+};
+
+/// Default interval between heartbeat message edits.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Number of consecutive failures after which edits are paused.
+pub const FAILURES_BEFORE_PAUSE: u32 = 3;
+
+/// Maximum number of beats to skip in a row while edits are paused.
+pub const MAX_PAUSED_BEATS: u32 = 16;
+
+/// Heartbeat settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    /// Chat to keep the heartbeat message in.
+    pub chat_id: ChatId,
+    /// Interval between heartbeat message edits.
+    pub interval: Duration,
+}
+
+/// Outcome of a single [`Heartbeat::beat()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Beat {
+    /// New heartbeat message was sent and pinned.
+    Created(MessageId),
+    /// Existing heartbeat message was edited.
+    Edited,
+    /// Telegram request failed.
+    Failed,
+    /// Telegram was not touched because of repeated failures.
+    Paused,
+}
+
+/// Heartbeat keeping a pinned message with the time of the last successful check.
+///
+/// Edits are paused for an exponentially growing number of beats after
+/// [`FAILURES_BEFORE_PAUSE`] consecutive failures, so that the bot doesn't spiral into
+/// Telegram rate limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heartbeat {
+    /// Chat to keep the heartbeat message in.
+    chat_id: ChatId,
+    /// Id of the heartbeat message if it was already created.
+    message_id: Option<MessageId>,
+    /// Number of failed beats in a row.
+    consecutive_failures: u32,
+    /// Number of beats left to skip.
+    paused_beats: u32,
+}
+
+impl Heartbeat {
+    /// Create new heartbeat reusing `message_id` if the message was created before.
+    #[must_use]
+    pub const fn new(chat_id: ChatId, message_id: Option<MessageId>) -> Self {
+        Self {
+            chat_id,
+            message_id,
+            consecutive_failures: 0,
+            paused_beats: 0,
+        }
+    }
+
+    /// Get id of the heartbeat message if it's created.
+    #[must_use]
+    pub const fn message_id(&self) -> Option<MessageId> {
+        self.message_id
+    }
+
+    /// Create and pin the heartbeat message on the first run, edit it otherwise.
+    ///
+    /// If the message was deleted, it will be recreated on the next beat.
+    /// `now` is shown in `time_zone` of the chat, see [`time_zone::format_local()`].
+    pub async fn beat(&mut self, bot: &Bot, now: SystemTime, time_zone: Option<Tz>) -> Beat {
+        if self.paused_beats > 0 {
+            self.paused_beats = self.paused_beats.saturating_sub(1);
+            debug!(paused_beats = self.paused_beats, "Heartbeat is paused");
+            return Beat::Paused;
+        }
+
+        let text = heartbeat_text(now, time_zone);
+        let beat = match self.message_id {
+            Some(message_id) => self.edit(bot, message_id, text).await,
+            None => self.create(bot, text).await,
+        };
+
+        if beat == Beat::Failed {
+            self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+            if let Some(exponent) = self.consecutive_failures.checked_sub(FAILURES_BEFORE_PAUSE) {
+                self.paused_beats = 1_u32
+                    .checked_shl(exponent)
+                    .unwrap_or(MAX_PAUSED_BEATS)
+                    .min(MAX_PAUSED_BEATS);
+                warn!(
+                    consecutive_failures = self.consecutive_failures,
+                    paused_beats = self.paused_beats,
+                    "Heartbeat keeps failing, pausing it"
+                );
+            }
+        } else {
+            self.consecutive_failures = 0;
+        }
+
+        beat
+    }
+
+    /// Send and pin new heartbeat message.
+    async fn create(&mut self, bot: &Bot, text: String) -> Beat {
+        let message = match bot.send_message(self.chat_id, text).await {
+            Ok(message) => message,
+            Err(error) => {
+                warn!(%error, "Failed to send heartbeat message");
+                return Beat::Failed;
+            }
+        };
+
+        let message_id = message.id();
+        self.message_id = Some(message_id);
+        info!(%message_id, "Heartbeat message created");
+
+        if let Err(error) = bot.pin_chat_message(self.chat_id, message_id).await {
+            warn!(%error, "Failed to pin heartbeat message");
+        }
+
+        Beat::Created(message_id)
+    }
+
+    /// Edit existing heartbeat message.
+    async fn edit(&mut self, bot: &Bot, message_id: MessageId, text: String) -> Beat {
+        match bot.edit_message_text(self.chat_id, message_id, text).await {
+            Ok(_) | Err(RequestError::Api(ApiError::MessageNotModified)) => Beat::Edited,
+            Err(RequestError::Api(ApiError::MessageToEditNotFound)) => {
+                warn!(%message_id, "Heartbeat message was deleted, it will be recreated");
+                self.message_id = None;
+                Beat::Failed
+            }
+            Err(error) => {
+                warn!(%error, "Failed to edit heartbeat message");
+                Beat::Failed
+            }
+        }
+    }
+}
+
+/// Construct heartbeat message text with time of `now` in `time_zone`.
+fn heartbeat_text(now: SystemTime, time_zone: Option<Tz>) -> String {
+    format!(
+        "\u{2705} Telepass online, last check {}",
+        time_zone::format_local(now, time_zone)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::arithmetic_side_effects, reason = "it's ok in tests")]
+
+    use teloxide::types::Seconds;
+
+    use super::*;
+    use crate::test_utils::mock_bot::{MockBotBuilder, CHAT_ID};
+
+    const MESSAGE_ID: MessageId = MessageId(42);
+
+    /// Construct time of `hours`:`minutes` UTC.
+    fn at(hours: u64, minutes: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs((hours * 60 + minutes) * 60)
+    }
+
+    #[test]
+    fn heartbeat_text_contains_utc_time() {
+        assert_eq!(
+            heartbeat_text(at(12, 3), None),
+            "\u{2705} Telepass online, last check 1970-01-01 12:03 (UTC)"
+        );
+    }
+
+    #[test]
+    fn heartbeat_text_contains_local_time() {
+        assert_eq!(
+            heartbeat_text(at(12, 3), Some(Tz::Asia__Tokyo)),
+            "\u{2705} Telepass online, last check 1970-01-01 21:03 JST"
+        );
+    }
+
+    #[tokio::test]
+    async fn first_beat_creates_and_pins_message() {
+        let bot = MockBotBuilder::new()
+            .expect_send_message(heartbeat_text(at(12, 3), None))
+            .expect_into_future_with_id(MESSAGE_ID)
+            .expect_pin_chat_message(MESSAGE_ID)
+            .build();
+        let mut heartbeat = Heartbeat::new(CHAT_ID, None);
+
+        assert_eq!(
+            heartbeat.beat(&bot, at(12, 3), None).await,
+            Beat::Created(MESSAGE_ID)
+        );
+        assert_eq!(heartbeat.message_id(), Some(MESSAGE_ID));
+    }
+
+    #[tokio::test]
+    async fn subsequent_beats_edit_message() {
+        let bot = MockBotBuilder::new()
+            .expect_edit_message_text(MESSAGE_ID, heartbeat_text(at(12, 3), None))
+            .expect_into_future()
+            .expect_edit_message_text(MESSAGE_ID, heartbeat_text(at(12, 13), None))
+            .expect_into_future()
+            .build();
+        let mut heartbeat = Heartbeat::new(CHAT_ID, Some(MESSAGE_ID));
+
+        assert_eq!(heartbeat.beat(&bot, at(12, 3), None).await, Beat::Edited);
+        assert_eq!(heartbeat.beat(&bot, at(12, 13), None).await, Beat::Edited);
+        assert_eq!(heartbeat.message_id(), Some(MESSAGE_ID));
+    }
+
+    #[tokio::test]
+    async fn repeated_failures_pause_edits() {
+        let rate_limited = || RequestError::RetryAfter(Seconds::from_seconds(60));
+        let bot = MockBotBuilder::new()
+            .expect_edit_message_text(MESSAGE_ID, heartbeat_text(at(12, 0), None))
+            .expect_into_future_with_error(rate_limited())
+            .expect_edit_message_text(MESSAGE_ID, heartbeat_text(at(12, 10), None))
+            .expect_into_future_with_error(rate_limited())
+            .expect_edit_message_text(MESSAGE_ID, heartbeat_text(at(12, 20), None))
+            .expect_into_future_with_error(rate_limited())
+            .expect_edit_message_text(MESSAGE_ID, heartbeat_text(at(12, 40), None))
+            .expect_into_future_with_error(rate_limited())
+            .expect_edit_message_text(MESSAGE_ID, heartbeat_text(at(13, 10), None))
+            .expect_into_future()
+            .build();
+        let mut heartbeat = Heartbeat::new(CHAT_ID, Some(MESSAGE_ID));
+
+        let beats = [
+            (at(12, 0), Beat::Failed),
+            (at(12, 10), Beat::Failed),
+            // Third failure in a row pauses edits for one beat
+            (at(12, 20), Beat::Failed),
+            (at(12, 30), Beat::Paused),
+            // Next failure doubles the pause
+            (at(12, 40), Beat::Failed),
+            (at(12, 50), Beat::Paused),
+            (at(13, 0), Beat::Paused),
+            (at(13, 10), Beat::Edited),
+        ];
+        for (now, expected_beat) in beats {
+            assert_eq!(heartbeat.beat(&bot, now, None).await, expected_beat);
+        }
+        assert_eq!(heartbeat, Heartbeat::new(CHAT_ID, Some(MESSAGE_ID)));
+    }
+
+    #[tokio::test]
+    async fn deleted_message_is_recreated() {
+        let new_message_id = MessageId(43);
+        let bot = MockBotBuilder::new()
+            .expect_edit_message_text(MESSAGE_ID, heartbeat_text(at(12, 3), None))
+            .expect_into_future_with_error(RequestError::Api(ApiError::MessageToEditNotFound))
+            .expect_send_message(heartbeat_text(at(12, 13), None))
+            .expect_into_future_with_id(new_message_id)
+            .expect_pin_chat_message(new_message_id)
+            .build();
+        let mut heartbeat = Heartbeat::new(CHAT_ID, Some(MESSAGE_ID));
+
+        assert_eq!(heartbeat.beat(&bot, at(12, 3), None).await, Beat::Failed);
+        assert_eq!(
+            heartbeat.beat(&bot, at(12, 13), None).await,
+            Beat::Created(new_message_id)
+        );
+        assert_eq!(heartbeat.message_id(), Some(new_message_id));
+    }
+}
@@ -0,0 +1,136 @@
+//! Module with [`ResourcePrefix`] marking resource names on keyboard buttons.
+
+#![expect(clippy::non_ascii_literal, reason = "prefixes are emojis")]
+
+/// Prefix added to resource names on keyboard buttons.
+///
+/// Configured prefix may be changed between deployments, so buttons of already sent keyboards
+/// with [`ResourcePrefix::LEGACY`] prefix are still recognized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourcePrefix {
+    /// Prefix with a trailing separator.
+    prefix_with_separator: String,
+}
+
+impl ResourcePrefix {
+    /// Prefix used before it became configurable.
+    pub const LEGACY: &'static str = "🔑";
+    /// Separator between prefix and resource name.
+    const SEPARATOR: char = ' ';
+
+    /// Construct new [`ResourcePrefix`].
+    ///
+    /// Surrounding whitespaces are trimmed. Empty `prefix` falls back to
+    /// [`ResourcePrefix::LEGACY`].
+    #[must_use]
+    pub fn new(prefix: &str) -> Self {
+        let prefix = prefix.trim();
+        let prefix = if prefix.is_empty() {
+            Self::LEGACY
+        } else {
+            prefix
+        };
+
+        Self {
+            prefix_with_separator: format!("{prefix}{}", Self::SEPARATOR),
+        }
+    }
+
+    /// Get prefix without separator.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        self.prefix_with_separator
+            .strip_suffix(Self::SEPARATOR)
+            .unwrap_or(&self.prefix_with_separator)
+    }
+
+    /// Construct keyboard button text for resource with `name`.
+    #[must_use]
+    pub fn prefix_resource(&self, name: &str) -> String {
+        format!("{}{name}", self.prefix_with_separator)
+    }
+
+    /// Get resource name from keyboard button `text`.
+    ///
+    /// Only one prefix is stripped, so names starting with the prefix are preserved.
+    /// Configured prefix takes precedence over [`ResourcePrefix::LEGACY`] one.
+    /// Returns `text` as is if there is no prefix.
+    #[must_use]
+    pub fn strip_resource_prefix<'text>(&self, text: &'text str) -> &'text str {
+        text.strip_prefix(&self.prefix_with_separator)
+            .or_else(|| {
+                text.strip_prefix(Self::LEGACY)
+                    .and_then(|rest| rest.strip_prefix(Self::SEPARATOR))
+            })
+            .unwrap_or(text)
+    }
+}
+
+impl Default for ResourcePrefix {
+    fn default() -> Self {
+        Self::new(Self::LEGACY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_prefix_round_trip_success() {
+        let prefix = ResourcePrefix::default();
+
+        let text = prefix.prefix_resource("example.com");
+        assert_eq!(text, "🔑 example.com");
+        assert_eq!(prefix.strip_resource_prefix(&text), "example.com");
+    }
+
+    #[test]
+    fn custom_prefix_round_trip_success() {
+        let prefix = ResourcePrefix::new(" 🗝️ ");
+        assert_eq!(prefix.as_str(), "🗝️");
+
+        let text = prefix.prefix_resource("example.com");
+        assert_eq!(text, "🗝️ example.com");
+        assert_eq!(prefix.strip_resource_prefix(&text), "example.com");
+    }
+
+    #[test]
+    fn empty_prefix_falls_back_to_legacy() {
+        assert_eq!(ResourcePrefix::new("  "), ResourcePrefix::default());
+    }
+
+    #[test]
+    fn legacy_prefix_is_stripped_with_custom_prefix() {
+        let prefix = ResourcePrefix::new("🗝️");
+
+        assert_eq!(
+            prefix.strip_resource_prefix("🔑 example.com"),
+            "example.com"
+        );
+    }
+
+    #[test]
+    fn names_starting_with_prefix_are_preserved() {
+        let prefix = ResourcePrefix::new("🗝️");
+
+        for name in ["🗝️ vault", "🔑 vault", "🗝️vault", "🔑vault"] {
+            let text = prefix.prefix_resource(name);
+            assert_eq!(prefix.strip_resource_prefix(&text), name);
+        }
+
+        let legacy = ResourcePrefix::default();
+        assert_eq!(legacy.strip_resource_prefix("🔑 🔑 vault"), "🔑 vault");
+    }
+
+    #[test]
+    fn text_without_prefix_is_returned_as_is() {
+        let prefix = ResourcePrefix::new("🗝️");
+
+        assert_eq!(prefix.strip_resource_prefix("example.com"), "example.com");
+        assert_eq!(
+            prefix.strip_resource_prefix("🗝️example.com"),
+            "🗝️example.com"
+        );
+    }
+}
@@ -2,30 +2,65 @@
 
 use std::sync::Arc;
 
-use cfg_if::cfg_if;
 use teloxide::prelude::*;
 
-cfg_if! {
-    if #[cfg(test)] {
-        pub type Bot = test_utils::mock_bot::MockBot;
-        pub type TelegramMessage = test_utils::mock_bot::MockMessage;
-        pub type PasswordStorageClient = grpc::MockPasswordStorageClient;
-    } else {
-        pub type Bot = teloxide::Bot;
-        pub type TelegramMessage = teloxide::types::Message;
-        pub type PasswordStorageClient =
-            grpc::password_storage_client::PasswordStorageClient<tonic::transport::Channel>;
-    }
-}
+/// Telegram bot. Mocked in tests.
+#[cfg(not(test))]
+pub type Bot = teloxide::Bot;
+/// Telegram bot. Mocked in tests.
+#[cfg(test)]
+pub type Bot = test_utils::mock_bot::MockBot;
+
+/// Telegram message. Mocked in tests.
+#[cfg(not(test))]
+pub type TelegramMessage = teloxide::types::Message;
+/// Telegram message. Mocked in tests.
+#[cfg(test)]
+pub type TelegramMessage = test_utils::mock_bot::MockMessage;
+
+/// Request to send a message. Mocked in tests.
+#[cfg(not(test))]
+pub type SendMessage = <teloxide::Bot as teloxide::requests::Requester>::SendMessage;
+/// Request to send a message. Mocked in tests.
+#[cfg(test)]
+pub type SendMessage = test_utils::mock_bot::MockSendMessage;
+
+/// Client of the password storage service. Mocked in tests.
+#[cfg(not(test))]
+pub type PasswordStorageClient = grpc::StorageClient;
+/// Client of the password storage service. Mocked in tests.
+#[cfg(test)]
+pub type PasswordStorageClient = grpc::MockPasswordStorageClient;
 
 pub mod button;
 pub mod command;
 pub mod context;
+pub mod final_message;
+pub mod footer;
 pub mod grpc;
+pub mod handler;
+pub mod heartbeat;
+pub mod keyboard;
+pub mod markdown;
 pub mod message;
+pub mod message_group;
+pub mod release_notes;
+pub mod replay_cache;
+pub mod role;
+pub mod sensitive_message;
 pub mod state;
-pub(crate) mod test_utils;
+pub mod storage_health;
+pub mod task_registry;
+#[cfg(any(test, feature = "test-doubles"))]
+pub mod test_utils;
+pub mod time_zone;
+#[cfg(feature = "tls")]
+pub mod tls;
 pub mod transition;
+#[cfg(feature = "token-endpoint")]
+pub mod unlock_endpoint;
+pub mod unlock_token;
+pub mod update_limit;
 
 /// Trait to extend [`teloxide::types::Me`] with `user()` method.
 pub trait UserExt {
@@ -1,19 +1,37 @@
 //! Telegram Gate controls the bot using Telegram API.
 
-#![cfg(feature = "executable")]
-
-use std::{str::FromStr as _, sync::Arc};
+use std::{
+    path::PathBuf,
+    str::FromStr as _,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
 use color_eyre::{
     eyre::{eyre, WrapErr as _},
     Result,
 };
 use dotenvy::dotenv;
+use telepass_crypto::signed_link::LinkKey;
 use telepass_telegram_gate::{
     button::ButtonBox,
-    command, context, message,
+    command, context,
+    final_message::{self, RetryQueue},
+    footer::{self, MessageClass, MessageFooter},
+    handler::{self, CommandOrMessage},
+    heartbeat::{self, Heartbeat},
+    keyboard::ResourcePrefix,
+    message,
+    release_notes::SeenVersions,
+    replay_cache::ReplayCache,
+    role::{OwnerRoles, Role},
+    sensitive_message::{self, DeletionQueue},
     state::State,
-    transition::{FailedTransition, TransitionFailureReason, TryFromTransition},
+    storage_health::{self, Backoff, Readiness, StorageAvailability},
+    task_registry::TaskRegistry,
+    time_zone::TimeZones,
+    unlock_token::UnlockTokenStore,
+    update_limit::{self, UpdateLimit},
     PasswordStorageClient, TelegramMessage,
 };
 use teloxide::{
@@ -23,7 +41,10 @@ use teloxide::{
 };
 use tokio::sync::Mutex;
 use tonic::transport::Channel;
-use tracing::{error, info, instrument, warn, Level};
+use tonic_health::pb::{
+    health_check_response::ServingStatus, health_client::HealthClient, HealthCheckRequest,
+};
+use tracing::{info, instrument, warn, Level};
 use tracing_subscriber::{filter::LevelFilter, EnvFilter, FmtSubscriber};
 use url::Url;
 
@@ -36,10 +57,53 @@ async fn main() -> Result<()> {
     let _ignored = dotenv();
 
     let bot = Bot::from_env();
+    let mut tasks = TaskRegistry::default();
     let web_app_url = Arc::new(read_web_app_url_from_env()?);
-    let storage_client = Arc::new(Mutex::new(setup_storage_client().await?));
-    let owner_user_id = read_owner_user_id_from_env()?;
+    let (storage_client, health_client) = setup_storage_clients()?;
+    let storage_client = Arc::new(Mutex::new(storage_client));
+    let owner_roles = Arc::new(read_owner_roles_from_env()?);
+    let unlock_token_store = setup_unlock_token_store(&web_app_url, &mut tasks)?;
+    let (final_message_retries, final_message_retry_worker) =
+        final_message::retry_queue(final_message::DEFAULT_RETRY_DELAY);
+    tasks.spawn(
+        "final message retries",
+        final_message_retry_worker.run(bot.clone()),
+    );
+    let (sensitive_deletions, sensitive_deletion_worker) =
+        sensitive_message::deletion_queue(sensitive_message::DEFAULT_DELETION_DELAY);
+    tasks.spawn(
+        "sensitive message deletions",
+        sensitive_deletion_worker.run(bot.clone()),
+    );
+    let time_zones = Arc::new(setup_time_zones()?);
+    let ui_settings = Arc::new(UiSettings {
+        web_app_url,
+        resource_prefix: Arc::new(read_resource_prefix_from_env()?),
+        message_footer: Arc::new(read_message_footer_from_env()?),
+        final_message_retries,
+        sensitive_deletions,
+        seen_versions: Arc::new(setup_seen_versions()?),
+        temp_link_key: read_temp_link_key_from_env()?.map(Arc::new),
+        web_app_replays: Arc::new(ReplayCache::default()),
+        time_zones: Arc::clone(&time_zones),
+        update_limit: UpdateLimit::new(
+            read_max_concurrent_updates_from_env()?,
+            update_limit::DEFAULT_SLOW_WAIT,
+        ),
+    });
+    let storage_availability =
+        wait_for_storage(health_client, read_startup_wait_from_env()?, &mut tasks).await;
+    if let Some(heartbeat_config) = read_heartbeat_config_from_env()? {
+        spawn_heartbeat(
+            &mut tasks,
+            bot.clone(),
+            heartbeat_config,
+            read_optional_path_from_env("HEARTBEAT_MESSAGE_ID_PATH")?,
+            time_zones,
+        );
+    }
 
+    let filter_owner_roles = Arc::clone(&owner_roles);
     let handler = dptree::entry()
         .branch(
             Update::filter_message()
@@ -48,12 +112,7 @@ async fn main() -> Result<()> {
                         return false;
                     }
 
-                    let Some(owner_user_id) = owner_user_id else {
-                        return true; // Allow anyone if no owner specified
-                    };
-
-                    let owner_chat_id = teloxide::types::ChatId::from(owner_user_id);
-                    if msg.chat.id != owner_chat_id {
+                    if resolve_role(&filter_owner_roles, msg.chat.id).is_none() {
                         warn!(?msg, "Someone has tried to access the bot, access denied");
                         return false;
                     }
@@ -64,63 +123,98 @@ async fn main() -> Result<()> {
         )
         .branch(Update::filter_callback_query().endpoint(button_callback_handler));
 
+    let mut dispatcher = Dispatcher::builder(bot, handler)
+        .dependencies(dptree::deps![
+            InMemStorage::<State>::new(),
+            Arc::clone(&ui_settings),
+            Arc::clone(&storage_client),
+            owner_roles,
+            unlock_token_store,
+            storage_availability
+        ])
+        .enable_ctrlc_handler()
+        .build();
+    // Dispatching stops on Ctrl-C, then background tasks are shut down.
     // See: https://rust-lang.github.io/rust-clippy/master/index.html#/large_futures
-    Box::pin(
-        Dispatcher::builder(bot, handler)
-            .dependencies(dptree::deps![
-                InMemStorage::<State>::new(),
-                Arc::clone(&web_app_url),
-                Arc::clone(&storage_client)
-            ])
-            .enable_ctrlc_handler()
-            .build()
-            .dispatch(),
-    )
-    .await;
+    tasks.run_until(Box::pin(dispatcher.dispatch())).await;
+
+    info!(metrics = ?ui_settings.update_limit.metrics(), "Update queue waits");
+    info!("Bye!");
 
     Ok(())
 }
 
-#[instrument(skip(bot, me, state_storage, storage_client))]
+#[instrument(skip(
+    bot,
+    me,
+    state_storage,
+    storage_client,
+    owner_roles,
+    unlock_token_store,
+    storage_availability
+))]
+#[expect(
+    clippy::too_many_arguments,
+    reason = "dependencies are injected by `dptree`"
+)]
 async fn message_handler(
     bot: Bot,
     msg: teloxide::types::Message,
     me: Me,
     state_storage: Arc<InMemStorage<State>>,
-    web_app_url: Arc<Url>,
+    ui_settings: Arc<UiSettings>,
     storage_client: Arc<Mutex<PasswordStorageClient>>,
+    owner_roles: Arc<OwnerRoles>,
+    unlock_token_store: Option<Arc<UnlockTokenStore>>,
+    storage_availability: Arc<StorageAvailability>,
 ) -> color_eyre::Result<()> {
+    let _permit = ui_settings.update_limit.acquire().await;
     info!("Handling message");
 
     let chat_id = msg.chat.id;
+    let Some(role) = resolve_role(&owner_roles, chat_id) else {
+        warn!("Access denied");
+        return Ok(());
+    };
 
     let Some(command_or_message) = parse_command_or_message(msg, me.username()) else {
         bot.send_message(chat_id, "Unsupported message").await?;
         return Ok(());
     };
 
+    if !storage_availability.is_available() && !command_or_message.is_allowed_while_unavailable() {
+        bot.send_message(chat_id, storage_health::UNAVAILABLE_MESSAGE)
+            .await?;
+        return Ok(());
+    }
+
     let state = drain_state(Arc::clone(&state_storage), chat_id).await?;
 
     let end_state = {
-        let context = context::Context::new(bot, chat_id, web_app_url, storage_client);
-
-        let res = match command_or_message {
-            CommandOrMessage::Command(command) => {
-                <State as TryFromTransition<State, command::Command>>::try_from_transition(
-                    state, command, &context,
-                )
-                .await
-            }
-            CommandOrMessage::Message(message) => {
-                <State as TryFromTransition<State, message::MessageBox>>::try_from_transition(
-                    state, message, &context,
-                )
-                .await
-            }
-        };
+        let context = ui_settings.context(
+            bot,
+            &me,
+            chat_id,
+            role,
+            storage_client,
+            unlock_token_store,
+            storage_availability,
+        );
+
+        if !matches!(
+            command_or_message,
+            CommandOrMessage::Command(command::Command::WhatsNew(_))
+        ) {
+            announce_release_notes(&context, &ui_settings.seen_versions).await;
+        }
 
         // See: https://rust-lang.github.io/rust-clippy/master/index.html#/large_futures
-        Box::pin(unwrap_state(res, &context)).await
+        Box::pin(handler::handle_command_or_message(
+            state,
+            command_or_message,
+            &context,
+        ))
+        .await
     };
 
     Storage::update_dialogue(state_storage, chat_id, end_state)
@@ -128,15 +222,30 @@ async fn message_handler(
         .map_err(Into::into)
 }
 
-#[instrument(skip(bot, state_storage, storage_client))]
-#[expect(clippy::significant_drop_tightening, reason = "false positive")]
+#[instrument(skip(
+    bot,
+    state_storage,
+    storage_client,
+    owner_roles,
+    unlock_token_store,
+    storage_availability
+))]
+#[expect(
+    clippy::too_many_arguments,
+    reason = "dependencies are injected by `dptree`"
+)]
 async fn button_callback_handler(
     bot: Bot,
     query: CallbackQuery,
+    me: Me,
     state_storage: Arc<InMemStorage<State>>,
-    web_app_url: Arc<Url>,
+    ui_settings: Arc<UiSettings>,
     storage_client: Arc<Mutex<PasswordStorageClient>>,
+    owner_roles: Arc<OwnerRoles>,
+    unlock_token_store: Option<Arc<UnlockTokenStore>>,
+    storage_availability: Arc<StorageAvailability>,
 ) -> color_eyre::Result<()> {
+    let _permit = ui_settings.update_limit.acquire().await;
     info!("Handling button callback");
 
     // Tell telegram that we've seen this query, to remove loading icons from the clients
@@ -160,6 +269,17 @@ async fn button_callback_handler(
     };
 
     let chat_id = message.chat.id;
+    let Some(role) = resolve_role(&owner_roles, chat_id) else {
+        warn!("Someone has tried to press a button, access denied");
+        return Ok(());
+    };
+
+    if !storage_availability.is_available() {
+        bot.send_message(chat_id, storage_health::UNAVAILABLE_MESSAGE)
+            .await?;
+        return Ok(());
+    }
+
     let state = drain_state(Arc::clone(&state_storage), chat_id).await?;
 
     let button = match ButtonBox::new(message, &data) {
@@ -171,10 +291,16 @@ async fn button_callback_handler(
     };
 
     let end_state = {
-        let context = context::Context::new(bot, chat_id, web_app_url, storage_client);
-        // See: https://rust-lang.github.io/rust-clippy/master/index.html#/large_futures
-        let res = Box::pin(State::try_from_transition(state, button, &context)).await;
-        unwrap_state(res, &context).await
+        let context = ui_settings.context(
+            bot,
+            &me,
+            chat_id,
+            role,
+            storage_client,
+            unlock_token_store,
+            storage_availability,
+        );
+        handler::handle_button(state, button, &context).await
     };
 
     Storage::update_dialogue(state_storage, chat_id, end_state)
@@ -182,16 +308,7 @@ async fn button_callback_handler(
         .map_err(Into::into)
 }
 
-/// Enum with either [`command::Command`] or [`message::Message`] to be
-/// [`parsed`](parse_command_or_message).
-enum CommandOrMessage {
-    /// Command variant.
-    Command(command::Command),
-    /// Message variant.
-    Message(message::MessageBox),
-}
-
-/// Try to parse [`command::Command`] or [`message::Message`] if first failed.
+/// Try to parse [`command::Command`] or [`message::MessageBox`] if first failed.
 ///
 /// Returns [`None`] if message is unsupported.
 fn parse_command_or_message(msg: TelegramMessage, bot_name: &str) -> Option<CommandOrMessage> {
@@ -220,37 +337,114 @@ async fn drain_state(
     Ok(state)
 }
 
-/// Unpack [`State`] from [`Result`] sending message to the user.
-async fn unwrap_state(
-    res: Result<State, FailedTransition<State>>,
-    context: &context::Context,
-) -> State {
-    let chat_id = context.chat_id();
-
-    match res {
-        Ok(new_state) => {
-            info!(?new_state, "Transition succeed");
-            new_state
-        }
-        Err(failed_transition) => {
-            let failure_reason = failed_transition.reason;
-            match failure_reason {
-                TransitionFailureReason::User(reason) => {
-                    let _ignored = context.bot().send_message(chat_id, reason).await;
-                }
-                TransitionFailureReason::Internal(reason) => {
-                    let _ignored = context
-                        .bot()
-                        .send_message(chat_id, "Internal error occurred, check the server logs.")
-                        .await;
-                    error!(?reason, "Internal error occurred");
-                }
-            }
+/// Settings of the bot interface shared between all chats.
+#[derive(Debug)]
+struct UiSettings {
+    /// URL of the web app frontend.
+    web_app_url: Arc<Url>,
+    /// Prefix of resource names on keyboard buttons.
+    resource_prefix: Arc<ResourcePrefix>,
+    /// Footer appended to messages.
+    message_footer: Arc<MessageFooter>,
+    /// Queue of final messages of transitions to retry if they fail to be sent.
+    final_message_retries: RetryQueue,
+    /// Queue of sensitive messages to delete after a while.
+    sensitive_deletions: DeletionQueue,
+    /// Versions of the release notes seen by chats.
+    seen_versions: Arc<SeenVersions>,
+    /// Key to sign temporary links to records with. [`None`] if they are disabled.
+    temp_link_key: Option<Arc<LinkKey>>,
+    /// Outcomes of handled Web App messages to recognize their redeliveries.
+    web_app_replays: Arc<ReplayCache>,
+    /// Time zones set by chats.
+    time_zones: Arc<TimeZones>,
+    /// Limit of updates handled at the same time, acquired first by every handler.
+    update_limit: UpdateLimit,
+}
 
-            let old_state = failed_transition.target;
-            info!(?old_state, "Transition failed");
-            old_state
-        }
+impl UiSettings {
+    /// Construct [`context::Context`] of the update in `chat_id` with these settings.
+    #[expect(
+        clippy::too_many_arguments,
+        reason = "all dependencies are passed from the handler"
+    )]
+    fn context(
+        &self,
+        bot: Bot,
+        me: &Me,
+        chat_id: ChatId,
+        role: Role,
+        storage_client: Arc<Mutex<PasswordStorageClient>>,
+        unlock_token_store: Option<Arc<UnlockTokenStore>>,
+        storage_availability: Arc<StorageAvailability>,
+    ) -> context::Context {
+        context::Context::new(
+            bot,
+            chat_id,
+            role,
+            Arc::clone(&self.web_app_url),
+            Arc::clone(&self.resource_prefix),
+            Arc::clone(&self.message_footer),
+            storage_client,
+            unlock_token_store,
+            storage_availability,
+        )
+        .with_bot_username(Arc::from(me.username()))
+        .with_final_message_retries(self.final_message_retries.clone())
+        .with_seen_versions(Arc::clone(&self.seen_versions))
+        .with_temp_link_key(self.temp_link_key.clone())
+        .with_web_app_replays(Arc::clone(&self.web_app_replays))
+        .with_time_zones(Arc::clone(&self.time_zones))
+        .with_sensitive_deletions(self.sensitive_deletions.clone())
+    }
+}
+
+/// Send release notes the chat hasn't seen yet after an upgrade.
+///
+/// Failure to send them is only logged, so that the user's request is still handled.
+async fn announce_release_notes(context: &context::Context, seen_versions: &SeenVersions) {
+    let Some(notes) = seen_versions.announce(context.chat_id()) else {
+        return;
+    };
+    if let Err(error) = footer::send_text(context, notes, MessageClass::Plain).await {
+        warn!(?error, "Failed to announce release notes");
+    }
+}
+
+/// Setup store of release notes versions seen by chats.
+///
+/// Versions are persisted to the file at `RELEASE_NOTES_SEEN_PATH` if it's set.
+/// Otherwise they are forgotten on restart, so upgrades are not announced.
+fn setup_seen_versions() -> Result<SeenVersions> {
+    Ok(read_optional_path_from_env("RELEASE_NOTES_SEEN_PATH")?
+        .map_or_else(SeenVersions::default, SeenVersions::load))
+}
+
+/// Setup store of time zones set by chats.
+///
+/// Time zones are persisted to the file at `TIME_ZONES_PATH` if it's set.
+/// Otherwise they are forgotten on restart.
+fn setup_time_zones() -> Result<TimeZones> {
+    Ok(read_optional_path_from_env("TIME_ZONES_PATH")?
+        .map_or_else(TimeZones::default, TimeZones::load))
+}
+
+/// Read key to sign temporary links to records with from environment variable.
+///
+/// Returns `Ok(None)` if temporary links are not enabled.
+fn read_temp_link_key_from_env() -> Result<Option<LinkKey>> {
+    /// URL-safe base64 key, enables temporary links.
+    /// The Web App must be built with the same `TEMP_LINK_VERIFICATION_KEY`
+    const TEMP_LINK_SIGNING_KEY_ENV_VAR: &str = "TEMP_LINK_SIGNING_KEY";
+
+    match std::env::var(TEMP_LINK_SIGNING_KEY_ENV_VAR) {
+        Ok(key) if !key.is_empty() => LinkKey::from_base64(&key)
+            .map(Some)
+            .wrap_err_with(|| format!("Failed to parse `{TEMP_LINK_SIGNING_KEY_ENV_VAR}`")),
+        Ok(_) | Err(std::env::VarError::NotPresent) => Ok(None),
+        Err(std::env::VarError::NotUnicode(_)) => Err(eyre!(
+            "`{TEMP_LINK_SIGNING_KEY_ENV_VAR}` environment variable is not in unicode format"
+        )),
     }
 }
 
@@ -265,6 +459,78 @@ fn read_web_app_url_from_env() -> Result<Url> {
         .wrap_err_with(|| format!("Failed to parse `{WEB_APP_URL_ENV_VAR}` environment variable"))
 }
 
+/// Read prefix of resource names on keyboard buttons from environment variable.
+///
+/// Returns [`ResourcePrefix::default()`] if not specified.
+fn read_resource_prefix_from_env() -> Result<ResourcePrefix> {
+    /// Prefix of resource names on keyboard buttons, e.g. an emoji
+    const RESOURCE_PREFIX_ENV_VAR: &str = "RESOURCE_PREFIX";
+
+    match std::env::var(RESOURCE_PREFIX_ENV_VAR) {
+        Ok(prefix) => Ok(ResourcePrefix::new(&prefix)),
+        Err(std::env::VarError::NotPresent) => Ok(ResourcePrefix::default()),
+        Err(std::env::VarError::NotUnicode(_)) => Err(eyre!(
+            "`{RESOURCE_PREFIX_ENV_VAR}` environment variable is not in unicode format"
+        )),
+    }
+}
+
+/// Read footer appended to messages from environment variable.
+///
+/// Returns [`MessageFooter::default()`] without footer if not specified.
+fn read_message_footer_from_env() -> Result<MessageFooter> {
+    /// Footer appended to messages, e.g. to tell staging and production bots apart
+    const MESSAGE_FOOTER_ENV_VAR: &str = "MESSAGE_FOOTER";
+
+    match std::env::var(MESSAGE_FOOTER_ENV_VAR) {
+        Ok(footer) => Ok(MessageFooter::new(&footer)),
+        Err(std::env::VarError::NotPresent) => Ok(MessageFooter::default()),
+        Err(std::env::VarError::NotUnicode(_)) => Err(eyre!(
+            "`{MESSAGE_FOOTER_ENV_VAR}` environment variable is not in unicode format"
+        )),
+    }
+}
+
+/// Resolve role of the user in a private chat with `chat_id`.
+///
+/// Returns [`None`] if access is denied.
+fn resolve_role(owner_roles: &OwnerRoles, chat_id: ChatId) -> Option<Role> {
+    chat_id
+        .as_user()
+        .and_then(|user_id| owner_roles.role_of(user_id))
+}
+
+/// Read owner roles from environment variables.
+///
+/// `OWNER_ROLES` takes precedence over `OWNER_USER_ID`, which gives [`Role::Admin`] to a single
+/// user. Allows access to anyone if none of them is specified.
+fn read_owner_roles_from_env() -> Result<OwnerRoles> {
+    /// Comma-separated `id=role` list of users allowed to access the bot
+    const OWNER_ROLES_ENV_VAR: &str = "OWNER_ROLES";
+
+    match std::env::var(OWNER_ROLES_ENV_VAR) {
+        Ok(var) if !var.is_empty() => {
+            let owner_roles = var.parse().wrap_err_with(|| {
+                format!("Failed to parse `{OWNER_ROLES_ENV_VAR}` environment variable")
+            })?;
+            info!(?owner_roles, "Access granted only for");
+            return Ok(owner_roles);
+        }
+        Ok(_) | Err(std::env::VarError::NotPresent) => {}
+        Err(std::env::VarError::NotUnicode(_)) => {
+            return Err(eyre!(
+                "`{OWNER_ROLES_ENV_VAR}` environment variable is not in unicode format"
+            ))
+        }
+    }
+
+    Ok(
+        read_owner_user_id_from_env()?.map_or(OwnerRoles::Anyone, |id| {
+            OwnerRoles::Restricted(std::iter::once((id, Role::Admin)).collect())
+        }),
+    )
+}
+
 /// Read owner user id from environment variable.
 ///
 /// Returns `Ok(None)` if not specified.
@@ -294,10 +560,280 @@ fn read_owner_user_id_from_env() -> Result<Option<UserId>> {
     }
 }
 
-/// Setup [`PasswordStorageClient`] from environment variables.
+/// Setup [`UnlockTokenStore`] and spawn the endpoint resolving its tokens in `tasks`.
+///
+/// Returns `Ok(None)` if `token-endpoint` feature is disabled.
+#[cfg(feature = "token-endpoint")]
+fn setup_unlock_token_store(
+    web_app_url: &Url,
+    tasks: &mut TaskRegistry,
+) -> Result<Option<Arc<UnlockTokenStore>>> {
+    use telepass_telegram_gate::{unlock_endpoint, unlock_token};
+    use tracing::error;
+
+    let endpoint_url = read_env_var("UNLOCK_ENDPOINT_URL")?;
+    let endpoint_url = Url::parse(&endpoint_url)
+        .wrap_err("Failed to parse `UNLOCK_ENDPOINT_URL` environment variable")?;
+    let address = read_env_var("UNLOCK_ENDPOINT_ADDRESS")?
+        .parse()
+        .wrap_err("Failed to parse `UNLOCK_ENDPOINT_ADDRESS` environment variable")?;
+
+    let unlock_token_store = Arc::new(UnlockTokenStore::new(
+        unlock_token::DEFAULT_TTL,
+        endpoint_url,
+    ));
+    let router = unlock_endpoint::router(Arc::clone(&unlock_token_store), web_app_url)?;
+    tasks.spawn_graceful("unlock endpoint", move |shutdown| async move {
+        if let Err(error) = unlock_endpoint::serve(address, router, shutdown).await {
+            error!(?error, "Unlock endpoint stopped");
+        }
+    });
+
+    Ok(Some(unlock_token_store))
+}
+
+/// Setup [`UnlockTokenStore`].
+///
+/// Always returns `Ok(None)` cause `token-endpoint` feature is disabled.
+#[cfg(not(feature = "token-endpoint"))]
+#[expect(
+    clippy::unnecessary_wraps,
+    reason = "to have the same signature as with `token-endpoint` feature"
+)]
+fn setup_unlock_token_store(
+    _web_app_url: &Url,
+    _tasks: &mut TaskRegistry,
+) -> Result<Option<Arc<UnlockTokenStore>>> {
+    info!("`token-endpoint` feature is disabled, encrypted records will be passed in urls");
+    Ok(None)
+}
+
+/// Wait for password storage to become ready during `budget`.
+///
+/// If storage is still not ready, keeps probing it in background task spawned in `tasks` while
+/// the bot works in degraded mode answering that storage is unavailable.
+async fn wait_for_storage(
+    health_client: HealthClient<Channel>,
+    budget: Duration,
+    tasks: &mut TaskRegistry,
+) -> Arc<StorageAvailability> {
+    let probe = move || probe_storage_health(health_client.clone());
+    let storage_availability = Arc::new(StorageAvailability::new(probe.clone()));
+
+    info!(?budget, "Waiting for password storage to become ready");
+    match storage_health::wait_until_ready(probe.clone(), budget, Backoff::default()).await {
+        Readiness::Ready => storage_availability.set_available(true),
+        Readiness::TimedOut => {
+            warn!("Password storage is not ready, starting in degraded mode");
+            let background_availability = Arc::clone(&storage_availability);
+            tasks.spawn("storage readiness probe", async move {
+                let readiness =
+                    storage_health::wait_until_ready(probe, Duration::MAX, Backoff::default())
+                        .await;
+                if readiness == Readiness::Ready {
+                    info!("Password storage is ready, leaving degraded mode");
+                    background_availability.set_available(true);
+                }
+            });
+        }
+    }
+
+    storage_availability
+}
+
+/// Check if password storage reports that it's serving.
+async fn probe_storage_health(mut health_client: HealthClient<Channel>) -> Result<()> {
+    /// Name under which password storage reports its health
+    const PASSWORD_STORAGE_SERVICE: &str = "password_storage.PasswordStorage";
+
+    let status = health_client
+        .check(HealthCheckRequest {
+            service: PASSWORD_STORAGE_SERVICE.to_owned(),
+        })
+        .await
+        .wrap_err("Failed to check password storage health")?
+        .into_inner()
+        .status();
+
+    match status {
+        ServingStatus::Serving => Ok(()),
+        ServingStatus::Unknown | ServingStatus::NotServing | ServingStatus::ServiceUnknown => {
+            Err(eyre!("Password storage is not serving, status: {status:?}"))
+        }
+    }
+}
+
+/// Read how long to wait for password storage on startup from environment variable.
+///
+/// Defaults to 60 seconds if not specified.
+fn read_startup_wait_from_env() -> Result<Duration> {
+    /// Number of seconds to wait for password storage on startup
+    const STARTUP_WAIT_SECONDS_ENV_VAR: &str = "STARTUP_WAIT_SECONDS";
+    /// Default value for [`STARTUP_WAIT_SECONDS_ENV_VAR`]
+    const DEFAULT_STARTUP_WAIT: Duration = Duration::from_secs(60);
+
+    match std::env::var(STARTUP_WAIT_SECONDS_ENV_VAR) {
+        Ok(var) => u64::from_str(&var).map(Duration::from_secs).wrap_err_with(|| {
+            format!("Failed to parse `{STARTUP_WAIT_SECONDS_ENV_VAR}` environment variable as `u64`")
+        }),
+        Err(std::env::VarError::NotPresent) => Ok(DEFAULT_STARTUP_WAIT),
+        Err(std::env::VarError::NotUnicode(_)) => Err(eyre!(
+            "`{STARTUP_WAIT_SECONDS_ENV_VAR}` environment variable is not in unicode format"
+        )),
+    }
+}
+
+/// Read how many updates can be handled at the same time from environment variable.
+///
+/// Defaults to [`update_limit::DEFAULT_MAX_CONCURRENT_UPDATES`] if not specified.
+fn read_max_concurrent_updates_from_env() -> Result<usize> {
+    /// Maximum number of updates handled at the same time
+    const MAX_CONCURRENT_UPDATES_ENV_VAR: &str = "MAX_CONCURRENT_UPDATES";
+
+    match std::env::var(MAX_CONCURRENT_UPDATES_ENV_VAR) {
+        Ok(var) => match usize::from_str(&var) {
+            Ok(0) => Err(eyre!(
+                "`{MAX_CONCURRENT_UPDATES_ENV_VAR}` environment variable must be positive"
+            )),
+            parsed => parsed.wrap_err_with(|| {
+                format!(
+                    "Failed to parse `{MAX_CONCURRENT_UPDATES_ENV_VAR}` environment variable as `usize`"
+                )
+            }),
+        },
+        Err(std::env::VarError::NotPresent) => Ok(update_limit::DEFAULT_MAX_CONCURRENT_UPDATES),
+        Err(std::env::VarError::NotUnicode(_)) => Err(eyre!(
+            "`{MAX_CONCURRENT_UPDATES_ENV_VAR}` environment variable is not in unicode format"
+        )),
+    }
+}
+
+/// Read heartbeat settings from environment variables.
 ///
-/// Initialized secured connection if `tls` feature is enabled.
-async fn setup_storage_client() -> Result<PasswordStorageClient> {
+/// Returns `Ok(None)` if heartbeat is not enabled.
+fn read_heartbeat_config_from_env() -> Result<Option<heartbeat::Config>> {
+    /// Id of the chat to keep the heartbeat message in, enables heartbeat
+    const HEARTBEAT_CHAT_ID_ENV_VAR: &str = "HEARTBEAT_CHAT_ID";
+    /// Number of seconds between heartbeat message edits
+    const HEARTBEAT_INTERVAL_SECONDS_ENV_VAR: &str = "HEARTBEAT_INTERVAL_SECONDS";
+
+    let chat_id = match std::env::var(HEARTBEAT_CHAT_ID_ENV_VAR) {
+        Ok(var) if !var.is_empty() => ChatId(i64::from_str(&var).wrap_err_with(|| {
+            format!("Failed to parse `{HEARTBEAT_CHAT_ID_ENV_VAR}` environment variable as `i64`")
+        })?),
+        Ok(_) | Err(std::env::VarError::NotPresent) => return Ok(None),
+        Err(std::env::VarError::NotUnicode(_)) => {
+            return Err(eyre!(
+                "`{HEARTBEAT_CHAT_ID_ENV_VAR}` environment variable is not in unicode format"
+            ))
+        }
+    };
+
+    let interval = match std::env::var(HEARTBEAT_INTERVAL_SECONDS_ENV_VAR) {
+        Ok(var) => u64::from_str(&var).map(Duration::from_secs).wrap_err_with(|| {
+            format!(
+                "Failed to parse `{HEARTBEAT_INTERVAL_SECONDS_ENV_VAR}` environment variable as `u64`"
+            )
+        })?,
+        Err(std::env::VarError::NotPresent) => heartbeat::DEFAULT_INTERVAL,
+        Err(std::env::VarError::NotUnicode(_)) => {
+            return Err(eyre!(
+                "`{HEARTBEAT_INTERVAL_SECONDS_ENV_VAR}` environment variable is not in unicode format"
+            ))
+        }
+    };
+    if interval.is_zero() {
+        return Err(eyre!(
+            "`{HEARTBEAT_INTERVAL_SECONDS_ENV_VAR}` environment variable must be positive"
+        ));
+    }
+
+    info!(%chat_id, ?interval, "Heartbeat enabled");
+    Ok(Some(heartbeat::Config { chat_id, interval }))
+}
+
+/// Spawn background task in `tasks` keeping heartbeat message up to date.
+///
+/// Id of the heartbeat message is saved to `message_id_path` if provided, so that the same
+/// message is edited after restart. Time is shown in the time zone of the heartbeat chat from
+/// `time_zones`.
+fn spawn_heartbeat(
+    tasks: &mut TaskRegistry,
+    bot: Bot,
+    config: heartbeat::Config,
+    message_id_path: Option<PathBuf>,
+    time_zones: Arc<TimeZones>,
+) {
+    let message_id = message_id_path.as_ref().and_then(|path| {
+        let id = std::fs::read_to_string(path).ok()?;
+        match i32::from_str(id.trim()) {
+            Ok(id) => Some(teloxide::types::MessageId(id)),
+            Err(error) => {
+                warn!(
+                    ?error,
+                    ?path,
+                    "Failed to parse saved heartbeat message id, ignoring it"
+                );
+                None
+            }
+        }
+    });
+
+    let heartbeat = Heartbeat::new(config.chat_id, message_id);
+    tasks.spawn("heartbeat", async move {
+        run_heartbeat(bot, heartbeat, config, message_id_path, time_zones).await
+    });
+}
+
+/// Beat `heartbeat` every interval of `config` saving id of the newly created message to
+/// `message_id_path`.
+#[expect(
+    clippy::infinite_loop,
+    reason = "heartbeat runs until the bot is stopped"
+)]
+async fn run_heartbeat(
+    bot: Bot,
+    mut heartbeat: Heartbeat,
+    config: heartbeat::Config,
+    message_id_path: Option<PathBuf>,
+    time_zones: Arc<TimeZones>,
+) -> ! {
+    let mut interval = tokio::time::interval(config.interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+        let beat = heartbeat
+            .beat(&bot, SystemTime::now(), time_zones.get(config.chat_id))
+            .await;
+
+        if let (heartbeat::Beat::Created(created_id), Some(path)) = (beat, message_id_path.as_ref())
+        {
+            if let Err(error) = std::fs::write(path, created_id.to_string()) {
+                warn!(?error, ?path, "Failed to save heartbeat message id");
+            }
+        }
+    }
+}
+
+/// Read optional path from `var` environment variable.
+///
+/// Returns `Ok(None)` if not specified.
+fn read_optional_path_from_env(var: &str) -> Result<Option<PathBuf>> {
+    match std::env::var(var) {
+        Ok(path) if !path.is_empty() => Ok(Some(PathBuf::from(path))),
+        Ok(_) | Err(std::env::VarError::NotPresent) => Ok(None),
+        Err(std::env::VarError::NotUnicode(_)) => Err(eyre!(
+            "`{var}` environment variable is not in unicode format"
+        )),
+    }
+}
+
+/// Setup [`PasswordStorageClient`] and its [`HealthClient`] from environment variables.
+///
+/// Connection is established lazily, so clients can be created before password storage is
+/// started. Initialized secured connection if `tls` feature is enabled.
+fn setup_storage_clients() -> Result<(PasswordStorageClient, HealthClient<Channel>)> {
     let password_storage_url = read_env_var("PASSWORD_STORAGE_URL")?;
 
     let channel = Channel::from_shared(password_storage_url.clone())
@@ -312,20 +848,18 @@ async fn setup_storage_client() -> Result<PasswordStorageClient> {
         channel
     };
 
-    let channel = channel
-        .connect()
-        .await
-        .wrap_err("Failed to connect to the password_storage service")?;
-    info!(%password_storage_url, "Successfully connected to the password_storage service");
+    let channel = channel.connect_lazy();
+    info!(%password_storage_url, "Using password_storage service");
 
-    Ok(PasswordStorageClient::new(channel))
+    Ok((
+        PasswordStorageClient::new(channel.clone()),
+        HealthClient::new(channel),
+    ))
 }
 
 /// Prepare TLS configuration for `gRPC` client.
 #[cfg(feature = "tls")]
 fn prepare_tls_config() -> Result<tonic::transport::ClientTlsConfig> {
-    use tonic::transport::{Certificate, ClientTlsConfig, Identity};
-
     let client_cert_path = read_env_var("TELEGRAM_GATE_TLS_CERT_PATH")?;
     let client_key_path = read_env_var("TELEGRAM_GATE_TLS_KEY_PATH")?;
     let server_ca_cert_path = read_env_var("ROOT_CA_CERT_PATH")?;
@@ -335,17 +869,16 @@ fn prepare_tls_config() -> Result<tonic::transport::ClientTlsConfig> {
     })?;
     let client_key = std::fs::read_to_string(&client_key_path)
         .wrap_err_with(|| format!("Failed to read client key at path: {client_key_path}"))?;
-    let client_identity = Identity::from_pem(client_cert, client_key);
 
     let server_ca_cert = std::fs::read_to_string(&server_ca_cert_path).wrap_err_with(|| {
         format!("Failed to read server certificate at path: {server_ca_cert_path}",)
     })?;
-    let server_ca_cert = Certificate::from_pem(server_ca_cert);
 
-    Ok(ClientTlsConfig::new()
-        .domain_name("password_storage")
-        .ca_certificate(server_ca_cert)
-        .identity(client_identity))
+    Ok(telepass_telegram_gate::tls::client_config(
+        client_cert,
+        client_key,
+        server_ca_cert,
+    ))
 }
 
 /// Read `var` environment variable.
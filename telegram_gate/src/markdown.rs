@@ -0,0 +1,29 @@
+//! Module with helpers to format messages in `MarkdownV2`.
+//!
+//! Same as [`teloxide::utils::markdown`], but [`escape()`] also escapes backslashes.
+
+pub use teloxide::utils::markdown::bold;
+
+/// Escape all `MarkdownV2` special characters in `text`.
+///
+/// Unlike [`teloxide::utils::markdown::escape()`] escapes backslashes too,
+/// otherwise a trailing backslash of a resource name escapes the closing `*` of [`bold()`].
+#[must_use]
+pub fn escape(text: &str) -> String {
+    teloxide::utils::markdown::escape(&text.replace('\\', "\\\\"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backslash_is_escaped() {
+        assert_eq!(bold(&escape("test\\")), "*test\\\\*");
+    }
+
+    #[test]
+    fn specials_are_escaped() {
+        assert_eq!(escape("a_b.c!"), "a\\_b\\.c\\!");
+    }
+}
@@ -18,6 +18,8 @@ pub enum MessageBox {
     Add(Message<kind::Add>),
     /// "List" message.
     List(Message<kind::List>),
+    /// "Retry now" message sent from the degraded main menu.
+    RetryStorage(Message<kind::RetryStorage>),
     /// Any arbitrary text message. Parsing will always fallback to this if nothing else matched.
     Arbitrary(Message<kind::Arbitrary>),
 }
@@ -51,6 +53,10 @@ impl MessageBox {
                     .or_else(|_| {
                         kind::List::from_str(&text).map(|list| Message::new(id, list).into())
                     })
+                    .or_else(|_| {
+                        kind::RetryStorage::from_str(&text)
+                            .map(|retry| Message::new(id, retry).into())
+                    })
                     .unwrap_or_else(|_| Message::new(id, kind::Arbitrary(text)).into()),
             ),
             _ => None,
@@ -92,6 +98,14 @@ impl MessageBox {
         })
     }
 
+    #[must_use]
+    pub const fn retry_storage() -> Self {
+        Self::RetryStorage(Message {
+            id: MessageId(0),
+            kind: kind::RetryStorage,
+        })
+    }
+
     #[must_use]
     pub fn arbitrary(text: &'static str) -> Self {
         Self::Arbitrary(Message {
@@ -135,6 +149,11 @@ pub mod kind {
     #[display("🗒 List")]
     pub struct List;
 
+    /// "Retry now" message to check password storage availability again.
+    #[derive(Debug, Display, Copy, Clone, FromStr)]
+    #[display("🔄 Retry now")]
+    pub struct RetryStorage;
+
     /// Any arbitrary message.
     #[derive(Debug, Clone, Display)]
     #[display("{0}")]
@@ -166,6 +185,7 @@ mod tests {
             MessageBox::WebApp(_) => parse_web_app(),
             MessageBox::Add(_) => parse_add(),
             MessageBox::List(_) => parse_list(),
+            MessageBox::RetryStorage(_) => parse_retry_storage(),
             MessageBox::Arbitrary(_) => parse_arbitrary(),
         }
 
@@ -236,6 +256,14 @@ mod tests {
         assert!(matches!(message, Some(MessageBox::List(_))));
     }
 
+    #[test]
+    fn parse_retry_storage() {
+        let tg_message = text_tg_message("🔄 Retry now".to_owned());
+
+        let message = MessageBox::new(tg_message);
+        assert!(matches!(message, Some(MessageBox::RetryStorage(_))));
+    }
+
     #[test]
     fn parse_arbitrary() {
         let tg_message = text_tg_message("Any random string here".to_owned());
@@ -0,0 +1,218 @@
+//! Module with [`MessageGroup`] of bot messages which must be deleted together.
+
+use std::future::Future;
+
+use drop_bomb::DebugDropBomb;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(not(test))]
+use teloxide::requests::Requester as _;
+use teloxide::types::MessageId;
+use tracing::{debug, warn};
+
+#[mockall_double::double]
+use crate::context::Context;
+
+/// Group of messages in the chat which must be deleted together, e.g. when leaving a state.
+///
+/// Serialized as a list of message ids.
+///
+/// # Panics
+///
+/// Dropping a group with tracked messages without calling [`delete_all()`](Self::delete_all)
+/// will raise a panic in debug builds.
+#[derive(Debug, Default)]
+pub struct MessageGroup {
+    /// Tracked messages in the order they were added.
+    message_ids: Vec<MessageId>,
+    /// Bomb to prevent dropping this type without deleting messages.
+    ///
+    /// Armed only after the first message is tracked, so empty groups can be dropped freely.
+    bomb: Option<DebugDropBomb>,
+}
+
+impl MessageGroup {
+    /// Construct new empty [`MessageGroup`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track `message_id` to delete it together with the rest of the group.
+    pub fn track(&mut self, message_id: MessageId) {
+        self.bomb.get_or_insert_with(|| {
+            DebugDropBomb::new(
+                "`MessageGroup` messages should always be deleted before dropping this type",
+            )
+        });
+        self.message_ids.push(message_id);
+    }
+
+    /// Get tracked messages in the order they were added.
+    #[must_use]
+    pub fn message_ids(&self) -> &[MessageId] {
+        &self.message_ids
+    }
+
+    /// Delete all tracked messages in the order they were added.
+    ///
+    /// Deletion doesn't stop on the first failure, so that as many messages as possible are
+    /// removed from the chat.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the first error if any message failed to be deleted.
+    pub async fn delete_all(self, context: &Context) -> color_eyre::Result<()> {
+        self.delete_with(|message_id| async move {
+            context
+                .bot()
+                .delete_message(context.chat_id(), message_id)
+                .await
+                .map(|_response| ())
+                .map_err(color_eyre::Report::new)
+        })
+        .await
+    }
+
+    /// Delete all tracked messages with `delete` tolerating failures.
+    async fn delete_with<F, Fut>(self, mut delete: F) -> color_eyre::Result<()>
+    where
+        F: FnMut(MessageId) -> Fut + Send,
+        Fut: Future<Output = color_eyre::Result<()>> + Send,
+    {
+        let Self { message_ids, bomb } = self;
+        if let Some(mut armed) = bomb {
+            armed.defuse();
+        }
+
+        let mut first_error = None;
+        let mut failed = 0_usize;
+        for message_id in &message_ids {
+            if let Err(error) = delete(*message_id).await {
+                warn!(?error, ?message_id, "Failed to delete message");
+                failed = failed.saturating_add(1);
+                first_error.get_or_insert(error);
+            }
+        }
+
+        if let Some(error) = first_error {
+            return Err(error.wrap_err(format!(
+                "Failed to delete {failed} of {} messages",
+                message_ids.len()
+            )));
+        }
+
+        debug!(count = message_ids.len(), "Message group deleted");
+        Ok(())
+    }
+
+    /// Forget about tracked messages without deleting them.
+    #[cfg(test)]
+    pub fn defuse(&mut self) {
+        if let Some(bomb) = self.bomb.as_mut() {
+            bomb.defuse();
+        }
+    }
+}
+
+impl FromIterator<MessageId> for MessageGroup {
+    fn from_iter<T: IntoIterator<Item = MessageId>>(iter: T) -> Self {
+        let mut group = Self::new();
+        for message_id in iter {
+            group.track(message_id);
+        }
+        group
+    }
+}
+
+impl Serialize for MessageGroup {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.message_ids.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for MessageGroup {
+    /// Deserialized group with messages is armed as if they were just tracked.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<MessageId>::deserialize(deserializer).map(Self::from_iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use std::sync::{Arc, Mutex};
+
+    use tokio::test;
+
+    use super::*;
+    use crate::test_utils::mock_bot::{MockBotBuilder, CHAT_ID};
+
+    #[test]
+    async fn empty_group_can_be_dropped() {
+        drop(MessageGroup::new());
+    }
+
+    #[test]
+    #[should_panic(expected = "`MessageGroup` messages should always be deleted")]
+    async fn dropping_tracked_group_panics() {
+        let mut group = MessageGroup::new();
+        group.track(MessageId(1));
+    }
+
+    #[test]
+    async fn delete_all_deletes_tracked_messages() {
+        let group = MessageGroup::from_iter([MessageId(1), MessageId(2), MessageId(3)]);
+
+        let mut mock_context = Context::default();
+        mock_context.expect_chat_id().return_const(CHAT_ID);
+        mock_context.expect_bot().return_const(
+            MockBotBuilder::new()
+                .expect_delete_message(MessageId(1))
+                .expect_delete_message(MessageId(2))
+                .expect_delete_message(MessageId(3))
+                .build(),
+        );
+
+        group.delete_all(&mock_context).await.unwrap();
+    }
+
+    #[test]
+    async fn delete_continues_after_failure() {
+        let group = MessageGroup::from_iter([MessageId(1), MessageId(2), MessageId(3)]);
+        let attempted = Arc::new(Mutex::new(Vec::new()));
+
+        let error = group
+            .delete_with(|message_id| {
+                attempted.lock().unwrap().push(message_id);
+                async move {
+                    if message_id == MessageId(2) {
+                        Err(color_eyre::eyre::eyre!("message is too old"))
+                    } else {
+                        Ok(())
+                    }
+                }
+            })
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            *attempted.lock().unwrap(),
+            [MessageId(1), MessageId(2), MessageId(3)]
+        );
+        assert_eq!(error.to_string(), "Failed to delete 1 of 3 messages");
+        assert_eq!(error.root_cause().to_string(), "message is too old");
+    }
+
+    #[test]
+    async fn serde_round_trip() {
+        let mut group = MessageGroup::from_iter([MessageId(1), MessageId(2)]);
+
+        let json = serde_json::to_string(&group).unwrap();
+        let mut restored: MessageGroup = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.message_ids(), group.message_ids());
+        group.defuse();
+        restored.defuse();
+    }
+}
@@ -0,0 +1,349 @@
+//! Module with release notes telling users about changes of the bot.
+//!
+//! Users don't follow releases, so entries of [`RELEASE_NOTES`] newer than the version a chat
+//! has seen are sent once when the user interacts with the bot after an upgrade.
+//! They can also be requested with `/whatsnew`.
+
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{Mutex, MutexGuard},
+};
+
+use teloxide::types::ChatId;
+use tracing::warn;
+
+/// Version of the bot.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    parse_display::Display,
+    parse_display::FromStr,
+)]
+#[display("{major}.{minor}.{patch}")]
+pub struct Version {
+    /// Major version.
+    pub major: u32,
+    /// Minor version.
+    pub minor: u32,
+    /// Patch version.
+    pub patch: u32,
+}
+
+impl Version {
+    /// Construct new [`Version`].
+    #[must_use]
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+/// Changes of a single version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReleaseNote {
+    /// Version the changes were released in.
+    pub version: Version,
+    /// User-visible changes, one bullet each.
+    pub changes: &'static [&'static str],
+}
+
+/// Release notes sorted by version from the oldest one.
+///
+/// Add an entry for every release with user-visible changes.
+pub const RELEASE_NOTES: &[ReleaseNote] = &[ReleaseNote {
+    version: Version::new(0, 1, 0),
+    changes: &[
+        "/add opens the form to add a new password",
+        "/deepfind searches for a word inside your records",
+        "/whatsnew shows changes of the bot since your last visit",
+    ],
+}];
+
+/// Get version of the latest release note.
+///
+/// Returns [`None`] if there are no release notes.
+#[must_use]
+pub fn latest_version() -> Option<Version> {
+    RELEASE_NOTES.last().map(|note| note.version)
+}
+
+/// Get release notes of versions newer than `seen` one.
+///
+/// Returns all release notes if no version was seen.
+#[must_use]
+pub fn newer_than(seen: Option<Version>) -> &'static [ReleaseNote] {
+    let Some(seen) = seen else {
+        return RELEASE_NOTES;
+    };
+    let first_unseen = RELEASE_NOTES.partition_point(|note| note.version <= seen);
+    RELEASE_NOTES.get(first_unseen..).unwrap_or_default()
+}
+
+/// Format `notes` as a plain-text message.
+#[must_use]
+pub fn format(notes: &[ReleaseNote]) -> String {
+    let sections = notes.iter().rev().map(|note| {
+        let changes: Vec<_> = note
+            .changes
+            .iter()
+            .map(|change| format!("\u{2022} {change}"))
+            .collect();
+        format!("v{}\n{}", note.version, changes.join("\n"))
+    });
+
+    std::iter::once("\u{1f195} What's new:".to_owned())
+        .chain(sections)
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Latest versions of the release notes seen by chats.
+///
+/// Persisted to a file with `<chat id>=<version>` lines if it's specified,
+/// so that notes aren't sent again after restart.
+#[derive(Debug, Default)]
+pub struct SeenVersions {
+    /// Versions by chats.
+    versions: Mutex<BTreeMap<ChatId, Version>>,
+    /// File to persist versions to. [`None`] if they are kept in memory only.
+    path: Option<PathBuf>,
+}
+
+impl SeenVersions {
+    /// Load seen versions from the file at `path` and persist them there.
+    ///
+    /// Missing file is treated as empty, malformed lines are skipped.
+    #[must_use]
+    pub fn load(path: PathBuf) -> Self {
+        let versions = std::fs::read_to_string(&path)
+            .map(|content| parse(&content))
+            .unwrap_or_default();
+        Self {
+            versions: Mutex::new(versions),
+            path: Some(path),
+        }
+    }
+
+    /// Get the latest version seen by the chat with `chat_id`.
+    #[must_use]
+    pub fn last_seen(&self, chat_id: ChatId) -> Option<Version> {
+        self.lock_versions().get(&chat_id).copied()
+    }
+
+    /// Get release notes to send to the chat with `chat_id` after an upgrade.
+    ///
+    /// Notes are returned once per version. A chat which has seen no version yet didn't
+    /// experience any upgrade, so the latest version is remembered silently.
+    #[must_use]
+    pub fn announce(&self, chat_id: ChatId) -> Option<String> {
+        let mut versions = self.lock_versions();
+        let seen = versions.get(&chat_id).copied();
+        let notes = newer_than(seen);
+        if notes.is_empty() {
+            return None;
+        }
+        self.mark_latest_seen(&mut versions, chat_id);
+        drop(versions);
+
+        seen.map(|_seen| format(notes))
+    }
+
+    /// Get message with release notes the chat with `chat_id` hasn't seen yet for `/whatsnew`.
+    ///
+    /// All notes are returned if the chat has seen no version yet.
+    #[must_use]
+    pub fn whats_new(&self, chat_id: ChatId) -> String {
+        let mut versions = self.lock_versions();
+        let notes = newer_than(versions.get(&chat_id).copied());
+        if notes.is_empty() {
+            return latest_version().map_or_else(
+                || "\u{2705} You are up to date.".to_owned(),
+                |latest| format!("\u{2705} You are up to date with v{latest}."),
+            );
+        }
+        self.mark_latest_seen(&mut versions, chat_id);
+        drop(versions);
+
+        format(notes)
+    }
+
+    /// Remember that the chat with `chat_id` has seen the latest version and persist it.
+    fn mark_latest_seen(&self, versions: &mut BTreeMap<ChatId, Version>, chat_id: ChatId) {
+        let Some(latest) = latest_version() else {
+            return;
+        };
+        versions.insert(chat_id, latest);
+
+        if let Some(path) = self.path.as_ref() {
+            if let Err(error) = std::fs::write(path, serialize(versions)) {
+                warn!(?error, ?path, "Failed to save seen release notes versions");
+            }
+        }
+    }
+
+    /// Lock seen versions.
+    ///
+    /// Panics if lock is poisoned.
+    #[expect(clippy::expect_used, reason = "poisoning indicates programmer error")]
+    fn lock_versions(&self) -> MutexGuard<'_, BTreeMap<ChatId, Version>> {
+        self.versions
+            .lock()
+            .expect("`versions` should not be poisoned")
+    }
+}
+
+/// Parse `<chat id>=<version>` lines of `content` skipping malformed ones.
+fn parse(content: &str) -> BTreeMap<ChatId, Version> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            let parsed = line.split_once('=').and_then(|(chat_id, version)| {
+                Some((
+                    ChatId(chat_id.trim().parse().ok()?),
+                    version.trim().parse().ok()?,
+                ))
+            });
+            if parsed.is_none() {
+                warn!(line, "Malformed seen release notes version, skipping it");
+            }
+            parsed
+        })
+        .collect()
+}
+
+/// Serialize `versions` into `<chat id>=<version>` lines.
+fn serialize(versions: &BTreeMap<ChatId, Version>) -> String {
+    versions
+        .iter()
+        .map(|(chat_id, version)| format!("{chat_id}={version}\n"))
+        .collect::<Vec<_>>()
+        .concat()
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use super::*;
+
+    const CHAT_ID: ChatId = ChatId(12345);
+
+    #[test]
+    fn release_notes_are_sorted_and_not_ahead_of_the_bot() {
+        assert!(RELEASE_NOTES
+            .windows(2)
+            .all(|pair| pair.first().unwrap().version < pair.last().unwrap().version));
+        assert!(RELEASE_NOTES.iter().all(|note| !note.changes.is_empty()));
+
+        let current: Version = env!("CARGO_PKG_VERSION").parse().unwrap();
+        assert!(latest_version().unwrap() <= current);
+    }
+
+    #[test]
+    fn versions_are_compared_numerically() {
+        assert!(Version::new(0, 10, 0) > Version::new(0, 9, 1));
+        assert!(Version::new(1, 0, 0) > Version::new(0, 99, 99));
+        assert_eq!("0.10.2".parse::<Version>().unwrap(), Version::new(0, 10, 2));
+        assert_eq!(Version::new(0, 10, 2).to_string(), "0.10.2");
+        "0.10".parse::<Version>().unwrap_err();
+    }
+
+    #[test]
+    fn newer_than_skips_seen_versions() {
+        let latest = latest_version().unwrap();
+
+        assert_eq!(newer_than(None), RELEASE_NOTES);
+        assert!(newer_than(Some(latest)).is_empty());
+        assert_eq!(newer_than(Some(Version::new(0, 0, 0))), RELEASE_NOTES);
+    }
+
+    #[test]
+    fn notes_are_formatted_from_the_newest() {
+        let notes = [
+            ReleaseNote {
+                version: Version::new(0, 1, 0),
+                changes: &["First"],
+            },
+            ReleaseNote {
+                version: Version::new(0, 2, 0),
+                changes: &["Second", "Third"],
+            },
+        ];
+
+        assert_eq!(
+            format(&notes),
+            "\u{1f195} What's new:\n\nv0.2.0\n\u{2022} Second\n\u{2022} Third\n\nv0.1.0\n\u{2022} First"
+        );
+    }
+
+    #[test]
+    fn first_seen_chat_is_not_announced_but_remembered() {
+        let seen_versions = SeenVersions::default();
+
+        assert_eq!(seen_versions.announce(CHAT_ID), None);
+        assert_eq!(seen_versions.last_seen(CHAT_ID), latest_version());
+    }
+
+    #[test]
+    fn upgrade_is_announced_once() {
+        let seen_versions = SeenVersions::default();
+        seen_versions
+            .lock_versions()
+            .insert(CHAT_ID, Version::new(0, 0, 1));
+
+        assert_eq!(seen_versions.announce(CHAT_ID), Some(format(RELEASE_NOTES)));
+        // Repeated interaction
+        assert_eq!(seen_versions.announce(CHAT_ID), None);
+        assert_eq!(seen_versions.last_seen(CHAT_ID), latest_version());
+    }
+
+    #[test]
+    fn whats_new_shows_unseen_notes_then_up_to_date() {
+        let seen_versions = SeenVersions::default();
+
+        // First-seen chat gets all notes
+        assert_eq!(seen_versions.whats_new(CHAT_ID), format(RELEASE_NOTES));
+        assert_eq!(
+            seen_versions.whats_new(CHAT_ID),
+            format!(
+                "\u{2705} You are up to date with v{}.",
+                latest_version().unwrap()
+            )
+        );
+        assert_eq!(seen_versions.announce(CHAT_ID), None);
+    }
+
+    #[test]
+    fn seen_versions_are_persisted() {
+        let path =
+            std::env::temp_dir().join(format!("telepass_seen_versions_{}", std::process::id()));
+        std::fs::write(&path, "42=0.0.1\nmalformed\n7=not.a.version\n").unwrap();
+
+        let seen_versions = SeenVersions::load(path.clone());
+        assert_eq!(
+            seen_versions.last_seen(ChatId(42)),
+            Some(Version::new(0, 0, 1))
+        );
+        assert_eq!(seen_versions.last_seen(ChatId(7)), None);
+        assert!(seen_versions.announce(ChatId(42)).is_some());
+
+        let reloaded = SeenVersions::load(path.clone());
+        assert_eq!(reloaded.last_seen(ChatId(42)), latest_version());
+        assert_eq!(reloaded.announce(ChatId(42)), None);
+
+        std::fs::remove_file(path).unwrap();
+        let missing = SeenVersions::load(std::env::temp_dir().join("telepass_missing_versions"));
+        assert_eq!(missing.last_seen(ChatId(42)), None);
+    }
+}
@@ -0,0 +1,129 @@
+//! Module with [`ReplayCache`] to recognize Web App messages redelivered by Telegram.
+//!
+//! Telegram can deliver the same update again after reconnects. Handling a redelivered
+//! submission again would add a duplicate record or fail with a spurious error, so the outcome
+//! of the first handling is remembered for a while and repeated instead.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+/// Default time to remember outcomes for.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Remembered outcome with its expiration time.
+#[derive(Debug)]
+struct Entry {
+    /// Outcome message sent to the user.
+    outcome: String,
+    /// Moment after which the outcome is forgotten.
+    expires_at: Instant,
+}
+
+/// In-memory cache of outcomes of handled Web App messages.
+///
+/// Keyed by [idempotency keys](crate::grpc::idempotency_key()) of the messages.
+#[derive(Debug)]
+pub struct ReplayCache {
+    /// Remembered outcomes.
+    entries: Mutex<HashMap<String, Entry>>,
+    /// Time to remember an outcome for.
+    ttl: Duration,
+}
+
+impl Default for ReplayCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_TTL)
+    }
+}
+
+impl ReplayCache {
+    /// Create new empty [`ReplayCache`] remembering outcomes for `ttl`.
+    #[must_use]
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// Get outcome of the message with `key` if it was already handled and is not expired at
+    /// `now`.
+    pub fn outcome(&self, key: &str, now: Instant) -> Option<String> {
+        self.lock_entries()
+            .get(key)
+            .filter(|entry| entry.expires_at > now)
+            .map(|entry| entry.outcome.clone())
+    }
+
+    /// Remember `outcome` of the message with `key` handled at `now`.
+    ///
+    /// Also removes all expired outcomes.
+    pub fn remember(&self, key: String, outcome: String, now: Instant) {
+        let mut entries = self.lock_entries();
+        entries.retain(|_key, entry| entry.expires_at > now);
+        entries.insert(
+            key,
+            Entry {
+                outcome,
+                expires_at: now.checked_add(self.ttl).unwrap_or(now),
+            },
+        );
+    }
+
+    /// Lock remembered outcomes.
+    ///
+    /// Panics if lock is poisoned.
+    #[expect(clippy::expect_used, reason = "poisoning indicates programmer error")]
+    fn lock_entries(&self) -> MutexGuard<'_, HashMap<String, Entry>> {
+        self.entries
+            .lock()
+            .expect("`entries` should not be poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use super::*;
+
+    #[test]
+    fn outcome_of_remembered_message_success() {
+        let cache = ReplayCache::default();
+        let now = Instant::now();
+
+        cache.remember("key".to_owned(), "saved".to_owned(), now);
+
+        assert_eq!(cache.outcome("key", now), Some("saved".to_owned()));
+        assert_eq!(cache.outcome("other", now), None);
+    }
+
+    #[test]
+    fn outcome_expires_failure() {
+        let cache = ReplayCache::default();
+        let now = Instant::now();
+
+        cache.remember("key".to_owned(), "saved".to_owned(), now);
+
+        let after_expiration = now.checked_add(DEFAULT_TTL).unwrap();
+        assert_eq!(cache.outcome("key", after_expiration), None);
+    }
+
+    #[test]
+    fn remember_removes_expired_outcomes() {
+        let cache = ReplayCache::default();
+        let now = Instant::now();
+
+        cache.remember("expired".to_owned(), "saved".to_owned(), now);
+        cache.remember(
+            "fresh".to_owned(),
+            "saved".to_owned(),
+            now.checked_add(DEFAULT_TTL).unwrap(),
+        );
+
+        assert!(!cache.lock_entries().contains_key("expired"));
+    }
+}
@@ -0,0 +1,172 @@
+//! Module with [`Role`] of a bot user and [`OwnerRoles`] mapping users to their roles.
+
+use std::{collections::HashMap, str::FromStr};
+
+use teloxide::types::UserId;
+
+/// Role of a bot user.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, parse_display::Display, parse_display::FromStr)]
+#[display(style = "lowercase")]
+pub enum Role {
+    /// Can view and manage records.
+    Admin,
+    /// Can only view records.
+    Viewer,
+}
+
+impl Role {
+    /// Check if role allows to change or remove stored records.
+    #[must_use]
+    pub const fn can_manage(self) -> bool {
+        matches!(self, Self::Admin)
+    }
+}
+
+/// User error returned when [`Role`] doesn't allow an action.
+pub const PERMISSION_DENIED: &str = "You don't have permission to do this.";
+
+/// Mapping of users to their roles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OwnerRoles {
+    /// Anyone can access the bot with [`Role::Admin`] role.
+    Anyone,
+    /// Only listed users can access the bot.
+    Restricted(HashMap<UserId, Role>),
+}
+
+impl OwnerRoles {
+    /// Get role of `user`.
+    ///
+    /// Returns [`None`] if `user` is not allowed to access the bot.
+    #[must_use]
+    #[expect(
+        clippy::ref_patterns,
+        reason = "conflicts with `pattern_type_mismatch`"
+    )]
+    pub fn role_of(&self, user: UserId) -> Option<Role> {
+        match *self {
+            Self::Anyone => Some(Role::Admin),
+            Self::Restricted(ref roles) => roles.get(&user).copied(),
+        }
+    }
+}
+
+/// Error during [`OwnerRoles`] parsing.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ParseOwnerRolesError {
+    /// Entry is not in `id=role` format.
+    #[error("Expected `id=role` entry, got `{0}`")]
+    InvalidEntry(String),
+    /// Invalid user id.
+    #[error("Invalid user id `{0}`")]
+    InvalidUserId(String),
+    /// Unknown role.
+    #[error("Unknown role `{0}`, expected `admin` or `viewer`")]
+    UnknownRole(String),
+    /// User is listed more than once.
+    #[error("User `{0}` is listed more than once")]
+    DuplicatedUser(UserId),
+    /// No users listed.
+    #[error("No users listed")]
+    Empty,
+}
+
+impl FromStr for OwnerRoles {
+    type Err = ParseOwnerRolesError;
+
+    /// Parse [`OwnerRoles::Restricted`] from comma-separated `id=role` entries,
+    /// e.g. `12345=admin,67890=viewer`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut roles = HashMap::new();
+
+        for entry in s
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (id, role) = entry
+                .split_once('=')
+                .ok_or_else(|| ParseOwnerRolesError::InvalidEntry(entry.to_owned()))?;
+            let (id, role) = (id.trim(), role.trim());
+
+            let user = UserId(
+                id.parse()
+                    .map_err(|_err| ParseOwnerRolesError::InvalidUserId(id.to_owned()))?,
+            );
+            let role = role
+                .parse()
+                .map_err(|_err| ParseOwnerRolesError::UnknownRole(role.to_owned()))?;
+
+            if roles.insert(user, role).is_some() {
+                return Err(ParseOwnerRolesError::DuplicatedUser(user));
+            }
+        }
+
+        if roles.is_empty() {
+            return Err(ParseOwnerRolesError::Empty);
+        }
+
+        Ok(Self::Restricted(roles))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use super::*;
+
+    #[test]
+    fn parse_success() {
+        let roles: OwnerRoles = "12345=admin, 67890=viewer".parse().unwrap();
+
+        assert_eq!(roles.role_of(UserId(12345)), Some(Role::Admin));
+        assert_eq!(roles.role_of(UserId(67890)), Some(Role::Viewer));
+        assert_eq!(roles.role_of(UserId(1)), None);
+    }
+
+    #[test]
+    fn parse_invalid_entry_failure() {
+        assert_eq!(
+            "12345".parse::<OwnerRoles>(),
+            Err(ParseOwnerRolesError::InvalidEntry("12345".to_owned()))
+        );
+    }
+
+    #[test]
+    fn parse_invalid_user_id_failure() {
+        assert_eq!(
+            "abc=admin".parse::<OwnerRoles>(),
+            Err(ParseOwnerRolesError::InvalidUserId("abc".to_owned()))
+        );
+    }
+
+    #[test]
+    fn parse_unknown_role_failure() {
+        assert_eq!(
+            "12345=owner".parse::<OwnerRoles>(),
+            Err(ParseOwnerRolesError::UnknownRole("owner".to_owned()))
+        );
+    }
+
+    #[test]
+    fn parse_duplicated_user_failure() {
+        assert_eq!(
+            "12345=admin,12345=viewer".parse::<OwnerRoles>(),
+            Err(ParseOwnerRolesError::DuplicatedUser(UserId(12345)))
+        );
+    }
+
+    #[test]
+    fn parse_empty_failure() {
+        assert_eq!(
+            " , ".parse::<OwnerRoles>(),
+            Err(ParseOwnerRolesError::Empty)
+        );
+    }
+
+    #[test]
+    fn anyone_is_admin() {
+        assert_eq!(OwnerRoles::Anyone.role_of(UserId(1)), Some(Role::Admin));
+    }
+}
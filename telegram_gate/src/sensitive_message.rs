@@ -0,0 +1,185 @@
+//! Module with sending of messages showing secrets, e.g. generated passwords.
+//!
+//! Such messages are protected from forwarding and saving, the secret is hidden under a spoiler
+//! and the message is deleted by [`DeletionWorker`] after a while, so that the secret doesn't stay
+//! in the chat history and on synced devices.
+
+use std::time::Duration;
+
+use teloxide::types::{ChatId, MessageEntity, MessageId};
+#[cfg(not(test))]
+use teloxide::{payloads::SendMessageSetters as _, requests::Requester as _};
+use tokio::{sync::mpsc, time::Instant};
+use tracing::{info, warn};
+
+#[mockall_double::double]
+use crate::context::Context;
+use crate::{Bot, TelegramMessageGettersExt as _};
+
+/// Default delay before deleting a sensitive message.
+pub const DEFAULT_DELETION_DELAY: Duration = Duration::from_secs(60);
+
+/// Sensitive message waiting to be deleted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Deletion {
+    /// Chat the message was sent to.
+    chat_id: ChatId,
+    /// Sent message.
+    message_id: MessageId,
+    /// Moment the message was enqueued at.
+    enqueued_at: Instant,
+}
+
+/// Queue of sensitive messages to be deleted by [`DeletionWorker`].
+#[derive(Debug, Clone)]
+pub struct DeletionQueue {
+    /// Sender of the deletions to the worker.
+    sender: mpsc::UnboundedSender<Deletion>,
+}
+
+/// Worker deleting messages enqueued to [`DeletionQueue`].
+#[derive(Debug)]
+pub struct DeletionWorker {
+    /// Receiver of the deletions.
+    receiver: mpsc::UnboundedReceiver<Deletion>,
+    /// Delay before deleting a message.
+    delay: Duration,
+}
+
+/// Construct connected [`DeletionQueue`] and [`DeletionWorker`] deleting messages after `delay`.
+#[must_use]
+pub fn deletion_queue(delay: Duration) -> (DeletionQueue, DeletionWorker) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    (DeletionQueue { sender }, DeletionWorker { receiver, delay })
+}
+
+impl DeletionQueue {
+    /// Enqueue deletion of the message with `message_id` sent to `chat_id`.
+    pub fn enqueue(&self, chat_id: ChatId, message_id: MessageId) {
+        let deletion = Deletion {
+            chat_id,
+            message_id,
+            enqueued_at: Instant::now(),
+        };
+        if self.sender.send(deletion).is_err() {
+            warn!("Sensitive message deletion worker is stopped, message stays in the chat");
+        }
+    }
+}
+
+impl DeletionWorker {
+    /// Delete enqueued messages with `bot` each once its delay passes.
+    ///
+    /// Finishes when all [`DeletionQueue`]s are dropped.
+    pub async fn run(mut self, bot: Bot) {
+        // All deletions have the same delay, so they are due in the order they were enqueued
+        while let Some(deletion) = self.receiver.recv().await {
+            tokio::time::sleep(self.delay.saturating_sub(deletion.enqueued_at.elapsed())).await;
+
+            match bot
+                .delete_message(deletion.chat_id, deletion.message_id)
+                .await
+            {
+                Ok(_response) => info!(chat_id = %deletion.chat_id, "Sensitive message deleted"),
+                Err(error) => {
+                    warn!(%error, chat_id = %deletion.chat_id, "Failed to delete sensitive message");
+                }
+            }
+        }
+    }
+}
+
+/// Send `secret` titled with `header` to the chat of the `context`.
+///
+/// Message is protected from forwarding and saving, `secret` is hidden under a spoiler.
+/// Message is enqueued to the [`DeletionQueue`] of the `context`, footer is never appended.
+///
+/// # Errors
+///
+/// Fails if the message can't be sent.
+pub async fn send_sensitive(
+    context: &Context,
+    header: &str,
+    secret: &str,
+) -> Result<(), teloxide::RequestError> {
+    let message = context
+        .bot()
+        .send_message(context.chat_id(), format!("{header}\n{secret}"))
+        .entities(vec![spoiler(header, secret)])
+        .protect_content(true)
+        .await?;
+
+    if let Some(deletions) = context.sensitive_deletions() {
+        deletions.enqueue(context.chat_id(), message.id());
+    } else {
+        warn!("Sensitive message deletion is disabled, message stays in the chat");
+    }
+    Ok(())
+}
+
+/// Construct spoiler entity covering `secret` placed on the next line after `header`.
+fn spoiler(header: &str, secret: &str) -> MessageEntity {
+    // Entity offsets are measured in UTF-16 code units
+    let offset = header.encode_utf16().count().saturating_add(1);
+    MessageEntity::spoiler(offset, secret.encode_utf16().count())
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use super::*;
+    use crate::test_utils::mock_bot::{MockBotBuilder, CHAT_ID};
+
+    const DELAY: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn spoiler_covers_secret_in_utf16_units() {
+        // Emoji takes two UTF-16 code units
+        assert_eq!(
+            spoiler("\u{1f3b2} Password:", "p\u{e4}ss"),
+            MessageEntity::spoiler(13, 4)
+        );
+    }
+
+    #[tokio::test]
+    async fn send_sensitive_protects_hides_and_enqueues_deletion() {
+        let (deletions, mut deletion_worker) = deletion_queue(DELAY);
+        let mut mock_context = Context::default();
+        mock_context.expect_chat_id().return_const(CHAT_ID);
+        mock_context
+            .expect_sensitive_deletions()
+            .return_const(Some(deletions));
+        mock_context.expect_bot().return_const(
+            MockBotBuilder::new()
+                .expect_send_message("Password:\nsecret".to_owned())
+                .expect_entities(vec![MessageEntity::spoiler(10, 6)])
+                .expect_protect_content(true)
+                .expect_into_future_with_id(MessageId(42))
+                .build(),
+        );
+
+        send_sensitive(&mock_context, "Password:", "secret")
+            .await
+            .unwrap();
+
+        let deletion = deletion_worker.receiver.try_recv().unwrap();
+        assert_eq!(deletion.chat_id, CHAT_ID);
+        assert_eq!(deletion.message_id, MessageId(42));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn worker_deletes_message_after_delay() {
+        let (deletions, deletion_worker) = deletion_queue(DELAY);
+        let bot = MockBotBuilder::new()
+            .expect_delete_message(MessageId(42))
+            .build();
+
+        let start = Instant::now();
+        deletions.enqueue(CHAT_ID, MessageId(42));
+        drop(deletions);
+        deletion_worker.run(bot).await;
+
+        assert_eq!(start.elapsed(), DELAY);
+    }
+}
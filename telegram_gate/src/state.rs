@@ -6,24 +6,31 @@
 use std::sync::Arc;
 
 use derive_more::From;
-use drop_bomb::DebugDropBomb;
-#[cfg(not(test))]
-use teloxide::requests::Requester as _;
 use teloxide::types::MessageId;
 #[cfg(test)]
 use tokio::sync::RwLock;
-use tracing::debug;
+use tracing::{debug, warn};
+use url::Url;
 
 #[mockall_double::double]
 use crate::context::Context;
 use crate::{
-    button, command, message,
+    button, command,
+    footer::{self, MessageClass},
+    message,
+    message_group::MessageGroup,
+    release_notes, time_zone,
     transition::{try_with_state, FailedTransition, TransitionFailureReason, TryFromTransition},
 };
 
+mod deep_find_prompt;
 mod default;
 mod delete_confirmation;
+mod duplicate_name_prompt;
 mod main_menu;
+#[cfg(test)]
+mod markdown_templates;
+mod rename_prefix_confirmation;
 mod resource_actions;
 mod resources_list;
 
@@ -35,9 +42,132 @@ pub enum State {
     ResourcesList(resources_list::ResourcesList),
     ResourceActions(resource_actions::ResourceActions),
     DeleteConfirmation(delete_confirmation::DeleteConfirmation),
+    DuplicateNamePrompt(duplicate_name_prompt::DuplicateNamePrompt),
+    DeepFindPrompt(deep_find_prompt::DeepFindPrompt),
+    RenamePrefixConfirmation(rename_prefix_confirmation::RenamePrefixConfirmation),
+}
+
+impl State {
+    /// Get name of the state.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match *self {
+            Self::Default(_) => "Default",
+            Self::MainMenu(_) => "MainMenu",
+            Self::ResourcesList(_) => "ResourcesList",
+            Self::ResourceActions(_) => "ResourceActions",
+            Self::DeleteConfirmation(_) => "DeleteConfirmation",
+            Self::DuplicateNamePrompt(_) => "DuplicateNamePrompt",
+            Self::DeepFindPrompt(_) => "DeepFindPrompt",
+            Self::RenamePrefixConfirmation(_) => "RenamePrefixConfirmation",
+        }
+    }
+
+    /// Perform quick `action` passed with `/start` from the `main_menu`.
+    async fn perform_start_action(
+        main_menu: main_menu::MainMenu,
+        action: command::StartAction,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self>> {
+        match action {
+            command::StartAction::List => {
+                resources_list::ResourcesListOrMainMenu::list(main_menu, context)
+                    .await
+                    .map(Into::into)
+                    .map_err(FailedTransition::transform)
+            }
+            command::StartAction::Generate => {
+                main_menu::MainMenu::generate_password(main_menu, context)
+                    .await
+                    .map(Into::into)
+                    .map_err(FailedTransition::transform)
+            }
+        }
+    }
+
+    /// Cancel current operation of the `from` state.
+    async fn cancel(
+        from: Self,
+        cancel: command::Cancel,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self>> {
+        match from {
+            // ResourcesList --/cancel-> MainMenu
+            Self::ResourcesList(resources_list) => {
+                main_menu::MainMenu::try_from_transition(resources_list, cancel, context)
+                    .await
+                    .map(Into::into)
+                    .map_err(FailedTransition::transform)
+            }
+            // ResourceActions --/cancel-> ResourcesList
+            Self::ResourceActions(resource_actions) => {
+                resources_list::ResourcesList::try_from_transition(
+                    resource_actions,
+                    cancel,
+                    context,
+                )
+                .await
+                .map(Into::into)
+                .map_err(FailedTransition::transform)
+            }
+            // DeleteConfirmation --/cancel-> ResourcesList
+            Self::DeleteConfirmation(delete_confirmation) => {
+                resources_list::ResourcesList::try_from_transition(
+                    delete_confirmation,
+                    cancel,
+                    context,
+                )
+                .await
+                .map(Into::into)
+                .map_err(FailedTransition::transform)
+            }
+            // DuplicateNamePrompt --/cancel-> ResourcesList
+            Self::DuplicateNamePrompt(duplicate_name_prompt) => {
+                resources_list::ResourcesList::try_from_transition(
+                    duplicate_name_prompt,
+                    cancel,
+                    context,
+                )
+                .await
+                .map(Into::into)
+                .map_err(FailedTransition::transform)
+            }
+            // DeepFindPrompt --/cancel-> MainMenu
+            Self::DeepFindPrompt(deep_find_prompt) => {
+                main_menu::MainMenu::try_from_transition(deep_find_prompt, cancel, context)
+                    .await
+                    .map(Into::into)
+                    .map_err(FailedTransition::transform)
+            }
+            // RenamePrefixConfirmation --/cancel-> MainMenu
+            Self::RenamePrefixConfirmation(rename_prefix_confirmation) => {
+                main_menu::MainMenu::try_from_transition(
+                    rename_prefix_confirmation,
+                    cancel,
+                    context,
+                )
+                .await
+                .map(Into::into)
+                .map_err(FailedTransition::transform)
+            }
+            // Nothing to cancel
+            some_state @ (Self::Default(_) | Self::MainMenu(_)) => Err(FailedTransition::user(
+                some_state,
+                "Unavailable command in the current state.",
+            )),
+        }
+    }
 }
 
 #[cfg(test)]
+#[cfg_attr(test, allow(clippy::allow_attributes, reason = "false positive"))]
+#[cfg_attr(
+    test,
+    allow(
+        clippy::multiple_inherent_impl,
+        reason = "better looking conditional compilation"
+    )
+)]
 impl State {
     #[must_use]
     pub const fn main_menu() -> Self {
@@ -66,6 +196,28 @@ impl State {
         )
     }
 
+    #[must_use]
+    pub async fn duplicate_name_prompt(allow_not_deleted_messages: bool) -> Self {
+        Self::DuplicateNamePrompt(
+            duplicate_name_prompt::DuplicateNamePrompt::test(Self::create_displayed_resource_data(
+                allow_not_deleted_messages,
+            ))
+            .await,
+        )
+    }
+
+    #[must_use]
+    pub fn deep_find_prompt() -> Self {
+        Self::DeepFindPrompt(deep_find_prompt::DeepFindPrompt::test())
+    }
+
+    #[must_use]
+    pub fn rename_prefix_confirmation() -> Self {
+        Self::RenamePrefixConfirmation(rename_prefix_confirmation::RenamePrefixConfirmation::test(
+            MessageId(0),
+        ))
+    }
+
     fn create_displayed_resource_data(
         allow_not_deleted_messages: bool,
     ) -> Arc<RwLock<DisplayedResourceData>> {
@@ -77,7 +229,7 @@ impl State {
         );
 
         if allow_not_deleted_messages {
-            data.bomb.defuse();
+            data.messages.defuse();
         }
 
         Arc::new(RwLock::new(data))
@@ -89,19 +241,16 @@ impl State {
 /// # Panics
 ///
 /// Dropping a value of this type without calling [`delete_messages()`](Self::delete_messages)
-/// will raise a panic.
+/// will raise a panic, see [`MessageGroup`].
 #[derive(Debug)]
 pub struct DisplayedResourceData {
-    /// Message sent by user containing exact resource request.
-    pub resource_request_message_id: MessageId,
-    /// Currently displayed message with help message about `/cancel` command.
-    pub cancel_message_id: MessageId,
     /// Currently displayed message with resource name and attached buttons.
     pub resource_message_id: MessageId,
     /// Name of the requested resource.
     pub resource_name: String,
-    /// Bomb to prevent dropping this type without deleting messages.
-    bomb: DebugDropBomb,
+    /// Resource request sent by user, help message about `/cancel` command and
+    /// resource message.
+    messages: MessageGroup,
 }
 
 impl DisplayedResourceData {
@@ -114,40 +263,23 @@ impl DisplayedResourceData {
         resource_name: String,
     ) -> Self {
         Self {
-            resource_request_message_id,
-            cancel_message_id,
             resource_message_id,
             resource_name,
-            bomb: Self::init_bomb(),
+            messages: MessageGroup::from_iter([
+                resource_request_message_id,
+                cancel_message_id,
+                resource_message_id,
+            ]),
         }
     }
 
-    /// Initialize [`DebugDropBomb`] with a message.
-    fn init_bomb() -> DebugDropBomb {
-        DebugDropBomb::new(
-            "`DisplayedResourceData` messages should always \
-              be deleted before dropping this type",
-        )
-    }
-
     /// Delete contained messages.
     ///
     /// # Errors
     ///
-    /// Fails if there is an error while deleting messages.
-    pub async fn delete_messages(mut self, context: &Context) -> color_eyre::Result<()> {
-        self.bomb.defuse();
-
-        for message_id in [
-            self.resource_request_message_id,
-            self.cancel_message_id,
-            self.resource_message_id,
-        ] {
-            context
-                .bot()
-                .delete_message(context.chat_id(), message_id)
-                .await?;
-        }
+    /// Fails if any message failed to be deleted, see [`MessageGroup::delete_all()`].
+    pub async fn delete_messages(self, context: &Context) -> color_eyre::Result<()> {
+        self.messages.delete_all(context).await?;
 
         debug!("Displayed resource messages deleted");
         Ok(())
@@ -169,6 +301,17 @@ impl Default for State {
     }
 }
 
+impl From<resources_list::ResourcesListOrMainMenu> for State {
+    fn from(output: resources_list::ResourcesListOrMainMenu) -> Self {
+        match output {
+            resources_list::ResourcesListOrMainMenu::ResourcesList(resources_list) => {
+                resources_list.into()
+            }
+            resources_list::ResourcesListOrMainMenu::MainMenu(main_menu) => main_menu.into(),
+        }
+    }
+}
+
 impl TryFromTransition<Self, command::Command> for State {
     type ErrorTarget = Self;
 
@@ -182,41 +325,60 @@ impl TryFromTransition<Self, command::Command> for State {
         if let Command::Help(help) = cmd {
             return Self::try_from_transition(from, help, context).await;
         }
+        if let Command::WhatsNew(whats_new) = cmd {
+            return Self::try_from_transition(from, whats_new, context).await;
+        }
+        if let Command::TimeZone(time_zone) = cmd {
+            return Self::try_from_transition(from, time_zone, context).await;
+        }
+        if let Command::Cancel(cancel) = cmd {
+            return Self::cancel(from, cancel, context).await;
+        }
 
         let unavailable_command =
             |s: Self| FailedTransition::user(s, "Unavailable command in the current state.");
 
         match (from, cmd) {
-            // Default --/start-> MainMenu
+            // Default --/start [action]-> MainMenu [--action-> (ResourcesList | MainMenu)]
             (Self::Default(default), Command::Start(start)) => {
-                main_menu::MainMenu::try_from_transition(default, start, context)
+                let main_menu = main_menu::MainMenu::try_from_transition(default, start, context)
+                    .await
+                    .map_err(FailedTransition::transform)?;
+                match start.0 {
+                    Some(action) => Self::perform_start_action(main_menu, action, context).await,
+                    None => Ok(main_menu.into()),
+                }
+            }
+            // MainMenu --/start action-> (ResourcesList | MainMenu)
+            (Self::MainMenu(main_menu), Command::Start(command::Start(Some(action)))) => {
+                Self::perform_start_action(main_menu, action, context).await
+            }
+            // MainMenu --/add-> MainMenu
+            (Self::MainMenu(main_menu), Command::Add(add)) => {
+                main_menu::MainMenu::try_from_transition(main_menu, add, context)
                     .await
                     .map(Into::into)
                     .map_err(FailedTransition::transform)
             }
-            // ResourcesList --/cancel-> MainMenu
-            (Self::ResourcesList(resources_list), Command::Cancel(cancel)) => {
-                main_menu::MainMenu::try_from_transition(resources_list, cancel, context)
+            // MainMenu --/deepfind-> DeepFindPrompt
+            (Self::MainMenu(main_menu), Command::DeepFind(deep_find)) => {
+                deep_find_prompt::DeepFindPrompt::try_from_transition(main_menu, deep_find, context)
                     .await
                     .map(Into::into)
                     .map_err(FailedTransition::transform)
             }
-            // ResourceActions --/cancel-> ResourcesList
-            (Self::ResourceActions(resource_actions), Command::Cancel(cancel)) => {
-                resources_list::ResourcesList::try_from_transition(
-                    resource_actions,
-                    cancel,
-                    context,
-                )
-                .await
-                .map(Into::into)
-                .map_err(FailedTransition::transform)
+            // ResourcesList --/add-> MainMenu
+            (Self::ResourcesList(resources_list), Command::Add(add)) => {
+                main_menu::MainMenu::try_from_transition(resources_list, add, context)
+                    .await
+                    .map(Into::into)
+                    .map_err(FailedTransition::transform)
             }
-            // DeleteConfirmation --/cancel-> ResourcesList
-            (Self::DeleteConfirmation(delete_confirmation), Command::Cancel(cancel)) => {
-                resources_list::ResourcesList::try_from_transition(
-                    delete_confirmation,
-                    cancel,
+            // MainMenu --/renameprefix-> RenamePrefixConfirmation
+            (Self::MainMenu(main_menu), Command::RenamePrefix(rename_prefix)) => {
+                rename_prefix_confirmation::RenamePrefixConfirmation::try_from_transition(
+                    main_menu,
+                    rename_prefix,
                     context,
                 )
                 .await
@@ -229,7 +391,10 @@ impl TryFromTransition<Self, command::Command> for State {
                 | Self::MainMenu(_)
                 | Self::ResourcesList(_)
                 | Self::ResourceActions(_)
-                | Self::DeleteConfirmation(_)),
+                | Self::DeleteConfirmation(_)
+                | Self::DuplicateNamePrompt(_)
+                | Self::DeepFindPrompt(_)
+                | Self::RenamePrefixConfirmation(_)),
                 _cmd,
             ) => Err(unavailable_command(some_state)),
         }
@@ -250,9 +415,18 @@ impl TryFromTransition<Self, message::MessageBox> for State {
             |s: Self| FailedTransition::user(s, "Unexpected message in the current state.");
 
         match (state, msg) {
-            // MainMenu --list-> ResourcesList
+            // MainMenu --list-> (ResourcesList | MainMenu)
             (Self::MainMenu(main_menu), MessageBox::List(list)) => {
-                resources_list::ResourcesList::try_from_transition(main_menu, list, context)
+                resources_list::ResourcesListOrMainMenu::try_from_transition(
+                    main_menu, list, context,
+                )
+                .await
+                .map(Into::into)
+                .map_err(FailedTransition::transform)
+            }
+            // Default --Add (WebApp)-> MainMenu
+            (Self::Default(default), MessageBox::WebApp(web_app)) => {
+                main_menu::MainMenu::try_from_transition(default, web_app, context)
                     .await
                     .map(Into::into)
                     .map_err(FailedTransition::transform)
@@ -264,6 +438,13 @@ impl TryFromTransition<Self, message::MessageBox> for State {
                     .map(Into::into)
                     .map_err(FailedTransition::transform)
             }
+            // MainMenu --retry storage-> MainMenu
+            (Self::MainMenu(main_menu), MessageBox::RetryStorage(retry)) => {
+                main_menu::MainMenu::try_from_transition(main_menu, retry, context)
+                    .await
+                    .map(Into::into)
+                    .map_err(FailedTransition::transform)
+            }
             // ResourcesList --arbitrary-> (ResourcesList | ResourceActions)
             (Self::ResourcesList(resources_list), MessageBox::Arbitrary(arbitrary)) => {
                 let output = resources_list::SearchResultsOrResourceActions::try_from_transition(
@@ -283,13 +464,37 @@ impl TryFromTransition<Self, message::MessageBox> for State {
                     ) => resource_actions.into(),
                 })
             }
+            // DuplicateNamePrompt --arbitrary-> MainMenu
+            (
+                Self::DuplicateNamePrompt(duplicate_name_prompt),
+                MessageBox::Arbitrary(arbitrary),
+            ) => {
+                main_menu::MainMenu::try_from_transition(duplicate_name_prompt, arbitrary, context)
+                    .await
+                    .map(Into::into)
+                    .map_err(FailedTransition::transform)
+            }
+            // DeepFindPrompt --DeepFind (WebApp)-> ResourcesList
+            (Self::DeepFindPrompt(deep_find_prompt), MessageBox::WebApp(web_app)) => {
+                resources_list::ResourcesList::try_from_transition(
+                    deep_find_prompt,
+                    web_app,
+                    context,
+                )
+                .await
+                .map(Into::into)
+                .map_err(FailedTransition::transform)
+            }
             // Unexpected message
             (
                 some_state @ (Self::Default(_)
                 | Self::MainMenu(_)
                 | Self::ResourcesList(_)
                 | Self::ResourceActions(_)
-                | Self::DeleteConfirmation(_)),
+                | Self::DeleteConfirmation(_)
+                | Self::DuplicateNamePrompt(_)
+                | Self::DeepFindPrompt(_)
+                | Self::RenamePrefixConfirmation(_)),
                 _msg,
             ) => Err(unexpected_message(some_state)),
         }
@@ -321,6 +526,17 @@ impl TryFromTransition<Self, button::ButtonBox> for State {
                 .map(Into::into)
                 .map_err(FailedTransition::transform)
             }
+            // ResourceActions --[duplicate]-> DuplicateNamePrompt
+            (Self::ResourceActions(resource_actions), ButtonBox::Duplicate(duplicate)) => {
+                duplicate_name_prompt::DuplicateNamePrompt::try_from_transition(
+                    resource_actions,
+                    duplicate,
+                    context,
+                )
+                .await
+                .map(Into::into)
+                .map_err(FailedTransition::transform)
+            }
             // DeleteConfirmation --[yes]-> MainMenu
             (Self::DeleteConfirmation(delete_confirmation), ButtonBox::Yes(yes)) => {
                 main_menu::MainMenu::try_from_transition(delete_confirmation, yes, context)
@@ -328,6 +544,20 @@ impl TryFromTransition<Self, button::ButtonBox> for State {
                     .map(Into::into)
                     .map_err(FailedTransition::transform)
             }
+            // RenamePrefixConfirmation --[yes]-> MainMenu
+            (Self::RenamePrefixConfirmation(rename_prefix_confirmation), ButtonBox::Yes(yes)) => {
+                main_menu::MainMenu::try_from_transition(rename_prefix_confirmation, yes, context)
+                    .await
+                    .map(Into::into)
+                    .map_err(FailedTransition::transform)
+            }
+            // RenamePrefixConfirmation --[no]-> MainMenu
+            (Self::RenamePrefixConfirmation(rename_prefix_confirmation), ButtonBox::No(no)) => {
+                main_menu::MainMenu::try_from_transition(rename_prefix_confirmation, no, context)
+                    .await
+                    .map(Into::into)
+                    .map_err(FailedTransition::transform)
+            }
             // DeleteConfirmation --[no]-> ResourceActions
             (Self::DeleteConfirmation(delete_confirmation), ButtonBox::No(no)) => {
                 resource_actions::ResourceActions::try_from_transition(
@@ -345,7 +575,10 @@ impl TryFromTransition<Self, button::ButtonBox> for State {
                 | Self::MainMenu(_)
                 | Self::ResourcesList(_)
                 | Self::ResourceActions(_)
-                | Self::DeleteConfirmation(_)),
+                | Self::DeleteConfirmation(_)
+                | Self::DuplicateNamePrompt(_)
+                | Self::DeepFindPrompt(_)
+                | Self::RenamePrefixConfirmation(_)),
                 _button,
             ) => Err(unexpected_button(some_state)),
         }
@@ -364,12 +597,83 @@ impl<T: Into<State> + Send> TryFromTransition<Self, command::Help> for T {
 
         try_with_state!(
             state,
-            context
-                .bot()
-                .send_message(
-                    context.chat_id(),
-                    command::Command::descriptions().to_string()
+            footer::send_text(
+                context,
+                command::Command::descriptions().to_string(),
+                MessageClass::Plain
+            )
+            .await
+            .map_err(TransitionFailureReason::internal)
+        );
+        Ok(state)
+    }
+}
+
+impl<T: Into<State> + Send> TryFromTransition<Self, command::WhatsNew> for T {
+    type ErrorTarget = Self;
+
+    async fn try_from_transition(
+        state: T,
+        _whats_new: command::WhatsNew,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self>> {
+        let text = context.seen_versions().map_or_else(
+            || release_notes::format(release_notes::RELEASE_NOTES),
+            |seen_versions| seen_versions.whats_new(context.chat_id()),
+        );
+
+        try_with_state!(
+            state,
+            footer::send_text(context, text, MessageClass::Plain)
+                .await
+                .map_err(TransitionFailureReason::internal)
+        );
+        Ok(state)
+    }
+}
+
+impl<T: Into<State> + Send> TryFromTransition<Self, command::TimeZone> for T {
+    type ErrorTarget = Self;
+
+    async fn try_from_transition(
+        state: T,
+        time_zone: command::TimeZone,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self>> {
+        let command::TimeZone(name) = time_zone;
+        let time_zones = context.time_zones();
+
+        let text = if name.is_empty() {
+            time_zones
+                .and_then(|zones| zones.get(context.chat_id()))
+                .map_or_else(
+                    || {
+                        "🕒 Times are shown in UTC. \
+                         Set your time zone with /timezone, e.g. /timezone Europe/Berlin."
+                            .to_owned()
+                    },
+                    |zone| format!("🕒 Times are shown in {}.", zone.name()),
                 )
+        } else {
+            let Some(time_zones) = time_zones else {
+                return Err(FailedTransition::user(
+                    state,
+                    "❎ Time zone can't be changed in this bot.",
+                ));
+            };
+            let zone = try_with_state!(
+                state,
+                time_zone::parse(&name).map_err(|_err| TransitionFailureReason::user(format!(
+                    "❎ Unknown time zone \"{name}\", use a name like Europe/Berlin."
+                )))
+            );
+            time_zones.set(context.chat_id(), zone);
+            format!("✅ Times are now shown in {}.", zone.name())
+        };
+
+        try_with_state!(
+            state,
+            footer::send_text(context, text, MessageClass::Plain)
                 .await
                 .map_err(TransitionFailureReason::internal)
         );
@@ -377,6 +681,49 @@ impl<T: Into<State> + Send> TryFromTransition<Self, command::Help> for T {
     }
 }
 
+/// Construct url of the Web App page at absolute `route`.
+///
+/// Same as joining `route` to the Web App url, but can't fail.
+fn web_app_route_url(context: &Context, route: &str) -> Url {
+    let mut url = context.web_app_url().clone();
+    url.set_path(route);
+    url.set_query(None);
+    url.set_fragment(None);
+    url
+}
+
+/// Parse `data` sent by the Web App to the chat from `context`.
+///
+/// Web App buttons can be forwarded to other chats, so the data is rejected if the Web App
+/// reports another user than the owner of the private chat. `data_name` is used in the error
+/// message if `data` is malformed.
+fn parse_web_app_data<T: serde::de::DeserializeOwned>(
+    data: &str,
+    context: &Context,
+    data_name: &str,
+) -> Result<T, TransitionFailureReason> {
+    let message: telepass_data_model::WebAppMessage<T> =
+        serde_json::from_str(data).map_err(|_err| {
+            TransitionFailureReason::user(format!(
+                "Failed to parse {data_name}, your Telegram Client is probably invalid."
+            ))
+        })?;
+
+    // Private chat id is the same as the user id
+    if i64::try_from(message.user_id).ok() != Some(context.chat_id().0) {
+        warn!(
+            user_id = message.user_id,
+            "Web App was opened by another user, rejecting its data"
+        );
+        return Err(TransitionFailureReason::user(
+            "⚠️ This form was opened by another Telegram user, so its data is rejected. \
+             Open forms only with buttons in your chat with the bot.",
+        ));
+    }
+
+    Ok(message.data)
+}
+
 #[cfg(test)]
 mod tests {
     #![expect(clippy::panic, reason = "it's ok in tests")]
@@ -408,12 +755,50 @@ mod tests {
         match (state, cmd) {
             (State::Default(_), Command::Help(_)) => default::tests::command::help_success(),
             (State::Default(_), Command::Start(_)) => {
-                main_menu::tests::command::from_default_by_start_success()
+                main_menu::tests::command::from_default_by_start_success();
+                main_menu::tests::command::from_default_by_start_with_quick_actions_success();
+                main_menu::tests::command::from_default_by_start_generate_success();
+                main_menu::tests::command::from_default_by_start_with_unavailable_storage_success()
             }
             (State::Default(_), Command::Cancel(_)) => default::tests::command::cancel_failure(),
+            (State::Default(_), Command::Add(_)) => default::tests::command::add_failure(),
+            (State::Default(_), Command::DeepFind(_)) => {
+                default::tests::command::deep_find_failure()
+            }
+            (State::Default(_), Command::WhatsNew(_)) => {
+                default::tests::command::whats_new_success()
+            }
+            (State::Default(_), Command::TimeZone(_)) => {
+                default::tests::command::time_zone_success()
+            }
+            (State::Default(_), Command::RenamePrefix(_)) => {
+                default::tests::command::rename_prefix_failure()
+            }
             (State::MainMenu(_), Command::Help(_)) => main_menu::tests::command::help_success(),
-            (State::MainMenu(_), Command::Start(_)) => main_menu::tests::command::start_failure(),
+            (State::MainMenu(_), Command::Start(_)) => {
+                main_menu::tests::command::start_failure();
+                main_menu::tests::command::from_main_menu_by_start_generate_success();
+                resources_list::tests::command::from_main_menu_by_start_list_success()
+            }
             (State::MainMenu(_), Command::Cancel(_)) => main_menu::tests::command::cancel_failure(),
+            (State::MainMenu(_), Command::Add(_)) => main_menu::tests::command::add_success(),
+            (State::MainMenu(_), Command::DeepFind(_)) => {
+                deep_find_prompt::tests::command::from_main_menu_by_deep_find_success();
+                deep_find_prompt::tests::command::from_main_menu_by_deep_find_without_single_keyword_failure()
+            }
+            (State::MainMenu(_), Command::WhatsNew(_)) => {
+                main_menu::tests::command::whats_new_success()
+            }
+            (State::MainMenu(_), Command::TimeZone(_)) => {
+                main_menu::tests::command::time_zone_success()
+            }
+            (State::MainMenu(_), Command::RenamePrefix(_)) => {
+                rename_prefix_confirmation::tests::command::from_main_menu_by_rename_prefix_success(
+                );
+                rename_prefix_confirmation::tests::command::from_main_menu_by_rename_prefix_without_prefixes_failure();
+                rename_prefix_confirmation::tests::command::from_main_menu_by_rename_prefix_as_viewer_failure();
+                rename_prefix_confirmation::tests::command::from_main_menu_by_rename_prefix_without_matches_failure()
+            }
             (State::ResourcesList(_), Command::Help(_)) => {
                 resources_list::tests::command::help_success()
             }
@@ -423,6 +808,21 @@ mod tests {
             (State::ResourcesList(_), Command::Cancel(_)) => {
                 main_menu::tests::command::from_resources_list_by_cancel_success()
             }
+            (State::ResourcesList(_), Command::Add(_)) => {
+                resources_list::tests::command::add_success()
+            }
+            (State::ResourcesList(_), Command::DeepFind(_)) => {
+                resources_list::tests::command::deep_find_failure()
+            }
+            (State::ResourcesList(_), Command::WhatsNew(_)) => {
+                resources_list::tests::command::whats_new_success()
+            }
+            (State::ResourcesList(_), Command::TimeZone(_)) => {
+                resources_list::tests::command::time_zone_success()
+            }
+            (State::ResourcesList(_), Command::RenamePrefix(_)) => {
+                resources_list::tests::command::rename_prefix_failure()
+            }
             (State::ResourceActions(_), Command::Help(_)) => {
                 resource_actions::tests::command::help_success()
             }
@@ -432,6 +832,21 @@ mod tests {
             (State::ResourceActions(_), Command::Cancel(_)) => {
                 resources_list::tests::command::from_resource_actions_by_cancel_success()
             }
+            (State::ResourceActions(_), Command::Add(_)) => {
+                resource_actions::tests::command::add_failure()
+            }
+            (State::ResourceActions(_), Command::DeepFind(_)) => {
+                resource_actions::tests::command::deep_find_failure()
+            }
+            (State::ResourceActions(_), Command::WhatsNew(_)) => {
+                resource_actions::tests::command::whats_new_success()
+            }
+            (State::ResourceActions(_), Command::TimeZone(_)) => {
+                resource_actions::tests::command::time_zone_success()
+            }
+            (State::ResourceActions(_), Command::RenamePrefix(_)) => {
+                resource_actions::tests::command::rename_prefix_failure()
+            }
             (State::DeleteConfirmation(_), Command::Help(_)) => {
                 delete_confirmation::tests::command::help_success()
             }
@@ -441,27 +856,124 @@ mod tests {
             (State::DeleteConfirmation(_), Command::Cancel(_)) => {
                 resources_list::tests::command::from_delete_confirmation_by_cancel_success()
             }
+            (State::DeleteConfirmation(_), Command::Add(_)) => {
+                delete_confirmation::tests::command::add_failure()
+            }
+            (State::DeleteConfirmation(_), Command::DeepFind(_)) => {
+                delete_confirmation::tests::command::deep_find_failure()
+            }
+            (State::DeleteConfirmation(_), Command::WhatsNew(_)) => {
+                delete_confirmation::tests::command::whats_new_success()
+            }
+            (State::DeleteConfirmation(_), Command::TimeZone(_)) => {
+                delete_confirmation::tests::command::time_zone_success()
+            }
+            (State::DeleteConfirmation(_), Command::RenamePrefix(_)) => {
+                delete_confirmation::tests::command::rename_prefix_failure()
+            }
+            (State::DuplicateNamePrompt(_), Command::Help(_)) => {
+                duplicate_name_prompt::tests::command::help_success()
+            }
+            (State::DuplicateNamePrompt(_), Command::Start(_)) => {
+                duplicate_name_prompt::tests::command::start_failure()
+            }
+            (State::DuplicateNamePrompt(_), Command::Cancel(_)) => {
+                resources_list::tests::command::from_duplicate_name_prompt_by_cancel_success()
+            }
+            (State::DuplicateNamePrompt(_), Command::Add(_)) => {
+                duplicate_name_prompt::tests::command::add_failure()
+            }
+            (State::DuplicateNamePrompt(_), Command::DeepFind(_)) => {
+                duplicate_name_prompt::tests::command::deep_find_failure()
+            }
+            (State::DuplicateNamePrompt(_), Command::WhatsNew(_)) => {
+                duplicate_name_prompt::tests::command::whats_new_success()
+            }
+            (State::DuplicateNamePrompt(_), Command::TimeZone(_)) => {
+                duplicate_name_prompt::tests::command::time_zone_success()
+            }
+            (State::DuplicateNamePrompt(_), Command::RenamePrefix(_)) => {
+                duplicate_name_prompt::tests::command::rename_prefix_failure()
+            }
+            (State::DeepFindPrompt(_), Command::Help(_)) => {
+                deep_find_prompt::tests::command::help_success()
+            }
+            (State::DeepFindPrompt(_), Command::Start(_)) => {
+                deep_find_prompt::tests::command::start_failure()
+            }
+            (State::DeepFindPrompt(_), Command::Cancel(_)) => {
+                main_menu::tests::command::from_deep_find_prompt_by_cancel_success()
+            }
+            (State::DeepFindPrompt(_), Command::Add(_)) => {
+                deep_find_prompt::tests::command::add_failure()
+            }
+            (State::DeepFindPrompt(_), Command::DeepFind(_)) => {
+                deep_find_prompt::tests::command::deep_find_failure()
+            }
+            (State::DeepFindPrompt(_), Command::WhatsNew(_)) => {
+                deep_find_prompt::tests::command::whats_new_success()
+            }
+            (State::DeepFindPrompt(_), Command::TimeZone(_)) => {
+                deep_find_prompt::tests::command::time_zone_success()
+            }
+            (State::DeepFindPrompt(_), Command::RenamePrefix(_)) => {
+                deep_find_prompt::tests::command::rename_prefix_failure()
+            }
+            (State::RenamePrefixConfirmation(_), Command::Help(_)) => {
+                rename_prefix_confirmation::tests::command::help_success()
+            }
+            (State::RenamePrefixConfirmation(_), Command::Start(_)) => {
+                rename_prefix_confirmation::tests::command::start_failure()
+            }
+            (State::RenamePrefixConfirmation(_), Command::Cancel(_)) => {
+                main_menu::tests::command::from_rename_prefix_confirmation_by_cancel_success()
+            }
+            (State::RenamePrefixConfirmation(_), Command::Add(_)) => {
+                rename_prefix_confirmation::tests::command::add_failure()
+            }
+            (State::RenamePrefixConfirmation(_), Command::DeepFind(_)) => {
+                rename_prefix_confirmation::tests::command::deep_find_failure()
+            }
+            (State::RenamePrefixConfirmation(_), Command::WhatsNew(_)) => {
+                rename_prefix_confirmation::tests::command::whats_new_success()
+            }
+            (State::RenamePrefixConfirmation(_), Command::TimeZone(_)) => {
+                rename_prefix_confirmation::tests::command::time_zone_success()
+            }
+            (State::RenamePrefixConfirmation(_), Command::RenamePrefix(_)) => {
+                rename_prefix_confirmation::tests::command::rename_prefix_failure()
+            }
         }
 
         // Will fail to compile if a new state or message will be added
         match (state, msg) {
             (State::Default(_), MessageBox::WebApp(_)) => {
-                default::tests::message::web_app_failure()
+                main_menu::tests::message::from_default_by_web_app_success();
+                main_menu::tests::message::from_default_by_web_app_wrong_button_text_failure()
             }
             (State::Default(_), MessageBox::Add(_)) => default::tests::message::add_failure(),
             (State::Default(_), MessageBox::List(_)) => default::tests::message::list_failure(),
+            (State::Default(_), MessageBox::RetryStorage(_)) => {
+                default::tests::message::retry_storage_failure()
+            }
             (State::Default(_), MessageBox::Arbitrary(_)) => {
                 default::tests::message::arbitrary_failure()
             }
             (State::MainMenu(_), MessageBox::WebApp(_)) => {
                 main_menu::tests::message::web_app_success();
+                main_menu::tests::message::web_app_redelivered_success();
                 main_menu::tests::message::web_app_wrong_button_text_failure();
+                main_menu::tests::message::web_app_invalid_record_failure();
                 main_menu::tests::message::web_app_wrong_data_failure()
             }
             (State::MainMenu(_), MessageBox::Add(_)) => main_menu::tests::message::add_failure(),
             (State::MainMenu(_), MessageBox::List(_)) => {
                 resources_list::tests::message::from_main_menu_by_list_success();
-                resources_list::tests::message::from_main_menu_by_list_with_empty_user_failure();
+                resources_list::tests::message::from_main_menu_by_list_with_empty_vault_success();
+            }
+            (State::MainMenu(_), MessageBox::RetryStorage(_)) => {
+                main_menu::tests::message::retry_storage_success();
+                main_menu::tests::message::retry_storage_still_unavailable_failure()
             }
             (State::MainMenu(_), MessageBox::Arbitrary(_)) => {
                 main_menu::tests::message::arbitrary_failure()
@@ -475,6 +987,9 @@ mod tests {
             (State::ResourcesList(_), MessageBox::List(_)) => {
                 resources_list::tests::message::list_failure()
             }
+            (State::ResourcesList(_), MessageBox::RetryStorage(_)) => {
+                resources_list::tests::message::retry_storage_failure()
+            }
             (State::ResourcesList(_), MessageBox::Arbitrary(_)) => {
                 resource_actions::tests::message::from_resources_list_by_existing_resource_success(
                 );
@@ -490,6 +1005,9 @@ mod tests {
             (State::ResourceActions(_), MessageBox::List(_)) => {
                 resource_actions::tests::message::list_failure()
             }
+            (State::ResourceActions(_), MessageBox::RetryStorage(_)) => {
+                resource_actions::tests::message::retry_storage_failure()
+            }
             (State::ResourceActions(_), MessageBox::Arbitrary(_)) => {
                 resource_actions::tests::message::arbitrary_failure()
             }
@@ -502,9 +1020,63 @@ mod tests {
             (State::DeleteConfirmation(_), MessageBox::List(_)) => {
                 delete_confirmation::tests::message::list_failure()
             }
+            (State::DeleteConfirmation(_), MessageBox::RetryStorage(_)) => {
+                delete_confirmation::tests::message::retry_storage_failure()
+            }
             (State::DeleteConfirmation(_), MessageBox::Arbitrary(_)) => {
                 delete_confirmation::tests::message::arbitrary_failure()
             }
+            (State::DuplicateNamePrompt(_), MessageBox::WebApp(_)) => {
+                duplicate_name_prompt::tests::message::web_app_failure()
+            }
+            (State::DuplicateNamePrompt(_), MessageBox::Add(_)) => {
+                duplicate_name_prompt::tests::message::add_failure()
+            }
+            (State::DuplicateNamePrompt(_), MessageBox::List(_)) => {
+                duplicate_name_prompt::tests::message::list_failure()
+            }
+            (State::DuplicateNamePrompt(_), MessageBox::RetryStorage(_)) => {
+                duplicate_name_prompt::tests::message::retry_storage_failure()
+            }
+            (State::DuplicateNamePrompt(_), MessageBox::Arbitrary(_)) => {
+                main_menu::tests::message::from_duplicate_name_prompt_by_arbitrary_success();
+                main_menu::tests::message::from_duplicate_name_prompt_by_existing_name_failure();
+                main_menu::tests::message::from_duplicate_name_prompt_by_blank_name_failure()
+            }
+            (State::DeepFindPrompt(_), MessageBox::WebApp(_)) => {
+                resources_list::tests::message::from_deep_find_prompt_by_web_app_success();
+                resources_list::tests::message::from_deep_find_prompt_by_web_app_without_results_failure();
+                resources_list::tests::message::from_deep_find_prompt_by_web_app_wrong_button_text_failure();
+                resources_list::tests::message::from_deep_find_prompt_by_web_app_wrong_data_failure(
+                )
+            }
+            (State::DeepFindPrompt(_), MessageBox::Add(_)) => {
+                deep_find_prompt::tests::message::add_failure()
+            }
+            (State::DeepFindPrompt(_), MessageBox::List(_)) => {
+                deep_find_prompt::tests::message::list_failure()
+            }
+            (State::DeepFindPrompt(_), MessageBox::RetryStorage(_)) => {
+                deep_find_prompt::tests::message::retry_storage_failure()
+            }
+            (State::DeepFindPrompt(_), MessageBox::Arbitrary(_)) => {
+                deep_find_prompt::tests::message::arbitrary_failure()
+            }
+            (State::RenamePrefixConfirmation(_), MessageBox::WebApp(_)) => {
+                rename_prefix_confirmation::tests::message::web_app_failure()
+            }
+            (State::RenamePrefixConfirmation(_), MessageBox::Add(_)) => {
+                rename_prefix_confirmation::tests::message::add_failure()
+            }
+            (State::RenamePrefixConfirmation(_), MessageBox::List(_)) => {
+                rename_prefix_confirmation::tests::message::list_failure()
+            }
+            (State::RenamePrefixConfirmation(_), MessageBox::RetryStorage(_)) => {
+                rename_prefix_confirmation::tests::message::retry_storage_failure()
+            }
+            (State::RenamePrefixConfirmation(_), MessageBox::Arbitrary(_)) => {
+                rename_prefix_confirmation::tests::message::arbitrary_failure()
+            }
         }
 
         // Will fail to compile if a new state or button will be added
@@ -513,12 +1085,18 @@ mod tests {
             (State::Default(_), ButtonBox::Yes(_)) => default::tests::button::yes_failure(),
             (State::Default(_), ButtonBox::No(_)) => default::tests::button::no_failure(),
             (State::Default(_), ButtonBox::Show(_)) => default::tests::button::show_failure(),
+            (State::Default(_), ButtonBox::Duplicate(_)) => {
+                default::tests::button::duplicate_failure()
+            }
             (State::MainMenu(_), ButtonBox::Delete(_)) => {
                 main_menu::tests::button::delete_failure()
             }
             (State::MainMenu(_), ButtonBox::Yes(_)) => main_menu::tests::button::yes_failure(),
             (State::MainMenu(_), ButtonBox::No(_)) => main_menu::tests::button::no_failure(),
             (State::MainMenu(_), ButtonBox::Show(_)) => main_menu::tests::button::show_failure(),
+            (State::MainMenu(_), ButtonBox::Duplicate(_)) => {
+                main_menu::tests::button::duplicate_failure()
+            }
             (State::ResourcesList(_), ButtonBox::Delete(_)) => {
                 resources_list::tests::button::delete_failure()
             }
@@ -531,8 +1109,12 @@ mod tests {
             (State::ResourcesList(_), ButtonBox::Show(_)) => {
                 resources_list::tests::button::show_failure()
             }
+            (State::ResourcesList(_), ButtonBox::Duplicate(_)) => {
+                resources_list::tests::button::duplicate_failure()
+            }
             (State::ResourceActions(_), ButtonBox::Delete(_)) => {
-                delete_confirmation::tests::button::from_resource_actions_by_delete_success()
+                delete_confirmation::tests::button::from_resource_actions_by_delete_success();
+                delete_confirmation::tests::button::from_resource_actions_by_delete_as_viewer_failure()
             }
             (State::ResourceActions(_), ButtonBox::Yes(_)) => {
                 resource_actions::tests::button::yes_failure()
@@ -543,11 +1125,16 @@ mod tests {
             (State::ResourceActions(_), ButtonBox::Show(_)) => {
                 resource_actions::tests::button::show_failure()
             }
+            (State::ResourceActions(_), ButtonBox::Duplicate(_)) => {
+                duplicate_name_prompt::tests::button::from_resource_actions_by_duplicate_success();
+                duplicate_name_prompt::tests::button::from_resource_actions_by_duplicate_as_viewer_failure()
+            }
             (State::DeleteConfirmation(_), ButtonBox::Delete(_)) => {
                 delete_confirmation::tests::button::delete_failure()
             }
             (State::DeleteConfirmation(_), ButtonBox::Yes(_)) => {
-                main_menu::tests::button::from_delete_confirmation_by_yes_success()
+                main_menu::tests::button::from_delete_confirmation_by_yes_success();
+                main_menu::tests::button::from_delete_confirmation_by_yes_as_viewer_failure()
             }
             (State::DeleteConfirmation(_), ButtonBox::No(_)) => {
                 resource_actions::tests::button::from_delete_confirmation_by_no_success()
@@ -555,6 +1142,56 @@ mod tests {
             (State::DeleteConfirmation(_), ButtonBox::Show(_)) => {
                 delete_confirmation::tests::button::show_failure()
             }
+            (State::DeleteConfirmation(_), ButtonBox::Duplicate(_)) => {
+                delete_confirmation::tests::button::duplicate_failure()
+            }
+            (State::DuplicateNamePrompt(_), ButtonBox::Delete(_)) => {
+                duplicate_name_prompt::tests::button::delete_failure()
+            }
+            (State::DuplicateNamePrompt(_), ButtonBox::Yes(_)) => {
+                duplicate_name_prompt::tests::button::yes_failure()
+            }
+            (State::DuplicateNamePrompt(_), ButtonBox::No(_)) => {
+                duplicate_name_prompt::tests::button::no_failure()
+            }
+            (State::DuplicateNamePrompt(_), ButtonBox::Show(_)) => {
+                duplicate_name_prompt::tests::button::show_failure()
+            }
+            (State::DuplicateNamePrompt(_), ButtonBox::Duplicate(_)) => {
+                duplicate_name_prompt::tests::button::duplicate_failure()
+            }
+            (State::DeepFindPrompt(_), ButtonBox::Delete(_)) => {
+                deep_find_prompt::tests::button::delete_failure()
+            }
+            (State::DeepFindPrompt(_), ButtonBox::Yes(_)) => {
+                deep_find_prompt::tests::button::yes_failure()
+            }
+            (State::DeepFindPrompt(_), ButtonBox::No(_)) => {
+                deep_find_prompt::tests::button::no_failure()
+            }
+            (State::DeepFindPrompt(_), ButtonBox::Show(_)) => {
+                deep_find_prompt::tests::button::show_failure()
+            }
+            (State::DeepFindPrompt(_), ButtonBox::Duplicate(_)) => {
+                deep_find_prompt::tests::button::duplicate_failure()
+            }
+            (State::RenamePrefixConfirmation(_), ButtonBox::Delete(_)) => {
+                rename_prefix_confirmation::tests::button::delete_failure()
+            }
+            (State::RenamePrefixConfirmation(_), ButtonBox::Yes(_)) => {
+                main_menu::tests::button::from_rename_prefix_confirmation_by_yes_success();
+                main_menu::tests::button::from_rename_prefix_confirmation_by_yes_with_conflicts_success();
+                main_menu::tests::button::from_rename_prefix_confirmation_by_yes_as_viewer_failure()
+            }
+            (State::RenamePrefixConfirmation(_), ButtonBox::No(_)) => {
+                main_menu::tests::button::from_rename_prefix_confirmation_by_no_success()
+            }
+            (State::RenamePrefixConfirmation(_), ButtonBox::Show(_)) => {
+                rename_prefix_confirmation::tests::button::show_failure()
+            }
+            (State::RenamePrefixConfirmation(_), ButtonBox::Duplicate(_)) => {
+                rename_prefix_confirmation::tests::button::duplicate_failure()
+            }
         }
 
         unreachable!()
@@ -0,0 +1,304 @@
+//! [`Deep find prompt`](DeepFindPrompt) state implementation.
+
+#[cfg(not(test))]
+use teloxide::payloads::SendMessageSetters as _;
+use teloxide::types::{KeyboardButton, KeyboardMarkup};
+
+use super::{main_menu::MainMenu, web_app_route_url, Context};
+use crate::{
+    command,
+    footer::{self, MessageClass},
+    transition::{try_with_state, FailedTransition, TransitionFailureReason, TryFromTransition},
+};
+
+/// Text of the Web App button computing the search token.
+pub const BUTTON_TEXT: &str = "🔎 Deep find";
+
+/// State when bot is waiting for the Web App to send a token of the searched keyword.
+///
+/// Records content is encrypted, so the keyword is blinded with the master password in the
+/// Web App and the storage matches the token against blind indexes of the records.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeepFindPrompt {
+    /// Normalized keyword to search for.
+    keyword: String,
+}
+
+impl DeepFindPrompt {
+    /// Create a new [`DeepFindPrompt`] state for tests.
+    #[cfg(test)]
+    pub fn test() -> Self {
+        Self {
+            keyword: "router".to_owned(),
+        }
+    }
+
+    /// Get keyword to search for.
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+}
+
+impl TryFromTransition<MainMenu, command::DeepFind> for DeepFindPrompt {
+    type ErrorTarget = MainMenu;
+
+    async fn try_from_transition(
+        main_menu: MainMenu,
+        deep_find: command::DeepFind,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
+        let mut keywords = telepass_data_model::crypto::keywords(&deep_find.0).into_iter();
+        let (Some(keyword), None) = (keywords.next(), keywords.next()) else {
+            return Err(FailedTransition::user(
+                main_menu,
+                format!(
+                    "❎ Type a single word of at least {} letters to search for, \
+                     e.g. /deepfind router.",
+                    telepass_data_model::crypto::MIN_KEYWORD_LENGTH
+                ),
+            ));
+        };
+
+        let mut url = web_app_route_url(context, "/deepfind");
+        url.query_pairs_mut().append_pair("word", &keyword);
+        let keyboard = KeyboardMarkup::new([[KeyboardButton::new(BUTTON_TEXT).request(
+            teloxide::types::ButtonRequest::WebApp(teloxide::types::WebAppInfo { url }),
+        )]])
+        .resize_keyboard();
+
+        try_with_state!(
+            main_menu,
+            footer::send_text(
+                context,
+                format!(
+                    "🔎 Press the button below and enter your master password \
+                         to find records mentioning \"{keyword}\".\n\nType /cancel to go back."
+                ),
+                MessageClass::Plain
+            )
+            .reply_markup(keyboard)
+            .await
+            .map_err(TransitionFailureReason::internal)
+        );
+
+        Ok(Self { keyword })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    pub mod command {
+        use teloxide::types::{KeyboardButton, KeyboardMarkup};
+        use tokio::test;
+
+        use crate::{
+            command::Command,
+            state::{
+                deep_find_prompt::{DeepFindPrompt, BUTTON_TEXT},
+                Context, State,
+            },
+            test_utils::{
+                mock_bot::{MockBotBuilder, CHAT_ID},
+                test_help_success, test_time_zone_success, test_unavailable_command,
+                test_whats_new_success, web_app_test_url,
+            },
+            transition::{TransitionFailureReason, TryFromTransition as _},
+        };
+
+        #[test]
+        pub async fn help_success() {
+            let deep_find_prompt = State::deep_find_prompt();
+
+            test_help_success(deep_find_prompt).await
+        }
+
+        #[test]
+        pub async fn whats_new_success() {
+            let deep_find_prompt = State::deep_find_prompt();
+
+            test_whats_new_success(deep_find_prompt).await
+        }
+
+        #[test]
+        pub async fn time_zone_success() {
+            let deep_find_prompt = State::deep_find_prompt();
+
+            test_time_zone_success(deep_find_prompt).await
+        }
+
+        #[test]
+        pub async fn start_failure() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let start = Command::start();
+
+            test_unavailable_command(deep_find_prompt, start).await
+        }
+
+        #[test]
+        pub async fn add_failure() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let add = Command::add();
+
+            test_unavailable_command(deep_find_prompt, add).await
+        }
+
+        #[test]
+        pub async fn deep_find_failure() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let deep_find = Command::deep_find("router");
+
+            test_unavailable_command(deep_find_prompt, deep_find).await
+        }
+
+        #[test]
+        pub async fn rename_prefix_failure() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let rename_prefix = Command::rename_prefix("work/", "team/");
+
+            test_unavailable_command(deep_find_prompt, rename_prefix).await
+        }
+
+        #[test]
+        pub async fn from_main_menu_by_deep_find_success() {
+            let main_menu = State::main_menu();
+            let deep_find = Command::deep_find("Router");
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context.expect_bot().return_const(
+                MockBotBuilder::new()
+                    .expect_send_message(
+                        "🔎 Press the button below and enter your master password \
+                         to find records mentioning \"router\".\n\nType /cancel to go back."
+                            .to_owned(),
+                    )
+                    .expect_reply_markup(
+                        KeyboardMarkup::new([[KeyboardButton::new(BUTTON_TEXT).request(
+                            teloxide::types::ButtonRequest::WebApp(teloxide::types::WebAppInfo {
+                                url: web_app_test_url().join("/deepfind?word=router").unwrap(),
+                            }),
+                        )]])
+                        .resize_keyboard(),
+                    )
+                    .expect_into_future()
+                    .build(),
+            );
+
+            let state = State::try_from_transition(main_menu, deep_find, &mock_context)
+                .await
+                .unwrap();
+            assert_eq!(state, State::DeepFindPrompt(DeepFindPrompt::test()));
+        }
+
+        #[test]
+        pub async fn from_main_menu_by_deep_find_without_single_keyword_failure() {
+            let main_menu = State::main_menu();
+
+            for word in ["", "the", "ab", "home router"] {
+                let deep_find = Command::deep_find(word);
+                let mock_context = Context::default();
+
+                let err = State::try_from_transition(main_menu.clone(), deep_find, &mock_context)
+                    .await
+                    .unwrap_err();
+                assert!(matches!(
+                    err.reason,
+                    TransitionFailureReason::User(message) if message.starts_with("❎ Type a single word"),
+                ));
+                assert_eq!(err.target, main_menu);
+            }
+        }
+    }
+
+    pub mod message {
+        use tokio::test;
+
+        use crate::{message::MessageBox, state::State, test_utils::test_unexpected_message};
+
+        #[test]
+        pub async fn add_failure() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let add = MessageBox::add();
+
+            test_unexpected_message(deep_find_prompt, add).await
+        }
+
+        #[test]
+        pub async fn list_failure() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let list = MessageBox::list();
+
+            test_unexpected_message(deep_find_prompt, list).await
+        }
+
+        #[test]
+        pub async fn retry_storage_failure() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let retry_storage = MessageBox::retry_storage();
+
+            test_unexpected_message(deep_find_prompt, retry_storage).await
+        }
+
+        #[test]
+        pub async fn arbitrary_failure() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let arbitrary = MessageBox::arbitrary("router");
+
+            test_unexpected_message(deep_find_prompt, arbitrary).await
+        }
+    }
+
+    pub mod button {
+        use tokio::test;
+
+        use crate::{button::ButtonBox, state::State, test_utils::test_unexpected_button};
+
+        #[test]
+        pub async fn delete_failure() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let delete_button = ButtonBox::delete();
+
+            test_unexpected_button(deep_find_prompt, delete_button).await;
+        }
+
+        #[test]
+        pub async fn yes_failure() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let yes_button = ButtonBox::yes();
+
+            test_unexpected_button(deep_find_prompt, yes_button).await;
+        }
+
+        #[test]
+        pub async fn no_failure() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let no_button = ButtonBox::no();
+
+            test_unexpected_button(deep_find_prompt, no_button).await;
+        }
+
+        #[test]
+        pub async fn show_failure() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let show_button = ButtonBox::show();
+
+            test_unexpected_button(deep_find_prompt, show_button).await;
+        }
+
+        #[test]
+        pub async fn duplicate_failure() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let duplicate_button = ButtonBox::duplicate();
+
+            test_unexpected_button(deep_find_prompt, duplicate_button).await;
+        }
+    }
+}
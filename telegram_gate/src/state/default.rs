@@ -12,7 +12,10 @@ pub mod tests {
         use crate::{
             command::Command,
             state::State,
-            test_utils::{test_help_success, test_unavailable_command},
+            test_utils::{
+                test_help_success, test_time_zone_success, test_unavailable_command,
+                test_whats_new_success,
+            },
         };
 
         #[test]
@@ -22,6 +25,20 @@ pub mod tests {
             test_help_success(default).await
         }
 
+        #[test]
+        pub async fn whats_new_success() {
+            let default = State::default();
+
+            test_whats_new_success(default).await
+        }
+
+        #[test]
+        pub async fn time_zone_success() {
+            let default = State::default();
+
+            test_time_zone_success(default).await
+        }
+
         #[test]
         pub async fn cancel_failure() {
             let default = State::default();
@@ -29,21 +46,37 @@ pub mod tests {
 
             test_unavailable_command(default, cancel).await
         }
-    }
 
-    pub mod message {
-        use tokio::test;
+        #[test]
+        pub async fn add_failure() {
+            let default = State::default();
+            let add = Command::add();
 
-        use crate::{message::MessageBox, state::State, test_utils::test_unexpected_message};
+            test_unavailable_command(default, add).await
+        }
 
         #[test]
-        pub async fn web_app_failure() {
+        pub async fn deep_find_failure() {
             let default = State::default();
-            let web_app = MessageBox::web_app("data".to_owned(), "button_text".to_owned());
+            let deep_find = Command::deep_find("router");
 
-            test_unexpected_message(default, web_app).await
+            test_unavailable_command(default, deep_find).await
         }
 
+        #[test]
+        pub async fn rename_prefix_failure() {
+            let default = State::default();
+            let rename_prefix = Command::rename_prefix("work/", "team/");
+
+            test_unavailable_command(default, rename_prefix).await
+        }
+    }
+
+    pub mod message {
+        use tokio::test;
+
+        use crate::{message::MessageBox, state::State, test_utils::test_unexpected_message};
+
         #[test]
         pub async fn add_failure() {
             let default = State::default();
@@ -60,6 +93,14 @@ pub mod tests {
             test_unexpected_message(default, list).await
         }
 
+        #[test]
+        pub async fn retry_storage_failure() {
+            let default = State::default();
+            let retry_storage = MessageBox::retry_storage();
+
+            test_unexpected_message(default, retry_storage).await
+        }
+
         #[test]
         pub async fn arbitrary_failure() {
             let default = State::default();
@@ -105,5 +146,13 @@ pub mod tests {
 
             test_unexpected_button(default, show_button).await;
         }
+
+        #[test]
+        pub async fn duplicate_failure() {
+            let default = State::default();
+            let duplicate_button = ButtonBox::duplicate();
+
+            test_unexpected_button(default, duplicate_button).await;
+        }
     }
 }
@@ -2,7 +2,6 @@
 
 use std::sync::Arc;
 
-use teloxide::utils::markdown;
 #[cfg(not(test))]
 use teloxide::{
     payloads::{EditMessageReplyMarkupSetters as _, EditMessageTextSetters as _},
@@ -14,7 +13,8 @@ use tracing::debug;
 use super::{resource_actions::ResourceActions, Context, DisplayedResourceData};
 use crate::{
     button::{self, Button},
-    grpc,
+    grpc, markdown,
+    role::PERMISSION_DENIED,
     transition::{
         try_with_state, Destroy, FailedTransition, TransitionFailureReason, TryFromTransition,
     },
@@ -42,7 +42,15 @@ impl DeleteConfirmation {
                     name: resource_name,
                 }),
                 encrypted_payload: b"unused".to_vec(),
-                salt: b"unused".to_vec(),
+                salt: vec![0; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+                kdf_iterations: 0,
+                kdf_salt: Vec::new(),
+                algorithm: 0,
+                bound_to_resource_name: false,
+                revision: 0,
+                chunk_count: 0,
+                key_commitment: Vec::new(),
+                output_version: 0,
             },
             displayed_resource_data,
         }
@@ -58,10 +66,26 @@ impl DeleteConfirmation {
         self.record
     }
 
+    /// Replace record with its fresh revision.
+    #[must_use]
+    pub fn with_record(mut self, record: grpc::Record) -> Self {
+        self.record = record;
+        self
+    }
+
     /// Get displayed resource data.
     pub fn displayed_resource_data(&self) -> Arc<RwLock<DisplayedResourceData>> {
         Arc::clone(&self.displayed_resource_data)
     }
+
+    /// Construct prompt to confirm deletion of `resource_name`.
+    #[must_use]
+    pub fn prompt_text(resource_name: &str) -> String {
+        format!(
+            "🗑 Delete {} forever?",
+            markdown::bold(&markdown::escape(resource_name))
+        )
+    }
 }
 
 impl Destroy for DeleteConfirmation {
@@ -98,6 +122,10 @@ impl TryFromTransition<ResourceActions, Button<button::kind::Delete>> for Delete
         _delete_button: Button<button::kind::Delete>,
         context: &Context,
     ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
+        if !context.role().can_manage() {
+            return Err(FailedTransition::user(resource_actions, PERMISSION_DENIED));
+        }
+
         let resource_message_id = resource_actions
             .displayed_resource_data()
             .read()
@@ -111,15 +139,12 @@ impl TryFromTransition<ResourceActions, Button<button::kind::Delete>> for Delete
                 .edit_message_text(
                     context.chat_id(),
                     resource_message_id,
-                    format!(
-                        "🗑 Delete {} forever?",
-                        markdown::bold(&markdown::escape(
-                            &resource_actions
-                                .displayed_resource_data()
-                                .read()
-                                .await
-                                .resource_name
-                        ))
+                    Self::prompt_text(
+                        &resource_actions
+                            .displayed_resource_data()
+                            .read()
+                            .await
+                            .resource_name
                     )
                 )
                 .parse_mode(teloxide::types::ParseMode::MarkdownV2)
@@ -163,7 +188,10 @@ pub mod tests {
         use crate::{
             command::Command,
             state::State,
-            test_utils::{test_help_success, test_unavailable_command},
+            test_utils::{
+                test_help_success, test_time_zone_success, test_unavailable_command,
+                test_whats_new_success,
+            },
         };
 
         #[test]
@@ -173,6 +201,20 @@ pub mod tests {
             test_help_success(delete_confirmation).await
         }
 
+        #[test]
+        pub async fn whats_new_success() {
+            let delete_confirmation = State::delete_confirmation(true).await;
+
+            test_whats_new_success(delete_confirmation).await
+        }
+
+        #[test]
+        pub async fn time_zone_success() {
+            let delete_confirmation = State::delete_confirmation(true).await;
+
+            test_time_zone_success(delete_confirmation).await
+        }
+
         #[test]
         pub async fn start_failure() {
             let delete_confirmation = State::delete_confirmation(true).await;
@@ -180,6 +222,30 @@ pub mod tests {
 
             test_unavailable_command(delete_confirmation, start).await
         }
+
+        #[test]
+        pub async fn add_failure() {
+            let delete_confirmation = State::delete_confirmation(true).await;
+            let add = Command::add();
+
+            test_unavailable_command(delete_confirmation, add).await
+        }
+
+        #[test]
+        pub async fn deep_find_failure() {
+            let delete_confirmation = State::delete_confirmation(true).await;
+            let deep_find = Command::deep_find("router");
+
+            test_unavailable_command(delete_confirmation, deep_find).await
+        }
+
+        #[test]
+        pub async fn rename_prefix_failure() {
+            let delete_confirmation = State::delete_confirmation(true).await;
+            let rename_prefix = Command::rename_prefix("work/", "team/");
+
+            test_unavailable_command(delete_confirmation, rename_prefix).await
+        }
     }
 
     pub mod message {
@@ -203,6 +269,14 @@ pub mod tests {
             test_unexpected_message(delete_confirmation, list).await
         }
 
+        #[test]
+        pub async fn retry_storage_failure() {
+            let delete_confirmation = State::delete_confirmation(true).await;
+            let retry_storage = MessageBox::retry_storage();
+
+            test_unexpected_message(delete_confirmation, retry_storage).await
+        }
+
         #[test]
         pub async fn add_failure() {
             let delete_confirmation = State::delete_confirmation(true).await;
@@ -227,12 +301,13 @@ pub mod tests {
 
         use crate::{
             button::ButtonBox,
+            role::{Role, PERMISSION_DENIED},
             state::{resource_actions::ResourceActions, Context, DisplayedResourceData, State},
             test_utils::{
                 mock_bot::{MockBotBuilder, CHAT_ID},
                 test_unexpected_button,
             },
-            transition::TryFromTransition as _,
+            transition::{TransitionFailureReason, TryFromTransition as _},
         };
 
         #[test]
@@ -251,6 +326,7 @@ pub mod tests {
 
             let mut mock_context = Context::default();
             mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context.expect_role().return_const(Role::Admin);
 
             let expected_reply_markup = teloxide::types::InlineKeyboardMarkup::new([[
                 crate::button::kind::Yes.to_string(),
@@ -283,10 +359,29 @@ pub mod tests {
                 .displayed_resource_data
                 .write()
                 .await
-                .bomb
+                .messages
                 .defuse();
         }
 
+        #[test]
+        pub async fn from_resource_actions_by_delete_as_viewer_failure() {
+            let resource_actions = State::resource_actions(true);
+            let delete_button = ButtonBox::delete();
+
+            let mut mock_context = Context::default();
+            mock_context.expect_role().return_const(Role::Viewer);
+
+            let err =
+                State::try_from_transition(resource_actions.clone(), delete_button, &mock_context)
+                    .await
+                    .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == PERMISSION_DENIED,
+            ));
+            assert_eq!(err.target, resource_actions);
+        }
+
         #[test]
         pub async fn show_failure() {
             let delete_confirmation = State::delete_confirmation(true).await;
@@ -302,5 +397,13 @@ pub mod tests {
 
             test_unexpected_button(delete_confirmation, delete_button).await;
         }
+
+        #[test]
+        pub async fn duplicate_failure() {
+            let delete_confirmation = State::delete_confirmation(true).await;
+            let duplicate_button = ButtonBox::duplicate();
+
+            test_unexpected_button(delete_confirmation, duplicate_button).await;
+        }
     }
 }
@@ -0,0 +1,432 @@
+//! [`Duplicate name prompt`](DuplicateNamePrompt) state implementation.
+
+use std::sync::Arc;
+
+#[cfg(not(test))]
+use teloxide::{
+    payloads::{EditMessageReplyMarkupSetters as _, EditMessageTextSetters as _},
+    requests::Requester as _,
+};
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use super::{resource_actions::ResourceActions, Context, DisplayedResourceData};
+use crate::{
+    button::{self, Button},
+    grpc, markdown,
+    role::PERMISSION_DENIED,
+    transition::{
+        try_with_state, Destroy, FailedTransition, TransitionFailureReason, TryFromTransition,
+    },
+};
+
+/// Message explaining why a record bound to its name can't be duplicated.
+pub const BOUND_RECORD_DUPLICATION: &str =
+    "❎ This record is protected from being moved to another name, so it can't be copied. \
+     Add a new record with the same content instead.";
+
+/// State when bot is waiting for user to type a name for a copy of the resource.
+///
+/// Copy reuses encrypted payload and salt of the source record, so it has the same
+/// master password and the same content. Payloads bound to the resource name can't be
+/// re-encrypted by the bot, so such records are never copied.
+#[derive(Debug, Clone)]
+pub struct DuplicateNamePrompt {
+    /// Source record to copy.
+    record: grpc::Record,
+    /// Currently displayed messages related to a resource.
+    displayed_resource_data: Arc<RwLock<DisplayedResourceData>>,
+}
+
+impl DuplicateNamePrompt {
+    /// Create a new [`DuplicateNamePrompt`] state for tests.
+    #[cfg(test)]
+    pub async fn test(displayed_resource_data: Arc<RwLock<DisplayedResourceData>>) -> Self {
+        let resource_name = displayed_resource_data.read().await.resource_name.clone();
+        Self {
+            record: grpc::Record {
+                resource: Some(grpc::Resource {
+                    name: resource_name,
+                }),
+                encrypted_payload: b"unused".to_vec(),
+                salt: vec![0; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+                kdf_iterations: 0,
+                kdf_salt: Vec::new(),
+                algorithm: 0,
+                bound_to_resource_name: false,
+                revision: 0,
+                chunk_count: 0,
+                key_commitment: Vec::new(),
+                output_version: 0,
+            },
+            displayed_resource_data,
+        }
+    }
+
+    /// Get source record.
+    pub const fn record(&self) -> &grpc::Record {
+        &self.record
+    }
+
+    /// Get displayed resource data.
+    pub fn displayed_resource_data(&self) -> Arc<RwLock<DisplayedResourceData>> {
+        Arc::clone(&self.displayed_resource_data)
+    }
+
+    /// Construct prompt to type a name for a copy of `resource_name`.
+    #[must_use]
+    pub fn prompt_text(resource_name: &str) -> String {
+        format!(
+            "📄 Type a name for a copy of {}\\.",
+            markdown::bold(&markdown::escape(resource_name))
+        )
+    }
+}
+
+impl Destroy for DuplicateNamePrompt {
+    async fn destroy(self, context: &Context) -> color_eyre::Result<()> {
+        let Some(displayed_resource_data_lock) = Arc::into_inner(self.displayed_resource_data)
+        else {
+            debug!(
+                "There are other strong references to `DisplayedResourceData`, skipping deletion"
+            );
+            return Ok(());
+        };
+        displayed_resource_data_lock
+            .into_inner()
+            .delete_messages(context)
+            .await
+    }
+}
+
+impl PartialEq for DuplicateNamePrompt {
+    /// [`Arc`] pointer comparison without accessing the inner value.
+    fn eq(&self, other: &Self) -> bool {
+        (&self.record, Arc::as_ptr(&self.displayed_resource_data))
+            == (&other.record, Arc::as_ptr(&other.displayed_resource_data))
+    }
+}
+
+impl Eq for DuplicateNamePrompt {}
+
+impl TryFromTransition<ResourceActions, Button<button::kind::Duplicate>> for DuplicateNamePrompt {
+    type ErrorTarget = ResourceActions;
+
+    async fn try_from_transition(
+        resource_actions: ResourceActions,
+        _duplicate_button: Button<button::kind::Duplicate>,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
+        if !context.role().can_manage() {
+            return Err(FailedTransition::user(resource_actions, PERMISSION_DENIED));
+        }
+        if resource_actions.record().bound_to_resource_name {
+            return Err(FailedTransition::user(
+                resource_actions,
+                BOUND_RECORD_DUPLICATION,
+            ));
+        }
+
+        let resource_message_id;
+        let resource_name;
+        {
+            let displayed_resource_data = resource_actions.displayed_resource_data();
+            let displayed_resource_data = displayed_resource_data.read().await;
+
+            resource_message_id = displayed_resource_data.resource_message_id;
+            resource_name = displayed_resource_data.resource_name.clone();
+        }
+
+        try_with_state!(
+            resource_actions,
+            context
+                .bot()
+                .edit_message_text(
+                    context.chat_id(),
+                    resource_message_id,
+                    Self::prompt_text(&resource_name)
+                )
+                .parse_mode(teloxide::types::ParseMode::MarkdownV2)
+                .await
+                .map_err(TransitionFailureReason::internal)
+        );
+
+        try_with_state!(
+            resource_actions,
+            context
+                .bot()
+                .edit_message_reply_markup(context.chat_id(), resource_message_id)
+                .reply_markup(teloxide::types::InlineKeyboardMarkup::default())
+                .await
+                .map_err(TransitionFailureReason::internal)
+        );
+
+        Ok(Self {
+            displayed_resource_data: resource_actions.displayed_resource_data(),
+            record: resource_actions.take_record(),
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    #![expect(clippy::panic, clippy::unwrap_used, reason = "it's ok in tests")]
+
+    pub mod command {
+        use tokio::test;
+
+        use crate::{
+            command::Command,
+            state::State,
+            test_utils::{
+                test_help_success, test_time_zone_success, test_unavailable_command,
+                test_whats_new_success,
+            },
+        };
+
+        #[test]
+        pub async fn help_success() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+
+            test_help_success(duplicate_name_prompt).await
+        }
+
+        #[test]
+        pub async fn whats_new_success() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+
+            test_whats_new_success(duplicate_name_prompt).await
+        }
+
+        #[test]
+        pub async fn time_zone_success() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+
+            test_time_zone_success(duplicate_name_prompt).await
+        }
+
+        #[test]
+        pub async fn start_failure() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+            let start = Command::start();
+
+            test_unavailable_command(duplicate_name_prompt, start).await
+        }
+
+        #[test]
+        pub async fn add_failure() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+            let add = Command::add();
+
+            test_unavailable_command(duplicate_name_prompt, add).await
+        }
+
+        #[test]
+        pub async fn deep_find_failure() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+            let deep_find = Command::deep_find("router");
+
+            test_unavailable_command(duplicate_name_prompt, deep_find).await
+        }
+
+        #[test]
+        pub async fn rename_prefix_failure() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+            let rename_prefix = Command::rename_prefix("work/", "team/");
+
+            test_unavailable_command(duplicate_name_prompt, rename_prefix).await
+        }
+    }
+
+    pub mod message {
+        use tokio::test;
+
+        use crate::{message::MessageBox, state::State, test_utils::test_unexpected_message};
+
+        #[test]
+        pub async fn web_app_failure() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+            let web_app = MessageBox::web_app("data".to_owned(), "button_text".to_owned());
+
+            test_unexpected_message(duplicate_name_prompt, web_app).await
+        }
+
+        #[test]
+        pub async fn list_failure() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+            let list = MessageBox::list();
+
+            test_unexpected_message(duplicate_name_prompt, list).await
+        }
+
+        #[test]
+        pub async fn retry_storage_failure() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+            let retry_storage = MessageBox::retry_storage();
+
+            test_unexpected_message(duplicate_name_prompt, retry_storage).await
+        }
+
+        #[test]
+        pub async fn add_failure() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+            let add = MessageBox::add();
+
+            test_unexpected_message(duplicate_name_prompt, add).await
+        }
+    }
+
+    pub mod button {
+        use std::sync::Arc;
+
+        use tokio::{sync::RwLock, test};
+
+        use crate::{
+            button::ButtonBox,
+            role::{Role, PERMISSION_DENIED},
+            state::{
+                duplicate_name_prompt::BOUND_RECORD_DUPLICATION, resource_actions::ResourceActions,
+                Context, DisplayedResourceData, State,
+            },
+            test_utils::{
+                mock_bot::{MockBotBuilder, CHAT_ID},
+                test_unexpected_button,
+            },
+            transition::{TransitionFailureReason, TryFromTransition as _},
+        };
+
+        #[test]
+        pub async fn from_resource_actions_by_duplicate_success() {
+            let resource_message_id = teloxide::types::MessageId(702);
+
+            let resource_actions = State::ResourceActions(ResourceActions::test(Arc::new(
+                RwLock::new(DisplayedResourceData::new(
+                    teloxide::types::MessageId(700),
+                    teloxide::types::MessageId(701),
+                    resource_message_id,
+                    "test.resource.com".to_owned(),
+                )),
+            )));
+            let duplicate_button = ButtonBox::duplicate();
+
+            let mut mock_context = Context::default();
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context.expect_role().return_const(Role::Admin);
+            mock_context.expect_bot().return_const(
+                MockBotBuilder::new()
+                    .expect_edit_message_text(
+                        resource_message_id,
+                        "📄 Type a name for a copy of *test\\.resource\\.com*\\.".to_owned(),
+                    )
+                    .expect_parse_mode(teloxide::types::ParseMode::MarkdownV2)
+                    .expect_into_future()
+                    .expect_edit_message_reply_markup(resource_message_id)
+                    .expect_reply_markup(teloxide::types::InlineKeyboardMarkup::default())
+                    .expect_into_future()
+                    .build(),
+            );
+
+            let state =
+                State::try_from_transition(resource_actions, duplicate_button, &mock_context)
+                    .await
+                    .unwrap();
+            let State::DuplicateNamePrompt(duplicate_name_prompt) = state else {
+                panic!("Expected `State::DuplicateNamePrompt`, got {state:?}");
+            };
+            duplicate_name_prompt
+                .displayed_resource_data
+                .write()
+                .await
+                .messages
+                .defuse();
+        }
+
+        #[test]
+        pub async fn from_resource_actions_by_duplicate_as_viewer_failure() {
+            let resource_actions = State::resource_actions(true);
+            let duplicate_button = ButtonBox::duplicate();
+
+            let mut mock_context = Context::default();
+            mock_context.expect_role().return_const(Role::Viewer);
+
+            let err = State::try_from_transition(
+                resource_actions.clone(),
+                duplicate_button,
+                &mock_context,
+            )
+            .await
+            .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == PERMISSION_DENIED,
+            ));
+            assert_eq!(err.target, resource_actions);
+        }
+
+        #[test]
+        pub async fn from_resource_actions_by_duplicate_bound_record_failure() {
+            let State::ResourceActions(resource_actions) = State::resource_actions(true) else {
+                unreachable!()
+            };
+            let resource_actions =
+                State::ResourceActions(resource_actions.bound_to_resource_name());
+            let duplicate_button = ButtonBox::duplicate();
+
+            let mut mock_context = Context::default();
+            mock_context.expect_role().return_const(Role::Admin);
+
+            let err = State::try_from_transition(
+                resource_actions.clone(),
+                duplicate_button,
+                &mock_context,
+            )
+            .await
+            .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == BOUND_RECORD_DUPLICATION,
+            ));
+            assert_eq!(err.target, resource_actions);
+        }
+
+        #[test]
+        pub async fn delete_failure() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+            let delete_button = ButtonBox::delete();
+
+            test_unexpected_button(duplicate_name_prompt, delete_button).await;
+        }
+
+        #[test]
+        pub async fn yes_failure() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+            let yes_button = ButtonBox::yes();
+
+            test_unexpected_button(duplicate_name_prompt, yes_button).await;
+        }
+
+        #[test]
+        pub async fn no_failure() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+            let no_button = ButtonBox::no();
+
+            test_unexpected_button(duplicate_name_prompt, no_button).await;
+        }
+
+        #[test]
+        pub async fn show_failure() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+            let show_button = ButtonBox::show();
+
+            test_unexpected_button(duplicate_name_prompt, show_button).await;
+        }
+
+        #[test]
+        pub async fn duplicate_failure() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+            let duplicate_button = ButtonBox::duplicate();
+
+            test_unexpected_button(duplicate_name_prompt, duplicate_button).await;
+        }
+    }
+}
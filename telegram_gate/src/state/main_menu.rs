@@ -1,22 +1,39 @@
 //! [`Main menu`](MainMenu) state implementation.
 
+use color_eyre::eyre::OptionExt as _;
+use telepass_crypto::generator::{generate_password_with_rng, PasswordPolicy};
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, KeyboardButton, KeyboardMarkup};
 #[cfg(not(test))]
 use teloxide::{payloads::SendMessageSetters as _, requests::Requester as _};
-use teloxide::{
-    types::{KeyboardButton, KeyboardMarkup},
-    utils::markdown,
+use tracing::{info, warn};
+
+use super::{
+    deep_find_prompt::DeepFindPrompt,
+    delete_confirmation::DeleteConfirmation,
+    duplicate_name_prompt::{DuplicateNamePrompt, BOUND_RECORD_DUPLICATION},
+    rename_prefix_confirmation::RenamePrefixConfirmation,
+    resources_list::ResourcesList,
+    web_app_route_url, Context,
 };
-
-use super::{delete_confirmation::DeleteConfirmation, resources_list::ResourcesList, Context};
 use crate::{
     button::{self, Button},
-    command,
+    command::{self, StartAction},
+    context::RngAdapter,
+    final_message,
+    footer::{self, MessageClass},
+    grpc::{self, StorageApi as _},
+    markdown,
     message::{self, Message},
+    role::PERMISSION_DENIED,
+    sensitive_message,
     transition::{
         try_with_state, Destroy, FailedTransition, TransitionFailureReason, TryFromTransition,
     },
 };
 
+/// Header of the message with a generated password.
+const GENERATED_PASSWORD_HEADER: &str = "🎲 Generated password:";
+
 /// Main menu state.
 ///
 /// Waits for user to input an action.
@@ -56,30 +73,284 @@ impl MainMenu {
     }
 
     /// [`setup()`](Self::setup) and [`setup_destroying()`](Self::setup_destroying) implementation.
-    #[expect(clippy::expect_used, reason = "indicates programmer error")]
+    ///
+    /// If password storage is unavailable, the only action is to retry.
+    /// Otherwise also sends [quick actions](Self::send_quick_actions).
     async fn setup_impl(context: &Context) -> Result<Self, TransitionFailureReason> {
-        let buttons = [
-            [KeyboardButton::new(message::kind::List.to_string())],
-            [KeyboardButton::new(message::kind::Add.to_string()).request(
+        let available = context.storage_availability().is_available();
+        let (text, keyboard) = if available {
+            ("🏠 Welcome to the main menu.", Self::keyboard(context))
+        } else {
+            let buttons = vec![vec![KeyboardButton::new(
+                message::kind::RetryStorage.to_string(),
+            )]];
+            (
+                "🏠 Welcome to the main menu.\n⚠️ Storage unavailable — retrying automatically.",
+                KeyboardMarkup::new(buttons).resize_keyboard(),
+            )
+        };
+
+        footer::send_text(context, text, MessageClass::Plain)
+            .reply_markup(keyboard)
+            .await
+            .map_err(TransitionFailureReason::internal)?;
+
+        if available {
+            Self::send_quick_actions(context).await?;
+        }
+
+        Ok(Self(()))
+    }
+
+    /// Construct keyboard with all actions available while storage is up.
+    ///
+    /// Web App can send data only if it's opened with a reply keyboard button,
+    /// so adding a record is available only here.
+    pub fn keyboard(context: &Context) -> KeyboardMarkup {
+        KeyboardMarkup::new([
+            vec![KeyboardButton::new(message::kind::List.to_string())],
+            vec![KeyboardButton::new(message::kind::Add.to_string()).request(
                 teloxide::types::ButtonRequest::WebApp(teloxide::types::WebAppInfo {
-                    url: context
-                        .web_app_url()
-                        .clone()
-                        .join("/submit")
-                        .expect("Failed to join Web App url with `/show`"),
+                    url: web_app_route_url(context, "/submit"),
                 }),
             )],
-        ];
-        let keyboard = KeyboardMarkup::new(buttons).resize_keyboard();
+        ])
+        .resize_keyboard()
+    }
 
-        context
-            .bot()
-            .send_message(context.chat_id(), "🏠 Welcome to the main menu.")
-            .reply_markup(keyboard)
+    /// Send inline buttons with deep links to [`StartAction`]s.
+    ///
+    /// Links can be pinned or shared to other chats of the user to perform actions in one tap.
+    /// Nothing is sent if the bot username is unknown.
+    async fn send_quick_actions(context: &Context) -> Result<(), TransitionFailureReason> {
+        let Some(bot_username) = context.bot_username() else {
+            return Ok(());
+        };
+
+        let buttons = [
+            ("🗒 List", StartAction::List),
+            ("🎲 Generate password", StartAction::Generate),
+        ]
+        .into_iter()
+        .map(|(text, action)| {
+            let url = format!("https://t.me/{bot_username}?start={action}")
+                .parse()
+                .map_err(TransitionFailureReason::internal)?;
+            Ok([InlineKeyboardButton::url(text, url)])
+        })
+        .collect::<Result<Vec<_>, TransitionFailureReason>>()?;
+
+        footer::send_text(context, "⚡ Quick actions:", MessageClass::Plain)
+            .reply_markup(InlineKeyboardMarkup::new(buttons))
             .await
             .map_err(TransitionFailureReason::internal)?;
 
-        Ok(Self(()))
+        Ok(())
+    }
+
+    /// Send a random password generated with the default policy.
+    ///
+    /// Password isn't stored anywhere, so that the user can copy it into a new record.
+    /// It's sent with [`sensitive_message::send_sensitive()`], so that it doesn't stay in the chat.
+    pub async fn generate_password(
+        main_menu: Self,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self>> {
+        let password =
+            generate_password_with_rng(PasswordPolicy::default(), &mut RngAdapter(&*context.rng()));
+
+        try_with_state!(
+            main_menu,
+            sensitive_message::send_sensitive(context, GENERATED_PASSWORD_HEADER, &password)
+                .await
+                .map_err(TransitionFailureReason::internal)
+        );
+
+        Ok(main_menu)
+    }
+
+    /// Add a new record submitted from the Web App.
+    ///
+    /// # Errors
+    ///
+    /// Fails if:
+    /// - Message is sent by unexpected button;
+    /// - Message data is not a valid new record;
+    /// - Web App was opened by another user;
+    /// - Unable to add the record or its attachments to the storage.
+    ///
+    /// Confirmation is [delivered](final_message::deliver) as the final message.
+    async fn add_web_app_record(
+        web_app_msg: Message<message::kind::WebApp>,
+        context: &Context,
+    ) -> Result<(), TransitionFailureReason> {
+        let teloxide::types::WebAppData { data, button_text } = web_app_msg.kind.0;
+        if button_text != message::kind::Add.to_string() {
+            return Err(TransitionFailureReason::user(
+                "Unexpected WebApp button text.",
+            ));
+        }
+
+        let web_app_message_id = web_app_msg.id;
+        let idempotency_key = grpc::idempotency_key(context.chat_id(), web_app_message_id);
+        let replays = context.web_app_replays();
+        if let Some(outcome) = replays
+            .as_ref()
+            .and_then(|cache| cache.outcome(&idempotency_key, context.clock().now()))
+        {
+            info!("Web App message is redelivered, repeating its outcome");
+            footer::send_text(context, outcome, MessageClass::MarkdownV2)
+                .parse_mode(teloxide::types::ParseMode::MarkdownV2)
+                .await
+                .map_err(TransitionFailureReason::internal)?;
+            return Ok(());
+        }
+
+        let record: telepass_data_model::RawNewRecord =
+            super::parse_web_app_data(&data, context, "a new record")?;
+        let record = telepass_data_model::NewRecord::try_from(record)
+            .map_err(|error| TransitionFailureReason::user(format!("❎ {error}.")))?;
+        let blind_index = record.blind_index().to_vec();
+        let blind_index_version = record.blind_index_version();
+        let password_fingerprint = record.password_fingerprint();
+        let resource_name = record.resource_name().as_str().to_owned();
+        let attachments = record
+            .attachments()
+            .iter()
+            .map(grpc::Attachment::from)
+            .collect::<Vec<_>>();
+        let record = grpc::Record::from(record);
+
+        let mut request = grpc::AddRequest::new(record, idempotency_key.clone())
+            .with_blind_index(&blind_index)
+            .with_blind_index_version(blind_index_version);
+        if let Some(password_fingerprint) = password_fingerprint.as_ref() {
+            request = request.with_password_fingerprint(password_fingerprint);
+        }
+
+        let reused_by = grpc::add_record(&mut *context.storage_client().lock().await, request)
+            .await
+            .map_err(TransitionFailureReason::internal)?
+            .into_inner()
+            .reused_by;
+        Self::add_attachments(&resource_name, attachments, context).await?;
+        let confirmation = Self::saved_confirmation(&resource_name, &reused_by);
+        if let Some(replays) = replays {
+            replays.remember(idempotency_key, confirmation.clone(), context.clock().now());
+        }
+
+        // Service message only says that data was transferred, confirmation replaces it
+        if let Err(error) = context
+            .bot()
+            .delete_message(context.chat_id(), web_app_message_id)
+            .await
+        {
+            warn!(?error, "Failed to delete Web App service message");
+        }
+
+        final_message::deliver(
+            context,
+            footer::send_text(context, confirmation, MessageClass::MarkdownV2)
+                .parse_mode(teloxide::types::ParseMode::MarkdownV2),
+            &format!("{resource_name} saved"),
+        )
+        .await;
+
+        Ok(())
+    }
+
+    /// Add `attachments` to the just added record of `resource_name`.
+    ///
+    /// Attachments which already exist are skipped, so that a replayed submission succeeds.
+    async fn add_attachments(
+        resource_name: &str,
+        attachments: Vec<grpc::Attachment>,
+        context: &Context,
+    ) -> Result<(), TransitionFailureReason> {
+        for attachment in attachments {
+            let result = context
+                .storage_client()
+                .lock()
+                .await
+                .add_attachment(grpc::AddAttachmentRequest {
+                    resource: Some(grpc::Resource {
+                        name: resource_name.to_owned(),
+                    }),
+                    attachment: Some(attachment),
+                })
+                .await;
+            match result {
+                Ok(_response) => {}
+                Err(status) if status.code() == tonic::Code::AlreadyExists => {}
+                Err(status) => return Err(TransitionFailureReason::internal(status)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Construct confirmation that `resource_name` is saved warning that its password is also
+    /// used by `reused_by`.
+    #[must_use]
+    pub fn saved_confirmation(resource_name: &str, reused_by: &[String]) -> String {
+        let resource_name = markdown::bold(&markdown::escape(resource_name));
+        if reused_by.is_empty() {
+            return format!("✅ {resource_name} saved\\.");
+        }
+
+        let reused_by = reused_by
+            .iter()
+            .map(|name| format!("• {}", markdown::bold(&markdown::escape(name))))
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!(
+            "✅ {resource_name} saved\\.\n\n\
+             ⚠️ Its password is also used for:\n{reused_by}\n\nConsider changing it\\."
+        )
+    }
+
+    /// Construct confirmation that `resource_name` is deleted.
+    #[must_use]
+    pub fn deleted_confirmation(resource_name: &str) -> String {
+        format!(
+            "✅ {} deleted\\.",
+            markdown::bold(&markdown::escape(resource_name))
+        )
+    }
+
+    /// Construct confirmation that `source_name` is duplicated as `new_name`.
+    #[must_use]
+    pub fn duplicated_confirmation(source_name: &str, new_name: &str) -> String {
+        format!(
+            "✅ {} duplicated as {}\\.",
+            markdown::bold(&markdown::escape(source_name)),
+            markdown::bold(&markdown::escape(new_name))
+        )
+    }
+
+    /// Reload record of `delete_confirmation` changed by someone else, so that the user
+    /// confirms deletion of its current revision.
+    async fn reopen_changed_record(
+        delete_confirmation: DeleteConfirmation,
+        resource_name: String,
+        context: &Context,
+    ) -> FailedTransition<DeleteConfirmation> {
+        let record = grpc::get_record(
+            &mut *context.storage_client().lock().await,
+            grpc::GetRequest {
+                name: resource_name,
+                bypass_cache: true,
+            },
+        )
+        .await;
+
+        match record {
+            Ok(record) => FailedTransition::user(
+                delete_confirmation.with_record(record.into_inner()),
+                "⚠️ This record was changed by someone else, reopening it. \
+                 Press Yes again to delete it anyway.",
+            ),
+            Err(status) => FailedTransition::internal(delete_confirmation, status),
+        }
     }
 }
 
@@ -107,6 +378,55 @@ impl TryFromTransition<ResourcesList, command::Cancel> for MainMenu {
     }
 }
 
+/// Records submitted from the Web App are accepted only in [`MainMenu`],
+/// so `/add` leads there from every state it's available in.
+impl<P: Send> TryFromTransition<P, command::Add> for MainMenu {
+    type ErrorTarget = P;
+
+    async fn try_from_transition(
+        prev_state: P,
+        _add: command::Add,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
+        try_with_state!(
+            prev_state,
+            footer::send_text(
+                context,
+                "Press the button below to add a new password.",
+                MessageClass::Plain
+            )
+            .reply_markup(Self::keyboard(context))
+            .await
+            .map_err(TransitionFailureReason::internal)
+        );
+        Ok(Self(()))
+    }
+}
+
+impl TryFromTransition<DeepFindPrompt, command::Cancel> for MainMenu {
+    type ErrorTarget = DeepFindPrompt;
+
+    async fn try_from_transition(
+        deep_find_prompt: DeepFindPrompt,
+        _cancel: command::Cancel,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
+        Self::setup(deep_find_prompt, context).await
+    }
+}
+
+impl TryFromTransition<RenamePrefixConfirmation, command::Cancel> for MainMenu {
+    type ErrorTarget = RenamePrefixConfirmation;
+
+    async fn try_from_transition(
+        rename_prefix_confirmation: RenamePrefixConfirmation,
+        _cancel: command::Cancel,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
+        Self::setup_destroying(rename_prefix_confirmation, context).await
+    }
+}
+
 impl TryFromTransition<Self, Message<message::kind::WebApp>> for MainMenu {
     type ErrorTarget = Self;
 
@@ -115,34 +435,50 @@ impl TryFromTransition<Self, Message<message::kind::WebApp>> for MainMenu {
         web_app_msg: Message<message::kind::WebApp>,
         context: &Context,
     ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
-        let teloxide::types::WebAppData { data, button_text } = web_app_msg.kind.0;
-        if button_text != message::kind::Add.to_string() {
+        try_with_state!(
+            main_menu,
+            Self::add_web_app_record(web_app_msg, context).await
+        );
+
+        Ok(main_menu)
+    }
+}
+
+impl TryFromTransition<Self, Message<message::kind::RetryStorage>> for MainMenu {
+    type ErrorTarget = Self;
+
+    async fn try_from_transition(
+        main_menu: Self,
+        _retry: Message<message::kind::RetryStorage>,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
+        if !context.storage_availability().reprobe().await {
             return Err(FailedTransition::user(
                 main_menu,
-                "Unexpected WebApp button text.",
+                "⚠️ Storage is still unavailable, try again later.",
             ));
         }
 
-        let record: telepass_data_model::NewRecord = try_with_state!(
-            main_menu,
-            serde_json::from_str(&data).map_err(|_err| TransitionFailureReason::user(
-                "Failed to parse a new record, your Telegram Client is probably invalid."
-            ))
-        );
-        let record = crate::grpc::Record::from(record);
+        Self::setup(main_menu, context).await
+    }
+}
+
+/// Web App message can arrive in [`Default`](super::default::Default) state if the bot was
+/// restarted while the user was filling the form.
+impl TryFromTransition<super::default::Default, Message<message::kind::WebApp>> for MainMenu {
+    type ErrorTarget = super::default::Default;
 
+    async fn try_from_transition(
+        default: super::default::Default,
+        web_app_msg: Message<message::kind::WebApp>,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
         try_with_state!(
-            main_menu,
-            context
-                .storage_client()
-                .lock()
-                .await
-                .add(record)
-                .await
-                .map_err(TransitionFailureReason::internal)
+            default,
+            Self::add_web_app_record(web_app_msg, context).await
         );
 
-        Ok(main_menu)
+        Self::setup(default, context).await
     }
 }
 
@@ -154,6 +490,13 @@ impl TryFromTransition<DeleteConfirmation, Button<button::kind::Yes>> for MainMe
         _yes: Button<button::kind::Yes>,
         context: &Context,
     ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
+        if !context.role().can_manage() {
+            return Err(FailedTransition::user(
+                delete_confirmation,
+                PERMISSION_DENIED,
+            ));
+        }
+
         let resource_name = delete_confirmation
             .displayed_resource_data()
             .read()
@@ -161,36 +504,198 @@ impl TryFromTransition<DeleteConfirmation, Button<button::kind::Yes>> for MainMe
             .resource_name
             .clone();
 
-        try_with_state!(
-            delete_confirmation,
-            context
-                .storage_client()
-                .lock()
-                .await
-                .delete(crate::grpc::Resource {
-                    name: resource_name.clone(),
-                })
-                .await
+        let deleted = context
+            .storage_client()
+            .lock()
+            .await
+            .delete(grpc::DeleteRequest {
+                name: resource_name.clone(),
+                expected_revision: delete_confirmation.record().revision,
+            })
+            .await;
+        match deleted {
+            Ok(_response) => {}
+            Err(status) if status.code() == tonic::Code::Aborted => {
+                return Err(Self::reopen_changed_record(
+                    delete_confirmation,
+                    resource_name,
+                    context,
+                )
+                .await);
+            }
+            Err(status) if status.code() == tonic::Code::NotFound => {
+                return Err(FailedTransition::user(
+                    delete_confirmation,
+                    "❎ This record was already deleted by someone else, type /cancel to go back.",
+                ));
+            }
+            Err(status) => {
+                return Err(FailedTransition::internal(delete_confirmation, status));
+            }
+        }
+
+        final_message::deliver(
+            context,
+            footer::send_text(
+                context,
+                Self::deleted_confirmation(&resource_name),
+                MessageClass::MarkdownV2,
+            )
+            .parse_mode(teloxide::types::ParseMode::MarkdownV2),
+            &format!("{resource_name} deleted"),
+        )
+        .await;
+
+        Self::setup_destroying(delete_confirmation, context).await
+    }
+}
+
+impl TryFromTransition<RenamePrefixConfirmation, Button<button::kind::Yes>> for MainMenu {
+    type ErrorTarget = RenamePrefixConfirmation;
+
+    async fn try_from_transition(
+        rename_prefix_confirmation: RenamePrefixConfirmation,
+        _yes: Button<button::kind::Yes>,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
+        if !context.role().can_manage() {
+            return Err(FailedTransition::user(
+                rename_prefix_confirmation,
+                PERMISSION_DENIED,
+            ));
+        }
+
+        let report = rename_prefix_confirmation.rename_all(context).await;
+        let summary = rename_prefix_confirmation.summary_text(&report);
+        final_message::deliver(
+            context,
+            footer::send_text(context, summary.clone(), MessageClass::Plain),
+            &summary,
+        )
+        .await;
+
+        Self::setup_destroying(rename_prefix_confirmation, context).await
+    }
+}
+
+impl TryFromTransition<RenamePrefixConfirmation, Button<button::kind::No>> for MainMenu {
+    type ErrorTarget = RenamePrefixConfirmation;
+
+    async fn try_from_transition(
+        rename_prefix_confirmation: RenamePrefixConfirmation,
+        _no: Button<button::kind::No>,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
+        Self::setup_destroying(rename_prefix_confirmation, context).await
+    }
+}
+
+impl TryFromTransition<DuplicateNamePrompt, Message<message::kind::Arbitrary>> for MainMenu {
+    type ErrorTarget = DuplicateNamePrompt;
+
+    async fn try_from_transition(
+        duplicate_name_prompt: DuplicateNamePrompt,
+        arbitrary: Message<message::kind::Arbitrary>,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
+        let new_name = arbitrary.to_string().trim().to_owned();
+        if new_name.is_empty() {
+            return Err(FailedTransition::user(
+                duplicate_name_prompt,
+                "❎ Resource name can't be empty, type another one.",
+            ));
+        }
+
+        let source_name = try_with_state!(
+            duplicate_name_prompt,
+            duplicate_name_prompt
+                .record()
+                .resource
+                .as_ref()
+                .map(|resource| resource.name.clone())
+                .ok_or_eyre("Source record doesn't contain a resource")
                 .map_err(TransitionFailureReason::internal)
         );
 
+        let source = try_with_state!(
+            duplicate_name_prompt,
+            grpc::get_record(
+                &mut *context.storage_client().lock().await,
+                grpc::GetRequest {
+                    name: source_name.clone(),
+                    bypass_cache: false,
+                }
+            )
+            .await
+            .map_err(|status| if status.code() == tonic::Code::NotFound {
+                TransitionFailureReason::user(
+                    "❎ Source resource doesn't exist anymore, type /cancel to go back.",
+                )
+            } else {
+                TransitionFailureReason::internal(status)
+            })
+        )
+        .into_inner();
+        // Source could be replaced since the prompt
+        if source.bound_to_resource_name {
+            return Err(FailedTransition::user(
+                duplicate_name_prompt,
+                BOUND_RECORD_DUPLICATION,
+            ));
+        }
+
         try_with_state!(
-            delete_confirmation,
-            context
-                .bot()
-                .send_message(
-                    context.chat_id(),
-                    format!(
-                        "✅ {} deleted\\.",
-                        markdown::bold(&markdown::escape(&resource_name))
-                    )
+            duplicate_name_prompt,
+            grpc::add_record(
+                &mut *context.storage_client().lock().await,
+                grpc::AddRequest::new(
+                    grpc::Record {
+                        resource: Some(grpc::Resource {
+                            name: new_name.clone(),
+                        }),
+                        encrypted_payload: source.encrypted_payload,
+                        salt: source.salt,
+                        kdf_iterations: source.kdf_iterations,
+                        kdf_salt: source.kdf_salt,
+                        algorithm: source.algorithm,
+                        bound_to_resource_name: false,
+                        revision: 0,
+                        chunk_count: 0,
+                        key_commitment: source.key_commitment,
+                        output_version: source.output_version,
+                    },
+                    grpc::idempotency_key(context.chat_id(), arbitrary.id),
                 )
-                .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-                .await
-                .map_err(TransitionFailureReason::internal)
+            )
+            .await
+            .map_err(|status| {
+                if matches!(
+                    status.code(),
+                    tonic::Code::AlreadyExists | tonic::Code::InvalidArgument
+                ) {
+                    TransitionFailureReason::User(format!(
+                        "❎ {}. Type another name or /cancel to go back.",
+                        status.message()
+                    ))
+                } else {
+                    TransitionFailureReason::internal(status)
+                }
+            })
         );
 
-        Self::setup_destroying(delete_confirmation, context).await
+        final_message::deliver(
+            context,
+            footer::send_text(
+                context,
+                Self::duplicated_confirmation(&source_name, &new_name),
+                MessageClass::MarkdownV2,
+            )
+            .parse_mode(teloxide::types::ParseMode::MarkdownV2),
+            &format!("{source_name} duplicated as {new_name}"),
+        )
+        .await;
+
+        Self::setup_destroying(duplicate_name_prompt, context).await
     }
 }
 
@@ -198,47 +703,61 @@ impl TryFromTransition<DeleteConfirmation, Button<button::kind::Yes>> for MainMe
 pub mod tests {
     #![expect(clippy::unwrap_used, clippy::expect_used, reason = "it's ok in tests")]
 
+    #[test]
+    fn saved_confirmation_warns_only_about_reused_password() {
+        assert_eq!(
+            super::MainMenu::saved_confirmation("a_b.com", &[]),
+            "✅ *a\\_b\\.com* saved\\."
+        );
+        assert_eq!(
+            super::MainMenu::saved_confirmation("a.com", &["b*.com".to_owned()]),
+            "✅ *a\\.com* saved\\.\n\n\
+             ⚠️ Its password is also used for:\n\
+             • *b\\*\\.com*\n\n\
+             Consider changing it\\."
+        );
+    }
+
     pub mod command {
-        use teloxide::types::{KeyboardButton, KeyboardMarkup};
+        use std::sync::Arc;
+
+        use telepass_crypto::generator::{generate_password_with_rng, PasswordPolicy};
+        use teloxide::types::{
+            InlineKeyboardButton, InlineKeyboardMarkup, KeyboardButton, KeyboardMarkup,
+            MessageEntity, MessageId,
+        };
         use tokio::test;
 
         use crate::{
-            command::Command,
+            command::{Command, StartAction},
+            context::{MockRng, Rng, RngAdapter},
+            sensitive_message::{deletion_queue, DEFAULT_DELETION_DELAY},
             state::{Context, State},
             test_utils::{
+                main_menu_keyboard,
                 mock_bot::{MockBotBuilder, CHAT_ID},
-                test_help_success, test_unavailable_command, web_app_test_url,
+                storage_availability, test_add_success, test_help_success, test_time_zone_success,
+                test_unavailable_command, test_whats_new_success, web_app_test_url,
             },
             transition::TryFromTransition as _,
         };
 
         async fn test_main_menu_setup(state: State, cmd: Command) {
             let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
             mock_context.expect_chat_id().return_const(CHAT_ID);
             mock_context
                 .expect_web_app_url()
                 .return_const(web_app_test_url());
-            mock_context.expect_bot().return_const(
-                MockBotBuilder::new()
-                    .expect_send_message("🏠 Welcome to the main menu.")
-                    .expect_reply_markup(
-                        KeyboardMarkup::new([
-                            [KeyboardButton::new(crate::message::kind::List.to_string())],
-                            [
-                                KeyboardButton::new(crate::message::kind::Add.to_string()).request(
-                                    teloxide::types::ButtonRequest::WebApp(
-                                        teloxide::types::WebAppInfo {
-                                            url: web_app_test_url().join("/submit").unwrap(),
-                                        },
-                                    ),
-                                ),
-                            ],
-                        ])
-                        .resize_keyboard(),
-                    )
-                    .expect_into_future()
-                    .build(),
-            );
+            mock_context
+                .expect_storage_availability()
+                .return_const(storage_availability(true, true));
+            mock_context.expect_bot_username().return_const(None);
+            mock_context
+                .expect_bot()
+                .return_const(expect_welcome(MockBotBuilder::new()).build());
 
             let state = State::try_from_transition(state, cmd, &mock_context)
                 .await
@@ -246,6 +765,43 @@ pub mod tests {
             assert!(matches!(state, State::MainMenu(_)))
         }
 
+        /// Expect welcome message of the main menu with available storage.
+        pub(super) fn expect_welcome(mock_bot_builder: MockBotBuilder) -> MockBotBuilder {
+            mock_bot_builder
+                .expect_send_message("🏠 Welcome to the main menu.".to_owned())
+                .expect_reply_markup(main_menu_keyboard())
+                .expect_into_future()
+        }
+
+        /// Construct [`Rng`] producing bytes `0, 1, 2, ...`, so that generated password is
+        /// reproducible.
+        fn counting_rng() -> Arc<dyn Rng> {
+            let mut rng = MockRng::new();
+            let mut next = 0_u8;
+            rng.expect_fill_bytes().returning(move |dest| {
+                for byte in dest {
+                    *byte = next;
+                    next = next.wrapping_add(1);
+                }
+            });
+            Arc::new(rng)
+        }
+
+        /// Expect protected message with the password generated by [`counting_rng()`] hidden
+        /// under a spoiler.
+        fn expect_generated_password(mock_bot_builder: MockBotBuilder) -> MockBotBuilder {
+            let password = generate_password_with_rng(
+                PasswordPolicy::default(),
+                &mut RngAdapter(&*counting_rng()),
+            );
+
+            mock_bot_builder
+                .expect_send_message(format!("🎲 Generated password:\n{password}"))
+                .expect_entities(vec![MessageEntity::spoiler(23, password.len())])
+                .expect_protect_content(true)
+                .expect_into_future_with_id(MessageId(1))
+        }
+
         #[test]
         pub async fn help_success() {
             let main_menu = State::main_menu();
@@ -254,21 +810,42 @@ pub mod tests {
         }
 
         #[test]
-        pub async fn start_failure() {
+        pub async fn whats_new_success() {
             let main_menu = State::main_menu();
-            let start = Command::start();
 
-            test_unavailable_command(main_menu, start).await
+            test_whats_new_success(main_menu).await
         }
 
         #[test]
-        pub async fn cancel_failure() {
+        pub async fn time_zone_success() {
+            let main_menu = State::main_menu();
+
+            test_time_zone_success(main_menu).await
+        }
+
+        #[test]
+        pub async fn start_failure() {
+            let main_menu = State::main_menu();
+            let start = Command::start();
+
+            test_unavailable_command(main_menu, start).await
+        }
+
+        #[test]
+        pub async fn cancel_failure() {
             let main_menu = State::main_menu();
             let cancel = Command::cancel();
 
             test_unavailable_command(main_menu, cancel).await
         }
 
+        #[test]
+        pub async fn add_success() {
+            let main_menu = State::main_menu();
+
+            test_add_success(main_menu).await
+        }
+
         #[test]
         pub async fn from_default_by_start_success() {
             let default = State::default();
@@ -277,6 +854,139 @@ pub mod tests {
             test_main_menu_setup(default, start).await
         }
 
+        #[test]
+        pub async fn from_default_by_start_with_quick_actions_success() {
+            let default = State::default();
+            let start = Command::start();
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context
+                .expect_storage_availability()
+                .return_const(storage_availability(true, true));
+            mock_context
+                .expect_bot_username()
+                .return_const(Some(Arc::from("telepass_bot")));
+            mock_context.expect_bot().return_const(
+                expect_welcome(MockBotBuilder::new())
+                    .expect_send_message("⚡ Quick actions:".to_owned())
+                    .expect_reply_markup(InlineKeyboardMarkup::new([
+                        [InlineKeyboardButton::url(
+                            "🗒 List",
+                            "https://t.me/telepass_bot?start=list".parse().unwrap(),
+                        )],
+                        [InlineKeyboardButton::url(
+                            "🎲 Generate password",
+                            "https://t.me/telepass_bot?start=generate".parse().unwrap(),
+                        )],
+                    ]))
+                    .expect_into_future()
+                    .build(),
+            );
+
+            let state = State::try_from_transition(default, start, &mock_context)
+                .await
+                .unwrap();
+            assert!(matches!(state, State::MainMenu(_)))
+        }
+
+        #[test]
+        pub async fn from_default_by_start_generate_success() {
+            let default = State::default();
+            let start = Command::start_with(StartAction::Generate);
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context
+                .expect_storage_availability()
+                .return_const(storage_availability(true, true));
+            mock_context.expect_bot_username().return_const(None);
+            mock_context.expect_rng().return_const(counting_rng());
+            let (deletions, _deletion_worker) = deletion_queue(DEFAULT_DELETION_DELAY);
+            mock_context
+                .expect_sensitive_deletions()
+                .return_const(Some(deletions));
+            mock_context.expect_bot().return_const(
+                expect_generated_password(expect_welcome(MockBotBuilder::new())).build(),
+            );
+
+            let state = State::try_from_transition(default, start, &mock_context)
+                .await
+                .unwrap();
+            assert!(matches!(state, State::MainMenu(_)))
+        }
+
+        #[test]
+        pub async fn from_main_menu_by_start_generate_success() {
+            let main_menu = State::main_menu();
+            let start = Command::start_with(StartAction::Generate);
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context.expect_rng().return_const(counting_rng());
+            let (deletions, _deletion_worker) = deletion_queue(DEFAULT_DELETION_DELAY);
+            mock_context
+                .expect_sensitive_deletions()
+                .return_const(Some(deletions));
+            mock_context
+                .expect_bot()
+                .return_const(expect_generated_password(MockBotBuilder::new()).build());
+
+            let state = State::try_from_transition(main_menu, start, &mock_context)
+                .await
+                .unwrap();
+            assert_eq!(state, State::main_menu())
+        }
+
+        #[test]
+        pub async fn from_default_by_start_with_unavailable_storage_success() {
+            let default = State::default();
+            let start = Command::start();
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_storage_availability()
+                .return_const(storage_availability(false, false));
+            mock_context.expect_bot().return_const(
+                MockBotBuilder::new()
+                    .expect_send_message(
+                        "🏠 Welcome to the main menu.\n⚠️ Storage unavailable — retrying automatically.".to_owned(),
+                    )
+                    .expect_reply_markup(
+                        KeyboardMarkup::new([[KeyboardButton::new(
+                            crate::message::kind::RetryStorage.to_string(),
+                        )]])
+                        .resize_keyboard(),
+                    )
+                    .expect_into_future()
+                    .build(),
+            );
+
+            let state = State::try_from_transition(default, start, &mock_context)
+                .await
+                .unwrap();
+            assert!(matches!(state, State::MainMenu(_)))
+        }
+
         #[test]
         pub async fn from_resources_list_by_cancel_success() {
             let resources_list = State::resources_list();
@@ -284,116 +994,863 @@ pub mod tests {
 
             test_main_menu_setup(resources_list, cancel).await
         }
+
+        #[test]
+        pub async fn from_deep_find_prompt_by_cancel_success() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let cancel = Command::cancel();
+
+            test_main_menu_setup(deep_find_prompt, cancel).await
+        }
+
+        #[test]
+        pub async fn from_rename_prefix_confirmation_by_cancel_success() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let cancel = Command::cancel();
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context
+                .expect_storage_availability()
+                .return_const(storage_availability(true, true));
+            mock_context.expect_bot_username().return_const(None);
+            mock_context.expect_bot().return_const(
+                expect_welcome(MockBotBuilder::new())
+                    .expect_delete_message(teloxide::types::MessageId(0))
+                    .build(),
+            );
+
+            let state =
+                State::try_from_transition(rename_prefix_confirmation, cancel, &mock_context)
+                    .await
+                    .unwrap();
+            assert!(matches!(state, State::MainMenu(_)))
+        }
     }
 
     pub mod message {
+        use std::sync::Arc;
+
         use mockall::predicate;
-        use tokio::test;
+        use teloxide::types::{KeyboardButton, KeyboardMarkup, MessageId};
+        use tokio::{sync::RwLock, test};
 
         use crate::{
+            grpc,
             message::MessageBox,
-            state::{Context, State},
-            test_utils::{mock_bot::MockBotBuilder, test_unexpected_message},
+            state::{
+                duplicate_name_prompt::{DuplicateNamePrompt, BOUND_RECORD_DUPLICATION},
+                Context, DisplayedResourceData, State,
+            },
+            test_utils::{
+                mock_bot::{MockBotBuilder, CHAT_ID},
+                storage_availability, test_unexpected_message, web_app_data, web_app_test_url,
+            },
             transition::{TransitionFailureReason, TryFromTransition as _},
         };
 
+        /// Construct mock storage client returning source record, which is `bound` to its name
+        /// or not.
+        fn mock_source_storage_client(bound: bool) -> crate::PasswordStorageClient {
+            let mut mock_storage_client = crate::PasswordStorageClient::default();
+            mock_storage_client
+                .expect_get::<grpc::GetRequest>()
+                .with(predicate::eq(grpc::GetRequest {
+                    name: "test.resource.com".to_owned(),
+                    bypass_cache: false,
+                }))
+                .returning(move |request| {
+                    Ok(tonic::Response::new(grpc::Record {
+                        resource: Some(grpc::Resource { name: request.name }),
+                        encrypted_payload: b"payload".to_vec(),
+                        salt: b"salt".to_vec(),
+                        kdf_iterations: 200_000,
+                        kdf_salt: Vec::new(),
+                        algorithm: 0,
+                        bound_to_resource_name: bound,
+                        revision: 0,
+                        chunk_count: 0,
+                        key_commitment: Vec::new(),
+                        output_version: 0,
+                    }))
+                });
+            mock_storage_client
+        }
+
+        /// Construct mock storage client returning unbound source record and handling `add`
+        /// with `add_result`.
+        fn mock_duplicating_storage_client(
+            add_result: Result<grpc::AddResponse, tonic::Status>,
+        ) -> crate::PasswordStorageClient {
+            let mut mock_storage_client = mock_source_storage_client(false);
+            mock_storage_client
+                .expect_add::<grpc::AddRequest>()
+                .with(predicate::eq(grpc::AddRequest::new(
+                    grpc::Record {
+                        resource: Some(grpc::Resource {
+                            name: "copy.resource.com".to_owned(),
+                        }),
+                        encrypted_payload: b"payload".to_vec(),
+                        salt: b"salt".to_vec(),
+                        kdf_iterations: 200_000,
+                        kdf_salt: Vec::new(),
+                        algorithm: 0,
+                        bound_to_resource_name: false,
+                        revision: 0,
+                        chunk_count: 0,
+                        key_commitment: Vec::new(),
+                        output_version: 0,
+                    },
+                    grpc::idempotency_key(CHAT_ID, MessageId(0)),
+                )))
+                .return_once(|_record| add_result.map(tonic::Response::new));
+            mock_storage_client
+        }
+
         #[test]
         pub async fn web_app_success() {
             let main_menu = State::main_menu();
 
-            let record = telepass_data_model::NewRecord {
-                resource_name: "test.resource.com".to_owned(),
-                encryption_output: telepass_data_model::crypto::EncryptionOutput {
+            let blind_index = [[2; telepass_data_model::crypto::BLIND_TOKEN_SIZE]];
+            let record = telepass_data_model::NewRecord::builder()
+                .resource_name("test.resource.com")
+                .encryption_output(telepass_data_model::crypto::EncryptionOutput {
+                    version: telepass_data_model::crypto::OUTPUT_VERSION_1,
                     encrypted_payload: b"SomeSecret".to_vec(),
-                    salt: [1; telepass_data_model::crypto::SALT_SIZE],
-                },
+                    salt: telepass_data_model::crypto::Salt::Aes256Gcm(
+                        [1; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+                    ),
+                    kdf_iterations: telepass_data_model::crypto::DEFAULT_KDF_ITERATIONS,
+                    kdf_salt: None,
+                    key_commitment: None,
+                })
+                .blind_index(blind_index.to_vec())
+                .build()
+                .unwrap();
+            let web_app = MessageBox::web_app(web_app_data(&record), "🆕 Add".to_owned());
+
+            let mut mock_context = Context::default();
+            mock_context.expect_web_app_replays().return_const(None);
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_bot().return_const(
+                MockBotBuilder::new()
+                    .expect_delete_message(MessageId(0))
+                    .expect_send_message("✅ *test\\.resource\\.com* saved\\.".to_owned())
+                    .expect_parse_mode(teloxide::types::ParseMode::MarkdownV2)
+                    .expect_into_future()
+                    .build(),
+            );
+
+            let mut mock_storage_client = crate::PasswordStorageClient::default();
+            mock_storage_client
+                .expect_add::<grpc::AddRequest>()
+                .with(predicate::eq(
+                    grpc::AddRequest::new(
+                        grpc::Record::from(record),
+                        grpc::idempotency_key(CHAT_ID, MessageId(0)),
+                    )
+                    .with_blind_index(&blind_index)
+                    .with_blind_index_version(
+                        telepass_data_model::crypto::LATEST_BLIND_INDEX_VERSION,
+                    ),
+                ))
+                .returning(|_record| Ok(tonic::Response::new(grpc::AddResponse::default())));
+
+            mock_context
+                .expect_storage_client()
+                .return_const(tokio::sync::Mutex::new(mock_storage_client));
+
+            let state = State::try_from_transition(main_menu.clone(), web_app, &mock_context)
+                .await
+                .unwrap();
+            assert_eq!(state, main_menu)
+        }
+
+        #[test]
+        pub async fn web_app_redelivered_success() {
+            let main_menu = State::main_menu();
+
+            let record = telepass_data_model::NewRecord::builder()
+                .resource_name("test.resource.com")
+                .encryption_output(telepass_data_model::crypto::EncryptionOutput {
+                    version: telepass_data_model::crypto::OUTPUT_VERSION_1,
+                    encrypted_payload: b"SomeSecret".to_vec(),
+                    salt: telepass_data_model::crypto::Salt::Aes256Gcm(
+                        [1; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+                    ),
+                    kdf_iterations: telepass_data_model::crypto::DEFAULT_KDF_ITERATIONS,
+                    kdf_salt: None,
+                    key_commitment: None,
+                })
+                .build()
+                .unwrap();
+            let web_app = MessageBox::web_app(web_app_data(&record), "🆕 Add".to_owned());
+
+            let web_app_replays = Arc::new(crate::replay_cache::ReplayCache::default());
+
+            let mut mock_storage_client = crate::PasswordStorageClient::default();
+            mock_storage_client
+                .expect_add::<grpc::AddRequest>()
+                .returning(|_record| Ok(tonic::Response::new(grpc::AddResponse::default())));
+            let first_context = mock_replaying_context(
+                Arc::clone(&web_app_replays),
+                mock_storage_client,
+                MockBotBuilder::new().expect_delete_message(MessageId(0)),
+            );
+
+            let first_state =
+                State::try_from_transition(main_menu.clone(), web_app.clone(), &first_context)
+                    .await
+                    .unwrap();
+            assert_eq!(first_state, main_menu);
+
+            // The same update is delivered again after reconnect, storage is not called
+            let redelivery_context = mock_replaying_context(
+                web_app_replays,
+                crate::PasswordStorageClient::default(),
+                MockBotBuilder::new(),
+            );
+
+            let redelivery_state =
+                State::try_from_transition(main_menu.clone(), web_app, &redelivery_context)
+                    .await
+                    .unwrap();
+            assert_eq!(redelivery_state, main_menu)
+        }
+
+        /// Construct mock context with `web_app_replays` expecting saved confirmation after
+        /// `mock_bot_builder` expectations.
+        fn mock_replaying_context(
+            web_app_replays: Arc<crate::replay_cache::ReplayCache>,
+            mock_storage_client: crate::PasswordStorageClient,
+            mock_bot_builder: MockBotBuilder,
+        ) -> Context {
+            let mut mock_context = Context::default();
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_web_app_replays()
+                .return_const(Some(web_app_replays));
+            mock_context
+                .expect_clock()
+                .returning(|| Arc::new(crate::context::SystemClock));
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_bot().return_const(
+                mock_bot_builder
+                    .expect_send_message("✅ *test\\.resource\\.com* saved\\.".to_owned())
+                    .expect_parse_mode(teloxide::types::ParseMode::MarkdownV2)
+                    .expect_into_future()
+                    .build(),
+            );
+            mock_context
+                .expect_storage_client()
+                .return_const(tokio::sync::Mutex::new(mock_storage_client));
+            mock_context
+        }
+
+        #[test]
+        pub async fn web_app_with_attachments_success() {
+            let main_menu = State::main_menu();
+
+            let encryption_output = telepass_data_model::crypto::EncryptionOutput {
+                version: telepass_data_model::crypto::OUTPUT_VERSION_1,
+                encrypted_payload: b"SomeSecret".to_vec(),
+                salt: telepass_data_model::crypto::Salt::Aes256Gcm(
+                    [1; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+                ),
+                kdf_iterations: telepass_data_model::crypto::DEFAULT_KDF_ITERATIONS,
+                kdf_salt: None,
+                key_commitment: None,
             };
+            let attachments = ["a.txt", "b.txt"].map(|filename| {
+                telepass_data_model::NewAttachment::new(filename, encryption_output.clone(), 4)
+                    .unwrap()
+            });
+            let record = telepass_data_model::NewRecord::builder()
+                .resource_name("test.resource.com")
+                .encryption_output(encryption_output)
+                .attachments(attachments.to_vec())
+                .build()
+                .unwrap();
+            let web_app = MessageBox::web_app(web_app_data(&record), "🆕 Add".to_owned());
+
+            let mut mock_context = Context::default();
+            mock_context.expect_web_app_replays().return_const(None);
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_bot().return_const(
+                MockBotBuilder::new()
+                    .expect_delete_message(MessageId(0))
+                    .expect_send_message("✅ *test\\.resource\\.com* saved\\.".to_owned())
+                    .expect_parse_mode(teloxide::types::ParseMode::MarkdownV2)
+                    .expect_into_future()
+                    .build(),
+            );
+
+            let mut mock_storage_client = crate::PasswordStorageClient::default();
+            mock_storage_client
+                .expect_add::<grpc::AddRequest>()
+                .returning(|_record| Ok(tonic::Response::new(grpc::AddResponse::default())));
+            let mut sequence = mockall::Sequence::new();
+            for (attachment, result) in attachments.iter().zip([
+                // Already added by the replayed submission
+                Err(tonic::Status::already_exists("a.txt")),
+                Ok(tonic::Response::new(grpc::Response {})),
+            ]) {
+                mock_storage_client
+                    .expect_add_attachment::<grpc::AddAttachmentRequest>()
+                    .with(predicate::eq(grpc::AddAttachmentRequest {
+                        resource: Some(grpc::Resource {
+                            name: "test.resource.com".to_owned(),
+                        }),
+                        attachment: Some(grpc::Attachment::from(attachment)),
+                    }))
+                    .times(1)
+                    .in_sequence(&mut sequence)
+                    .return_once(move |_request| result);
+            }
+
+            mock_context
+                .expect_storage_client()
+                .return_const(tokio::sync::Mutex::new(mock_storage_client));
+
+            let state = State::try_from_transition(main_menu.clone(), web_app, &mock_context)
+                .await
+                .unwrap();
+            assert_eq!(state, main_menu)
+        }
+
+        #[test]
+        pub async fn web_app_with_reused_password_success() {
+            let main_menu = State::main_menu();
+
+            let password_fingerprint = [3; telepass_data_model::crypto::PASSWORD_FINGERPRINT_SIZE];
+            let record = telepass_data_model::NewRecord::builder()
+                .resource_name("test.resource.com")
+                .encryption_output(telepass_data_model::crypto::EncryptionOutput {
+                    version: telepass_data_model::crypto::OUTPUT_VERSION_1,
+                    encrypted_payload: b"SomeSecret".to_vec(),
+                    salt: telepass_data_model::crypto::Salt::Aes256Gcm(
+                        [1; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+                    ),
+                    kdf_iterations: telepass_data_model::crypto::DEFAULT_KDF_ITERATIONS,
+                    kdf_salt: None,
+                    key_commitment: None,
+                })
+                .password_fingerprint(password_fingerprint)
+                .build()
+                .unwrap();
+            let web_app = MessageBox::web_app(web_app_data(&record), "🆕 Add".to_owned());
+
+            let mut mock_context = Context::default();
+            mock_context.expect_web_app_replays().return_const(None);
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_bot().return_const(
+                MockBotBuilder::new()
+                    .expect_delete_message(MessageId(0))
+                    .expect_send_message(
+                        "✅ *test\\.resource\\.com* saved\\.\n\n\
+                         ⚠️ Its password is also used for:\n\
+                         • *bank\\.com*\n\
+                         • *mail\\.com*\n\n\
+                         Consider changing it\\."
+                            .to_owned(),
+                    )
+                    .expect_parse_mode(teloxide::types::ParseMode::MarkdownV2)
+                    .expect_into_future()
+                    .build(),
+            );
+
+            let mut mock_storage_client = crate::PasswordStorageClient::default();
+            mock_storage_client
+                .expect_add::<grpc::AddRequest>()
+                .with(predicate::eq(
+                    grpc::AddRequest::new(
+                        grpc::Record::from(record),
+                        grpc::idempotency_key(CHAT_ID, MessageId(0)),
+                    )
+                    .with_password_fingerprint(&password_fingerprint)
+                    .with_blind_index_version(
+                        telepass_data_model::crypto::LATEST_BLIND_INDEX_VERSION,
+                    ),
+                ))
+                .returning(|_record| {
+                    Ok(tonic::Response::new(grpc::AddResponse {
+                        reused_by: vec!["bank.com".to_owned(), "mail.com".to_owned()],
+                    }))
+                });
+
+            mock_context
+                .expect_storage_client()
+                .return_const(tokio::sync::Mutex::new(mock_storage_client));
+
+            let state = State::try_from_transition(main_menu.clone(), web_app, &mock_context)
+                .await
+                .unwrap();
+            assert_eq!(state, main_menu)
+        }
+
+        #[test]
+        pub async fn web_app_from_another_user_failure() {
+            let main_menu = State::main_menu();
+
+            let record = telepass_data_model::NewRecord::builder()
+                .resource_name("test.resource.com")
+                .encryption_output(telepass_data_model::crypto::EncryptionOutput {
+                    version: telepass_data_model::crypto::OUTPUT_VERSION_1,
+                    encrypted_payload: b"SomeSecret".to_vec(),
+                    salt: telepass_data_model::crypto::Salt::Aes256Gcm(
+                        [1; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+                    ),
+                    kdf_iterations: telepass_data_model::crypto::DEFAULT_KDF_ITERATIONS,
+                    kdf_salt: None,
+                    key_commitment: None,
+                })
+                .build()
+                .unwrap();
             let web_app = MessageBox::web_app(
-                serde_json::to_string(&record).expect("Failed to serialize record"),
+                serde_json::to_string(&telepass_data_model::WebAppMessage {
+                    user_id: 42,
+                    data: record,
+                })
+                .expect("Failed to serialize record"),
                 "🆕 Add".to_owned(),
             );
 
+            // Storage is not touched
             let mut mock_context = Context::default();
+            mock_context.expect_web_app_replays().return_const(None);
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+
+            let err = State::try_from_transition(main_menu.clone(), web_app, &mock_context)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message.starts_with("⚠️ This form was opened by another Telegram user"),
+            ));
+            assert_eq!(err.target, main_menu);
+        }
+
+        #[test]
+        pub async fn web_app_wrong_data_failure() {
+            let main_menu = State::main_menu();
 
+            let record_json = serde_json::json!({
+                "wrong_field": "test.resource.com",
+                "encrypted_payload": "SomeSecret",
+                "salt": "TestSalt"
+            });
+            let web_app = MessageBox::web_app(record_json.to_string(), "🆕 Add".to_owned());
+
+            let mut mock_context = Context::default();
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context.expect_web_app_replays().return_const(None);
+
+            let err = State::try_from_transition(main_menu.clone(), web_app, &mock_context)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == "Failed to parse a new record, your Telegram Client is probably invalid.",
+            ))
+        }
+
+        #[test]
+        pub async fn web_app_invalid_record_failure() {
+            let main_menu = State::main_menu();
+
+            let record = telepass_data_model::NewRecord::new(
+                "test.resource.com",
+                telepass_data_model::crypto::EncryptionOutput {
+                    version: telepass_data_model::crypto::OUTPUT_VERSION_1,
+                    encrypted_payload: b"SomeSecret".to_vec(),
+                    salt: telepass_data_model::crypto::Salt::Aes256Gcm(
+                        [1; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+                    ),
+                    kdf_iterations: telepass_data_model::crypto::DEFAULT_KDF_ITERATIONS,
+                    kdf_salt: None,
+                    key_commitment: None,
+                },
+            )
+            .unwrap();
+            let mut record_json = serde_json::to_value(record).unwrap();
+            *record_json.get_mut("resource_name").unwrap() = "test\nresource".into();
+            let web_app = MessageBox::web_app(web_app_data(record_json), "🆕 Add".to_owned());
+
+            let mut mock_context = Context::default();
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context.expect_web_app_replays().return_const(None);
+
+            let err = State::try_from_transition(main_menu.clone(), web_app, &mock_context)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == "❎ Invalid new record: \
+                    resource name cannot contain line breaks or other control characters.",
+            ));
+            assert_eq!(err.target, main_menu);
+        }
+
+        #[test]
+        pub async fn web_app_wrong_button_text_failure() {
+            let main_menu = State::main_menu();
+
+            let record_json = serde_json::json!({
+                "resource_name": "test.resource.com",
+                "encrypted_payload": "SomeSecret",
+                "salt": "TestSalt"
+            });
+            let web_app =
+                MessageBox::web_app(record_json.to_string(), "Wrong Button Text".to_owned());
+
+            let mock_context = Context::default();
+
+            let err = State::try_from_transition(main_menu.clone(), web_app, &mock_context)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == "Unexpected WebApp button text.",
+            ))
+        }
+
+        #[test]
+        pub async fn from_default_by_web_app_success() {
+            // Bot was restarted while the user was filling the form
+            let default = State::default();
+
+            let record = telepass_data_model::NewRecord::builder()
+                .resource_name("test.resource.com")
+                .encryption_output(telepass_data_model::crypto::EncryptionOutput {
+                    version: telepass_data_model::crypto::OUTPUT_VERSION_1,
+                    encrypted_payload: b"SomeSecret".to_vec(),
+                    salt: telepass_data_model::crypto::Salt::Aes256Gcm(
+                        [1; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+                    ),
+                    kdf_iterations: telepass_data_model::crypto::DEFAULT_KDF_ITERATIONS,
+                    kdf_salt: None,
+                    key_commitment: None,
+                })
+                .build()
+                .unwrap();
+            let web_app =
+                MessageBox::web_app(web_app_data(&record), crate::message::kind::Add.to_string());
+
+            let mut mock_context = Context::default();
+            mock_context.expect_web_app_replays().return_const(None);
             mock_context
-                .expect_bot()
-                .return_const(MockBotBuilder::new().build());
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context
+                .expect_storage_availability()
+                .return_const(storage_availability(true, true));
+            mock_context.expect_bot_username().return_const(None);
+            mock_context.expect_bot().return_const(
+                MockBotBuilder::new()
+                    .expect_delete_message(MessageId(0))
+                    .expect_send_message("✅ *test\\.resource\\.com* saved\\.".to_owned())
+                    .expect_parse_mode(teloxide::types::ParseMode::MarkdownV2)
+                    .expect_into_future()
+                    .expect_send_message("🏠 Welcome to the main menu.".to_owned())
+                    .expect_reply_markup(
+                        KeyboardMarkup::new([
+                            [KeyboardButton::new(crate::message::kind::List.to_string())],
+                            [
+                                KeyboardButton::new(crate::message::kind::Add.to_string()).request(
+                                    teloxide::types::ButtonRequest::WebApp(
+                                        teloxide::types::WebAppInfo {
+                                            url: web_app_test_url().join("/submit").unwrap(),
+                                        },
+                                    ),
+                                ),
+                            ],
+                        ])
+                        .resize_keyboard(),
+                    )
+                    .expect_into_future()
+                    .build(),
+            );
 
             let mut mock_storage_client = crate::PasswordStorageClient::default();
             mock_storage_client
-                .expect_add::<crate::grpc::Record>()
-                .with(predicate::eq(crate::grpc::Record::from(record)))
-                .returning(|_record| Ok(tonic::Response::new(crate::grpc::Response {})));
+                .expect_add::<grpc::AddRequest>()
+                .with(predicate::eq(
+                    grpc::AddRequest::new(
+                        grpc::Record::from(record),
+                        grpc::idempotency_key(CHAT_ID, MessageId(0)),
+                    )
+                    .with_blind_index_version(
+                        telepass_data_model::crypto::LATEST_BLIND_INDEX_VERSION,
+                    ),
+                ))
+                .times(1)
+                .returning(|_record| Ok(tonic::Response::new(grpc::AddResponse::default())));
+            mock_context
+                .expect_storage_client()
+                .return_const(tokio::sync::Mutex::new(mock_storage_client));
+
+            let state = State::try_from_transition(default, web_app, &mock_context)
+                .await
+                .unwrap();
+            assert!(matches!(state, State::MainMenu(_)))
+        }
+
+        #[test]
+        pub async fn from_default_by_web_app_wrong_button_text_failure() {
+            let default = State::default();
+            let web_app = MessageBox::web_app("data".to_owned(), "Wrong Button Text".to_owned());
+
+            let mock_context = Context::default();
 
+            let err = State::try_from_transition(default.clone(), web_app, &mock_context)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == "Unexpected WebApp button text.",
+            ));
+            assert_eq!(err.target, default);
+        }
+
+        #[test]
+        pub async fn retry_storage_success() {
+            let main_menu = State::main_menu();
+            let retry_storage = MessageBox::retry_storage();
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context
+                .expect_storage_availability()
+                .return_const(storage_availability(false, true));
+            mock_context.expect_bot_username().return_const(None);
+            mock_context.expect_bot().return_const(
+                MockBotBuilder::new()
+                    .expect_send_message("🏠 Welcome to the main menu.".to_owned())
+                    .expect_reply_markup(
+                        KeyboardMarkup::new([
+                            [KeyboardButton::new(crate::message::kind::List.to_string())],
+                            [
+                                KeyboardButton::new(crate::message::kind::Add.to_string()).request(
+                                    teloxide::types::ButtonRequest::WebApp(
+                                        teloxide::types::WebAppInfo {
+                                            url: web_app_test_url().join("/submit").unwrap(),
+                                        },
+                                    ),
+                                ),
+                            ],
+                        ])
+                        .resize_keyboard(),
+                    )
+                    .expect_into_future()
+                    .build(),
+            );
+
+            let state = State::try_from_transition(main_menu.clone(), retry_storage, &mock_context)
+                .await
+                .unwrap();
+            assert_eq!(state, main_menu);
+            assert!(mock_context.storage_availability().is_available());
+        }
+
+        #[test]
+        pub async fn retry_storage_still_unavailable_failure() {
+            let main_menu = State::main_menu();
+            let retry_storage = MessageBox::retry_storage();
+
+            // Menu is not rendered again
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_storage_availability()
+                .return_const(storage_availability(false, false));
+
+            let err = State::try_from_transition(main_menu.clone(), retry_storage, &mock_context)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == "⚠️ Storage is still unavailable, try again later.",
+            ));
+            assert_eq!(err.target, main_menu);
+            assert!(!mock_context.storage_availability().is_available());
+        }
+
+        #[test]
+        pub async fn add_failure() {
+            let main_menu = State::main_menu();
+            let add = MessageBox::add();
+
+            test_unexpected_message(main_menu, add).await
+        }
+
+        #[test]
+        pub async fn arbitrary_failure() {
+            let main_menu = State::main_menu();
+            let arbitrary = MessageBox::arbitrary("Test arbitrary message");
+
+            test_unexpected_message(main_menu, arbitrary).await
+        }
+
+        #[test]
+        pub async fn from_duplicate_name_prompt_by_arbitrary_success() {
+            const REQUEST_MESSAGE_ID: i32 = 300;
+            const CANCEL_MESSAGE_ID: i32 = 301;
+            const RESOURCE_MESSAGE_ID: i32 = 302;
+
+            let duplicate_name_prompt = State::DuplicateNamePrompt(
+                DuplicateNamePrompt::test(Arc::new(RwLock::new(DisplayedResourceData::new(
+                    MessageId(REQUEST_MESSAGE_ID),
+                    MessageId(CANCEL_MESSAGE_ID),
+                    MessageId(RESOURCE_MESSAGE_ID),
+                    "test.resource.com".to_owned(),
+                ))))
+                .await,
+            );
+            let new_name = MessageBox::arbitrary("copy.resource.com");
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context
+                .expect_storage_availability()
+                .return_const(storage_availability(true, true));
+            mock_context.expect_bot_username().return_const(None);
+            mock_context.expect_bot().return_const(
+                MockBotBuilder::new()
+                    .expect_send_message(
+                        "✅ *test\\.resource\\.com* duplicated as *copy\\.resource\\.com*\\."
+                            .to_owned(),
+                    )
+                    .expect_parse_mode(teloxide::types::ParseMode::MarkdownV2)
+                    .expect_into_future()
+                    .expect_send_message("🏠 Welcome to the main menu.".to_owned())
+                    .expect_reply_markup(
+                        KeyboardMarkup::new([
+                            [KeyboardButton::new(crate::message::kind::List.to_string())],
+                            [
+                                KeyboardButton::new(crate::message::kind::Add.to_string()).request(
+                                    teloxide::types::ButtonRequest::WebApp(
+                                        teloxide::types::WebAppInfo {
+                                            url: web_app_test_url().join("/submit").unwrap(),
+                                        },
+                                    ),
+                                ),
+                            ],
+                        ])
+                        .resize_keyboard(),
+                    )
+                    .expect_into_future()
+                    .expect_delete_message(MessageId(REQUEST_MESSAGE_ID))
+                    .expect_delete_message(MessageId(CANCEL_MESSAGE_ID))
+                    .expect_delete_message(MessageId(RESOURCE_MESSAGE_ID))
+                    .build(),
+            );
             mock_context
                 .expect_storage_client()
-                .return_const(tokio::sync::Mutex::new(mock_storage_client));
+                .return_const(tokio::sync::Mutex::new(mock_duplicating_storage_client(
+                    Ok(grpc::AddResponse::default()),
+                )));
 
-            let state = State::try_from_transition(main_menu.clone(), web_app, &mock_context)
+            let state = State::try_from_transition(duplicate_name_prompt, new_name, &mock_context)
                 .await
                 .unwrap();
-            assert_eq!(state, main_menu)
+            assert!(matches!(state, State::MainMenu(_)))
         }
 
         #[test]
-        pub async fn web_app_wrong_data_failure() {
-            let main_menu = State::main_menu();
-
-            let record_json = serde_json::json!({
-                "wrong_field": "test.resource.com",
-                "encrypted_payload": "SomeSecret",
-                "salt": "TestSalt"
-            });
-            let web_app = MessageBox::web_app(record_json.to_string(), "🆕 Add".to_owned());
-
-            let mock_context = Context::default();
+        pub async fn from_duplicate_name_prompt_by_existing_name_failure() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+            let new_name = MessageBox::arbitrary("copy.resource.com");
 
-            let err = State::try_from_transition(main_menu.clone(), web_app, &mock_context)
-                .await
-                .unwrap_err();
+            let mut mock_context = Context::default();
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_storage_client()
+                .return_const(tokio::sync::Mutex::new(mock_duplicating_storage_client(
+                    Err(tonic::Status::already_exists(
+                        "Password for resource `Copy.resource.com` already exists",
+                    )),
+                )));
+
+            let err =
+                State::try_from_transition(duplicate_name_prompt.clone(), new_name, &mock_context)
+                    .await
+                    .unwrap_err();
             assert!(matches!(
                 err.reason,
-                TransitionFailureReason::User(message) if message == "Failed to parse a new record, your Telegram Client is probably invalid.",
-            ))
+                TransitionFailureReason::User(message) if message == "❎ Password for resource `Copy.resource.com` already exists. Type another name or /cancel to go back.",
+            ));
+            assert_eq!(err.target, duplicate_name_prompt);
         }
 
         #[test]
-        pub async fn web_app_wrong_button_text_failure() {
-            let main_menu = State::main_menu();
-
-            let record_json = serde_json::json!({
-                "resource_name": "test.resource.com",
-                "encrypted_payload": "SomeSecret",
-                "salt": "TestSalt"
-            });
-            let web_app =
-                MessageBox::web_app(record_json.to_string(), "Wrong Button Text".to_owned());
+        pub async fn from_duplicate_name_prompt_by_bound_source_failure() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+            let new_name = MessageBox::arbitrary("copy.resource.com");
 
-            let mock_context = Context::default();
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_storage_client()
+                .return_const(tokio::sync::Mutex::new(mock_source_storage_client(true)));
 
-            let err = State::try_from_transition(main_menu.clone(), web_app, &mock_context)
-                .await
-                .unwrap_err();
+            let err =
+                State::try_from_transition(duplicate_name_prompt.clone(), new_name, &mock_context)
+                    .await
+                    .unwrap_err();
             assert!(matches!(
                 err.reason,
-                TransitionFailureReason::User(message) if message == "Unexpected WebApp button text.",
-            ))
+                TransitionFailureReason::User(message) if message == BOUND_RECORD_DUPLICATION,
+            ));
+            assert_eq!(err.target, duplicate_name_prompt);
         }
 
         #[test]
-        pub async fn add_failure() {
-            let main_menu = State::main_menu();
-            let add = MessageBox::add();
-
-            test_unexpected_message(main_menu, add).await
-        }
+        pub async fn from_duplicate_name_prompt_by_blank_name_failure() {
+            let duplicate_name_prompt = State::duplicate_name_prompt(true).await;
+            let new_name = MessageBox::arbitrary("  ");
 
-        #[test]
-        pub async fn arbitrary_failure() {
-            let main_menu = State::main_menu();
-            let arbitrary = MessageBox::arbitrary("Test arbitrary message");
+            let mock_context = Context::default();
 
-            test_unexpected_message(main_menu, arbitrary).await
+            let err =
+                State::try_from_transition(duplicate_name_prompt.clone(), new_name, &mock_context)
+                    .await
+                    .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == "❎ Resource name can't be empty, type another one.",
+            ));
+            assert_eq!(err.target, duplicate_name_prompt);
         }
     }
 
@@ -404,16 +1861,18 @@ pub mod tests {
         use teloxide::types::{KeyboardButton, KeyboardMarkup, MessageId};
         use tokio::{sync::RwLock, test};
 
+        use super::command::expect_welcome;
         use crate::{
             button::ButtonBox,
+            role::{Role, PERMISSION_DENIED},
             state::{
                 delete_confirmation::DeleteConfirmation, Context, DisplayedResourceData, State,
             },
             test_utils::{
                 mock_bot::{MockBotBuilder, CHAT_ID},
-                test_unexpected_button, web_app_test_url,
+                storage_availability, test_unexpected_button, web_app_test_url,
             },
-            transition::TryFromTransition as _,
+            transition::{TransitionFailureReason, TryFromTransition as _},
             PasswordStorageClient,
         };
 
@@ -449,6 +1908,14 @@ pub mod tests {
             test_unexpected_button(main_menu, show_button).await;
         }
 
+        #[test]
+        pub async fn duplicate_failure() {
+            let main_menu = State::main_menu();
+            let duplicate_button = ButtonBox::duplicate();
+
+            test_unexpected_button(main_menu, duplicate_button).await;
+        }
+
         #[test]
         pub async fn from_delete_confirmation_by_yes_success() {
             const REQUEST_MESSAGE_ID: i32 = 200;
@@ -468,16 +1935,108 @@ pub mod tests {
             let yes_button = ButtonBox::yes();
 
             let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
             mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context.expect_role().return_const(Role::Admin);
             mock_context
                 .expect_web_app_url()
                 .return_const(web_app_test_url());
+            mock_context
+                .expect_storage_availability()
+                .return_const(storage_availability(true, true));
+            mock_context.expect_bot_username().return_const(None);
             mock_context.expect_bot().return_const(
                 MockBotBuilder::new()
                     .expect_send_message("✅ *test\\.resource\\.com* deleted\\.".to_owned())
                     .expect_parse_mode(teloxide::types::ParseMode::MarkdownV2)
                     .expect_into_future()
-                    .expect_send_message("🏠 Welcome to the main menu.")
+                    .expect_send_message("🏠 Welcome to the main menu.".to_owned())
+                    .expect_reply_markup(
+                        KeyboardMarkup::new([
+                            [KeyboardButton::new(crate::message::kind::List.to_string())],
+                            [
+                                KeyboardButton::new(crate::message::kind::Add.to_string()).request(
+                                    teloxide::types::ButtonRequest::WebApp(
+                                        teloxide::types::WebAppInfo {
+                                            url: web_app_test_url().join("/submit").unwrap(),
+                                        },
+                                    ),
+                                ),
+                            ],
+                        ])
+                        .resize_keyboard(),
+                    )
+                    .expect_into_future()
+                    .expect_delete_message(MessageId(REQUEST_MESSAGE_ID))
+                    .expect_delete_message(MessageId(CANCEL_MESSAGE_ID))
+                    .expect_delete_message(MessageId(RESOURCE_MESSAGE_ID))
+                    .build(),
+            );
+
+            let mut mock_storage_client = PasswordStorageClient::default();
+            mock_storage_client
+                .expect_delete()
+                .with(predicate::eq(crate::grpc::DeleteRequest {
+                    name: "test.resource.com".to_owned(),
+                    expected_revision: 0,
+                }))
+                .returning(|_resource| Ok(tonic::Response::new(crate::grpc::Response {})));
+            mock_context
+                .expect_storage_client()
+                .return_const(tokio::sync::Mutex::new(mock_storage_client));
+
+            let state = State::try_from_transition(delete_confirmation, yes_button, &mock_context)
+                .await
+                .unwrap();
+            assert!(matches!(state, State::MainMenu(_)))
+        }
+
+        #[test]
+        pub async fn from_delete_confirmation_by_yes_with_undelivered_confirmation_success() {
+            const REQUEST_MESSAGE_ID: i32 = 200;
+            const CANCEL_MESSAGE_ID: i32 = 201;
+            const RESOURCE_MESSAGE_ID: i32 = 202;
+
+            let delete_confirmation = State::DeleteConfirmation(
+                DeleteConfirmation::test(Arc::new(RwLock::new(DisplayedResourceData::new(
+                    teloxide::types::MessageId(REQUEST_MESSAGE_ID),
+                    teloxide::types::MessageId(CANCEL_MESSAGE_ID),
+                    teloxide::types::MessageId(RESOURCE_MESSAGE_ID),
+                    "test.resource.com".to_owned(),
+                ))))
+                .await,
+            );
+
+            let yes_button = ButtonBox::yes();
+
+            let (retry_queue, retry_worker) =
+                crate::final_message::retry_queue(std::time::Duration::ZERO);
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_final_message_retries()
+                .return_const(Some(retry_queue));
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context.expect_role().return_const(Role::Admin);
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context
+                .expect_storage_availability()
+                .return_const(storage_availability(true, true));
+            mock_context.expect_bot_username().return_const(None);
+            mock_context.expect_bot().return_const(
+                MockBotBuilder::new()
+                    .expect_send_message("✅ *test\\.resource\\.com* deleted\\.".to_owned())
+                    .expect_parse_mode(teloxide::types::ParseMode::MarkdownV2)
+                    .expect_into_future_with_error(teloxide::RequestError::RetryAfter(
+                        teloxide::types::Seconds::from_seconds(1),
+                    ))
+                    .expect_send_message("🏠 Welcome to the main menu.".to_owned())
                     .expect_reply_markup(
                         KeyboardMarkup::new([
                             [KeyboardButton::new(crate::message::kind::List.to_string())],
@@ -503,8 +2062,9 @@ pub mod tests {
             let mut mock_storage_client = PasswordStorageClient::default();
             mock_storage_client
                 .expect_delete()
-                .with(predicate::eq(crate::grpc::Resource {
+                .with(predicate::eq(crate::grpc::DeleteRequest {
                     name: "test.resource.com".to_owned(),
+                    expected_revision: 0,
                 }))
                 .returning(|_resource| Ok(tonic::Response::new(crate::grpc::Response {})));
             mock_context
@@ -514,7 +2074,296 @@ pub mod tests {
             let state = State::try_from_transition(delete_confirmation, yes_button, &mock_context)
                 .await
                 .unwrap();
+            // Record is deleted, so the transition succeeds and the summary is re-sent later
+            assert!(matches!(state, State::MainMenu(_)));
+            drop(mock_context);
+            let bot = MockBotBuilder::new()
+                .expect_send_message(
+                    "✅ Your last action completed: test.resource.com deleted".to_owned(),
+                )
+                .expect_into_future()
+                .build();
+            retry_worker.run(bot).await;
+        }
+
+        /// Construct mock context of an admin with `mock_storage_client`.
+        fn mock_admin_context(mock_storage_client: PasswordStorageClient) -> Context {
+            let mut mock_context = Context::default();
+            mock_context.expect_role().return_const(Role::Admin);
+            mock_context
+                .expect_storage_client()
+                .return_const(tokio::sync::Mutex::new(mock_storage_client));
+            mock_context
+        }
+
+        #[test]
+        pub async fn from_delete_confirmation_by_yes_with_changed_record_failure() {
+            let State::DeleteConfirmation(delete_confirmation) =
+                State::delete_confirmation(true).await
+            else {
+                unreachable!("`State::delete_confirmation()` constructs delete confirmation");
+            };
+            let resource_name = delete_confirmation.record().resource.clone().unwrap().name;
+            let fresh_record = crate::grpc::Record {
+                revision: 7,
+                ..delete_confirmation.record().clone()
+            };
+
+            let mut mock_storage_client = PasswordStorageClient::default();
+            mock_storage_client
+                .expect_delete()
+                .with(predicate::eq(crate::grpc::DeleteRequest {
+                    name: resource_name.clone(),
+                    expected_revision: 0,
+                }))
+                .returning(|_request| Err(tonic::Status::aborted("Changed concurrently")));
+            let returned_record = fresh_record.clone();
+            mock_storage_client
+                .expect_get()
+                .with(predicate::eq(crate::grpc::GetRequest {
+                    name: resource_name,
+                    bypass_cache: true,
+                }))
+                .return_once(|_request| Ok(tonic::Response::new(returned_record)));
+            let mock_context = mock_admin_context(mock_storage_client);
+
+            let err = State::try_from_transition(
+                State::DeleteConfirmation(delete_confirmation.clone()),
+                ButtonBox::yes(),
+                &mock_context,
+            )
+            .await
+            .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message)
+                    if message.starts_with("⚠️ This record was changed by someone else, reopening it."),
+            ));
+            assert_eq!(
+                err.target,
+                State::DeleteConfirmation(delete_confirmation.with_record(fresh_record))
+            );
+        }
+
+        #[test]
+        pub async fn from_delete_confirmation_by_yes_with_deleted_record_failure() {
+            let delete_confirmation = State::delete_confirmation(true).await;
+
+            let mut mock_storage_client = PasswordStorageClient::default();
+            mock_storage_client
+                .expect_delete::<crate::grpc::DeleteRequest>()
+                .returning(|_request| Err(tonic::Status::not_found("Not found")));
+            let mock_context = mock_admin_context(mock_storage_client);
+
+            let err = State::try_from_transition(
+                delete_confirmation.clone(),
+                ButtonBox::yes(),
+                &mock_context,
+            )
+            .await
+            .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message)
+                    if message.starts_with("❎ This record was already deleted by someone else"),
+            ));
+            assert_eq!(err.target, delete_confirmation);
+        }
+
+        #[test]
+        pub async fn from_delete_confirmation_by_yes_as_viewer_failure() {
+            let delete_confirmation = State::delete_confirmation(true).await;
+            let yes_button = ButtonBox::yes();
+
+            let mut mock_context = Context::default();
+            mock_context.expect_role().return_const(Role::Viewer);
+
+            let err =
+                State::try_from_transition(delete_confirmation.clone(), yes_button, &mock_context)
+                    .await
+                    .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == PERMISSION_DENIED,
+            ));
+            assert_eq!(err.target, delete_confirmation);
+        }
+
+        #[test]
+        pub async fn from_rename_prefix_confirmation_by_yes_success() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let yes_button = ButtonBox::yes();
+
+            let mut mock_storage_client = PasswordStorageClient::default();
+            for name in ["work/github", "work/jira"] {
+                expect_rename(&mut mock_storage_client, name, Ok(()));
+            }
+            let mock_context = mock_renaming_context(
+                mock_storage_client,
+                "✅ Renamed 2 of 2 records from \"work/\" to \"team/\".",
+            );
+
+            let state =
+                State::try_from_transition(rename_prefix_confirmation, yes_button, &mock_context)
+                    .await
+                    .unwrap();
             assert!(matches!(state, State::MainMenu(_)))
         }
+
+        #[test]
+        pub async fn from_rename_prefix_confirmation_by_yes_with_conflicts_success() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let yes_button = ButtonBox::yes();
+
+            let mut mock_storage_client = PasswordStorageClient::default();
+            expect_rename(
+                &mut mock_storage_client,
+                "work/github",
+                Err(tonic::Status::already_exists("Already exists")),
+            );
+            expect_rename(&mut mock_storage_client, "work/jira", Ok(()));
+            let mock_context = mock_renaming_context(
+                mock_storage_client,
+                "✅ Renamed 1 of 2 records from \"work/\" to \"team/\".\n\n\
+                 ❎ Not renamed, records with these names already exist:\n• team/github",
+            );
+
+            let state =
+                State::try_from_transition(rename_prefix_confirmation, yes_button, &mock_context)
+                    .await
+                    .unwrap();
+            assert!(matches!(state, State::MainMenu(_)))
+        }
+
+        #[test]
+        pub async fn from_rename_prefix_confirmation_by_yes_with_bound_record_success() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let yes_button = ButtonBox::yes();
+
+            let mut mock_storage_client = PasswordStorageClient::default();
+            expect_rename(
+                &mut mock_storage_client,
+                "work/github",
+                Err(tonic::Status::failed_precondition("Can't be renamed")),
+            );
+            expect_rename(&mut mock_storage_client, "work/jira", Ok(()));
+            let mock_context = mock_renaming_context(
+                mock_storage_client,
+                "✅ Renamed 1 of 2 records from \"work/\" to \"team/\".\n\n\
+                 ⚠️ Failed to rename:\n• work/github: protected from being moved to another name",
+            );
+
+            let state =
+                State::try_from_transition(rename_prefix_confirmation, yes_button, &mock_context)
+                    .await
+                    .unwrap();
+            assert!(matches!(state, State::MainMenu(_)))
+        }
+
+        #[test]
+        pub async fn from_rename_prefix_confirmation_by_yes_as_viewer_failure() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let yes_button = ButtonBox::yes();
+
+            let mut mock_context = Context::default();
+            mock_context.expect_role().return_const(Role::Viewer);
+
+            let err = State::try_from_transition(
+                rename_prefix_confirmation.clone(),
+                yes_button,
+                &mock_context,
+            )
+            .await
+            .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == PERMISSION_DENIED,
+            ));
+            assert_eq!(err.target, rename_prefix_confirmation);
+        }
+
+        #[test]
+        pub async fn from_rename_prefix_confirmation_by_no_success() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let no_button = ButtonBox::no();
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context
+                .expect_storage_availability()
+                .return_const(storage_availability(true, true));
+            mock_context.expect_bot_username().return_const(None);
+            mock_context.expect_bot().return_const(
+                expect_welcome(MockBotBuilder::new())
+                    .expect_delete_message(MessageId(0))
+                    .build(),
+            );
+
+            let state =
+                State::try_from_transition(rename_prefix_confirmation, no_button, &mock_context)
+                    .await
+                    .unwrap();
+            assert!(matches!(state, State::MainMenu(_)))
+        }
+
+        /// Expect renaming of the record with `name` from `work/` to `team/` resulting with
+        /// `result`.
+        fn expect_rename(
+            mock_storage_client: &mut PasswordStorageClient,
+            name: &str,
+            result: Result<(), tonic::Status>,
+        ) {
+            mock_storage_client
+                .expect_rename()
+                .with(predicate::eq(crate::grpc::RenameRequest {
+                    name: name.to_owned(),
+                    new_name: name.replacen("work/", "team/", 1),
+                    expected_revision: 0,
+                }))
+                .return_once(|_request| {
+                    result.map(|()| tonic::Response::new(crate::grpc::Response {}))
+                });
+        }
+
+        /// Construct mock context of an admin renaming records with `mock_storage_client`
+        /// and expecting `summary` at the end.
+        fn mock_renaming_context(
+            mock_storage_client: PasswordStorageClient,
+            summary: &str,
+        ) -> Context {
+            let mut mock_context = mock_admin_context(mock_storage_client);
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context
+                .expect_storage_availability()
+                .return_const(storage_availability(true, true));
+            mock_context.expect_bot_username().return_const(None);
+            mock_context.expect_bot().return_const(
+                expect_welcome(
+                    MockBotBuilder::new()
+                        .expect_edit_message_text(
+                            MessageId(0),
+                            "⏳ Renaming records: 0 of 2 done.".to_owned(),
+                        )
+                        .expect_into_future()
+                        .expect_send_message(summary.to_owned())
+                        .expect_into_future(),
+                )
+                .expect_delete_message(MessageId(0))
+                .build(),
+            );
+            mock_context
+        }
     }
 }
@@ -0,0 +1,111 @@
+//! Rendering of every formatted message template with arbitrary user data.
+//!
+//! Templates are checked with [`crate::test_utils::markdown::validate()`], so that escaping
+//! regressions are caught for any resource name.
+
+use super::{
+    delete_confirmation::DeleteConfirmation, duplicate_name_prompt::DuplicateNamePrompt,
+    main_menu::MainMenu, resource_actions::ResourceActions,
+};
+#[mockall_double::double]
+use crate::context::Context;
+use crate::{
+    footer::{MessageClass, MessageFooter},
+    grpc,
+    keyboard::ResourcePrefix,
+};
+
+/// Render every formatted message template with `user_data` in place of all user data,
+/// e.g. resource names, passwords, attachment filenames and footer.
+///
+/// Returns pairs of template names and rendered texts.
+pub fn render_templates(user_data: &str) -> Vec<(&'static str, String)> {
+    vec![
+        (
+            "saved confirmation",
+            MainMenu::saved_confirmation(user_data, &[]),
+        ),
+        (
+            "saved confirmation with reused password",
+            MainMenu::saved_confirmation(user_data, &[user_data.to_owned()]),
+        ),
+        (
+            "deletion confirmation",
+            MainMenu::deleted_confirmation(user_data),
+        ),
+        (
+            "duplication confirmation",
+            MainMenu::duplicated_confirmation(user_data, user_data),
+        ),
+        ("delete prompt", DeleteConfirmation::prompt_text(user_data)),
+        (
+            "duplicate name prompt",
+            DuplicateNamePrompt::prompt_text(user_data),
+        ),
+        ("resource card", render_resource_card(user_data)),
+        (
+            "footer",
+            MessageFooter::new(user_data).apply(
+                MainMenu::deleted_confirmation("test.resource.com"),
+                MessageClass::MarkdownV2,
+            ),
+        ),
+    ]
+}
+
+/// Render card of a large record with an attachment, so that all optional parts are shown.
+fn render_resource_card(user_data: &str) -> String {
+    let mut mock_context = Context::default();
+    mock_context
+        .expect_resource_prefix()
+        .return_const(ResourcePrefix::new(user_data));
+    mock_context.expect_unlock_token_store().return_const(None);
+
+    let record = grpc::Record {
+        encrypted_payload: vec![0; grpc::MAX_INLINE_PAYLOAD_SIZE.saturating_add(1)],
+        ..grpc::Record::default()
+    };
+    let attachments = [grpc::AttachmentInfo {
+        filename: user_data.to_owned(),
+        size: 42,
+    }];
+    ResourceActions::construct_choose_an_action_text(
+        &record,
+        &attachments,
+        user_data,
+        &mock_context,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::panic, reason = "it's ok in tests")]
+
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::test_utils::markdown::validate;
+
+    /// Assert that all templates rendered with `user_data` are valid.
+    fn assert_templates_are_valid(user_data: &str) {
+        for (name, text) in render_templates(user_data) {
+            if let Err(violation) = validate(&text) {
+                panic!("{name} rendered with {user_data:?} is invalid: {violation}\n{text}");
+            }
+        }
+    }
+
+    #[test]
+    fn templates_with_all_special_characters_are_valid() {
+        assert_templates_are_valid("_*[]()~`>#+-=|{}.!\\");
+    }
+
+    proptest! {
+        #[test]
+        fn templates_with_arbitrary_user_data_are_valid(
+            user_data in "[_*\\[\\]()~`>#+\\-=|{}.!\\\\a-z0-9 \u{1f511}]{1,32}"
+        ) {
+            assert_templates_are_valid(&user_data);
+        }
+    }
+}
@@ -0,0 +1,693 @@
+//! [`Rename prefix confirmation`](RenamePrefixConfirmation) state implementation.
+
+use std::{sync::Arc, time::Duration};
+
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId};
+#[cfg(not(test))]
+use teloxide::{payloads::SendMessageSetters as _, requests::Requester as _};
+use tokio::{sync::RwLock, time::Instant};
+use tracing::{debug, warn};
+
+use super::{main_menu::MainMenu, Context};
+use crate::{
+    button, command,
+    footer::{self, MessageClass},
+    grpc::{self, StorageApi as _},
+    message_group::MessageGroup,
+    role::PERMISSION_DENIED,
+    transition::{
+        try_with_state, Destroy, FailedTransition, TransitionFailureReason, TryFromTransition,
+    },
+    TelegramMessageGettersExt as _,
+};
+
+/// Number of records listed in the confirmation, the rest are only counted.
+pub const LISTED_RECORDS: usize = 10;
+
+/// Minimal interval between edits of the progress message, so that renaming of many records
+/// doesn't hit Telegram rate limits.
+pub const PROGRESS_EDIT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// State when bot is waiting for user to confirm renaming of all records starting with a prefix
+/// or to cancel the operation.
+///
+/// Records are renamed one by one keeping their search index and password fingerprint.
+/// Records bound to their names and records with attachments can't be moved,
+/// so they are reported as failed.
+#[derive(Debug, Clone)]
+pub struct RenamePrefixConfirmation {
+    /// Prefix to replace.
+    old_prefix: String,
+    /// Prefix to replace the old one with.
+    new_prefix: String,
+    /// Sorted names of the records to rename.
+    names: Vec<String>,
+    /// Message with the confirmation buttons, replaced with the progress after confirmation.
+    message_id: MessageId,
+    /// Messages to delete when leaving the state.
+    messages: Arc<RwLock<MessageGroup>>,
+}
+
+impl PartialEq for RenamePrefixConfirmation {
+    /// Skipping `messages`, which are shared between clones.
+    fn eq(&self, other: &Self) -> bool {
+        (
+            &self.old_prefix,
+            &self.new_prefix,
+            &self.names,
+            self.message_id,
+        ) == (
+            &other.old_prefix,
+            &other.new_prefix,
+            &other.names,
+            other.message_id,
+        )
+    }
+}
+
+impl Eq for RenamePrefixConfirmation {}
+
+/// Outcome of renaming all records of [`RenamePrefixConfirmation`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenameReport {
+    /// Number of renamed records.
+    pub renamed: usize,
+    /// New names which are already taken by other records.
+    pub conflicts: Vec<String>,
+    /// Names of the records failed to be renamed with the reasons.
+    pub failures: Vec<(String, String)>,
+}
+
+/// Reason a single record is not renamed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RenameFailure {
+    /// Record with the new name already exists.
+    Conflict,
+    /// Any other failure with its description.
+    Other(String),
+}
+
+impl RenamePrefixConfirmation {
+    /// Create a new [`RenamePrefixConfirmation`] state for tests.
+    ///
+    /// Messages are allowed to be left undeleted.
+    #[cfg(test)]
+    pub fn test(message_id: MessageId) -> Self {
+        let mut messages = MessageGroup::from_iter([message_id]);
+        messages.defuse();
+
+        Self {
+            old_prefix: "work/".to_owned(),
+            new_prefix: "team/".to_owned(),
+            names: vec!["work/github".to_owned(), "work/jira".to_owned()],
+            message_id,
+            messages: Arc::new(RwLock::new(messages)),
+        }
+    }
+
+    /// Get names of records starting with `old_prefix` sorted.
+    async fn matching_names(
+        old_prefix: &str,
+        context: &Context,
+    ) -> Result<Vec<String>, TransitionFailureReason> {
+        let mut names: Vec<_> = context
+            .storage_client()
+            .lock()
+            .await
+            .list(grpc::Empty {})
+            .await
+            .map_err(TransitionFailureReason::internal)?
+            .into_inner()
+            .resources
+            .into_iter()
+            .map(|resource| resource.name)
+            .filter(|name| name.starts_with(old_prefix))
+            .collect();
+        names.sort_unstable();
+        Ok(names)
+    }
+
+    /// Get name of the record with `name` after renaming.
+    fn new_name(&self, name: &str) -> String {
+        let rest = name.strip_prefix(&self.old_prefix).unwrap_or(name);
+        format!("{}{rest}", self.new_prefix)
+    }
+
+    /// Construct prompt to confirm renaming of `names` from `old_prefix` to `new_prefix`.
+    ///
+    /// Only the first [`LISTED_RECORDS`] names are listed.
+    #[must_use]
+    pub fn prompt_text(&self) -> String {
+        let listed = self
+            .names
+            .iter()
+            .take(LISTED_RECORDS)
+            .map(|name| format!("• {name} → {}", self.new_name(name)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let prompt = format!(
+            "✏️ Rename {} records starting with \"{}\" to start with \"{}\"?\n\n{listed}",
+            self.names.len(),
+            self.old_prefix,
+            self.new_prefix
+        );
+
+        match self.names.len().checked_sub(LISTED_RECORDS) {
+            Some(rest) if rest > 0 => format!("{prompt}\n…and {rest} more"),
+            _ => prompt,
+        }
+    }
+
+    /// Construct progress message after renaming of `done` records.
+    #[must_use]
+    pub fn progress_text(&self, done: usize) -> String {
+        format!("⏳ Renaming records: {done} of {} done.", self.names.len())
+    }
+
+    /// Construct summary of the renaming with `report`.
+    #[must_use]
+    pub fn summary_text(&self, report: &RenameReport) -> String {
+        let mut sections = vec![format!(
+            "✅ Renamed {} of {} records from \"{}\" to \"{}\".",
+            report.renamed,
+            self.names.len(),
+            self.old_prefix,
+            self.new_prefix
+        )];
+
+        if !report.conflicts.is_empty() {
+            let conflicts = report
+                .conflicts
+                .iter()
+                .map(|name| format!("• {name}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            sections.push(format!(
+                "❎ Not renamed, records with these names already exist:\n{conflicts}"
+            ));
+        }
+        if !report.failures.is_empty() {
+            let failures = report
+                .failures
+                .iter()
+                .map(|failure| format!("• {}: {}", failure.0, failure.1))
+                .collect::<Vec<_>>()
+                .join("\n");
+            sections.push(format!("⚠️ Failed to rename:\n{failures}"));
+        }
+
+        sections.join("\n\n")
+    }
+
+    /// Rename all records one by one collecting failures.
+    ///
+    /// Confirmation message is replaced with the progress, which is edited at most once per
+    /// [`PROGRESS_EDIT_INTERVAL`].
+    pub async fn rename_all(&self, context: &Context) -> RenameReport {
+        let mut report = RenameReport::default();
+        self.edit_progress(0, context).await;
+        let mut throttle = EditThrottle::new(PROGRESS_EDIT_INTERVAL, Instant::now());
+
+        for (done, name) in (1_usize..).zip(&self.names) {
+            match self.rename(name, context).await {
+                Ok(()) => report.renamed = report.renamed.saturating_add(1),
+                Err(RenameFailure::Conflict) => report.conflicts.push(self.new_name(name)),
+                Err(RenameFailure::Other(reason)) => {
+                    warn!(name, reason, "Failed to rename record");
+                    report.failures.push((name.clone(), reason));
+                }
+            }
+
+            if done < self.names.len() && throttle.try_edit(Instant::now()) {
+                self.edit_progress(done, context).await;
+            }
+        }
+
+        report
+    }
+
+    /// Edit progress message after renaming of `done` records.
+    ///
+    /// Progress is secondary to renaming, so failure to edit is only logged.
+    async fn edit_progress(&self, done: usize, context: &Context) {
+        if let Err(error) = context
+            .bot()
+            .edit_message_text(context.chat_id(), self.message_id, self.progress_text(done))
+            .await
+        {
+            warn!(?error, "Failed to edit renaming progress");
+        }
+    }
+
+    /// Rename record with `name`.
+    async fn rename(&self, name: &str, context: &Context) -> Result<(), RenameFailure> {
+        let renamed = context
+            .storage_client()
+            .lock()
+            .await
+            .rename(grpc::RenameRequest {
+                name: name.to_owned(),
+                new_name: self.new_name(name),
+                expected_revision: 0,
+            })
+            .await;
+
+        match renamed {
+            Ok(_response) => Ok(()),
+            Err(status) if status.code() == tonic::Code::AlreadyExists => {
+                Err(RenameFailure::Conflict)
+            }
+            Err(status) if status.code() == tonic::Code::FailedPrecondition => Err(
+                RenameFailure::Other("protected from being moved to another name".to_owned()),
+            ),
+            Err(status) => Err(RenameFailure::Other(status.message().to_owned())),
+        }
+    }
+}
+
+impl Destroy for RenamePrefixConfirmation {
+    async fn destroy(self, context: &Context) -> color_eyre::Result<()> {
+        let Some(messages_lock) = Arc::into_inner(self.messages) else {
+            debug!("There are other strong references to `MessageGroup`, skipping deletion");
+            return Ok(());
+        };
+        messages_lock.into_inner().delete_all(context).await
+    }
+}
+
+impl TryFromTransition<MainMenu, command::RenamePrefix> for RenamePrefixConfirmation {
+    type ErrorTarget = MainMenu;
+
+    async fn try_from_transition(
+        main_menu: MainMenu,
+        rename_prefix: command::RenamePrefix,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
+        let command::RenamePrefix { old, new } = rename_prefix;
+        if old.is_empty() {
+            return Err(FailedTransition::user(
+                main_menu,
+                "❎ Type the old and the new prefixes, e.g. /renameprefix work/ team/.",
+            ));
+        }
+        if !context.role().can_manage() {
+            return Err(FailedTransition::user(main_menu, PERMISSION_DENIED));
+        }
+        if old == new {
+            return Err(FailedTransition::user(
+                main_menu,
+                "❎ The new prefix is the same as the old one, nothing to rename.",
+            ));
+        }
+
+        let names = try_with_state!(main_menu, Self::matching_names(&old, context).await);
+        if names.is_empty() {
+            return Err(FailedTransition::user(
+                main_menu,
+                format!("❎ No records start with \"{old}\"."),
+            ));
+        }
+
+        let mut confirmation = Self {
+            old_prefix: old,
+            new_prefix: new,
+            names,
+            message_id: MessageId(0),
+            messages: Arc::default(),
+        };
+        let keyboard = InlineKeyboardMarkup::new([[
+            button::kind::Yes.to_string(),
+            button::kind::No.to_string(),
+        ]
+        .map(|button_data| InlineKeyboardButton::callback(button_data.clone(), button_data))]);
+
+        let message = try_with_state!(
+            main_menu,
+            footer::send_text(context, confirmation.prompt_text(), MessageClass::Plain)
+                .reply_markup(keyboard)
+                .await
+                .map_err(TransitionFailureReason::internal)
+        );
+        confirmation.message_id = message.id();
+        confirmation.messages.write().await.track(message.id());
+
+        Ok(confirmation)
+    }
+}
+
+/// Limiter of edits of a message.
+#[derive(Debug, Clone, Copy)]
+struct EditThrottle {
+    /// Minimal interval between edits.
+    interval: Duration,
+    /// Moment of the last edit.
+    last_edit: Instant,
+}
+
+impl EditThrottle {
+    /// Construct new [`EditThrottle`] of a message last edited at `last_edit`.
+    const fn new(interval: Duration, last_edit: Instant) -> Self {
+        Self {
+            interval,
+            last_edit,
+        }
+    }
+
+    /// Check if the message can be edited at `now` and remember the edit if so.
+    fn try_edit(&mut self, now: Instant) -> bool {
+        if now.saturating_duration_since(self.last_edit) < self.interval {
+            return false;
+        }
+        self.last_edit = now;
+        true
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    #![expect(clippy::panic, clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use super::*;
+
+    #[test]
+    fn edits_are_throttled() {
+        let start = Instant::now();
+        let after = |millis| start.checked_add(Duration::from_millis(millis)).unwrap();
+        let mut throttle = EditThrottle::new(Duration::from_secs(2), start);
+
+        assert!(!throttle.try_edit(after(1_999)));
+        assert!(throttle.try_edit(after(2_000)));
+        assert!(!throttle.try_edit(after(3_000)));
+        assert!(throttle.try_edit(after(4_500)));
+    }
+
+    #[test]
+    fn prompt_lists_only_first_records() {
+        let mut confirmation = RenamePrefixConfirmation::test(MessageId(0));
+        assert_eq!(
+            confirmation.prompt_text(),
+            "✏️ Rename 2 records starting with \"work/\" to start with \"team/\"?\n\n\
+             • work/github → team/github\n\
+             • work/jira → team/jira"
+        );
+
+        confirmation.names = (0_u8..12).map(|index| format!("work/{index:02}")).collect();
+        let prompt = confirmation.prompt_text();
+        assert!(prompt.starts_with("✏️ Rename 12 records"));
+        assert!(prompt.contains("• work/09 → team/09\n…and 2 more"));
+        assert!(!prompt.contains("work/10"));
+    }
+
+    #[test]
+    fn summary_reports_conflicts_and_failures() {
+        let confirmation = RenamePrefixConfirmation::test(MessageId(0));
+
+        assert_eq!(
+            confirmation.summary_text(&RenameReport {
+                renamed: 2,
+                ..RenameReport::default()
+            }),
+            "✅ Renamed 2 of 2 records from \"work/\" to \"team/\"."
+        );
+        assert_eq!(
+            confirmation.summary_text(&RenameReport {
+                renamed: 0,
+                conflicts: vec!["team/github".to_owned()],
+                failures: vec![("work/jira".to_owned(), "unavailable".to_owned())],
+            }),
+            "✅ Renamed 0 of 2 records from \"work/\" to \"team/\".\n\n\
+             ❎ Not renamed, records with these names already exist:\n• team/github\n\n\
+             ⚠️ Failed to rename:\n• work/jira: unavailable"
+        );
+    }
+
+    pub mod command {
+        use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId};
+        use tokio::test;
+
+        use crate::{
+            command::Command,
+            role::{Role, PERMISSION_DENIED},
+            state::{Context, State},
+            test_utils::{
+                mock_bot::{MockBotBuilder, CHAT_ID},
+                test_help_success, test_time_zone_success, test_unavailable_command,
+                test_whats_new_success,
+            },
+            transition::{TransitionFailureReason, TryFromTransition as _},
+            PasswordStorageClient,
+        };
+
+        const MESSAGE_ID: MessageId = MessageId(0);
+
+        /// Construct mock storage client listing `names`.
+        fn mock_listing_storage_client(names: &[&str]) -> PasswordStorageClient {
+            let resources: Vec<_> = names
+                .iter()
+                .map(|name| crate::grpc::Resource {
+                    name: (*name).to_owned(),
+                })
+                .collect();
+            let mut mock_storage_client = PasswordStorageClient::default();
+            mock_storage_client
+                .expect_list::<crate::grpc::Empty>()
+                .return_once(|_empty| {
+                    Ok(tonic::Response::new(crate::grpc::ListOfResources {
+                        resources,
+                    }))
+                });
+            mock_storage_client
+        }
+
+        #[test]
+        pub async fn help_success() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+
+            test_help_success(rename_prefix_confirmation).await
+        }
+
+        #[test]
+        pub async fn whats_new_success() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+
+            test_whats_new_success(rename_prefix_confirmation).await
+        }
+
+        #[test]
+        pub async fn time_zone_success() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+
+            test_time_zone_success(rename_prefix_confirmation).await
+        }
+
+        #[test]
+        pub async fn start_failure() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let start = Command::start();
+
+            test_unavailable_command(rename_prefix_confirmation, start).await
+        }
+
+        #[test]
+        pub async fn add_failure() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let add = Command::add();
+
+            test_unavailable_command(rename_prefix_confirmation, add).await
+        }
+
+        #[test]
+        pub async fn deep_find_failure() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let deep_find = Command::deep_find("router");
+
+            test_unavailable_command(rename_prefix_confirmation, deep_find).await
+        }
+
+        #[test]
+        pub async fn rename_prefix_failure() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let rename_prefix = Command::rename_prefix("work/", "team/");
+
+            test_unavailable_command(rename_prefix_confirmation, rename_prefix).await
+        }
+
+        #[test]
+        pub async fn from_main_menu_by_rename_prefix_success() {
+            let main_menu = State::main_menu();
+            let rename_prefix = Command::rename_prefix("work/", "team/");
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context.expect_role().return_const(Role::Admin);
+            mock_context
+                .expect_storage_client()
+                .return_const(tokio::sync::Mutex::new(mock_listing_storage_client(&[
+                    "work/jira",
+                    "home/router",
+                    "work/github",
+                ])));
+            mock_context.expect_bot().return_const(
+                MockBotBuilder::new()
+                    .expect_send_message(
+                        "✏️ Rename 2 records starting with \"work/\" to start with \"team/\"?\n\n\
+                         • work/github → team/github\n\
+                         • work/jira → team/jira"
+                            .to_owned(),
+                    )
+                    .expect_reply_markup(InlineKeyboardMarkup::new([[
+                        InlineKeyboardButton::callback("✅ Yes", "✅ Yes"),
+                        InlineKeyboardButton::callback("❌ No", "❌ No"),
+                    ]]))
+                    .expect_into_future_with_id(MESSAGE_ID)
+                    .build(),
+            );
+
+            let state = State::try_from_transition(main_menu, rename_prefix, &mock_context)
+                .await
+                .unwrap();
+            assert_eq!(state, State::rename_prefix_confirmation());
+            let State::RenamePrefixConfirmation(rename_prefix_confirmation) = state else {
+                panic!("Expected `State::RenamePrefixConfirmation`, got {state:?}");
+            };
+            rename_prefix_confirmation.messages.write().await.defuse();
+        }
+
+        #[test]
+        pub async fn from_main_menu_by_rename_prefix_without_prefixes_failure() {
+            let main_menu = State::main_menu();
+            let rename_prefix = Command::rename_prefix("", "");
+
+            let mock_context = Context::default();
+
+            let err = State::try_from_transition(main_menu.clone(), rename_prefix, &mock_context)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message.starts_with("❎ Type the old and the new prefixes"),
+            ));
+            assert_eq!(err.target, main_menu);
+        }
+
+        #[test]
+        pub async fn from_main_menu_by_rename_prefix_as_viewer_failure() {
+            let main_menu = State::main_menu();
+            let rename_prefix = Command::rename_prefix("work/", "team/");
+
+            let mut mock_context = Context::default();
+            mock_context.expect_role().return_const(Role::Viewer);
+
+            let err = State::try_from_transition(main_menu.clone(), rename_prefix, &mock_context)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == PERMISSION_DENIED,
+            ));
+            assert_eq!(err.target, main_menu);
+        }
+
+        #[test]
+        pub async fn from_main_menu_by_rename_prefix_without_matches_failure() {
+            let main_menu = State::main_menu();
+            let rename_prefix = Command::rename_prefix("work/", "team/");
+
+            let mut mock_context = Context::default();
+            mock_context.expect_role().return_const(Role::Admin);
+            mock_context
+                .expect_storage_client()
+                .return_const(tokio::sync::Mutex::new(mock_listing_storage_client(&[
+                    "home/router",
+                ])));
+
+            let err = State::try_from_transition(main_menu.clone(), rename_prefix, &mock_context)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == "❎ No records start with \"work/\".",
+            ));
+            assert_eq!(err.target, main_menu);
+        }
+    }
+
+    pub mod message {
+        use tokio::test;
+
+        use crate::{message::MessageBox, state::State, test_utils::test_unexpected_message};
+
+        #[test]
+        pub async fn web_app_failure() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let web_app = MessageBox::web_app("data".to_owned(), "button_text".to_owned());
+
+            test_unexpected_message(rename_prefix_confirmation, web_app).await
+        }
+
+        #[test]
+        pub async fn add_failure() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let add = MessageBox::add();
+
+            test_unexpected_message(rename_prefix_confirmation, add).await
+        }
+
+        #[test]
+        pub async fn list_failure() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let list = MessageBox::list();
+
+            test_unexpected_message(rename_prefix_confirmation, list).await
+        }
+
+        #[test]
+        pub async fn retry_storage_failure() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let retry_storage = MessageBox::retry_storage();
+
+            test_unexpected_message(rename_prefix_confirmation, retry_storage).await
+        }
+
+        #[test]
+        pub async fn arbitrary_failure() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let arbitrary = MessageBox::arbitrary("team/");
+
+            test_unexpected_message(rename_prefix_confirmation, arbitrary).await
+        }
+    }
+
+    pub mod button {
+        use tokio::test;
+
+        use crate::{button::ButtonBox, state::State, test_utils::test_unexpected_button};
+
+        #[test]
+        pub async fn delete_failure() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let delete_button = ButtonBox::delete();
+
+            test_unexpected_button(rename_prefix_confirmation, delete_button).await;
+        }
+
+        #[test]
+        pub async fn show_failure() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let show_button = ButtonBox::show();
+
+            test_unexpected_button(rename_prefix_confirmation, show_button).await;
+        }
+
+        #[test]
+        pub async fn duplicate_failure() {
+            let rename_prefix_confirmation = State::rename_prefix_confirmation();
+            let duplicate_button = ButtonBox::duplicate();
+
+            test_unexpected_button(rename_prefix_confirmation, duplicate_button).await;
+        }
+    }
+}
@@ -1,9 +1,12 @@
 //! [`Resource actions`](ResourceActions) state implementation.
 
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use base64::{engine::general_purpose::URL_SAFE, Engine as _};
 use color_eyre::eyre::OptionExt;
+use teloxide::types::MessageId;
 #[cfg(not(test))]
 use teloxide::{
     payloads::{
@@ -11,23 +14,29 @@ use teloxide::{
     },
     requests::Requester as _,
 };
-use teloxide::{types::MessageId, utils::markdown};
 use tokio::sync::RwLock;
-use tracing::debug;
+use tracing::{debug, warn};
+use url::Url;
 
 use super::{
-    delete_confirmation::DeleteConfirmation, resources_list::ResourcesList, Context,
-    DisplayedResourceData,
+    delete_confirmation::DeleteConfirmation, resources_list::ResourcesList, web_app_route_url,
+    Context, DisplayedResourceData,
 };
 use crate::{
     button::{self, Button},
-    grpc,
+    footer::{self, MessageClass},
+    grpc::{self, StorageApi as _},
+    markdown,
     transition::{
         try_with_state, Destroy, FailedTransition, TransitionFailureReason, TryFromTransition,
     },
+    unlock_token::LockedRecord,
     TelegramMessageGettersExt as _,
 };
 
+/// How long temporary links to records are valid for.
+pub const TEMP_LINK_TTL: Duration = Duration::from_secs(15 * 60);
+
 /// State when bot is waiting for user to press some inline button
 /// to make an action with a resource attached to a message.
 #[derive(Debug, Clone)]
@@ -75,19 +84,39 @@ impl ResourceActions {
                     name: "Test".to_owned(),
                 }),
                 encrypted_payload: b"unused".to_vec(),
-                salt: b"unused".to_vec(),
+                salt: vec![0; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+                kdf_iterations: 0,
+                kdf_salt: Vec::new(),
+                algorithm: 0,
+                bound_to_resource_name: false,
+                revision: 0,
+                chunk_count: 0,
+                key_commitment: Vec::new(),
+                output_version: 0,
             },
             displayed_resource_data,
         }
     }
 
+    /// Bind the test record to its resource name.
+    #[cfg(test)]
+    #[must_use]
+    pub const fn bound_to_resource_name(mut self) -> Self {
+        self.record.bound_to_resource_name = true;
+        self
+    }
+
     pub async fn from_resources_list(
         resources_list: ResourcesList,
         arbitrary_id: MessageId,
         record: grpc::Record,
         context: &Context,
     ) -> Result<Self, FailedTransition<ResourcesList>> {
-        let actions_keyboard = Self::construct_actions_keyboard(&record, context);
+        let actions_keyboard = try_with_state!(
+            resources_list,
+            Self::construct_actions_keyboard(&record, context)
+                .map_err(TransitionFailureReason::internal)
+        );
 
         let resource_name = try_with_state!(
             resources_list,
@@ -99,11 +128,11 @@ impl ResourceActions {
                 .map_err(TransitionFailureReason::internal)
         );
 
+        let attachments = Self::fetch_attachments(&resource_name, context).await;
+
         let cancel_message = try_with_state!(
             resources_list,
-            context
-                .bot()
-                .send_message(context.chat_id(), "Type /cancel to go back.",)
+            footer::send_text(context, "Type /cancel to go back.", MessageClass::Plain)
                 .reply_markup(teloxide::types::ReplyMarkup::kb_remove())
                 .await
                 .map_err(TransitionFailureReason::internal)
@@ -111,16 +140,20 @@ impl ResourceActions {
 
         let message = try_with_state!(
             resources_list,
-            context
-                .bot()
-                .send_message(
-                    context.chat_id(),
-                    Self::construct_choose_an_action_text(&resource_name),
-                )
-                .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-                .reply_markup(actions_keyboard)
-                .await
-                .map_err(TransitionFailureReason::internal)
+            footer::send_text(
+                context,
+                Self::construct_choose_an_action_text(
+                    &record,
+                    &attachments,
+                    &resource_name,
+                    context
+                ),
+                MessageClass::Sensitive
+            )
+            .parse_mode(teloxide::types::ParseMode::MarkdownV2)
+            .reply_markup(actions_keyboard)
+            .await
+            .map_err(TransitionFailureReason::internal)
         );
 
         Ok(Self {
@@ -134,6 +167,11 @@ impl ResourceActions {
         })
     }
 
+    /// Get record.
+    pub const fn record(&self) -> &grpc::Record {
+        &self.record
+    }
+
     /// Take record.
     pub fn take_record(self) -> grpc::Record {
         self.record
@@ -144,49 +182,230 @@ impl ResourceActions {
         Arc::clone(&self.displayed_resource_data)
     }
 
+    /// Fetch attachments of `resource_name` to list them in the resource message.
+    ///
+    /// Attachments are secondary to the record, so failure to fetch them is only logged.
+    async fn fetch_attachments(
+        resource_name: &str,
+        context: &Context,
+    ) -> Vec<grpc::AttachmentInfo> {
+        let result = context
+            .storage_client()
+            .lock()
+            .await
+            .list_attachments(grpc::Resource {
+                name: resource_name.to_owned(),
+            })
+            .await;
+        match result {
+            Ok(response) => response.into_inner().attachments,
+            Err(error) => {
+                warn!(?error, resource_name, "Failed to list attachments");
+                Vec::new()
+            }
+        }
+    }
+
     /// Construct text for a message with resource name and attached buttons with possible actions.
-    fn construct_choose_an_action_text(resource_name: &str) -> String {
-        format!(
-            "🔑 {}\n\n\
-             Choose an action:",
+    ///
+    /// Explains why there is no Show button if `record` can't be shown and lists `attachments`.
+    #[must_use]
+    pub fn construct_choose_an_action_text(
+        record: &grpc::Record,
+        attachments: &[grpc::AttachmentInfo],
+        resource_name: &str,
+        context: &Context,
+    ) -> String {
+        let mut text = format!(
+            "{} {}\n\n",
+            markdown::escape(context.resource_prefix().as_str()),
             markdown::bold(&markdown::escape(resource_name)),
-        )
+        );
+        if !Self::can_show(record, context) {
+            text.push_str(&markdown::escape(
+                "⚠️ This record is too large to be shown. \
+                 Ask the administrator to enable unlock links to see it.\n\n",
+            ));
+        }
+        if !attachments.is_empty() {
+            text.push_str("📎 Attachments:\n");
+            for attachment in attachments {
+                text.push_str(&markdown::escape(&format!(
+                    "• {} ({} bytes)\n",
+                    attachment.filename, attachment.size
+                )));
+            }
+            text.push('\n');
+        }
+        text.push_str("Choose an action:");
+        text
+    }
+
+    /// Check if the Web App can show `record`.
+    ///
+    /// Large records don't fit into the url, so they can only be passed with unlock tokens.
+    fn can_show(record: &grpc::Record, context: &Context) -> bool {
+        !record.is_large() || context.unlock_token_store().is_some()
     }
 
     /// Construct keyboard with possible actions for a resource.
-    #[expect(clippy::expect_used, reason = "indicates programmer error")]
+    ///
+    /// Delete and Duplicate buttons are omitted if user can't manage records,
+    /// Duplicate button is also omitted if the record is bound to its name,
+    /// Show button is omitted if the record can't be shown,
+    /// Temp link button is omitted if there is no temporary link to the record.
+    ///
+    /// Fails if the Web App won't be able to decrypt the record.
     fn construct_actions_keyboard(
         record: &grpc::Record,
         context: &Context,
-    ) -> teloxide::types::InlineKeyboardMarkup {
-        let payload = URL_SAFE.encode(&record.encrypted_payload);
-        let salt = URL_SAFE.encode(&record.salt);
-
-        let resource_name_param = record
-            .resource
-            .as_ref()
-            .map(|resource| format!("resource_name={}&", resource.name))
-            .unwrap_or_default();
-
-        let keyboard = teloxide::types::InlineKeyboardMarkup::new([[
-            teloxide::types::InlineKeyboardButton::callback(
-                button::kind::Delete.to_string(),
-                button::kind::Delete.to_string(),
-            ),
-            teloxide::types::InlineKeyboardButton::web_app(
-                button::kind::Show.to_string(),
-                teloxide::types::WebAppInfo {
-                    url: context
-                        .web_app_url()
-                        .clone()
-                        .join(&format!(
-                            "/show?{resource_name_param}payload={payload}&salt={salt}",
-                        ))
-                        .expect("Failed to join Web App url with `/show`"),
-                },
-            ),
-        ]]);
-        keyboard
+    ) -> Result<teloxide::types::InlineKeyboardMarkup, grpc::InvalidRecordError> {
+        let duplicate = (!record.bound_to_resource_name).then_some(button::kind::Duplicate);
+        let manage_buttons = context
+            .role()
+            .can_manage()
+            .then(|| {
+                std::iter::once(button::kind::Delete.to_string())
+                    .chain(duplicate.map(|kind| kind.to_string()))
+                    .map(|button_data| {
+                        teloxide::types::InlineKeyboardButton::callback(
+                            button_data.clone(),
+                            button_data,
+                        )
+                    })
+            })
+            .into_iter()
+            .flatten();
+        let show = Self::can_show(record, context)
+            .then(|| Self::construct_show_url(record, context))
+            .transpose()?
+            .map(|url| {
+                teloxide::types::InlineKeyboardButton::web_app(
+                    button::kind::Show.to_string(),
+                    teloxide::types::WebAppInfo { url },
+                )
+            });
+
+        let temp_link =
+            Self::construct_temp_link_url(record, context, SystemTime::now())?.map(|url| {
+                teloxide::types::InlineKeyboardButton::url(button::kind::TempLink.to_string(), url)
+            });
+
+        Ok(teloxide::types::InlineKeyboardMarkup::new([manage_buttons
+            .chain(show)
+            .chain(temp_link)
+            .collect::<Vec<_>>()]))
+    }
+
+    /// Construct url of the Web App page showing a resource.
+    ///
+    /// If unlock links are enabled, then the url contains a one-time token instead of the
+    /// encrypted record itself.
+    ///
+    /// Fails if the Web App won't be able to decrypt the record.
+    fn construct_show_url(
+        record: &grpc::Record,
+        context: &Context,
+    ) -> Result<Url, grpc::InvalidRecordError> {
+        let encryption_output = record.encryption_output()?;
+
+        let Some(unlock_token_store) = context.unlock_token_store() else {
+            return Ok(Self::construct_inline_show_url(
+                record,
+                &encryption_output,
+                context,
+            ));
+        };
+
+        let token = unlock_token_store.mint(
+            LockedRecord {
+                encrypted_payload: record.encrypted_payload.clone(),
+                salt: record.salt.clone(),
+                kdf_iterations: record.kdf_iterations,
+                kdf_salt: record.kdf_salt.clone(),
+                algorithm: record.algorithm,
+                bound_to_resource_name: record.bound_to_resource_name,
+                key_commitment: record.key_commitment.clone(),
+                output_version: record.output_version,
+            },
+            &*context.clock(),
+            &*context.rng(),
+        );
+
+        let mut url = web_app_route_url(context, "/show");
+        {
+            let mut query = url.query_pairs_mut();
+            if let Some(resource) = record.resource.as_ref() {
+                query.append_pair("resource_name", &resource.name);
+            }
+            query.append_pair("token", &token).append_pair(
+                "unlock_endpoint",
+                unlock_token_store.endpoint_url().as_str(),
+            );
+        }
+        Ok(url)
+    }
+
+    /// Construct url of the Web App page showing a resource passed in the url itself.
+    fn construct_inline_show_url(
+        record: &grpc::Record,
+        encryption_output: &telepass_data_model::crypto::EncryptionOutput,
+        context: &Context,
+    ) -> Url {
+        let mut url = web_app_route_url(context, "/show");
+        {
+            let mut query = url.query_pairs_mut();
+            if let Some(resource) = record.resource.as_ref() {
+                query.append_pair("resource_name", &resource.name);
+            }
+            for (name, value) in encryption_output.to_url_query().pairs() {
+                query.append_pair(name, &value);
+            }
+            if record.bound_to_resource_name {
+                query.append_pair("bound_to_resource_name", "true");
+            }
+        }
+        url
+    }
+
+    /// Construct url of the Web App page showing a resource outside of Telegram,
+    /// signed to be valid for [`TEMP_LINK_TTL`] since `now`.
+    ///
+    /// Unlike unlock links, the link can be opened many times, so the encrypted record is always
+    /// passed in the url itself. See [`telepass_crypto::signed_link`] for what the signature
+    /// protects from.
+    ///
+    /// Returns [`None`] if temporary links are disabled or the record is too large to fit
+    /// into the url. Fails if the Web App won't be able to decrypt the record.
+    fn construct_temp_link_url(
+        record: &grpc::Record,
+        context: &Context,
+        now: SystemTime,
+    ) -> Result<Option<Url>, grpc::InvalidRecordError> {
+        let Some(temp_link_key) = context.temp_link_key() else {
+            return Ok(None);
+        };
+        if record.is_large() {
+            return Ok(None);
+        }
+
+        let mut url =
+            Self::construct_inline_show_url(record, &record.encryption_output()?, context);
+        // Time before the epoch produces an already expired link
+        let expires_at = now
+            .checked_add(TEMP_LINK_TTL)
+            .and_then(|expiration| expiration.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |since_epoch| since_epoch.as_secs());
+        let params: Vec<_> = url.query_pairs().into_owned().collect();
+        let signature = temp_link_key.sign(
+            params
+                .iter()
+                .map(|param| (param.0.as_str(), param.1.as_str())),
+            expires_at,
+        );
+        url.query_pairs_mut().extend_pairs(signature);
+
+        Ok(Some(url))
     }
 }
 
@@ -207,10 +426,19 @@ impl TryFromTransition<DeleteConfirmation, Button<button::kind::No>> for Resourc
             resource_message_id = displayed_resource_data.resource_message_id;
             resource_name = displayed_resource_data.resource_name.clone();
         }
-        let choose_an_action_text = Self::construct_choose_an_action_text(&resource_name);
+        let attachments = Self::fetch_attachments(&resource_name, context).await;
+        let choose_an_action_text = Self::construct_choose_an_action_text(
+            delete_confirmation.record(),
+            &attachments,
+            &resource_name,
+            context,
+        );
 
-        let actions_keyboard =
-            Self::construct_actions_keyboard(delete_confirmation.record(), context);
+        let actions_keyboard = try_with_state!(
+            delete_confirmation,
+            Self::construct_actions_keyboard(delete_confirmation.record(), context)
+                .map_err(TransitionFailureReason::internal)
+        );
 
         try_with_state!(
             delete_confirmation,
@@ -253,7 +481,10 @@ pub mod tests {
         use crate::{
             command::Command,
             state::State,
-            test_utils::{test_help_success, test_unavailable_command},
+            test_utils::{
+                test_help_success, test_time_zone_success, test_unavailable_command,
+                test_whats_new_success,
+            },
         };
 
         #[test]
@@ -263,6 +494,20 @@ pub mod tests {
             test_help_success(resource_actions).await
         }
 
+        #[test]
+        pub async fn whats_new_success() {
+            let resource_actions = State::resource_actions(true);
+
+            test_whats_new_success(resource_actions).await
+        }
+
+        #[test]
+        pub async fn time_zone_success() {
+            let resource_actions = State::resource_actions(true);
+
+            test_time_zone_success(resource_actions).await
+        }
+
         #[test]
         pub async fn start_failure() {
             let resource_actions = State::resource_actions(true);
@@ -270,6 +515,30 @@ pub mod tests {
 
             test_unavailable_command(resource_actions, start).await
         }
+
+        #[test]
+        pub async fn add_failure() {
+            let resource_actions = State::resource_actions(true);
+            let add = Command::add();
+
+            test_unavailable_command(resource_actions, add).await
+        }
+
+        #[test]
+        pub async fn deep_find_failure() {
+            let resource_actions = State::resource_actions(true);
+            let deep_find = Command::deep_find("router");
+
+            test_unavailable_command(resource_actions, deep_find).await
+        }
+
+        #[test]
+        pub async fn rename_prefix_failure() {
+            let resource_actions = State::resource_actions(true);
+            let rename_prefix = Command::rename_prefix("work/", "team/");
+
+            test_unavailable_command(resource_actions, rename_prefix).await
+        }
     }
 
     pub mod message {
@@ -279,6 +548,7 @@ pub mod tests {
         use crate::{
             grpc,
             message::{Message, MessageBox},
+            role::Role,
             state::{Context, State},
             test_utils::{
                 mock_bot::{MockBotBuilder, CHAT_ID},
@@ -301,14 +571,23 @@ pub mod tests {
             });
 
             let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
             mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_resource_prefix()
+                .return_const(crate::keyboard::ResourcePrefix::default());
             mock_context
                 .expect_web_app_url()
                 .return_const(web_app_test_url());
+            mock_context.expect_unlock_token_store().return_const(None);
+            mock_context.expect_temp_link_key().return_const(None);
+            mock_context.expect_role().return_const(Role::Admin);
 
             mock_context.expect_bot().return_const(
                 MockBotBuilder::new()
-                    .expect_send_message("Type /cancel to go back.")
+                    .expect_send_message("Type /cancel to go back.".to_owned())
                     .expect_reply_markup(teloxide::types::ReplyMarkup::kb_remove())
                     .expect_into_future_with_id(teloxide::types::MessageId(CANCEL_MSG_ID))
                     .expect_send_message(
@@ -322,11 +601,15 @@ pub mod tests {
                             crate::button::kind::Delete.to_string(),
                             crate::button::kind::Delete.to_string(),
                         ),
+                        teloxide::types::InlineKeyboardButton::callback(
+                            crate::button::kind::Duplicate.to_string(),
+                            crate::button::kind::Duplicate.to_string(),
+                        ),
                         teloxide::types::InlineKeyboardButton::web_app(
                             "👀 Show",
                             teloxide::types::WebAppInfo {
                                 url: web_app_test_url()
-                                    .join("/show?resource_name=test.resource.com&payload=dW51c2Vk&salt=dW51c2Vk")
+                                    .join("/show?resource_name=test.resource.com&payload=dW51c2Vk&salt=AAAAAAAAAAAAAAAA&kdf_iterations=100000")
                                     .unwrap(),
                             },
                         ),
@@ -337,17 +620,29 @@ pub mod tests {
 
             let mut mock_storage_client = crate::PasswordStorageClient::default();
             mock_storage_client
-                .expect_get::<grpc::Resource>()
-                .with(predicate::eq(grpc::Resource {
+                .expect_get::<grpc::GetRequest>()
+                .with(predicate::eq(grpc::GetRequest {
                     name: "test.resource.com".to_owned(),
+                    bypass_cache: false,
                 }))
-                .returning(|resource| {
+                .returning(|request| {
                     Ok(tonic::Response::new(grpc::Record {
-                        resource: Some(resource),
+                        resource: Some(grpc::Resource { name: request.name }),
                         encrypted_payload: b"unused".to_vec(),
-                        salt: b"unused".to_vec(),
+                        salt: vec![0; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+                        kdf_iterations: 0,
+                        kdf_salt: Vec::new(),
+                        algorithm: 0,
+                        bound_to_resource_name: false,
+                        revision: 0,
+                        chunk_count: 0,
+                        key_commitment: Vec::new(),
+                        output_version: 0,
                     }))
                 });
+            mock_storage_client
+                .expect_list_attachments::<grpc::Resource>()
+                .returning(|_request| Ok(tonic::Response::new(grpc::ListOfAttachments::default())));
             mock_context
                 .expect_storage_client()
                 .return_const(tokio::sync::Mutex::new(mock_storage_client));
@@ -363,7 +658,7 @@ pub mod tests {
                 .displayed_resource_data
                 .write()
                 .await
-                .bomb
+                .messages
                 .defuse();
         }
 
@@ -384,6 +679,14 @@ pub mod tests {
             test_unexpected_message(resource_actions, list).await
         }
 
+        #[test]
+        pub async fn retry_storage_failure() {
+            let resource_actions = State::resource_actions(true);
+            let retry_storage = MessageBox::retry_storage();
+
+            test_unexpected_message(resource_actions, retry_storage).await
+        }
+
         #[test]
         pub async fn add_failure() {
             let resource_actions = State::resource_actions(true);
@@ -410,6 +713,7 @@ pub mod tests {
 
         use crate::{
             button::ButtonBox,
+            role::Role,
             state::{
                 delete_confirmation::DeleteConfirmation, Context, DisplayedResourceData, State,
             },
@@ -461,9 +765,15 @@ pub mod tests {
 
             let mut mock_context = Context::default();
             mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_resource_prefix()
+                .return_const(crate::keyboard::ResourcePrefix::default());
             mock_context
                 .expect_web_app_url()
                 .return_const(web_app_test_url());
+            mock_context.expect_unlock_token_store().return_const(None);
+            mock_context.expect_temp_link_key().return_const(None);
+            mock_context.expect_role().return_const(Role::Admin);
 
             mock_context.expect_bot().return_const(
                 MockBotBuilder::new()
@@ -481,11 +791,15 @@ pub mod tests {
                             crate::button::kind::Delete.to_string(),
                             crate::button::kind::Delete.to_string(),
                         ),
+                        teloxide::types::InlineKeyboardButton::callback(
+                            crate::button::kind::Duplicate.to_string(),
+                            crate::button::kind::Duplicate.to_string(),
+                        ),
                         teloxide::types::InlineKeyboardButton::web_app(
                             "👀 Show",
                             teloxide::types::WebAppInfo {
                                 url: web_app_test_url()
-                                    .join("/show?resource_name=test.resource.com&payload=dW51c2Vk&salt=dW51c2Vk")
+                                    .join("/show?resource_name=test.resource.com&payload=dW51c2Vk&salt=AAAAAAAAAAAAAAAA&kdf_iterations=100000")
                                     .unwrap(),
                             },
                         ),
@@ -494,6 +808,14 @@ pub mod tests {
                     .build(),
             );
 
+            let mut mock_storage_client = crate::PasswordStorageClient::default();
+            mock_storage_client
+                .expect_list_attachments::<crate::grpc::Resource>()
+                .returning(|_request| Err(tonic::Status::unavailable("Storage is down")));
+            mock_context
+                .expect_storage_client()
+                .return_const(tokio::sync::Mutex::new(mock_storage_client));
+
             let state = State::try_from_transition(delete_confirmation, no_button, &mock_context)
                 .await
                 .unwrap();
@@ -504,8 +826,561 @@ pub mod tests {
                 .displayed_resource_data
                 .write()
                 .await
-                .bomb
+                .messages
                 .defuse();
         }
     }
+    pub mod show_url {
+        use std::{
+            sync::Arc,
+            time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+        };
+
+        use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+        use telepass_crypto::signed_link::{self, LinkKey};
+        use telepass_data_model::crypto::{self, EncryptionOutput, UrlQuery};
+        use url::Url;
+
+        use super::super::{ResourceActions, TEMP_LINK_TTL};
+        use crate::{
+            context::{Clock, MockClock, MockRng, OsRandom, Rng, SystemClock},
+            grpc,
+            state::Context,
+            test_utils::web_app_test_url,
+            unlock_token::{LockedRecord, UnlockTokenStore, DEFAULT_TTL},
+        };
+
+        #[test]
+        pub fn inline_with_kdf_params_success() {
+            let record = grpc::Record {
+                resource: None,
+                encrypted_payload: b"payload".to_vec(),
+                salt: vec![1; crypto::AES_256_GCM_SALT_SIZE],
+                kdf_iterations: 200_000,
+                kdf_salt: vec![2; crypto::KDF_SALT_SIZE],
+                algorithm: 0,
+                bound_to_resource_name: false,
+                revision: 0,
+                chunk_count: 0,
+                key_commitment: Vec::new(),
+                output_version: 0,
+            };
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context.expect_unlock_token_store().return_const(None);
+
+            let url = ResourceActions::construct_show_url(&record, &mock_context).unwrap();
+
+            assert_eq!(
+                url,
+                web_app_test_url()
+                    .join(
+                        "/show?payload=cGF5bG9hZA%3D%3D&salt=AQEBAQEBAQEBAQEB\
+                         &kdf_iterations=200000&kdf_salt=AgICAgICAgICAgICAgICAg%3D%3D"
+                    )
+                    .unwrap()
+            );
+        }
+
+        #[test]
+        pub fn inline_bound_to_resource_name_success() {
+            let record = grpc::Record {
+                resource: Some(grpc::Resource {
+                    name: "bank & co".to_owned(),
+                }),
+                encrypted_payload: b"payload".to_vec(),
+                salt: vec![1; crypto::AES_256_GCM_SALT_SIZE],
+                kdf_iterations: 0,
+                kdf_salt: Vec::new(),
+                algorithm: 0,
+                bound_to_resource_name: true,
+                revision: 0,
+                chunk_count: 0,
+                key_commitment: Vec::new(),
+                output_version: 0,
+            };
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context.expect_unlock_token_store().return_const(None);
+
+            let url = ResourceActions::construct_show_url(&record, &mock_context).unwrap();
+
+            assert_eq!(
+                url,
+                web_app_test_url()
+                    .join(
+                        "/show?resource_name=bank+%26+co&payload=cGF5bG9hZA%3D%3D\
+                         &salt=AQEBAQEBAQEBAQEB&kdf_iterations=100000&bound_to_resource_name=true"
+                    )
+                    .unwrap()
+            );
+        }
+
+        #[test]
+        pub fn inline_round_trip_success() {
+            let record = grpc::Record {
+                resource: Some(grpc::Resource {
+                    name: "bank & co".to_owned(),
+                }),
+                encrypted_payload: b"payload".to_vec(),
+                salt: vec![1; crypto::XCHACHA20_POLY1305_SALT_SIZE],
+                kdf_iterations: 200_000,
+                kdf_salt: vec![2; crypto::KDF_SALT_SIZE],
+                algorithm: grpc::Algorithm::Xchacha20Poly1305.into(),
+                bound_to_resource_name: false,
+                revision: 0,
+                chunk_count: 0,
+                key_commitment: Vec::new(),
+                output_version: 0,
+            };
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context.expect_unlock_token_store().return_const(None);
+
+            let url = ResourceActions::construct_show_url(&record, &mock_context).unwrap();
+
+            let mut query = UrlQuery::default();
+            for (name, value) in url.query_pairs().into_owned() {
+                match name.as_str() {
+                    "resource_name" => assert_eq!(value, "bank & co"),
+                    "payload" => query.payload = value,
+                    "salt" => query.salt = value,
+                    "kdf_iterations" => query.kdf_iterations = Some(value.parse().unwrap()),
+                    "kdf_salt" => query.kdf_salt = Some(value),
+                    "algorithm" => query.algorithm = Some(value),
+                    unexpected => panic!("Unexpected query parameter `{unexpected}`"),
+                }
+            }
+            assert_eq!(
+                EncryptionOutput::from_url_query(&query).unwrap(),
+                record.encryption_output().unwrap()
+            );
+        }
+
+        #[test]
+        pub fn invalid_record_failure() {
+            let record = grpc::Record {
+                resource: None,
+                encrypted_payload: b"payload".to_vec(),
+                salt: b"salt".to_vec(),
+                kdf_iterations: 0,
+                kdf_salt: Vec::new(),
+                algorithm: 0,
+                bound_to_resource_name: false,
+                revision: 0,
+                chunk_count: 0,
+                key_commitment: Vec::new(),
+                output_version: 0,
+            };
+
+            let mock_context = Context::default();
+
+            assert!(matches!(
+                ResourceActions::construct_show_url(&record, &mock_context),
+                Err(grpc::InvalidRecordError::WrongSaltLength(_))
+            ));
+        }
+
+        #[test]
+        pub fn with_unlock_token_success() {
+            let unlock_endpoint = Url::parse("https://gate.test/unlock/").unwrap();
+            let unlock_token_store =
+                Arc::new(UnlockTokenStore::new(DEFAULT_TTL, unlock_endpoint.clone()));
+            let record = grpc::Record {
+                resource: Some(grpc::Resource {
+                    name: "test.resource.com".to_owned(),
+                }),
+                encrypted_payload: b"payload".to_vec(),
+                salt: vec![1; crypto::AES_256_GCM_SALT_SIZE],
+                kdf_iterations: 200_000,
+                kdf_salt: vec![2; crypto::KDF_SALT_SIZE],
+                algorithm: 0,
+                bound_to_resource_name: false,
+                revision: 0,
+                chunk_count: 0,
+                key_commitment: Vec::new(),
+                output_version: 0,
+            };
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context
+                .expect_unlock_token_store()
+                .return_const(Some(Arc::clone(&unlock_token_store)));
+            let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+            mock_context.expect_clock().return_const(clock);
+            let rng: Arc<dyn Rng> = Arc::new(OsRandom);
+            mock_context.expect_rng().return_const(rng);
+
+            let url = ResourceActions::construct_show_url(&record, &mock_context).unwrap();
+
+            assert_eq!(url.path(), "/show");
+            let mut query = url.query_pairs().into_owned();
+            assert_eq!(
+                query.next(),
+                Some(("resource_name".to_owned(), "test.resource.com".to_owned()))
+            );
+            let (token_key, token) = query.next().unwrap();
+            assert_eq!(token_key, "token");
+            assert_eq!(
+                query.next(),
+                Some(("unlock_endpoint".to_owned(), unlock_endpoint.to_string()))
+            );
+            assert_eq!(query.next(), None);
+
+            assert_eq!(
+                unlock_token_store.resolve(&token),
+                Some(LockedRecord {
+                    encrypted_payload: record.encrypted_payload,
+                    salt: record.salt,
+                    kdf_iterations: record.kdf_iterations,
+                    kdf_salt: record.kdf_salt,
+                    algorithm: record.algorithm,
+                    bound_to_resource_name: record.bound_to_resource_name,
+                    key_commitment: record.key_commitment,
+                    output_version: record.output_version,
+                })
+            );
+        }
+
+        #[test]
+        pub fn unlock_token_expires_by_context_clock() {
+            let unlock_token_store = Arc::new(UnlockTokenStore::new(
+                DEFAULT_TTL,
+                Url::parse("https://gate.test/unlock/").unwrap(),
+            ));
+            let record = grpc::Record {
+                resource: None,
+                encrypted_payload: b"payload".to_vec(),
+                salt: vec![1; crypto::AES_256_GCM_SALT_SIZE],
+                kdf_iterations: 0,
+                kdf_salt: Vec::new(),
+                algorithm: 0,
+                bound_to_resource_name: false,
+                revision: 0,
+                chunk_count: 0,
+                key_commitment: Vec::new(),
+                output_version: 0,
+            };
+            let minted_at = Instant::now();
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context
+                .expect_unlock_token_store()
+                .return_const(Some(Arc::clone(&unlock_token_store)));
+            let mut clock = MockClock::new();
+            clock.expect_now().return_const(minted_at);
+            let clock: Arc<dyn Clock> = Arc::new(clock);
+            mock_context.expect_clock().return_const(clock);
+            let mut rng = MockRng::new();
+            rng.expect_fill_bytes().returning(|dest| dest.fill(7));
+            let rng: Arc<dyn Rng> = Arc::new(rng);
+            mock_context.expect_rng().return_const(rng);
+
+            let token = URL_SAFE_NO_PAD.encode([7_u8; 16]);
+            for (elapsed, expected_resolved) in [
+                (
+                    DEFAULT_TTL.checked_sub(Duration::from_secs(1)).unwrap(),
+                    true,
+                ),
+                (DEFAULT_TTL, false),
+            ] {
+                let url = ResourceActions::construct_show_url(&record, &mock_context).unwrap();
+                assert!(url
+                    .query_pairs()
+                    .any(|(key, value)| key == "token" && value == token));
+
+                let now = minted_at.checked_add(elapsed).unwrap();
+                assert_eq!(
+                    unlock_token_store.resolve_at(&token, now).is_some(),
+                    expected_resolved
+                );
+            }
+        }
+
+        #[test]
+        pub fn temp_link_is_signed_until_expiration_success() {
+            let record = grpc::Record {
+                resource: Some(grpc::Resource {
+                    name: "test.resource.com".to_owned(),
+                }),
+                encrypted_payload: b"payload".to_vec(),
+                salt: vec![1; crypto::AES_256_GCM_SALT_SIZE],
+                kdf_iterations: 0,
+                kdf_salt: Vec::new(),
+                algorithm: 0,
+                bound_to_resource_name: false,
+                revision: 0,
+                chunk_count: 0,
+                key_commitment: Vec::new(),
+                output_version: 0,
+            };
+            let now = UNIX_EPOCH
+                .checked_add(Duration::from_secs(1_700_000_000))
+                .unwrap();
+            let temp_link_key = Arc::new(LinkKey::new(b"link key").unwrap());
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context
+                .expect_temp_link_key()
+                .return_const(Some(Arc::clone(&temp_link_key)));
+
+            let url = ResourceActions::construct_temp_link_url(&record, &mock_context, now)
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(url.path(), "/show");
+            let params: Vec<_> = url.query_pairs().into_owned().collect();
+            assert_eq!(
+                params
+                    .iter()
+                    .map(|param| param.0.as_str())
+                    .collect::<Vec<_>>(),
+                [
+                    "resource_name",
+                    "payload",
+                    "salt",
+                    "kdf_iterations",
+                    "exp",
+                    "sig"
+                ]
+            );
+            assert!(params.contains(&("payload".to_owned(), "cGF5bG9hZA==".to_owned())));
+            let verify = |at: u64| {
+                temp_link_key.verify(
+                    params
+                        .iter()
+                        .map(|param| (param.0.as_str(), param.1.as_str())),
+                    at,
+                )
+            };
+            verify(1_700_000_000).unwrap();
+            verify(1_700_000_000 + TEMP_LINK_TTL.as_secs() - 1).unwrap();
+            assert_eq!(
+                verify(1_700_000_000 + TEMP_LINK_TTL.as_secs()),
+                Err(signed_link::Error::Expired(
+                    1_700_000_000 + TEMP_LINK_TTL.as_secs()
+                ))
+            );
+        }
+
+        #[test]
+        pub fn temp_link_is_disabled_or_too_large_success() {
+            let record = grpc::Record {
+                resource: None,
+                encrypted_payload: vec![0; grpc::MAX_INLINE_PAYLOAD_SIZE + 1],
+                salt: vec![1; crypto::AES_256_GCM_SALT_SIZE],
+                kdf_iterations: 0,
+                kdf_salt: Vec::new(),
+                algorithm: 0,
+                bound_to_resource_name: false,
+                revision: 0,
+                chunk_count: 0,
+                key_commitment: Vec::new(),
+                output_version: 0,
+            };
+
+            let mut disabled_context = Context::default();
+            disabled_context.expect_temp_link_key().return_const(None);
+            let mut enabled_context = Context::default();
+            enabled_context
+                .expect_temp_link_key()
+                .return_const(Some(Arc::new(LinkKey::new(b"link key").unwrap())));
+
+            for mock_context in [disabled_context, enabled_context] {
+                assert_eq!(
+                    ResourceActions::construct_temp_link_url(
+                        &record,
+                        &mock_context,
+                        SystemTime::now()
+                    )
+                    .unwrap(),
+                    None
+                );
+            }
+        }
+    }
+
+    pub mod actions_keyboard {
+        use std::sync::Arc;
+
+        use telepass_crypto::signed_link::LinkKey;
+
+        use super::super::ResourceActions;
+        use crate::{grpc, role::Role, state::Context, test_utils::web_app_test_url};
+
+        fn construct_actions_keyboard(role: Role) -> teloxide::types::InlineKeyboardMarkup {
+            construct_actions_keyboard_for(&record(b"payload".to_vec()), role, None)
+        }
+
+        fn record(encrypted_payload: Vec<u8>) -> grpc::Record {
+            grpc::Record {
+                resource: Some(grpc::Resource {
+                    name: "test.resource.com".to_owned(),
+                }),
+                encrypted_payload,
+                salt: vec![0; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+                kdf_iterations: 0,
+                kdf_salt: Vec::new(),
+                algorithm: 0,
+                bound_to_resource_name: false,
+                revision: 0,
+                chunk_count: 0,
+                key_commitment: Vec::new(),
+                output_version: 0,
+            }
+        }
+
+        fn construct_actions_keyboard_for(
+            record: &grpc::Record,
+            role: Role,
+            temp_link_key: Option<Arc<LinkKey>>,
+        ) -> teloxide::types::InlineKeyboardMarkup {
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context.expect_unlock_token_store().return_const(None);
+            mock_context
+                .expect_temp_link_key()
+                .return_const(temp_link_key);
+            mock_context.expect_role().return_const(role);
+
+            ResourceActions::construct_actions_keyboard(record, &mock_context).unwrap()
+        }
+
+        fn button_texts(keyboard: &teloxide::types::InlineKeyboardMarkup) -> Vec<String> {
+            keyboard
+                .inline_keyboard
+                .iter()
+                .flatten()
+                .map(|button| button.text.clone())
+                .collect()
+        }
+
+        #[test]
+        pub fn admin_success() {
+            let keyboard = construct_actions_keyboard(Role::Admin);
+
+            assert_eq!(
+                button_texts(&keyboard),
+                [
+                    crate::button::kind::Delete.to_string(),
+                    crate::button::kind::Duplicate.to_string(),
+                    crate::button::kind::Show.to_string()
+                ]
+            );
+        }
+
+        #[test]
+        pub fn viewer_without_manage_buttons_success() {
+            let keyboard = construct_actions_keyboard(Role::Viewer);
+
+            assert_eq!(
+                button_texts(&keyboard),
+                [crate::button::kind::Show.to_string()]
+            );
+        }
+
+        #[test]
+        pub fn temp_link_success() {
+            let keyboard = construct_actions_keyboard_for(
+                &record(b"payload".to_vec()),
+                Role::Viewer,
+                Some(Arc::new(LinkKey::new(b"link key").unwrap())),
+            );
+
+            assert_eq!(
+                button_texts(&keyboard),
+                [
+                    crate::button::kind::Show.to_string(),
+                    crate::button::kind::TempLink.to_string()
+                ]
+            );
+        }
+
+        #[test]
+        pub fn large_record_without_unlock_links_success() {
+            let record = record(vec![0; grpc::MAX_INLINE_PAYLOAD_SIZE + 1]);
+
+            let keyboard = construct_actions_keyboard_for(
+                &record,
+                Role::Admin,
+                Some(Arc::new(LinkKey::new(b"link key").unwrap())),
+            );
+
+            assert_eq!(
+                button_texts(&keyboard),
+                [
+                    crate::button::kind::Delete.to_string(),
+                    crate::button::kind::Duplicate.to_string()
+                ]
+            );
+
+            let mut mock_context = Context::default();
+            mock_context.expect_unlock_token_store().return_const(None);
+            mock_context
+                .expect_resource_prefix()
+                .return_const(crate::keyboard::ResourcePrefix::default());
+            let text = ResourceActions::construct_choose_an_action_text(
+                &record,
+                &[],
+                "test.resource.com",
+                &mock_context,
+            );
+            assert!(text.contains("too large to be shown"), "{text}");
+        }
+
+        #[test]
+        pub fn attachments_are_listed_success() {
+            let record = record(b"payload".to_vec());
+            let attachments = [
+                grpc::AttachmentInfo {
+                    filename: "recovery.pdf".to_owned(),
+                    size: 1024,
+                },
+                grpc::AttachmentInfo {
+                    filename: "notes.txt".to_owned(),
+                    size: 7,
+                },
+            ];
+
+            let mut mock_context = Context::default();
+            mock_context.expect_unlock_token_store().return_const(None);
+            mock_context
+                .expect_resource_prefix()
+                .return_const(crate::keyboard::ResourcePrefix::default());
+            let text = ResourceActions::construct_choose_an_action_text(
+                &record,
+                &attachments,
+                "test.resource.com",
+                &mock_context,
+            );
+            assert_eq!(
+                text,
+                "🔑 *test\\.resource\\.com*\n\n\
+                 📎 Attachments:\n\
+                 • recovery\\.pdf \\(1024 bytes\\)\n\
+                 • notes\\.txt \\(7 bytes\\)\n\n\
+                 Choose an action:"
+            );
+        }
+    }
 }
@@ -4,16 +4,22 @@ use std::fmt::Debug;
 
 use color_eyre::eyre::Context as _;
 use nonempty::NonEmpty;
-use teloxide::types::{KeyboardButton, KeyboardMarkup};
 #[cfg(not(test))]
-use teloxide::{payloads::SendMessageSetters as _, requests::Requester as _};
+use teloxide::payloads::SendMessageSetters as _;
+use teloxide::types::{KeyboardButton, KeyboardMarkup};
 
 use super::{
-    delete_confirmation::DeleteConfirmation, main_menu::MainMenu,
-    resource_actions::ResourceActions, Context,
+    deep_find_prompt::{self, DeepFindPrompt},
+    delete_confirmation::DeleteConfirmation,
+    duplicate_name_prompt::DuplicateNamePrompt,
+    main_menu::MainMenu,
+    resource_actions::ResourceActions,
+    Context,
 };
 use crate::{
-    command, grpc,
+    command,
+    footer::{self, MessageClass},
+    grpc::{self, StorageApi as _},
     message::{self, Message},
     transition::{
         try_with_state, Destroy, FailedTransition, TransitionFailureReason, TryFromTransition,
@@ -24,6 +30,24 @@ use crate::{
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct ResourcesList(());
 
+/// User error returned when going back to the list while there are no stored resources.
+const NO_STORED_PASSWORDS: &str = "❎ There are no stored passwords yet.";
+
+/// Guide sent instead of the list if there are no stored resources.
+const EMPTY_VAULT_GUIDE: &str = "🗄 Your vault is empty, let's add the first password!\n\n\
+    Each record keeps a login and a password for one resource. \
+    They are encrypted with your master password before being stored, \
+    so nobody can read them without it.\n\n\
+    Press the button below to add a new password.";
+
+/// Outcome of [`ResourcesList::from_state_impl()`].
+enum Listing {
+    /// List of stored resources is shown.
+    Shown(ResourcesList),
+    /// There are no stored resources, nothing is shown.
+    EmptyVault,
+}
+
 impl ResourcesList {
     /// Create a new [`ResourcesList`] state for tests.
     #[cfg(test)]
@@ -31,7 +55,7 @@ impl ResourcesList {
         Self(())
     }
 
-    /// Setup [`ResourcesList`] state from previous state.
+    /// Setup [`ResourcesList`] state from previous state destroying it.
     ///
     /// Constructs a keyboard with resources for all stored passwords.
     ///
@@ -41,15 +65,6 @@ impl ResourcesList {
     /// - Unable to retrieve the list of stored resources;
     /// - There are no stored resources;
     /// - Unable to send a message.
-    async fn from_state<P>(prev_state: P, context: &Context) -> Result<Self, FailedTransition<P>>
-    where
-        P: Debug + Send + Sync + 'static,
-    {
-        let resources_list = try_with_state!(prev_state, Self::from_state_impl(context).await);
-        Ok(resources_list)
-    }
-
-    /// [`from_state()`](Self::from_state) analog with destroying previous state.
     async fn from_state_destroying<P>(
         prev_state: P,
         context: &Context,
@@ -57,15 +72,21 @@ impl ResourcesList {
     where
         P: Debug + Destroy + Send + Sync + 'static,
     {
-        let resources_list = try_with_state!(prev_state, Self::from_state_impl(context).await);
+        let listing = try_with_state!(prev_state, Self::from_state_impl(context).await);
+        let Listing::Shown(resources_list) = listing else {
+            return Err(FailedTransition::user(prev_state, NO_STORED_PASSWORDS));
+        };
 
         prev_state.destroy_and_log_err(context).await;
         Ok(resources_list)
     }
 
-    /// [`from_state()`](Self::from_state) and
-    /// [`from_state_destroying()`](Self::from_state_destroying) implementation.
-    async fn from_state_impl(context: &Context) -> Result<Self, TransitionFailureReason> {
+    /// [`from_state_destroying()`](Self::from_state_destroying) and
+    /// [`ResourcesListOrMainMenu`] implementation.
+    ///
+    /// Doesn't send anything if there are no stored resources,
+    /// leaving it to the caller to decide how to handle it.
+    async fn from_state_impl(context: &Context) -> Result<Listing, TransitionFailureReason> {
         let resources = context
             .storage_client()
             .lock()
@@ -77,9 +98,9 @@ impl ResourcesList {
             .into_inner()
             .resources;
 
-        let resources = NonEmpty::from_vec(resources).ok_or_else(|| {
-            TransitionFailureReason::User("❎ There are no stored passwords yet.".to_owned())
-        })?;
+        let Some(resources) = NonEmpty::from_vec(resources) else {
+            return Ok(Listing::EmptyVault);
+        };
 
         Self::from_resources(
             resources,
@@ -87,6 +108,7 @@ impl ResourcesList {
             "👉 Choose a resource or type for search.",
         )
         .await
+        .map(Listing::Shown)
     }
 
     /// Construct [`ResourcesList`] state with given resources.
@@ -95,26 +117,36 @@ impl ResourcesList {
         context: &Context,
         message: &'static str,
     ) -> Result<Self, TransitionFailureReason> {
-        let buttons = resources
-            .into_iter()
-            .map(|resource| [KeyboardButton::new(format!("🔑 {}", resource.name))]);
+        let resource_prefix = context.resource_prefix();
+        let buttons = resources.into_iter().map(|resource| {
+            [KeyboardButton::new(
+                resource_prefix.prefix_resource(&resource.name),
+            )]
+        });
         let keyboard = KeyboardMarkup::new(buttons).resize_keyboard();
 
-        context
-            .bot()
-            .send_message(
-                context.chat_id(),
-                format!("{message}\n\nType /cancel to go back."),
-            )
-            .reply_markup(keyboard)
-            .await
-            .map_err(TransitionFailureReason::internal)?;
+        footer::send_text(
+            context,
+            format!("{message}\n\nType /cancel to go back."),
+            MessageClass::Plain,
+        )
+        .reply_markup(keyboard)
+        .await
+        .map_err(TransitionFailureReason::internal)?;
 
         Ok(Self(()))
     }
 }
 
-impl TryFromTransition<MainMenu, Message<message::kind::List>> for ResourcesList {
+/// Result of [`list`](message::kind::List) message sent on [`MainMenu`] state.
+pub enum ResourcesListOrMainMenu {
+    /// List of stored resources.
+    ResourcesList(ResourcesList),
+    /// Main menu with a guide to add the first record if there are no stored resources.
+    MainMenu(MainMenu),
+}
+
+impl TryFromTransition<MainMenu, Message<message::kind::List>> for ResourcesListOrMainMenu {
     type ErrorTarget = MainMenu;
 
     async fn try_from_transition(
@@ -122,7 +154,32 @@ impl TryFromTransition<MainMenu, Message<message::kind::List>> for ResourcesList
         _list: Message<message::kind::List>,
         context: &Context,
     ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
-        Self::from_state(main_menu, context).await
+        Self::list(main_menu, context).await
+    }
+}
+
+impl ResourcesListOrMainMenu {
+    /// List stored resources from `main_menu`.
+    ///
+    /// Shared by the [`list`](message::kind::List) message and the
+    /// [`List`](command::StartAction::List) quick action.
+    pub async fn list(
+        main_menu: MainMenu,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<MainMenu>> {
+        let listing = try_with_state!(main_menu, ResourcesList::from_state_impl(context).await);
+        if let Listing::Shown(resources_list) = listing {
+            return Ok(Self::ResourcesList(resources_list));
+        }
+
+        try_with_state!(
+            main_menu,
+            footer::send_text(context, EMPTY_VAULT_GUIDE, MessageClass::Plain)
+                .reply_markup(MainMenu::keyboard(context))
+                .await
+                .map_err(TransitionFailureReason::internal)
+        );
+        Ok(Self::MainMenu(main_menu))
     }
 }
 
@@ -144,17 +201,17 @@ impl TryFromTransition<ResourcesList, Message<message::kind::Arbitrary>>
         arbitrary: Message<message::kind::Arbitrary>,
         context: &Context,
     ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
-        let resource_name = arbitrary.to_string();
-        let resource_name = resource_name.strip_prefix("🔑 ").unwrap_or(&resource_name);
+        let text = arbitrary.to_string();
+        let resource_name = context.resource_prefix().strip_resource_prefix(&text);
 
-        let res = context
-            .storage_client()
-            .lock()
-            .await
-            .get(grpc::Resource {
+        let res = grpc::get_record(
+            &mut *context.storage_client().lock().await,
+            grpc::GetRequest {
                 name: resource_name.to_owned(),
-            })
-            .await;
+                bypass_cache: false,
+            },
+        )
+        .await;
 
         match res {
             Ok(response) => Ok(Self::ResourceActions(
@@ -201,6 +258,72 @@ impl TryFromTransition<ResourcesList, Message<message::kind::Arbitrary>>
     }
 }
 
+impl TryFromTransition<DeepFindPrompt, Message<message::kind::WebApp>> for ResourcesList {
+    type ErrorTarget = DeepFindPrompt;
+
+    async fn try_from_transition(
+        deep_find_prompt: DeepFindPrompt,
+        web_app_msg: Message<message::kind::WebApp>,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
+        let teloxide::types::WebAppData { data, button_text } = web_app_msg.kind.0;
+        if button_text != deep_find_prompt::BUTTON_TEXT {
+            return Err(FailedTransition::user(
+                deep_find_prompt,
+                "Unexpected WebApp button text.",
+            ));
+        }
+
+        let search: telepass_data_model::BlindSearch = try_with_state!(
+            deep_find_prompt,
+            super::parse_web_app_data(&data, context, "a search request")
+        );
+
+        // Records indexed before the latest blind index version are found by the legacy token.
+        let mut found_resources = Vec::new();
+        for token in std::iter::once(search.token).chain(search.legacy_token) {
+            found_resources.extend(
+                try_with_state!(
+                    deep_find_prompt,
+                    context
+                        .storage_client()
+                        .lock()
+                        .await
+                        .search_blind(grpc::BlindTokens {
+                            tokens: vec![token.to_vec()],
+                        })
+                        .await
+                        .wrap_err("Failed to search by blind index")
+                        .map_err(TransitionFailureReason::internal)
+                )
+                .into_inner()
+                .resources,
+            );
+        }
+        found_resources.sort_by(|left, right| left.name.cmp(&right.name));
+        found_resources.dedup();
+        let Some(found_resources) = NonEmpty::from_vec(found_resources) else {
+            let message = format!(
+                "❎ No records mention \"{}\". Check your master password and try again \
+                 or type /cancel to go back.",
+                deep_find_prompt.keyword()
+            );
+            return Err(FailedTransition::user(deep_find_prompt, message));
+        };
+
+        let resources_list = try_with_state!(
+            deep_find_prompt,
+            Self::from_resources(
+                found_resources,
+                context,
+                "👉 The following resources mention the word, choose one of them or type for search.",
+            )
+            .await
+        );
+        Ok(resources_list)
+    }
+}
+
 impl TryFromTransition<ResourceActions, command::Cancel> for ResourcesList {
     type ErrorTarget = ResourceActions;
 
@@ -225,10 +348,38 @@ impl TryFromTransition<DeleteConfirmation, command::Cancel> for ResourcesList {
     }
 }
 
+impl TryFromTransition<DuplicateNamePrompt, command::Cancel> for ResourcesList {
+    type ErrorTarget = DuplicateNamePrompt;
+
+    async fn try_from_transition(
+        duplicate_name_prompt: DuplicateNamePrompt,
+        _cancel: command::Cancel,
+        context: &Context,
+    ) -> Result<Self, FailedTransition<Self::ErrorTarget>> {
+        Self::from_state_destroying(duplicate_name_prompt, context).await
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
 
+    use crate::grpc;
+
+    /// Construct mock storage client with no stored resources.
+    fn mock_empty_storage_client() -> crate::PasswordStorageClient {
+        let mut mock_storage_client = crate::PasswordStorageClient::default();
+        mock_storage_client
+            .expect_list::<grpc::Empty>()
+            .with(mockall::predicate::eq(grpc::Empty {}))
+            .returning(|_empty| {
+                Ok(tonic::Response::new(grpc::ListOfResources {
+                    resources: Vec::new(),
+                }))
+            });
+        mock_storage_client
+    }
+
     pub mod command {
         use std::{future::ready, sync::Arc};
 
@@ -237,17 +388,21 @@ pub mod tests {
         use tokio::{sync::RwLock, test};
 
         use crate::{
-            command::Command,
+            command::{Command, StartAction},
             grpc,
             state::{
-                delete_confirmation::DeleteConfirmation, resource_actions::ResourceActions,
+                delete_confirmation::DeleteConfirmation,
+                duplicate_name_prompt::DuplicateNamePrompt,
+                resource_actions::ResourceActions,
+                resources_list::{tests::mock_empty_storage_client, NO_STORED_PASSWORDS},
                 Context, DisplayedResourceData, State,
             },
             test_utils::{
                 mock_bot::{MockBotBuilder, MockSendMessage, CHAT_ID},
-                test_help_success, test_unavailable_command,
+                test_add_success, test_help_success, test_time_zone_success,
+                test_unavailable_command, test_whats_new_success,
             },
-            transition::TryFromTransition as _,
+            transition::{TransitionFailureReason, TryFromTransition as _},
             TelegramMessage,
         };
 
@@ -263,7 +418,13 @@ pub mod tests {
             ];
 
             let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
             mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_resource_prefix()
+                .return_const(crate::keyboard::ResourcePrefix::default());
 
             mock_bot
                 .expect_send_message::<_, String>()
@@ -290,7 +451,7 @@ pub mod tests {
                             let mut inner_mock_send_message = MockSendMessage::default();
                             inner_mock_send_message
                                 .expect_into_future()
-                                .return_const(ready(Ok(TelegramMessage::default())));
+                                .return_once(|| ready(Ok(TelegramMessage::default())));
                             inner_mock_send_message
                         });
 
@@ -327,14 +488,51 @@ pub mod tests {
             test_help_success(resources_list).await
         }
 
+        #[test]
+        pub async fn whats_new_success() {
+            let resources_list = State::resources_list();
+
+            test_whats_new_success(resources_list).await
+        }
+
+        #[test]
+        pub async fn time_zone_success() {
+            let resources_list = State::resources_list();
+
+            test_time_zone_success(resources_list).await
+        }
+
         #[test]
         pub async fn start_failure() {
             let resources_list = State::resources_list();
-            let start = Command::Start(crate::command::Start);
+            let start = Command::start();
 
             test_unavailable_command(resources_list, start).await
         }
 
+        #[test]
+        pub async fn add_success() {
+            let resources_list = State::resources_list();
+
+            test_add_success(resources_list).await
+        }
+
+        #[test]
+        pub async fn deep_find_failure() {
+            let resources_list = State::resources_list();
+            let deep_find = Command::deep_find("router");
+
+            test_unavailable_command(resources_list, deep_find).await
+        }
+
+        #[test]
+        pub async fn rename_prefix_failure() {
+            let resources_list = State::resources_list();
+            let rename_prefix = Command::rename_prefix("work/", "team/");
+
+            test_unavailable_command(resources_list, rename_prefix).await
+        }
+
         #[test]
         pub async fn from_resource_actions_by_cancel_success() {
             const REQUEST_MESSAGE_ID: i32 = 100;
@@ -365,6 +563,26 @@ pub mod tests {
             test_resources_actions_setup(resource_actions, cancel, mock_bot).await
         }
 
+        #[test]
+        pub async fn from_resource_actions_by_cancel_with_empty_vault_failure() {
+            let resource_actions = State::resource_actions(true);
+            let cancel = Command::Cancel(crate::command::Cancel);
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_storage_client()
+                .return_const(tokio::sync::Mutex::new(mock_empty_storage_client()));
+
+            let err = State::try_from_transition(resource_actions.clone(), cancel, &mock_context)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == NO_STORED_PASSWORDS
+            ));
+            assert_eq!(err.target, resource_actions);
+        }
+
         #[test]
         pub async fn from_delete_confirmation_by_cancel_success() {
             const REQUEST_MESSAGE_ID: i32 = 100;
@@ -395,6 +613,41 @@ pub mod tests {
 
             test_resources_actions_setup(delete_confirmation, cancel, mock_bot).await
         }
+
+        #[test]
+        pub async fn from_duplicate_name_prompt_by_cancel_success() {
+            const REQUEST_MESSAGE_ID: i32 = 100;
+            const CANCEL_MESSAGE_ID: i32 = 101;
+            const RESOURCE_MESSAGE_ID: i32 = 102;
+
+            let duplicate_name_prompt = State::DuplicateNamePrompt(
+                DuplicateNamePrompt::test(Arc::new(RwLock::new(DisplayedResourceData::new(
+                    teloxide::types::MessageId(REQUEST_MESSAGE_ID),
+                    teloxide::types::MessageId(CANCEL_MESSAGE_ID),
+                    teloxide::types::MessageId(RESOURCE_MESSAGE_ID),
+                    "test.resource.com".to_owned(),
+                ))))
+                .await,
+            );
+
+            let cancel = Command::Cancel(crate::command::Cancel);
+
+            let mock_bot = MockBotBuilder::new()
+                .expect_delete_message(MessageId(REQUEST_MESSAGE_ID))
+                .expect_delete_message(MessageId(CANCEL_MESSAGE_ID))
+                .expect_delete_message(MessageId(RESOURCE_MESSAGE_ID))
+                .build();
+
+            test_resources_actions_setup(duplicate_name_prompt, cancel, mock_bot).await
+        }
+
+        #[test]
+        pub async fn from_main_menu_by_start_list_success() {
+            let main_menu = State::main_menu();
+            let start = Command::start_with(StartAction::List);
+
+            test_resources_actions_setup(main_menu, start, MockBotBuilder::new().build()).await
+        }
     }
 
     pub mod message {
@@ -405,10 +658,15 @@ pub mod tests {
         use crate::{
             grpc,
             message::MessageBox,
-            state::{Context, State},
+            state::{
+                deep_find_prompt,
+                resources_list::{tests::mock_empty_storage_client, EMPTY_VAULT_GUIDE},
+                Context, State,
+            },
             test_utils::{
+                main_menu_keyboard,
                 mock_bot::{MockBotBuilder, CHAT_ID},
-                test_unexpected_message,
+                test_unexpected_message, web_app_data, web_app_test_url,
             },
             transition::{TransitionFailureReason, TryFromTransition as _},
         };
@@ -429,6 +687,14 @@ pub mod tests {
             test_unexpected_message(resources_list, list).await
         }
 
+        #[test]
+        pub async fn retry_storage_failure() {
+            let resources_list = State::resources_list();
+            let retry_storage = MessageBox::retry_storage();
+
+            test_unexpected_message(resources_list, retry_storage).await
+        }
+
         #[test]
         pub async fn add_failure() {
             let resources_list = State::resources_list();
@@ -449,7 +715,13 @@ pub mod tests {
             let list = MessageBox::list();
 
             let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
             mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_resource_prefix()
+                .return_const(crate::keyboard::ResourcePrefix::default());
 
             let expected_buttons = RESOURCE_NAMES
                 .into_iter()
@@ -491,32 +763,33 @@ pub mod tests {
         }
 
         #[test]
-        pub async fn from_main_menu_by_list_with_empty_user_failure() {
+        pub async fn from_main_menu_by_list_with_empty_vault_success() {
             let main_menu = State::main_menu();
             let list = MessageBox::list();
 
             let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
             mock_context.expect_chat_id().return_const(CHAT_ID);
-
-            let mut mock_storage_client = crate::PasswordStorageClient::default();
-            mock_storage_client
-                .expect_list::<grpc::Empty>()
-                .with(predicate::eq(grpc::Empty {}))
-                .returning(|_empty| {
-                    Ok(tonic::Response::new(grpc::ListOfResources {
-                        resources: Vec::new(),
-                    }))
-                });
+            mock_context
+                .expect_web_app_url()
+                .return_const(web_app_test_url());
+            mock_context.expect_bot().return_const(
+                MockBotBuilder::new()
+                    .expect_send_message(EMPTY_VAULT_GUIDE.to_owned())
+                    .expect_reply_markup(main_menu_keyboard())
+                    .expect_into_future()
+                    .build(),
+            );
             mock_context
                 .expect_storage_client()
-                .return_const(tokio::sync::Mutex::new(mock_storage_client));
+                .return_const(tokio::sync::Mutex::new(mock_empty_storage_client()));
 
-            let err = State::try_from_transition(main_menu, list, &mock_context)
+            let state = State::try_from_transition(main_menu.clone(), list, &mock_context)
                 .await
-                .unwrap_err();
-            assert!(matches!(
-                err.reason,
-                TransitionFailureReason::User(message) if message == "❎ There are no stored passwords yet."))
+                .unwrap();
+            assert_eq!(state, main_menu);
         }
 
         #[test]
@@ -528,7 +801,13 @@ pub mod tests {
             let search_resource_name_msg = MessageBox::arbitrary("search.test.resource.com");
 
             let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
             mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_resource_prefix()
+                .return_const(crate::keyboard::ResourcePrefix::default());
 
             let expected_buttons = FOUND_RESOURCE_NAMES
                 .into_iter()
@@ -548,9 +827,10 @@ pub mod tests {
 
             let mut mock_storage_client = crate::PasswordStorageClient::default();
             mock_storage_client
-                .expect_get::<crate::grpc::Resource>()
-                .with(predicate::eq(crate::grpc::Resource {
+                .expect_get::<crate::grpc::GetRequest>()
+                .with(predicate::eq(crate::grpc::GetRequest {
                     name: "search.test.resource.com".to_owned(),
+                    bypass_cache: false,
                 }))
                 .returning(|_resource| {
                     Err(tonic::Status::not_found(
@@ -590,12 +870,16 @@ pub mod tests {
 
             let mut mock_context = Context::default();
             mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_resource_prefix()
+                .return_const(crate::keyboard::ResourcePrefix::default());
 
             let mut mock_storage_client = crate::PasswordStorageClient::default();
             mock_storage_client
-                .expect_get::<crate::grpc::Resource>()
-                .with(predicate::eq(crate::grpc::Resource {
+                .expect_get::<crate::grpc::GetRequest>()
+                .with(predicate::eq(crate::grpc::GetRequest {
                     name: "search.test.resource.com".to_owned(),
+                    bypass_cache: false,
                 }))
                 .returning(|_resource| {
                     Err(tonic::Status::not_found(
@@ -624,6 +908,245 @@ pub mod tests {
                 err.reason,
                 TransitionFailureReason::User(message) if message == "❎ No passwords found for a given query."))
         }
+
+        /// Construct Web App message with a search request for `token`.
+        fn deep_find_web_app(token: telepass_data_model::crypto::BlindToken) -> MessageBox {
+            deep_find_web_app_with_legacy_token(token, None)
+        }
+
+        /// Construct Web App message with a search request for `token` and `legacy_token`.
+        fn deep_find_web_app_with_legacy_token(
+            token: telepass_data_model::crypto::BlindToken,
+            legacy_token: Option<telepass_data_model::crypto::BlindToken>,
+        ) -> MessageBox {
+            MessageBox::web_app(
+                web_app_data(telepass_data_model::BlindSearch {
+                    token,
+                    legacy_token,
+                }),
+                deep_find_prompt::BUTTON_TEXT.to_owned(),
+            )
+        }
+
+        /// Construct mock storage client finding `found_resource_names` by a single token.
+        fn mock_blind_search_storage_client(
+            token: telepass_data_model::crypto::BlindToken,
+            found_resource_names: &'static [&'static str],
+        ) -> crate::PasswordStorageClient {
+            let mut mock_storage_client = crate::PasswordStorageClient::default();
+            mock_storage_client
+                .expect_search_blind::<grpc::BlindTokens>()
+                .with(predicate::eq(grpc::BlindTokens {
+                    tokens: vec![token.to_vec()],
+                }))
+                .returning(move |_tokens| {
+                    Ok(tonic::Response::new(grpc::ListOfResources {
+                        resources: found_resource_names
+                            .iter()
+                            .map(|&name| grpc::Resource {
+                                name: name.to_owned(),
+                            })
+                            .collect(),
+                    }))
+                });
+            mock_storage_client
+        }
+
+        #[test]
+        pub async fn from_deep_find_prompt_by_web_app_success() {
+            const FOUND_RESOURCE_NAMES: [&str; 2] = ["home.router", "office.router"];
+            let token = [7; telepass_data_model::crypto::BLIND_TOKEN_SIZE];
+
+            let deep_find_prompt = State::deep_find_prompt();
+            let web_app = deep_find_web_app(token);
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_resource_prefix()
+                .return_const(crate::keyboard::ResourcePrefix::default());
+
+            let expected_buttons = FOUND_RESOURCE_NAMES
+                .into_iter()
+                .map(|name| [KeyboardButton::new(format!("🔑 {name}"))]);
+            let expected_keyboard = KeyboardMarkup::new(expected_buttons).resize_keyboard();
+            mock_context.expect_bot().return_const(
+                MockBotBuilder::new()
+                    .expect_send_message(
+                        "👉 The following resources mention the word, choose one of them \
+                         or type for search.\n\nType /cancel to go back."
+                            .to_owned(),
+                    )
+                    .expect_reply_markup(expected_keyboard)
+                    .expect_into_future()
+                    .build(),
+            );
+            mock_context
+                .expect_storage_client()
+                .return_const(tokio::sync::Mutex::new(mock_blind_search_storage_client(
+                    token,
+                    &FOUND_RESOURCE_NAMES,
+                )));
+
+            let state = State::try_from_transition(deep_find_prompt, web_app, &mock_context)
+                .await
+                .unwrap();
+            assert_eq!(state, State::resources_list());
+        }
+
+        #[test]
+        pub async fn from_deep_find_prompt_by_web_app_with_legacy_token_success() {
+            let token = [7; telepass_data_model::crypto::BLIND_TOKEN_SIZE];
+            let legacy_token = [8; telepass_data_model::crypto::BLIND_TOKEN_SIZE];
+
+            let deep_find_prompt = State::deep_find_prompt();
+            let web_app = deep_find_web_app_with_legacy_token(token, Some(legacy_token));
+
+            let mut mock_context = Context::default();
+            mock_context
+                .expect_message_footer()
+                .return_const(crate::footer::MessageFooter::default());
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_resource_prefix()
+                .return_const(crate::keyboard::ResourcePrefix::default());
+
+            let expected_buttons = ["home.router", "office.router"]
+                .into_iter()
+                .map(|name| [KeyboardButton::new(format!("🔑 {name}"))]);
+            let expected_keyboard = KeyboardMarkup::new(expected_buttons).resize_keyboard();
+            mock_context.expect_bot().return_const(
+                MockBotBuilder::new()
+                    .expect_send_message(
+                        "👉 The following resources mention the word, choose one of them \
+                         or type for search.\n\nType /cancel to go back."
+                            .to_owned(),
+                    )
+                    .expect_reply_markup(expected_keyboard)
+                    .expect_into_future()
+                    .build(),
+            );
+            let mut mock_storage_client =
+                mock_blind_search_storage_client(token, &["office.router"]);
+            mock_storage_client
+                .expect_search_blind::<grpc::BlindTokens>()
+                .with(predicate::eq(grpc::BlindTokens {
+                    tokens: vec![legacy_token.to_vec()],
+                }))
+                .returning(|_tokens| {
+                    Ok(tonic::Response::new(grpc::ListOfResources {
+                        resources: ["office.router", "home.router"]
+                            .into_iter()
+                            .map(|name| grpc::Resource {
+                                name: name.to_owned(),
+                            })
+                            .collect(),
+                    }))
+                });
+            mock_context
+                .expect_storage_client()
+                .return_const(tokio::sync::Mutex::new(mock_storage_client));
+
+            let state = State::try_from_transition(deep_find_prompt, web_app, &mock_context)
+                .await
+                .unwrap();
+            assert_eq!(state, State::resources_list());
+        }
+
+        #[test]
+        pub async fn from_deep_find_prompt_by_web_app_without_results_failure() {
+            let token = [7; telepass_data_model::crypto::BLIND_TOKEN_SIZE];
+
+            let deep_find_prompt = State::deep_find_prompt();
+            let web_app = deep_find_web_app(token);
+
+            let mut mock_context = Context::default();
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+            mock_context
+                .expect_storage_client()
+                .return_const(tokio::sync::Mutex::new(mock_blind_search_storage_client(
+                    token,
+                    &[],
+                )));
+
+            let err = State::try_from_transition(deep_find_prompt.clone(), web_app, &mock_context)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == "❎ No records mention \"router\". \
+                    Check your master password and try again or type /cancel to go back.",
+            ));
+            assert_eq!(err.target, deep_find_prompt);
+        }
+
+        #[test]
+        pub async fn from_deep_find_prompt_by_web_app_wrong_button_text_failure() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let web_app = MessageBox::web_app("data".to_owned(), "🆕 Add".to_owned());
+
+            let mock_context = Context::default();
+
+            let err = State::try_from_transition(deep_find_prompt.clone(), web_app, &mock_context)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == "Unexpected WebApp button text.",
+            ));
+            assert_eq!(err.target, deep_find_prompt);
+        }
+
+        #[test]
+        pub async fn from_deep_find_prompt_by_web_app_wrong_data_failure() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let web_app = MessageBox::web_app(
+                r#"{"token": "router"}"#.to_owned(),
+                deep_find_prompt::BUTTON_TEXT.to_owned(),
+            );
+
+            let mock_context = Context::default();
+
+            let err = State::try_from_transition(deep_find_prompt.clone(), web_app, &mock_context)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message == "Failed to parse a search request, your Telegram Client is probably invalid.",
+            ));
+            assert_eq!(err.target, deep_find_prompt);
+        }
+
+        #[test]
+        pub async fn from_deep_find_prompt_by_web_app_from_another_user_failure() {
+            let deep_find_prompt = State::deep_find_prompt();
+            let web_app = MessageBox::web_app(
+                serde_json::to_string(&telepass_data_model::WebAppMessage {
+                    user_id: 42,
+                    data: telepass_data_model::BlindSearch {
+                        token: [7; telepass_data_model::crypto::BLIND_TOKEN_SIZE],
+                        legacy_token: None,
+                    },
+                })
+                .unwrap(),
+                deep_find_prompt::BUTTON_TEXT.to_owned(),
+            );
+
+            let mut mock_context = Context::default();
+            mock_context.expect_chat_id().return_const(CHAT_ID);
+
+            let err = State::try_from_transition(deep_find_prompt.clone(), web_app, &mock_context)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.reason,
+                TransitionFailureReason::User(message) if message.starts_with("⚠️ This form was opened by another Telegram user"),
+            ));
+            assert_eq!(err.target, deep_find_prompt);
+        }
     }
 
     pub mod button {
@@ -662,5 +1185,13 @@ pub mod tests {
 
             test_unexpected_button(resources_list, no_button).await;
         }
+
+        #[test]
+        pub async fn duplicate_failure() {
+            let resources_list = State::resources_list();
+            let duplicate_button = ButtonBox::duplicate();
+
+            test_unexpected_button(resources_list, duplicate_button).await;
+        }
     }
 }
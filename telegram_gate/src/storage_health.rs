@@ -0,0 +1,282 @@
+//! Module to wait for the password storage service to become ready and to track its availability.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// Message sent to the user when password storage is not available.
+pub const UNAVAILABLE_MESSAGE: &str =
+    "Password storage is unavailable right now, please try again later.";
+
+/// Boxed health probe of the password storage.
+type Probe =
+    Box<dyn Fn() -> Pin<Box<dyn Future<Output = color_eyre::Result<()>> + Send>> + Send + Sync>;
+
+/// Shared availability state of the password storage.
+///
+/// Requests are not handled while storage is unavailable, except the ones which lead to the
+/// degraded main menu where user can [`reprobe()`](Self::reprobe) storage manually.
+///
+/// Storage is initially unavailable.
+pub struct StorageAvailability {
+    /// Whether storage is available.
+    available: AtomicBool,
+    /// Probe to check storage health on demand.
+    probe: Probe,
+}
+
+impl std::fmt::Debug for StorageAvailability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StorageAvailability")
+            .field("available", &self.available)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StorageAvailability {
+    /// Construct new [`StorageAvailability`] which checks storage health with `probe`.
+    pub fn new<P, F>(probe: P) -> Self
+    where
+        P: Fn() -> F + Send + Sync + 'static,
+        F: Future<Output = color_eyre::Result<()>> + Send + 'static,
+    {
+        Self {
+            available: AtomicBool::new(false),
+            probe: Box::new(move || Box::pin(probe())),
+        }
+    }
+
+    /// Check if storage is available.
+    #[must_use]
+    pub fn is_available(&self) -> bool {
+        self.available.load(Ordering::Acquire)
+    }
+
+    /// Mark storage as available or unavailable.
+    pub fn set_available(&self, available: bool) {
+        self.available.store(available, Ordering::Release);
+    }
+
+    /// Probe storage health once and update availability with the result.
+    ///
+    /// Returns new availability.
+    pub async fn reprobe(&self) -> bool {
+        let available = match (self.probe)().await {
+            Ok(()) => {
+                info!("Password storage is ready after manual retry");
+                true
+            }
+            Err(error) => {
+                warn!(
+                    ?error,
+                    "Password storage is still not ready after manual retry"
+                );
+                false
+            }
+        };
+        self.set_available(available);
+        available
+    }
+}
+
+/// Exponential backoff between health probes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    /// Delay after the first failed probe.
+    pub initial: Duration,
+    /// Maximum delay between probes.
+    pub max: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Outcome of [`wait_until_ready()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Readiness {
+    /// Storage responded that it's ready.
+    Ready,
+    /// Storage didn't become ready during the time budget.
+    TimedOut,
+}
+
+/// Call `probe` with `backoff` until it succeeds or `budget` is exhausted.
+///
+/// Probe which doesn't complete before the end of the budget is cancelled.
+pub async fn wait_until_ready<P, F>(mut probe: P, budget: Duration, backoff: Backoff) -> Readiness
+where
+    P: FnMut() -> F + Send,
+    F: Future<Output = color_eyre::Result<()>> + Send,
+{
+    let deadline = Instant::now().checked_add(budget);
+    let mut delay = backoff.initial;
+
+    for attempt in 1_u32.. {
+        let result = match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, probe())
+                .await
+                .unwrap_or_else(|_elapsed| Err(color_eyre::eyre::eyre!("Probe timed out"))),
+            None => probe().await,
+        };
+
+        let error = match result {
+            Ok(()) => {
+                info!(attempt, "Password storage is ready");
+                return Readiness::Ready;
+            }
+            Err(error) => error,
+        };
+
+        let retry_in = deadline.map_or(delay, |until| {
+            delay.min(until.saturating_duration_since(Instant::now()))
+        });
+        if retry_in.is_zero() {
+            warn!(attempt, ?error, "Password storage is not ready, giving up");
+            return Readiness::TimedOut;
+        }
+
+        info!(
+            attempt,
+            ?error,
+            ?retry_in,
+            "Password storage is not ready yet"
+        );
+        tokio::time::sleep(retry_in).await;
+        delay = delay.saturating_mul(2).min(backoff.max);
+    }
+
+    Readiness::TimedOut
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    use color_eyre::eyre::eyre;
+
+    use super::*;
+
+    const BUDGET: Duration = Duration::from_secs(30);
+
+    /// Mock health client which becomes ready after `failures` failed probes.
+    #[derive(Debug, Clone)]
+    struct MockHealthClient {
+        failures: u32,
+        attempts: Arc<AtomicU32>,
+    }
+
+    impl MockHealthClient {
+        fn new(failures: u32) -> Self {
+            Self {
+                failures,
+                attempts: Arc::new(AtomicU32::new(0)),
+            }
+        }
+
+        fn attempts(&self) -> u32 {
+            self.attempts.load(Ordering::SeqCst)
+        }
+
+        fn check(self) -> std::future::Ready<color_eyre::Result<()>> {
+            let previous_attempts = self.attempts.fetch_add(1, Ordering::SeqCst);
+            std::future::ready(if previous_attempts < self.failures {
+                Err(eyre!("Not serving"))
+            } else {
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn ready_success() {
+        let client = MockHealthClient::new(0);
+
+        let readiness =
+            wait_until_ready(|| client.clone().check(), BUDGET, Backoff::default()).await;
+
+        assert_eq!(readiness, Readiness::Ready);
+        assert_eq!(client.attempts(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn delayed_ready_success() {
+        let client = MockHealthClient::new(3);
+        let start = Instant::now();
+
+        let readiness =
+            wait_until_ready(|| client.clone().check(), BUDGET, Backoff::default()).await;
+
+        assert_eq!(readiness, Readiness::Ready);
+        assert_eq!(client.attempts(), 4);
+        // 0.5 + 1 + 2 seconds of backoff
+        assert_eq!(start.elapsed(), Duration::from_millis(3500));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn timeout_failure() {
+        let client = MockHealthClient::new(u32::MAX);
+        let start = Instant::now();
+
+        let readiness =
+            wait_until_ready(|| client.clone().check(), BUDGET, Backoff::default()).await;
+
+        assert_eq!(readiness, Readiness::TimedOut);
+        assert_eq!(start.elapsed(), BUDGET);
+        // 0.5 + 1 + 2 + 4 + 8 + 10 + 4.5 seconds of backoff and the last probe at the deadline
+        assert_eq!(client.attempts(), 8);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hanging_probe_timeout_failure() {
+        let start = Instant::now();
+
+        let readiness = wait_until_ready(
+            std::future::pending::<color_eyre::Result<()>>,
+            BUDGET,
+            Backoff::default(),
+        )
+        .await;
+
+        assert_eq!(readiness, Readiness::TimedOut);
+        assert_eq!(start.elapsed(), BUDGET);
+    }
+
+    #[test]
+    fn availability_is_initially_false() {
+        let client = MockHealthClient::new(0);
+        let availability = StorageAvailability::new(move || client.clone().check());
+        assert!(!availability.is_available());
+
+        availability.set_available(true);
+        assert!(availability.is_available());
+    }
+
+    #[tokio::test]
+    async fn reprobe_success() {
+        let client = MockHealthClient::new(1);
+        let probe_client = client.clone();
+        let availability = StorageAvailability::new(move || probe_client.clone().check());
+
+        assert!(!availability.reprobe().await);
+        assert!(!availability.is_available());
+
+        assert!(availability.reprobe().await);
+        assert!(availability.is_available());
+        assert_eq!(client.attempts(), 2);
+    }
+}
@@ -0,0 +1,292 @@
+//! Module with [`TaskRegistry`] owning background tasks of the bot.
+
+use std::{collections::HashMap, future::Future, time::Duration};
+
+use tokio::{
+    sync::watch,
+    task::{Id, JoinError, JoinSet},
+};
+use tracing::{debug, error, info, warn};
+
+/// Default time given to tasks spawned with [`TaskRegistry::spawn_graceful()`] to finish on
+/// shutdown.
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Signal telling a task spawned with [`TaskRegistry::spawn_graceful()`] to finish.
+#[derive(Debug, Clone)]
+pub struct ShutdownSignal(watch::Receiver<bool>);
+
+impl ShutdownSignal {
+    /// Wait until shutdown is requested.
+    ///
+    /// Also completes if the [`TaskRegistry`] is dropped.
+    pub async fn recv(mut self) {
+        let _dropped_registry_means_shutdown = self.0.wait_for(|&requested| requested).await;
+    }
+}
+
+/// Registry owning background tasks, so that none of them outlives the bot unnoticed.
+///
+/// Every task has a name used in logs. Finished, failed and panicked tasks are logged by
+/// [`TaskRegistry::run_until()`], remaining tasks are cancelled on shutdown.
+#[derive(Debug)]
+pub struct TaskRegistry {
+    /// Running tasks.
+    tasks: JoinSet<()>,
+    /// Names of the running tasks.
+    names: HashMap<Id, &'static str>,
+    /// Sender of the [`ShutdownSignal`].
+    shutdown: watch::Sender<bool>,
+    /// Time given to graceful tasks to finish on shutdown.
+    grace_period: Duration,
+}
+
+impl Default for TaskRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_GRACE_PERIOD)
+    }
+}
+
+impl TaskRegistry {
+    /// Construct new [`TaskRegistry`] giving graceful tasks `grace_period` to finish on shutdown.
+    #[must_use]
+    pub fn new(grace_period: Duration) -> Self {
+        Self {
+            tasks: JoinSet::new(),
+            names: HashMap::new(),
+            shutdown: watch::Sender::new(false),
+            grace_period,
+        }
+    }
+
+    /// Spawn `task` with `name`, which is cancelled right away on shutdown.
+    #[expect(
+        clippy::integer_division_remainder_used,
+        clippy::pattern_type_mismatch,
+        reason = "generated by `tokio::select!`"
+    )]
+    pub fn spawn<F>(&mut self, name: &'static str, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let mut shutdown = self.shutdown.subscribe();
+        self.spawn_named(name, async move {
+            tokio::select! {
+                () = task => {}
+                _dropped_registry_means_shutdown = shutdown.wait_for(|&requested| requested) => {}
+            }
+        });
+    }
+
+    /// Spawn task with `name`, which finishes by itself after receiving [`ShutdownSignal`].
+    ///
+    /// Task is aborted if it doesn't finish during the grace period.
+    pub fn spawn_graceful<F, Fut>(&mut self, name: &'static str, task: F)
+    where
+        F: FnOnce(ShutdownSignal) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let task = task(ShutdownSignal(self.shutdown.subscribe()));
+        self.spawn_named(name, task);
+    }
+
+    /// Spawn `task` remembering its `name`.
+    fn spawn_named<F>(&mut self, name: &'static str, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let id = self.tasks.spawn(task).id();
+        self.names.insert(id, name);
+        debug!(task = name, "Spawned background task");
+    }
+
+    /// Get number of running tasks.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Check if there are no running tasks.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Run `main` future logging finished tasks meanwhile, then [`shutdown`](Self::shutdown)
+    /// all remaining tasks.
+    #[expect(
+        clippy::integer_division_remainder_used,
+        clippy::pattern_type_mismatch,
+        reason = "generated by `tokio::select!`"
+    )]
+    pub async fn run_until<F>(mut self, main: F) -> F::Output
+    where
+        F: Future + Send,
+        F::Output: Send,
+    {
+        let mut main = std::pin::pin!(main);
+        loop {
+            tokio::select! {
+                output = &mut main => {
+                    self.shutdown().await;
+                    return output;
+                }
+                Some(joined) = self.tasks.join_next_with_id() => self.log_joined(joined),
+            }
+        }
+    }
+
+    /// Send [`ShutdownSignal`] and wait for all tasks to finish.
+    ///
+    /// Tasks which are still running after the grace period are aborted.
+    pub async fn shutdown(&mut self) {
+        if self.tasks.is_empty() {
+            return;
+        }
+
+        info!(tasks = self.tasks.len(), "Shutting down background tasks");
+        self.shutdown.send_replace(true);
+
+        let grace_period = self.grace_period;
+        let joined_in_time = tokio::time::timeout(grace_period, async {
+            while let Some(joined) = self.tasks.join_next_with_id().await {
+                self.log_joined(joined);
+            }
+        })
+        .await;
+
+        if joined_in_time.is_err() {
+            warn!(
+                ?grace_period,
+                tasks = ?self.names.values().collect::<Vec<_>>(),
+                "Background tasks didn't finish in time, aborting them"
+            );
+            self.tasks.abort_all();
+            while let Some(joined) = self.tasks.join_next_with_id().await {
+                self.log_joined(joined);
+            }
+        }
+    }
+
+    /// Log result of the finished task and forget its name.
+    fn log_joined(&mut self, joined: Result<(Id, ()), JoinError>) {
+        let id = joined.as_ref().map_or_else(JoinError::id, |&(id, ())| id);
+        let name = self.names.remove(&id).unwrap_or("<unknown>");
+
+        match joined {
+            Ok((_id, ())) => info!(task = name, "Background task finished"),
+            Err(error) if error.is_cancelled() => {
+                info!(task = name, "Background task cancelled");
+            }
+            Err(error) => {
+                let reason = panic_message(error);
+                error!(task = name, %reason, "Background task panicked");
+            }
+        }
+    }
+}
+
+/// Extract message from the panicked task `error`.
+fn panic_message(error: JoinError) -> String {
+    match error.try_into_panic() {
+        Ok(payload) => payload
+            .downcast_ref::<&str>()
+            .map(|&message| message.to_owned())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "<non-string panic payload>".to_owned()),
+        Err(error) => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::panic, clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use super::*;
+
+    /// Guard setting the flag when dropped together with the task owning it.
+    struct DropGuard(Arc<AtomicBool>);
+
+    impl Drop for DropGuard {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn plain_task_is_cancelled_on_shutdown() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let mut registry = TaskRegistry::default();
+        let guard = DropGuard(Arc::clone(&dropped));
+        registry.spawn("pending", async move {
+            let _guard = guard;
+            std::future::pending::<()>().await;
+        });
+        assert_eq!(registry.len(), 1);
+
+        assert_eq!(registry.run_until(async { 42_i32 }).await, 42_i32);
+
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn graceful_task_receives_shutdown_signal() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let mut registry = TaskRegistry::default();
+        let task_finished = Arc::clone(&finished);
+        registry.spawn_graceful("graceful", |shutdown| async move {
+            shutdown.recv().await;
+            task_finished.store(true, Ordering::SeqCst);
+        });
+
+        registry.run_until(async {}).await;
+
+        assert!(finished.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn graceful_task_is_aborted_after_grace_period() {
+        let mut registry = TaskRegistry::new(Duration::from_secs(1));
+        registry.spawn_graceful("stubborn", |_shutdown| std::future::pending());
+
+        registry.shutdown().await;
+
+        assert!(registry.is_empty());
+        assert!(registry.names.is_empty());
+    }
+
+    #[tokio::test]
+    async fn panic_is_captured() {
+        let mut registry = TaskRegistry::default();
+        registry.spawn("panicking", async { panic!("boom") });
+        registry.spawn("pending", std::future::pending());
+
+        let Some(Err(error)) = registry.tasks.join_next_with_id().await else {
+            unreachable!("only panicking task can finish");
+        };
+        let id = error.id();
+        assert!(error.is_panic());
+        assert_eq!(registry.names.get(&id), Some(&"panicking"));
+
+        registry.log_joined(Err(error));
+        assert!(!registry.names.contains_key(&id));
+
+        registry.shutdown().await;
+        assert!(registry.is_empty());
+        assert!(registry.names.is_empty());
+    }
+
+    #[tokio::test]
+    async fn panic_message_is_extracted() {
+        let error = tokio::spawn(async { panic!("boom {}", 42_i32) })
+            .await
+            .unwrap_err();
+
+        assert_eq!(panic_message(error), "boom 42");
+    }
+}
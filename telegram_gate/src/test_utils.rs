@@ -1,29 +1,53 @@
 //! Test utilities.
+//!
+//! [`mock_bot`] is also available outside of the crate with `test-doubles` feature,
+//! while state checks are used only by the unit tests.
+//! `simulation` is available only outside of the crate, as unit tests mock the context.
 
-#![cfg(test)]
-#![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+#![expect(
+    clippy::unwrap_used,
+    clippy::missing_panics_doc,
+    reason = "it's ok in tests"
+)]
 
+#[cfg(test)]
+use std::sync::Arc;
+
+#[cfg(test)]
 use mock_bot::{MockBotBuilder, CHAT_ID};
+#[cfg(test)]
 use teloxide::utils::command::BotCommands as _;
 use url::Url;
 
+#[cfg(test)]
 #[mockall_double::double]
 use crate::context::Context;
+#[cfg(test)]
 use crate::{
     button::ButtonBox,
     command::Command,
     message::MessageBox,
+    release_notes::{self, SeenVersions},
     state::*,
+    time_zone::{TimeZones, Tz},
     transition::{TransitionFailureReason, TryFromTransition as _},
 };
 
+#[cfg(test)]
+pub mod markdown;
 pub mod mock_bot;
+#[cfg(all(feature = "test-doubles", not(test)))]
+pub mod simulation;
 
 /// Test that [`Command::Help`] is handled correctly for `state`.
+#[cfg(test)]
 pub async fn test_help_success(state: State) {
     let help = Command::Help(crate::command::Help);
 
     let mut mock_context = Context::default();
+    mock_context
+        .expect_message_footer()
+        .return_const(crate::footer::MessageFooter::default());
     mock_context.expect_chat_id().return_const(CHAT_ID);
     mock_context.expect_bot().return_const(
         MockBotBuilder::new()
@@ -39,7 +63,100 @@ pub async fn test_help_success(state: State) {
     assert_eq!(state, new_state);
 }
 
+/// Test that [`Command::WhatsNew`] is handled correctly for `state`.
+#[cfg(test)]
+pub async fn test_whats_new_success(state: State) {
+    let seen_versions = Arc::new(SeenVersions::default());
+
+    let mut mock_context = Context::default();
+    mock_context
+        .expect_message_footer()
+        .return_const(crate::footer::MessageFooter::default());
+    mock_context.expect_chat_id().return_const(CHAT_ID);
+    mock_context
+        .expect_seen_versions()
+        .return_const(Some(Arc::clone(&seen_versions)));
+    mock_context.expect_bot().return_const(
+        MockBotBuilder::new()
+            .expect_send_message(release_notes::format(release_notes::RELEASE_NOTES))
+            .expect_into_future()
+            .build(),
+    );
+
+    let new_state = State::try_from_transition(state.clone(), Command::whats_new(), &mock_context)
+        .await
+        .unwrap();
+
+    assert_eq!(state, new_state);
+    assert_eq!(
+        seen_versions.last_seen(CHAT_ID),
+        release_notes::latest_version()
+    );
+}
+
+/// Test that [`Command::TimeZone`] is handled correctly for `state`.
+#[cfg(test)]
+#[expect(clippy::non_ascii_literal, reason = "messages may contain emojis")]
+pub async fn test_time_zone_success(state: State) {
+    let time_zones = Arc::new(TimeZones::default());
+
+    let mut mock_context = Context::default();
+    mock_context
+        .expect_message_footer()
+        .return_const(crate::footer::MessageFooter::default());
+    mock_context.expect_chat_id().return_const(CHAT_ID);
+    mock_context
+        .expect_time_zones()
+        .return_const(Some(Arc::clone(&time_zones)));
+    mock_context.expect_bot().return_const(
+        MockBotBuilder::new()
+            .expect_send_message("✅ Times are now shown in Europe/Berlin.".to_owned())
+            .expect_into_future()
+            .build(),
+    );
+
+    let new_state = State::try_from_transition(
+        state.clone(),
+        Command::time_zone("Europe/Berlin"),
+        &mock_context,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(state, new_state);
+    assert_eq!(time_zones.get(CHAT_ID), Some(Tz::Europe__Berlin));
+}
+
+/// Test that [`Command::Add`] leads from `state` to the main menu with its keyboard.
+#[cfg(test)]
+pub async fn test_add_success(state: State) {
+    let add = Command::add();
+
+    let mut mock_context = Context::default();
+    mock_context
+        .expect_message_footer()
+        .return_const(crate::footer::MessageFooter::default());
+    mock_context.expect_chat_id().return_const(CHAT_ID);
+    mock_context
+        .expect_web_app_url()
+        .return_const(web_app_test_url());
+    mock_context.expect_bot().return_const(
+        MockBotBuilder::new()
+            .expect_send_message("Press the button below to add a new password.".to_owned())
+            .expect_reply_markup(main_menu_keyboard())
+            .expect_into_future()
+            .build(),
+    );
+
+    let new_state = State::try_from_transition(state, add, &mock_context)
+        .await
+        .unwrap();
+
+    assert_eq!(new_state, State::main_menu());
+}
+
 /// Test that `cmd` is not available for `state`.
+#[cfg(test)]
 pub async fn test_unavailable_command(state: State, cmd: Command) {
     let mock_context = Context::default();
 
@@ -54,6 +171,7 @@ pub async fn test_unavailable_command(state: State, cmd: Command) {
 }
 
 /// Test that `msg` is not expected for `state`.
+#[cfg(test)]
 pub async fn test_unexpected_message(state: State, msg: MessageBox) {
     let mock_context = Context::default();
 
@@ -68,6 +186,7 @@ pub async fn test_unexpected_message(state: State, msg: MessageBox) {
 }
 
 /// Test that `btn` is not expected for `state`.
+#[cfg(test)]
 pub async fn test_unexpected_button(state: State, btn: ButtonBox) {
     let mock_context = Context::default();
 
@@ -81,7 +200,56 @@ pub async fn test_unexpected_button(state: State, btn: ButtonBox) {
     assert_eq!(err.target, state)
 }
 
+/// Serialize `data` sent by the Web App opened by the owner of the test chat.
+#[cfg(test)]
+#[must_use]
+pub fn web_app_data<T: serde::Serialize>(data: T) -> String {
+    serde_json::to_string(&telepass_data_model::WebAppMessage {
+        user_id: CHAT_ID.0.try_into().unwrap(),
+        data,
+    })
+    .unwrap()
+}
+
 /// Construct test Web App URL.
+#[must_use]
 pub fn web_app_test_url() -> Url {
     Url::parse("http://localhost:8081").unwrap()
 }
+
+/// Construct keyboard of the main menu with [`web_app_test_url()`].
+#[cfg(test)]
+#[must_use]
+pub fn main_menu_keyboard() -> teloxide::types::KeyboardMarkup {
+    use teloxide::types::{ButtonRequest, KeyboardButton, KeyboardMarkup, WebAppInfo};
+
+    KeyboardMarkup::new([
+        [KeyboardButton::new(crate::message::kind::List.to_string())],
+        [
+            KeyboardButton::new(crate::message::kind::Add.to_string()).request(
+                ButtonRequest::WebApp(WebAppInfo {
+                    url: web_app_test_url().join("/submit").unwrap(),
+                }),
+            ),
+        ],
+    ])
+    .resize_keyboard()
+}
+
+/// Construct storage availability with `available` state, which probe succeeds if `serving`.
+#[cfg(test)]
+#[must_use]
+pub fn storage_availability(
+    available: bool,
+    serving: bool,
+) -> crate::storage_health::StorageAvailability {
+    let availability = crate::storage_health::StorageAvailability::new(move || {
+        std::future::ready(if serving {
+            Ok(())
+        } else {
+            Err(color_eyre::eyre::eyre!("Not serving"))
+        })
+    });
+    availability.set_available(available);
+    availability
+}
@@ -0,0 +1,115 @@
+//! Validation of messages formatted in `MarkdownV2`.
+
+/// Characters which must be escaped outside of code entities.
+const SPECIAL_CHARS: [char; 18] = [
+    '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!',
+];
+
+/// Formatting entity of `MarkdownV2` opened and closed by the same marker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Entity {
+    /// `*bold*`.
+    Bold,
+    /// `_italic_`.
+    Italic,
+    /// `__underline__`.
+    Underline,
+    /// `~strikethrough~`.
+    Strikethrough,
+    /// `||spoiler||`.
+    Spoiler,
+}
+
+/// Validate that `text` is a valid `MarkdownV2` message.
+///
+/// Supports the subset used by the bot: escaped characters, entities opened and closed by
+/// the same marker and inline code. Links, block quotations and pre blocks are rejected.
+///
+/// # Errors
+///
+/// Returns description of the first violation:
+/// - Special character isn't escaped outside of code;
+/// - Escaped character is not an ASCII one or is not `` ` `` or `\` inside code;
+/// - Entities are not balanced or closed not in the reverse order;
+/// - Code is empty or not closed.
+pub fn validate(text: &str) -> Result<(), String> {
+    let mut entities = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let entity = match c {
+            '\\' => match chars.next() {
+                Some('\u{1}'..='\u{7e}') => continue,
+                other => return Err(format!("Invalid escape of {other:?}")),
+            },
+            '`' => {
+                validate_code(&mut chars)?;
+                continue;
+            }
+            '*' => Entity::Bold,
+            '_' if chars.next_if_eq(&'_').is_some() => Entity::Underline,
+            '_' => Entity::Italic,
+            '~' => Entity::Strikethrough,
+            '|' if chars.next_if_eq(&'|').is_some() => Entity::Spoiler,
+            special if SPECIAL_CHARS.contains(&special) => {
+                return Err(format!("Unescaped {special:?}"))
+            }
+            _ => continue,
+        };
+
+        if entities.last() == Some(&entity) {
+            entities.pop();
+        } else if entities.contains(&entity) {
+            return Err(format!("{entity:?} is closed before inner entities"));
+        } else {
+            entities.push(entity);
+        }
+    }
+
+    entities
+        .last()
+        .map_or(Ok(()), |entity| Err(format!("{entity:?} is not closed")))
+}
+
+/// Validate inline code up to and including its closing `` ` ``.
+fn validate_code(chars: &mut impl Iterator<Item = char>) -> Result<(), String> {
+    let mut is_empty = true;
+    while let Some(c) = chars.next() {
+        match c {
+            '`' if is_empty => return Err("Empty code".to_owned()),
+            '`' => return Ok(()),
+            '\\' => match chars.next() {
+                Some('`' | '\\') => {}
+                other => return Err(format!("Invalid escape of {other:?} in code")),
+            },
+            _ => {}
+        }
+        is_empty = false;
+    }
+    Err("Code is not closed".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validator_accepts_entities_and_code() {
+        validate("*bold _italic_* __underline__ ~strike~ ||spoiler|| `co\\`de` \\.").unwrap();
+    }
+
+    #[test]
+    fn validator_rejects_violations() {
+        for text in [
+            "test.resource.com",
+            "*test\\*",
+            "*bold _italic*_",
+            "`code",
+            "``",
+            "`\\a`",
+            "[link](https://example.com)",
+            "trailing\\",
+        ] {
+            validate(text).unwrap_err();
+        }
+    }
+}
@@ -42,6 +42,14 @@ mock! {
         ) -> MockEditMessageReplyMarkup
         where
             C: Into<teloxide::types::Recipient> + 'static;
+
+        pub fn pin_chat_message<C>(
+            &self,
+            chat_id: C,
+            message_id: teloxide::types::MessageId
+        ) -> MockPinChatMessage
+        where
+            C: Into<teloxide::types::Recipient> + 'static;
     }
 }
 
@@ -52,6 +60,10 @@ mock! {
             T: Into<teloxide::types::ReplyMarkup> + 'static;
 
         pub fn parse_mode(self, value: teloxide::types::ParseMode) -> Self;
+
+        pub fn entities(self, value: Vec<teloxide::types::MessageEntity>) -> Self;
+
+        pub fn protect_content(self, value: bool) -> Self;
     }
 
     impl IntoFuture for SendMessage {
@@ -95,7 +107,7 @@ mock! {
 
 pub type MockError = std::convert::Infallible;
 
-pub type MockMessageFuture = Ready<Result<MockMessage, MockError>>;
+pub type MockMessageFuture = Ready<Result<MockMessage, teloxide::RequestError>>;
 
 mock! {
     #[derive(Debug)]
@@ -126,6 +138,18 @@ impl IntoFuture for MockDeleteMessage {
     }
 }
 
+#[derive(Default)]
+pub struct MockPinChatMessage;
+
+impl IntoFuture for MockPinChatMessage {
+    type IntoFuture = Ready<Result<(), std::convert::Infallible>>;
+    type Output = <Self::IntoFuture as Future>::Output;
+
+    fn into_future(self) -> Self::IntoFuture {
+        ready(Ok(()))
+    }
+}
+
 mock! {
     pub EditMessageText {
         pub fn parse_mode(self, value: teloxide::types::ParseMode) -> Self;
@@ -134,7 +158,7 @@ mock! {
     impl IntoFuture for EditMessageText {
         type Output = <<MockEditMessageText as IntoFuture>::IntoFuture as Future>::Output;
 
-        type IntoFuture = Ready<Result<MockMessage, std::convert::Infallible>>;
+        type IntoFuture = Ready<Result<MockMessage, teloxide::RequestError>>;
 
         fn into_future(self) -> <MockEditMessageText as IntoFuture>::IntoFuture;
     }
@@ -156,6 +180,7 @@ mock! {
     }
 }
 
+/// Builders to conveniently set up expectations of [`MockBot`].
 mod builder {
     use mockall::predicate::eq;
 
@@ -163,6 +188,7 @@ mod builder {
 
     #[derive(Default)]
     pub struct MockBotBuilder {
+        /// Bot with all expectations set so far.
         mock_bot: MockBot,
     }
 
@@ -230,6 +256,14 @@ mod builder {
             self
         }
 
+        #[must_use]
+        pub fn expect_pin_chat_message(mut self, message_id: teloxide::types::MessageId) -> Self {
+            self.mock_bot
+                .expect_pin_chat_message()
+                .with(eq(CHAT_ID), eq(message_id));
+            self
+        }
+
         #[must_use]
         pub fn build(self) -> MockBot {
             self.mock_bot
@@ -241,8 +275,11 @@ mod builder {
         T: Into<String> + PartialEq + std::fmt::Debug + Send + Sync + 'static,
         M: Into<teloxide::types::ReplyMarkup> + PartialEq + std::fmt::Debug + Send + Sync + 'static,
     {
+        /// Builder to return to after the message is built.
         mock_bot_builder: MockBotBuilder,
+        /// Expected message text.
         message: T,
+        /// Expectations of the message setters in the order they should be called.
         expectations: Vec<MockSendMessageExpectation<M>>,
     }
 
@@ -265,6 +302,7 @@ mod builder {
             }
         }
 
+        /// Add expectation of the next setter call.
         #[must_use]
         fn add_expectation(mut self, expectation: MockSendMessageExpectation<M>) -> Self {
             self.expectations.push(expectation);
@@ -276,12 +314,35 @@ mod builder {
             self.add_expectation(MockSendMessageExpectation::ParseMode(parse_mode))
         }
 
+        #[must_use]
+        pub fn expect_entities(self, entities: Vec<teloxide::types::MessageEntity>) -> Self {
+            self.add_expectation(MockSendMessageExpectation::Entities(entities))
+        }
+
+        #[must_use]
+        pub fn expect_protect_content(self, protect_content: bool) -> Self {
+            self.add_expectation(MockSendMessageExpectation::ProtectContent(protect_content))
+        }
+
         #[must_use]
         pub fn expect_into_future(self) -> MockBotBuilder {
             let mut mock_send_message_into_future = MockSendMessage::default();
             mock_send_message_into_future
                 .expect_into_future()
-                .return_const(ready(Ok(MockMessage::default())));
+                .return_once(|| ready(Ok(MockMessage::default())));
+
+            self.build(mock_send_message_into_future)
+        }
+
+        #[must_use]
+        pub fn expect_into_future_with_error(
+            self,
+            error: teloxide::RequestError,
+        ) -> MockBotBuilder {
+            let mut mock_send_message_into_future = MockSendMessage::default();
+            mock_send_message_into_future
+                .expect_into_future()
+                .return_once(|| ready(Err(error)));
 
             self.build(mock_send_message_into_future)
         }
@@ -303,6 +364,8 @@ mod builder {
             self.build(mock_send_message_into_future)
         }
 
+        /// Set up the message to be returned by the bot wrapping `inner_mock_send_message` into
+        /// setter expectations.
         fn build(mut self, inner_mock_send_message: MockSendMessage) -> MockBotBuilder {
             let mock_send_message = self.expectations.into_iter().rev().fold(
                 inner_mock_send_message,
@@ -321,6 +384,18 @@ mod builder {
                                 .with(eq(reply_markup))
                                 .return_once(move |_reply_markup| mock_send_message);
                         }
+                        MockSendMessageExpectation::Entities(entities) => {
+                            new_mock_send_message
+                                .expect_entities()
+                                .with(eq(entities))
+                                .return_once(move |_entities| mock_send_message);
+                        }
+                        MockSendMessageExpectation::ProtectContent(protect_content) => {
+                            new_mock_send_message
+                                .expect_protect_content()
+                                .with(eq(protect_content))
+                                .return_once(move |_protect_content| mock_send_message);
+                        }
                     }
                     new_mock_send_message
                 },
@@ -368,12 +443,15 @@ mod builder {
     {
         ParseMode(teloxide::types::ParseMode),
         ReplyMarkup(M),
+        Entities(Vec<teloxide::types::MessageEntity>),
+        ProtectContent(bool),
     }
 
     impl<M> MockSendMessageExpectation<M>
     where
         M: Into<teloxide::types::ReplyMarkup> + PartialEq + std::fmt::Debug + Send + Sync,
     {
+        /// Transform expectation to use another reply markup type.
         #[must_use]
         fn transform<M2>(self) -> MockSendMessageExpectation<M2>
         where
@@ -381,6 +459,10 @@ mod builder {
         {
             match self {
                 Self::ParseMode(parse_mode) => MockSendMessageExpectation::ParseMode(parse_mode),
+                Self::Entities(entities) => MockSendMessageExpectation::Entities(entities),
+                Self::ProtectContent(protect_content) => {
+                    MockSendMessageExpectation::ProtectContent(protect_content)
+                }
                 Self::ReplyMarkup(_reply_markup) => {
                     unreachable!(
                         "Transforming one reply markup to another is not \
@@ -395,15 +477,20 @@ mod builder {
     where
         T: Into<String> + PartialEq + std::fmt::Debug + Send + Sync + 'static,
     {
+        /// Builder to return to after the message is built.
         mock_bot_builder: MockBotBuilder,
+        /// Id of the message to edit.
         message_id: teloxide::types::MessageId,
+        /// Expected new message text.
         message: T,
+        /// Expectations of the message setters in the order they should be called.
         expectations: Vec<MockEditMessageTextExpectation>,
     }
 
     impl<T: Into<String> + PartialEq + std::fmt::Debug + Send + Sync + 'static>
         MockEditMessageTextBuilder<T>
     {
+        /// Create new builder.
         const fn new(
             mock_bot_builder: MockBotBuilder,
             message_id: teloxide::types::MessageId,
@@ -429,11 +516,26 @@ mod builder {
             let mut mock_edit_message_text_into_future = MockEditMessageText::default();
             mock_edit_message_text_into_future
                 .expect_into_future()
-                .return_const(ready(Ok(MockMessage::default())));
+                .return_once(|| ready(Ok(MockMessage::default())));
 
             self.build(mock_edit_message_text_into_future)
         }
 
+        #[must_use]
+        pub fn expect_into_future_with_error(
+            self,
+            error: teloxide::RequestError,
+        ) -> MockBotBuilder {
+            let mut mock_edit_message_text_into_future = MockEditMessageText::default();
+            mock_edit_message_text_into_future
+                .expect_into_future()
+                .return_once(|| ready(Err(error)));
+
+            self.build(mock_edit_message_text_into_future)
+        }
+
+        /// Set up the message to be returned by the bot wrapping `inner_mock_edit_message_text`
+        /// into setter expectations.
         fn build(mut self, inner_mock_edit_message_text: MockEditMessageText) -> MockBotBuilder {
             let mock_edit_message_text = self.expectations.into_iter().rev().fold(
                 inner_mock_edit_message_text,
@@ -469,12 +571,16 @@ mod builder {
     where
         M: Into<teloxide::types::ReplyMarkup> + PartialEq + std::fmt::Debug + Send + Sync,
     {
+        /// Builder to return to after the message is built.
         mock_bot_builder: MockBotBuilder,
+        /// Id of the message to edit.
         message_id: teloxide::types::MessageId,
+        /// Expected new reply markup.
         reply_markup: Option<M>,
     }
 
     impl MockEditMessageReplyMarkupBuilder<NoReplyMarkup> {
+        /// Create new builder.
         const fn new(
             mock_bot_builder: MockBotBuilder,
             message_id: teloxide::types::MessageId,
@@ -545,6 +651,7 @@ mod builder {
         }
     }
 
+    #[cfg(test)]
     mod tests {
         use tokio::test;
 
@@ -619,6 +726,24 @@ mod builder {
                     .unwrap();
             }
 
+            #[test]
+            async fn entities_and_protect_content_success() {
+                let spoiler = teloxide::types::MessageEntity::spoiler(0, 4);
+                let mock_bot = MockBotBuilder::new()
+                    .expect_send_message("Test Message")
+                    .expect_entities(vec![spoiler.clone()])
+                    .expect_protect_content(true)
+                    .expect_into_future()
+                    .build();
+
+                mock_bot
+                    .send_message(CHAT_ID, "Test Message")
+                    .entities(vec![spoiler])
+                    .protect_content(true)
+                    .await
+                    .unwrap();
+            }
+
             #[test]
             #[should_panic(
                 expected = "MockSendMessage::parse_mode(Html): No matching expectation found"
@@ -0,0 +1,318 @@
+//! Module to replay scripted updates through the bot states without Telegram and password storage.
+//!
+//! Updates are handled the same way as by the executable, but the bot talks to a fake Telegram
+//! Bot API served by [`Recorder`] and the password storage is replaced with [`StorageStub`],
+//! both running in-process. Used by the `simulate` binary to reproduce bugs without clicking
+//! through Telegram.
+//!
+//! Script is a YAML document like this:
+//!
+//! ```yaml
+//! # Resources stored before the first update
+//! records:
+//!   - github.com
+//! updates:
+//!   - command: /start
+//!   - message: 🗒 List
+//!   - message: github.com
+//!   - button: 🗑 Delete
+//!   - web_app:
+//!       data: '{"user_id": 0, "data": {}}'
+//!       button_text: Find
+//! ```
+
+use std::{fmt, sync::Arc};
+
+use color_eyre::eyre::{eyre, WrapErr as _};
+use serde::Deserialize;
+use teloxide::{types::MessageId, utils::command::BotCommands as _};
+
+pub use self::{
+    recording_bot::{BotCall, EditedText, Recorder, SentMessage},
+    storage_stub::StorageStub,
+};
+use super::{mock_bot::CHAT_ID, web_app_test_url};
+use crate::{
+    button::ButtonBox,
+    command,
+    context::Context,
+    footer::MessageFooter,
+    handler::{self, CommandOrMessage},
+    keyboard::ResourcePrefix,
+    message,
+    role::Role,
+    state::State,
+    storage_health::StorageAvailability,
+};
+
+mod recording_bot;
+mod storage_stub;
+
+/// Name of the simulated bot to parse commands addressed to it.
+const BOT_NAME: &str = "telepass_simulation_bot";
+/// Id of the simulated bot user.
+const BOT_ID: u64 = 1;
+
+/// Script of updates to replay.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Script {
+    /// Names of resources stored before the first update.
+    #[serde(default)]
+    pub records: Vec<String>,
+    /// Updates sent by the user in order.
+    #[serde(with = "serde_yaml::with::singleton_map_recursive")]
+    pub updates: Vec<Update>,
+}
+
+impl Script {
+    /// Parse script from `yaml`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `yaml` is not a valid script.
+    pub fn from_yaml(yaml: &str) -> color_eyre::Result<Self> {
+        serde_yaml::from_str(yaml).wrap_err("Failed to parse simulation script")
+    }
+}
+
+/// Update sent by the user.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Update {
+    /// Command, e.g. `/start`.
+    Command(String),
+    /// Text message, e.g. a resource name.
+    Message(String),
+    /// Data sent by the Web App opened with the keyboard button.
+    WebApp {
+        /// Data sent by the Web App.
+        data: String,
+        /// Text of the button the Web App was opened with.
+        button_text: String,
+    },
+    /// Press of the inline button with data.
+    Button(String),
+}
+
+impl fmt::Display for Update {
+    #[expect(
+        clippy::ref_patterns,
+        clippy::use_debug,
+        reason = "conflicts with `pattern_type_mismatch`, texts are quoted"
+    )]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Command(ref command) => write!(f, "command {command:?}"),
+            Self::Message(ref text) => write!(f, "message {text:?}"),
+            Self::WebApp {
+                ref data,
+                ref button_text,
+            } => write!(f, "web_app {data:?} from {button_text:?}"),
+            Self::Button(ref data) => write!(f, "button {data:?}"),
+        }
+    }
+}
+
+/// Update parsed the same way as the executable does.
+enum Input {
+    /// Command or message.
+    CommandOrMessage(CommandOrMessage),
+    /// Pressed button.
+    Button(ButtonBox),
+}
+
+impl Input {
+    /// Parse `update` assigning message ids with `recorder`.
+    #[expect(
+        clippy::ref_patterns,
+        reason = "conflicts with `pattern_type_mismatch`"
+    )]
+    fn parse(update: &Update, recorder: &Recorder) -> color_eyre::Result<Self> {
+        match *update {
+            Update::Command(ref text) => command::Command::parse(text, BOT_NAME)
+                .map(|command| Self::CommandOrMessage(CommandOrMessage::Command(command)))
+                .wrap_err_with(|| format!("Failed to parse command `{text}`")),
+            Update::Message(ref text) => {
+                Self::message(serde_json::json!({ "text": text }), recorder)
+            }
+            Update::WebApp {
+                ref data,
+                ref button_text,
+            } => Self::message(
+                serde_json::json!({
+                    "web_app_data": { "data": data, "button_text": button_text }
+                }),
+                recorder,
+            ),
+            // Message with the button is not tracked, as the bot finds its messages by itself
+            Update::Button(ref data) => ButtonBox::new(
+                telegram_message(message_json(MessageId(0), true, ""))?,
+                data,
+            )
+            .map(Self::Button)
+            .wrap_err_with(|| format!("Unexpected button data `{data}`")),
+        }
+    }
+
+    /// Parse message from the user with specific `fields` of the Telegram message.
+    fn message(fields: serde_json::Value, recorder: &Recorder) -> color_eyre::Result<Self> {
+        let mut json = message_json(recorder.next_message_id(), false, "");
+        if let (Some(message), serde_json::Value::Object(fields)) = (json.as_object_mut(), fields) {
+            message.remove("text");
+            message.extend(fields);
+        }
+
+        message::MessageBox::new(telegram_message(json)?)
+            .map(|message| Self::CommandOrMessage(CommandOrMessage::Message(message)))
+            .ok_or_else(|| eyre!("Unsupported message"))
+    }
+}
+
+/// Construct JSON of the text message with `message_id` in the simulated chat.
+///
+/// Message is sent by the bot if `from_bot` is `true` and by the user otherwise.
+fn message_json(message_id: MessageId, from_bot: bool, text: &str) -> serde_json::Value {
+    let from = if from_bot {
+        serde_json::json!({ "id": BOT_ID, "is_bot": true, "first_name": "Telepass", "username": BOT_NAME })
+    } else {
+        serde_json::json!({ "id": CHAT_ID.0, "is_bot": false, "first_name": "Simulation" })
+    };
+    serde_json::json!({
+        "message_id": message_id.0,
+        "date": 0_i64,
+        "chat": { "id": CHAT_ID.0, "type": "private", "first_name": "Simulation" },
+        "from": from,
+        "text": text,
+    })
+}
+
+/// Parse Telegram message from `json`.
+fn telegram_message(json: serde_json::Value) -> color_eyre::Result<teloxide::types::Message> {
+    serde_json::from_value(json).wrap_err("Failed to construct Telegram message")
+}
+
+/// Outcome of handling a single update.
+#[derive(Debug)]
+pub struct Step {
+    /// Handled update.
+    pub update: Update,
+    /// Calls made by the bot while handling the update.
+    pub bot_calls: Vec<BotCall>,
+    /// Name of the state after handling the update.
+    pub state: &'static str,
+}
+
+/// Outcome of the whole script.
+#[derive(Debug)]
+pub struct Report {
+    /// Outcomes of all updates in order.
+    pub steps: Vec<Step>,
+    /// Names of the resources stored after the last update.
+    pub records: Vec<String>,
+}
+
+impl Report {
+    /// Get name of the state after the last update.
+    ///
+    /// Returns [`None`] if there were no updates.
+    #[must_use]
+    pub fn final_state(&self) -> Option<&'static str> {
+        self.steps.last().map(|step| step.state)
+    }
+}
+
+impl fmt::Display for Report {
+    #[expect(clippy::use_debug, reason = "resource names are quoted")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "> {}", step.update)?;
+            for call in &step.bot_calls {
+                writeln!(f, "  {call}")?;
+            }
+            writeln!(f, "= {}", step.state)?;
+        }
+        writeln!(f, "Stored records: {:?}", self.records)
+    }
+}
+
+/// Replay `script` starting from the [`State::Default`] state.
+///
+/// # Errors
+///
+/// Fails if any update can't be parsed, before handling the first one,
+/// or if fakes of Telegram and password storage can't be served.
+pub async fn run(script: Script) -> color_eyre::Result<Report> {
+    let recorder = Recorder::default();
+    let inputs = script
+        .updates
+        .iter()
+        .enumerate()
+        .map(|(index, update)| {
+            Input::parse(update, &recorder)
+                .wrap_err_with(|| format!("Failed to parse update #{}", index.saturating_add(1)))
+        })
+        .collect::<color_eyre::Result<Vec<_>>>()?;
+
+    let storage = StorageStub::new(script.records);
+    let (bot, telegram_server) = recorder.serve().await?;
+    let (storage_client, storage_server) = storage.serve().await?;
+    let storage_client = Arc::new(tokio::sync::Mutex::new(storage_client));
+    let web_app_url = Arc::new(web_app_test_url());
+    let resource_prefix = Arc::new(ResourcePrefix::default());
+    let message_footer = Arc::new(MessageFooter::default());
+    let storage_availability = Arc::new(StorageAvailability::new(|| std::future::ready(Ok(()))));
+    storage_availability.set_available(true);
+
+    let mut state = State::default();
+    let mut steps = Vec::with_capacity(inputs.len());
+    for (update, input) in script.updates.into_iter().zip(inputs) {
+        let context = Context::new(
+            bot.clone(),
+            CHAT_ID,
+            Role::Admin,
+            Arc::clone(&web_app_url),
+            Arc::clone(&resource_prefix),
+            Arc::clone(&message_footer),
+            Arc::clone(&storage_client),
+            None,
+            Arc::clone(&storage_availability),
+        );
+
+        // See: https://rust-lang.github.io/rust-clippy/master/index.html#/large_futures
+        state = match input {
+            Input::CommandOrMessage(command_or_message) => {
+                Box::pin(handler::handle_command_or_message(
+                    state,
+                    command_or_message,
+                    &context,
+                ))
+                .await
+            }
+            Input::Button(button) => {
+                Box::pin(handler::handle_button(state, button, &context)).await
+            }
+        };
+
+        steps.push(Step {
+            update,
+            bot_calls: recorder.take_calls(),
+            state: state.name(),
+        });
+    }
+
+    // Messages displayed in the final state are never deleted as the chat ends with the script,
+    // so its drop bomb must not go off
+    #[expect(
+        clippy::mem_forget,
+        reason = "final state is intentionally leaked to defuse its drop bomb"
+    )]
+    std::mem::forget(state);
+    telegram_server.abort();
+    storage_server.abort();
+
+    Ok(Report {
+        steps,
+        records: storage.resource_names(),
+    })
+}
@@ -0,0 +1,369 @@
+//! Module with [`Recorder`] serving fake Telegram Bot API which records calls instead of
+//! performing them.
+
+use std::{
+    fmt,
+    net::Ipv4Addr,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+};
+
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    routing::post,
+    Json, Router,
+};
+use color_eyre::eyre::WrapErr as _;
+use serde::{
+    de::{DeserializeOwned, Error as _},
+    Deserialize, Deserializer,
+};
+use teloxide::types::{MessageId, ParseMode, ReplyMarkup};
+use tokio::task::JoinHandle;
+use url::Url;
+
+use super::message_json;
+
+/// Call of the bot made while handling an update.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BotCall {
+    /// New message sent.
+    SendMessage(SentMessage),
+    /// Text of the message edited.
+    EditMessageText(EditedText),
+    /// Inline keyboard of the message edited.
+    EditMessageReplyMarkup {
+        /// Id of the edited message.
+        message_id: MessageId,
+        /// New inline keyboard. [`None`] if it's removed.
+        reply_markup: Option<ReplyMarkup>,
+    },
+    /// Message deleted.
+    DeleteMessage {
+        /// Id of the deleted message.
+        message_id: MessageId,
+    },
+    /// Message pinned.
+    PinChatMessage {
+        /// Id of the pinned message.
+        message_id: MessageId,
+    },
+}
+
+/// Message sent by the bot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SentMessage {
+    /// Id assigned to the message.
+    pub message_id: MessageId,
+    /// Message text.
+    pub text: String,
+    /// Parse mode of the text. [`None`] for plain text.
+    pub parse_mode: Option<ParseMode>,
+    /// Keyboard attached to the message.
+    pub reply_markup: Option<ReplyMarkup>,
+}
+
+/// New text of the message edited by the bot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditedText {
+    /// Id of the edited message.
+    pub message_id: MessageId,
+    /// New message text.
+    pub text: String,
+    /// Parse mode of the text. [`None`] for plain text.
+    pub parse_mode: Option<ParseMode>,
+}
+
+impl fmt::Display for BotCall {
+    #[expect(
+        clippy::ref_patterns,
+        clippy::use_debug,
+        reason = "conflicts with `pattern_type_mismatch`, texts are quoted"
+    )]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::SendMessage(ref message) => {
+                write!(f, "send_message #{} {:?}", message.message_id, message.text)?;
+                if let Some(parse_mode) = message.parse_mode {
+                    write!(f, " ({parse_mode:?})")?;
+                }
+                if let Some(ref reply_markup) = message.reply_markup {
+                    write!(f, " {}", DisplayReplyMarkup(reply_markup))?;
+                }
+                Ok(())
+            }
+            Self::EditMessageText(ref edited) => {
+                write!(
+                    f,
+                    "edit_message_text #{} {:?}",
+                    edited.message_id, edited.text
+                )?;
+                if let Some(parse_mode) = edited.parse_mode {
+                    write!(f, " ({parse_mode:?})")?;
+                }
+                Ok(())
+            }
+            Self::EditMessageReplyMarkup {
+                message_id,
+                ref reply_markup,
+            } => {
+                write!(f, "edit_message_reply_markup #{message_id}")?;
+                if let Some(ref reply_markup) = *reply_markup {
+                    write!(f, " {}", DisplayReplyMarkup(reply_markup))?;
+                }
+                Ok(())
+            }
+            Self::DeleteMessage { message_id } => write!(f, "delete_message #{message_id}"),
+            Self::PinChatMessage { message_id } => write!(f, "pin_chat_message #{message_id}"),
+        }
+    }
+}
+
+/// Wrapper to display button texts of [`ReplyMarkup`] in rows.
+struct DisplayReplyMarkup<'markup>(&'markup ReplyMarkup);
+
+impl fmt::Display for DisplayReplyMarkup<'_> {
+    #[expect(
+        clippy::ref_patterns,
+        clippy::use_debug,
+        reason = "conflicts with `pattern_type_mismatch`, button texts are quoted"
+    )]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, rows): (_, Vec<Vec<&str>>) = match *self.0 {
+            ReplyMarkup::InlineKeyboard(ref keyboard) => (
+                "inline keyboard",
+                keyboard
+                    .inline_keyboard
+                    .iter()
+                    .map(|row| row.iter().map(|button| button.text.as_str()).collect())
+                    .collect(),
+            ),
+            ReplyMarkup::Keyboard(ref keyboard) => (
+                "keyboard",
+                keyboard
+                    .keyboard
+                    .iter()
+                    .map(|row| row.iter().map(|button| button.text.as_str()).collect())
+                    .collect(),
+            ),
+            ReplyMarkup::KeyboardRemove(_) => return write!(f, "[remove keyboard]"),
+            ReplyMarkup::ForceReply(_) => return write!(f, "[force reply]"),
+        };
+        write!(f, "[{kind}: {rows:?}]")
+    }
+}
+
+/// Body of the `sendMessage` request.
+#[derive(Deserialize)]
+struct SendMessageBody {
+    /// Message text.
+    text: String,
+    /// Parse mode of the text.
+    parse_mode: Option<ParseMode>,
+    /// Keyboard attached to the message.
+    #[serde(default, deserialize_with = "deserialize_reply_markup")]
+    reply_markup: Option<ReplyMarkup>,
+}
+
+/// Body of the `editMessageText` request.
+#[derive(Deserialize)]
+struct EditMessageTextBody {
+    /// Id of the edited message.
+    #[serde(flatten)]
+    message_id: MessageId,
+    /// New message text.
+    text: String,
+    /// Parse mode of the text.
+    parse_mode: Option<ParseMode>,
+}
+
+/// Body of the `editMessageReplyMarkup` request.
+#[derive(Deserialize)]
+struct EditMessageReplyMarkupBody {
+    /// Id of the edited message.
+    #[serde(flatten)]
+    message_id: MessageId,
+    /// New inline keyboard.
+    #[serde(default, deserialize_with = "deserialize_reply_markup")]
+    reply_markup: Option<ReplyMarkup>,
+}
+
+/// Body of the requests only referring to a message, like `deleteMessage`.
+#[derive(Deserialize)]
+struct MessageBody {
+    /// Id of the message.
+    #[serde(flatten)]
+    message_id: MessageId,
+}
+
+/// Recorder of the bot calls shared between all handled updates.
+///
+/// Also assigns ids to messages, so that messages sent by the bot and by the user don't clash.
+#[derive(Debug, Default, Clone)]
+pub struct Recorder {
+    /// Calls recorded since the last [`take_calls()`](Self::take_calls).
+    calls: Arc<Mutex<Vec<BotCall>>>,
+    /// Id of the last message.
+    last_message_id: Arc<AtomicI32>,
+}
+
+impl Recorder {
+    /// Get id for a new message.
+    #[must_use]
+    pub fn next_message_id(&self) -> MessageId {
+        MessageId(
+            self.last_message_id
+                .fetch_add(1, Ordering::Relaxed)
+                .wrapping_add(1),
+        )
+    }
+
+    /// Take calls recorded so far.
+    pub fn take_calls(&self) -> Vec<BotCall> {
+        std::mem::take(&mut *self.calls.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Record `call`.
+    fn record(&self, call: BotCall) {
+        self.calls
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(call);
+    }
+
+    /// Serve fake Telegram Bot API on a random local port.
+    ///
+    /// Returns bot sending its requests there and the task serving them,
+    /// which should be aborted when the bot is not needed anymore.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the port can't be bound.
+    pub async fn serve(&self) -> color_eyre::Result<(teloxide::Bot, JoinHandle<()>)> {
+        let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .wrap_err("Failed to bind fake Telegram Bot API")?;
+        let api_url = Url::parse(&format!("http://{}", listener.local_addr()?))?;
+
+        let router = Router::new()
+            .route("/:token/:method", post(handle_call))
+            .with_state(self.clone());
+        let server = tokio::spawn(async move {
+            if let Err(error) = axum::serve(listener, router).await {
+                tracing::error!(?error, "Fake Telegram Bot API failed");
+            }
+        });
+
+        Ok((
+            teloxide::Bot::new("simulation").set_api_url(api_url),
+            server,
+        ))
+    }
+
+    /// Record call of the Telegram Bot API `method` with `body`.
+    ///
+    /// Returns result of the call in the Telegram Bot API format
+    /// or description of the error if `method` is not simulated.
+    fn call(&self, method: &str, body: &[u8]) -> Result<serde_json::Value, String> {
+        match method.to_lowercase().as_str() {
+            "sendmessage" => {
+                let body: SendMessageBody = parse_body(body)?;
+                let message_id = self.next_message_id();
+                let result = message_json(message_id, true, &body.text);
+                self.record(BotCall::SendMessage(SentMessage {
+                    message_id,
+                    text: body.text,
+                    parse_mode: body.parse_mode,
+                    reply_markup: body.reply_markup,
+                }));
+                Ok(result)
+            }
+            "editmessagetext" => {
+                let body: EditMessageTextBody = parse_body(body)?;
+                let result = message_json(body.message_id, true, &body.text);
+                self.record(BotCall::EditMessageText(EditedText {
+                    message_id: body.message_id,
+                    text: body.text,
+                    parse_mode: body.parse_mode,
+                }));
+                Ok(result)
+            }
+            "editmessagereplymarkup" => {
+                let body: EditMessageReplyMarkupBody = parse_body(body)?;
+                self.record(BotCall::EditMessageReplyMarkup {
+                    message_id: body.message_id,
+                    reply_markup: body.reply_markup,
+                });
+                Ok(message_json(body.message_id, true, ""))
+            }
+            "deletemessage" => {
+                let body: MessageBody = parse_body(body)?;
+                self.record(BotCall::DeleteMessage {
+                    message_id: body.message_id,
+                });
+                Ok(serde_json::Value::Bool(true))
+            }
+            "pinchatmessage" => {
+                let body: MessageBody = parse_body(body)?;
+                self.record(BotCall::PinChatMessage {
+                    message_id: body.message_id,
+                });
+                Ok(serde_json::Value::Bool(true))
+            }
+            _ => Err(format!("Method `{method}` is not simulated")),
+        }
+    }
+}
+
+/// Handle call of the Telegram Bot API `method` with `body` recording it with `recorder`.
+async fn handle_call(
+    State(recorder): State<Recorder>,
+    Path((_token, method)): Path<(String, String)>,
+    body: Bytes,
+) -> Json<serde_json::Value> {
+    Json(match recorder.call(&method, &body) {
+        Ok(result) => serde_json::json!({ "ok": true, "result": result }),
+        Err(description) => serde_json::json!({
+            "ok": false,
+            "error_code": 400_u16,
+            "description": format!("Bad Request: {description}"),
+        }),
+    })
+}
+
+/// Deserialize keyboard sent by the bot.
+///
+/// Bot omits `false` and empty fields of the keyboards, which are required by
+/// [`ReplyMarkup`] deserialization, so they are filled with defaults first.
+fn deserialize_reply_markup<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<ReplyMarkup>, D::Error> {
+    let Some(mut markup) =
+        Option::<serde_json::Map<String, serde_json::Value>>::deserialize(deserializer)?
+    else {
+        return Ok(None);
+    };
+    for flag in [
+        "is_persistent",
+        "resize_keyboard",
+        "one_time_keyboard",
+        "selective",
+    ] {
+        markup.entry(flag).or_insert(serde_json::Value::Bool(false));
+    }
+    markup
+        .entry("input_field_placeholder")
+        .or_insert_with(|| serde_json::Value::String(String::new()));
+
+    ReplyMarkup::deserialize(serde_json::Value::Object(markup))
+        .map(Some)
+        .map_err(D::Error::custom)
+}
+
+/// Parse `body` of the request.
+fn parse_body<T: DeserializeOwned>(body: &[u8]) -> Result<T, String> {
+    serde_json::from_slice(body).map_err(|error| format!("invalid request body: {error}"))
+}
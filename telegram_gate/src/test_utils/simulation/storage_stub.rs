@@ -0,0 +1,279 @@
+//! Module with [`StorageStub`] keeping records of the simulated password storage in memory.
+
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    net::Ipv4Addr,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use color_eyre::eyre::WrapErr as _;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{Request, Response, Status};
+
+use crate::grpc::{self, password_storage_server::PasswordStorageServer};
+
+/// Payload of the records added by the script instead of actually encrypted passwords.
+const STUB_PAYLOAD: &[u8] = b"simulated";
+
+/// Password storage keeping records in memory.
+///
+/// Behaves like the real storage for the requests sent by the bot, except that blind search
+/// never finds anything, as stub records don't have a blind index.
+#[derive(Debug, Default, Clone)]
+pub struct StorageStub {
+    /// Records by resource names.
+    records: Arc<Mutex<BTreeMap<String, grpc::Record>>>,
+}
+
+impl StorageStub {
+    /// Construct storage with stub records of `resource_names`.
+    pub fn new(resource_names: impl IntoIterator<Item = String>) -> Self {
+        let records = resource_names
+            .into_iter()
+            .map(|name| (name.clone(), stub_record(name)))
+            .collect();
+        Self {
+            records: Arc::new(Mutex::new(records)),
+        }
+    }
+
+    /// Get names of the stored resources in alphabetical order.
+    #[must_use]
+    pub fn resource_names(&self) -> Vec<String> {
+        self.lock().keys().cloned().collect()
+    }
+
+    /// Lock records.
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, grpc::Record>> {
+        self.records.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Serve this storage on a random local port.
+    ///
+    /// Returns client connected to it and the task serving it,
+    /// which should be aborted when the client is not needed anymore.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the port can't be bound or the client can't connect.
+    pub async fn serve(&self) -> color_eyre::Result<(grpc::StorageClient, JoinHandle<()>)> {
+        let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .wrap_err("Failed to bind password storage stub")?;
+        let address = listener.local_addr()?;
+
+        let server = tonic::transport::Server::builder()
+            .add_service(PasswordStorageServer::new(self.clone()))
+            .serve_with_incoming(TcpListenerStream::new(listener));
+        let server = tokio::spawn(async move {
+            if let Err(error) = server.await {
+                tracing::error!(?error, "Password storage stub failed");
+            }
+        });
+
+        let channel = tonic::transport::Channel::from_shared(format!("http://{address}"))?
+            .connect()
+            .await
+            .wrap_err("Failed to connect to password storage stub")?;
+        Ok((grpc::StorageClient::new(channel), server))
+    }
+}
+
+#[tonic::async_trait]
+impl grpc::password_storage_server::PasswordStorage for StorageStub {
+    type GetChunkedStream = grpc::ResponseStream<grpc::RecordChunk>;
+
+    async fn add(
+        &self,
+        request: Request<grpc::AddRequest>,
+    ) -> Result<Response<grpc::AddResponse>, Status> {
+        let request = request.into_inner();
+        let name = request
+            .resource
+            .map(|resource| resource.name)
+            .unwrap_or_default();
+        let record = grpc::Record {
+            encrypted_payload: request.encrypted_payload,
+            salt: request.salt,
+            kdf_iterations: request.kdf_iterations,
+            kdf_salt: request.kdf_salt,
+            algorithm: request.algorithm,
+            bound_to_resource_name: request.bound_to_resource_name,
+            ..stub_record(name.clone())
+        };
+        match self.lock().entry(name) {
+            Entry::Occupied(entry) => Err(Status::already_exists(format!(
+                "Resource `{}` already exists",
+                entry.key()
+            ))),
+            Entry::Vacant(entry) => {
+                entry.insert(record);
+                Ok(Response::new(grpc::AddResponse::default()))
+            }
+        }
+    }
+
+    async fn delete(
+        &self,
+        request: Request<grpc::DeleteRequest>,
+    ) -> Result<Response<grpc::Response>, Status> {
+        let request = request.into_inner();
+        let mut records = self.lock();
+        match records.get(&request.name).map(|record| record.revision) {
+            None => return Err(Status::not_found(request.name)),
+            Some(revision)
+                if request.expected_revision != 0 && request.expected_revision != revision =>
+            {
+                return Err(Status::aborted("Record was changed concurrently"));
+            }
+            Some(_revision) => {}
+        }
+        records.remove(&request.name);
+        drop(records);
+        Ok(Response::new(grpc::Response {}))
+    }
+
+    async fn rename(
+        &self,
+        request: Request<grpc::RenameRequest>,
+    ) -> Result<Response<grpc::Response>, Status> {
+        let request = request.into_inner();
+        let mut records = self.lock();
+        match records.get(&request.name) {
+            None => return Err(Status::not_found(request.name)),
+            Some(record)
+                if request.expected_revision != 0
+                    && request.expected_revision != record.revision =>
+            {
+                return Err(Status::aborted("Record was changed concurrently"));
+            }
+            Some(record) if record.bound_to_resource_name => {
+                return Err(Status::failed_precondition("Payload is bound to the name"));
+            }
+            Some(_record) => {}
+        }
+        if records.contains_key(&request.new_name) {
+            return Err(Status::already_exists(request.new_name));
+        }
+
+        if let Some(record) = records.remove(&request.name) {
+            let renamed = grpc::Record {
+                resource: Some(grpc::Resource {
+                    name: request.new_name.clone(),
+                }),
+                revision: record.revision.saturating_add(1),
+                ..record
+            };
+            records.insert(request.new_name, renamed);
+        }
+        drop(records);
+        Ok(Response::new(grpc::Response {}))
+    }
+
+    async fn get(
+        &self,
+        request: Request<grpc::GetRequest>,
+    ) -> Result<Response<grpc::Record>, Status> {
+        let request = request.into_inner();
+        self.lock()
+            .get(&request.name)
+            .cloned()
+            .map(Response::new)
+            .ok_or_else(|| Status::not_found(request.name))
+    }
+
+    async fn list(
+        &self,
+        _request: Request<grpc::Empty>,
+    ) -> Result<Response<grpc::ListOfResources>, Status> {
+        Ok(Response::new(resources(self.lock().keys().cloned())))
+    }
+
+    async fn search(
+        &self,
+        request: Request<grpc::Resource>,
+    ) -> Result<Response<grpc::ListOfResources>, Status> {
+        let query = request.into_inner().name.to_lowercase();
+        Ok(Response::new(resources(
+            self.lock()
+                .keys()
+                .filter(|name| name.to_lowercase().contains(&query))
+                .cloned(),
+        )))
+    }
+
+    async fn search_blind(
+        &self,
+        _request: Request<grpc::BlindTokens>,
+    ) -> Result<Response<grpc::ListOfResources>, Status> {
+        Ok(Response::new(resources(std::iter::empty())))
+    }
+
+    async fn add_chunked(
+        &self,
+        _request: Request<tonic::Streaming<grpc::RecordChunk>>,
+    ) -> Result<Response<grpc::AddResponse>, Status> {
+        Err(Status::unimplemented("Chunked records are not simulated"))
+    }
+
+    async fn get_chunked(
+        &self,
+        _request: Request<grpc::GetRequest>,
+    ) -> Result<Response<Self::GetChunkedStream>, Status> {
+        Err(Status::unimplemented("Chunked records are not simulated"))
+    }
+
+    async fn add_attachment(
+        &self,
+        _request: Request<grpc::AddAttachmentRequest>,
+    ) -> Result<Response<grpc::Response>, Status> {
+        Err(Status::unimplemented("Attachments are not simulated"))
+    }
+
+    async fn get_attachment(
+        &self,
+        _request: Request<grpc::GetAttachmentRequest>,
+    ) -> Result<Response<grpc::Attachment>, Status> {
+        Err(Status::unimplemented("Attachments are not simulated"))
+    }
+
+    // Stub records have no attachments
+    async fn list_attachments(
+        &self,
+        _request: Request<grpc::Resource>,
+    ) -> Result<Response<grpc::ListOfAttachments>, Status> {
+        Ok(Response::new(grpc::ListOfAttachments::default()))
+    }
+
+    async fn service_status(
+        &self,
+        _request: Request<grpc::Empty>,
+    ) -> Result<Response<grpc::ServiceStatus>, Status> {
+        Err(Status::unimplemented("Service status is not simulated"))
+    }
+}
+
+/// Construct stub record of resource with `name`.
+fn stub_record(name: String) -> grpc::Record {
+    grpc::Record {
+        resource: Some(grpc::Resource { name }),
+        encrypted_payload: STUB_PAYLOAD.to_vec(),
+        salt: vec![0; telepass_data_model::crypto::AES_256_GCM_SALT_SIZE],
+        kdf_iterations: 0,
+        kdf_salt: Vec::new(),
+        algorithm: grpc::Algorithm::Aes256Gcm.into(),
+        bound_to_resource_name: false,
+        revision: 1,
+        chunk_count: 0,
+        key_commitment: Vec::new(),
+        output_version: 0,
+    }
+}
+
+/// Construct list of resources with `names`.
+fn resources(names: impl Iterator<Item = String>) -> grpc::ListOfResources {
+    grpc::ListOfResources {
+        resources: names.map(|name| grpc::Resource { name }).collect(),
+    }
+}
@@ -0,0 +1,217 @@
+//! Module with per-chat time zones used to show times in bot messages.
+//!
+//! Times are shown in UTC with a `(UTC)` suffix until the user sets a time zone with
+//! `/timezone`, so that it's clear they are not local.
+
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{Mutex, MutexGuard},
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Utc};
+pub use chrono_tz::Tz;
+use teloxide::types::ChatId;
+use tracing::warn;
+
+/// Format `time` in `time_zone` as `YYYY-MM-DD HH:MM <abbreviation>`.
+///
+/// Falls back to UTC with a `(UTC)` suffix if `time_zone` is not set.
+#[must_use]
+pub fn format_local(time: SystemTime, time_zone: Option<Tz>) -> String {
+    let utc = DateTime::<Utc>::from(time);
+    time_zone.map_or_else(
+        || format!("{} (UTC)", utc.format("%Y-%m-%d %H:%M")),
+        |zone| {
+            utc.with_timezone(&zone)
+                .format("%Y-%m-%d %H:%M %Z")
+                .to_string()
+        },
+    )
+}
+
+/// Parse IANA time zone `name`, e.g. `Europe/Berlin`.
+///
+/// # Errors
+///
+/// Fails if `name` is not a known IANA time zone.
+pub fn parse(name: &str) -> Result<Tz, UnknownTimeZone> {
+    name.parse()
+        .map_err(|_err| UnknownTimeZone(name.to_owned()))
+}
+
+/// Error of [`parse()`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown time zone \"{0}\"")]
+pub struct UnknownTimeZone(pub String);
+
+/// Time zones set by chats.
+///
+/// Persisted to a file with `<chat id>=<time zone>` lines if it's specified,
+/// so that they survive restarts.
+#[derive(Debug, Default)]
+pub struct TimeZones {
+    /// Time zones by chats.
+    zones: Mutex<BTreeMap<ChatId, Tz>>,
+    /// File to persist time zones to. [`None`] if they are kept in memory only.
+    path: Option<PathBuf>,
+}
+
+impl TimeZones {
+    /// Load time zones from the file at `path` and persist them there.
+    ///
+    /// Missing file is treated as empty, malformed lines are skipped.
+    #[must_use]
+    pub fn load(path: PathBuf) -> Self {
+        let zones = std::fs::read_to_string(&path)
+            .map(|content| parse_lines(&content))
+            .unwrap_or_default();
+        Self {
+            zones: Mutex::new(zones),
+            path: Some(path),
+        }
+    }
+
+    /// Get time zone of the chat with `chat_id`, [`None`] if it's not set.
+    #[must_use]
+    pub fn get(&self, chat_id: ChatId) -> Option<Tz> {
+        self.lock_zones().get(&chat_id).copied()
+    }
+
+    /// Set `time_zone` of the chat with `chat_id` and persist it.
+    pub fn set(&self, chat_id: ChatId, time_zone: Tz) {
+        let mut zones = self.lock_zones();
+        zones.insert(chat_id, time_zone);
+        let content = serialize(&zones);
+        drop(zones);
+
+        if let Some(path) = self.path.as_ref() {
+            if let Err(error) = std::fs::write(path, content) {
+                warn!(?error, ?path, "Failed to save time zones");
+            }
+        }
+    }
+
+    /// Lock time zones.
+    ///
+    /// Panics if lock is poisoned.
+    #[expect(clippy::expect_used, reason = "poisoning indicates programmer error")]
+    fn lock_zones(&self) -> MutexGuard<'_, BTreeMap<ChatId, Tz>> {
+        self.zones.lock().expect("`zones` should not be poisoned")
+    }
+}
+
+/// Parse `<chat id>=<time zone>` lines of `content` skipping malformed ones.
+fn parse_lines(content: &str) -> BTreeMap<ChatId, Tz> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            let parsed = line.split_once('=').and_then(|(chat_id, time_zone)| {
+                Some((
+                    ChatId(chat_id.trim().parse().ok()?),
+                    parse(time_zone.trim()).ok()?,
+                ))
+            });
+            if parsed.is_none() {
+                warn!(line, "Malformed time zone, skipping it");
+            }
+            parsed
+        })
+        .collect()
+}
+
+/// Serialize `zones` into `<chat id>=<time zone>` lines.
+fn serialize(zones: &BTreeMap<ChatId, Tz>) -> String {
+    zones
+        .iter()
+        .map(|(chat_id, time_zone)| format!("{chat_id}={}\n", time_zone.name()))
+        .collect::<Vec<_>>()
+        .concat()
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Construct time `seconds` after the Unix epoch.
+    fn at(seconds: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH
+            .checked_add(Duration::from_secs(seconds))
+            .unwrap()
+    }
+
+    /// 2024-03-31 00:59 UTC, a minute before Central European Summer Time starts.
+    const BEFORE_SPRING_FORWARD: u64 = 1_711_846_740;
+
+    /// 2024-10-27 00:59 UTC, a minute before Central European Summer Time ends.
+    const BEFORE_FALL_BACK: u64 = 1_729_990_740;
+
+    #[test]
+    fn unset_time_zone_falls_back_to_utc() {
+        assert_eq!(
+            format_local(at(BEFORE_SPRING_FORWARD), None),
+            "2024-03-31 00:59 (UTC)"
+        );
+    }
+
+    #[test]
+    fn spring_forward_skips_an_hour() {
+        let berlin = Some(parse("Europe/Berlin").unwrap());
+
+        assert_eq!(
+            format_local(at(BEFORE_SPRING_FORWARD), berlin),
+            "2024-03-31 01:59 CET"
+        );
+        assert_eq!(
+            format_local(at(BEFORE_SPRING_FORWARD + 60), berlin),
+            "2024-03-31 03:00 CEST"
+        );
+    }
+
+    #[test]
+    fn fall_back_repeats_an_hour() {
+        let berlin = Some(parse("Europe/Berlin").unwrap());
+
+        assert_eq!(
+            format_local(at(BEFORE_FALL_BACK), berlin),
+            "2024-10-27 02:59 CEST"
+        );
+        assert_eq!(
+            format_local(at(BEFORE_FALL_BACK + 60), berlin),
+            "2024-10-27 02:00 CET"
+        );
+    }
+
+    #[test]
+    fn unknown_time_zone_is_rejected() {
+        assert_eq!(
+            parse("Mars/Olympus_Mons").unwrap_err(),
+            UnknownTimeZone("Mars/Olympus_Mons".to_owned())
+        );
+    }
+
+    #[test]
+    fn time_zones_are_persisted() {
+        let path = std::env::temp_dir().join(format!("telepass_time_zones_{}", std::process::id()));
+        std::fs::write(&path, "42=Europe/Berlin\nmalformed\n7=Mars/Olympus_Mons\n").unwrap();
+
+        let time_zones = TimeZones::load(path.clone());
+        assert_eq!(time_zones.get(ChatId(42)), Some(Tz::Europe__Berlin));
+        assert_eq!(time_zones.get(ChatId(7)), None);
+        time_zones.set(ChatId(7), Tz::Asia__Tokyo);
+
+        let reloaded = TimeZones::load(path.clone());
+        assert_eq!(reloaded.get(ChatId(42)), Some(Tz::Europe__Berlin));
+        assert_eq!(reloaded.get(ChatId(7)), Some(Tz::Asia__Tokyo));
+
+        std::fs::remove_file(path).unwrap();
+        let missing = TimeZones::load(std::env::temp_dir().join("telepass_missing_time_zones"));
+        assert_eq!(missing.get(ChatId(42)), None);
+    }
+}
@@ -0,0 +1,21 @@
+//! Module with TLS configuration of the `password_storage` client.
+
+use tonic::transport::{Certificate, ClientTlsConfig, Identity};
+
+/// Domain name which `password_storage` server certificate is issued for.
+pub const PASSWORD_STORAGE_DOMAIN_NAME: &str = "password_storage";
+
+/// Prepare client TLS configuration to authenticate in `password_storage`.
+///
+/// Accepts PEM-encoded client certificate, client private key and certificate of the CA
+/// issuing server certificate.
+pub fn client_config(
+    cert: impl AsRef<[u8]>,
+    key: impl AsRef<[u8]>,
+    server_ca_cert: impl AsRef<[u8]>,
+) -> ClientTlsConfig {
+    ClientTlsConfig::new()
+        .domain_name(PASSWORD_STORAGE_DOMAIN_NAME)
+        .ca_certificate(Certificate::from_pem(server_ca_cert))
+        .identity(Identity::from_pem(cert, key))
+}
@@ -0,0 +1,81 @@
+//! HTTP endpoint to resolve one-time unlock tokens from [`UnlockTokenStore`].
+
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderValue, Method, StatusCode},
+    response::{IntoResponse as _, Response},
+    routing::get,
+    Json, Router,
+};
+use base64::{engine::general_purpose::URL_SAFE, Engine as _};
+use color_eyre::{eyre::WrapErr as _, Result};
+use tower_http::cors::CorsLayer;
+use tracing::{debug, info};
+use url::Url;
+
+use crate::{grpc, task_registry::ShutdownSignal, unlock_token::UnlockTokenStore};
+
+/// Construct router with `GET /unlock/{token}` route.
+///
+/// Only requests from `web_app_url` origin are allowed by CORS.
+///
+/// # Errors
+///
+/// Fails if `web_app_url` origin can't be used as a header value.
+pub fn router(unlock_token_store: Arc<UnlockTokenStore>, web_app_url: &Url) -> Result<Router> {
+    let origin = HeaderValue::from_str(&web_app_url.origin().ascii_serialization())
+        .wrap_err("Failed to use Web App origin as a header value")?;
+
+    Ok(Router::new()
+        .route("/unlock/:token", get(unlock))
+        .layer(
+            CorsLayer::new()
+                .allow_origin(origin)
+                .allow_methods([Method::GET]),
+        )
+        .with_state(unlock_token_store))
+}
+
+/// Serve `router` on `address` until `shutdown` signal is received.
+///
+/// # Errors
+///
+/// Fails if it's not possible to bind to `address` or if serving fails.
+pub async fn serve(address: SocketAddr, router: Router, shutdown: ShutdownSignal) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(address)
+        .await
+        .wrap_err_with(|| format!("Failed to bind unlock endpoint to `{address}`"))?;
+    info!(%address, "Serving unlock endpoint");
+
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown.recv())
+        .await
+        .wrap_err("Unlock endpoint failed")
+}
+
+/// Resolve `token` into an encrypted record.
+///
+/// Responds with `404 Not Found` if token is unknown, already used or expired.
+async fn unlock(
+    State(unlock_token_store): State<Arc<UnlockTokenStore>>,
+    Path(token): Path<String>,
+) -> Response {
+    let Some(record) = unlock_token_store.resolve(&token) else {
+        debug!("Unknown or expired unlock token");
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    Json(serde_json::json!({
+        "payload": URL_SAFE.encode(record.encrypted_payload),
+        "salt": URL_SAFE.encode(record.salt),
+        "kdf_iterations": record.kdf_iterations,
+        "kdf_salt": URL_SAFE.encode(record.kdf_salt),
+        "algorithm": grpc::web_app_algorithm_name(record.algorithm),
+        "bound_to_resource_name": record.bound_to_resource_name,
+        "key_commitment": URL_SAFE.encode(record.key_commitment),
+        "version": record.output_version,
+    }))
+    .into_response()
+}
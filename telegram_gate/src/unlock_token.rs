@@ -0,0 +1,230 @@
+//! Module with [`UnlockTokenStore`] to hand out encrypted records to the Web App by short tokens
+//! instead of embedding them into urls.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use url::Url;
+
+use crate::context::{Clock, Rng};
+
+/// Default time to live of a minted token.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Number of random bytes in a token.
+const TOKEN_SIZE: usize = 16;
+
+/// Encrypted record data which can be unlocked by a token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedRecord {
+    /// Payload encrypted with a master password.
+    pub encrypted_payload: Vec<u8>,
+    /// Salt used for encryption.
+    pub salt: Vec<u8>,
+    /// Number of key derivation iterations used for encryption, zero for the default.
+    pub kdf_iterations: u32,
+    /// Salt used for key derivation, empty for the legacy constant one.
+    pub kdf_salt: Vec<u8>,
+    /// Encryption algorithm as defined by [`crate::grpc::Algorithm`].
+    pub algorithm: i32,
+    /// Whether the payload is bound to the resource name it's shown with.
+    pub bound_to_resource_name: bool,
+    /// Commitment to the encryption key, empty if there is none.
+    pub key_commitment: Vec<u8>,
+    /// Version of the encryption output, zero for the first one.
+    pub output_version: u32,
+}
+
+/// Stored [`LockedRecord`] with its expiration time.
+#[derive(Debug)]
+struct Entry {
+    /// Record to hand out.
+    record: LockedRecord,
+    /// Moment after which the token is not valid anymore.
+    expires_at: Instant,
+}
+
+/// In-memory store of one-time tokens.
+///
+/// Each token can be [resolved](Self::resolve) only once and only until it expires.
+#[derive(Debug)]
+pub struct UnlockTokenStore {
+    /// Minted tokens.
+    entries: Mutex<HashMap<String, Entry>>,
+    /// Time to live of a token.
+    ttl: Duration,
+    /// Public url of the endpoint resolving tokens.
+    endpoint_url: Url,
+}
+
+impl UnlockTokenStore {
+    /// Create new empty [`UnlockTokenStore`].
+    ///
+    /// `endpoint_url` is a public url of the endpoint which resolves tokens.
+    #[must_use]
+    pub fn new(ttl: Duration, endpoint_url: Url) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+            endpoint_url,
+        }
+    }
+
+    /// Get public url of the endpoint which resolves tokens.
+    #[must_use]
+    pub const fn endpoint_url(&self) -> &Url {
+        &self.endpoint_url
+    }
+
+    /// Mint a new token for `record` expiring after TTL from the `clock` current time.
+    ///
+    /// Token bytes are taken from `rng`. Also removes all expired tokens.
+    pub fn mint(&self, record: LockedRecord, clock: &dyn Clock, rng: &dyn Rng) -> String {
+        let now = clock.now();
+        let mut bytes = [0; TOKEN_SIZE];
+        rng.fill_bytes(&mut bytes);
+        let token = URL_SAFE_NO_PAD.encode(bytes);
+
+        let mut entries = self.lock_entries();
+        entries.retain(|_token, entry| entry.expires_at > now);
+        entries.insert(
+            token.clone(),
+            Entry {
+                record,
+                expires_at: now.checked_add(self.ttl).unwrap_or(now),
+            },
+        );
+
+        token
+    }
+
+    /// Resolve `token` into a record.
+    ///
+    /// Returns [`None`] if token is unknown, already used or expired.
+    pub fn resolve(&self, token: &str) -> Option<LockedRecord> {
+        self.resolve_at(token, Instant::now())
+    }
+
+    /// [`resolve()`](Self::resolve) with explicit current time.
+    pub fn resolve_at(&self, token: &str, now: Instant) -> Option<LockedRecord> {
+        let entry = self.lock_entries().remove(token)?;
+
+        (entry.expires_at > now).then_some(entry.record)
+    }
+
+    /// Lock minted tokens.
+    ///
+    /// Panics if lock is poisoned.
+    #[expect(clippy::expect_used, reason = "poisoning indicates programmer error")]
+    fn lock_entries(&self) -> MutexGuard<'_, HashMap<String, Entry>> {
+        self.entries
+            .lock()
+            .expect("`entries` should not be poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use super::*;
+    use crate::context::{MockClock, OsRandom, SystemClock};
+
+    fn store() -> UnlockTokenStore {
+        UnlockTokenStore::new(
+            DEFAULT_TTL,
+            Url::parse("http://localhost:8082/unlock/").unwrap(),
+        )
+    }
+
+    fn record() -> LockedRecord {
+        LockedRecord {
+            encrypted_payload: b"payload".to_vec(),
+            salt: b"salt".to_vec(),
+            kdf_iterations: 0,
+            kdf_salt: Vec::new(),
+            algorithm: 0,
+            bound_to_resource_name: false,
+            key_commitment: Vec::new(),
+            output_version: 0,
+        }
+    }
+
+    fn clock_at(now: Instant) -> MockClock {
+        let mut clock = MockClock::new();
+        clock.expect_now().return_const(now);
+        clock
+    }
+
+    fn mint(store: &UnlockTokenStore) -> String {
+        store.mint(record(), &SystemClock, &OsRandom)
+    }
+
+    #[test]
+    fn mint_gives_different_tokens() {
+        let store = store();
+
+        let first = mint(&store);
+        let second = mint(&store);
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn resolve_success() {
+        let store = store();
+
+        let token = mint(&store);
+
+        assert_eq!(store.resolve(&token), Some(record()));
+    }
+
+    #[test]
+    fn resolve_twice_failure() {
+        let store = store();
+
+        let token = mint(&store);
+
+        assert!(store.resolve(&token).is_some());
+        assert!(store.resolve(&token).is_none());
+    }
+
+    #[test]
+    fn resolve_unknown_failure() {
+        let store = store();
+
+        mint(&store);
+
+        assert!(store.resolve("unknown").is_none());
+    }
+
+    #[test]
+    fn resolve_expired_failure() {
+        let store = store();
+        let now = Instant::now();
+
+        let token = store.mint(record(), &clock_at(now), &OsRandom);
+
+        let after_expiration = now.checked_add(DEFAULT_TTL).unwrap();
+        assert!(store.resolve_at(&token, after_expiration).is_none());
+    }
+
+    #[test]
+    fn mint_removes_expired_tokens() {
+        let store = store();
+        let now = Instant::now();
+
+        let expired_token = store.mint(record(), &clock_at(now), &OsRandom);
+        store.mint(
+            record(),
+            &clock_at(now.checked_add(DEFAULT_TTL).unwrap()),
+            &OsRandom,
+        );
+
+        assert!(!store.lock_entries().contains_key(&expired_token));
+    }
+}
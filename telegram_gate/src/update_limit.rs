@@ -0,0 +1,194 @@
+//! Module with [`UpdateLimit`] bounding the number of updates handled at the same time.
+//!
+//! Telegram redelivers all pending updates at once after downtime. Handling them all
+//! concurrently would queue them on the storage client lock and spam the Telegram API, so
+//! handlers wait for a permit first. Updates of the same chat are already handled one by one
+//! by the dispatcher, so this bounds the total work.
+
+use std::{
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+use tokio::{
+    sync::{Semaphore, SemaphorePermit},
+    time::Instant,
+};
+use tracing::warn;
+
+/// Default number of updates handled at the same time.
+pub const DEFAULT_MAX_CONCURRENT_UPDATES: usize = 16;
+
+/// Default wait for a permit after which a warning is logged.
+pub const DEFAULT_SLOW_WAIT: Duration = Duration::from_secs(1);
+
+/// Statistics of waits for a permit to handle an update.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueWaitMetrics {
+    /// Number of granted permits.
+    pub acquired: u64,
+    /// Number of waits longer than the slow wait threshold.
+    pub slow_waits: u64,
+    /// Total time spent waiting.
+    pub total_wait: Duration,
+    /// Longest wait.
+    pub max_wait: Duration,
+}
+
+impl QueueWaitMetrics {
+    /// Record a wait of `wait` which is slow if it's longer than `slow_wait`.
+    fn record(&mut self, wait: Duration, slow_wait: Duration) {
+        self.acquired = self.acquired.saturating_add(1);
+        if wait > slow_wait {
+            self.slow_waits = self.slow_waits.saturating_add(1);
+        }
+        self.total_wait = self.total_wait.saturating_add(wait);
+        self.max_wait = self.max_wait.max(wait);
+    }
+}
+
+/// Limit of updates handled at the same time.
+#[derive(Debug)]
+pub struct UpdateLimit {
+    /// Permits to handle an update.
+    semaphore: Semaphore,
+    /// Wait for a permit after which a warning is logged.
+    slow_wait: Duration,
+    /// Statistics of waits for a permit.
+    metrics: Mutex<QueueWaitMetrics>,
+}
+
+impl Default for UpdateLimit {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONCURRENT_UPDATES, DEFAULT_SLOW_WAIT)
+    }
+}
+
+impl UpdateLimit {
+    /// Create new [`UpdateLimit`] allowing `max_concurrent` updates at the same time and
+    /// warning about waits longer than `slow_wait`.
+    #[must_use]
+    pub fn new(max_concurrent: usize, slow_wait: Duration) -> Self {
+        Self {
+            semaphore: Semaphore::new(max_concurrent),
+            slow_wait,
+            metrics: Mutex::new(QueueWaitMetrics::default()),
+        }
+    }
+
+    /// Wait for a permit to handle an update, it's returned back when the permit is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Never, cause the semaphore is never closed.
+    #[expect(clippy::expect_used, reason = "semaphore is never closed")]
+    pub async fn acquire(&self) -> SemaphorePermit<'_> {
+        let start = Instant::now();
+        let permit = self
+            .semaphore
+            .acquire()
+            .await
+            .expect("`semaphore` should never be closed");
+        let wait = start.elapsed();
+
+        if wait > self.slow_wait {
+            warn!(
+                ?wait,
+                available_permits = self.semaphore.available_permits(),
+                "Update waited too long to be handled"
+            );
+        }
+        self.lock_metrics().record(wait, self.slow_wait);
+
+        permit
+    }
+
+    /// Get statistics of waits for a permit.
+    pub fn metrics(&self) -> QueueWaitMetrics {
+        *self.lock_metrics()
+    }
+
+    /// Lock statistics of waits.
+    ///
+    /// Panics if lock is poisoned.
+    #[expect(clippy::expect_used, reason = "poisoning indicates programmer error")]
+    fn lock_metrics(&self) -> MutexGuard<'_, QueueWaitMetrics> {
+        self.metrics
+            .lock()
+            .expect("`metrics` should not be poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+
+    /// Transition recording how many transitions are running at the same time.
+    async fn instrumented_transition(
+        limit: &UpdateLimit,
+        running: &AtomicUsize,
+        max_running: &AtomicUsize,
+    ) {
+        let _permit = limit.acquire().await;
+
+        let now_running = running.fetch_add(1, Ordering::SeqCst).saturating_add(1);
+        max_running.fetch_max(now_running, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        running.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn limits_concurrent_transitions() {
+        let limit = Arc::new(UpdateLimit::new(3, DEFAULT_SLOW_WAIT));
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..10_usize)
+            .map(|_| {
+                let limit = Arc::clone(&limit);
+                let running = Arc::clone(&running);
+                let max_running = Arc::clone(&max_running);
+                tokio::spawn(async move {
+                    instrumented_transition(&limit, &running, &max_running).await;
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert_eq!(max_running.load(Ordering::SeqCst), 3);
+        let metrics = limit.metrics();
+        assert_eq!(metrics.acquired, 10);
+        // The last three transitions waited for three rounds of others.
+        assert_eq!(metrics.max_wait, Duration::from_millis(300));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_waits_are_counted() {
+        let limit = Arc::new(UpdateLimit::new(1, Duration::from_secs(1)));
+
+        let permit = limit.acquire().await;
+        let waiting = tokio::spawn({
+            let limit = Arc::clone(&limit);
+            async move {
+                drop(limit.acquire().await);
+            }
+        });
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        drop(permit);
+        waiting.await.unwrap();
+
+        let metrics = limit.metrics();
+        assert_eq!(metrics.acquired, 2);
+        assert_eq!(metrics.slow_waits, 1);
+        assert_eq!(metrics.total_wait, Duration::from_secs(2));
+    }
+}
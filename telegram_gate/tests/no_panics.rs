@@ -0,0 +1,248 @@
+//! Source-scanning test forbidding panicking calls in dialogue transitions.
+//!
+//! A panic inside a transition leaves the user without any answer, so `state` module files
+//! must not contain `.expect(`, `.unwrap(`, `panic!` or `todo!` outside of `#[cfg(test)]`
+//! items. Justified exceptions are listed in `no_panics_allowlist.txt`.
+
+#![expect(
+    clippy::tests_outside_test_module,
+    clippy::expect_used,
+    reason = "integration tests"
+)]
+
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use syn::{
+    punctuated::Punctuated,
+    visit::{self, Visit},
+    Attribute, Expr, ExprMethodCall, ImplItemFn, ItemFn, ItemImpl, ItemMod, Macro, Token,
+};
+
+/// Methods which panic on failure.
+const PANICKING_METHODS: [&str; 2] = ["expect", "unwrap"];
+
+/// Macros which always panic.
+const PANICKING_MACROS: [&str; 2] = ["panic", "todo"];
+
+/// Panicking call found in the source, formatted as `<file> <function> <call>`.
+type Violation = String;
+
+/// Visitor collecting panicking calls outside of `#[cfg(test)]` items.
+struct Scanner<'file> {
+    /// Path of the scanned file relative to `src`.
+    file: &'file str,
+    /// Names of the functions enclosing the visited node, innermost last.
+    functions: Vec<String>,
+    /// Found violations.
+    violations: Vec<Violation>,
+}
+
+impl<'file> Scanner<'file> {
+    /// Scan `source` of the `file` and get found violations.
+    fn scan(file: &'file str, source: &str) -> Vec<Violation> {
+        let syntax = syn::parse_file(source).expect("Source file should be valid Rust");
+        let mut scanner = Self {
+            file,
+            functions: Vec::new(),
+            violations: Vec::new(),
+        };
+        scanner.visit_file(&syntax);
+        scanner.violations
+    }
+
+    /// Record panicking `call` in the current function.
+    fn record(&mut self, call: &str) {
+        let function = self.functions.last().map_or("<module>", String::as_str);
+        self.violations
+            .push(format!("{} {function} {call}", self.file));
+    }
+
+    /// Visit function `name` with `visit_body` unless it's a test-only one.
+    fn visit_function(
+        &mut self,
+        attrs: &[Attribute],
+        name: String,
+        visit_body: impl FnOnce(&mut Self),
+    ) {
+        if is_cfg_test(attrs) {
+            return;
+        }
+        self.functions.push(name);
+        visit_body(self);
+        self.functions.pop();
+    }
+}
+
+impl<'ast> Visit<'ast> for Scanner<'_> {
+    fn visit_item_mod(&mut self, item: &'ast ItemMod) {
+        if !is_cfg_test(&item.attrs) {
+            visit::visit_item_mod(self, item);
+        }
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast ItemImpl) {
+        if !is_cfg_test(&item.attrs) {
+            visit::visit_item_impl(self, item);
+        }
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast ItemFn) {
+        self.visit_function(&item.attrs, item.sig.ident.to_string(), |scanner| {
+            visit::visit_item_fn(scanner, item);
+        });
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast ImplItemFn) {
+        self.visit_function(&item.attrs, item.sig.ident.to_string(), |scanner| {
+            visit::visit_impl_item_fn(scanner, item);
+        });
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        let method = call.method.to_string();
+        if PANICKING_METHODS.contains(&method.as_str()) {
+            self.record(&method);
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        if let Some(name) = mac.path.get_ident().map(ToString::to_string) {
+            if PANICKING_MACROS.contains(&name.as_str()) {
+                self.record(&format!("{name}!"));
+            }
+        }
+
+        // Transitions are mostly wrapped into macros like `try_with_state!`,
+        // so their arguments are scanned as well if they are expressions
+        if let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+    }
+}
+
+/// Check if `attrs` contain `#[cfg(test)]`.
+fn is_cfg_test(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("cfg")
+            && attr
+                .meta
+                .require_list()
+                .is_ok_and(|list| list.tokens.to_string() == "test")
+    })
+}
+
+/// Get paths of the `state` module files relative to `src`.
+fn state_files(src: &Path) -> Vec<String> {
+    let mut files = vec!["state.rs".to_owned()];
+    let mut nested: Vec<PathBuf> = std::fs::read_dir(src.join("state"))
+        .expect("`state` directory should be readable")
+        .map(|entry| entry.expect("`state` entry should be readable").path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "rs"))
+        .collect();
+    nested.sort();
+    files.extend(nested.iter().map(|path| {
+        path.strip_prefix(src)
+            .expect("Path should be inside `src`")
+            .to_string_lossy()
+            .into_owned()
+    }));
+    files
+}
+
+/// Parse allowlist `content` skipping comments and empty lines.
+fn parse_allowlist(content: &str) -> BTreeSet<Violation> {
+    content
+        .lines()
+        .map(|line| line.split_once('#').map_or(line, |(entry, _comment)| entry))
+        .map(|entry| entry.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+#[test]
+fn state_has_no_panicking_calls() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let src = manifest_dir.join("src");
+    let allowlist = parse_allowlist(
+        &std::fs::read_to_string(manifest_dir.join("tests/no_panics_allowlist.txt"))
+            .expect("Allowlist should be readable"),
+    );
+
+    let violations: BTreeSet<Violation> = state_files(&src)
+        .iter()
+        .flat_map(|file| {
+            let source =
+                std::fs::read_to_string(src.join(file)).expect("Source file should be readable");
+            Scanner::scan(file, &source)
+        })
+        .collect();
+
+    let forbidden: Vec<_> = violations.difference(&allowlist).collect();
+    assert!(
+        forbidden.is_empty(),
+        "Panicking calls in transition code, return an error instead \
+         or justify them in the allowlist: {forbidden:#?}"
+    );
+    let stale: Vec<_> = allowlist.difference(&violations).collect();
+    assert!(stale.is_empty(), "Stale allowlist entries: {stale:#?}");
+}
+
+#[test]
+fn scanner_skips_test_items_and_sees_into_macros() {
+    let source = r#"
+        fn transition() {
+            let value = option.unwrap();
+            try_with_state!(state, result.expect("message"));
+            todo!();
+        }
+
+        impl State {
+            fn keyboard(&self) {
+                panic!("message");
+            }
+        }
+
+        #[cfg(test)]
+        mod tests {
+            fn helper() {
+                option.unwrap();
+            }
+        }
+
+        #[cfg(test)]
+        fn test_only() {
+            panic!("message");
+        }
+    "#;
+
+    assert_eq!(
+        Scanner::scan("state/sample.rs", source),
+        [
+            "state/sample.rs transition unwrap",
+            "state/sample.rs transition expect",
+            "state/sample.rs transition todo!",
+            "state/sample.rs keyboard panic!",
+        ]
+    );
+}
+
+#[test]
+fn allowlist_ignores_comments() {
+    let allowlist = parse_allowlist(
+        "# Comment\n\nstate.rs  helper   expect # Justification\nstate/main_menu.rs <module> panic!\n",
+    );
+
+    assert_eq!(
+        allowlist.into_iter().collect::<Vec<_>>(),
+        [
+            "state.rs helper expect",
+            "state/main_menu.rs <module> panic!"
+        ]
+    );
+}
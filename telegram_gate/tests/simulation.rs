@@ -0,0 +1,92 @@
+//! Tests replaying example scripts from the `simulations` directory.
+
+#![expect(
+    clippy::tests_outside_test_module,
+    clippy::expect_used,
+    reason = "integration tests"
+)]
+#![expect(clippy::non_ascii_literal, reason = "messages contain emojis")]
+
+use telepass_telegram_gate::test_utils::simulation::{self, BotCall, Report, Script};
+
+/// Run example script with `yaml` content.
+async fn run(yaml: &str) -> Report {
+    let script = Script::from_yaml(yaml).expect("Example script should be valid");
+    simulation::run(script)
+        .await
+        .expect("Example script should be replayed")
+}
+
+/// Get texts of messages sent by the bot during all steps of `report`.
+#[expect(
+    clippy::ref_patterns,
+    reason = "conflicts with `pattern_type_mismatch`"
+)]
+fn sent_texts(report: &Report) -> Vec<&str> {
+    report
+        .steps
+        .iter()
+        .flat_map(|step| &step.bot_calls)
+        .filter_map(|call| {
+            if let BotCall::SendMessage(ref message) = *call {
+                Some(message.text.as_str())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn sign_in_and_delete() {
+    let report = run(include_str!("../simulations/sign_in_and_delete.yaml")).await;
+
+    let states = report
+        .steps
+        .iter()
+        .map(|step| step.state)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        states,
+        [
+            "MainMenu",
+            "ResourcesList",
+            "ResourceActions",
+            "DeleteConfirmation",
+            "MainMenu"
+        ]
+    );
+    assert_eq!(report.final_state(), Some("MainMenu"));
+    assert!(sent_texts(&report).contains(&"✅ *github\\.com* deleted\\."));
+    assert_eq!(report.records, ["gitlab.com"]);
+}
+
+#[tokio::test]
+async fn search() {
+    let report = run(include_str!("../simulations/search.yaml")).await;
+
+    let states = report
+        .steps
+        .iter()
+        .map(|step| step.state)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        states,
+        [
+            "MainMenu",
+            "ResourcesList",
+            "ResourcesList",
+            "ResourceActions",
+            "ResourcesList"
+        ]
+    );
+    assert_eq!(report.final_state(), Some("ResourcesList"));
+    assert!(sent_texts(&report).contains(
+        &"👉 The following resources were found, choose one of them or type for a new search.\n\n\
+          Type /cancel to go back."
+    ));
+    assert_eq!(
+        report.records,
+        ["github.com", "mail.google.com", "mail.yandex.ru"]
+    );
+}
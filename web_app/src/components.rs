@@ -1,8 +1,12 @@
 //! Module with Web App components.
 
 mod common;
+pub mod deep_find;
+pub mod error_view;
 pub mod show;
 pub mod submit;
 
+pub use deep_find::DeepFind;
+pub use error_view::ErrorView;
 pub use show::Show;
 pub use submit::Submit;
@@ -5,10 +5,11 @@ use leptos::{
     html::{ElementDescriptor, Input, Textarea},
     view, Children, IntoView, NodeRef, ReadSignal, WriteSignal,
 };
-use serde::{Deserialize, Serialize};
 use web_sys::SubmitEvent;
 
-mod css {
+use crate::tg_api::WebAppUser;
+
+pub mod css {
     //! Module with css classes
 
     /// Type of password field protected with stars.
@@ -21,19 +22,6 @@ mod css {
     pub const SLASHED_EYE_CLASS: &str = "fas fa-eye-slash";
 }
 
-/// Payload with user data to be encrypted and sent to the bot backend.
-#[derive(Serialize, Deserialize)]
-pub struct Payload {
-    /// Resource name.
-    pub resource_name: String,
-    /// Login.
-    pub login: String,
-    /// Password.
-    pub password: String,
-    /// Any additional comments.
-    pub comments: String,
-}
-
 /// Parameter of [`RecordForm`] component describing how element should be shown.
 pub struct RecordFormParamRead<T: ElementDescriptor + 'static> {
     /// Value of the element.
@@ -99,6 +87,12 @@ pub fn RecordForm<F: Fn(SubmitEvent) + 'static>(
     submit_value: &'static str,
     /// Callback, which will be called when user presses the submit button.
     on_submit: F,
+    /// Input to enter the master password once again, omitted if not set.
+    #[prop(optional)]
+    master_password_confirmation_element: Option<NodeRef<Input>>,
+    /// Additional form items rendered after the basic ones, omitted if not set.
+    #[prop(optional)]
+    children: Option<Children>,
 ) -> impl IntoView {
     let resource_name_element = resource_name.element;
     let login_element = login.element;
@@ -163,6 +157,18 @@ pub fn RecordForm<F: Fn(SubmitEvent) + 'static>(
                 </InputBox>
             </FormItem>
 
+            {master_password_confirmation_element.map(|element| view! {
+                <FormItem>
+                    <label for="master-password-confirmation">Confirm Master Password</label>
+                    <InputBox>
+                        <input type="password" id="master-password-confirmation" node_ref=element
+                            autocapitalize="false" autocorrect="false" spellcheck="false"/>
+                    </InputBox>
+                </FormItem>
+            })}
+
+            {children.map(|advanced| advanced())}
+
             <FormItem>
                 <input type="submit" value=submit_value/>
             </FormItem>
@@ -172,7 +178,7 @@ pub fn RecordForm<F: Fn(SubmitEvent) + 'static>(
 
 /// Component for form items like login, password and etc.
 #[component]
-fn FormItem(
+pub fn FormItem(
     /// Child component to render inside form item.
     children: Children,
 ) -> impl IntoView {
@@ -183,9 +189,30 @@ fn FormItem(
     }
 }
 
+/// Read-only form item with id of the Telegram user who opened the app.
+#[component]
+pub fn TelegramUser(
+    /// User who opened the app if launched from the bot.
+    user: Option<WebAppUser>,
+) -> impl IntoView {
+    let user_id = user.map_or_else(
+        || "Unknown".to_owned(),
+        |known_user| known_user.id.to_string(),
+    );
+
+    view! {
+        <FormItem>
+            <label for="telegram-user">Telegram User</label>
+            <InputBox>
+                <input type="text" id="telegram-user" prop:value=user_id readonly=true/>
+            </InputBox>
+        </FormItem>
+    }
+}
+
 /// Input box for login, password and etc.
 #[component]
-fn InputBox(
+pub fn InputBox(
     /// Child component to render inside input box.
     children: Children,
 ) -> impl IntoView {
@@ -247,7 +274,7 @@ fn Copyable(
 ///
 /// Initial visibility is expected to be hidden.
 #[component]
-fn VisibilityToggle(
+pub fn VisibilityToggle(
     /// Writer to send visibility css types.
     set_ty: WriteSignal<&'static str>,
     /// Child component to add eye icon to.
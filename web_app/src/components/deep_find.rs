@@ -0,0 +1,174 @@
+//! Module with [`DeepFind`] component implementation.
+
+use std::rc::Rc;
+
+use leptos::{
+    component, create_node_ref, create_signal, html::Input, view, IntoView, Params,
+    SignalGetUntracked as _, WriteSignal,
+};
+use leptos_router::{use_query, Params, ParamsError};
+use telepass_data_model::BlindSearch;
+use web_sys::SubmitEvent;
+
+use super::common::{css, FormItem, InputBox, TelegramUser, VisibilityToggle};
+use crate::tg_api::WebApp;
+
+/// Error during search request construction.
+#[derive(Debug, Clone, thiserror::Error, displaydoc::Display)]
+pub enum Error {
+    /// Failed to parse query
+    ParamsParsing(#[from] ParamsError),
+    /// Only a single word of at least 3 letters can be searched for
+    InvalidWord,
+    /// This page should be opened with a button in the chat with the bot
+    NotLaunchedFromBot,
+    /// Failed to serialize data: {0}
+    Serialization(String),
+    /// Failed to send data to the bot backend: {0}
+    Sending(String),
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Serialization(e.to_string())
+    }
+}
+
+/// Query parameters for `/deepfind` url.
+#[derive(Params, Clone, PartialEq, Eq)]
+struct QueryParams {
+    /// Word to search for.
+    word: Option<String>,
+}
+
+/// Construct request to search for `word` blinded with `master_password`.
+///
+/// Legacy token is added if `master_password` isn't normalized, to find records indexed before
+/// the master password was normalized.
+fn blind_search(word: &str, master_password: &str) -> Result<BlindSearch, Error> {
+    let token = telepass_crypto::BlindIndexKey::derive(master_password)
+        .token(word)
+        .ok_or(Error::InvalidWord)?;
+    let legacy_token = telepass_crypto::BlindIndexKey::derive_legacy(master_password)
+        .and_then(|legacy_key| legacy_key.token(word));
+    Ok(BlindSearch {
+        token,
+        legacy_token,
+    })
+}
+
+/// Component with master password form to search for a word inside records content.
+///
+/// The word is blinded with the master password and sent to the bot via `web_app`,
+/// so neither the bot nor the storage learn it.
+#[component]
+pub fn DeepFind(
+    /// Telegram API.
+    web_app: Rc<WebApp>,
+    /// Writer to set the result of user action.
+    set_result: WriteSignal<Result<(), Error>>,
+) -> impl IntoView {
+    let word = match use_query::<QueryParams>().get_untracked() {
+        Ok(params) => params.word.unwrap_or_default(),
+        Err(err) => {
+            set_result(Err(err.into()));
+            String::new()
+        }
+    };
+    let searched_word = word.clone();
+
+    let user = web_app.user();
+    if user.is_none() {
+        set_result(Err(Error::NotLaunchedFromBot));
+    }
+
+    let master_password_element = create_node_ref::<Input>();
+    let (master_password_ty, set_master_password_ty) = create_signal(css::PASSWORD_TY);
+
+    let on_submit = move |event: SubmitEvent| {
+        event.prevent_default(); // Prevent page reload
+
+        let master_password = master_password_element()
+            .expect("No master_password element")
+            .value();
+
+        set_result(|| -> Result<(), Error> {
+            let user = user.ok_or(Error::NotLaunchedFromBot)?;
+            let search = blind_search(&searched_word, &master_password)?;
+            let message = telepass_data_model::WebAppMessage {
+                user_id: user.id,
+                data: search,
+            };
+
+            web_app
+                .sendData(serde_json::to_value(message)?.to_string().into())
+                .map_err(|err| Error::Sending(format!("{err:?}")))
+        }());
+    };
+
+    view! {
+        <form on:submit=on_submit class="record-form">
+            <TelegramUser user=user/>
+
+            <FormItem>
+                <label for="word">Word to search for</label>
+                <InputBox>
+                    <input type="text" id="word" prop:value=word readonly=true/>
+                </InputBox>
+            </FormItem>
+
+            <FormItem>
+                <label for="master-password">Master Password</label>
+                <InputBox>
+                    <VisibilityToggle set_ty=set_master_password_ty>
+                        <input type=master_password_ty id="master-password" node_ref=master_password_element
+                            autocapitalize="false" autocorrect="false" spellcheck="false"/>
+                    </VisibilityToggle>
+                </InputBox>
+            </FormItem>
+
+            <FormItem>
+                <input type="submit" value="Search"/>
+            </FormItem>
+        </form>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use super::*;
+
+    #[test]
+    fn blind_search_matches_index_of_submitted_record() {
+        let index = telepass_crypto::BlindIndexKey::derive("password").index("admin\nHome Router");
+
+        let search = blind_search("router", "password").unwrap();
+        assert!(index.contains(&search.token));
+
+        assert_eq!(search.legacy_token, None);
+
+        let other_search = blind_search("router", "wrong password").unwrap();
+        assert!(!index.contains(&other_search.token));
+
+        assert!(matches!(
+            blind_search("home router", "password"),
+            Err(Error::InvalidWord)
+        ));
+    }
+
+    #[test]
+    fn blind_search_finds_records_indexed_with_not_normalized_master_password() {
+        let legacy_key = telepass_crypto::BlindIndexKey::derive_with_version(
+            "cafe\u{301}",
+            telepass_crypto::BLIND_INDEX_VERSION_1,
+        )
+        .unwrap();
+        let legacy_index = legacy_key.index("Home Router");
+
+        let search = blind_search("router", "cafe\u{301}").unwrap();
+        assert!(!legacy_index.contains(&search.token));
+        assert!(legacy_index.contains(&search.legacy_token.unwrap()));
+    }
+}
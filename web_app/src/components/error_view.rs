@@ -0,0 +1,42 @@
+//! Module with [`ErrorView`] component implementation.
+
+use std::rc::Rc;
+
+use leptos::{component, view, Callable, Callback, IntoView};
+
+use crate::tg_api::WebApp;
+
+/// Component to show a recoverable error to the user instead of the requested page.
+///
+/// Provides a way to retry the action if `on_retry` is set and a way to close the Web App.
+#[component]
+pub fn ErrorView(
+    /// Telegram API.
+    web_app: Rc<WebApp>,
+    /// Human-readable description of what went wrong.
+    message: String,
+    /// Short error code to simplify bug reports.
+    code: &'static str,
+    /// Callback to call when user presses the "Try again" button.
+    /// Button is not shown if [`None`].
+    on_retry: Option<Callback<()>>,
+) -> impl IntoView {
+    let retry_button = on_retry.map(|callback| {
+        view! {
+            <button type="button" on:click=move |_event| Callable::call(&callback, ())>"Try again"</button>
+        }
+    });
+
+    view! {
+        <div class="error-container">
+            <div class="error-view">
+                <p class="error">{message}</p>
+                <p class="error-code">"Error code: " {code}</p>
+                <div class="error-actions">
+                    {retry_button}
+                    <button type="button" on:click=move |_event| web_app.close()>"Close"</button>
+                </div>
+            </div>
+        </div>
+    }
+}
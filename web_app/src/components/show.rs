@@ -1,26 +1,57 @@
 //! Module with [`Show`] component implementation.
 
-use base64::{engine::general_purpose::URL_SAFE, Engine as _};
+use std::rc::Rc;
+
 use leptos::{
-    component, create_node_ref, html::Input, view, IntoView, Params, SignalGetUntracked as _,
-    SignalSet as _, WriteSignal,
+    component, create_node_ref, create_signal, html::Input, store_value, view, Callback, IntoView,
+    Params, SignalGet as _, SignalGetUntracked as _, SignalSet as _,
 };
-use leptos_router::{use_query, Params, ParamsError};
+use leptos_router::{use_query, use_query_map, Params, ParamsError};
+use serde::Deserialize;
+use telepass_crypto::signed_link::{self, LinkKey};
+use telepass_data_model::RecordPayload;
+use wasm_bindgen::{JsCast as _, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
 use web_sys::SubmitEvent;
 
-use super::common::{create_record_form_parameter, Payload, RecordForm};
+use super::{
+    common::{create_record_form_parameter, RecordForm},
+    ErrorView,
+};
+use crate::{
+    tg_api::WebApp,
+    viewport::{self, Page},
+};
 
 /// Error during record presentation.
 #[derive(Debug, Clone, thiserror::Error, displaydoc::Display)]
 pub enum Error {
     /// Failed to parse query
     ParamsParsing(#[from] ParamsError),
-    /// Both payload and salt must be provided
+    /// Either payload and salt or token and unlock endpoint must be provided
     MissingParams,
     /// Failed to decode payload or salt
     Base64Decoding(#[from] base64::DecodeError),
+    /// Unknown encryption algorithm
+    UnknownAlgorithm,
     /// Wrong salt length
     WrongSaltLength,
+    /// Wrong key derivation salt length
+    WrongKdfSaltLength,
+    /// Wrong key commitment length
+    WrongKeyCommitmentLength,
+    /// Unlock token is expired or already used
+    ExpiredToken,
+    /// Temporary link signature doesn't match
+    InvalidSignature,
+    /// Temporary link has expired
+    ExpiredLink,
+    /// Link opened outside of Telegram is not signed
+    UnsignedLink,
+    /// Web App is built without a key to verify temporary links
+    UnverifiableLink,
+    /// Failed to fetch record by unlock token: {0}
+    Fetching(String),
     /// Failed to decrypt data
     Decryption(#[from] telepass_crypto::Error),
     /// Failed to deserialize data: {0}
@@ -30,21 +61,203 @@ pub enum Error {
 /// Components result type.
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
+impl Error {
+    /// Short error code to be shown to the user.
+    pub const fn code(&self) -> &'static str {
+        match *self {
+            Self::ParamsParsing(_) => "SHOW_PARAMS_PARSING",
+            Self::MissingParams => "SHOW_MISSING_PARAMS",
+            Self::Base64Decoding(_) => "SHOW_BASE64_DECODING",
+            Self::UnknownAlgorithm => "SHOW_UNKNOWN_ALGORITHM",
+            Self::WrongSaltLength => "SHOW_WRONG_SALT_LENGTH",
+            Self::WrongKdfSaltLength => "SHOW_WRONG_KDF_SALT_LENGTH",
+            Self::WrongKeyCommitmentLength => "SHOW_WRONG_KEY_COMMITMENT_LENGTH",
+            Self::ExpiredToken => "SHOW_EXPIRED_TOKEN",
+            Self::InvalidSignature => "SHOW_INVALID_SIGNATURE",
+            Self::ExpiredLink => "SHOW_EXPIRED_LINK",
+            Self::UnsignedLink => "SHOW_UNSIGNED_LINK",
+            Self::UnverifiableLink => "SHOW_UNVERIFIABLE_LINK",
+            Self::Fetching(_) => "SHOW_FETCHING",
+            Self::Decryption(telepass_crypto::Error::WrongPassword) => "SHOW_WRONG_PASSWORD",
+            Self::Decryption(telepass_crypto::Error::CorruptedData) => "SHOW_CORRUPTED_DATA",
+            Self::Decryption(_) => "SHOW_DECRYPTION",
+            Self::Deserialization(_) => "SHOW_DESERIALIZATION",
+        }
+    }
+
+    /// Human-readable explanation of the error.
+    pub const fn human_message(&self) -> &'static str {
+        match *self {
+            Self::ParamsParsing(_)
+            | Self::MissingParams
+            | Self::Base64Decoding(_)
+            | Self::UnknownAlgorithm
+            | Self::WrongSaltLength
+            | Self::WrongKdfSaltLength
+            | Self::WrongKeyCommitmentLength
+            | Self::InvalidSignature => {
+                "This link is broken. Please, open the record from the bot once again."
+            }
+            Self::ExpiredLink => {
+                "This temporary link has expired. Please, create a new one in the bot."
+            }
+            Self::UnsignedLink => {
+                "Only temporary links can be opened outside of Telegram. \
+                 Please, create one in the bot."
+            }
+            Self::UnverifiableLink => "Temporary links are not supported by this Web App.",
+            Self::ExpiredToken => {
+                "This link has expired or was already used. \
+                 Please, open the record from the bot once again."
+            }
+            Self::Fetching(_) => "Failed to load the record. Please, try again later.",
+            Self::Decryption(telepass_crypto::Error::WrongPassword) => {
+                "Wrong master password. Please, try again."
+            }
+            Self::Decryption(telepass_crypto::Error::CorruptedData) => {
+                "The record is corrupted and can't be decrypted with any master password."
+            }
+            Self::Decryption(_) => {
+                "Failed to decrypt the record. Please, check your master password and try again."
+            }
+            Self::Deserialization(_) => "The record is decrypted, but its content is malformed.",
+        }
+    }
+
+    /// Check if retrying with another master password can help.
+    pub const fn is_retryable(&self) -> bool {
+        matches!(
+            *self,
+            Self::Decryption(
+                telepass_crypto::Error::WrongPassword | telepass_crypto::Error::Decryption
+            )
+        )
+    }
+}
+
+impl From<signed_link::Error> for Error {
+    fn from(e: signed_link::Error) -> Self {
+        match e {
+            signed_link::Error::Expired(_) => Self::ExpiredLink,
+            signed_link::Error::MissingSignature => Self::UnsignedLink,
+            signed_link::Error::EmptyKey
+            | signed_link::Error::KeyDecoding(_)
+            | signed_link::Error::MissingExpiration
+            | signed_link::Error::MalformedExpiration
+            | signed_link::Error::InvalidSignature => Self::InvalidSignature,
+        }
+    }
+}
+
 impl From<serde_json::Error> for Error {
     fn from(e: serde_json::Error) -> Self {
         Self::Deserialization(e.to_string())
     }
 }
 
-/// Query parameters for `/show` url.
+/// Encrypted record to show.
 #[derive(Clone)]
+struct EncryptedRecord {
+    /// Encrypted payload with password and etc.
+    output: telepass_crypto::EncryptionOutput,
+    /// Whether the payload is bound to the name of the resource it's shown with.
+    bound_to_resource_name: bool,
+}
+
+impl From<telepass_crypto::UrlQueryError> for Error {
+    fn from(e: telepass_crypto::UrlQueryError) -> Self {
+        match e {
+            telepass_crypto::UrlQueryError::Base64(err) => Self::Base64Decoding(err),
+            telepass_crypto::UrlQueryError::UnknownAlgorithm(_) => Self::UnknownAlgorithm,
+            telepass_crypto::UrlQueryError::WrongSaltLength(_) => Self::WrongSaltLength,
+            telepass_crypto::UrlQueryError::WrongKdfSaltLength(_) => Self::WrongKdfSaltLength,
+            telepass_crypto::UrlQueryError::WrongKeyCommitmentLength(_) => {
+                Self::WrongKeyCommitmentLength
+            }
+        }
+    }
+}
+
+impl EncryptedRecord {
+    /// Decode [`EncryptedRecord`] from url `query`.
+    fn decode(query: &telepass_crypto::UrlQuery, bound_to_resource_name: bool) -> Result<Self> {
+        let output = telepass_crypto::EncryptionOutput::from_url_query(query)?;
+
+        Ok(Self {
+            output,
+            bound_to_resource_name,
+        })
+    }
+
+    /// Fetch [`EncryptedRecord`] by one-time unlock token.
+    #[expect(clippy::future_not_send, reason = "JS futures are never `Send`")]
+    async fn fetch(unlock_url: &str) -> Result<Self> {
+        /// Body of a successful unlock endpoint response.
+        #[derive(Deserialize)]
+        struct UnlockedRecord {
+            /// Encoded encryption output.
+            #[serde(flatten)]
+            query: telepass_crypto::UrlQuery,
+            /// Whether the payload is bound to the resource name, missing in responses of older
+            /// bots.
+            #[serde(default)]
+            bound_to_resource_name: bool,
+        }
+
+        /// Convert JS error into [`Error::Fetching`].
+        fn fetching_error(err: &JsValue) -> Error {
+            Error::Fetching(format!("{err:?}"))
+        }
+
+        let window =
+            web_sys::window().ok_or_else(|| Error::Fetching("No window found".to_owned()))?;
+        let response: web_sys::Response = JsFuture::from(window.fetch_with_str(unlock_url))
+            .await
+            .map_err(|err| fetching_error(&err))?
+            .dyn_into()
+            .map_err(|err| fetching_error(&err))?;
+
+        if response.status() == NOT_FOUND_STATUS {
+            return Err(Error::ExpiredToken);
+        }
+        if !response.ok() {
+            return Err(Error::Fetching(format!(
+                "Unexpected response status {}",
+                response.status()
+            )));
+        }
+
+        let body = JsFuture::from(response.text().map_err(|err| fetching_error(&err))?)
+            .await
+            .map_err(|err| fetching_error(&err))?
+            .as_string()
+            .ok_or_else(|| Error::Fetching("Response body is not a string".to_owned()))?;
+        let unlocked: UnlockedRecord =
+            serde_json::from_str(&body).map_err(|err| Error::Fetching(err.to_string()))?;
+
+        Self::decode(&unlocked.query, unlocked.bound_to_resource_name)
+    }
+}
+
+/// HTTP status returned by the unlock endpoint for unknown, used or expired tokens.
+const NOT_FOUND_STATUS: u16 = 404;
+
+/// Where to get an [`EncryptedRecord`] from.
+enum RecordSource {
+    /// Record is passed directly in url.
+    Inline(EncryptedRecord),
+    /// Record should be fetched by one-time token from the url.
+    Unlock(String),
+}
+
+/// Query parameters for `/show` url.
 struct QueryParams {
     /// Name of the displayed resource.
     resource_name: Option<String>,
-    /// Encrypted payload with password and etc.
-    payload: Vec<u8>,
-    /// Salt used for encryption.
-    salt: telepass_crypto::Salt,
+    /// Source of the encrypted record.
+    source: RecordSource,
+    /// Whether the bot asked to keep the Mini App compact, see [`Page::Show`].
+    compact: Option<bool>,
 }
 
 /// [`QueryParams`] candidate which is easy to parse.
@@ -56,101 +269,762 @@ struct QueryParamsCandidate {
     payload: Option<String>,
     /// Salt used for encryption.
     salt: Option<String>,
+    /// Number of key derivation iterations used for encryption.
+    kdf_iterations: Option<u32>,
+    /// Salt used for key derivation.
+    kdf_salt: Option<String>,
+    /// Encryption algorithm name.
+    algorithm: Option<String>,
+    /// Commitment to the key used for encryption.
+    key_commitment: Option<String>,
+    /// Version of the encryption output.
+    version: Option<u8>,
+    /// Whether the payload is bound to `resource_name`.
+    bound_to_resource_name: Option<bool>,
+    /// One-time token to fetch payload and salt with.
+    token: Option<String>,
+    /// Url of the endpoint resolving `token`.
+    unlock_endpoint: Option<String>,
+    /// Whether to keep the Mini App compact.
+    compact: Option<bool>,
 }
 
 impl QueryParams {
-    /// Parse [`QueryParams`] from url.
-    fn parse_from_url() -> Result<Self> {
+    /// Parse [`QueryParams`] from url, verifying it with [`verify_link()`].
+    fn parse_from_url(in_telegram: bool) -> Result<Self> {
+        verify_link(
+            use_query_map()
+                .get_untracked()
+                .0
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str())),
+            temp_link_key().as_ref(),
+            in_telegram,
+            now_seconds(),
+        )?;
+
         let candidate = use_query::<QueryParamsCandidate>().get_untracked()?;
 
-        let (Some(payload), Some(salt)) = (candidate.payload, candidate.salt) else {
+        let source = if let (Some(payload), Some(salt)) = (candidate.payload, candidate.salt) {
+            let query = telepass_crypto::UrlQuery {
+                payload,
+                salt,
+                kdf_iterations: candidate.kdf_iterations,
+                kdf_salt: candidate.kdf_salt,
+                algorithm: candidate.algorithm,
+                key_commitment: candidate.key_commitment,
+                version: candidate.version,
+            };
+            RecordSource::Inline(EncryptedRecord::decode(
+                &query,
+                candidate.bound_to_resource_name.unwrap_or_default(),
+            )?)
+        } else if let (Some(token), Some(unlock_endpoint)) =
+            (candidate.token, candidate.unlock_endpoint)
+        {
+            RecordSource::Unlock(format!("{}/{token}", unlock_endpoint.trim_end_matches('/')))
+        } else {
             return Err(Error::MissingParams);
         };
 
-        let payload = URL_SAFE.decode(payload)?;
-
-        let salt = URL_SAFE.decode(salt)?;
-        let salt = salt.try_into().map_err(|_err| Error::WrongSaltLength)?;
-
         Ok(Self {
             resource_name: candidate.resource_name,
-            payload,
-            salt,
+            source,
+            compact: candidate.compact,
         })
     }
 }
 
+/// Get key verifying temporary links.
+///
+/// It's passed at build time in `TEMP_LINK_VERIFICATION_KEY` and must be the same as the bot's
+/// `TEMP_LINK_SIGNING_KEY`. It's public, see [`signed_link`] for what it protects from.
+fn temp_link_key() -> Option<LinkKey> {
+    option_env!("TEMP_LINK_VERIFICATION_KEY").map(|key| {
+        LinkKey::from_base64(key).expect("`TEMP_LINK_VERIFICATION_KEY` should be a valid key")
+    })
+}
+
+/// Get current Unix timestamp in seconds.
+#[expect(
+    clippy::as_conversions,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "milliseconds since the epoch are positive and fit into `u64`"
+)]
+fn now_seconds() -> u64 {
+    std::time::Duration::from_millis(js_sys::Date::now() as u64).as_secs()
+}
+
+/// Verify the link with query `params` at `now` if it's a temporary one.
+///
+/// Links opened `in_telegram` by the bot buttons are not signed, so signature is required
+/// only outside of Telegram and only if the Web App is built with a `key`.
+fn verify_link<'param>(
+    params: impl IntoIterator<Item = (&'param str, &'param str)>,
+    key: Option<&LinkKey>,
+    in_telegram: bool,
+    now: u64,
+) -> Result<()> {
+    let params: Vec<_> = params.into_iter().collect();
+    let is_signed = params.iter().any(|&(name, _value)| {
+        name == signed_link::SIGNATURE_PARAM || name == signed_link::EXPIRATION_PARAM
+    });
+
+    match key {
+        None if is_signed => Err(Error::UnverifiableLink),
+        None => Ok(()),
+        Some(_) if in_telegram && !is_signed => Ok(()),
+        Some(key) => key.verify(params, now).map_err(Into::into),
+    }
+}
+
+/// Decrypt `record` of `resource_name` with `master_password` skipping key derivation for
+/// `rejected_passwords`.
+///
+/// Bound payloads are authenticated with `resource_name`, so a payload moved to another record
+/// fails to decrypt. Password is added to `rejected_passwords` if it's wrong or, for records
+/// without key commitment, if it fails to decrypt the record for whatever reason.
+fn decrypt_payload(
+    record: EncryptedRecord,
+    resource_name: &str,
+    master_password: &str,
+    rejected_passwords: &mut Vec<telepass_crypto::PasswordVerifier>,
+) -> Result<RecordPayload> {
+    if rejected_passwords
+        .iter()
+        .any(|verifier| verifier.matches(master_password))
+    {
+        return Err(Error::Decryption(
+            if record.output.key_commitment.is_some() {
+                telepass_crypto::Error::WrongPassword
+            } else {
+                telepass_crypto::Error::Decryption
+            },
+        ));
+    }
+
+    let aad = if record.bound_to_resource_name {
+        resource_name.as_bytes()
+    } else {
+        &[]
+    };
+    let decrypted = telepass_crypto::decrypt_with_aad(record.output, master_password, aad)
+        .inspect_err(|err| {
+            if matches!(
+                *err,
+                telepass_crypto::Error::WrongPassword | telepass_crypto::Error::Decryption
+            ) {
+                rejected_passwords.push(telepass_crypto::PasswordVerifier::new(master_password));
+            }
+        })?;
+
+    RecordPayload::from_json_str(&decrypted).map_err(|err| Error::Deserialization(err.to_string()))
+}
+
+/// Component to show decrypted record.
+///
+/// Errors are shown with [`ErrorView`] instead of the form.
 #[component]
 pub fn Show(
-    /// Writer to set the result of user action.
-    set_result: WriteSignal<Result<()>>,
+    /// Telegram API.
+    web_app: Rc<WebApp>,
 ) -> impl IntoView {
-    let query_params = QueryParams::parse_from_url()
-        .map_err(|err| {
-            set_result(Err(err));
-        })
-        .ok();
-
-    let (resource_name, set_resource_name) = create_record_form_parameter(
-        query_params
-            .as_ref()
-            .and_then(|params| params.resource_name.clone())
-            .unwrap_or_default(),
-        true,
+    let (error, set_error) = create_signal(None);
+    let (encrypted_record, set_encrypted_record) = create_signal(None);
+
+    let (resource_name, compact) = match QueryParams::parse_from_url(web_app.user().is_some()) {
+        Ok(QueryParams {
+            resource_name,
+            source,
+            compact,
+        }) => {
+            match source {
+                RecordSource::Inline(record) => set_encrypted_record(Some(record)),
+                RecordSource::Unlock(unlock_url) => spawn_local(async move {
+                    match EncryptedRecord::fetch(&unlock_url).await {
+                        Ok(record) => set_encrypted_record(Some(record)),
+                        Err(err) => set_error(Some(err)),
+                    }
+                }),
+            }
+            (resource_name, compact)
+        }
+        Err(err) => {
+            set_error(Some(err));
+            (None, None)
+        }
+    };
+    viewport::expand_for(
+        &web_app,
+        Page::Show {
+            compact,
+            has_comments: false,
+        },
     );
+
+    let resource_name = resource_name.unwrap_or_default();
+    // Kept apart from the form, so that the payload is authenticated with the name from the url
+    let bound_name = store_value(resource_name.clone());
+    let (resource_name, _) = create_record_form_parameter(resource_name, true);
     let (login, set_login) = create_record_form_parameter(String::new(), true);
     let (password, set_password) = create_record_form_parameter(String::new(), true);
     let (comments, set_comments) = create_record_form_parameter(String::new(), true);
     let master_password_element = create_node_ref::<Input>();
+    // Verifiers of master passwords which failed to decrypt the record,
+    // so that retrying with the same password doesn't run slow key derivation again
+    let rejected_passwords = store_value(Vec::<telepass_crypto::PasswordVerifier>::new());
 
+    // Stored to keep `on_decrypt` copyable
+    let decrypt_web_app = store_value(Rc::clone(&web_app));
     let on_decrypt = move |event: SubmitEvent| {
         event.prevent_default(); // Prevent page reload
 
-        let Some(query_params) = query_params.clone() else {
-            return;
+        let Some(record) = encrypted_record.get_untracked() else {
+            return; // Record is not fetched yet
         };
 
         let master_password = master_password_element()
             .expect("No master_password element")
             .value();
 
-        let payload = telepass_crypto::decrypt(
-            telepass_crypto::EncryptionOutput {
-                encrypted_payload: query_params.payload,
-                salt: query_params.salt,
-            },
-            &master_password,
-        )
-        .map_err(Error::from)
-        .and_then(|record| serde_json::from_str::<Payload>(&record).map_err(Into::into));
+        let Some(payload) = rejected_passwords.try_update_value(|rejected| {
+            bound_name.with_value(|name| decrypt_payload(record, name, &master_password, rejected))
+        }) else {
+            return; // Component is already disposed
+        };
 
         let payload = match payload {
-            Ok(payload) => {
-                set_result(Ok(()));
-                payload
-            }
+            Ok(payload) => payload,
             Err(err) => {
-                set_result(Err(err));
+                set_error(Some(err));
                 return;
             }
         };
 
-        set_resource_name.value.set(payload.resource_name);
-        set_login.value.set(payload.login);
+        let page = Page::Show {
+            compact,
+            has_comments: payload.notes.is_some(),
+        };
+        decrypt_web_app.with_value(|stored| viewport::expand_for(stored, page));
+        set_login.value.set(payload.login.unwrap_or_default());
         set_password.value.set(payload.password);
-        set_comments.value.set(payload.comments);
+        set_comments.value.set(payload.notes.unwrap_or_default());
     };
 
-    view! {
-        <RecordForm
-            resource_name=resource_name
-            login=login
-            password=password
-            comments=comments
-            master_password_element=master_password_element
-            copy_buttons_enabled=true
-            submit_value="Decrypt"
-            on_submit=on_decrypt
-        />
+    move || {
+        error.get().map_or_else(
+            || {
+                view! {
+                    <RecordForm
+                        resource_name=resource_name
+                        login=login
+                        password=password
+                        comments=comments
+                        master_password_element=master_password_element
+                        copy_buttons_enabled=true
+                        submit_value="Decrypt"
+                        on_submit=on_decrypt
+                    />
+                }
+            },
+            |err| {
+                let on_retry = err
+                    .is_retryable()
+                    .then(|| Callback::new(move |()| set_error(None)));
+                view! {
+                    <ErrorView
+                        web_app=Rc::clone(&web_app)
+                        message=err.human_message().to_owned()
+                        code=err.code()
+                        on_retry=on_retry
+                    />
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::URL_SAFE, Engine as _};
+
+    use super::*;
+
+    /// Construct url query with `b"payload"` payload.
+    fn query(
+        salt: &str,
+        kdf_iterations: Option<u32>,
+        kdf_salt: Option<&str>,
+        algorithm: Option<&str>,
+    ) -> telepass_crypto::UrlQuery {
+        telepass_crypto::UrlQuery {
+            payload: "cGF5bG9hZA==".to_owned(),
+            salt: salt.to_owned(),
+            kdf_iterations,
+            kdf_salt: kdf_salt.map(ToOwned::to_owned),
+            algorithm: algorithm.map(ToOwned::to_owned),
+            key_commitment: None,
+            version: None,
+        }
+    }
+
+    #[test]
+    fn decryption_error_is_retryable() {
+        let error = Error::Decryption(telepass_crypto::Error::Decryption);
+
+        assert!(error.is_retryable());
+        assert_eq!(error.code(), "SHOW_DECRYPTION");
+    }
+
+    #[test]
+    fn wrong_password_is_retryable_but_corrupted_data_is_not() {
+        let wrong_password = Error::Decryption(telepass_crypto::Error::WrongPassword);
+        assert!(wrong_password.is_retryable());
+        assert_eq!(wrong_password.code(), "SHOW_WRONG_PASSWORD");
+
+        let corrupted = Error::Decryption(telepass_crypto::Error::CorruptedData);
+        assert!(!corrupted.is_retryable());
+        assert_eq!(corrupted.code(), "SHOW_CORRUPTED_DATA");
+    }
+
+    #[test]
+    fn expired_token_error_is_not_retryable() {
+        let error = Error::ExpiredToken;
+
+        assert!(!error.is_retryable());
+        assert_eq!(error.code(), "SHOW_EXPIRED_TOKEN");
+    }
+
+    #[test]
+    #[expect(clippy::expect_used, reason = "it's ok in tests")]
+    fn repeated_wrong_password_is_rejected_until_correct_one() {
+        let payload = RecordPayload {
+            login: Some("login".to_owned()),
+            password: "secret".to_owned(),
+            ..RecordPayload::default()
+        };
+        let output = telepass_crypto::encrypt(&payload.to_json_string(), "password", None)
+            .expect("Failed to encrypt payload");
+        let record = EncryptedRecord {
+            output,
+            bound_to_resource_name: false,
+        };
+        let mut rejected_passwords = Vec::new();
+
+        for _ in 0..2_u8 {
+            let error = decrypt_payload(record.clone(), "", "wrong", &mut rejected_passwords)
+                .expect_err("Wrong password is expected to fail");
+            assert!(matches!(
+                error,
+                Error::Decryption(telepass_crypto::Error::WrongPassword)
+            ));
+            assert_eq!(rejected_passwords.len(), 1);
+        }
+
+        let decrypted = decrypt_payload(record, "", "password", &mut rejected_passwords)
+            .expect("Failed to decrypt with the correct password");
+        assert_eq!(decrypted.password, "secret");
+        assert_eq!(rejected_passwords.len(), 1);
+    }
+
+    #[test]
+    #[expect(clippy::expect_used, reason = "it's ok in tests")]
+    fn bound_payload_is_decrypted_only_with_its_resource_name() {
+        let payload = serde_json::json!({
+            "resource_name": "github.com",
+            "login": "login",
+            "password": "secret",
+            "comments": "",
+        });
+        let output = telepass_crypto::encrypt_with_aad(
+            &payload.to_string(),
+            "password",
+            None,
+            b"github.com",
+        )
+        .expect("Failed to encrypt payload");
+        let query = output.to_url_query();
+        let decode = |bound_to_resource_name| {
+            EncryptedRecord::decode(&query, bound_to_resource_name)
+                .expect("Failed to decode record")
+        };
+
+        // Payload moved to another record or stripped of its binding
+        for (resource_name, bound_to_resource_name) in
+            [("bank.com", true), ("", true), ("github.com", false)]
+        {
+            let error = decrypt_payload(
+                decode(bound_to_resource_name),
+                resource_name,
+                "password",
+                &mut Vec::new(),
+            )
+            .expect_err("Decryption with another resource name is expected to fail");
+            assert!(matches!(
+                error,
+                Error::Decryption(telepass_crypto::Error::CorruptedData)
+            ));
+        }
+
+        let decrypted = decrypt_payload(decode(true), "github.com", "password", &mut Vec::new())
+            .expect("Failed to decrypt with the bound resource name");
+        assert_eq!(decrypted.password, "secret");
+    }
+
+    #[test]
+    #[expect(clippy::expect_used, reason = "it's ok in tests")]
+    fn missing_kdf_iterations_fall_back_to_default() {
+        let salt = URL_SAFE.encode([0; telepass_crypto::AES_256_GCM_SALT_SIZE]);
+
+        for (kdf_iterations, expected) in [
+            (None, telepass_crypto::DEFAULT_KDF_ITERATIONS),
+            (Some(0), telepass_crypto::DEFAULT_KDF_ITERATIONS),
+            (Some(200_000), 200_000),
+        ] {
+            let record = EncryptedRecord::decode(&query(&salt, kdf_iterations, None, None), false)
+                .expect("Failed to decode record");
+            assert_eq!(record.output.kdf_iterations, expected);
+        }
+    }
+
+    #[test]
+    #[expect(clippy::expect_used, reason = "it's ok in tests")]
+    fn kdf_salt_is_decoded_if_present() {
+        let salt = URL_SAFE.encode([0; telepass_crypto::AES_256_GCM_SALT_SIZE]);
+        let kdf_salt = [1; telepass_crypto::KDF_SALT_SIZE];
+
+        for (encoded_kdf_salt, expected) in [
+            (None, None),
+            (Some(String::new()), None),
+            (Some(URL_SAFE.encode(kdf_salt)), Some(kdf_salt)),
+        ] {
+            let record = EncryptedRecord::decode(
+                &query(&salt, None, encoded_kdf_salt.as_deref(), None),
+                false,
+            )
+            .expect("Failed to decode record");
+            assert_eq!(record.output.kdf_salt, expected);
+        }
+
+        assert!(matches!(
+            EncryptedRecord::decode(&query(&salt, None, Some("a2RmX3NhbHQ="), None), false),
+            Err(Error::WrongKdfSaltLength)
+        ));
+    }
+
+    #[test]
+    #[expect(clippy::expect_used, reason = "it's ok in tests")]
+    fn salt_is_decoded_for_algorithm() {
+        let aes_salt = URL_SAFE.encode([0; telepass_crypto::AES_256_GCM_SALT_SIZE]);
+        let xchacha_salt = URL_SAFE.encode([0; telepass_crypto::XCHACHA20_POLY1305_SALT_SIZE]);
+
+        for (salt, algorithm, expected) in [
+            (&aes_salt, None, telepass_crypto::Algorithm::Aes256Gcm),
+            (&aes_salt, Some(""), telepass_crypto::Algorithm::Aes256Gcm),
+            (
+                &xchacha_salt,
+                Some("xchacha20-poly1305"),
+                telepass_crypto::Algorithm::XChaCha20Poly1305,
+            ),
+        ] {
+            let record = EncryptedRecord::decode(&query(salt, None, None, algorithm), false)
+                .expect("Failed to decode record");
+            assert_eq!(record.output.algorithm(), expected);
+        }
+
+        assert!(matches!(
+            EncryptedRecord::decode(
+                &query(&aes_salt, None, None, Some("xchacha20-poly1305")),
+                false
+            ),
+            Err(Error::WrongSaltLength)
+        ));
+        assert!(matches!(
+            EncryptedRecord::decode(&query(&aes_salt, None, None, Some("rot13")), false),
+            Err(Error::UnknownAlgorithm)
+        ));
+    }
+
+    #[test]
+    #[expect(clippy::expect_used, reason = "it's ok in tests")]
+    fn temp_link_is_verified_outside_of_telegram() {
+        const NOW: u64 = 1_700_000_000;
+        let key = LinkKey::new(b"link key").expect("Failed to create key");
+        let params = [("payload", "cGF5bG9hZA=="), ("salt", "AQEBAQEBAQEBAQEB")];
+        let signature = key.sign(params, NOW + 15 * 60);
+        let link: Vec<_> = params
+            .into_iter()
+            .chain(signature.iter().map(|param| (param.0, param.1.as_str())))
+            .collect();
+
+        for in_telegram in [false, true] {
+            verify_link(link.clone(), Some(&key), in_telegram, NOW)
+                .expect("Signed link is expected to be valid");
+        }
+        assert!(matches!(
+            verify_link(link.clone(), Some(&key), false, NOW + 15 * 60),
+            Err(Error::ExpiredLink)
+        ));
+
+        let tampered: Vec<_> = link
+            .iter()
+            .map(|&(name, value)| {
+                if name == signed_link::EXPIRATION_PARAM {
+                    (name, "1800000000")
+                } else {
+                    (name, value)
+                }
+            })
+            .collect();
+        assert!(matches!(
+            verify_link(tampered, Some(&key), false, NOW),
+            Err(Error::InvalidSignature)
+        ));
+
+        assert!(matches!(
+            verify_link(link, None, false, NOW),
+            Err(Error::UnverifiableLink)
+        ));
+    }
+
+    #[test]
+    #[expect(clippy::expect_used, reason = "it's ok in tests")]
+    fn unsigned_link_is_accepted_only_in_telegram() {
+        let key = LinkKey::new(b"link key").expect("Failed to create key");
+        let params = [("payload", "cGF5bG9hZA=="), ("salt", "AQEBAQEBAQEBAQEB")];
+
+        verify_link(params, Some(&key), true, 0).expect("Telegram links are not signed");
+        verify_link(params, None, false, 0).expect("Links are not verified without a key");
+        assert!(matches!(
+            verify_link(params, Some(&key), false, 0),
+            Err(Error::UnsignedLink)
+        ));
+    }
+
+    #[test]
+    fn broken_link_errors_are_not_retryable() {
+        for error in [
+            Error::MissingParams,
+            Error::UnknownAlgorithm,
+            Error::WrongSaltLength,
+            Error::WrongKdfSaltLength,
+            Error::InvalidSignature,
+        ] {
+            assert!(!error.is_retryable());
+            assert_eq!(
+                error.human_message(),
+                "This link is broken. Please, open the record from the bot once again."
+            );
+        }
+    }
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    mod browser {
+        //! Tests rendering components in a browser.
+        //!
+        //! Run with `cargo test -p telepass_web_app --target wasm32-unknown-unknown`,
+        //! which needs `wasm-bindgen-test-runner` from `wasm-bindgen-cli` of the same version as
+        //! `wasm-bindgen` and a browser driver like `geckodriver`.
+
+        #![expect(
+            clippy::unwrap_used,
+            reason = "`wasm_bindgen_test` functions are not recognized as tests"
+        )]
+        #![expect(clippy::future_not_send, reason = "JS futures are never `Send`")]
+
+        use std::cell::Cell;
+
+        use js_sys::{Function, Object, Reflect};
+        use leptos_router::Router;
+        use wasm_bindgen::JsCast;
+        use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+        use web_sys::{HtmlElement, HtmlFormElement, HtmlInputElement};
+
+        use super::*;
+
+        wasm_bindgen_test_configure!(run_in_browser);
+
+        /// Construct Telegram API of the Mini App opened outside of Telegram.
+        fn web_app_stub() -> Rc<WebApp> {
+            let web_app = Object::new();
+            for (name, value) in [
+                ("initData", JsValue::from_str("")),
+                ("isExpanded", JsValue::TRUE),
+                ("expand", Function::new_no_args("").into()),
+                ("close", Function::new_no_args("").into()),
+            ] {
+                Reflect::set(&web_app, &JsValue::from_str(name), &value).unwrap();
+            }
+            Rc::new(web_app.unchecked_into())
+        }
+
+        /// Mount view returned by `f` into a new element of the document and get the element.
+        fn mount<V: IntoView>(f: impl FnOnce() -> V + 'static) -> HtmlElement {
+            let document = web_sys::window().unwrap().document().unwrap();
+            let container: HtmlElement = document.create_element("div").unwrap().unchecked_into();
+            document.body().unwrap().append_child(&container).unwrap();
+            leptos::mount_to(container.clone(), f);
+            container
+        }
+
+        /// Find the first element matching `selector` in `container`.
+        fn find<T: JsCast>(container: &HtmlElement, selector: &str) -> T {
+            container
+                .query_selector(selector)
+                .unwrap()
+                .unwrap()
+                .unchecked_into()
+        }
+
+        /// Let pending reactive updates reach the document.
+        async fn next_tick() {
+            JsFuture::from(js_sys::Promise::resolve(&JsValue::NULL))
+                .await
+                .unwrap();
+        }
+
+        /// Replace query of the page url with `query`.
+        fn set_url_query(query: &telepass_crypto::UrlQuery) {
+            let params = [
+                ("payload", Some(query.payload.clone())),
+                ("salt", Some(query.salt.clone())),
+                (
+                    "kdf_iterations",
+                    query.kdf_iterations.map(|i| i.to_string()),
+                ),
+                ("kdf_salt", query.kdf_salt.clone()),
+                ("algorithm", query.algorithm.clone()),
+                ("key_commitment", query.key_commitment.clone()),
+                ("version", query.version.map(|version| version.to_string())),
+            ];
+            let search = params
+                .into_iter()
+                .filter_map(|(name, value)| {
+                    let value = value?;
+                    Some(format!(
+                        "{name}={}",
+                        String::from(js_sys::encode_uri_component(&value))
+                    ))
+                })
+                .collect::<Vec<_>>()
+                .join("&");
+
+            web_sys::window()
+                .unwrap()
+                .history()
+                .unwrap()
+                .replace_state_with_url(&JsValue::NULL, "", Some(&format!("?{search}")))
+                .unwrap();
+        }
+
+        /// Submit record form in `container` with `master_password`.
+        async fn submit_master_password(container: &HtmlElement, master_password: &str) {
+            find::<HtmlInputElement>(container, "#master-password").set_value(master_password);
+            find::<HtmlFormElement>(container, "form")
+                .request_submit()
+                .unwrap();
+            next_tick().await;
+        }
+
+        #[wasm_bindgen_test]
+        fn error_view_shows_message_and_code_and_retries() {
+            let retries = Rc::new(Cell::new(0_u8));
+            let counted_retries = Rc::clone(&retries);
+            let container = mount(move || {
+                view! {
+                    <ErrorView
+                        web_app=web_app_stub()
+                        message="Something went wrong".to_owned()
+                        code="TEST_ERROR"
+                        on_retry=Some(Callback::new(move |()| {
+                            counted_retries.set(counted_retries.get().saturating_add(1));
+                        }))
+                    />
+                }
+            });
+
+            assert_eq!(
+                find::<HtmlElement>(&container, ".error").inner_text(),
+                "Something went wrong"
+            );
+            assert_eq!(
+                find::<HtmlElement>(&container, ".error-code").inner_text(),
+                "Error code: TEST_ERROR"
+            );
+
+            let retry_button = find::<HtmlElement>(&container, ".error-actions button");
+            assert_eq!(retry_button.inner_text(), "Try again");
+            retry_button.click();
+            assert_eq!(retries.get(), 1);
+        }
+
+        #[wasm_bindgen_test]
+        fn error_view_without_retry_only_closes() {
+            let container = mount(|| {
+                view! {
+                    <ErrorView
+                        web_app=web_app_stub()
+                        message="Something went wrong".to_owned()
+                        code="TEST_ERROR"
+                        on_retry=None
+                    />
+                }
+            });
+
+            let buttons = container
+                .query_selector_all(".error-actions button")
+                .unwrap();
+            assert_eq!(buttons.length(), 1);
+            assert_eq!(
+                find::<HtmlElement>(&container, ".error-actions button").inner_text(),
+                "Close"
+            );
+        }
+
+        #[wasm_bindgen_test]
+        async fn show_retries_after_wrong_master_password() {
+            let payload = RecordPayload {
+                login: Some("login".to_owned()),
+                password: "secret".to_owned(),
+                ..RecordPayload::default()
+            };
+            let output = telepass_crypto::encrypt(
+                &payload.to_json_string(),
+                "password",
+                Some(telepass_crypto::EncryptParams {
+                    kdf_iterations: 1000,
+                    ..telepass_crypto::EncryptParams::default()
+                }),
+            )
+            .unwrap();
+            set_url_query(&output.to_url_query());
+            let container = mount(|| view! { <Router><Show web_app=web_app_stub()/></Router> });
+
+            submit_master_password(&container, "wrong").await;
+            assert_eq!(
+                find::<HtmlElement>(&container, ".error").inner_text(),
+                "Wrong master password. Please, try again."
+            );
+            assert_eq!(
+                find::<HtmlElement>(&container, ".error-code").inner_text(),
+                "Error code: SHOW_WRONG_PASSWORD"
+            );
+
+            find::<HtmlElement>(&container, ".error-actions button").click();
+            next_tick().await;
+            assert!(container.query_selector(".error").unwrap().is_none());
+
+            submit_master_password(&container, "password").await;
+            assert!(container.query_selector(".error").unwrap().is_none());
+            assert_eq!(
+                find::<HtmlInputElement>(&container, "#login").value(),
+                "login"
+            );
+            assert_eq!(
+                find::<HtmlInputElement>(&container, "#password").value(),
+                "secret"
+            );
+        }
     }
 }
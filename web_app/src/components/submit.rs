@@ -1,36 +1,416 @@
 //! Module with [`Submit`] component implementation.
 
-use std::rc::Rc;
+use std::{collections::BTreeMap, rc::Rc};
 
 use leptos::{
-    component, create_node_ref,
+    component, create_memo, create_node_ref, create_signal, document, event_target,
     html::{Input, Textarea},
-    view, IntoView, WriteSignal,
+    request_animation_frame, view, IntoView, NodeRef, ReadSignal, Show, SignalGet as _,
+    SignalGetUntracked as _, WriteSignal,
 };
+use telepass_data_model::RecordPayload;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
 use web_sys::SubmitEvent;
+use zeroize::Zeroizing;
 
-use super::common::{create_record_form_parameter, Payload, RecordForm};
-use crate::tg_api::WebApp;
+use super::common::{create_record_form_parameter, FormItem, InputBox, RecordForm, TelegramUser};
+use crate::tg_api::{WebApp, WebAppUser};
 
 /// Error during new password submission.
 #[derive(Debug, Clone, thiserror::Error, displaydoc::Display)]
 pub enum Error {
     /// Invalid input: {0}
-    Validation(&'static str),
+    Validation(#[from] telepass_data_model::ResourceNameError),
+    /// Invalid record: {0}
+    InvalidRecord(#[from] telepass_data_model::BuildError),
+    /// Invalid tag: {0}
+    InvalidTag(#[from] telepass_data_model::TagError),
+    /// Invalid attachment: {0}
+    InvalidAttachment(#[from] telepass_data_model::AttachmentError),
+    /// Failed to read the attached file: {0}
+    ReadingAttachment(String),
     /// Failed to encrypt data
     Encryption(#[from] telepass_crypto::Error),
+    /// Master passwords don't match, the record is not submitted
+    MasterPasswordMismatch,
+    /// This page should be opened with a button in the chat with the bot
+    NotLaunchedFromBot,
     /// Failed to serialize data: {0}
     Serialization(String),
     /// Failed to send data to the bot backend: {0}
     Sending(String),
 }
 
+impl Error {
+    /// Get id of the field in the advanced section the error is about.
+    ///
+    /// [`None`] if the error is not about an advanced option.
+    #[expect(
+        clippy::ref_patterns,
+        reason = "conflicts with `pattern_type_mismatch`"
+    )]
+    fn advanced_field_id(&self) -> Option<&'static str> {
+        use telepass_data_model::BuildProblem;
+
+        match *self {
+            Self::InvalidTag(_) => Some(TAGS_ID),
+            Self::InvalidAttachment(_) | Self::ReadingAttachment(_) => Some(ATTACHMENT_ID),
+            Self::InvalidRecord(ref error) => {
+                error.problems().iter().find_map(|problem| match *problem {
+                    BuildProblem::DuplicateTag => Some(TAGS_ID),
+                    BuildProblem::DuplicateAttachmentFilename => Some(ATTACHMENT_ID),
+                    BuildProblem::MissingResourceName
+                    | BuildProblem::InvalidResourceName(_)
+                    | BuildProblem::MissingEncryptionOutput
+                    | BuildProblem::EmptyEncryptedPayload
+                    | BuildProblem::BlindIndexTooLarge
+                    | BuildProblem::UnsupportedBlindIndexVersion(_) => None,
+                })
+            }
+            Self::Validation(_)
+            | Self::Encryption(_)
+            | Self::MasterPasswordMismatch
+            | Self::NotLaunchedFromBot
+            | Self::Serialization(_)
+            | Self::Sending(_) => None,
+        }
+    }
+}
+
 impl From<serde_json::Error> for Error {
     fn from(e: serde_json::Error) -> Self {
         Self::Serialization(e.to_string())
     }
 }
 
+/// Id of the tags input in the advanced section.
+const TAGS_ID: &str = "tags";
+
+/// Id of the attachment input in the advanced section.
+const ATTACHMENT_ID: &str = "attachment";
+
+/// Key of the session storage item remembering if the advanced section is open.
+const ADVANCED_OPEN_KEY: &str = "telepass.submit.advanced-open";
+
+/// Read whether the advanced section was left open in this session.
+fn load_advanced_open() -> bool {
+    web_sys::window()
+        .and_then(|window| window.session_storage().ok().flatten())
+        .and_then(|storage| storage.get_item(ADVANCED_OPEN_KEY).ok().flatten())
+        .is_some_and(|value| value == "true")
+}
+
+/// Remember whether the advanced section is `open` for this session.
+///
+/// Failures are ignored, cause it's just a convenience.
+fn save_advanced_open(open: bool) {
+    if let Some(storage) =
+        web_sys::window().and_then(|window| window.session_storage().ok().flatten())
+    {
+        let _ignored = storage.set_item(ADVANCED_OPEN_KEY, if open { "true" } else { "false" });
+    }
+}
+
+/// Parse tags separated by commas or whitespace.
+///
+/// Tags are lowercased and duplicates are dropped, so that typing `Work, work` is not an error.
+fn parse_tags(input: &str) -> Result<Vec<telepass_data_model::Tag>, Error> {
+    let mut tags = Vec::new();
+    for word in input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|word| !word.is_empty())
+    {
+        let tag = telepass_data_model::Tag::try_from(word.to_lowercase())?;
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    Ok(tags)
+}
+
+/// File attached to the record before encryption.
+struct AttachedFile {
+    /// Name of the file.
+    filename: String,
+    /// Content of the file.
+    content: Vec<u8>,
+}
+
+/// Get additional authenticated data binding attachment with `filename` to `resource_name`.
+///
+/// NUL is allowed neither in resource names nor in filenames, so the result is unambiguous.
+fn attachment_aad(resource_name: &telepass_data_model::ResourceName, filename: &str) -> Vec<u8> {
+    format!("{resource_name}\0{filename}").into_bytes()
+}
+
+/// Encrypt attached `file` of `resource_name` with `master_password`.
+///
+/// Content is bound to the resource name and the filename, so it can't be moved to another
+/// record or swapped with another attachment.
+fn encrypt_attachment(
+    file: &AttachedFile,
+    resource_name: &telepass_data_model::ResourceName,
+    master_password: &str,
+) -> Result<telepass_data_model::NewAttachment, Error> {
+    let size = u64::try_from(file.content.len())
+        .map_err(|_err| telepass_data_model::AttachmentError::TooLarge)?;
+    // Check size before encryption, cause key derivation is slow
+    if size > telepass_data_model::MAX_ATTACHMENT_SIZE {
+        return Err(telepass_data_model::AttachmentError::TooLarge.into());
+    }
+
+    let encryption_output = telepass_crypto::encrypt_bytes(
+        &file.content,
+        master_password,
+        None,
+        &attachment_aad(resource_name, &file.filename),
+    )?;
+    Ok(telepass_data_model::NewAttachment::new(
+        file.filename.clone(),
+        encryption_output,
+        size,
+    )?)
+}
+
+/// Read the file selected in the `input` element if any.
+///
+/// # Errors
+///
+/// Fails if the file is larger than [`telepass_data_model::MAX_ATTACHMENT_SIZE`] or can't be read.
+#[expect(clippy::future_not_send, reason = "JS futures are never `Send`")]
+async fn read_attached_file(
+    input: &web_sys::HtmlInputElement,
+) -> Result<Option<AttachedFile>, Error> {
+    let Some(file) = input.files().and_then(|files| files.get(0)) else {
+        return Ok(None);
+    };
+    // Don't read huge files into memory just to reject them
+    let max_size = u32::try_from(telepass_data_model::MAX_ATTACHMENT_SIZE).unwrap_or(u32::MAX);
+    if file.size() > f64::from(max_size) {
+        return Err(telepass_data_model::AttachmentError::TooLarge.into());
+    }
+
+    let buffer = JsFuture::from(file.array_buffer())
+        .await
+        .map_err(|err| Error::ReadingAttachment(format!("{err:?}")))?;
+    Ok(Some(AttachedFile {
+        filename: file.name(),
+        content: js_sys::Uint8Array::new(&buffer).to_vec(),
+    }))
+}
+
+/// Data entered into the [`Submit`] form.
+struct SubmittedForm {
+    /// User who opened the app if launched from the bot.
+    user: Option<WebAppUser>,
+    /// Name of the resource as typed by the user.
+    resource_name: String,
+    /// Data to encrypt.
+    payload: RecordPayload,
+    /// Tags as typed by the user, empty if the advanced section was never opened.
+    tags: String,
+    /// Master password to encrypt with.
+    master_password: Zeroizing<String>,
+    /// Master password entered once again to verify the encryption with,
+    /// [`None`] if the verification is skipped.
+    master_password_confirmation: Option<Zeroizing<String>>,
+    /// Attached file, if any.
+    attachment: Option<AttachedFile>,
+    /// Whether to bind the payload to the resource name, so it can't be moved to another record.
+    ///
+    /// Bound records can't be duplicated or renamed, so it's opt-in.
+    bind_to_resource_name: bool,
+}
+
+/// Check that `encryption_output` of the payload bound to `aad` can be decrypted with
+/// `master_password_confirmation`.
+///
+/// Catches typos in the master password before the record becomes impossible to decrypt.
+fn verify_master_password(
+    encryption_output: &telepass_crypto::EncryptionOutput,
+    master_password_confirmation: &str,
+    aad: &[u8],
+) -> Result<(), Error> {
+    match telepass_crypto::decrypt_with_aad(
+        encryption_output.clone(),
+        master_password_confirmation,
+        aad,
+    ) {
+        Ok(payload) => {
+            // Payload is not needed, only the fact that it's decrypted
+            drop(Zeroizing::new(payload));
+            Ok(())
+        }
+        Err(telepass_crypto::Error::WrongPassword) => Err(Error::MasterPasswordMismatch),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Encrypt `form` into a message for the bot.
+fn encrypt_form(
+    form: SubmittedForm,
+) -> Result<telepass_data_model::WebAppMessage<telepass_data_model::NewRecord>, Error> {
+    let SubmittedForm {
+        user,
+        resource_name,
+        payload,
+        tags,
+        master_password,
+        master_password_confirmation,
+        attachment,
+        bind_to_resource_name,
+    } = form;
+    let user = user.ok_or(Error::NotLaunchedFromBot)?;
+
+    // Validate name and tags before encryption, cause key derivation is slow
+    let resource_name = telepass_data_model::ResourceName::try_from(resource_name.trim())?;
+    let tags = parse_tags(&tags)?;
+
+    // Password is not indexed, so it can't be guessed by searching for it
+    let blind_index_key = telepass_crypto::BlindIndexKey::derive(&master_password);
+    let blind_index = blind_index_key.index(&format!(
+        "{}\n{}",
+        payload.login.as_deref().unwrap_or_default(),
+        payload.notes.as_deref().unwrap_or_default()
+    ));
+    // Empty passwords are not reused, they are just not set
+    let password_fingerprint = (!payload.password.is_empty())
+        .then(|| blind_index_key.password_fingerprint(&payload.password));
+
+    let aad = if bind_to_resource_name {
+        resource_name.as_str().as_bytes()
+    } else {
+        &[]
+    };
+    let encryption_output =
+        telepass_crypto::encrypt_with_aad(&payload.to_json_string(), &master_password, None, aad)?;
+    if let Some(master_password_confirmation) = master_password_confirmation {
+        verify_master_password(&encryption_output, &master_password_confirmation, aad)?;
+    }
+
+    let attachments = attachment
+        .map(|file| encrypt_attachment(&file, &resource_name, &master_password))
+        .transpose()?
+        .into_iter()
+        .collect();
+
+    let mut new_record = telepass_data_model::NewRecord::builder()
+        .resource_name(resource_name)
+        .encryption_output(encryption_output)
+        .blind_index(blind_index)
+        .bound_to_resource_name(bind_to_resource_name)
+        .attachments(attachments)
+        .tags(tags);
+    if let Some(password_fingerprint) = password_fingerprint {
+        new_record = new_record.password_fingerprint(password_fingerprint);
+    }
+    let new_record = new_record.build()?;
+    Ok(telepass_data_model::WebAppMessage {
+        user_id: user.id,
+        data: new_record,
+    })
+}
+
+/// Encrypt `form` and send it to the bot with `web_app`.
+fn send_record(web_app: &WebApp, form: SubmittedForm) -> Result<(), Error> {
+    let message = encrypt_form(form)?;
+
+    // Telegram JS code checks some additional properties of the data (e.g. length),
+    // So it's easier to serialize it to JSON and send as a string rather than use
+    // something like `serde_wasm_bindgen`.
+    web_app
+        .sendData(serde_json::to_value(message)?.to_string().into())
+        .map_err(|err| Error::Sending(format!("{err:?}")))
+}
+
+/// Treat empty optional form `value` as not set.
+fn non_empty(value: String) -> Option<String> {
+    (!value.is_empty()).then_some(value)
+}
+
+/// Collapsible section with options most users don't need.
+///
+/// Its content is rendered only after the section is opened for the first time, and is kept
+/// afterwards, so that entered values survive collapsing. Elements of a never opened section
+/// are not set.
+#[component]
+fn AdvancedOptions(
+    /// Whether the section is open.
+    open: ReadSignal<bool>,
+    /// Writer to open or close the section.
+    set_open: WriteSignal<bool>,
+    /// Checkbox to skip the master password confirmation.
+    skip_verification_element: NodeRef<Input>,
+    /// Checkbox to bind the payload to the resource name.
+    bind_to_resource_name_element: NodeRef<Input>,
+    /// Input for the record tags.
+    tags_element: NodeRef<Input>,
+    /// File input to attach a file to the record.
+    attachment_element: NodeRef<Input>,
+) -> impl IntoView {
+    let rendered = create_memo(move |rendered: Option<&bool>| {
+        rendered.copied().unwrap_or_default() || open.get()
+    });
+
+    let on_toggle = move |event| {
+        let now_open = event_target::<web_sys::HtmlDetailsElement>(&event).open();
+        if now_open != open.get_untracked() {
+            set_open(now_open);
+        }
+        save_advanced_open(now_open);
+    };
+
+    view! {
+        <FormItem>
+            <details prop:open=open on:toggle=on_toggle>
+                <summary>Advanced</summary>
+                <Show when=move || rendered.get()>
+                    <label for="skip-verification">
+                        <input type="checkbox" id="skip-verification" node_ref=skip_verification_element/>
+                        " Skip master password verification"
+                    </label>
+
+                    <label for="bind-to-resource-name">
+                        <input type="checkbox" id="bind-to-resource-name"
+                            node_ref=bind_to_resource_name_element/>
+                        " Bind to the resource name (can't be duplicated or renamed)"
+                    </label>
+
+                    <FormItem>
+                        <label for=TAGS_ID>Tags</label>
+                        <InputBox>
+                            <input type="text" id=TAGS_ID node_ref=tags_element
+                                placeholder="work, finance" autocapitalize="false"
+                                autocorrect="false" spellcheck="false"/>
+                        </InputBox>
+                    </FormItem>
+
+                    <FormItem>
+                        <label for=ATTACHMENT_ID>Attachment</label>
+                        <InputBox>
+                            <input type="file" id=ATTACHMENT_ID node_ref=attachment_element/>
+                        </InputBox>
+                    </FormItem>
+                </Show>
+            </details>
+        </FormItem>
+    }
+}
+
+/// Open the advanced section and scroll to the field `error` is about, if it's there.
+fn reveal_advanced_error(error: &Error, set_advanced_open: WriteSignal<bool>) {
+    let Some(field_id) = error.advanced_field_id() else {
+        return;
+    };
+    set_advanced_open(true);
+    // Wait for the section to be rendered
+    request_animation_frame(move || {
+        if let Some(field) = document().get_element_by_id(field_id) {
+            field.scroll_into_view();
+        }
+    });
+}
+
 /// Component with input forms and `Submit` button.
 ///
 /// Clicking on the button will send encrypted info to the bot via `web_app` and close the app.
@@ -43,12 +423,23 @@ pub fn Submit(
 ) -> impl IntoView {
     web_app.enableClosingConfirmation();
 
+    let user = web_app.user();
+    if user.is_none() {
+        set_result(Err(Error::NotLaunchedFromBot));
+    }
+
     let (resource_name, _set_resource_name) =
         create_record_form_parameter::<Input>(String::new(), false);
     let (login, _set_login) = create_record_form_parameter::<Input>(String::new(), false);
     let (password, _set_password) = create_record_form_parameter::<Input>(String::new(), false);
     let (comments, _set_comments) = create_record_form_parameter::<Textarea>(String::new(), false);
     let master_password_element = create_node_ref::<Input>();
+    let master_password_confirmation_element = create_node_ref::<Input>();
+    let skip_verification_element = create_node_ref::<Input>();
+    let bind_to_resource_name_element = create_node_ref::<Input>();
+    let tags_element = create_node_ref::<Input>();
+    let attachment_element = create_node_ref::<Input>();
+    let (advanced_open, set_advanced_open) = create_signal(load_advanced_open());
 
     let on_submit = move |event: SubmitEvent| {
         event.prevent_default(); // Prevent page reload
@@ -59,42 +450,59 @@ pub fn Submit(
             .expect("No resource_name element")
             .value();
 
-        let payload = Payload {
-            resource_name: resource_name.clone(),
-            login: login.element.get().expect("No login element").value(),
-            password: password.element.get().expect("No password element").value(),
-            comments: comments.element.get().expect("No comments element").value(),
+        let form = SubmittedForm {
+            user,
+            resource_name,
+            payload: RecordPayload {
+                login: non_empty(login.element.get().expect("No login element").value()),
+                password: password.element.get().expect("No password element").value(),
+                notes: non_empty(comments.element.get().expect("No comments element").value()),
+                extra: BTreeMap::new(),
+            },
+            tags: tags_element
+                .get()
+                .map(|element| element.value())
+                .unwrap_or_default(),
+            master_password: Zeroizing::new(
+                master_password_element()
+                    .expect("No master_password element")
+                    .value(),
+            ),
+            master_password_confirmation: (!skip_verification_element
+                .get()
+                .is_some_and(|element| element.checked()))
+            .then(|| {
+                Zeroizing::new(
+                    master_password_confirmation_element()
+                        .expect("No master_password_confirmation element")
+                        .value(),
+                )
+            }),
+            attachment: None,
+            bind_to_resource_name: bind_to_resource_name_element
+                .get()
+                .is_some_and(|element| element.checked()),
         };
-        let master_password = master_password_element()
-            .expect("No master_password element")
-            .value();
-
-        set_result(|| -> Result<(), Error> {
-            let resource_name = resource_name.trim();
-            if resource_name.is_empty() {
-                return Err(Error::Validation("Resource name cannot be empty"));
-            }
-
-            let encryption_output = telepass_crypto::encrypt(
-                &serde_json::to_value(payload)?.to_string(),
-                &master_password,
-            )?;
+        let attachment_input = attachment_element.get();
 
-            let new_record = telepass_data_model::NewRecord {
-                resource_name: resource_name.to_owned(),
-                encryption_output,
+        // Reading a file is asynchronous in browsers
+        let web_app = Rc::clone(&web_app);
+        spawn_local(async move {
+            let read_attachment = match attachment_input {
+                Some(input) => read_attached_file(&input).await,
+                None => Ok(None),
             };
-
-            // Telegram JS code checks some additional properties of the data (e.g. length),
-            // So it's easier to serialize it to JSON and send as a string rather than use
-            // something like `serde_wasm_bindgen`.
-            web_app
-                .sendData(serde_json::to_value(new_record)?.to_string().into())
-                .map_err(|err| Error::Sending(format!("{err:?}")))
-        }());
+            let result = read_attachment
+                .and_then(|attachment| send_record(&web_app, SubmittedForm { attachment, ..form }));
+            if let Some(error) = result.as_ref().err() {
+                reveal_advanced_error(error, set_advanced_open);
+            }
+            set_result(result);
+        });
     };
 
     view! {
+        <TelegramUser user=user/>
         <RecordForm
             resource_name=resource_name
             login=login
@@ -104,6 +512,233 @@ pub fn Submit(
             copy_buttons_enabled=false
             submit_value="Submit"
             on_submit=on_submit
-        />
+            master_password_confirmation_element=master_password_confirmation_element
+        >
+            <AdvancedOptions
+                open=advanced_open
+                set_open=set_advanced_open
+                skip_verification_element=skip_verification_element
+                bind_to_resource_name_element=bind_to_resource_name_element
+                tags_element=tags_element
+                attachment_element=attachment_element
+            />
+        </RecordForm>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used, reason = "it's ok in tests")]
+
+    use super::*;
+
+    fn resource_name() -> telepass_data_model::ResourceName {
+        telepass_data_model::ResourceName::try_from("test.resource.com").unwrap()
+    }
+
+    /// Construct form of `test.resource.com` encrypted with `master_password` and verified
+    /// with `master_password_confirmation`.
+    fn form(master_password: &str, master_password_confirmation: Option<&str>) -> SubmittedForm {
+        SubmittedForm {
+            user: Some(WebAppUser { id: 42 }),
+            resource_name: "test.resource.com".to_owned(),
+            payload: RecordPayload {
+                login: Some("login".to_owned()),
+                password: "password".to_owned(),
+                ..RecordPayload::default()
+            },
+            tags: String::new(),
+            master_password: Zeroizing::new(master_password.to_owned()),
+            master_password_confirmation: master_password_confirmation
+                .map(|confirmation| Zeroizing::new(confirmation.to_owned())),
+            attachment: None,
+            bind_to_resource_name: false,
+        }
+    }
+
+    #[test]
+    fn matching_master_password_confirmation_is_accepted() {
+        let message = encrypt_form(form("master", Some("master"))).unwrap();
+
+        assert_eq!(message.user_id, 42);
+        let (_resource_name, encryption_output, _blind_index) = message.data.into_parts();
+        let decrypted = telepass_crypto::decrypt(encryption_output, "master").unwrap();
+        assert!(decrypted.contains("\"login\":\"login\""), "{decrypted}");
+    }
+
+    #[test]
+    fn newly_submitted_record_can_be_duplicated_and_renamed() {
+        let message = encrypt_form(form("master", Some("master"))).unwrap();
+
+        // Bot refuses to duplicate and rename only bound records
+        assert!(!message.data.is_bound_to_resource_name());
+        // Payload doesn't depend on the name, so it's decrypted under any other one
+        let (_resource_name, encryption_output, _blind_index) = message.data.into_parts();
+        telepass_crypto::decrypt(encryption_output, "master").unwrap();
+    }
+
+    #[test]
+    fn opted_in_record_is_bound_to_resource_name() {
+        let message = encrypt_form(SubmittedForm {
+            bind_to_resource_name: true,
+            ..form("master", Some("master"))
+        })
+        .unwrap();
+
+        assert!(message.data.is_bound_to_resource_name());
+        let (resource_name, encryption_output, _blind_index) = message.data.into_parts();
+        telepass_crypto::decrypt(encryption_output.clone(), "master").unwrap_err();
+        telepass_crypto::decrypt_with_aad(
+            encryption_output,
+            "master",
+            resource_name.as_str().as_bytes(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn mismatching_master_password_confirmation_is_rejected() {
+        assert!(matches!(
+            encrypt_form(form("master", Some("mastre"))),
+            Err(Error::MasterPasswordMismatch)
+        ));
+    }
+
+    #[test]
+    fn skipped_verification_accepts_any_master_password() {
+        encrypt_form(form("master", None)).unwrap();
+    }
+
+    #[test]
+    fn attachment_is_encrypted_and_bound_to_record() {
+        let file = AttachedFile {
+            filename: "recovery.pdf".to_owned(),
+            content: vec![0xFF, 0x00, 0xFE, 0x01],
+        };
+
+        let attachment = encrypt_attachment(&file, &resource_name(), "master").unwrap();
+        assert_eq!(attachment.filename(), "recovery.pdf");
+        assert_eq!(attachment.size(), 4);
+        assert_ne!(
+            attachment.encryption_output().encrypted_payload,
+            file.content
+        );
+
+        let decrypted = telepass_crypto::decrypt_bytes(
+            attachment.encryption_output().clone(),
+            "master",
+            &attachment_aad(&resource_name(), "recovery.pdf"),
+        )
+        .unwrap();
+        assert_eq!(decrypted, file.content);
+
+        telepass_crypto::decrypt_bytes(
+            attachment.encryption_output().clone(),
+            "master",
+            &attachment_aad(&resource_name(), "another.pdf"),
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    fn too_large_attachment_is_rejected() {
+        let max_size = usize::try_from(telepass_data_model::MAX_ATTACHMENT_SIZE).unwrap();
+        let file = AttachedFile {
+            filename: "huge.bin".to_owned(),
+            content: vec![0; max_size + 1],
+        };
+
+        assert!(matches!(
+            encrypt_attachment(&file, &resource_name(), "master"),
+            Err(Error::InvalidAttachment(
+                telepass_data_model::AttachmentError::TooLarge
+            ))
+        ));
+    }
+
+    #[test]
+    fn invalid_filename_is_rejected() {
+        let file = AttachedFile {
+            filename: String::new(),
+            content: vec![1],
+        };
+
+        assert!(matches!(
+            encrypt_attachment(&file, &resource_name(), "master"),
+            Err(Error::InvalidAttachment(
+                telepass_data_model::AttachmentError::EmptyFilename
+            ))
+        ));
+    }
+
+    #[test]
+    fn form_without_advanced_options_has_no_tags_and_attachments() {
+        let message = encrypt_form(form("master", Some("master"))).unwrap();
+
+        assert!(message.data.tags().is_empty());
+        assert!(message.data.attachments().is_empty());
+    }
+
+    #[test]
+    fn tags_are_lowercased_and_deduplicated() {
+        let message = encrypt_form(SubmittedForm {
+            tags: " Work, finance  work,,personal ".to_owned(),
+            ..form("master", None)
+        })
+        .unwrap();
+
+        let tags: Vec<_> = message
+            .data
+            .tags()
+            .iter()
+            .map(telepass_data_model::Tag::as_str)
+            .collect();
+        assert_eq!(tags, ["work", "finance", "personal"]);
+    }
+
+    #[test]
+    fn invalid_tag_is_rejected_and_points_to_tags() {
+        let too_long = "a".repeat(telepass_data_model::MAX_TAG_LENGTH + 1);
+
+        let error = encrypt_form(SubmittedForm {
+            tags: too_long,
+            ..form("master", None)
+        })
+        .unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::InvalidTag(telepass_data_model::TagError::TooLong)
+        ));
+        assert_eq!(error.advanced_field_id(), Some(TAGS_ID));
+    }
+
+    #[test]
+    fn advanced_errors_point_to_their_fields() {
+        let duplicate_tag = telepass_data_model::Tag::try_from("work").unwrap();
+        let duplicate_tags = telepass_data_model::NewRecord::builder()
+            .resource_name("test.resource.com")
+            .tags(vec![duplicate_tag.clone(), duplicate_tag])
+            .build()
+            .unwrap_err();
+
+        for (error, expected) in [
+            (Error::InvalidRecord(duplicate_tags), Some(TAGS_ID)),
+            (
+                Error::InvalidAttachment(telepass_data_model::AttachmentError::TooLarge),
+                Some(ATTACHMENT_ID),
+            ),
+            (
+                Error::ReadingAttachment("aborted".to_owned()),
+                Some(ATTACHMENT_ID),
+            ),
+            (Error::MasterPasswordMismatch, None),
+            (
+                Error::Validation(telepass_data_model::ResourceNameError::Empty),
+                None,
+            ),
+        ] {
+            assert_eq!(error.advanced_field_id(), expected, "{error}");
+        }
     }
 }
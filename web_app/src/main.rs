@@ -18,6 +18,7 @@ use wasm_bindgen::prelude::*;
 
 mod components;
 mod tg_api;
+mod viewport;
 
 /// Main component.
 #[component]
@@ -30,21 +31,32 @@ fn App() -> impl IntoView {
 
     // `WebApp` is not a class, so checked casts like `dyn_into` fail.
     let web_app = web_app.unchecked_into::<tg_api::WebApp>();
-    web_app.expand();
 
     let web_app = Rc::new(web_app);
+    let submit_web_app = Rc::clone(&web_app);
+    let show_web_app = Rc::clone(&web_app);
+    let deep_find_web_app = Rc::clone(&web_app);
 
     let (submission_result, set_submission_result) = create_signal(Ok(()));
-    let (show_result, set_show_result) = create_signal(Ok(()));
+    let (deep_find_result, set_deep_find_result) = create_signal(Ok(()));
 
     view! {
         <Router>
             <Routes>
-                <Route path="/submit" view=move || view! {
-                    <components::Submit web_app=Rc::clone(&web_app) set_result=set_submission_result/>
+                <Route path="/submit" view=move || {
+                    viewport::expand_for(&submit_web_app, viewport::Page::Submit);
+                    view! {
+                        <components::Submit web_app=Rc::clone(&submit_web_app) set_result=set_submission_result/>
+                    }
                 }/>
                 <Route path="/show" view=move || view! {
-                    <components::Show set_result=set_show_result/>
+                    <components::Show web_app=Rc::clone(&show_web_app)/>
+                }/>
+                <Route path="/deepfind" view=move || {
+                    viewport::expand_for(&deep_find_web_app, viewport::Page::DeepFind);
+                    view! {
+                        <components::DeepFind web_app=Rc::clone(&deep_find_web_app) set_result=set_deep_find_result/>
+                    }
                 }/>
                 <Route path="/*any" view=|| view! { <h1>"Not Found"</h1> }/>
             </Routes>
@@ -62,7 +74,7 @@ fn App() -> impl IntoView {
             </div>
         }>
             { submission_result }
-            { show_result }
+            { deep_find_result }
         </ErrorBoundary>
     }
 }
@@ -1,5 +1,6 @@
 //! Telegram JS API bindings.
 
+use serde::Deserialize;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -13,6 +14,18 @@ extern "C" {
     #[wasm_bindgen(method)]
     pub fn expand(this: &WebApp);
 
+    /// Current height of the visible area of the Mini App in pixels.
+    #[wasm_bindgen(method, getter)]
+    pub fn viewportHeight(this: &WebApp) -> f64;
+
+    /// `true` if the Mini App is expanded to the maximum available height.
+    #[wasm_bindgen(method, getter)]
+    pub fn isExpanded(this: &WebApp) -> bool;
+
+    /// A method that closes the Mini App.
+    #[wasm_bindgen(method)]
+    pub fn close(this: &WebApp);
+
     /// A method that enables a confirmation dialog while the user is trying to close the Mini App.
     #[wasm_bindgen(method)]
     pub fn enableClosingConfirmation(this: &WebApp);
@@ -22,4 +35,46 @@ extern "C" {
     /// of the length up to 4096 bytes, and the Mini App is closed.
     #[wasm_bindgen(method, catch)]
     pub fn sendData(this: &WebApp, data: JsValue) -> Result<(), JsValue>;
+
+    /// A string with raw data transferred to the Mini App.
+    /// Empty if the Mini App wasn't launched from a bot keyboard button.
+    #[wasm_bindgen(method, getter)]
+    pub fn initData(this: &WebApp) -> String;
+
+    /// An object with input data transferred to the Mini App.
+    ///
+    /// **Data is not validated** and can be forged, so it should be only used as a hint.
+    #[wasm_bindgen(method, getter)]
+    pub fn initDataUnsafe(this: &WebApp) -> WebAppInitData;
+
+    /// Input data transferred to the Mini App: <https://core.telegram.org/bots/webapps#webappinitdata>.
+    pub type WebAppInitData;
+
+    /// An object containing data about the current user.
+    #[wasm_bindgen(method, getter)]
+    pub fn user(this: &WebAppInitData) -> JsValue;
+}
+
+/// Telegram user who opened the Mini App: <https://core.telegram.org/bots/webapps#webappuser>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct WebAppUser {
+    /// Unique identifier of the user.
+    pub id: u64,
+}
+
+impl WebApp {
+    /// Get the user who opened the Mini App.
+    ///
+    /// Returns [`None`] if the Mini App wasn't launched from a bot keyboard button,
+    /// e.g. when its link was opened directly.
+    pub fn user(&self) -> Option<WebAppUser> {
+        if self.initData().is_empty() {
+            return None;
+        }
+
+        // User id may not fit into `f64` without precision loss,
+        // so it's parsed from JSON instead of reading it as a JS number
+        let user = js_sys::JSON::stringify(&self.initDataUnsafe().user()).ok()?;
+        serde_json::from_str(&JsValue::from(user).as_string()?).ok()
+    }
 }
@@ -0,0 +1,79 @@
+//! Module deciding whether the Mini App should be expanded to the full height.
+
+use crate::tg_api::WebApp;
+
+/// Page of the Mini App with everything affecting its height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Page {
+    /// Form to submit a new record.
+    Submit,
+    /// Form to show a record.
+    Show {
+        /// Value of `compact` query parameter, [`None`] if not passed.
+        compact: Option<bool>,
+        /// Whether the decrypted record has comments, `false` until it's decrypted.
+        has_comments: bool,
+    },
+    /// Search by keywords.
+    DeepFind,
+}
+
+/// Decide whether the Mini App should be expanded to the full height on `page`.
+///
+/// Forms to fill need space, while showing a short record looks better compact.
+pub const fn should_expand(page: Page) -> bool {
+    match page {
+        Page::Submit => true,
+        Page::Show {
+            compact,
+            has_comments,
+        } => matches!(compact, Some(false)) || has_comments,
+        Page::DeepFind => false,
+    }
+}
+
+/// Expand `web_app` if it [should be expanded](should_expand) on `page` and it's not yet.
+pub fn expand_for(web_app: &WebApp, page: Page) {
+    if should_expand(page) && !web_app.isExpanded() {
+        web_app.expand();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn submit_expands() {
+        assert!(should_expand(Page::Submit));
+    }
+
+    #[test]
+    fn deep_find_stays_compact() {
+        assert!(!should_expand(Page::DeepFind));
+    }
+
+    #[test]
+    fn show_stays_compact_by_default() {
+        for compact in [None, Some(true)] {
+            assert!(!should_expand(Page::Show {
+                compact,
+                has_comments: false,
+            }));
+        }
+    }
+
+    #[test]
+    fn show_expands_if_not_compact_or_has_comments() {
+        assert!(should_expand(Page::Show {
+            compact: Some(false),
+            has_comments: false,
+        }));
+        for compact in [None, Some(true), Some(false)] {
+            assert!(should_expand(Page::Show {
+                compact,
+                has_comments: true,
+            }));
+        }
+    }
+}